@@ -0,0 +1,46 @@
+// generated by `sqlx migrate build-script`
+fn main() {
+    // trigger recompilation when a new migration is added
+    println!("cargo:rerun-if-changed=migrations");
+
+    #[cfg(feature = "grpc")]
+    grpc::compile();
+}
+
+/// Generates the gRPC services of `proto/qna.proto` from the declarations below rather than the file itself, so
+/// building does not need protoc. The messages they refer to are declared in `src/grpc.rs`.
+#[cfg(feature = "grpc")]
+mod grpc {
+    use tonic_build::manual::{Builder, Method, Service};
+
+    fn method(name: &str, route_name: &str, input_type: &str, output_type: &str) -> Method {
+        Method::builder()
+            .name(name)
+            .route_name(route_name)
+            .input_type(format!("crate::grpc::proto::{}", input_type))
+            .output_type(format!("crate::grpc::proto::{}", output_type))
+            .codec_path("tonic::codec::ProstCodec")
+            .build()
+    }
+
+    pub fn compile() {
+        let questions = Service::builder()
+            .name("QuestionService")
+            .package("qna.v1")
+            .method(method("create_question", "CreateQuestion", "CreateQuestionRequest", "Question"))
+            .method(method("list_questions", "ListQuestions", "ListQuestionsRequest", "ListQuestionsResponse"))
+            .method(method("lookup_questions", "LookupQuestions", "LookupQuestionsRequest", "ListQuestionsResponse"))
+            .method(method("delete_question", "DeleteQuestion", "DeleteQuestionRequest", "DeleteQuestionResponse"))
+            .build();
+
+        let answers = Service::builder()
+            .name("AnswerService")
+            .package("qna.v1")
+            .method(method("create_answer", "CreateAnswer", "CreateAnswerRequest", "Answer"))
+            .method(method("list_answers", "ListAnswers", "ListAnswersRequest", "ListAnswersResponse"))
+            .method(method("delete_answer", "DeleteAnswer", "DeleteAnswerRequest", "DeleteAnswerResponse"))
+            .build();
+
+        Builder::new().compile(&[questions, answers]);
+    }
+}
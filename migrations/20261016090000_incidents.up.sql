@@ -0,0 +1,9 @@
+-- Up migration script
+
+CREATE TABLE IF NOT EXISTS incidents (
+    incident_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    title VARCHAR(255) NOT NULL,
+    description VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    resolved_at TIMESTAMP
+);
@@ -0,0 +1,4 @@
+-- Down migration script
+
+ALTER TABLE questions DROP COLUMN IF EXISTS status;
+DROP TYPE IF EXISTS question_status;
@@ -0,0 +1,5 @@
+-- Up migration script
+
+CREATE TYPE question_status AS ENUM ('open', 'closed', 'archived');
+
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS status question_status NOT NULL DEFAULT 'open';
@@ -0,0 +1,8 @@
+-- Up migration script
+
+CREATE TABLE IF NOT EXISTS webhooks (
+    webhook_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    url VARCHAR(2048) NOT NULL,
+    secret VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,18 @@
+-- Down migration script
+
+ALTER TABLE answers RENAME TO answers_partitioned;
+ALTER TABLE answers_partitioned RENAME CONSTRAINT answers_pkey TO answers_partitioned_pkey;
+-- Renaming the foreign key would leave its name on the partitions
+ALTER TABLE answers_partitioned DROP CONSTRAINT answers_question_uuid_fkey;
+
+CREATE TABLE answers (
+    answer_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    question_uuid uuid NOT NULL REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    content VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO answers SELECT answer_uuid, question_uuid, content, created_at FROM answers_partitioned;
+
+-- Drops the partitions along with it
+DROP TABLE answers_partitioned;
@@ -0,0 +1,36 @@
+-- Up migration script
+
+-- Every read of answers is by question, so hashing on it lets those queries scan a single partition. The primary
+-- key has to include the partition key, so it only keeps an answer UUID unique within its question. Clients may choose
+-- answer UUIDs, so the answers_claim_answer_id trigger of a later migration claims each one in the answer_ids table,
+-- whose primary key keeps it unique across questions.
+ALTER TABLE answers RENAME TO answers_unpartitioned;
+ALTER TABLE answers_unpartitioned RENAME CONSTRAINT answers_pkey TO answers_unpartitioned_pkey;
+ALTER TABLE answers_unpartitioned RENAME CONSTRAINT answers_question_uuid_fkey TO answers_unpartitioned_question_uuid_fkey;
+
+CREATE TABLE answers (
+    answer_uuid uuid NOT NULL DEFAULT gen_random_uuid(),
+    question_uuid uuid NOT NULL REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    content VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (answer_uuid, question_uuid)
+) PARTITION BY HASH (question_uuid);
+
+-- Changing the number of partitions means repartitioning the whole table, so there are enough for years of growth
+DO $$
+BEGIN
+    FOR remainder IN 0..15 LOOP
+        EXECUTE format(
+            'CREATE TABLE answers_p%s PARTITION OF answers FOR VALUES WITH (MODULUS 16, REMAINDER %s)',
+            remainder, remainder
+        );
+    END LOOP;
+END
+$$;
+
+-- Answers are read oldest first
+CREATE INDEX answers_question_uuid_created_at_idx ON answers (question_uuid, created_at);
+
+INSERT INTO answers SELECT answer_uuid, question_uuid, content, created_at FROM answers_unpartitioned;
+
+DROP TABLE answers_unpartitioned;
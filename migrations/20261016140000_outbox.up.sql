@@ -0,0 +1,12 @@
+-- Up migration script
+
+-- Events of the writes, recorded in the same transaction so none is lost or published for a rolled back write
+CREATE TABLE IF NOT EXISTS outbox (
+    event_id BIGINT GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    event JSONB NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    delivered_at TIMESTAMP
+);
+
+-- Only undelivered events are looked for, oldest first
+CREATE INDEX IF NOT EXISTS outbox_undelivered_idx ON outbox (event_id) WHERE delivered_at IS NULL;
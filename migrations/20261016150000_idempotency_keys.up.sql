@@ -0,0 +1,16 @@
+-- Up migration script
+
+-- Requests made with an idempotency key, along with their responses to replay to retries
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    route VARCHAR(255) NOT NULL,
+    idempotency_key VARCHAR(255) NOT NULL,
+    request_hash VARCHAR(64) NOT NULL,
+    -- Both NULL while the request is still being served
+    status SMALLINT,
+    body TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (route, idempotency_key)
+);
+
+-- Expired keys are purged by age
+CREATE INDEX IF NOT EXISTS idempotency_keys_created_at_idx ON idempotency_keys (created_at);
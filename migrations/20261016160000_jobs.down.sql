@@ -0,0 +1,3 @@
+-- Down migration script
+
+DROP TABLE IF EXISTS jobs;
@@ -0,0 +1,19 @@
+-- Up migration script
+
+-- Background jobs, along with their progress, so they resume where they were after a restart
+CREATE TABLE IF NOT EXISTS jobs (
+    job_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    kind VARCHAR(64) NOT NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'running',
+    processed BIGINT NOT NULL DEFAULT 0,
+    -- The last question processed, which the job resumes after
+    last_question_uuid uuid,
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Instances look for running jobs to resume
+CREATE INDEX IF NOT EXISTS jobs_status_idx ON jobs (status);
+-- Two jobs of a kind would only do the same work twice, so a single one may run at a time
+CREATE UNIQUE INDEX IF NOT EXISTS jobs_running_kind_idx ON jobs (kind) WHERE status = 'running';
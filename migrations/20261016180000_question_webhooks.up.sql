@@ -0,0 +1,13 @@
+-- Up migration script
+
+CREATE TABLE IF NOT EXISTS question_webhooks (
+    webhook_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    question_uuid uuid NOT NULL REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    url VARCHAR(2048) NOT NULL,
+    secret VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    expires_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS question_webhooks_question_uuid_idx ON question_webhooks (question_uuid);
+CREATE INDEX IF NOT EXISTS question_webhooks_expires_at_idx ON question_webhooks (expires_at);
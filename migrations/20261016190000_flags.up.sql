@@ -0,0 +1,19 @@
+-- Up migration script
+
+-- Flags raised on questions and answers. Content may be deleted while flagged, and flags are kept as the record of
+-- what moderators did, so they do not reference the content.
+CREATE TABLE IF NOT EXISTS flags (
+    flag_uuid uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    question_uuid uuid NOT NULL,
+    -- NULL for flags on questions
+    answer_uuid uuid,
+    reason VARCHAR(32) NOT NULL,
+    comment VARCHAR(255),
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    resolution VARCHAR(16),
+    resolved_at TIMESTAMP
+);
+
+-- The moderation queue, oldest first
+CREATE INDEX IF NOT EXISTS flags_open_idx ON flags (created_at) WHERE resolved_at IS NULL;
+CREATE INDEX IF NOT EXISTS flags_content_idx ON flags (question_uuid, answer_uuid);
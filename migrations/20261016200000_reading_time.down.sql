@@ -0,0 +1,6 @@
+-- Down migration script
+
+ALTER TABLE answers DROP COLUMN IF EXISTS reading_time_seconds;
+ALTER TABLE answers DROP COLUMN IF EXISTS word_count;
+ALTER TABLE questions DROP COLUMN IF EXISTS reading_time_seconds;
+ALTER TABLE questions DROP COLUMN IF EXISTS word_count;
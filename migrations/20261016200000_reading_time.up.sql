@@ -0,0 +1,13 @@
+-- Up migration script
+
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS word_count BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE questions ADD COLUMN IF NOT EXISTS reading_time_seconds BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE answers ADD COLUMN IF NOT EXISTS word_count BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE answers ADD COLUMN IF NOT EXISTS reading_time_seconds BIGINT NOT NULL DEFAULT 0;
+
+-- Counted like the API counts them when content is written, as runs of characters other than whitespace, read at
+-- 200 words per minute rounded up to the second
+UPDATE questions SET word_count = (SELECT COUNT(*) FROM regexp_matches(title || ' ' || description, '\S+', 'g'));
+UPDATE questions SET reading_time_seconds = (word_count * 60 + 199) / 200;
+UPDATE answers SET word_count = (SELECT COUNT(*) FROM regexp_matches(content, '\S+', 'g'));
+UPDATE answers SET reading_time_seconds = (word_count * 60 + 199) / 200;
@@ -0,0 +1,5 @@
+-- Down migration script
+
+DROP TRIGGER IF EXISTS answers_claim_answer_id ON answers;
+DROP FUNCTION IF EXISTS claim_answer_id();
+DROP TABLE IF EXISTS answer_ids;
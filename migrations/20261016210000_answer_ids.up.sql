@@ -0,0 +1,29 @@
+-- Up migration script
+
+-- The primary key of the partitioned answers table has to include their question, so it only keeps an answer UUID
+-- unique within a question. Clients may choose the UUIDs of their answers, and answers are read and deleted by UUID
+-- alone, so every UUID is also claimed in a table of its own, whose primary key keeps it unique across questions.
+-- Claiming it from a trigger, in the transaction inserting the answer, leaves no window for a concurrent insert of
+-- the same UUID under another question: it waits for the first to commit, then violates the primary key.
+CREATE TABLE IF NOT EXISTS answer_ids (
+    answer_uuid uuid PRIMARY KEY
+);
+
+INSERT INTO answer_ids SELECT answer_uuid FROM answers ON CONFLICT DO NOTHING;
+
+CREATE OR REPLACE FUNCTION claim_answer_id() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP IN ('DELETE', 'UPDATE') THEN
+        DELETE FROM answer_ids WHERE answer_uuid = OLD.answer_uuid;
+    END IF;
+    IF TG_OP IN ('INSERT', 'UPDATE') THEN
+        INSERT INTO answer_ids ( answer_uuid ) VALUES ( NEW.answer_uuid );
+    END IF;
+    RETURN NULL;
+END
+$$ LANGUAGE plpgsql;
+
+-- Fires on every partition, and for the answers deleted along with their question
+CREATE TRIGGER answers_claim_answer_id
+    AFTER INSERT OR DELETE OR UPDATE OF answer_uuid ON answers
+    FOR EACH ROW EXECUTE FUNCTION claim_answer_id();
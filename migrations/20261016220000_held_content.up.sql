@@ -0,0 +1,5 @@
+-- Up migration script
+
+-- Questions and answers a content filter holds for moderation are not created until a moderator publishes them, so
+-- no read or event sees them meanwhile. They are kept as submitted on the flag holding them, written at once.
+ALTER TABLE flags ADD COLUMN IF NOT EXISTS held_content JSONB;
@@ -0,0 +1,18 @@
+-- Up migration script
+
+-- UUIDs are generated by the application, as MySQL has no type for them
+
+CREATE TABLE IF NOT EXISTS questions (
+    question_uuid CHAR(36) PRIMARY KEY,
+    title VARCHAR(255) NOT NULL,
+    description VARCHAR(255) NOT NULL,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6)
+);
+
+CREATE TABLE IF NOT EXISTS answers (
+    answer_uuid CHAR(36) PRIMARY KEY,
+    question_uuid CHAR(36) NOT NULL,
+    content VARCHAR(255) NOT NULL,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    FOREIGN KEY (question_uuid) REFERENCES questions (question_uuid) ON DELETE CASCADE
+);
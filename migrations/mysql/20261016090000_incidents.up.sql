@@ -0,0 +1,9 @@
+-- Up migration script
+
+CREATE TABLE IF NOT EXISTS incidents (
+    incident_uuid CHAR(36) PRIMARY KEY,
+    title VARCHAR(255) NOT NULL,
+    description VARCHAR(255) NOT NULL,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    resolved_at DATETIME(6)
+);
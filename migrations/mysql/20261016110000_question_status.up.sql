@@ -0,0 +1,3 @@
+-- Up migration script
+
+ALTER TABLE questions ADD COLUMN status ENUM('open', 'closed', 'archived') NOT NULL DEFAULT 'open';
@@ -0,0 +1,9 @@
+-- Up migration script
+
+CREATE TABLE IF NOT EXISTS outbox (
+    event_id BIGINT AUTO_INCREMENT PRIMARY KEY,
+    event JSON NOT NULL,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    delivered_at DATETIME(6),
+    INDEX outbox_delivered_at_idx (delivered_at, event_id)
+);
@@ -0,0 +1,12 @@
+-- Up migration script
+
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    route VARCHAR(255) NOT NULL,
+    idempotency_key VARCHAR(255) NOT NULL,
+    request_hash VARCHAR(64) NOT NULL,
+    status SMALLINT,
+    body TEXT,
+    created_at DATETIME(6) NOT NULL DEFAULT CURRENT_TIMESTAMP(6),
+    PRIMARY KEY (route, idempotency_key),
+    INDEX idempotency_keys_created_at_idx (created_at)
+);
@@ -0,0 +1,16 @@
+-- Up migration script
+
+CREATE TABLE IF NOT EXISTS jobs (
+    job_uuid CHAR(36) PRIMARY KEY,
+    kind VARCHAR(64) NOT NULL,
+    status VARCHAR(16) NOT NULL,
+    processed BIGINT NOT NULL,
+    last_question_uuid CHAR(36),
+    last_error TEXT,
+    created_at DATETIME(6) NOT NULL,
+    updated_at DATETIME(6) NOT NULL,
+    -- MySQL has no partial indexes, so the kind of running jobs only, NULL for the others, is indexed instead
+    running_kind VARCHAR(64) AS (CASE WHEN status = 'running' THEN kind END) VIRTUAL,
+    INDEX jobs_status_idx (status),
+    UNIQUE INDEX jobs_running_kind_idx (running_kind)
+);
@@ -0,0 +1,3 @@
+-- Down migration script
+
+ALTER TABLE questions DROP COLUMN view_count;
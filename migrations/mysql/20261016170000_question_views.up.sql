@@ -0,0 +1,3 @@
+-- Up migration script
+
+ALTER TABLE questions ADD COLUMN view_count BIGINT NOT NULL DEFAULT 0;
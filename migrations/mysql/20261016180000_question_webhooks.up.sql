@@ -0,0 +1,12 @@
+-- Up migration script
+
+CREATE TABLE IF NOT EXISTS question_webhooks (
+    webhook_uuid CHAR(36) PRIMARY KEY,
+    question_uuid CHAR(36) NOT NULL,
+    url VARCHAR(2048) NOT NULL,
+    secret VARCHAR(255) NOT NULL,
+    created_at DATETIME(6) NOT NULL,
+    expires_at DATETIME(6) NOT NULL,
+    INDEX question_webhooks_expires_at_idx (expires_at),
+    FOREIGN KEY (question_uuid) REFERENCES questions (question_uuid) ON DELETE CASCADE
+);
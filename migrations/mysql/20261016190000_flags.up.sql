@@ -0,0 +1,13 @@
+-- Up migration script
+
+CREATE TABLE IF NOT EXISTS flags (
+    flag_uuid CHAR(36) PRIMARY KEY,
+    question_uuid CHAR(36) NOT NULL,
+    answer_uuid CHAR(36),
+    reason VARCHAR(32) NOT NULL,
+    comment VARCHAR(255),
+    created_at DATETIME(6) NOT NULL,
+    resolution VARCHAR(16),
+    resolved_at DATETIME(6),
+    INDEX flags_content_idx (question_uuid, answer_uuid)
+);
@@ -0,0 +1,4 @@
+-- Down migration script
+
+ALTER TABLE answers DROP COLUMN reading_time_seconds, DROP COLUMN word_count;
+ALTER TABLE questions DROP COLUMN reading_time_seconds, DROP COLUMN word_count;
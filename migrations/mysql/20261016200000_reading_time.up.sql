@@ -0,0 +1,15 @@
+-- Up migration script
+
+ALTER TABLE questions ADD COLUMN word_count BIGINT NOT NULL DEFAULT 0, ADD COLUMN reading_time_seconds BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE answers ADD COLUMN word_count BIGINT NOT NULL DEFAULT 0, ADD COLUMN reading_time_seconds BIGINT NOT NULL DEFAULT 0;
+
+-- Counted like the API counts them when content is written, as runs of characters other than whitespace, read at
+-- 200 words per minute rounded up to the second. Replacing each word with a single character, then with nothing,
+-- leaves as many characters of difference as there are words.
+UPDATE questions SET word_count =
+    CHAR_LENGTH(REGEXP_REPLACE(CONCAT(title, ' ', description), '[^[:space:]]+', 'x'))
+    - CHAR_LENGTH(REGEXP_REPLACE(CONCAT(title, ' ', description), '[^[:space:]]+', ''));
+UPDATE questions SET reading_time_seconds = (word_count * 60 + 199) DIV 200;
+UPDATE answers SET word_count =
+    CHAR_LENGTH(REGEXP_REPLACE(content, '[^[:space:]]+', 'x')) - CHAR_LENGTH(REGEXP_REPLACE(content, '[^[:space:]]+', ''));
+UPDATE answers SET reading_time_seconds = (word_count * 60 + 199) DIV 200;
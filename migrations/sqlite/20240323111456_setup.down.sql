@@ -0,0 +1,4 @@
+-- Down migration script
+
+DROP TABLE IF EXISTS answers;
+DROP TABLE IF EXISTS questions;
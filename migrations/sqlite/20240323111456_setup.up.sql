@@ -0,0 +1,17 @@
+-- Up migration script
+
+-- UUIDs and timestamps are generated by the application, as SQLite has no types for them
+
+CREATE TABLE IF NOT EXISTS questions (
+    question_uuid TEXT PRIMARY KEY,
+    title VARCHAR(255) NOT NULL,
+    description VARCHAR(255) NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS answers (
+    answer_uuid TEXT PRIMARY KEY,
+    question_uuid TEXT NOT NULL REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    content VARCHAR(255) NOT NULL,
+    created_at TEXT NOT NULL
+);
@@ -0,0 +1,9 @@
+-- Up migration script
+
+CREATE TABLE IF NOT EXISTS incidents (
+    incident_uuid TEXT PRIMARY KEY,
+    title VARCHAR(255) NOT NULL,
+    description VARCHAR(255) NOT NULL,
+    created_at TEXT NOT NULL,
+    resolved_at TEXT
+);
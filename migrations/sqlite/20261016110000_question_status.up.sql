@@ -0,0 +1,3 @@
+-- Up migration script
+
+ALTER TABLE questions ADD COLUMN status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'closed', 'archived'));
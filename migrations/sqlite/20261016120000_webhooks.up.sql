@@ -0,0 +1,8 @@
+-- Up migration script
+
+CREATE TABLE IF NOT EXISTS webhooks (
+    webhook_uuid TEXT PRIMARY KEY,
+    url VARCHAR(2048) NOT NULL,
+    secret VARCHAR(255) NOT NULL,
+    created_at TEXT NOT NULL
+);
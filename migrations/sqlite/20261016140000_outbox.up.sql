@@ -0,0 +1,10 @@
+-- Up migration script
+
+CREATE TABLE IF NOT EXISTS outbox (
+    event_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    delivered_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS outbox_undelivered_idx ON outbox (event_id) WHERE delivered_at IS NULL;
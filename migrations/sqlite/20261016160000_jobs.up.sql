@@ -0,0 +1,15 @@
+-- Up migration script
+
+CREATE TABLE IF NOT EXISTS jobs (
+    job_uuid TEXT PRIMARY KEY,
+    kind TEXT NOT NULL,
+    status TEXT NOT NULL,
+    processed INTEGER NOT NULL,
+    last_question_uuid TEXT,
+    last_error TEXT,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS jobs_status_idx ON jobs (status);
+CREATE UNIQUE INDEX IF NOT EXISTS jobs_running_kind_idx ON jobs (kind) WHERE status = 'running';
@@ -0,0 +1,13 @@
+-- Up migration script
+
+CREATE TABLE IF NOT EXISTS question_webhooks (
+    webhook_uuid TEXT PRIMARY KEY,
+    question_uuid TEXT NOT NULL REFERENCES questions (question_uuid) ON DELETE CASCADE,
+    url VARCHAR(2048) NOT NULL,
+    secret VARCHAR(255) NOT NULL,
+    created_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS question_webhooks_question_uuid_idx ON question_webhooks (question_uuid);
+CREATE INDEX IF NOT EXISTS question_webhooks_expires_at_idx ON question_webhooks (expires_at);
@@ -0,0 +1,14 @@
+-- Up migration script
+
+CREATE TABLE IF NOT EXISTS flags (
+    flag_uuid TEXT PRIMARY KEY,
+    question_uuid TEXT NOT NULL,
+    answer_uuid TEXT,
+    reason TEXT NOT NULL,
+    comment VARCHAR(255),
+    created_at TEXT NOT NULL,
+    resolution TEXT,
+    resolved_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS flags_content_idx ON flags (question_uuid, answer_uuid);
@@ -0,0 +1,6 @@
+-- Down migration script
+
+ALTER TABLE answers DROP COLUMN reading_time_seconds;
+ALTER TABLE answers DROP COLUMN word_count;
+ALTER TABLE questions DROP COLUMN reading_time_seconds;
+ALTER TABLE questions DROP COLUMN word_count;
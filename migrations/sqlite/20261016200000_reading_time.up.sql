@@ -0,0 +1,34 @@
+-- Up migration script
+
+ALTER TABLE questions ADD COLUMN word_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE questions ADD COLUMN reading_time_seconds INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE answers ADD COLUMN word_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE answers ADD COLUMN reading_time_seconds INTEGER NOT NULL DEFAULT 0;
+
+-- Counted like the API counts them when content is written, as runs of characters other than whitespace, read at
+-- 200 words per minute rounded up to the second. SQLite has no regular expressions, so the characters are walked
+-- one by one, counting where words start.
+UPDATE questions SET word_count = (
+    WITH RECURSIVE chars (text, i, inside, words) AS (
+        SELECT questions.title || ' ' || questions.description, 1, 0, 0
+        UNION ALL
+        SELECT text, i + 1,
+               substr(text, i, 1) NOT IN (' ', char(9), char(10), char(13)),
+               words + (NOT inside AND substr(text, i, 1) NOT IN (' ', char(9), char(10), char(13)))
+        FROM chars WHERE i <= length(text)
+    )
+    SELECT MAX(words) FROM chars
+);
+UPDATE questions SET reading_time_seconds = (word_count * 60 + 199) / 200;
+UPDATE answers SET word_count = (
+    WITH RECURSIVE chars (text, i, inside, words) AS (
+        SELECT answers.content, 1, 0, 0
+        UNION ALL
+        SELECT text, i + 1,
+               substr(text, i, 1) NOT IN (' ', char(9), char(10), char(13)),
+               words + (NOT inside AND substr(text, i, 1) NOT IN (' ', char(9), char(10), char(13)))
+        FROM chars WHERE i <= length(text)
+    )
+    SELECT MAX(words) FROM chars
+);
+UPDATE answers SET reading_time_seconds = (word_count * 60 + 199) / 200;
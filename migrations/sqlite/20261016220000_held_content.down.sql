@@ -0,0 +1,3 @@
+-- Down migration script
+
+ALTER TABLE flags DROP COLUMN held_content;
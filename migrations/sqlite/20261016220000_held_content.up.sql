@@ -0,0 +1,3 @@
+-- Up migration script
+
+ALTER TABLE flags ADD COLUMN held_content TEXT;
@@ -0,0 +1,512 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::{
+    cache_control::add_vary,
+    client_ip::ClientIp,
+    policy::{AuthorizationPolicy, PolicyInput},
+};
+
+// Operators call the admin API with the admin token, which grants every endpoint. Other services call it as service
+// principals instead, each with a secret of its own and the scopes it is granted: they send a short-lived JWT client
+// assertion, signed with their secret, as a bearer token, so the secret itself never travels and a leaked assertion
+// is only good for minutes. Every admin request is logged along with the principal who made it. When a policy is
+// configured, it decides what services may do instead of their scopes (see `policy`).
+
+/// Audience service assertions must be addressed to
+pub const ASSERTION_AUDIENCE: &str = "tech-qna-api";
+
+/// Longest time between the issue and the expiry of a service assertion, in seconds
+const MAX_ASSERTION_LIFETIME_SECS: i64 = 300;
+
+/// Clock skew tolerated between a service and this instance, in seconds
+const CLOCK_LEEWAY_SECS: i64 = 30;
+
+/// Represents the endpoints of the admin API a service principal may call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AdminScope {
+    /// Opening and resolving incidents
+    Incidents,
+    /// Managing webhooks
+    Webhooks,
+    /// Starting background jobs and following them
+    Jobs,
+    /// Reading error budgets, requests in flight, uses of deprecated features and database maintenance statistics
+    Monitoring,
+    /// Reviewing flagged questions and answers, and acting on them
+    Moderation,
+}
+
+impl AdminScope {
+
+    /// The scope of an admin route.
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - The path of the route, e.g. `/admin/incident/resolve` or `/moderation/queue`.
+    ///
+    /// # Returns
+    ///
+    /// The scope granting the route, `None` for routes only the admin token grants.
+    fn of_route(route: &str) -> Option<AdminScope> {
+        let route = route.strip_prefix("/admin").unwrap_or(route);
+
+        match route.split('/').nth(1)? {
+            "incident" => Some(AdminScope::Incidents),
+            "webhook" | "webhooks" => Some(AdminScope::Webhooks),
+            "search" | "jobs" => Some(AdminScope::Jobs),
+            "slo" | "concurrency" | "deprecations" | "maintenance" => Some(AdminScope::Monitoring),
+            "moderation" => Some(AdminScope::Moderation),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for AdminScope {
+    type Err = String;
+
+    fn from_str(scope: &str) -> Result<Self, Self::Err> {
+        match scope {
+            "incidents" => Ok(AdminScope::Incidents),
+            "webhooks" => Ok(AdminScope::Webhooks),
+            "jobs" => Ok(AdminScope::Jobs),
+            "monitoring" => Ok(AdminScope::Monitoring),
+            "moderation" => Ok(AdminScope::Moderation),
+            _ => Err(format!("Unknown admin scope: {}", scope)),
+        }
+    }
+}
+
+/// A service calling the admin API, with the secret its assertions are signed with and the scopes it is granted
+#[derive(Debug)]
+pub struct ServicePrincipal {
+    name: String,
+    secret: String,
+    scopes: Vec<AdminScope>,
+}
+
+/// Header of a service assertion
+#[derive(Deserialize)]
+struct AssertionHeader {
+    alg: String,
+}
+
+/// Claims of a service assertion, the issuer being the name of the service
+#[derive(Deserialize)]
+struct AssertionClaims {
+    iss: String,
+    aud: String,
+    iat: i64,
+    exp: i64,
+}
+
+/// Credentials accepted by the admin API, and the policy deciding what services may do, if any
+pub struct AdminAuth {
+    token: Arc<str>,
+    services: Vec<ServicePrincipal>,
+    policy: Option<Arc<dyn AuthorizationPolicy + Send + Sync>>,
+}
+
+impl AdminAuth {
+
+    /// Creates the credentials of the admin API.
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The admin token, granting every endpoint.
+    /// * `services` - The name, secret and scopes of each service principal.
+    /// * `policy` - The policy deciding what services may do, their scopes deciding when `None`.
+    ///
+    /// # Returns
+    ///
+    /// An `AdminAuth` authorizing admin requests.
+    pub fn new(
+        token: Arc<str>,
+        services: impl IntoIterator<Item = (String, String, Vec<AdminScope>)>,
+        policy: Option<Arc<dyn AuthorizationPolicy + Send + Sync>>,
+    ) -> Self {
+        let services = services
+            .into_iter()
+            .map(|(name, secret, scopes)| ServicePrincipal { name, secret, scopes })
+            .collect();
+
+        AdminAuth { token, services, policy }
+    }
+
+    /// Decides whether a service may make an admin request, by the policy if there is one, otherwise by its scopes.
+    ///
+    /// # Arguments
+    ///
+    /// * `service` - The service principal making the request.
+    /// * `method` - The method of the request.
+    /// * `route` - The route requested, e.g. `/admin/incident/resolve`.
+    ///
+    /// # Returns
+    ///
+    /// Whether the request is allowed. The scopes of the service decide when the policy cannot.
+    async fn authorize(&self, service: &ServicePrincipal, method: &str, route: &str) -> bool {
+        let scope = AdminScope::of_route(route);
+        let granted = scope.is_some_and(|scope| service.scopes.contains(&scope));
+
+        let Some(policy) = &self.policy else {
+            return granted;
+        };
+
+        let input = PolicyInput {
+            service: service.name.clone(),
+            method: method.to_owned(),
+            route: route.to_owned(),
+            scope,
+            granted: service.scopes.clone(),
+        };
+
+        match policy.authorize(&input).await {
+            Ok(allowed) => allowed,
+            Err(err) => {
+                warn!("Falling back to the scopes of {} for {} {}: {}", service.name, method, route, err);
+                granted
+            }
+        }
+    }
+
+    /// Verifies a service assertion: a JWT signed with HS256 by the secret of the service it was issued by,
+    /// addressed to `ASSERTION_AUDIENCE`, unexpired and living no longer than `MAX_ASSERTION_LIFETIME_SECS`.
+    ///
+    /// # Arguments
+    ///
+    /// * `assertion` - The compact serialization of the JWT.
+    /// * `now` - The current time, in seconds since the Unix epoch.
+    ///
+    /// # Returns
+    ///
+    /// The service principal that issued the assertion, or why the assertion is refused.
+    fn verify_assertion(&self, assertion: &str, now: i64) -> Result<&ServicePrincipal, &'static str> {
+        let decode = |part: &str| URL_SAFE_NO_PAD.decode(part).map_err(|_| "not a JWT");
+
+        let (signed, signature) = assertion.rsplit_once('.').ok_or("not a JWT")?;
+        let (header, claims) = signed.split_once('.').ok_or("not a JWT")?;
+
+        let header: AssertionHeader = serde_json::from_slice(&decode(header)?).map_err(|_| "not a JWT")?;
+        // The algorithm is not left to the assertion, which could otherwise claim to need no signature
+        if header.alg != "HS256" {
+            return Err("not signed with HS256");
+        }
+
+        let claims: AssertionClaims = serde_json::from_slice(&decode(claims)?).map_err(|_| "invalid claims")?;
+        let service = self.services.iter().find(|service| service.name == claims.iss).ok_or("unknown issuer")?;
+
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(service.secret.as_bytes()).expect("HMAC accepts keys of any length");
+        mac.update(signed.as_bytes());
+        mac.verify_slice(&decode(signature)?).map_err(|_| "invalid signature")?;
+
+        if claims.aud != ASSERTION_AUDIENCE {
+            Err("addressed to another audience")
+        } else if claims.exp + CLOCK_LEEWAY_SECS <= now {
+            Err("expired")
+        } else if claims.iat - CLOCK_LEEWAY_SECS > now {
+            Err("issued in the future")
+        } else if claims.exp - claims.iat > MAX_ASSERTION_LIFETIME_SECS {
+            Err("valid for too long")
+        } else {
+            Ok(service)
+        }
+    }
+}
+
+/// Middleware rejecting requests that don't carry the admin token, or the assertion of a service granted the route,
+/// as a bearer token.
+///
+/// # Arguments
+///
+/// * `State(auth)` - The configured admin token and service principals.
+/// * `client_ip` - The address of the client, if known, for the audit log.
+/// * `request` - The incoming request.
+/// * `next` - The rest of the middleware stack.
+///
+/// # Returns
+///
+/// The response of the inner service if the token matches, or the assertion is valid and its service is granted the
+/// route, otherwise a `401 Unauthorized` or `403 Forbidden` response. Either depends on the `Authorization` header,
+/// which `Vary` names.
+pub async fn require_admin_token(
+    State(auth): State<Arc<AdminAuth>>,
+    client_ip: Option<ClientIp>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    let client = client_ip.map_or_else(|| "an unknown address".to_owned(), |ip| ip.to_string());
+    let (method, path) = (request.method().clone(), request.uri().path().to_owned());
+
+    let service = match provided {
+        Some(token) if constant_time_eq(token.as_bytes(), auth.token.as_bytes()) => None,
+        Some(token) if !auth.services.is_empty() => match auth.verify_assertion(token, Utc::now().timestamp()) {
+            Ok(service) => Some(service),
+            Err(reason) => {
+                warn!("Rejected admin request {} {} from {}: assertion {}.", method, path, client, reason);
+                return unauthorized();
+            }
+        },
+        _ => {
+            warn!("Rejected admin request {} {} from {}.", method, path, client);
+            return unauthorized();
+        }
+    };
+
+    let mut response = match service {
+        None => {
+            info!("Admin request {} {} from {}.", method, path, client);
+            next.run(request).await
+        }
+        Some(service) => {
+            let route = request.extensions().get::<MatchedPath>().map_or(path.as_str(), |route| route.as_str());
+
+            if auth.authorize(service, method.as_str(), route).await {
+                info!("Admin request {} {} by service {} from {}.", method, path, service.name, client);
+                next.run(request).await
+            } else {
+                let service = &service.name;
+                warn!("Refused admin request {} {} by {} from {}: not granted.", method, path, service, client);
+                (StatusCode::FORBIDDEN, "The service is not granted this endpoint.").into_response()
+            }
+        }
+    };
+
+    add_vary(response.headers_mut(), header::AUTHORIZATION);
+    response
+}
+
+/// The response to an admin request without valid credentials, which depends on the `Authorization` header.
+fn unauthorized() -> Response {
+    let mut response = (StatusCode::UNAUTHORIZED, "A valid admin token is required.").into_response();
+    add_vary(response.headers_mut(), header::AUTHORIZATION);
+    response
+}
+
+/// Compares two byte strings in time independent of where they first differ, so the
+/// token can't be guessed byte by byte from response timings.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use async_trait::async_trait;
+    use axum::{body::Body, middleware::from_fn_with_state, routing::get, Router};
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    use crate::policy::PolicyError;
+
+    const SECRET: &str = "status-page-s3cr3t";
+
+    /// Policy allowing only the routes it lists, or failing on every decision when it lists none
+    struct RoutesPolicy(&'static [&'static str]);
+
+    #[async_trait]
+    impl AuthorizationPolicy for RoutesPolicy {
+        async fn authorize(&self, input: &PolicyInput) -> Result<bool, PolicyError> {
+            if self.0.is_empty() {
+                return Err(PolicyError::Status(StatusCode::SERVICE_UNAVAILABLE));
+            }
+
+            Ok(self.0.contains(&input.route.as_str()))
+        }
+    }
+
+    fn app() -> Router {
+        app_with(None)
+    }
+
+    fn app_with(policy: Option<Arc<dyn AuthorizationPolicy + Send + Sync>>) -> Router {
+        let auth = AdminAuth::new(
+            Arc::from("s3cr3t-t0ken"),
+            [("status-page".to_owned(), SECRET.to_owned(), vec![AdminScope::Incidents])],
+            policy,
+        );
+
+        let admin = Router::new()
+            .route("/incident", get(|| async { "secret" }))
+            .route("/slo", get(|| async { "secret" }))
+            .route_layer(from_fn_with_state(Arc::new(auth), require_admin_token));
+
+        Router::new().nest("/admin", admin)
+    }
+
+    /// A service assertion with the given header and claims, signed with `secret`
+    fn assertion(header: Value, claims: Value, secret: &str) -> String {
+        let signed = format!(
+            "{}.{}",
+            URL_SAFE_NO_PAD.encode(header.to_string()),
+            URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(signed.as_bytes());
+
+        format!("{}.{}", signed, URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes()))
+    }
+
+    /// Claims of an assertion of the status page issued now, for a minute
+    fn claims() -> Value {
+        let now = Utc::now().timestamp();
+        json!({ "iss": "status-page", "aud": ASSERTION_AUDIENCE, "iat": now, "exp": now + 60 })
+    }
+
+    fn hs256() -> Value {
+        json!({ "alg": "HS256", "typ": "JWT" })
+    }
+
+    async fn service_status(uri: &str, assertion: &str) -> StatusCode {
+        service_status_with(app(), uri, assertion).await
+    }
+
+    async fn service_status_with(app: Router, uri: &str, assertion: &str) -> StatusCode {
+        let request = Request::get(uri).header(header::AUTHORIZATION, format!("Bearer {}", assertion));
+
+        app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+    }
+
+    async fn status_with(authorization: Option<&str>) -> StatusCode {
+        let mut request = Request::builder().uri("/admin/incident");
+        if let Some(authorization) = authorization {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+
+        app()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn should_accept_matching_token() {
+        assert_eq!(status_with(Some("Bearer s3cr3t-t0ken")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn should_reject_missing_or_wrong_token() {
+        assert_eq!(status_with(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status_with(Some("Bearer wrong")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status_with(Some("s3cr3t-t0ken")).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn should_vary_by_authorization() {
+        for authorization in [Some("Bearer s3cr3t-t0ken"), None] {
+            let mut request = Request::builder().uri("/admin/incident");
+            if let Some(authorization) = authorization {
+                request = request.header(header::AUTHORIZATION, authorization);
+            }
+
+            let response = app().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+            assert_eq!(response.headers()[header::VARY], "authorization");
+        }
+    }
+
+    #[tokio::test]
+    async fn should_accept_assertions_of_services_for_the_routes_they_are_granted() {
+        let assertion = assertion(hs256(), claims(), SECRET);
+
+        assert_eq!(service_status("/admin/incident", &assertion).await, StatusCode::OK);
+        assert_eq!(service_status("/admin/slo", &assertion).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn should_let_the_policy_decide_over_scopes() {
+        let assertion = assertion(hs256(), claims(), SECRET);
+        let app = || app_with(Some(Arc::new(RoutesPolicy(&["/admin/slo"]))));
+
+        assert_eq!(service_status_with(app(), "/admin/incident", &assertion).await, StatusCode::FORBIDDEN);
+        assert_eq!(service_status_with(app(), "/admin/slo", &assertion).await, StatusCode::OK);
+        // The admin token is not submitted to the policy
+        assert_eq!(service_status_with(app(), "/admin/incident", "s3cr3t-t0ken").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn should_fall_back_to_scopes_when_the_policy_fails() {
+        let assertion = assertion(hs256(), claims(), SECRET);
+        let app = || app_with(Some(Arc::new(RoutesPolicy(&[]))));
+
+        assert_eq!(service_status_with(app(), "/admin/incident", &assertion).await, StatusCode::OK);
+        assert_eq!(service_status_with(app(), "/admin/slo", &assertion).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn should_reject_invalid_assertions() {
+        let now = Utc::now().timestamp();
+        let with = |claims: Value| assertion(hs256(), claims, SECRET);
+
+        let invalid = [
+            assertion(hs256(), claims(), "another-s3cr3t-entirely"),
+            assertion(json!({ "alg": "none" }), claims(), SECRET),
+            with(json!({ "iss": "billing", "aud": ASSERTION_AUDIENCE, "iat": now, "exp": now + 60 })),
+            with(json!({ "iss": "status-page", "aud": "another-api", "iat": now, "exp": now + 60 })),
+            with(json!({ "iss": "status-page", "aud": ASSERTION_AUDIENCE, "iat": now - 120, "exp": now - 60 })),
+            with(json!({ "iss": "status-page", "aud": ASSERTION_AUDIENCE, "iat": now, "exp": now + 3600 })),
+            "not.a.jwt".to_owned(),
+        ];
+
+        for assertion in invalid {
+            assert_eq!(service_status("/admin/incident", &assertion).await, StatusCode::UNAUTHORIZED, "{}", assertion);
+        }
+    }
+
+    #[test]
+    fn should_scope_every_admin_route() {
+        let routes = [
+            ("/admin/incident", AdminScope::Incidents),
+            ("/admin/incident/resolve", AdminScope::Incidents),
+            ("/admin/webhook", AdminScope::Webhooks),
+            ("/admin/webhooks", AdminScope::Webhooks),
+            ("/admin/search/reindex", AdminScope::Jobs),
+            ("/admin/jobs/:job_uuid", AdminScope::Jobs),
+            ("/admin/slo", AdminScope::Monitoring),
+            ("/admin/concurrency", AdminScope::Monitoring),
+            ("/admin/deprecations", AdminScope::Monitoring),
+            ("/admin/maintenance", AdminScope::Monitoring),
+            ("/moderation/queue", AdminScope::Moderation),
+            ("/moderation/:flag_uuid/resolve", AdminScope::Moderation),
+        ];
+
+        for (route, scope) in routes {
+            assert_eq!(AdminScope::of_route(route), Some(scope), "{}", route);
+        }
+    }
+
+    #[test]
+    fn constant_time_eq_should_compare_contents() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"abcd"));
+    }
+}
@@ -0,0 +1,275 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method},
+    middleware::Next,
+    response::{IntoResponse, Redirect, Response},
+};
+
+// CDNs in front of the API cache responses as their `Cache-Control` header says, so caching is set up here rather
+// than in edge rules, next to the routes it applies to. Handlers setting the header themselves know better, and
+// errors are never marked cacheable, so an outage is not served from the CDN once it is over.
+//
+// A cache keys responses on their whole URL, query string included, and on the request headers named by `Vary`.
+// Responses depending on a request header must name it with `add_vary`, or a cache would serve them to requests
+// without it. Query strings of shared routes are redirected to a canonical order, so that
+// `?status=open&profile=compact` and `?profile=compact&status=open` are cached once.
+
+/// A route pattern and the `Cache-Control` directives of the responses it matches
+#[derive(Debug)]
+struct Rule {
+    segments: Vec<String>,
+    directives: HeaderValue,
+}
+
+impl Rule {
+
+    /// Whether the rule applies to a path.
+    ///
+    /// A `*` segment matches any one segment, or any number of them, but at least one, when it ends the pattern.
+    fn matches(&self, path: &str) -> bool {
+        let mut path = path.trim_start_matches('/').split('/');
+
+        for (i, segment) in self.segments.iter().enumerate() {
+            let is_last = i + 1 == self.segments.len();
+
+            match path.next() {
+                Some(part) if segment == "*" && is_last => return !part.is_empty(),
+                Some(part) if segment == "*" || segment == part => {}
+                _ => return false,
+            }
+        }
+
+        path.next().is_none()
+    }
+}
+
+/// `Cache-Control` directives set on the responses of the routes matching a pattern, the first matching rule winning.
+#[derive(Debug)]
+pub struct CachePolicy {
+    rules: Vec<Rule>,
+}
+
+impl CachePolicy {
+
+    /// Creates a policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `rules` - Route patterns, e.g. `/questions` or `/me/*`, and their directives, e.g. `public, max-age=30`,
+    ///   in order of precedence. Rules with directives that cannot be sent in a header are skipped.
+    ///
+    /// # Returns
+    ///
+    /// A `CachePolicy` applying the rules.
+    pub fn new(rules: impl IntoIterator<Item = (String, String)>) -> Self {
+        let rules = rules
+            .into_iter()
+            .filter_map(|(pattern, directives)| {
+                Some(Rule {
+                    segments: pattern.trim_start_matches('/').split('/').map(str::to_owned).collect(),
+                    directives: HeaderValue::from_str(&directives).ok()?,
+                })
+            })
+            .collect();
+
+        CachePolicy { rules }
+    }
+
+    /// The directives for the responses of a path, `None` if no rule matches it.
+    pub fn directives(&self, path: &str) -> Option<&HeaderValue> {
+        self.rules.iter().find(|rule| rule.matches(path)).map(|rule| &rule.directives)
+    }
+}
+
+/// Adds a request header to the `Vary` header of a response, as one the response depends on.
+///
+/// # Arguments
+///
+/// * `headers` - The headers of the response.
+/// * `name` - The request header the response depends on.
+pub fn add_vary(headers: &mut HeaderMap, name: HeaderName) {
+    let varies = headers
+        .get_all(header::VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|varied| varied.trim() == "*" || varied.trim().eq_ignore_ascii_case(name.as_str()));
+
+    if !varies {
+        headers.append(header::VARY, HeaderValue::from(name));
+    }
+}
+
+/// Whether directives let shared caches, such as CDNs, store the response.
+fn is_shared(directives: &HeaderValue) -> bool {
+    let directives = directives.to_str().unwrap_or_default().to_ascii_lowercase();
+    !directives.contains("private") && !directives.contains("no-store")
+}
+
+/// The query string with its parameters sorted by name, the values of a repeated parameter keeping their order.
+///
+/// # Returns
+///
+/// The canonical query string, `None` if the query string already is.
+fn canonical_query(query: &str) -> Option<String> {
+    let mut parameters: Vec<&str> = query.split('&').filter(|parameter| !parameter.is_empty()).collect();
+    parameters.sort_by_key(|parameter| parameter.split('=').next());
+
+    let canonical = parameters.join("&");
+    (canonical != query).then_some(canonical)
+}
+
+/// Middleware setting the `Cache-Control` header of successful reads from the policy, unless the handler set it.
+///
+/// Reads of routes shared caches may store are redirected to their canonical query string first, if they do not use
+/// it already.
+///
+/// # Arguments
+///
+/// * `State(policy)` - The cache policy.
+/// * `request` - The incoming request.
+/// * `next` - The rest of the middleware stack.
+///
+/// # Returns
+///
+/// The response, with the directives of the first rule matching its path.
+pub async fn apply_policy(State(policy): State<Arc<CachePolicy>>, request: Request, next: Next) -> Response {
+    let is_read = request.method() == Method::GET || request.method() == Method::HEAD;
+    let Some(directives) = policy.directives(request.uri().path()).filter(|_| is_read).cloned() else {
+        return next.run(request).await;
+    };
+
+    let canonical = request.uri().query().and_then(canonical_query).filter(|_| is_shared(&directives));
+    let mut response = match canonical {
+        Some(query) => Redirect::permanent(&format!("{}?{}", request.uri().path(), query)).into_response(),
+        None => next.run(request).await,
+    };
+
+    let cacheable = response.status().is_success() || response.status().is_redirection();
+    if cacheable {
+        response.headers_mut().entry(header::CACHE_CONTROL).or_insert(directives);
+    }
+
+    response
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::{
+        body::Body,
+        http::StatusCode,
+        middleware::from_fn_with_state,
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    fn policy() -> CachePolicy {
+        CachePolicy::new([
+            ("/questions".to_owned(), "public, max-age=30".to_owned()),
+            ("/questions/*/full".to_owned(), "public, max-age=10".to_owned()),
+            ("/me/*".to_owned(), "no-store".to_owned()),
+        ])
+    }
+
+    async fn send(method: Method, uri: &str) -> Response {
+        let app = Router::new()
+            .route("/questions", get(|| async { "questions" }).post(|| async { "created" }))
+            .route("/questions/:question_uuid/full", get(|| async { StatusCode::NOT_FOUND }))
+            .route("/me/tags", get(|| async { ([(header::CACHE_CONTROL, "private, max-age=60")], "tags") }))
+            .layer(from_fn_with_state(Arc::new(policy()), apply_policy));
+
+        let request = Request::builder().method(method).uri(uri).body(Body::empty()).unwrap();
+        app.oneshot(request).await.unwrap()
+    }
+
+    async fn cache_control(method: Method, uri: &str) -> Option<String> {
+        let response = send(method, uri).await;
+        response.headers().get(header::CACHE_CONTROL).map(|value| value.to_str().unwrap().to_owned())
+    }
+
+    #[test]
+    fn should_match_first_rule_for_path() {
+        let policy = policy();
+
+        assert_eq!(policy.directives("/questions").unwrap(), "public, max-age=30");
+        assert_eq!(policy.directives("/questions/d347261c/full").unwrap(), "public, max-age=10");
+        assert_eq!(policy.directives("/me/tags/rust").unwrap(), "no-store");
+        assert_eq!(policy.directives("/me"), None);
+        assert_eq!(policy.directives("/me/"), None);
+        assert_eq!(policy.directives("/questions/search"), None);
+    }
+
+    #[test]
+    fn should_skip_invalid_directives() {
+        let policy = CachePolicy::new([("/questions".to_owned(), "max-age=30\n".to_owned())]);
+
+        assert_eq!(policy.directives("/questions"), None);
+    }
+
+    #[tokio::test]
+    async fn should_only_set_directives_of_successful_reads() {
+        assert_eq!(cache_control(Method::GET, "/questions?status=open").await.as_deref(), Some("public, max-age=30"));
+        assert_eq!(cache_control(Method::POST, "/questions").await, None);
+        assert_eq!(cache_control(Method::GET, "/questions/d347261c/full").await, None);
+    }
+
+    #[test]
+    fn should_sort_query_parameters_by_name() {
+        assert_eq!(canonical_query("status=open&profile=compact").as_deref(), Some("profile=compact&status=open"));
+        assert_eq!(canonical_query("tag=b&limit=5&tag=a&").as_deref(), Some("limit=5&tag=b&tag=a"));
+        assert_eq!(canonical_query("profile=compact&status=open"), None);
+        assert_eq!(canonical_query("q=rust"), None);
+    }
+
+    #[tokio::test]
+    async fn should_redirect_shared_reads_to_canonical_query() {
+        let response = send(Method::GET, "/questions?status=open&profile=compact").await;
+
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(response.headers()[header::LOCATION], "/questions?profile=compact&status=open");
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "public, max-age=30");
+
+        let response = send(Method::GET, "/questions?profile=compact&status=open").await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Only shared caches key on the query string of others
+        let response = send(Method::GET, "/me/tags?b=1&a=2").await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn should_add_each_varying_header_once() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::VARY, HeaderValue::from_static("origin, Authorization"));
+
+        add_vary(&mut headers, header::AUTHORIZATION);
+        add_vary(&mut headers, header::ACCEPT_LANGUAGE);
+        add_vary(&mut headers, header::ACCEPT_LANGUAGE);
+
+        let vary: Vec<_> = headers.get_all(header::VARY).iter().collect();
+        assert_eq!(vary, ["origin, Authorization", "accept-language"]);
+    }
+
+    #[test]
+    fn should_not_add_to_vary_star() {
+        let mut headers = HeaderMap::from_iter([(header::VARY, HeaderValue::from_static("*"))]);
+
+        add_vary(&mut headers, header::AUTHORIZATION);
+
+        assert_eq!(headers.get_all(header::VARY).iter().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_keep_directives_set_by_handlers() {
+        assert_eq!(cache_control(Method::GET, "/me/tags").await.as_deref(), Some("private, max-age=60"));
+    }
+}
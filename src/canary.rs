@@ -0,0 +1,310 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderName, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tokio::sync::Semaphore;
+
+/// Header marking requests sent by the shadowing middleware, so a canary never shadows them again
+pub const SHADOW_HEADER: HeaderName = HeaderName::from_static("x-shadow-request");
+
+/// Largest request body middleware buffers, matching Axum's default body limit
+pub(crate) const MAX_BODY_SIZE: usize = 2 * 1024 * 1024;
+
+/// Shadow requests still in flight past this limit are dropped rather than queued
+const MAX_IN_FLIGHT: usize = 64;
+
+const SHADOW_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest excerpt of a response body included in diff logs
+const MAX_LOGGED_BODY: usize = 512;
+
+/// Duplicates a share of read traffic to a canary instance and logs how its responses differ.
+///
+/// Shadow requests are fire-and-forget: they run after the primary response is produced and
+/// their outcome never reaches the client.
+pub struct Canary {
+    client: reqwest::Client,
+    base_url: String,
+    traffic_ratio: f64,
+    diff_sample_ratio: f64,
+    in_flight: Arc<Semaphore>,
+}
+
+impl Canary {
+
+    /// Creates a canary shadowing to `base_url`.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_url` - Base URL of the canary instance, e.g. `http://canary:8000`.
+    /// * `traffic_percent` - Percentage of read requests to duplicate.
+    /// * `diff_sample_percent` - Percentage of duplicated requests whose responses are compared.
+    ///
+    /// # Returns
+    ///
+    /// A `Canary` ready to be used by the `shadow_reads` middleware.
+    pub fn new(base_url: &str, traffic_percent: f64, diff_sample_percent: f64) -> Self {
+        Canary {
+            client: reqwest::Client::builder()
+                .timeout(SHADOW_TIMEOUT)
+                .build()
+                .expect("Failed to create canary HTTP client!"),
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            traffic_ratio: traffic_percent / 100.0,
+            diff_sample_ratio: diff_sample_percent / 100.0,
+            in_flight: Arc::new(Semaphore::new(MAX_IN_FLIGHT)),
+        }
+    }
+
+    /// Sends a copy of a request to the canary, comparing the responses if `primary` is given.
+    async fn shadow(&self, method: Method, uri: String, headers: HeaderMap, body: Bytes, primary: Option<(StatusCode, Bytes)>) {
+        let url = format!("{}{}", self.base_url, uri);
+
+        let result = self.client
+            .request(method.clone(), &url)
+            .headers(headers)
+            .header(SHADOW_HEADER, "1")
+            .body(body)
+            .send()
+            .await;
+
+        let response = match result {
+            Ok(response) => response,
+            Err(err) => {
+                warn!("Canary request {} {} failed: {}", method, uri, err);
+                return;
+            }
+        };
+
+        let Some((primary_status, primary_body)) = primary else {
+            return;
+        };
+
+        let status = response.status();
+        let body = match response.bytes().await {
+            Ok(body) => body,
+            Err(err) => {
+                warn!("Failed to read canary response to {} {}: {}", method, uri, err);
+                return;
+            }
+        };
+
+        match diff((primary_status, &primary_body), (status, &body)) {
+            Some(difference) => warn!("Canary response to {} {} differs: {}", method, uri, difference),
+            None => debug!("Canary response to {} {} matches.", method, uri),
+        }
+    }
+}
+
+/// Describes how two responses differ.
+///
+/// JSON bodies are compared by value, so formatting and key order do not count as differences.
+///
+/// # Returns
+///
+/// `None` if the responses are equivalent, otherwise a description of the first difference.
+fn diff(primary: (StatusCode, &[u8]), canary: (StatusCode, &[u8])) -> Option<String> {
+    if primary.0 != canary.0 {
+        return Some(format!("status {} (primary) != {} (canary)", primary.0, canary.0));
+    }
+
+    let equal = match (
+        serde_json::from_slice::<serde_json::Value>(primary.1),
+        serde_json::from_slice::<serde_json::Value>(canary.1),
+    ) {
+        (Ok(primary), Ok(canary)) => primary == canary,
+        _ => primary.1 == canary.1,
+    };
+
+    if equal {
+        return None;
+    }
+
+    Some(format!(
+        "body {} (primary) != {} (canary)",
+        excerpt(primary.1),
+        excerpt(canary.1)
+    ))
+}
+
+fn excerpt(body: &[u8]) -> String {
+    let body = String::from_utf8_lossy(body);
+
+    match body.char_indices().nth(MAX_LOGGED_BODY) {
+        Some((end, _)) => format!("{}...", &body[..end]),
+        None => body.into_owned(),
+    }
+}
+
+/// Middleware duplicating a share of `GET` requests to the canary.
+///
+/// # Arguments
+///
+/// * `State(canary)` - The canary to shadow to.
+/// * `request` - The incoming request.
+/// * `next` - The rest of the middleware stack.
+///
+/// # Returns
+///
+/// The response produced by the inner service, unchanged.
+pub async fn shadow_reads(State(canary): State<Arc<Canary>>, request: Request, next: Next) -> Response {
+    if request.method() != Method::GET
+        || request.headers().contains_key(SHADOW_HEADER)
+        || rand::random::<f64>() >= canary.traffic_ratio
+    {
+        return next.run(request).await;
+    }
+
+    // Shed shadow traffic rather than let a slow canary pile up tasks
+    let Ok(permit) = canary.in_flight.clone().try_acquire_owned() else {
+        debug!("Too many canary requests in flight, not shadowing {}.", request.uri());
+        return next.run(request).await;
+    };
+
+    let (parts, body) = request.into_parts();
+    let body = match to_bytes(body, MAX_BODY_SIZE).await {
+        Ok(body) => body,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+
+    let method = parts.method.clone();
+    let uri = parts.uri.path_and_query().map(|p| p.to_string()).unwrap_or_default();
+    let headers: HeaderMap = parts.headers
+        .iter()
+        .filter(|(name, _)| [header::CONTENT_TYPE, header::ACCEPT].contains(name))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+
+    let response = next.run(Request::from_parts(parts, Body::from(body.clone()))).await;
+
+    // Only buffer the primary response when it is going to be compared
+    let (response, primary) = if rand::random::<f64>() < canary.diff_sample_ratio {
+        let (parts, response_body) = response.into_parts();
+
+        match to_bytes(response_body, usize::MAX).await {
+            Ok(response_body) => {
+                let primary = (parts.status, response_body.clone());
+                (Response::from_parts(parts, Body::from(response_body)), Some(primary))
+            }
+            Err(err) => {
+                error!("Failed to buffer response to {}: {}", uri, err);
+                (StatusCode::INTERNAL_SERVER_ERROR.into_response(), None)
+            }
+        }
+    } else {
+        (response, None)
+    };
+
+    tokio::spawn(async move {
+        let _permit = permit;
+        canary.shadow(method, uri, headers, body, primary).await;
+    });
+
+    response
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::{middleware::from_fn_with_state, routing::get, Router};
+    use tokio::sync::mpsc;
+    use tower::ServiceExt;
+
+    #[test]
+    fn should_ignore_json_formatting() {
+        let primary = br#"{"title": "Title", "description": "Description"}"#;
+        let canary = br#"{"description":"Description","title":"Title"}"#;
+
+        assert_eq!(diff((StatusCode::OK, primary), (StatusCode::OK, canary)), None);
+    }
+
+    #[test]
+    fn should_report_status_difference() {
+        let result = diff((StatusCode::OK, b"[]"), (StatusCode::INTERNAL_SERVER_ERROR, b"[]"));
+
+        assert!(result.unwrap().contains("500"));
+    }
+
+    #[test]
+    fn should_report_body_difference() {
+        let result = diff((StatusCode::OK, b"[1]"), (StatusCode::OK, b"[2]"));
+
+        assert_eq!(result.unwrap(), "body [1] (primary) != [2] (canary)");
+    }
+
+    #[test]
+    fn should_truncate_long_bodies() {
+        let body = "a".repeat(MAX_LOGGED_BODY * 2);
+
+        assert_eq!(excerpt(body.as_bytes()).len(), MAX_LOGGED_BODY + 3);
+    }
+
+    /// Serves a canary that forwards the URI and shadow header of each request it receives.
+    async fn spawn_canary() -> (String, mpsc::UnboundedReceiver<(String, bool)>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        let app = Router::new().fallback(move |request: Request| {
+            let sender = sender.clone();
+            async move {
+                let shadowed = request.headers().contains_key(SHADOW_HEADER);
+                sender.send((request.uri().to_string(), shadowed)).unwrap();
+                "[]"
+            }
+        });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (format!("http://{}", address), receiver)
+    }
+
+    fn primary(canary: Canary) -> Router {
+        Router::new()
+            .route("/questions", get(|| async { "[]" }).post(|| async { "{}" }))
+            .route_layer(from_fn_with_state(Arc::new(canary), shadow_reads))
+    }
+
+    #[tokio::test]
+    async fn should_shadow_reads_to_canary() {
+        let (url, mut received) = spawn_canary().await;
+        let app = primary(Canary::new(&url, 100.0, 100.0));
+
+        let response = app
+            .oneshot(Request::get("/questions?page=2").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let (uri, shadowed) = tokio::time::timeout(Duration::from_secs(5), received.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(uri, "/questions?page=2");
+        assert!(shadowed);
+    }
+
+    #[tokio::test]
+    async fn should_not_shadow_writes_or_unsampled_reads() {
+        let (url, mut received) = spawn_canary().await;
+
+        let app = primary(Canary::new(&url, 100.0, 100.0));
+        app.oneshot(Request::post("/questions").body(Body::empty()).unwrap()).await.unwrap();
+
+        let app = primary(Canary::new(&url, 0.0, 100.0));
+        app.oneshot(Request::get("/questions").body(Body::empty()).unwrap()).await.unwrap();
+
+        let result = tokio::time::timeout(Duration::from_millis(200), received.recv()).await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,307 @@
+use std::{
+    fmt,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use axum::{
+    extract::{ConnectInfo, FromRequestParts, Request, State},
+    http::{header, request::Parts, HeaderMap, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use ipnet::IpNet;
+
+const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Address of the client a request came from, as reported by trusted proxies.
+///
+/// Only available behind the `resolve_client_ip` middleware, and only when the server is run with
+/// connection info, as the address of the peer is where the search for the client starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl fmt::Display for ClientIp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ClientIp {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<ClientIp>()
+            .copied()
+            .ok_or((StatusCode::INTERNAL_SERVER_ERROR, "Client address is unknown."))
+    }
+}
+
+/// Networks of the reverse proxies and load balancers trusted to report the client address.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrustedProxies {
+    networks: Vec<IpNet>,
+}
+
+impl TrustedProxies {
+
+    /// Creates the list of trusted proxies.
+    ///
+    /// # Arguments
+    ///
+    /// * `networks` - The networks the proxies connect from.
+    ///
+    /// # Returns
+    ///
+    /// A `TrustedProxies`, which trusts nobody if `networks` is empty.
+    pub fn new(networks: Vec<IpNet>) -> Self {
+        TrustedProxies { networks }
+    }
+
+    /// Whether requests from an address may report the address of the client.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.networks.iter().any(|network| network.contains(&ip))
+    }
+
+    /// Finds the address of the client a request came from.
+    ///
+    /// Proxies append the address they received a request from, so the addresses in the
+    /// `Forwarded` header, or `X-Forwarded-For` if there is none, are walked from the right
+    /// for as long as they belong to trusted proxies. Anything to the left of the first untrusted
+    /// address could have been sent by the client itself and is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `peer` - The address the connection came from.
+    /// * `headers` - The request headers.
+    ///
+    /// # Returns
+    ///
+    /// The address of the client, which is the peer itself unless it is a trusted proxy.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let mut client = peer.to_canonical();
+
+        if !self.contains(client) {
+            return client;
+        }
+
+        for hop in forwarded_for(headers).into_iter().rev() {
+            match hop {
+                Some(ip) => client = ip.to_canonical(),
+                // Obfuscated or malformed, so the last trusted hop is as far as we can tell
+                None => break,
+            }
+
+            if !self.contains(client) {
+                break;
+            }
+        }
+
+        client
+    }
+}
+
+/// Parses a trusted proxy entry, either a network such as `10.0.0.0/8` or a single address.
+///
+/// # Arguments
+///
+/// * `entry` - The entry to parse.
+///
+/// # Returns
+///
+/// The network, or `None` if the entry is neither a network nor an address.
+pub fn parse_network(entry: &str) -> Option<IpNet> {
+    entry
+        .parse::<IpNet>()
+        .ok()
+        .or_else(|| entry.parse::<IpAddr>().ok().map(IpNet::from))
+}
+
+/// Middleware storing the `ClientIp` of a request in its extensions.
+///
+/// # Arguments
+///
+/// * `State(trusted_proxies)` - The proxies trusted to report the client address.
+/// * `connect_info` - The address of the peer, if the server was run with connection info.
+/// * `request` - The incoming request.
+/// * `next` - The rest of the middleware stack.
+///
+/// # Returns
+///
+/// The response of the inner service.
+pub async fn resolve_client_ip(
+    State(trusted_proxies): State<Arc<TrustedProxies>>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    if let Some(ConnectInfo(peer)) = connect_info {
+        let client_ip = trusted_proxies.client_ip(peer.ip(), request.headers());
+        request.extensions_mut().insert(ClientIp(client_ip));
+    }
+
+    next.run(request).await
+}
+
+/// The addresses a request was forwarded for, from the client to the nearest proxy, with `None`
+/// for the ones that are not IP addresses.
+fn forwarded_for(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    if headers.contains_key(header::FORWARDED) {
+        // RFC 7239, e.g. `for=192.0.2.60;proto=http, for="[2001:db8::17]:4711"`
+        header_elements(headers, header::FORWARDED.as_str())
+            .map(|element| {
+                element
+                    .split(';')
+                    .filter_map(|pair| pair.split_once('='))
+                    .find(|(name, _)| name.trim().eq_ignore_ascii_case("for"))
+                    .and_then(|(_, node)| parse_node(node))
+            })
+            .collect()
+    } else {
+        header_elements(headers, X_FORWARDED_FOR).map(parse_node).collect()
+    }
+}
+
+/// The comma-separated elements of every occurrence of a header, in order.
+fn header_elements<'a>(headers: &'a HeaderMap, name: &str) -> impl Iterator<Item = &'a str> {
+    headers
+        .get_all(name)
+        .iter()
+        // Values that are not visible ASCII can't be trusted, yet still count as a hop
+        .flat_map(|value| value.to_str().unwrap_or("unknown").split(','))
+        .map(str::trim)
+}
+
+/// Parses a node such as `192.0.2.60`, `192.0.2.60:4711`, `"[2001:db8::17]:4711"` or `2001:db8::17`.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+
+    if let Some(rest) = node.strip_prefix('[') {
+        return rest.split_once(']').and_then(|(ip, _)| ip.parse().ok());
+    }
+
+    node.parse().ok().or_else(|| {
+        let (ip, _port) = node.split_once(':')?;
+        ip.parse().ok()
+    })
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::{
+        body::Body,
+        extract::connect_info::MockConnectInfo,
+        http::HeaderValue,
+        middleware::from_fn_with_state,
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    fn trusted(entries: &[&str]) -> TrustedProxies {
+        TrustedProxies::new(entries.iter().map(|entry| parse_network(entry).unwrap()).collect())
+    }
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn should_ignore_headers_from_untrusted_peers() {
+        let proxies = trusted(&["10.0.0.0/8"]);
+
+        let client = proxies.client_ip(ip("203.0.113.9"), &headers(&[("x-forwarded-for", "198.51.100.1")]));
+
+        assert_eq!(client, ip("203.0.113.9"));
+    }
+
+    #[test]
+    fn should_stop_at_first_untrusted_hop() {
+        let proxies = trusted(&["10.0.0.0/8", "192.168.1.1"]);
+        // The client made up the first entry, the load balancer appended the second
+        let headers = headers(&[("x-forwarded-for", "1.2.3.4, 198.51.100.1"), ("x-forwarded-for", "192.168.1.1")]);
+
+        assert_eq!(proxies.client_ip(ip("10.0.0.5"), &headers), ip("198.51.100.1"));
+    }
+
+    #[test]
+    fn should_use_leftmost_hop_when_all_are_trusted() {
+        let proxies = trusted(&["10.0.0.0/8"]);
+
+        let client = proxies.client_ip(ip("10.0.0.5"), &headers(&[("x-forwarded-for", "10.1.1.1, 10.2.2.2")]));
+
+        assert_eq!(client, ip("10.1.1.1"));
+    }
+
+    #[test]
+    fn should_prefer_forwarded_header() {
+        let proxies = trusted(&["10.0.0.0/8"]);
+        let headers = headers(&[
+            ("forwarded", "for=192.0.2.60;proto=http, For=\"[2001:db8::17]:4711\";by=10.0.0.1"),
+            ("x-forwarded-for", "198.51.100.1"),
+        ]);
+
+        assert_eq!(proxies.client_ip(ip("10.0.0.5"), &headers), ip("2001:db8::17"));
+    }
+
+    #[test]
+    fn should_stop_at_unknown_hops() {
+        let proxies = trusted(&["10.0.0.0/8"]);
+        let headers = headers(&[("forwarded", "for=198.51.100.1, for=_hidden, for=10.1.1.1")]);
+
+        assert_eq!(proxies.client_ip(ip("10.0.0.5"), &headers), ip("10.1.1.1"));
+    }
+
+    #[test]
+    fn should_match_ipv4_mapped_peers() {
+        let proxies = trusted(&["10.0.0.0/8"]);
+
+        let client = proxies.client_ip(ip("::ffff:10.0.0.5"), &headers(&[("x-forwarded-for", "198.51.100.1:5000")]));
+
+        assert_eq!(client, ip("198.51.100.1"));
+    }
+
+    #[test]
+    fn should_parse_networks_and_addresses() {
+        assert_eq!(parse_network("10.0.0.0/8"), Some("10.0.0.0/8".parse().unwrap()));
+        assert_eq!(parse_network("::1"), Some("::1/128".parse().unwrap()));
+        assert_eq!(parse_network("load-balancer"), None);
+    }
+
+    #[tokio::test]
+    async fn should_provide_client_ip_to_handlers() {
+        let app = Router::new()
+            .route("/", get(|ClientIp(ip): ClientIp| async move { ip.to_string() }))
+            .layer(from_fn_with_state(Arc::new(trusted(&["127.0.0.1"])), resolve_client_ip))
+            .layer(MockConnectInfo(SocketAddr::from(([127, 0, 0, 1], 4000))));
+
+        let request = Request::builder()
+            .uri("/")
+            .header(X_FORWARDED_FOR, "198.51.100.1")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        assert_eq!(&body[..], b"198.51.100.1");
+    }
+}
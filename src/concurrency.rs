@@ -0,0 +1,255 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::models::RouteConcurrencyReport;
+
+/// Seconds clients are asked to wait before retrying a request refused for being over the limit
+const RETRY_AFTER_SECS: &str = "1";
+
+/// In-flight requests of one route
+#[derive(Debug, Default)]
+struct RouteGauge {
+    in_flight: AtomicU64,
+    peak_in_flight: AtomicU64,
+    rejected: AtomicU64,
+    /// Permits for the requests the route may serve at once, unlimited when `None`
+    limit: Option<(u64, Arc<Semaphore>)>,
+}
+
+/// Decrements the gauge of a route when its request completes, or is cancelled.
+struct InFlight {
+    gauge: Arc<RouteGauge>,
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.gauge.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Tracks the requests in flight on every route, and caps them on the routes with a limit.
+pub struct ConcurrencyTracker {
+    gauges: RwLock<HashMap<String, Arc<RouteGauge>>>,
+}
+
+impl ConcurrencyTracker {
+
+    /// Creates a tracker.
+    ///
+    /// # Arguments
+    ///
+    /// * `limits` - The most requests each route may serve at once, keyed by route path, e.g. `/questions/search`.
+    ///
+    /// # Returns
+    ///
+    /// A `ConcurrencyTracker` with no requests in flight.
+    pub fn new(limits: impl IntoIterator<Item = (String, u64)>) -> Self {
+        let gauges = limits
+            .into_iter()
+            .map(|(route, limit)| {
+                let semaphore = Arc::new(Semaphore::new(limit as usize));
+                (route, Arc::new(RouteGauge { limit: Some((limit, semaphore)), ..RouteGauge::default() }))
+            })
+            .collect();
+
+        ConcurrencyTracker { gauges: RwLock::new(gauges) }
+    }
+
+    /// Starts a request on a route, unless the route is at its limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - The path of the route.
+    ///
+    /// # Returns
+    ///
+    /// A guard keeping the request counted as in flight until dropped, or `None` if the request must be refused.
+    fn start(&self, route: &str) -> Option<InFlight> {
+        let gauge = self.gauge(route);
+
+        let permit = match &gauge.limit {
+            Some((_, semaphore)) => match semaphore.clone().try_acquire_owned() {
+                Ok(permit) => Some(permit),
+                Err(_) => {
+                    gauge.rejected.fetch_add(1, Ordering::Relaxed);
+                    return None;
+                }
+            },
+            None => None,
+        };
+
+        let in_flight = gauge.in_flight.fetch_add(1, Ordering::Relaxed) + 1;
+        gauge.peak_in_flight.fetch_max(in_flight, Ordering::Relaxed);
+
+        Some(InFlight { gauge, _permit: permit })
+    }
+
+    fn gauge(&self, route: &str) -> Arc<RouteGauge> {
+        if let Some(gauge) = self.gauges.read().unwrap().get(route) {
+            return gauge.clone();
+        }
+
+        // Routes without a limit get a gauge on their first request
+        self.gauges.write().unwrap().entry(route.to_owned()).or_default().clone()
+    }
+
+    /// Reports the requests in flight on every route that has a limit or has been requested.
+    ///
+    /// # Returns
+    ///
+    /// A `RouteConcurrencyReport` per route, ordered by route.
+    pub fn report(&self) -> Vec<RouteConcurrencyReport> {
+        let gauges = self.gauges.read().unwrap();
+
+        gauges
+            .iter()
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .map(|(route, gauge)| RouteConcurrencyReport {
+                route: route.clone(),
+                in_flight: gauge.in_flight.load(Ordering::Relaxed),
+                peak_in_flight: gauge.peak_in_flight.load(Ordering::Relaxed),
+                limit: gauge.limit.as_ref().map(|(limit, _)| *limit),
+                rejected_requests: gauge.rejected.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+}
+
+/// Middleware counting the requests in flight on the route it wraps, and refusing requests over its limit.
+///
+/// Requests over the limit are refused straight away rather than queued, as queued requests
+/// would still hold on to connections and memory while a slow route catches up.
+///
+/// # Arguments
+///
+/// * `State(tracker)` - The tracker to count requests in.
+/// * `request` - The incoming request.
+/// * `next` - The rest of the middleware stack.
+///
+/// # Returns
+///
+/// The response of the inner service, or a `503 Service Unavailable` response if the route is at its limit.
+pub async fn limit_concurrency(State(tracker): State<Arc<ConcurrencyTracker>>, request: Request, next: Next) -> Response {
+    // Only missing outside of a router, in which case there is no route to count the request in
+    let Some(route) = request.extensions().get::<MatchedPath>().map(|path| path.as_str().to_owned()) else {
+        return next.run(request).await;
+    };
+
+    match tracker.start(&route) {
+        Some(_in_flight) => next.run(request).await,
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            [(header::RETRY_AFTER, RETRY_AFTER_SECS)],
+            "Too many requests to this endpoint, please try again.",
+        )
+            .into_response(),
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::{body::Body, middleware::from_fn_with_state, routing::get, Router};
+    use tokio::sync::Notify;
+    use tower::ServiceExt;
+
+    fn report_for(tracker: &ConcurrencyTracker, route: &str) -> RouteConcurrencyReport {
+        tracker.report().into_iter().find(|report| report.route == route).unwrap()
+    }
+
+    #[test]
+    fn should_count_requests_in_flight() {
+        let tracker = ConcurrencyTracker::new([]);
+
+        let first = tracker.start("/questions").unwrap();
+        let second = tracker.start("/questions").unwrap();
+        drop(first);
+
+        let report = report_for(&tracker, "/questions");
+        assert_eq!(report.in_flight, 1);
+        assert_eq!(report.peak_in_flight, 2);
+        assert_eq!(report.limit, None);
+
+        drop(second);
+        assert_eq!(report_for(&tracker, "/questions").in_flight, 0);
+    }
+
+    #[test]
+    fn should_refuse_requests_over_limit() {
+        let tracker = ConcurrencyTracker::new([("/questions/search".to_owned(), 1)]);
+
+        let first = tracker.start("/questions/search").unwrap();
+        assert!(tracker.start("/questions/search").is_none());
+        // Other routes are not affected
+        assert!(tracker.start("/questions").is_some());
+
+        drop(first);
+        assert!(tracker.start("/questions/search").is_some());
+
+        let report = report_for(&tracker, "/questions/search");
+        assert_eq!(report.limit, Some(1));
+        assert_eq!(report.rejected_requests, 1);
+    }
+
+    #[test]
+    fn should_report_limited_routes_before_any_request() {
+        let tracker = ConcurrencyTracker::new([("/answers".to_owned(), 4)]);
+
+        assert_eq!(
+            tracker.report(),
+            vec![RouteConcurrencyReport {
+                route: "/answers".to_owned(),
+                in_flight: 0,
+                peak_in_flight: 0,
+                limit: Some(4),
+                rejected_requests: 0,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_respond_with_service_unavailable_over_limit() {
+        let tracker = Arc::new(ConcurrencyTracker::new([("/slow".to_owned(), 1)]));
+        let release = Arc::new(Notify::new());
+
+        let app = Router::new()
+            .route("/slow", get({
+                let release = release.clone();
+                || async move { release.notified().await }
+            }))
+            .route_layer(from_fn_with_state(tracker.clone(), limit_concurrency));
+
+        let first = tokio::spawn(app.clone().oneshot(Request::get("/slow").body(Body::empty()).unwrap()));
+        while report_for(&tracker, "/slow").in_flight == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let response = app.oneshot(Request::get("/slow").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], RETRY_AFTER_SECS);
+
+        release.notify_one();
+        assert_eq!(first.await.unwrap().unwrap().status(), StatusCode::OK);
+        assert_eq!(report_for(&tracker, "/slow").in_flight, 0);
+    }
+}
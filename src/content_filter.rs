@@ -0,0 +1,176 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::{Flag, FlagReason};
+
+// New questions and answers go through content filters before reaching the database. Each filter looks for one kind
+// of objectionable content, and the operator chooses what happens to content it matches: rejected outright, held in
+// the moderation queue until a moderator publishes it, or tagged in the logs only, to try a filter out before trusting it with the other two.
+//
+// Two heuristics are built in, a list of banned words and the density of links, which spam is full of. Library users
+// can add their own by implementing `ContentFilter`.
+
+/// Fewest links content must have before its link density counts, so a short answer pointing at the docs passes
+const MIN_LINKS: usize = 2;
+
+/// Represents what happens to content a filter matches, from the most to the least lenient
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterAction {
+    /// Created, with a warning logged
+    Tag,
+    /// Stored on a flag for moderators to review, and only created once they dismiss it
+    Hold,
+    /// Not created, the client getting a 400 status code
+    Reject,
+}
+
+/// Looks for one kind of objectionable content in questions and answers
+pub trait ContentFilter {
+
+    /// Checks the text of a question, its title and description, or of an answer.
+    ///
+    /// # Returns
+    ///
+    /// The flag raised on the content, `None` if the filter does not match it.
+    fn check(&self, text: &str) -> Option<Flag>;
+}
+
+/// Matches content containing any of a list of words, whatever their case
+#[derive(Debug)]
+pub struct WordListFilter {
+    words: HashSet<String>,
+}
+
+impl WordListFilter {
+
+    /// Creates a filter matching the given words, as whole words.
+    pub fn new(words: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        WordListFilter { words: words.into_iter().map(|word| word.as_ref().trim().to_lowercase()).collect() }
+    }
+}
+
+impl ContentFilter for WordListFilter {
+    fn check(&self, text: &str) -> Option<Flag> {
+        let word = text
+            .split(|c: char| !c.is_alphanumeric())
+            .map(str::to_lowercase)
+            .find(|word| self.words.contains(word))?;
+
+        Some(Flag { reason: FlagReason::Offensive, comment: Some(format!("Contains the banned word \"{}\"", word)) })
+    }
+}
+
+/// Matches content in which too many words are links
+#[derive(Debug)]
+pub struct LinkDensityFilter {
+    /// Most links per word
+    max_ratio: f64,
+}
+
+impl LinkDensityFilter {
+
+    /// Creates a filter matching content with more links per word than `max_ratio`, once it has a few links.
+    pub fn new(max_ratio: f64) -> Self {
+        LinkDensityFilter { max_ratio }
+    }
+}
+
+impl ContentFilter for LinkDensityFilter {
+    fn check(&self, text: &str) -> Option<Flag> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let links = words
+            .iter()
+            .filter(|word| ["http://", "https://", "www."].iter().any(|prefix| word.contains(prefix)))
+            .count();
+
+        let too_dense = links >= MIN_LINKS && links as f64 / words.len() as f64 > self.max_ratio;
+        too_dense.then(|| Flag {
+            reason: FlagReason::Spam,
+            comment: Some(format!("{} of its {} words are links", links, words.len())),
+        })
+    }
+}
+
+/// Content filters new questions and answers go through, with the action taken on the content each one matches
+#[derive(Default)]
+pub struct ContentFilters {
+    filters: Vec<(Box<dyn ContentFilter + Send + Sync>, FilterAction)>,
+}
+
+impl ContentFilters {
+
+    /// Creates the pipeline, running the filters in order.
+    pub fn new(filters: Vec<(Box<dyn ContentFilter + Send + Sync>, FilterAction)>) -> Self {
+        ContentFilters { filters }
+    }
+
+    /// Runs every filter on the text of a question or answer.
+    ///
+    /// # Returns
+    ///
+    /// The strictest action among the filters matching the text, with the flag raised by the first of them, `None` if
+    /// none does.
+    pub fn check(&self, text: &str) -> Option<(FilterAction, Flag)> {
+        self.filters
+            .iter()
+            .filter_map(|(filter, action)| Some((*action, filter.check(text)?)))
+            // The first of the strictest, `max_by_key` returning the last
+            .rev()
+            .max_by_key(|(action, _)| *action)
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_match_banned_words_whatever_their_case() {
+        let filter = WordListFilter::new(["Darn", " heck "]);
+
+        let flag = filter.check("Why the HECK does it panic?").unwrap();
+        assert_eq!(flag.reason, FlagReason::Offensive);
+        assert_eq!(flag.comment.as_deref(), Some("Contains the banned word \"heck\""));
+
+        assert!(filter.check("darn.").is_some());
+        // Only whole words
+        assert!(filter.check("Check the darned logs").is_none());
+    }
+
+    #[test]
+    fn should_match_content_with_too_many_links_per_word() {
+        let filter = LinkDensityFilter::new(0.3);
+
+        let flag = filter.check("Cheap licenses https://a.example www.b.example http://c.example").unwrap();
+        assert_eq!(flag.reason, FlagReason::Spam);
+        assert_eq!(flag.comment.as_deref(), Some("3 of its 5 words are links"));
+
+        // A single link passes, however short the content
+        assert!(filter.check("https://docs.rs").is_none());
+        assert!(filter.check("See https://docs.rs and https://crates.io for the details of the crate").is_none());
+    }
+
+    #[test]
+    fn should_take_the_strictest_action_of_the_matching_filters() {
+        let filters = ContentFilters::new(vec![
+            (Box::new(WordListFilter::new(["heck"])), FilterAction::Tag),
+            (Box::new(LinkDensityFilter::new(0.3)), FilterAction::Hold),
+            (Box::new(WordListFilter::new(["darn"])), FilterAction::Hold),
+        ]);
+
+        assert_eq!(filters.check("All fine"), None);
+        assert_eq!(filters.check("heck").map(|(action, _)| action), Some(FilterAction::Tag));
+
+        let (action, flag) = filters.check("darn heck https://a.example https://b.example").unwrap();
+        assert_eq!(action, FilterAction::Hold);
+        assert_eq!(flag.reason, FlagReason::Spam);
+
+        assert_eq!(ContentFilters::default().check("darn heck"), None);
+    }
+}
@@ -0,0 +1,278 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderName, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+
+use crate::models::DeprecationReport;
+
+// Routes and query parameters are deprecated from configuration, as the API versions, rather than by their handlers.
+// Responses to requests using one announce it with the `Deprecation` header (RFC 9745), when the feature is removed
+// with `Sunset` (RFC 8594) and what replaces it with a `successor-version` link. Every use is counted, so a feature
+// can be removed once clients have stopped using it.
+
+/// A route, or a query parameter of a route, that clients should stop using.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deprecation {
+    /// The method deprecated, every method of the route when `None`
+    pub method: Option<Method>,
+    /// The route, as registered with the router, e.g. `/questions/:question_uuid/full`
+    pub route: String,
+    /// The query parameter deprecated, the whole route when `None`
+    pub parameter: Option<String>,
+    pub deprecated_at: DateTime<Utc>,
+    /// When the feature is removed, if decided
+    pub sunset_at: Option<DateTime<Utc>>,
+    /// The URL of what replaces the feature, if anything
+    pub successor: Option<String>,
+}
+
+impl Deprecation {
+
+    /// The feature deprecated, as reported, e.g. `GET /answers` or `/questions?profile`.
+    fn feature(&self) -> String {
+        let method = self.method.as_ref().map_or(String::new(), |method| format!("{} ", method));
+        let parameter = self.parameter.as_ref().map_or(String::new(), |parameter| format!("?{}", parameter));
+
+        format!("{}{}{}", method, self.route, parameter)
+    }
+
+    /// Whether a request uses the feature.
+    fn matches(&self, method: &Method, route: &str, query: Option<&str>) -> bool {
+        let uses_parameter = |parameter: &String| {
+            query
+                .unwrap_or_default()
+                .split('&')
+                .any(|pair| pair.split('=').next() == Some(parameter.as_str()))
+        };
+
+        self.method.as_ref().is_none_or(|deprecated| deprecated == method)
+            && self.route == route
+            && self.parameter.as_ref().is_none_or(uses_parameter)
+    }
+
+    /// The headers announcing the deprecation, dropping any that cannot be sent.
+    fn headers(&self) -> Vec<(HeaderName, HeaderValue)> {
+        // A date in seconds since the epoch is only made of characters allowed in headers
+        let deprecation = HeaderValue::from_str(&format!("@{}", self.deprecated_at.timestamp())).unwrap();
+        let mut headers = vec![(HeaderName::from_static("deprecation"), deprecation)];
+
+        if let Some(sunset_at) = self.sunset_at {
+            let sunset = sunset_at.format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+            headers.extend(HeaderValue::from_str(&sunset).ok().map(|value| (HeaderName::from_static("sunset"), value)));
+        }
+
+        if let Some(successor) = &self.successor {
+            let link = format!("<{}>; rel=\"successor-version\"", successor);
+            headers.extend(HeaderValue::from_str(&link).ok().map(|value| (header::LINK, value)));
+        }
+
+        headers
+    }
+}
+
+/// A deprecation, with the headers announcing it and how much it is still used
+struct Tracked {
+    deprecation: Deprecation,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    uses: AtomicU64,
+    last_used_at: Mutex<Option<DateTime<Utc>>>,
+}
+
+/// Registry of the deprecated features, counting their uses since the server started.
+pub struct DeprecationRegistry {
+    deprecations: Vec<Tracked>,
+}
+
+impl DeprecationRegistry {
+
+    /// Creates a registry.
+    ///
+    /// # Arguments
+    ///
+    /// * `deprecations` - The deprecated features, the first one matching a request announcing the deprecation when
+    ///   several do.
+    ///
+    /// # Returns
+    ///
+    /// A `DeprecationRegistry` with no uses counted.
+    pub fn new(deprecations: impl IntoIterator<Item = Deprecation>) -> Self {
+        let deprecations = deprecations
+            .into_iter()
+            .map(|deprecation| Tracked {
+                headers: deprecation.headers(),
+                deprecation,
+                uses: AtomicU64::new(0),
+                last_used_at: Mutex::new(None),
+            })
+            .collect();
+
+        DeprecationRegistry { deprecations }
+    }
+
+    /// Whether nothing is deprecated.
+    pub fn is_empty(&self) -> bool {
+        self.deprecations.is_empty()
+    }
+
+    /// Counts a use of every feature a request uses.
+    ///
+    /// # Returns
+    ///
+    /// The headers of the first deprecation the request uses, `None` if it uses no deprecated feature.
+    fn record(&self, method: &Method, route: &str, query: Option<&str>) -> Option<&[(HeaderName, HeaderValue)]> {
+        let now = Utc::now();
+        let mut headers = None;
+
+        for tracked in &self.deprecations {
+            if tracked.deprecation.matches(method, route, query) {
+                tracked.uses.fetch_add(1, Ordering::Relaxed);
+                *tracked.last_used_at.lock().unwrap() = Some(now);
+                headers.get_or_insert(tracked.headers.as_slice());
+            }
+        }
+
+        headers
+    }
+
+    /// Reports every deprecated feature with its uses.
+    ///
+    /// # Returns
+    ///
+    /// A `DeprecationReport` per feature, in the order they were registered.
+    pub fn report(&self) -> Vec<DeprecationReport> {
+        self.deprecations
+            .iter()
+            .map(|tracked| DeprecationReport {
+                feature: tracked.deprecation.feature(),
+                deprecated_at: tracked.deprecation.deprecated_at,
+                sunset_at: tracked.deprecation.sunset_at,
+                successor: tracked.deprecation.successor.clone(),
+                uses: tracked.uses.load(Ordering::Relaxed),
+                last_used_at: *tracked.last_used_at.lock().unwrap(),
+            })
+            .collect()
+    }
+}
+
+/// Middleware counting the uses of deprecated features and announcing their deprecation on the responses.
+///
+/// # Arguments
+///
+/// * `State(registry)` - The registry of deprecated features.
+/// * `request` - The incoming request.
+/// * `next` - The rest of the middleware stack.
+///
+/// # Returns
+///
+/// The response of the inner service, with the `Deprecation`, `Sunset` and `Link` headers of the first deprecated
+/// feature the request uses.
+pub async fn announce_deprecations(
+    State(registry): State<Arc<DeprecationRegistry>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    // Only missing outside of a router, in which case there is no route to match
+    let Some(route) = request.extensions().get::<MatchedPath>().map(|path| path.as_str().to_owned()) else {
+        return next.run(request).await;
+    };
+
+    let headers = registry.record(request.method(), &route, request.uri().query()).map(<[_]>::to_vec);
+
+    let mut response = next.run(request).await;
+    for (name, value) in headers.into_iter().flatten() {
+        response.headers_mut().append(name, value);
+    }
+
+    response
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::{body::Body, middleware::from_fn_with_state, routing::get, Router};
+    use chrono::TimeZone;
+    use tower::ServiceExt;
+
+    fn date(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap()
+    }
+
+    fn registry() -> DeprecationRegistry {
+        DeprecationRegistry::new([
+            Deprecation {
+                method: Some(Method::GET),
+                route: "/answers".to_owned(),
+                parameter: None,
+                deprecated_at: date(2026, 10, 1),
+                sunset_at: Some(date(2027, 4, 1)),
+                successor: Some("/questions/:question_uuid/full".to_owned()),
+            },
+            Deprecation {
+                method: None,
+                route: "/questions".to_owned(),
+                parameter: Some("profile".to_owned()),
+                deprecated_at: date(2026, 11, 1),
+                sunset_at: None,
+                successor: None,
+            },
+        ])
+    }
+
+    async fn send(registry: Arc<DeprecationRegistry>, uri: &str) -> Response {
+        let app = Router::new()
+            .route("/answers", get(|| async { "answers" }))
+            .route("/questions", get(|| async { "questions" }))
+            .route_layer(from_fn_with_state(registry, announce_deprecations));
+
+        app.oneshot(Request::get(uri).body(Body::empty()).unwrap()).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_announce_deprecated_routes() {
+        let response = send(Arc::new(registry()), "/answers?question_uuid=1").await;
+
+        let headers = response.headers();
+        assert_eq!(headers["deprecation"], "@1790812800");
+        assert_eq!(headers["sunset"], "Thu, 01 Apr 2027 00:00:00 GMT");
+        assert_eq!(headers[header::LINK], "</questions/:question_uuid/full>; rel=\"successor-version\"");
+    }
+
+    #[tokio::test]
+    async fn should_announce_deprecated_parameters_only_when_used() {
+        let registry = Arc::new(registry());
+
+        let response = send(registry.clone(), "/questions?status=open").await;
+        assert!(response.headers().get("deprecation").is_none());
+
+        let response = send(registry, "/questions?status=open&profile=compact").await;
+        assert_eq!(response.headers()["deprecation"], "@1793491200");
+        assert!(response.headers().get("sunset").is_none());
+    }
+
+    #[tokio::test]
+    async fn should_count_uses_of_each_feature() {
+        let registry = Arc::new(registry());
+
+        for uri in ["/answers", "/answers", "/questions", "/questions?profile=web"] {
+            send(registry.clone(), uri).await;
+        }
+
+        let report = registry.report();
+        let uses: Vec<_> = report.iter().map(|report| (report.feature.as_str(), report.uses)).collect();
+        assert_eq!(uses, [("GET /answers", 2), ("/questions?profile", 1)]);
+        assert!(report.iter().all(|report| report.last_used_at.is_some()));
+    }
+}
@@ -0,0 +1,154 @@
+use serde_json::{json, Map, Value};
+use utoipa::ToSchema;
+
+use crate::models::{AnswerDetail, AnswerId, EventSchemas, QuestionDetail, QuestionId};
+
+// Webhook consumers validate the payloads they receive, and generate their types, from JSON Schemas of the events.
+// The schemas are built from the same `ToSchema` derives as the OpenAPI specification, so they change along with the
+// models and are served by the very build that sends the events, the API version telling builds apart.
+//
+// OpenAPI 3.1 schemas are JSON Schemas, only referencing each other under `#/components/schemas`, so each event
+// schema carries the schemas it references under `$defs` instead, making it usable on its own.
+
+/// Draft of JSON Schema the schemas are written in
+const DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Builds the JSON Schemas of the payloads of every content event.
+///
+/// # Returns
+///
+/// The `EventSchemas`, by event name, along with the version of the API.
+pub fn event_schemas() -> EventSchemas {
+    let schemas = [
+        ("question.created", event_schema::<QuestionDetail>("question.created")),
+        ("question.deleted", event_schema::<QuestionId>("question.deleted")),
+        ("answer.created", event_schema::<AnswerDetail>("answer.created")),
+        ("answer.deleted", event_schema::<AnswerId>("answer.deleted")),
+    ];
+
+    EventSchemas {
+        version: env!("CARGO_PKG_VERSION").to_owned(),
+        schemas: schemas.into_iter().map(|(event, schema)| (event.to_owned(), schema)).collect(),
+    }
+}
+
+/// Builds the JSON Schema of the payload of an event, whose `data` is a `T`.
+fn event_schema<T: ToSchema>(event: &str) -> Value {
+    let mut defs = vec![(T::name().into_owned(), T::schema())];
+    T::schemas(&mut defs);
+
+    let defs: Map<String, Value> = defs
+        .into_iter()
+        .map(|(name, schema)| (name, serde_json::to_value(schema).expect("Schemas always serialize")))
+        .collect();
+
+    let mut schema = json!({
+        "$schema": DIALECT,
+        "title": event,
+        "type": "object",
+        "required": ["occurred_at", "event", "data"],
+        "properties": {
+            "delivery_uuid": {
+                "type": "string",
+                "format": "uuid",
+                "description": "Unique identifier of the delivery to a webhook, the same for every attempt, absent from \
+                                the messages published to brokers",
+            },
+            "occurred_at": { "type": "string", "format": "date-time" },
+            "event": { "const": event },
+            "data": { "$ref": format!("#/$defs/{}", T::name()) },
+        },
+        "$defs": defs,
+    });
+    point_refs_at_defs(&mut schema);
+
+    schema
+}
+
+/// Points the references of a schema at `$defs` rather than at the components of the OpenAPI specification.
+fn point_refs_at_defs(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            if let Some(Value::String(reference)) = object.get_mut("$ref") {
+                if let Some(name) = reference.strip_prefix("#/components/schemas/") {
+                    *reference = format!("#/$defs/{}", name);
+                }
+            }
+
+            object.values_mut().for_each(point_refs_at_defs);
+        }
+        Value::Array(items) => items.iter_mut().for_each(point_refs_at_defs),
+        _ => {}
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        outbox::ContentEvent,
+        test_support::{AnswerBuilder, QuestionBuilder},
+    };
+
+    /// Collects every reference of a schema.
+    fn refs(value: &Value, found: &mut Vec<String>) {
+        match value {
+            Value::Object(object) => {
+                if let Some(Value::String(reference)) = object.get("$ref") {
+                    found.push(reference.clone());
+                }
+                object.values().for_each(|value| refs(value, found));
+            }
+            Value::Array(items) => items.iter().for_each(|value| refs(value, found)),
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn should_describe_the_payload_of_every_event() {
+        let question = QuestionBuilder::new().build_detail();
+        let answer = AnswerBuilder::new(question.question_uuid).build_detail();
+        let events = [
+            ContentEvent::QuestionCreated(question.clone()),
+            ContentEvent::QuestionDeleted(QuestionId { question_uuid: question.question_uuid }),
+            ContentEvent::AnswerCreated(answer.clone()),
+            ContentEvent::AnswerDeleted(AnswerId { answer_uuid: answer.answer_uuid }),
+        ];
+
+        let EventSchemas { version, schemas } = event_schemas();
+        assert_eq!(version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(schemas.len(), events.len());
+
+        for event in events {
+            let schema = &schemas[event.name()];
+            assert_eq!(schema["properties"]["event"]["const"], event.name());
+
+            // Every field sent is described
+            let data_def = schema["properties"]["data"]["$ref"].as_str().unwrap().trim_start_matches("#/$defs/");
+            let properties = schema["$defs"][data_def]["properties"].as_object().unwrap();
+            let payload = serde_json::to_value(&event).unwrap();
+            for field in payload["data"].as_object().unwrap().keys() {
+                assert!(properties.contains_key(field), "{} of {}", field, event.name());
+            }
+        }
+    }
+
+    #[test]
+    fn should_only_reference_definitions_the_schema_carries() {
+        for (event, schema) in event_schemas().schemas {
+            let mut found = Vec::new();
+            refs(&schema, &mut found);
+
+            assert!(!found.is_empty());
+            for reference in found {
+                let name = reference.strip_prefix("#/$defs/").unwrap_or_else(|| panic!("{} in {}", reference, event));
+                assert!(schema["$defs"].get(name).is_some(), "{} in {}", reference, event);
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use thiserror::Error;
+use tokio::{
+    sync::broadcast::{error::RecvError, Receiver},
+    task::JoinHandle,
+};
+use uuid::Uuid;
+
+use crate::{outbox::ContentEvent, AppState};
+
+// Events are published for the analytics pipeline, which subscribes to the broker rather than polling the database.
+// Each broker is behind its own feature, `nats` or `kafka`, so builds only link the clients they use.
+
+/// How long Kafka may hold a message while the brokers are unreachable, before reporting it failed
+#[cfg(feature = "kafka")]
+const KAFKA_QUEUE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Wait between two attempts at reaching the NATS server, while it is unreachable
+#[cfg(feature = "nats")]
+const NATS_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Errors for connecting to a broker and publishing events to it
+#[derive(Error, Debug)]
+pub enum EventsError {
+
+    /// The URL scheme names a broker this build has no client for
+    #[error("Unsupported events URL, expected one starting with {0}")]
+    UnsupportedScheme(String),
+
+    #[cfg(feature = "nats")]
+    #[error("NATS: {0}")]
+    Nats(String),
+
+    #[cfg(feature = "kafka")]
+    #[error("Kafka: {0}")]
+    Kafka(#[from] rdkafka::error::KafkaError),
+
+    #[error("Failed to serialize the event: {0}")]
+    Serialize(#[from] serde_json::Error),
+}
+
+/// Body of the messages published, the same as webhook payloads without the delivery identifier
+#[derive(Serialize)]
+struct Message<'a> {
+    occurred_at: DateTime<Utc>,
+    #[serde(flatten)]
+    event: &'a ContentEvent,
+}
+
+/// Client of the broker events are published to
+#[derive(Clone)]
+pub enum Publisher {
+    #[cfg(feature = "nats")]
+    Nats(async_nats::Client),
+    #[cfg(feature = "kafka")]
+    Kafka(rdkafka::producer::FutureProducer),
+}
+
+/// URL schemes of the brokers this build supports
+fn supported_schemes() -> Vec<&'static str> {
+    [("nats://", cfg!(feature = "nats")), ("kafka://", cfg!(feature = "kafka"))]
+        .into_iter()
+        .filter_map(|(scheme, supported)| supported.then_some(scheme))
+        .collect()
+}
+
+impl Publisher {
+
+    /// Creates a client for the broker an `EVENTS_URL` points at.
+    ///
+    /// Neither client waits for the broker to be reachable, so the API starts, and events are buffered, while it is
+    /// down.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - `nats://host:4222`, or `kafka://` followed by comma separated `host:port` bootstrap brokers.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the publisher on success, or an `EventsError` if the URL is not supported or invalid.
+    pub async fn connect(url: &str) -> Result<Self, EventsError> {
+        #[cfg(feature = "nats")]
+        if url.starts_with("nats://") {
+            let client = async_nats::ConnectOptions::new()
+                .retry_on_initial_connect()
+                .reconnect_delay_callback(|_| NATS_RECONNECT_DELAY)
+                .connect(url)
+                .await
+                .map_err(|err| EventsError::Nats(err.to_string()))?;
+
+            return Ok(Publisher::Nats(client));
+        }
+
+        #[cfg(feature = "kafka")]
+        if let Some(brokers) = url.strip_prefix("kafka://") {
+            let producer = rdkafka::ClientConfig::new()
+                .set("bootstrap.servers", brokers)
+                // Retried messages must not be duplicated or reordered
+                .set("enable.idempotence", "true")
+                .create()?;
+
+            return Ok(Publisher::Kafka(producer));
+        }
+
+        Err(EventsError::UnsupportedScheme(supported_schemes().join(" or ")))
+    }
+
+    /// Publishes an event to a topic, keyed by the question or answer it is about so that the events of a resource
+    /// keep their order.
+    ///
+    /// # Arguments
+    ///
+    /// * `topic` - The Kafka topic or NATS subject.
+    /// * `event` - The event to publish.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned once the broker has the event,
+    /// otherwise, an `EventsError` is returned.
+    pub async fn publish(&self, topic: &str, event: &ContentEvent) -> Result<(), EventsError> {
+        let body = serde_json::to_vec(&Message {
+            occurred_at: Utc::now(),
+            event,
+        })?;
+
+        match self {
+            #[cfg(feature = "nats")]
+            Publisher::Nats(client) => {
+                // NATS has no keys, subscribers needing the resource read it from the body
+                client
+                    .publish(topic.to_owned(), body.into())
+                    .await
+                    .map_err(|err| EventsError::Nats(err.to_string()))
+            }
+            #[cfg(feature = "kafka")]
+            Publisher::Kafka(producer) => {
+                let key = key(event).to_string();
+                let record = rdkafka::producer::FutureRecord::to(topic).key(&key).payload(&body);
+
+                producer
+                    .send(record, KAFKA_QUEUE_TIMEOUT)
+                    .await
+                    .map(|_| ())
+                    .map_err(|(err, _)| EventsError::Kafka(err))
+            }
+        }
+    }
+}
+
+/// The question or answer an event is about.
+#[cfg_attr(not(feature = "kafka"), allow(dead_code))]
+fn key(event: &ContentEvent) -> Uuid {
+    match event {
+        ContentEvent::QuestionCreated(question) => *question.question_uuid.as_uuid(),
+        ContentEvent::QuestionDeleted(question) => *question.question_uuid.as_uuid(),
+        ContentEvent::AnswerCreated(answer) => *answer.answer_uuid.as_uuid(),
+        ContentEvent::AnswerDeleted(answer) => *answer.answer_uuid.as_uuid(),
+    }
+}
+
+/// Starts publishing the content events published from now on to a broker.
+///
+/// # Arguments
+///
+/// * `state` - The application state, whose content events are published.
+/// * `publisher` - The client of the broker.
+/// * `topic` - The Kafka topic or NATS subject events are published to.
+///
+/// # Returns
+///
+/// A `JoinHandle` of the task publishing events, which runs until the feed of content events is dropped.
+pub fn start(state: &AppState, publisher: Publisher, topic: String) -> JoinHandle<()> {
+    tokio::spawn(publish_events(state.content_events.subscribe(), publisher, topic))
+}
+
+async fn publish_events(mut events: Receiver<ContentEvent>, publisher: Publisher, topic: String) {
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(missed)) => {
+                warn!("Event publishing missed {} events, as the broker could not keep up.", missed);
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+
+        // One at a time, so the events of a resource reach the broker in the order they happened
+        if let Err(err) = publisher.publish(&topic, &event).await {
+            error!("Failed to publish the {} event to {}: {}", event.name(), topic, err);
+        }
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::models::AnswerId;
+
+    #[test]
+    fn should_publish_events_shaped_like_webhook_payloads() {
+        let event = ContentEvent::AnswerDeleted(AnswerId {
+            answer_uuid: Uuid::nil().into(),
+        });
+
+        let message = serde_json::to_value(Message {
+            occurred_at: DateTime::UNIX_EPOCH,
+            event: &event,
+        })
+        .unwrap();
+
+        assert_eq!(
+            message,
+            serde_json::json!({
+                "occurred_at": "1970-01-01T00:00:00Z",
+                "event": "answer.deleted",
+                "data": { "answer_uuid": "00000000-0000-0000-0000-000000000000" },
+            })
+        );
+    }
+
+    #[test]
+    fn should_key_events_by_their_resource() {
+        let answer_uuid = Uuid::from_u128(456);
+        let event = ContentEvent::AnswerDeleted(AnswerId {
+            answer_uuid: answer_uuid.into(),
+        });
+
+        assert_eq!(key(&event), answer_uuid);
+    }
+
+    #[tokio::test]
+    async fn should_refuse_unsupported_urls() {
+        let result = Publisher::connect("amqp://localhost:5672").await;
+
+        assert!(matches!(result, Err(EventsError::UnsupportedScheme(_))));
+    }
+}
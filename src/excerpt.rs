@@ -0,0 +1,290 @@
+use crate::models::QuestionDetail;
+
+// Excerpts are plain text previews of markdown, for list views to show without downloading whole descriptions. The
+// markdown is stripped with a few rules covering what people write in questions rather than a full parser: a stray
+// marker left in a preview is harmless, as excerpts are never rendered as markdown or HTML.
+
+/// Marks an excerpt cut short of the end of its text
+const ELLIPSIS: char = '…';
+
+/// Makes a plain text excerpt of markdown, cut on a word boundary.
+///
+/// # Arguments
+///
+/// * `markdown` - The text to excerpt, such as a question description.
+/// * `max_chars` - Most characters of the excerpt, the ellipsis marking a cut text included.
+///
+/// # Returns
+///
+/// The text without markdown syntax and with its whitespace collapsed, ending with `…` if it had to be cut.
+pub fn excerpt(markdown: &str, max_chars: usize) -> String {
+    let text = strip_markdown(markdown);
+
+    if text.chars().count() <= max_chars {
+        return text;
+    }
+
+    let kept: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    let cut_inside_word = text.chars().nth(kept.chars().count()).is_some_and(|next| !next.is_whitespace());
+
+    // A single word too long for the excerpt is cut anyway
+    let kept = match kept.rfind(char::is_whitespace) {
+        Some(boundary) if cut_inside_word => &kept[..boundary],
+        _ => kept.as_str(),
+    };
+
+    let mut excerpt = kept.trim_end().trim_end_matches([',', ';', ':']).to_owned();
+    excerpt.push(ELLIPSIS);
+    excerpt
+}
+
+/// Sets the excerpts of listed questions, from their descriptions.
+///
+/// # Arguments
+///
+/// * `questions` - The questions listed.
+/// * `max_chars` - Most characters of the excerpts, 0 to leave them out.
+pub fn set_excerpts<'a>(questions: impl IntoIterator<Item = &'a mut QuestionDetail>, max_chars: usize) {
+    if max_chars == 0 {
+        return;
+    }
+
+    for question in questions {
+        question.excerpt = Some(excerpt(&question.description, max_chars));
+    }
+}
+
+/// Converts markdown to plain text for reading as is, keeping its paragraphs, and the lines of its code blocks.
+///
+/// # Arguments
+///
+/// * `markdown` - The text to convert, such as a question description.
+///
+/// # Returns
+///
+/// The text without markdown syntax nor control characters, paragraphs separated by a blank line.
+pub fn plain_text(markdown: &str) -> String {
+    // Control characters are dropped, so text piped into a terminal cannot carry escape sequences
+    let markdown: String = markdown.chars().filter(|&c| !c.is_control() || c == '\n').collect();
+
+    let mut paragraphs = Vec::new();
+    let mut paragraph = String::new();
+    let mut in_code = false;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+        } else if in_code {
+            paragraph.push_str(line.trim_end());
+            paragraph.push('\n');
+            continue;
+        } else if !trimmed.is_empty() && !is_fence_or_rule(trimmed) {
+            let mut text = String::new();
+            strip_inline(strip_block_markers(trimmed), &mut text);
+
+            paragraph.push_str(&text.split_whitespace().collect::<Vec<_>>().join(" "));
+            paragraph.push('\n');
+            continue;
+        }
+
+        // Blank lines, fences and rules end the paragraph
+        if !paragraph.trim().is_empty() {
+            paragraphs.push(paragraph.trim_end().to_owned());
+        }
+        paragraph.clear();
+    }
+
+    if !paragraph.trim().is_empty() {
+        paragraphs.push(paragraph.trim_end().to_owned());
+    }
+
+    paragraphs.join("\n\n")
+}
+
+/// Strips markdown syntax from text, keeping the words, and collapses its whitespace.
+fn strip_markdown(markdown: &str) -> String {
+    let mut text = String::with_capacity(markdown.len());
+
+    for line in markdown.lines() {
+        let line = strip_block_markers(line.trim());
+        if is_fence_or_rule(line) {
+            continue;
+        }
+
+        strip_inline(line, &mut text);
+        text.push(' ');
+    }
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Whether a line only opens or closes a code block, or draws a horizontal rule.
+fn is_fence_or_rule(line: &str) -> bool {
+    if line.starts_with("```") || line.starts_with("~~~") {
+        return true;
+    }
+
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3 && ["-", "*", "_"].iter().any(|rule| compact.chars().all(|c| rule.starts_with(c)))
+}
+
+/// Strips the quote, heading and list markers starting a line.
+fn strip_block_markers(mut line: &str) -> &str {
+    loop {
+        let stripped = line
+            .strip_prefix('>')
+            .or_else(|| line.strip_prefix("- ").filter(|_| !is_fence_or_rule(line)))
+            .or_else(|| line.strip_prefix("* ").filter(|_| !is_fence_or_rule(line)))
+            .or_else(|| line.strip_prefix("+ "))
+            .or_else(|| strip_heading(line))
+            .or_else(|| strip_ordered_item(line));
+
+        match stripped {
+            Some(rest) => line = rest.trim_start(),
+            None => return line,
+        }
+    }
+}
+
+/// Strips a `#` to `######` heading marker.
+fn strip_heading(line: &str) -> Option<&str> {
+    let rest = line.trim_start_matches('#');
+    let level = line.len() - rest.len();
+
+    ((1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' '))).then_some(rest)
+}
+
+/// Strips the number of an ordered list item, such as `1.` or `2)`.
+fn strip_ordered_item(line: &str) -> Option<&str> {
+    let rest = line.trim_start_matches(|c: char| c.is_ascii_digit());
+    let digits = line.len() - rest.len();
+
+    if !(1..=9).contains(&digits) {
+        return None;
+    }
+    rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") "))
+}
+
+/// Appends the words of a line to `text`, without emphasis, code spans, escapes, link destinations or HTML tags.
+fn strip_inline(line: &str, text: &mut String) {
+    let chars: Vec<char> = line.chars().collect();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let prev = i.checked_sub(1).map(|p| chars[p]);
+        let next = chars.get(i + 1).copied();
+
+        match c {
+            '\\' if next.is_some_and(|n| n.is_ascii_punctuation()) => {
+                text.push(chars[i + 1]);
+                i += 2;
+                continue;
+            }
+            '`' => {}
+            // Underscores within words, as in `snake_case`, are not emphasis
+            '_' if prev.is_some_and(char::is_alphanumeric) && next.is_some_and(char::is_alphanumeric) => text.push(c),
+            // Nor are markers surrounded by spaces, as in `2 * 3`
+            '*' | '_' | '~' if prev.is_none_or(char::is_whitespace) && next.is_none_or(char::is_whitespace) => {
+                text.push(c)
+            }
+            '*' | '_' | '~' => {}
+            '!' if next == Some('[') => {}
+            '[' => {}
+            ']' if next == Some('(') => {
+                // The destination of a link or image is dropped, its text was kept
+                match chars[i..].iter().position(|&c| c == ')') {
+                    Some(end) => i += end,
+                    None => text.push(c),
+                }
+            }
+            ']' => {}
+            '<' => match chars[i..].iter().position(|&c| c == '>') {
+                Some(end) => {
+                    let inner: String = chars[i + 1..i + end].iter().collect();
+                    // Autolinks keep their URL, tags are dropped
+                    if inner.contains("://") || inner.contains('@') {
+                        text.push_str(&inner);
+                    }
+                    i += end;
+                }
+                None => text.push(c),
+            },
+            c => text.push(c),
+        }
+
+        i += 1;
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_keep_short_plain_text() {
+        assert_eq!(excerpt("How do I split a crate?", 100), "How do I split a crate?");
+    }
+
+    #[test]
+    fn should_strip_markdown() {
+        let markdown = "## Setup\n\n> I use **tokio** and `axum`, see [the docs](https://docs.rs/axum).\n\n\
+                        ```rust\nfn main() {}\n```\n\n- first_item\n1. second *item*\n\n---\n\n![logo](logo.png) \\*literal\\*";
+
+        assert_eq!(
+            excerpt(markdown, 1000),
+            "Setup I use tokio and axum, see the docs. fn main() {} first_item second item logo *literal*"
+        );
+    }
+
+    #[test]
+    fn should_convert_markdown_to_paragraphs() {
+        let markdown = "## Setup\n\nI use **tokio**\nand `axum`.\n\n```rust\nfn main() {\n    run();\n}\n```\n---\n- done";
+
+        assert_eq!(plain_text(markdown), "Setup\n\nI use tokio\nand axum.\n\nfn main() {\n    run();\n}\n\ndone");
+    }
+
+    #[test]
+    fn should_drop_control_characters_from_plain_text() {
+        assert_eq!(plain_text("\u{1b}[31mred\u{1b}[0m\r\nline\u{7}"), "31mred0m\nline");
+    }
+
+    #[test]
+    fn should_keep_autolinks_and_drop_tags() {
+        assert_eq!(excerpt("See <https://example.com> or <b>this</b>", 100), "See https://example.com or this");
+    }
+
+    #[test]
+    fn should_keep_markers_that_are_not_emphasis() {
+        assert_eq!(excerpt("2 * 3 = 6 with snake_case", 100), "2 * 3 = 6 with snake_case");
+    }
+
+    #[test]
+    fn should_cut_on_word_boundary() {
+        assert_eq!(excerpt("How do I split a crate into several?", 15), "How do I split…");
+        assert_eq!(excerpt("One, two, three", 10), "One, two…");
+    }
+
+    #[test]
+    fn should_set_excerpts_unless_disabled() {
+        let mut questions = vec![crate::test_support::QuestionBuilder::new().description("A **bold** claim").build_detail()];
+
+        set_excerpts(&mut questions, 0);
+        assert_eq!(questions[0].excerpt, None);
+
+        set_excerpts(&mut questions, 100);
+        assert_eq!(questions[0].excerpt.as_deref(), Some("A bold claim"));
+    }
+
+    #[test]
+    fn should_cut_long_words() {
+        assert_eq!(excerpt("Supercalifragilistic", 6), "Super…");
+        assert_eq!(excerpt("ééééé", 3), "éé…");
+    }
+}
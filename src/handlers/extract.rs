@@ -0,0 +1,249 @@
+use async_trait::async_trait;
+use axum::{
+    extract::{rejection::JsonRejection, FromRequest, FromRequestParts, Query, Request},
+    http::{request::Parts, StatusCode},
+    Json as JsonAxum,
+};
+use serde::de::DeserializeOwned;
+use validator::{Validate, ValidationErrors};
+
+use crate::models::{FieldError, InvalidRequest, ProfileQuery, RenderFormat, RenderQuery, ResponseProfile};
+
+use super::ApiResponse;
+
+/// JSON request body that was both deserialized and validated.
+///
+/// Rejects the request with an `InvalidRequest` body listing what is wrong with it: a `422 Unprocessable Entity`
+/// for bodies failing validation or not matching `T`, or the status of the `JsonRejection` for bodies that are not
+/// JSON at all.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = ApiResponse<InvalidRequest>;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let JsonAxum(value) = JsonAxum::<T>::from_request(request, state)
+            .await
+            .map_err(json_rejection)?;
+
+        value.validate().map_err(validation_errors)?;
+
+        Ok(ValidatedJson(value))
+    }
+}
+
+/// Reads the `profile` of the query string, leaving the other parameters to the handler.
+///
+/// Rejects the request with a `400 Bad Request` and an `InvalidRequest` body naming the field when the profile is
+/// unknown.
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ResponseProfile {
+    type Rejection = ApiResponse<InvalidRequest>;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<ProfileQuery>::try_from_uri(&parts.uri).map_err(|rejection| {
+            let error = FieldError {
+                field: Some("profile".to_owned()),
+                code: "invalid_profile".to_owned(),
+                message: rejection.body_text(),
+            };
+
+            invalid_request(StatusCode::BAD_REQUEST, vec![error])
+        })?;
+
+        Ok(query.profile.unwrap_or_default())
+    }
+}
+
+/// Reads the `render` format of the query string, leaving the other parameters to the handler.
+///
+/// Rejects the request with a `400 Bad Request` and an `InvalidRequest` body naming the field when the format is
+/// unknown.
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for RenderFormat {
+    type Rejection = ApiResponse<InvalidRequest>;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Query(query) = Query::<RenderQuery>::try_from_uri(&parts.uri).map_err(|rejection| {
+            let error = FieldError {
+                field: Some("render".to_owned()),
+                code: "invalid_render".to_owned(),
+                message: rejection.body_text(),
+            };
+
+            invalid_request(StatusCode::BAD_REQUEST, vec![error])
+        })?;
+
+        Ok(query.render.unwrap_or_default())
+    }
+}
+
+fn invalid_request(status: StatusCode, errors: Vec<FieldError>) -> ApiResponse<InvalidRequest> {
+    ApiResponse::ok(InvalidRequest { errors }).status(status)
+}
+
+/// Reports a body that could not be deserialized, which concerns the body as a whole.
+fn json_rejection(rejection: JsonRejection) -> ApiResponse<InvalidRequest> {
+    let code = match rejection {
+        JsonRejection::JsonDataError(_) => "invalid_data",
+        JsonRejection::JsonSyntaxError(_) => "invalid_json",
+        JsonRejection::MissingJsonContentType(_) => "missing_content_type",
+        _ => "invalid_body",
+    };
+
+    let error = FieldError {
+        field: None,
+        code: code.to_owned(),
+        // The message of data errors includes the path of the offending field, e.g. `question_uuid: UUID parsing failed`
+        message: rejection.body_text(),
+    };
+
+    invalid_request(rejection.status(), vec![error])
+}
+
+/// Reports every field failing validation, ordered by field.
+fn validation_errors(errors: ValidationErrors) -> ApiResponse<InvalidRequest> {
+    invalid_request(StatusCode::UNPROCESSABLE_ENTITY, field_errors(errors))
+}
+
+/// Lists every field failing validation, ordered by field, for the interfaces reporting them in their own way.
+pub(crate) fn field_errors(errors: ValidationErrors) -> Vec<FieldError> {
+    let mut field_errors: Vec<FieldError> = errors
+        .field_errors()
+        .into_iter()
+        .flat_map(|(field, errors)| {
+            errors.iter().map(move |error| FieldError {
+                field: Some(field.to_string()),
+                code: error.code.to_string(),
+                message: error.message.as_deref().unwrap_or(&error.code).to_owned(),
+            })
+        })
+        .collect();
+
+    field_errors.sort_by(|a, b| a.field.cmp(&b.field));
+
+    field_errors
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::{
+        body::Body,
+        http::header,
+        response::{IntoResponse, Response},
+    };
+    use serde_json::{json, Value};
+
+    use crate::models::Question;
+
+    fn request(content_type: &str, body: &str) -> Request {
+        Request::builder()
+            .method("POST")
+            .header(header::CONTENT_TYPE, content_type)
+            .body(Body::from(body.to_owned()))
+            .unwrap()
+    }
+
+    async fn extract(request: Request) -> Result<Question, Response> {
+        ValidatedJson::<Question>::from_request(request, &())
+            .await
+            .map(|ValidatedJson(question)| question)
+            .map_err(IntoResponse::into_response)
+    }
+
+    async fn errors(response: Response) -> (StatusCode, Value) {
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn should_extract_valid_body() {
+        let question = extract(request("application/json", r#"{"title": "Title", "description": ""}"#)).await.unwrap();
+
+        assert_eq!(question.title, "Title");
+    }
+
+    #[tokio::test]
+    async fn should_report_every_invalid_field() {
+        let body = json!({ "title": " \n ", "description": "x".repeat(256) }).to_string();
+
+        let (status, body) = errors(extract(request("application/json", &body)).await.unwrap_err()).await;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            body,
+            json!({ "errors": [
+                { "field": "description", "code": "length", "message": "must be at most 255 characters" },
+                { "field": "title", "code": "blank", "message": "must not be blank" },
+            ] })
+        );
+    }
+
+    #[tokio::test]
+    async fn should_report_bodies_not_matching_type() {
+        let (status, body) = errors(extract(request("application/json", r#"{"title": "Title"}"#)).await.unwrap_err()).await;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["errors"][0]["field"], Value::Null);
+        assert_eq!(body["errors"][0]["code"], "invalid_data");
+        assert!(body["errors"][0]["message"].as_str().unwrap().contains("description"));
+    }
+
+    async fn profile(uri: &str) -> Result<ResponseProfile, Response> {
+        let (mut parts, _) = Request::get(uri).body(Body::empty()).unwrap().into_parts();
+
+        ResponseProfile::from_request_parts(&mut parts, &()).await.map_err(IntoResponse::into_response)
+    }
+
+    #[tokio::test]
+    async fn should_extract_response_profile() {
+        assert_eq!(profile("/questions").await.unwrap(), ResponseProfile::Full);
+        assert_eq!(profile("/questions?status=open&profile=compact").await.unwrap(), ResponseProfile::Compact);
+
+        let (status, body) = errors(profile("/questions?profile=tiny").await.unwrap_err()).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["errors"][0]["field"], "profile");
+        assert_eq!(body["errors"][0]["code"], "invalid_profile");
+    }
+
+    async fn render(uri: &str) -> Result<RenderFormat, Response> {
+        let (mut parts, _) = Request::get(uri).body(Body::empty()).unwrap().into_parts();
+
+        RenderFormat::from_request_parts(&mut parts, &()).await.map_err(IntoResponse::into_response)
+    }
+
+    #[tokio::test]
+    async fn should_extract_render_format() {
+        assert_eq!(render("/questions").await.unwrap(), RenderFormat::Markdown);
+        assert_eq!(render("/questions?profile=compact&render=html").await.unwrap(), RenderFormat::Html);
+
+        let (status, body) = errors(render("/questions?render=pdf").await.unwrap_err()).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["errors"][0]["field"], "render");
+        assert_eq!(body["errors"][0]["code"], "invalid_render");
+    }
+
+    #[tokio::test]
+    async fn should_report_bodies_that_are_not_json() {
+        let (status, body) = errors(extract(request("text/plain", "Title")).await.unwrap_err()).await;
+
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(body["errors"][0]["code"], "missing_content_type");
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    http::GraphiQLSource,
+    ComplexObject, Context, EmptySubscription, Error, ErrorExtensions, Object, Result, Schema,
+};
+use axum::{extract::State as AxumState, response::Html, Json as JsonAxum};
+use validator::{Validate, ValidationErrors};
+
+use crate::{models::*, redact::redact, AppState};
+
+use super::{extract::field_errors, handlers_inner, ApiResponse, ApiResult, HandlerError};
+
+async_graphql::scalar!(QuestionUuid, "QuestionUuid", "Identifies a question");
+async_graphql::scalar!(AnswerUuid, "AnswerUuid", "Identifies an answer");
+
+/// GraphQL schema of the API, resolved with the same DAOs and handlers as the REST API.
+pub type ApiSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
+
+/// Builds the GraphQL schema.
+///
+/// # Arguments
+///
+/// * `state` - The application state whose DAOs the resolvers use.
+///
+/// # Returns
+///
+/// An `ApiSchema` ready to execute requests.
+pub fn schema(state: &AppState) -> ApiSchema {
+    // Batches the answers of every question in a response into one lookup
+    let answers = DataLoader::new(AnswersLoader { state: state.clone() }, tokio::spawn)
+        .max_batch_size(MAX_BATCH_QUESTIONS as usize);
+
+    Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        .data(state.clone())
+        .data(answers)
+        .finish()
+}
+
+/// Asynchronously executes a GraphQL request.
+///
+/// # Arguments
+///
+/// * `AxumState(schema)` - The GraphQL schema.
+/// * `JsonAxum(request)` - The JSON payload containing the query, its variables and operation name.
+///
+/// # Returns
+///
+/// An `ApiResult` containing a JSON response with the data and errors of the request, errors included with a
+/// `200 OK` status as GraphQL clients expect.
+pub async fn graphql(
+    AxumState(schema): AxumState<ApiSchema>,
+    JsonAxum(request): JsonAxum<async_graphql::Request>,
+) -> ApiResult<async_graphql::Response> {
+    Ok(ApiResponse::ok(schema.execute(request).await))
+}
+
+/// Serves GraphiQL, to explore the GraphQL schema and try queries from a browser.
+pub async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+/// Loads the answers of questions, so that listing questions with their answers takes one lookup, not one per question.
+pub struct AnswersLoader {
+    state: AppState,
+}
+
+impl Loader<QuestionUuid> for AnswersLoader {
+    type Value = Vec<AnswerDetail>;
+    type Error = Error;
+
+    async fn load(&self, keys: &[QuestionUuid]) -> Result<HashMap<QuestionUuid, Self::Value>, Self::Error> {
+        let batch = AnswersBatch {
+            question_uuids: keys.to_vec(),
+        };
+
+        handlers_inner::read_answers_batch(batch, self.state.answers_dao.as_ref())
+            .await
+            .map_err(handler_error)
+    }
+}
+
+#[ComplexObject]
+impl QuestionDetail {
+
+    /// Answers of the question, oldest first
+    async fn answers(&self, ctx: &Context<'_>) -> Result<Vec<AnswerDetail>> {
+        let answers = ctx
+            .data_unchecked::<DataLoader<AnswersLoader>>()
+            .load_one(self.question_uuid)
+            .await?;
+
+        Ok(answers.unwrap_or_default())
+    }
+}
+
+/// Read operations of the GraphQL API.
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+
+    /// Questions, oldest first, only those with `status` if given
+    async fn questions(&self, ctx: &Context<'_>, status: Option<QuestionStatus>) -> Result<Vec<QuestionDetail>> {
+        handlers_inner::read_questions(QuestionFilter { status, ..QuestionFilter::default() }, state(ctx).questions_dao.as_ref())
+            .await
+            .map_err(handler_error)
+    }
+
+    /// A question, or `null` if there is no such question
+    async fn question(&self, ctx: &Context<'_>, question_uuid: QuestionUuid) -> Result<Option<QuestionDetail>> {
+        let lookup = QuestionsLookup {
+            question_uuids: vec![question_uuid],
+        };
+
+        handlers_inner::lookup_questions(lookup, state(ctx).questions_dao.as_ref())
+            .await
+            .map(|questions| questions.into_iter().next())
+            .map_err(handler_error)
+    }
+
+    /// Answers of a question, oldest first
+    async fn answers(&self, ctx: &Context<'_>, question_uuid: QuestionUuid) -> Result<Vec<AnswerDetail>> {
+        let question_id = QuestionId { question_uuid };
+
+        handlers_inner::read_answers(question_id, AnswerSort::default(), state(ctx).answers_dao.as_ref())
+            .await
+            .map_err(handler_error)
+    }
+
+    /// Questions matching the words in `q`, most relevant first
+    async fn search(&self, ctx: &Context<'_>, q: String, limit: Option<i64>) -> Result<Vec<QuestionSearchResult>> {
+        let state = state(ctx);
+        let search = QuestionSearch { q, limit };
+        handlers_inner::search_questions(search, state.questions_dao.as_ref(), state.search_index.as_deref())
+            .await
+            .map_err(handler_error)
+    }
+}
+
+/// Write operations of the GraphQL API.
+pub struct MutationRoot;
+
+#[Object]
+impl MutationRoot {
+
+    /// Asks a question
+    async fn create_question(&self, ctx: &Context<'_>, title: String, description: String) -> Result<QuestionDetail> {
+        let question = validated(Question { question_uuid: None, title, description })?;
+
+        let state = state(ctx);
+
+        handlers_inner::create_question(question, &state.content_filters, state.questions_dao.as_ref(), state.flags_dao.as_ref())
+            .await
+            .map_err(handler_error)
+    }
+
+    /// Deletes a question along with its answers
+    async fn delete_question(&self, ctx: &Context<'_>, question_uuid: QuestionUuid) -> Result<bool> {
+        handlers_inner::delete_question(QuestionId { question_uuid }, state(ctx).questions_dao.as_ref())
+            .await
+            .map_err(handler_error)?;
+
+        Ok(true)
+    }
+
+    /// Answers an open question
+    async fn create_answer(
+        &self,
+        ctx: &Context<'_>,
+        question_uuid: QuestionUuid,
+        content: String,
+    ) -> Result<AnswerDetail> {
+        let answer = validated(Answer { answer_uuid: None, question_uuid, content })?;
+
+        let state = state(ctx);
+
+        handlers_inner::create_answer(answer, &state.content_filters, state.answers_dao.as_ref(), state.flags_dao.as_ref())
+            .await
+            .map_err(handler_error)
+    }
+
+    /// Deletes an answer
+    async fn delete_answer(&self, ctx: &Context<'_>, answer_uuid: AnswerUuid) -> Result<bool> {
+        handlers_inner::delete_answer(AnswerId { answer_uuid }, state(ctx).answers_dao.as_ref())
+            .await
+            .map_err(handler_error)?;
+
+        Ok(true)
+    }
+}
+
+fn state<'a>(ctx: &Context<'a>) -> &'a AppState {
+    ctx.data_unchecked::<AppState>()
+}
+
+/// Validates arguments the way `ValidatedJson` validates REST bodies.
+fn validated<T: Validate>(value: T) -> Result<T> {
+    value.validate().map_err(invalid_input)?;
+
+    Ok(value)
+}
+
+/// Reports every argument failing validation in a single error, ordered by argument.
+fn invalid_input(errors: ValidationErrors) -> Error {
+    let messages: Vec<String> = field_errors(errors)
+        .into_iter()
+        .map(|error| format!("{}: {}", error.field.unwrap_or_default(), error.message))
+        .collect();
+
+    Error::new(messages.join(", ")).extend_with(|_, extensions| extensions.set("code", "BAD_USER_INPUT"))
+}
+
+/// Converts a `HandlerError` into a GraphQL error, with the kind of error in its `code` extension.
+fn handler_error(error: HandlerError) -> Error {
+    let (code, message) = match error {
+        HandlerError::BadRequest(message) => ("BAD_REQUEST", message),
+        HandlerError::Conflict(message) => ("CONFLICT", message),
+        HandlerError::InternalError(message) => ("INTERNAL_SERVER_ERROR", message),
+    };
+
+    Error::new(redact(&message)).extend_with(|_, extensions| extensions.set("code", code))
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::{json, Value};
+
+    use crate::config::Config;
+
+    async fn execute(schema: &ApiSchema, query: &str) -> Value {
+        let response = schema.execute(query).await;
+
+        serde_json::to_value(response).unwrap()
+    }
+
+    #[tokio::test]
+    async fn should_create_and_read_questions_with_their_answers() {
+        let schema = schema(&AppState::in_memory(&Config::default()));
+
+        let mut question_uuids = Vec::new();
+        for title in ["First question", "Second question"] {
+            let query = format!(
+                r#"mutation {{ createQuestion(title: "{}", description: "") {{ questionUuid }} }}"#,
+                title
+            );
+            let created = execute(&schema, &query).await;
+            question_uuids.push(created["data"]["createQuestion"]["questionUuid"].as_str().unwrap().to_owned());
+        }
+
+        let query = format!(
+            r#"mutation {{ createAnswer(questionUuid: "{}", content: "An answer") {{ answerUuid }} }}"#,
+            question_uuids[1]
+        );
+        execute(&schema, &query).await;
+
+        let questions = execute(&schema, "{ questions { title status answerCount answers { content } } }").await;
+
+        assert_eq!(
+            questions,
+            json!({ "data": { "questions": [
+                { "title": "First question", "status": "OPEN", "answerCount": 0, "answers": [] },
+                {
+                    "title": "Second question",
+                    "status": "OPEN",
+                    "answerCount": 1,
+                    "answers": [{ "content": "An answer" }],
+                },
+            ] } })
+        );
+
+        let query = format!(r#"{{ question(questionUuid: "{}") {{ title }} }}"#, question_uuids[0]);
+        assert_eq!(execute(&schema, &query).await, json!({ "data": { "question": { "title": "First question" } } }));
+
+        let query = r#"{ question(questionUuid: "00000000-0000-0000-0000-000000000000") { title } }"#;
+        assert_eq!(execute(&schema, query).await, json!({ "data": { "question": null } }));
+    }
+
+    #[tokio::test]
+    async fn should_report_invalid_arguments() {
+        let schema = schema(&AppState::in_memory(&Config::default()));
+
+        let response = execute(&schema, r#"mutation { createQuestion(title: " ", description: "") { title } }"#).await;
+
+        assert_eq!(response["errors"][0]["message"], "title: must not be blank");
+        assert_eq!(response["errors"][0]["extensions"]["code"], "BAD_USER_INPUT");
+    }
+
+    #[tokio::test]
+    async fn should_report_handler_errors_with_their_code() {
+        let schema = schema(&AppState::in_memory(&Config::default()));
+
+        let query = r#"mutation {
+            createAnswer(questionUuid: "00000000-0000-0000-0000-000000000000", content: "Hi") { content }
+        }"#;
+        let response = execute(&schema, query).await;
+
+        assert_eq!(response["errors"][0]["extensions"]["code"], "BAD_REQUEST");
+    }
+}
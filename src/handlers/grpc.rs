@@ -0,0 +1,504 @@
+// `Status` is large, but it is the error every gRPC method returns, so helpers return it too
+#![allow(clippy::result_large_err)]
+
+use std::str::FromStr;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use tonic::{
+    transport::{server::Router, Server},
+    Request, Response, Status,
+};
+use validator::{Validate, ValidationErrors};
+
+use crate::{models::*, redact::redact, AppState};
+
+use super::{extract::field_errors, handlers_inner, HandlerError};
+
+use proto::{
+    answer_service_server::{AnswerService, AnswerServiceServer},
+    question_service_server::{QuestionService, QuestionServiceServer},
+};
+
+/// Messages and services of `proto/qna.proto`.
+///
+/// The messages are declared by hand, with the field numbers of the proto file, and the services are generated by
+/// `build.rs`, so building does not need protoc.
+pub mod proto {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+    #[repr(i32)]
+    pub enum QuestionStatus {
+        Unspecified = 0,
+        Open = 1,
+        Closed = 2,
+        Archived = 3,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Question {
+        #[prost(string, tag = "1")]
+        pub question_uuid: String,
+        #[prost(string, tag = "2")]
+        pub title: String,
+        #[prost(string, tag = "3")]
+        pub description: String,
+        #[prost(enumeration = "QuestionStatus", tag = "4")]
+        pub status: i32,
+        #[prost(string, tag = "5")]
+        pub created_at: String,
+        #[prost(int64, tag = "6")]
+        pub answer_count: i64,
+        #[prost(string, tag = "7")]
+        pub last_activity_at: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Answer {
+        #[prost(string, tag = "1")]
+        pub answer_uuid: String,
+        #[prost(string, tag = "2")]
+        pub question_uuid: String,
+        #[prost(string, tag = "3")]
+        pub content: String,
+        #[prost(string, tag = "4")]
+        pub created_at: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CreateQuestionRequest {
+        #[prost(string, tag = "1")]
+        pub title: String,
+        #[prost(string, tag = "2")]
+        pub description: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ListQuestionsRequest {
+        #[prost(enumeration = "QuestionStatus", tag = "1")]
+        pub status: i32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ListQuestionsResponse {
+        #[prost(message, repeated, tag = "1")]
+        pub questions: Vec<Question>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct LookupQuestionsRequest {
+        #[prost(string, repeated, tag = "1")]
+        pub question_uuids: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct DeleteQuestionRequest {
+        #[prost(string, tag = "1")]
+        pub question_uuid: String,
+    }
+
+    #[derive(Clone, Copy, PartialEq, prost::Message)]
+    pub struct DeleteQuestionResponse {}
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CreateAnswerRequest {
+        #[prost(string, tag = "1")]
+        pub question_uuid: String,
+        #[prost(string, tag = "2")]
+        pub content: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ListAnswersRequest {
+        #[prost(string, tag = "1")]
+        pub question_uuid: String,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ListAnswersResponse {
+        #[prost(message, repeated, tag = "1")]
+        pub answers: Vec<Answer>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct DeleteAnswerRequest {
+        #[prost(string, tag = "1")]
+        pub answer_uuid: String,
+    }
+
+    #[derive(Clone, Copy, PartialEq, prost::Message)]
+    pub struct DeleteAnswerResponse {}
+
+    include!(concat!(env!("OUT_DIR"), "/qna.v1.QuestionService.rs"));
+    include!(concat!(env!("OUT_DIR"), "/qna.v1.AnswerService.rs"));
+}
+
+/// Builds the gRPC server, serving the same DAOs and handlers as the REST API.
+///
+/// # Arguments
+///
+/// * `state` - The application state whose DAOs the services use.
+///
+/// # Returns
+///
+/// A `Router` serving the question and answer services, ready to be bound to a port.
+pub fn server(state: AppState) -> Router {
+    let api = GrpcApi { state };
+
+    Server::builder()
+        .add_service(QuestionServiceServer::new(api.clone()))
+        .add_service(AnswerServiceServer::new(api))
+}
+
+/// Implements the gRPC services on top of the inner handlers.
+#[derive(Clone)]
+pub struct GrpcApi {
+    state: AppState,
+}
+
+#[tonic::async_trait]
+impl QuestionService for GrpcApi {
+    async fn create_question(
+        &self,
+        request: Request<proto::CreateQuestionRequest>,
+    ) -> Result<Response<proto::Question>, Status> {
+        let proto::CreateQuestionRequest { title, description } = request.into_inner();
+        let question = validated(Question { question_uuid: None, title, description })?;
+
+        let state = &self.state;
+
+        handlers_inner::create_question(question, &state.content_filters, state.questions_dao.as_ref(), state.flags_dao.as_ref())
+            .await
+            .map(|question| Response::new(question.into()))
+            .map_err(handler_status)
+    }
+
+    async fn list_questions(
+        &self,
+        request: Request<proto::ListQuestionsRequest>,
+    ) -> Result<Response<proto::ListQuestionsResponse>, Status> {
+        let status = status_filter(request.into_inner().status)?;
+
+        handlers_inner::read_questions(QuestionFilter { status, ..QuestionFilter::default() }, self.state.questions_dao.as_ref())
+            .await
+            .map(questions_response)
+            .map_err(handler_status)
+    }
+
+    async fn lookup_questions(
+        &self,
+        request: Request<proto::LookupQuestionsRequest>,
+    ) -> Result<Response<proto::ListQuestionsResponse>, Status> {
+        let question_uuids = request
+            .into_inner()
+            .question_uuids
+            .iter()
+            .map(|question_uuid| parse_uuid("question_uuids", question_uuid))
+            .collect::<Result<_, _>>()?;
+
+        let lookup = validated(QuestionsLookup { question_uuids })?;
+
+        handlers_inner::lookup_questions(lookup, self.state.questions_dao.as_ref())
+            .await
+            .map(questions_response)
+            .map_err(handler_status)
+    }
+
+    async fn delete_question(
+        &self,
+        request: Request<proto::DeleteQuestionRequest>,
+    ) -> Result<Response<proto::DeleteQuestionResponse>, Status> {
+        let question_uuid = parse_uuid("question_uuid", &request.into_inner().question_uuid)?;
+
+        handlers_inner::delete_question(QuestionId { question_uuid }, self.state.questions_dao.as_ref())
+            .await
+            .map(|_| Response::new(proto::DeleteQuestionResponse {}))
+            .map_err(handler_status)
+    }
+}
+
+#[tonic::async_trait]
+impl AnswerService for GrpcApi {
+    async fn create_answer(
+        &self,
+        request: Request<proto::CreateAnswerRequest>,
+    ) -> Result<Response<proto::Answer>, Status> {
+        let proto::CreateAnswerRequest { question_uuid, content } = request.into_inner();
+        let question_uuid = parse_uuid("question_uuid", &question_uuid)?;
+        let answer = validated(Answer { answer_uuid: None, question_uuid, content })?;
+
+        let state = &self.state;
+
+        handlers_inner::create_answer(answer, &state.content_filters, state.answers_dao.as_ref(), state.flags_dao.as_ref())
+            .await
+            .map(|answer| Response::new(answer.into()))
+            .map_err(handler_status)
+    }
+
+    async fn list_answers(
+        &self,
+        request: Request<proto::ListAnswersRequest>,
+    ) -> Result<Response<proto::ListAnswersResponse>, Status> {
+        let question_uuid = parse_uuid("question_uuid", &request.into_inner().question_uuid)?;
+
+        let question_id = QuestionId { question_uuid };
+
+        handlers_inner::read_answers(question_id, AnswerSort::default(), self.state.answers_dao.as_ref())
+            .await
+            .map(|answers| {
+                Response::new(proto::ListAnswersResponse {
+                    answers: answers.into_iter().map(Into::into).collect(),
+                })
+            })
+            .map_err(handler_status)
+    }
+
+    async fn delete_answer(
+        &self,
+        request: Request<proto::DeleteAnswerRequest>,
+    ) -> Result<Response<proto::DeleteAnswerResponse>, Status> {
+        let answer_uuid = parse_uuid("answer_uuid", &request.into_inner().answer_uuid)?;
+
+        handlers_inner::delete_answer(AnswerId { answer_uuid }, self.state.answers_dao.as_ref())
+            .await
+            .map(|_| Response::new(proto::DeleteAnswerResponse {}))
+            .map_err(handler_status)
+    }
+}
+
+impl From<QuestionStatus> for proto::QuestionStatus {
+    fn from(status: QuestionStatus) -> Self {
+        match status {
+            QuestionStatus::Open => proto::QuestionStatus::Open,
+            QuestionStatus::Closed => proto::QuestionStatus::Closed,
+            QuestionStatus::Archived => proto::QuestionStatus::Archived,
+        }
+    }
+}
+
+impl From<QuestionDetail> for proto::Question {
+    fn from(question: QuestionDetail) -> Self {
+        proto::Question {
+            question_uuid: question.question_uuid.to_string(),
+            title: question.title,
+            description: question.description,
+            status: proto::QuestionStatus::from(question.status).into(),
+            created_at: timestamp(question.created_at),
+            answer_count: question.answer_count,
+            last_activity_at: timestamp(question.last_activity_at),
+        }
+    }
+}
+
+impl From<AnswerDetail> for proto::Answer {
+    fn from(answer: AnswerDetail) -> Self {
+        proto::Answer {
+            answer_uuid: answer.answer_uuid.to_string(),
+            question_uuid: answer.question_uuid.to_string(),
+            content: answer.content,
+            created_at: timestamp(answer.created_at),
+        }
+    }
+}
+
+/// Formats a timestamp the way the REST API serializes it.
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+fn questions_response(questions: Vec<QuestionDetail>) -> Response<proto::ListQuestionsResponse> {
+    Response::new(proto::ListQuestionsResponse {
+        questions: questions.into_iter().map(Into::into).collect(),
+    })
+}
+
+/// Reads the status questions are filtered by, all questions are listed when unspecified.
+fn status_filter(status: i32) -> Result<Option<QuestionStatus>, Status> {
+    match proto::QuestionStatus::try_from(status) {
+        Ok(proto::QuestionStatus::Unspecified) => Ok(None),
+        Ok(proto::QuestionStatus::Open) => Ok(Some(QuestionStatus::Open)),
+        Ok(proto::QuestionStatus::Closed) => Ok(Some(QuestionStatus::Closed)),
+        Ok(proto::QuestionStatus::Archived) => Ok(Some(QuestionStatus::Archived)),
+        Err(_) => Err(Status::invalid_argument(format!("status: unknown question status {}", status))),
+    }
+}
+
+/// Parses an identifier, which protobuf carries as a string.
+fn parse_uuid<T: FromStr>(field: &str, value: &str) -> Result<T, Status> {
+    value
+        .parse()
+        .map_err(|_| Status::invalid_argument(format!("{}: invalid UUID {:?}", field, value)))
+}
+
+/// Validates requests the way `ValidatedJson` validates REST bodies.
+fn validated<T: Validate>(value: T) -> Result<T, Status> {
+    value.validate().map_err(invalid_argument)?;
+
+    Ok(value)
+}
+
+/// Reports every field failing validation in a single status, ordered by field.
+fn invalid_argument(errors: ValidationErrors) -> Status {
+    let messages: Vec<String> = field_errors(errors)
+        .into_iter()
+        .map(|error| format!("{}: {}", error.field.unwrap_or_default(), error.message))
+        .collect();
+
+    Status::invalid_argument(messages.join(", "))
+}
+
+/// Converts a `HandlerError` into the closest gRPC status.
+fn handler_status(error: HandlerError) -> Status {
+    match error {
+        HandlerError::BadRequest(message) => Status::invalid_argument(redact(&message)),
+        HandlerError::Conflict(message) => Status::failed_precondition(redact(&message)),
+        HandlerError::InternalError(message) => Status::internal(redact(&message)),
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tonic::{transport::server::TcpIncoming, Code};
+
+    use crate::config::Config;
+
+    use proto::{answer_service_client::AnswerServiceClient, question_service_client::QuestionServiceClient};
+
+    fn api() -> GrpcApi {
+        GrpcApi {
+            state: AppState::in_memory(&Config::default()),
+        }
+    }
+
+    async fn create_question(api: &GrpcApi, title: &str) -> proto::Question {
+        let request = proto::CreateQuestionRequest {
+            title: title.to_owned(),
+            description: "Description".to_owned(),
+        };
+
+        QuestionService::create_question(api, Request::new(request)).await.unwrap().into_inner()
+    }
+
+    #[tokio::test]
+    async fn should_create_and_list_questions_and_answers() {
+        let api = api();
+
+        let question = create_question(&api, "  How do I   use gRPC? ").await;
+
+        assert_eq!(question.title, "How do I use gRPC?");
+        assert_eq!(question.status(), proto::QuestionStatus::Open);
+        assert!(DateTime::parse_from_rfc3339(&question.created_at).is_ok());
+
+        let request = proto::CreateAnswerRequest {
+            question_uuid: question.question_uuid.clone(),
+            content: "With tonic".to_owned(),
+        };
+        AnswerService::create_answer(&api, Request::new(request)).await.unwrap();
+
+        let request = proto::ListQuestionsRequest {
+            status: proto::QuestionStatus::Open.into(),
+        };
+        let questions = api.list_questions(Request::new(request)).await.unwrap().into_inner().questions;
+
+        assert_eq!(questions.len(), 1);
+        assert_eq!(questions[0].answer_count, 1);
+
+        let request = proto::ListAnswersRequest {
+            question_uuid: question.question_uuid,
+        };
+        let answers = api.list_answers(Request::new(request)).await.unwrap().into_inner().answers;
+
+        assert_eq!(answers.len(), 1);
+        assert_eq!(answers[0].content, "With tonic");
+    }
+
+    #[tokio::test]
+    async fn should_look_up_questions() {
+        let api = api();
+
+        let first = create_question(&api, "First question").await;
+        create_question(&api, "Second question").await;
+
+        let request = proto::LookupQuestionsRequest {
+            question_uuids: vec![first.question_uuid.clone(), QuestionUuid::new_v4().to_string()],
+        };
+        let questions = api.lookup_questions(Request::new(request)).await.unwrap().into_inner().questions;
+
+        assert_eq!(questions, vec![first]);
+
+        let request = proto::LookupQuestionsRequest { question_uuids: Vec::new() };
+        let status = api.lookup_questions(Request::new(request)).await.unwrap_err();
+
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(status.message(), "question_uuids: must have between 1 and 100 questions");
+    }
+
+    #[tokio::test]
+    async fn should_reject_invalid_requests() {
+        let api = api();
+
+        let request = proto::CreateQuestionRequest {
+            title: " ".to_owned(),
+            description: String::new(),
+        };
+        let status = QuestionService::create_question(&api, Request::new(request)).await.unwrap_err();
+
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(status.message(), "title: must not be blank");
+
+        let request = proto::DeleteQuestionRequest {
+            question_uuid: "not a UUID".to_owned(),
+        };
+        let status = api.delete_question(Request::new(request)).await.unwrap_err();
+
+        assert_eq!(status.code(), Code::InvalidArgument);
+
+        let request = proto::ListQuestionsRequest { status: 42 };
+        let status = api.list_questions(Request::new(request)).await.unwrap_err();
+
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn should_map_handler_errors_to_status_codes() {
+        let api = api();
+
+        let request = proto::CreateAnswerRequest {
+            question_uuid: QuestionUuid::new_v4().to_string(),
+            content: "Answer".to_owned(),
+        };
+        let status = AnswerService::create_answer(&api, Request::new(request)).await.unwrap_err();
+
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn should_serve_over_http2() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+
+        tokio::spawn(server(AppState::in_memory(&Config::default())).serve_with_incoming(incoming));
+
+        let url = format!("http://{}", address);
+        let mut questions = QuestionServiceClient::connect(url.clone()).await.unwrap();
+        let mut answers = AnswerServiceClient::connect(url).await.unwrap();
+
+        let request = proto::CreateQuestionRequest {
+            title: "Over the wire?".to_owned(),
+            description: String::new(),
+        };
+        let question = questions.create_question(request).await.unwrap().into_inner();
+
+        let request = proto::ListAnswersRequest {
+            question_uuid: question.question_uuid,
+        };
+        let response = answers.list_answers(request).await.unwrap().into_inner();
+
+        assert!(response.answers.is_empty());
+    }
+}
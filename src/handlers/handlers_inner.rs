@@ -1,12 +1,41 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::{Days, TimeDelta, Utc};
+use tokio::sync::broadcast::Receiver;
+
 use crate::{
-    models::{Answer, AnswerDetail, AnswerId, DBError, Question, QuestionDetail, QuestionId},
-    persistance::{answers_dao::AnswersDao, questions_dao::QuestionsDao},
+    content_filter::{ContentFilters, FilterAction},
+    health::{check_readiness, HealthCheck},
+    live,
+    models::{
+        Answer, AnswerDetail, AnswerId, AnswerSort, AnswerUpdates, AnswerUpdatesQuery, AnswerUuid, AnswersBatch,
+        AnswersByQuestion, ContentStats, DBError, Flag, FlagDecision, FlagDetail, FlagResolution, FlagUuid,
+        HealthStatus, HeldContent, Incident, IncidentDetail, IncidentId, JobDetail, JobKind, JobUuid, MaintenanceReport,
+        PopularQuestions, Question, QuestionDetail, QuestionFilter, QuestionId, QuestionSearch, QuestionSearchResult,
+        QuestionStatus, QuestionUuid, QuestionWebhook, QuestionWebhookDetail, QuestionWithAnswers, QuestionsLookup,
+        ServiceStatus, StatusReport, SyncRequest, SyncResponse, SyncedQuestion, Webhook, WebhookDetail, WebhookId,
+        DEFAULT_QUESTION_WEBHOOK_TTL_HOURS,
+    },
+    normalize::{normalize_title, Normalize},
+    persistance::{
+        answers_dao::AnswersDao, flags_dao::FlagsDao, incidents_dao::IncidentsDao, jobs_dao::JobsDao,
+        maintenance_dao::MaintenanceDao, questions_dao::QuestionsDao, stats_dao::StatsDao, webhooks_dao::WebhooksDao,
+    },
+    reading_time,
+    search_index::{self, SearchIndex},
 };
 
+use super::response::entity_tag;
+
 /// Represents errors that can occur within request handlers.
 #[derive(Debug, PartialEq)]
 pub enum HandlerError {
     BadRequest(String),
+    Conflict(String),
     InternalError(String),
 }
 
@@ -24,36 +53,134 @@ impl HandlerError {
     }
 }
 
+/// Asynchronously creates a question using the provided `QuestionsDao`, once it went through the content filters.
+///
+/// A question a filter holds is not created: it is stored on its flag, for a moderator to publish.
+///
+/// # Arguments
+///
+/// * `question` - The question to be created.
+/// * `content_filters` - The filters the title and description go through.
+/// * `questions_dao` - A reference to an object implementing the `QuestionsDao` trait along with `Sync` and `Send` traits.
+/// * `flags_dao` - A reference to an object implementing the `FlagsDao` trait, holding and tagging filtered questions.
+///
+/// # Returns
+///
+/// A `Result` containing the created question detail, or the held question as it will be created, on success, or a
+/// `HandlerError` on failure. The error is `HandlerError::BadRequest` if a filter rejects the question.
 pub async fn create_question(
     question: Question,
+    content_filters: &ContentFilters,
     // Using a trait object here so that inner handlers do not depend on concrete DAO implementations
     questions_dao: &(dyn QuestionsDao + Sync + Send),
+    flags_dao: &(dyn FlagsDao + Send + Sync),
 ) -> Result<QuestionDetail, HandlerError> {
-
-    let question = questions_dao.create_question(question).await;
-
-    match question {
-        Ok(question) => Ok(question),
+    let question = question.normalize();
+    let tag = match content_filters.check(&format!("{}\n{}", question.title, question.description)) {
+        Some((FilterAction::Reject, flag)) => return Err(HandlerError::BadRequest(filter_rejection("Question", &flag))),
+        Some((FilterAction::Hold, flag)) => return hold_question(question, flag, flags_dao).await,
+        Some((FilterAction::Tag, flag)) => Some(flag),
+        None => None,
+    };
+
+    let question = match questions_dao.create_question(question).await {
+        Ok(question) => question,
+        // The client chose a UUID that is already taken
+        Err(DBError::Conflict(s)) => return Err(HandlerError::Conflict(s)),
         Err(err) => {
             error!("{:?}", err);
-            Err(HandlerError::default_internal_error())
+            return Err(HandlerError::default_internal_error());
+        }
+    };
+
+    if let Some(flag) = tag {
+        warn!("Question {} tagged by a content filter: {:?}", question.question_uuid, flag.comment);
+        // The question is created either way, so failing to tag it is no reason to fail the request
+        if let Err(err) = flags_dao.tag_question(question.question_uuid, flag).await {
+            error!("Failed to tag question {}: {:?}", question.question_uuid, err);
         }
     }
+
+    Ok(question)
+}
+
+/// Holds a question for moderation instead of creating it, so that neither reads nor events show it until a
+/// moderator publishes it.
+///
+/// # Returns
+///
+/// The question as it will be created, or a `HandlerError` if it could not be held.
+async fn hold_question(
+    mut question: Question,
+    flag: Flag,
+    flags_dao: &(dyn FlagsDao + Send + Sync),
+) -> Result<QuestionDetail, HandlerError> {
+    let question_uuid = *question.question_uuid.get_or_insert_with(QuestionUuid::new_v4);
+
+    let held = match flags_dao.hold_question(question.clone(), flag).await {
+        Ok(held) => held,
+        Err(err) => {
+            error!("Failed to hold question {} for moderation: {:?}", question_uuid, err);
+            return Err(HandlerError::default_internal_error());
+        }
+    };
+
+    let word_count = question.word_count();
+    Ok(QuestionDetail {
+        question_uuid,
+        title: question.title,
+        description: question.description,
+        status: QuestionStatus::Open,
+        created_at: held.created_at,
+        answer_count: 0,
+        last_activity_at: held.created_at,
+        view_count: 0,
+        word_count,
+        reading_time_seconds: reading_time::reading_time_seconds(word_count),
+        excerpt: None,
+    })
 }
 
 /// Asynchronously retrieves all questions using the provided `QuestionsDao`.
 ///
 /// # Arguments
 ///
+/// * `filter` - The conditions the questions must meet, such as their status, and their order.
 /// * `questions_dao` - A reference to an object implementing the `QuestionsDao` trait along with `Sync` and `Send` traits.
 ///
 /// # Returns
 ///
 /// A `Result` containing a vector of question details on success, or a `HandlerError` on failure.
 pub async fn read_questions(
+    filter: QuestionFilter,
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+) -> Result<Vec<QuestionDetail>, HandlerError> {
+    let questions = questions_dao.get_questions(&filter).await;
+
+    match questions {
+        Ok(questions) => Ok(questions),
+        Err(err) => {
+            error!("{:?}", err);
+            Err(HandlerError::default_internal_error())
+        }
+    }
+}
+
+/// Asynchronously retrieves several questions at once using the provided `QuestionsDao`.
+///
+/// # Arguments
+///
+/// * `lookup` - The unique identifiers of the questions to be retrieved.
+/// * `questions_dao` - A reference to an object implementing the `QuestionsDao` trait along with `Sync` and `Send` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the details of the questions that exist on success, or a `HandlerError` on failure.
+pub async fn lookup_questions(
+    lookup: QuestionsLookup,
     questions_dao: &(dyn QuestionsDao + Sync + Send),
 ) -> Result<Vec<QuestionDetail>, HandlerError> {
-    let questions = questions_dao.get_questions().await;
+    let questions = questions_dao.get_questions_by_uuids(lookup.question_uuids).await;
 
     match questions {
         Ok(questions) => Ok(questions),
@@ -64,6 +191,181 @@ pub async fn read_questions(
     }
 }
 
+/// How far before it reads questions a sync looks back for the next one, longer than a question takes to commit
+const SYNC_LOOKBACK: TimeDelta = TimeDelta::seconds(60);
+
+/// Asynchronously brings the questions a client holds up to date, and finds those created since its previous sync,
+/// using the provided `QuestionsDao` and `AnswersDao`.
+///
+/// The version of a question is the `ETag` of the question with its answers, as read in full, so the tag of a
+/// question read before can be synced too. Like the tag, it leaves out the view count, which reads alone change.
+///
+/// # Arguments
+///
+/// * `request` - The questions held, with their versions, and the time of the previous sync.
+/// * `questions_dao` - A reference to an object implementing the `QuestionsDao` trait along with `Sync` and `Send` traits.
+/// * `answers_dao` - A reference to an object implementing the `AnswersDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the questions changed, created and deleted on success, or a `HandlerError` on failure.
+pub async fn sync_questions(
+    request: SyncRequest,
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+    answers_dao: &(dyn AnswersDao + Send + Sync),
+) -> Result<SyncResponse, HandlerError> {
+    let internal_error = |err: &dyn std::fmt::Debug| {
+        error!("{:?}", err);
+        HandlerError::default_internal_error()
+    };
+
+    // Taken before reading, from the clock questions are stamped with. A question is stamped before it is committed,
+    // possibly before this time but too late to be read here, so the next sync looks back a little further.
+    let synced_at = questions_dao.current_time().await.map_err(|err| internal_error(&err))? - SYNC_LOOKBACK;
+    let held: HashMap<QuestionUuid, String> =
+        request.questions.into_iter().map(|cached| (cached.question_uuid, cached.version)).collect();
+
+    let existing = questions_dao
+        .get_questions_by_uuids(held.keys().copied().collect())
+        .await
+        .map_err(|err| internal_error(&err))?;
+
+    let created = match request.since {
+        Some(since) => {
+            let filter = QuestionFilter { created_after: Some(since), ..QuestionFilter::default() };
+            questions_dao.get_questions(&filter).await.map_err(|err| internal_error(&err))?
+        }
+        None => Vec::new(),
+    };
+    let created: Vec<_> = created.into_iter().filter(|question| !held.contains_key(&question.question_uuid)).collect();
+
+    let question_uuids = existing.iter().chain(&created).map(|question| question.question_uuid).collect();
+    let mut answers = answers_dao.get_answers_for_questions(question_uuids).await.map_err(|err| internal_error(&err))?;
+
+    let mut synced = |question: QuestionDetail| {
+        let answers = answers.remove(&question.question_uuid).unwrap_or_default();
+        let question = QuestionWithAnswers { question, answers };
+
+        serde_json::to_vec(&question)
+            .map(|json| SyncedQuestion { question, version: entity_tag(&json) })
+            .map_err(|err| internal_error(&err))
+    };
+
+    let existing_uuids: HashSet<_> = existing.iter().map(|question| question.question_uuid).collect();
+    let mut deleted: Vec<_> = held.keys().filter(|uuid| !existing_uuids.contains(uuid)).copied().collect();
+    deleted.sort();
+
+    let mut changed = Vec::new();
+    for question in existing {
+        let question_uuid = question.question_uuid;
+        let question = synced(question)?;
+        if held.get(&question_uuid) != Some(&question.version) {
+            changed.push(question);
+        }
+    }
+
+    let created = created.into_iter().map(&mut synced).collect::<Result<_, _>>()?;
+
+    Ok(SyncResponse { changed, created, deleted, synced_at })
+}
+
+/// Asynchronously retrieves a question along with all its answers using the provided `QuestionsDao`.
+///
+/// # Arguments
+///
+/// * `question_id` - The unique identifier of the question.
+/// * `questions_dao` - A reference to an object implementing the `QuestionsDao` trait along with `Sync` and `Send` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the question and its answers on success, or a `HandlerError` on failure.
+pub async fn read_question_with_answers(
+    question_id: QuestionId,
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+) -> Result<QuestionWithAnswers, HandlerError> {
+    let question = questions_dao.get_question_with_answers(question_id.question_uuid).await;
+
+    match question {
+        Ok(question) => Ok(question),
+        Err(err) => {
+            error!("{:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+                _ => Err(HandlerError::default_internal_error()),
+            }
+        }
+    }
+}
+
+/// Number of popular questions returned when the request does not ask for a number
+const DEFAULT_POPULAR_LIMIT: i64 = 20;
+
+/// Largest number of popular questions a request can ask for
+const MAX_POPULAR_LIMIT: i64 = 100;
+
+/// Asynchronously retrieves the most viewed questions using the provided `QuestionsDao`.
+///
+/// # Arguments
+///
+/// * `popular` - The maximum number of questions, capped at `MAX_POPULAR_LIMIT`.
+/// * `questions_dao` - A reference to an object implementing the `QuestionsDao` trait along with `Sync` and `Send` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the details of the questions, most viewed first, on success, or a `HandlerError` on failure.
+pub async fn read_popular_questions(
+    popular: PopularQuestions,
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+) -> Result<Vec<QuestionDetail>, HandlerError> {
+    let limit = popular.limit.unwrap_or(DEFAULT_POPULAR_LIMIT).clamp(1, MAX_POPULAR_LIMIT);
+
+    questions_dao.get_popular_questions(limit).await.map_err(|err| {
+        error!("{:?}", err);
+        HandlerError::default_internal_error()
+    })
+}
+
+/// Number of search results returned when the request does not ask for a number
+const DEFAULT_SEARCH_LIMIT: i64 = 20;
+
+/// Largest number of search results a request can ask for
+const MAX_SEARCH_LIMIT: i64 = 100;
+
+/// Asynchronously searches questions using the provided `SearchIndex`, or the `QuestionsDao` without one.
+///
+/// # Arguments
+///
+/// * `search` - The search query and the maximum number of results, capped at `MAX_SEARCH_LIMIT`.
+/// * `questions_dao` - A reference to an object implementing the `QuestionsDao` trait along with `Sync` and `Send` traits.
+/// * `search_index` - The index searched instead of the database, if one is configured.
+///
+/// # Returns
+///
+/// A `Result` containing the matching questions, best match first, on success, or a `HandlerError` on failure.
+pub async fn search_questions(
+    search: QuestionSearch,
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+    search_index: Option<&(dyn SearchIndex + Sync + Send)>,
+) -> Result<Vec<QuestionSearchResult>, HandlerError> {
+    let query = normalize_title(&search.q);
+
+    if query.is_empty() {
+        return Err(HandlerError::BadRequest("Search query must not be empty".to_owned()));
+    }
+
+    let limit = search.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT);
+
+    let results = match search_index {
+        Some(search_index) => search_index::search_questions(search_index, questions_dao, &query, limit)
+            .await
+            .map_err(|err| error!("{}", err)),
+        None => questions_dao.search_questions(query, limit).await.map_err(|err| error!("{:?}", err)),
+    };
+
+    results.map_err(|_| HandlerError::default_internal_error())
+}
+
 /// Asynchronously deletes a question identified by the given `QuestionId` using the provided `QuestionsDao`.
 ///
 /// # Arguments
@@ -87,33 +389,153 @@ pub async fn delete_question(
     Ok(())
 }
 
-/// Asynchronously creates an answer using the provided `AnswersDao`.
+/// Asynchronously closes an open question using the provided `QuestionsDao`.
+///
+/// # Arguments
+///
+/// * `question_id` - The unique identifier of the question to be closed.
+/// * `questions_dao` - A reference to an object implementing the `QuestionsDao` trait along with `Sync` and `Send` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the closed question detail on success, or a `HandlerError` on failure.
+pub async fn close_question(
+    question_id: QuestionId,
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+) -> Result<QuestionDetail, HandlerError> {
+    update_question_status(question_id, QuestionStatus::Open, QuestionStatus::Closed, questions_dao).await
+}
+
+/// Asynchronously reopens a closed question using the provided `QuestionsDao`.
+///
+/// # Arguments
+///
+/// * `question_id` - The unique identifier of the question to be reopened.
+/// * `questions_dao` - A reference to an object implementing the `QuestionsDao` trait along with `Sync` and `Send` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the reopened question detail on success, or a `HandlerError` on failure.
+pub async fn reopen_question(
+    question_id: QuestionId,
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+) -> Result<QuestionDetail, HandlerError> {
+    update_question_status(question_id, QuestionStatus::Closed, QuestionStatus::Open, questions_dao).await
+}
+
+async fn update_question_status(
+    question_id: QuestionId,
+    from: QuestionStatus,
+    to: QuestionStatus,
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+) -> Result<QuestionDetail, HandlerError> {
+    let question = questions_dao.update_question_status(question_id.question_uuid, from, to).await;
+
+    match question {
+        Ok(question) => Ok(question),
+        Err(err) => {
+            error!("{:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+                DBError::Conflict(s) => Err(HandlerError::Conflict(s)),
+                _ => Err(HandlerError::default_internal_error()),
+            }
+        }
+    }
+}
+
+/// Asynchronously creates an answer using the provided `AnswersDao`, once it went through the content filters.
+///
+/// An answer a filter holds is not created: it is stored on its flag, for a moderator to publish.
 ///
 /// # Arguments
 ///
 /// * `answer` - The answer to be created.
+/// * `content_filters` - The filters the content goes through.
 /// * `answers_dao` - A reference to an object implementing the `AnswersDao` trait along with `Send` and `Sync` traits.
+/// * `flags_dao` - A reference to an object implementing the `FlagsDao` trait, holding and tagging filtered answers.
 ///
 /// # Returns
 ///
-/// A `Result` containing the created answer detail on success, or a `HandlerError` on failure.
+/// A `Result` containing the created answer detail, or the held answer as it will be created, on success, or a
+/// `HandlerError` on failure. The error is `HandlerError::BadRequest` if a filter rejects the answer.
 pub async fn create_answer(
     answer: Answer,
+    content_filters: &ContentFilters,
     answers_dao: &(dyn AnswersDao + Send + Sync),
+    flags_dao: &(dyn FlagsDao + Send + Sync),
 ) -> Result<AnswerDetail, HandlerError> {
-    let answer = answers_dao.create_answer(answer).await;
-
-    match answer {
-        Ok(answer) => Ok(answer), // return answer
+    let answer = answer.normalize();
+    let tag = match content_filters.check(&answer.content) {
+        Some((FilterAction::Reject, flag)) => return Err(HandlerError::BadRequest(filter_rejection("Answer", &flag))),
+        Some((FilterAction::Hold, flag)) => return hold_answer(answer, flag, flags_dao).await,
+        Some((FilterAction::Tag, flag)) => Some(flag),
+        None => None,
+    };
+
+    let answer = match answers_dao.create_answer(answer).await {
+        Ok(answer) => answer,
         Err(err) => {
             error!("{:?}", err);
 
-            match err {
+            return match err {
                 DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+                // Answering a closed or archived question
+                DBError::Conflict(s) => Err(HandlerError::Conflict(s)),
                 _ => Err(HandlerError::default_internal_error()),
-            }
+            };
+        }
+    };
+
+    if let Some(flag) = tag {
+        warn!("Answer {} tagged by a content filter: {:?}", answer.answer_uuid, flag.comment);
+        // The answer is created either way, so failing to tag it is no reason to fail the request
+        if let Err(err) = flags_dao.tag_answer(answer.answer_uuid, flag).await {
+            error!("Failed to tag answer {}: {:?}", answer.answer_uuid, err);
         }
     }
+
+    Ok(answer)
+}
+
+/// Holds an answer for moderation instead of creating it, so that neither reads nor events show it until a
+/// moderator publishes it.
+///
+/// # Returns
+///
+/// The answer as it will be created, or a `HandlerError` if it could not be held. The error is
+/// `HandlerError::BadRequest` if the question answered does not exist.
+async fn hold_answer(
+    mut answer: Answer,
+    flag: Flag,
+    flags_dao: &(dyn FlagsDao + Send + Sync),
+) -> Result<AnswerDetail, HandlerError> {
+    let answer_uuid = *answer.answer_uuid.get_or_insert_with(AnswerUuid::new_v4);
+
+    let held = match flags_dao.hold_answer(answer.clone(), flag).await {
+        Ok(held) => held,
+        Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+        Err(err) => {
+            error!("Failed to hold answer {} for moderation: {:?}", answer_uuid, err);
+            return Err(HandlerError::default_internal_error());
+        }
+    };
+
+    let word_count = answer.word_count();
+    Ok(AnswerDetail {
+        answer_uuid,
+        question_uuid: answer.question_uuid,
+        content: answer.content,
+        created_at: held.created_at,
+        word_count,
+        reading_time_seconds: reading_time::reading_time_seconds(word_count),
+    })
+}
+
+/// The message of the error returned for content a filter rejects.
+fn filter_rejection(kind: &str, flag: &Flag) -> String {
+    format!("{} rejected by a content filter: {}", kind, flag.comment.as_deref().unwrap_or(flag.reason.as_str()))
 }
 
 /// Asynchronously retrieves answers associated with the given question ID using the provided `AnswersDao`.
@@ -121,6 +543,7 @@ pub async fn create_answer(
 /// # Arguments
 ///
 /// * `question_id` - The unique identifier of the question whose answers are to be retrieved.
+/// * `sort` - The order of the answers.
 /// * `answers_dao` - A reference to an object implementing the `AnswersDao` trait along with `Send` and `Sync` traits.
 ///
 /// # Returns
@@ -128,9 +551,35 @@ pub async fn create_answer(
 /// A `Result` containing a vector of answer details on success, or a `HandlerError` on failure.
 pub async fn read_answers(
     question_id: QuestionId,
+    sort: AnswerSort,
     answers_dao: &(dyn AnswersDao + Send + Sync),
 ) -> Result<Vec<AnswerDetail>, HandlerError> {
-    let answers = answers_dao.get_answers(question_id.question_uuid).await;
+    let answers = answers_dao.get_answers(question_id.question_uuid, sort).await;
+
+    match answers {
+        Ok(answers) => Ok(answers),
+        Err(e) => {
+            error!("{:?}", e);
+            Err(HandlerError::default_internal_error())
+        }
+    }
+}
+
+/// Asynchronously retrieves the answers of several questions at once using the provided `AnswersDao`.
+///
+/// # Arguments
+///
+/// * `batch` - The unique identifiers of the questions whose answers are to be retrieved.
+/// * `answers_dao` - A reference to an object implementing the `AnswersDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the answers keyed by question on success, or a `HandlerError` on failure.
+pub async fn read_answers_batch(
+    batch: AnswersBatch,
+    answers_dao: &(dyn AnswersDao + Send + Sync),
+) -> Result<AnswersByQuestion, HandlerError> {
+    let answers = answers_dao.get_answers_for_questions(batch.question_uuids).await;
 
     match answers {
         Ok(answers) => Ok(answers),
@@ -141,6 +590,59 @@ pub async fn read_answers(
     }
 }
 
+/// Time a long poll is held for when the client does not say
+const DEFAULT_POLL_WAIT: Duration = Duration::from_secs(30);
+
+/// Longest time a long poll is held for, short of the idle timeouts of most proxies
+const MAX_POLL_WAIT: Duration = Duration::from_secs(60);
+
+/// Asynchronously long polls for the answers created for a question after a cursor, returning those already in the
+/// database at once, or else the first ones published to the feed of answers before the wait expires.
+///
+/// # Arguments
+///
+/// * `question_uuid` - The unique identifier of the question whose answers are polled for.
+/// * `query` - The cursor of the previous poll, if any, and the longest time to wait, capped at `MAX_POLL_WAIT`.
+/// * `answers_dao` - A reference to an object implementing the `AnswersDao` trait along with `Send` and `Sync` traits.
+/// * `feed` - The subscription to the answers created, taken before the database is read so none is missed between.
+///
+/// # Returns
+///
+/// A `Result` containing the answers and the cursor to poll for the next ones on success, or a `HandlerError` on
+/// failure.
+pub async fn poll_answers(
+    question_uuid: QuestionUuid,
+    query: AnswerUpdatesQuery,
+    answers_dao: &(dyn AnswersDao + Send + Sync),
+    feed: &mut Receiver<AnswerDetail>,
+) -> Result<AnswerUpdates, HandlerError> {
+    let wait = match query.wait.as_deref() {
+        Some(wait) => parse_wait(wait)
+            .ok_or_else(|| HandlerError::BadRequest(format!("Invalid wait: {}, expected seconds such as 30s", wait)))?,
+        None => DEFAULT_POLL_WAIT,
+    };
+
+    let answers = answers_dao.get_answers(question_uuid, AnswerSort::Oldest).await.map_err(|e| {
+        error!("{:?}", e);
+        HandlerError::default_internal_error()
+    })?;
+
+    let mut answers: Vec<_> =
+        answers.into_iter().filter(|answer| query.since.is_none_or(|since| answer.created_at > since)).collect();
+    if answers.is_empty() {
+        answers = live::next_answers(feed, question_uuid, query.since, wait.min(MAX_POLL_WAIT)).await;
+    }
+
+    let cursor = answers.last().map(|answer| answer.created_at).or(query.since);
+
+    Ok(AnswerUpdates { answers, cursor })
+}
+
+/// Reads a wait in whole seconds, with or without an `s` suffix.
+fn parse_wait(wait: &str) -> Option<Duration> {
+    wait.strip_suffix('s').unwrap_or(wait).parse().ok().map(Duration::from_secs)
+}
+
 /// Asynchronously deletes an answer identified by the given `AnswerId` using the provided `AnswersDao`.
 ///
 /// # Arguments
@@ -164,33 +666,600 @@ pub async fn delete_answer(
     Ok(())
 }
 
-// ***********************************************************
-//                           Tests
-// ***********************************************************
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    use async_trait::async_trait;
-    use tokio::sync::Mutex;
-
-    struct QuestionsDaoMock {
-        create_question_response: Mutex<Option<Result<QuestionDetail, DBError>>>,
-        delete_question_response: Mutex<Option<Result<(), DBError>>>,
-        get_questions_response: Mutex<Option<Result<Vec<QuestionDetail>, DBError>>>,
+/// Asynchronously builds the status page from the dependency checks and the active incidents.
+///
+/// The status page has to stay up when the database is down, so failing to load incidents is
+/// logged and reported through the database check rather than as an error.
+///
+/// # Arguments
+///
+/// * `uptime` - How long the server has been running.
+/// * `health_checks` - The dependency checks to run.
+/// * `incidents_dao` - A reference to an object implementing the `IncidentsDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `StatusReport`, degraded if any dependency is down or any incident is unresolved.
+pub async fn read_status(
+    uptime: Duration,
+    health_checks: &[Arc<dyn HealthCheck + Send + Sync>],
+    incidents_dao: &(dyn IncidentsDao + Send + Sync),
+) -> StatusReport {
+    let (readiness, incidents) = tokio::join!(check_readiness(health_checks), incidents_dao.get_active_incidents());
+
+    let incidents = incidents.unwrap_or_else(|err| {
+        error!("{:?}", err);
+        Vec::new()
+    });
+
+    let degradations: Vec<_> = readiness
+        .checks
+        .into_iter()
+        .filter(|check| check.status == HealthStatus::Down)
+        .collect();
+
+    let status = if degradations.is_empty() && incidents.is_empty() {
+        ServiceStatus::Operational
+    } else {
+        ServiceStatus::Degraded
+    };
+
+    StatusReport {
+        status,
+        uptime_secs: uptime.as_secs(),
+        degradations,
+        incidents,
     }
+}
 
-    impl QuestionsDaoMock {
-        pub fn new() -> Self {
-            QuestionsDaoMock {
-                create_question_response: Mutex::new(None),
-                delete_question_response: Mutex::new(None),
-                get_questions_response: Mutex::new(None),
-            }
+/// Asynchronously creates an incident using the provided `IncidentsDao`.
+///
+/// # Arguments
+///
+/// * `incident` - The incident to be created.
+/// * `incidents_dao` - A reference to an object implementing the `IncidentsDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the created incident detail on success, or a `HandlerError` on failure.
+pub async fn create_incident(
+    incident: Incident,
+    incidents_dao: &(dyn IncidentsDao + Send + Sync),
+) -> Result<IncidentDetail, HandlerError> {
+    let incident = incidents_dao.create_incident(incident.normalize()).await;
+
+    match incident {
+        Ok(incident) => Ok(incident),
+        Err(err) => {
+            error!("{:?}", err);
+            Err(HandlerError::default_internal_error())
         }
-        pub fn mock_create_question(&mut self, response: Result<QuestionDetail, DBError>) {
-            self.create_question_response = Mutex::new(Some(response));
+    }
+}
+
+/// Asynchronously resolves the incident identified by the given `IncidentId` using the provided `IncidentsDao`.
+///
+/// # Arguments
+///
+/// * `incident_id` - The unique identifier of the incident to be resolved.
+/// * `incidents_dao` - A reference to an object implementing the `IncidentsDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `HandlerError` is returned.
+pub async fn resolve_incident(
+    incident_id: IncidentId,
+    incidents_dao: &(dyn IncidentsDao + Send + Sync),
+) -> Result<(), HandlerError> {
+    let result = incidents_dao.resolve_incident(incident_id.incident_uuid).await;
+
+    match result {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            error!("{:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+                _ => Err(HandlerError::default_internal_error()),
+            }
+        }
+    }
+}
+
+/// Number of days, today included, questions are counted per day for in the statistics
+const STATS_DAYS: u64 = 30;
+
+/// Asynchronously computes the statistics of the content using the provided `StatsDao`.
+///
+/// # Arguments
+///
+/// * `stats_dao` - A reference to an object implementing the `StatsDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the statistics, counting questions per day over the last `STATS_DAYS` days, on success, or
+/// a `HandlerError` on failure.
+pub async fn read_stats(stats_dao: &(dyn StatsDao + Send + Sync)) -> Result<ContentStats, HandlerError> {
+    let since = Utc::now().date_naive() - Days::new(STATS_DAYS - 1);
+
+    stats_dao.get_stats(since).await.map_err(|err| {
+        error!("{:?}", err);
+        HandlerError::default_internal_error()
+    })
+}
+
+/// Asynchronously registers a webhook using the provided `WebhooksDao`.
+///
+/// # Arguments
+///
+/// * `webhook` - The webhook to be registered.
+/// * `webhooks_dao` - A reference to an object implementing the `WebhooksDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the registered webhook detail on success, or a `HandlerError` on failure.
+pub async fn create_webhook(
+    webhook: Webhook,
+    webhooks_dao: &(dyn WebhooksDao + Send + Sync),
+) -> Result<WebhookDetail, HandlerError> {
+    let webhook = webhooks_dao.create_webhook(webhook).await;
+
+    match webhook {
+        Ok(webhook) => Ok(webhook),
+        Err(err) => {
+            error!("{:?}", err);
+            Err(HandlerError::default_internal_error())
+        }
+    }
+}
+
+/// Asynchronously reads the maintenance statistics of the database using the provided `MaintenanceDao`.
+///
+/// # Arguments
+///
+/// * `maintenance_dao` - A reference to an object implementing the `MaintenanceDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the maintenance report on success, or a `HandlerError` on failure.
+pub async fn read_maintenance(
+    maintenance_dao: &(dyn MaintenanceDao + Send + Sync),
+) -> Result<MaintenanceReport, HandlerError> {
+    let report = maintenance_dao.get_maintenance_report().await;
+
+    match report {
+        Ok(report) => Ok(report),
+        Err(err) => {
+            error!("{:?}", err);
+            Err(HandlerError::default_internal_error())
+        }
+    }
+}
+
+/// Asynchronously reads the registered webhooks using the provided `WebhooksDao`.
+///
+/// # Arguments
+///
+/// * `webhooks_dao` - A reference to an object implementing the `WebhooksDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the webhook details, oldest first, on success, or a `HandlerError` on failure.
+pub async fn read_webhooks(
+    webhooks_dao: &(dyn WebhooksDao + Send + Sync),
+) -> Result<Vec<WebhookDetail>, HandlerError> {
+    let webhooks = webhooks_dao.get_webhooks().await;
+
+    match webhooks {
+        Ok(webhooks) => Ok(webhooks),
+        Err(err) => {
+            error!("{:?}", err);
+            Err(HandlerError::default_internal_error())
+        }
+    }
+}
+
+/// Asynchronously deletes the webhook identified by the given `WebhookId` using the provided `WebhooksDao`.
+///
+/// # Arguments
+///
+/// * `webhook_id` - The unique identifier of the webhook to be deleted.
+/// * `webhooks_dao` - A reference to an object implementing the `WebhooksDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `HandlerError` is returned.
+pub async fn delete_webhook(
+    webhook_id: WebhookId,
+    webhooks_dao: &(dyn WebhooksDao + Send + Sync),
+) -> Result<(), HandlerError> {
+    let result = webhooks_dao.delete_webhook(webhook_id.webhook_uuid).await;
+
+    match result {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            error!("{:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+                _ => Err(HandlerError::default_internal_error()),
+            }
+        }
+    }
+}
+
+/// Asynchronously registers a webhook notified of the new answers to a question using the provided `WebhooksDao`.
+///
+/// # Arguments
+///
+/// * `question_uuid` - The unique identifier of the question watched.
+/// * `webhook` - The webhook to be registered, expiring after its `ttl_hours`, or a week.
+/// * `webhooks_dao` - A reference to an object implementing the `WebhooksDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the registered webhook detail on success, or a `HandlerError` on failure.
+pub async fn create_question_webhook(
+    question_uuid: QuestionUuid,
+    webhook: QuestionWebhook,
+    webhooks_dao: &(dyn WebhooksDao + Send + Sync),
+) -> Result<QuestionWebhookDetail, HandlerError> {
+    let ttl = TimeDelta::hours(webhook.ttl_hours.unwrap_or(DEFAULT_QUESTION_WEBHOOK_TTL_HOURS).into());
+    let QuestionWebhook { url, secret, .. } = webhook;
+
+    let webhook = webhooks_dao
+        .create_question_webhook(question_uuid, Webhook { url, secret }, Utc::now() + ttl)
+        .await;
+
+    match webhook {
+        Ok(webhook) => Ok(webhook),
+        Err(err) => {
+            error!("{:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+                _ => Err(HandlerError::default_internal_error()),
+            }
+        }
+    }
+}
+
+/// Asynchronously flags a question for moderators to review using the provided `FlagsDao`.
+///
+/// # Arguments
+///
+/// * `question_uuid` - The unique identifier of the question flagged.
+/// * `flag` - Why the question is flagged.
+/// * `flags_dao` - A reference to an object implementing the `FlagsDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the created flag detail on success, or a `HandlerError` on failure.
+pub async fn flag_question(
+    question_uuid: QuestionUuid,
+    flag: Flag,
+    flags_dao: &(dyn FlagsDao + Send + Sync),
+) -> Result<FlagDetail, HandlerError> {
+    let flag = flags_dao.flag_question(question_uuid, flag.normalize()).await;
+
+    flag.map_err(|err| {
+        error!("{:?}", err);
+
+        match err {
+            DBError::InvalidUUID(s) => HandlerError::BadRequest(s),
+            _ => HandlerError::default_internal_error(),
+        }
+    })
+}
+
+/// Asynchronously flags an answer for moderators to review using the provided `FlagsDao`.
+///
+/// # Arguments
+///
+/// * `answer_uuid` - The unique identifier of the answer flagged.
+/// * `flag` - Why the answer is flagged.
+/// * `flags_dao` - A reference to an object implementing the `FlagsDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the created flag detail on success, or a `HandlerError` on failure.
+pub async fn flag_answer(
+    answer_uuid: AnswerUuid,
+    flag: Flag,
+    flags_dao: &(dyn FlagsDao + Send + Sync),
+) -> Result<FlagDetail, HandlerError> {
+    let flag = flags_dao.flag_answer(answer_uuid, flag.normalize()).await;
+
+    flag.map_err(|err| {
+        error!("{:?}", err);
+
+        match err {
+            DBError::InvalidUUID(s) => HandlerError::BadRequest(s),
+            _ => HandlerError::default_internal_error(),
+        }
+    })
+}
+
+/// Asynchronously retrieves the moderation queue using the provided `FlagsDao`.
+///
+/// # Arguments
+///
+/// * `flags_dao` - A reference to an object implementing the `FlagsDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the open flags, oldest first, on success, or a `HandlerError` on failure.
+pub async fn read_moderation_queue(flags_dao: &(dyn FlagsDao + Send + Sync)) -> Result<Vec<FlagDetail>, HandlerError> {
+    flags_dao.get_open_flags().await.map_err(|err| {
+        error!("{:?}", err);
+        HandlerError::default_internal_error()
+    })
+}
+
+/// Asynchronously retrieves every flag raised on a question or its answers using the provided `FlagsDao`.
+///
+/// # Arguments
+///
+/// * `question_uuid` - The unique identifier of the question.
+/// * `flags_dao` - A reference to an object implementing the `FlagsDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the flags, tags included, oldest first, on success, or a `HandlerError` on failure.
+pub async fn read_question_flags(
+    question_uuid: QuestionUuid,
+    flags_dao: &(dyn FlagsDao + Send + Sync),
+) -> Result<Vec<FlagDetail>, HandlerError> {
+    flags_dao.get_question_flags(question_uuid).await.map_err(|err| {
+        error!("{:?}", err);
+        HandlerError::default_internal_error()
+    })
+}
+
+/// Asynchronously acts on the content a flag was raised on, resolving every open flag on that content.
+///
+/// Removed content is deleted before its flags are resolved, so a failure leaves the flags in the queue to retry.
+/// Content already deleted, by its author or an earlier removal, is not an error. Likewise, content a filter held is
+/// published before its flags are dismissed, and content already published by an earlier dismissal is not an error.
+///
+/// # Arguments
+///
+/// * `flag_uuid` - The unique identifier of the flag acted on.
+/// * `decision` - How the moderator acts on the content.
+/// * `flags_dao` - A reference to an object implementing the `FlagsDao` trait along with `Send` and `Sync` traits.
+/// * `questions_dao` - A reference to an object implementing the `QuestionsDao` trait, deleting removed questions
+///   and publishing held ones.
+/// * `answers_dao` - A reference to an object implementing the `AnswersDao` trait, deleting removed answers and
+///   publishing held ones.
+///
+/// # Returns
+///
+/// A `Result` containing the flags resolved, oldest first, on success, or a `HandlerError` on failure. The error is
+/// `HandlerError::Conflict` if the flag was resolved already, or if held content can no longer be published.
+pub async fn resolve_flag(
+    flag_uuid: FlagUuid,
+    decision: FlagDecision,
+    flags_dao: &(dyn FlagsDao + Send + Sync),
+    questions_dao: &(dyn QuestionsDao + Send + Sync),
+    answers_dao: &(dyn AnswersDao + Send + Sync),
+) -> Result<Vec<FlagDetail>, HandlerError> {
+    let internal_error = |err: DBError| {
+        error!("{:?}", err);
+        HandlerError::default_internal_error()
+    };
+
+    let flag = match flags_dao.get_flag(flag_uuid).await {
+        Ok(flag) => flag,
+        Err(DBError::InvalidUUID(s)) => return Err(HandlerError::BadRequest(s)),
+        Err(err) => return Err(internal_error(err)),
+    };
+
+    if flag.resolution.is_some() {
+        return Err(HandlerError::Conflict(format!("Flag {} is resolved already", flag_uuid)));
+    }
+
+    if decision.resolution == FlagResolution::Removed {
+        let deleted = match flag.answer_uuid {
+            Some(answer_uuid) => answers_dao.delete_answer(answer_uuid).await,
+            None => questions_dao.delete_question(flag.question_uuid).await,
+        };
+        deleted.map_err(internal_error)?;
+    }
+
+    if let (FlagResolution::Dismissed, Some(held)) = (decision.resolution, &flag.held) {
+        publish_held_content(held, questions_dao, answers_dao).await?;
+    }
+
+    let resolved = flags_dao.resolve_flags(&flag, decision.resolution).await.map_err(internal_error)?;
+
+    // Another moderator resolved the content meanwhile
+    if resolved.is_empty() {
+        return Err(HandlerError::Conflict(format!("Flag {} is resolved already", flag_uuid)));
+    }
+
+    Ok(resolved)
+}
+
+/// Creates the content a filter held, as it was submitted.
+///
+/// # Returns
+///
+/// Nothing on success, or a `HandlerError` on failure. The error is `HandlerError::Conflict` if the content cannot be
+/// created, its UUID taken or the question answered gone or no longer open, unless an earlier dismissal created it.
+async fn publish_held_content(
+    held: &HeldContent,
+    questions_dao: &(dyn QuestionsDao + Send + Sync),
+    answers_dao: &(dyn AnswersDao + Send + Sync),
+) -> Result<(), HandlerError> {
+    let internal_error = |err: DBError| {
+        error!("{:?}", err);
+        HandlerError::default_internal_error()
+    };
+
+    let refused = match held {
+        HeldContent::Question(question) => match questions_dao.create_question(question.clone()).await {
+            Ok(_) => return Ok(()),
+            Err(DBError::Conflict(msg)) => msg,
+            Err(err) => return Err(internal_error(err)),
+        },
+        HeldContent::Answer(answer) => match answers_dao.create_answer(answer.clone()).await {
+            Ok(_) => return Ok(()),
+            Err(DBError::Conflict(msg) | DBError::InvalidUUID(msg)) => msg,
+            Err(err) => return Err(internal_error(err)),
+        },
+    };
+
+    // An earlier dismissal may have published the content, then failed to resolve the flags
+    let published = match held {
+        HeldContent::Question(question) => questions_dao
+            .get_questions_by_uuids(question.question_uuid.into_iter().collect())
+            .await
+            .map_err(internal_error)?
+            .iter()
+            .any(|detail| detail.title == question.title && detail.description == question.description),
+        HeldContent::Answer(answer) => match answers_dao.get_answers(answer.question_uuid, AnswerSort::Oldest).await {
+            Ok(answers) => answers
+                .iter()
+                .any(|detail| Some(detail.answer_uuid) == answer.answer_uuid && detail.content == answer.content),
+            Err(DBError::InvalidUUID(_)) => false,
+            Err(err) => return Err(internal_error(err)),
+        },
+    };
+
+    if published {
+        Ok(())
+    } else {
+        Err(HandlerError::Conflict(refused))
+    }
+}
+
+/// Asynchronously creates a job of the given kind using the provided `JobsDao`, unless one is running already.
+///
+/// # Arguments
+///
+/// * `kind` - The work the job does.
+/// * `jobs_dao` - A reference to an object implementing the `JobsDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the created job detail on success, or a `HandlerError` on failure.
+pub async fn create_job(kind: JobKind, jobs_dao: &(dyn JobsDao + Send + Sync)) -> Result<JobDetail, HandlerError> {
+    let running = match jobs_dao.get_running_jobs().await {
+        Ok(running) => running,
+        Err(err) => {
+            error!("{:?}", err);
+            return Err(HandlerError::default_internal_error());
+        }
+    };
+
+    // Two jobs of a kind would only do the same work twice
+    if let Some(job) = running.iter().find(|job| job.kind == kind) {
+        return Err(HandlerError::Conflict(format!("Job {} is already running", job.job_uuid)));
+    }
+
+    let job = jobs_dao.create_job(kind).await;
+
+    match job {
+        Ok(job) => Ok(job),
+        // Created meanwhile, by a concurrent request
+        Err(DBError::Conflict(msg)) => Err(HandlerError::Conflict(msg)),
+        Err(err) => {
+            error!("{:?}", err);
+            Err(HandlerError::default_internal_error())
+        }
+    }
+}
+
+/// Asynchronously reads the job identified by the given `JobUuid` using the provided `JobsDao`.
+///
+/// # Arguments
+///
+/// * `job_uuid` - The unique identifier of the job.
+/// * `jobs_dao` - A reference to an object implementing the `JobsDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the job detail, with its progress, on success, or a `HandlerError` on failure.
+pub async fn read_job(job_uuid: JobUuid, jobs_dao: &(dyn JobsDao + Send + Sync)) -> Result<JobDetail, HandlerError> {
+    let job = jobs_dao.get_job(job_uuid).await;
+
+    match job {
+        Ok(job) => Ok(job),
+        Err(err) => {
+            error!("{:?}", err);
+
+            match err {
+                DBError::InvalidUUID(s) => Err(HandlerError::BadRequest(s)),
+                _ => Err(HandlerError::default_internal_error()),
+            }
+        }
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use chrono::{DateTime, Utc};
+    use uuid::Uuid;
+
+    use crate::{
+        content_filter::WordListFilter,
+        models::{AnswerUuid, CachedQuestion, FlagReason, JobStatus, QuestionUuid},
+        outbox::ContentEvent,
+        persistance::{
+            answers_dao::AnswersDaoInMemory, flags_dao::FlagsDaoInMemory, memory::MemoryStore,
+            questions_dao::QuestionsDaoInMemory,
+        },
+        test_support::{AnswerBuilder, QuestionBuilder},
+    };
+
+    use async_trait::async_trait;
+    use tokio::sync::Mutex;
+
+    /// A flags DAO over an empty store, for the handlers flagging filtered content.
+    fn flags_dao() -> FlagsDaoInMemory {
+        FlagsDaoInMemory::new(Arc::new(MemoryStore::new()))
+    }
+
+    struct QuestionsDaoMock {
+        create_question_response: Mutex<Option<Result<QuestionDetail, DBError>>>,
+        delete_question_response: Mutex<Option<Result<(), DBError>>>,
+        get_questions_response: Mutex<Option<Result<Vec<QuestionDetail>, DBError>>>,
+        get_questions_by_uuids_response: Mutex<Option<Result<Vec<QuestionDetail>, DBError>>>,
+        get_popular_questions_response: Mutex<Option<Result<Vec<QuestionDetail>, DBError>>>,
+        get_popular_questions_args: Mutex<Option<i64>>,
+        get_question_with_answers_response: Mutex<Option<Result<QuestionWithAnswers, DBError>>>,
+        search_questions_response: Mutex<Option<Result<Vec<QuestionSearchResult>, DBError>>>,
+        search_questions_args: Mutex<Option<(String, i64)>>,
+        update_question_status_response: Mutex<Option<Result<QuestionDetail, DBError>>>,
+        update_question_status_args: Mutex<Option<(QuestionStatus, QuestionStatus)>>,
+    }
+
+    impl QuestionsDaoMock {
+        pub fn new() -> Self {
+            QuestionsDaoMock {
+                create_question_response: Mutex::new(None),
+                delete_question_response: Mutex::new(None),
+                get_questions_response: Mutex::new(None),
+                get_questions_by_uuids_response: Mutex::new(None),
+                get_popular_questions_response: Mutex::new(None),
+                get_popular_questions_args: Mutex::new(None),
+                get_question_with_answers_response: Mutex::new(None),
+                search_questions_response: Mutex::new(None),
+                search_questions_args: Mutex::new(None),
+                update_question_status_response: Mutex::new(None),
+                update_question_status_args: Mutex::new(None),
+            }
+        }
+        pub fn mock_create_question(&mut self, response: Result<QuestionDetail, DBError>) {
+            self.create_question_response = Mutex::new(Some(response));
         }
         pub fn mock_delete_question(&mut self, response: Result<(), DBError>) {
             self.delete_question_response = Mutex::new(Some(response));
@@ -198,6 +1267,21 @@ mod tests {
         pub fn mock_get_questions(&mut self, response: Result<Vec<QuestionDetail>, DBError>) {
             self.get_questions_response = Mutex::new(Some(response));
         }
+        pub fn mock_get_questions_by_uuids(&mut self, response: Result<Vec<QuestionDetail>, DBError>) {
+            self.get_questions_by_uuids_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_popular_questions(&mut self, response: Result<Vec<QuestionDetail>, DBError>) {
+            self.get_popular_questions_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_question_with_answers(&mut self, response: Result<QuestionWithAnswers, DBError>) {
+            self.get_question_with_answers_response = Mutex::new(Some(response));
+        }
+        pub fn mock_search_questions(&mut self, response: Result<Vec<QuestionSearchResult>, DBError>) {
+            self.search_questions_response = Mutex::new(Some(response));
+        }
+        pub fn mock_update_question_status(&mut self, response: Result<QuestionDetail, DBError>) {
+            self.update_question_status_response = Mutex::new(Some(response));
+        }
     }
 
     #[async_trait]
@@ -209,191 +1293,1224 @@ mod tests {
                 .take()
                 .expect("create_question_response should not be None.")
         }
-        async fn delete_question(&self, _: String) -> Result<(), DBError> {
+        async fn delete_question(&self, _: QuestionUuid) -> Result<(), DBError> {
             self.delete_question_response
                 .lock()
                 .await
                 .take()
                 .expect("delete_question_response should not be None.")
         }
-        async fn get_questions(&self) -> Result<Vec<QuestionDetail>, DBError> {
+        async fn get_questions(&self, _: &QuestionFilter) -> Result<Vec<QuestionDetail>, DBError> {
             self.get_questions_response
                 .lock()
                 .await
                 .take()
                 .expect("get_questions_response should not be None.")
         }
+        async fn get_questions_by_uuids(&self, _: Vec<QuestionUuid>) -> Result<Vec<QuestionDetail>, DBError> {
+            self.get_questions_by_uuids_response
+                .lock()
+                .await
+                .take()
+                .expect("get_questions_by_uuids_response should not be None.")
+        }
+        async fn get_questions_after(&self, _: Option<QuestionUuid>, _: i64) -> Result<Vec<QuestionDetail>, DBError> {
+            unimplemented!("No handler pages through every question")
+        }
+        async fn get_popular_questions(&self, limit: i64) -> Result<Vec<QuestionDetail>, DBError> {
+            *self.get_popular_questions_args.lock().await = Some(limit);
+            self.get_popular_questions_response
+                .lock()
+                .await
+                .take()
+                .expect("get_popular_questions_response should not be None.")
+        }
+        async fn get_question_with_answers(&self, _: QuestionUuid) -> Result<QuestionWithAnswers, DBError> {
+            self.get_question_with_answers_response
+                .lock()
+                .await
+                .take()
+                .expect("get_question_with_answers_response should not be None.")
+        }
+        async fn search_questions(&self, query: String, limit: i64) -> Result<Vec<QuestionSearchResult>, DBError> {
+            *self.search_questions_args.lock().await = Some((query, limit));
+            self.search_questions_response
+                .lock()
+                .await
+                .take()
+                .expect("search_questions_response should not be None.")
+        }
+        async fn update_question_status(
+            &self,
+            _: QuestionUuid,
+            from: QuestionStatus,
+            to: QuestionStatus,
+        ) -> Result<QuestionDetail, DBError> {
+            *self.update_question_status_args.lock().await = Some((from, to));
+            self.update_question_status_response
+                .lock()
+                .await
+                .take()
+                .expect("update_question_status_response should not be None.")
+        }
+        async fn add_question_views(&self, _: &HashMap<QuestionUuid, i64>) -> Result<(), DBError> {
+            unimplemented!("No handler writes views, they are counted by the view counter")
+        }
+        async fn current_time(&self) -> Result<DateTime<Utc>, DBError> {
+            Ok(Utc::now())
+        }
+    }
+
+    struct AnswersDaoMock {
+        create_answer_response: Mutex<Option<Result<AnswerDetail, DBError>>>,
+        delete_answer_response: Mutex<Option<Result<(), DBError>>>,
+        get_answers_response: Mutex<Option<Result<Vec<AnswerDetail>, DBError>>>,
+        get_answers_for_questions_response: Mutex<Option<Result<AnswersByQuestion, DBError>>>,
+    }
+
+    impl AnswersDaoMock {
+        pub fn new() -> Self {
+            AnswersDaoMock {
+                create_answer_response: Mutex::new(None),
+                delete_answer_response: Mutex::new(None),
+                get_answers_response: Mutex::new(None),
+                get_answers_for_questions_response: Mutex::new(None),
+            }
+        }
+        pub fn mock_create_answer(&mut self, response: Result<AnswerDetail, DBError>) {
+            self.create_answer_response = Mutex::new(Some(response));
+        }
+        pub fn mock_delete_answer(&mut self, response: Result<(), DBError>) {
+            self.delete_answer_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_answers(&mut self, response: Result<Vec<AnswerDetail>, DBError>) {
+            self.get_answers_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_answers_for_questions(
+            &mut self,
+            response: Result<AnswersByQuestion, DBError>,
+        ) {
+            self.get_answers_for_questions_response = Mutex::new(Some(response));
+        }
+    }
+
+    #[async_trait]
+    impl AnswersDao for AnswersDaoMock {
+        async fn create_answer(&self, _: Answer) -> Result<AnswerDetail, DBError> {
+            self.create_answer_response
+                .lock()
+                .await
+                .take()
+                .expect("create_answer_response should not be None.")
+        }
+        async fn delete_answer(&self, _: AnswerUuid) -> Result<(), DBError> {
+            self.delete_answer_response
+                .lock()
+                .await
+                .take()
+                .expect("delete_answer_response should not be None.")
+        }
+        async fn get_answers(&self, _: QuestionUuid, _: AnswerSort) -> Result<Vec<AnswerDetail>, DBError> {
+            self.get_answers_response
+                .lock()
+                .await
+                .take()
+                .expect("get_answers_response should not be None.")
+        }
+        async fn get_answers_for_questions(
+            &self,
+            _: Vec<QuestionUuid>,
+        ) -> Result<AnswersByQuestion, DBError> {
+            self.get_answers_for_questions_response
+                .lock()
+                .await
+                .take()
+                .expect("get_answers_for_questions_response should not be None.")
+        }
+    }
+
+    #[tokio::test]
+    async fn create_question_should_return_question() {
+        let question = QuestionBuilder::new().build();
+
+        let question_detail = QuestionDetail {
+            question_uuid: Uuid::from_u128(123).into(),
+            title: question.title.clone(),
+            description: question.description.clone(),
+            status: QuestionStatus::Open,
+            created_at: Utc::now(),
+            answer_count: 0,
+            last_activity_at: Utc::now(),
+            view_count: 0,
+            word_count: 0,
+            reading_time_seconds: 0,
+            excerpt: None,
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_create_question(Ok(question_detail.clone()));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = create_question(question, &ContentFilters::default(), questions_dao.as_ref(), &flags_dao()).await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), question_detail);
+    }
+
+    #[tokio::test]
+    async fn create_question_should_return_error() {
+        let question = QuestionBuilder::new().build();
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_create_question(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = create_question(question, &ContentFilters::default(), questions_dao.as_ref(), &flags_dao()).await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn create_question_should_return_conflict_error() {
+        let question = QuestionBuilder::new().question_uuid(Uuid::from_u128(123).into()).build();
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_create_question(Err(DBError::Conflict("test".to_owned())));
+
+        let result = create_question(question, &ContentFilters::default(), &questions_dao, &flags_dao()).await;
+
+        assert_eq!(result, Err(HandlerError::Conflict("test".to_owned())));
+    }
+
+    #[tokio::test]
+    async fn create_question_should_reject_filtered_content_before_the_dao() {
+        let question = QuestionBuilder::new().description("Why the heck does it panic?").build();
+        let content_filters = ContentFilters::new(vec![(Box::new(WordListFilter::new(["heck"])), FilterAction::Reject)]);
+
+        // Without a response, so creating the question would panic
+        let questions_dao = QuestionsDaoMock::new();
+
+        let result = create_question(question, &content_filters, &questions_dao, &flags_dao()).await;
+
+        assert_eq!(
+            result,
+            Err(HandlerError::BadRequest(
+                "Question rejected by a content filter: Contains the banned word \"heck\"".to_owned()
+            ))
+        );
+    }
+
+    #[tokio::test]
+    async fn create_question_should_create_and_tag_filtered_content() {
+        let question = QuestionBuilder::new().description("Why the heck does it panic?").build();
+        let content_filters = ContentFilters::new(vec![(Box::new(WordListFilter::new(["heck"])), FilterAction::Tag)]);
+        let store = Arc::new(MemoryStore::new());
+        let flags_dao = FlagsDaoInMemory::new(store.clone());
+
+        let created = create_question(question, &content_filters, &QuestionsDaoInMemory::new(store), &flags_dao)
+            .await
+            .unwrap();
+
+        let flags = flags_dao.get_question_flags(created.question_uuid).await.unwrap();
+        assert_eq!(flags.len(), 1);
+        assert_eq!(
+            (flags[0].reason, flags[0].resolution, flags[0].comment.as_deref()),
+            (FlagReason::Offensive, Some(FlagResolution::Tagged), Some("Contains the banned word \"heck\""))
+        );
+        // Not held for moderators
+        assert_eq!(flags_dao.get_open_flags().await.unwrap(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn create_question_should_hold_filtered_content_out_of_reads_and_events() {
+        let question = QuestionBuilder::new().description("Why the heck does it panic?").build();
+        let content_filters = ContentFilters::new(vec![(Box::new(WordListFilter::new(["heck"])), FilterAction::Hold)]);
+        let store = Arc::new(MemoryStore::new());
+        let questions_dao = QuestionsDaoInMemory::new(store.clone());
+        let flags_dao = FlagsDaoInMemory::new(store.clone());
+
+        let held = create_question(question, &content_filters, &questions_dao, &flags_dao).await.unwrap();
+
+        assert_eq!(questions_dao.get_questions(&QuestionFilter::default()).await.unwrap(), vec![]);
+        assert!(store.outbox.read().unwrap().is_empty());
+
+        let queue = flags_dao.get_open_flags().await.unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!((queue[0].question_uuid, queue[0].reason), (held.question_uuid, FlagReason::Offensive));
+        match &queue[0].held {
+            Some(HeldContent::Question(question)) => {
+                assert_eq!(
+                    (question.question_uuid, question.description.as_str()),
+                    (Some(held.question_uuid), "Why the heck does it panic?")
+                );
+            }
+            other => panic!("Expected the question held on its flag but got: {:?}", other),
+        }
+    }
+
+    /// Holds a question on a flag of the given store, returning the question and the flag.
+    async fn held_question(store: &Arc<MemoryStore>) -> (QuestionDetail, FlagDetail) {
+        let question = QuestionBuilder::new().description("Why the heck does it panic?").build();
+        let content_filters = ContentFilters::new(vec![(Box::new(WordListFilter::new(["heck"])), FilterAction::Hold)]);
+        let flags_dao = FlagsDaoInMemory::new(store.clone());
+
+        let held = create_question(question, &content_filters, &QuestionsDaoInMemory::new(store.clone()), &flags_dao)
+            .await
+            .unwrap();
+        let flag = flags_dao.get_open_flags().await.unwrap().remove(0);
+        (held, flag)
+    }
+
+    #[tokio::test]
+    async fn resolve_flag_should_publish_held_content_once_dismissed() {
+        let store = Arc::new(MemoryStore::new());
+        let (held, flag) = held_question(&store).await;
+        let questions_dao = QuestionsDaoInMemory::new(store.clone());
+        let decision = FlagDecision { resolution: FlagResolution::Dismissed };
+
+        let resolved = resolve_flag(
+            flag.flag_uuid,
+            decision,
+            &FlagsDaoInMemory::new(store.clone()),
+            &questions_dao,
+            &AnswersDaoInMemory::new(store.clone()),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(resolved.len(), 1);
+        let questions = questions_dao.get_questions(&QuestionFilter::default()).await.unwrap();
+        assert_eq!(
+            questions.iter().map(|question| (question.question_uuid, &question.description)).collect::<Vec<_>>(),
+            vec![(held.question_uuid, &held.description)]
+        );
+        assert!(matches!(
+            store.outbox.read().unwrap().front(),
+            Some(ContentEvent::QuestionCreated(question)) if question.question_uuid == held.question_uuid
+        ));
+    }
+
+    #[tokio::test]
+    async fn resolve_flag_should_dismiss_held_content_published_by_an_earlier_attempt() {
+        let store = Arc::new(MemoryStore::new());
+        let (_, flag) = held_question(&store).await;
+        let questions_dao = QuestionsDaoInMemory::new(store.clone());
+        let Some(HeldContent::Question(question)) = flag.held.clone() else { panic!("Expected a held question") };
+
+        // As if dismissing it failed after publishing it
+        questions_dao.create_question(question).await.unwrap();
+
+        let resolved = resolve_flag(
+            flag.flag_uuid,
+            FlagDecision { resolution: FlagResolution::Dismissed },
+            &FlagsDaoInMemory::new(store.clone()),
+            &questions_dao,
+            &AnswersDaoInMemory::new(store.clone()),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(questions_dao.get_questions(&QuestionFilter::default()).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn resolve_flag_should_discard_held_content_once_removed() {
+        let store = Arc::new(MemoryStore::new());
+        let (_, flag) = held_question(&store).await;
+        let questions_dao = QuestionsDaoInMemory::new(store.clone());
+
+        let resolved = resolve_flag(
+            flag.flag_uuid,
+            FlagDecision { resolution: FlagResolution::Removed },
+            &FlagsDaoInMemory::new(store.clone()),
+            &questions_dao,
+            &AnswersDaoInMemory::new(store.clone()),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(resolved[0].resolution, Some(FlagResolution::Removed));
+        assert_eq!(questions_dao.get_questions(&QuestionFilter::default()).await.unwrap(), vec![]);
+        assert!(store.outbox.read().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn read_questions_should_return_questions() {
+        let question_detail = QuestionBuilder::new().build_detail();
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_questions(Ok(vec![question_detail.clone()]));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = read_questions(QuestionFilter::default(), questions_dao.as_ref()).await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), vec![question_detail]);
+    }
+
+    #[tokio::test]
+    async fn read_questions_should_return_error() {
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_questions(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = read_questions(QuestionFilter::default(), questions_dao.as_ref()).await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn read_popular_questions_should_cap_limit() {
+        let question = QuestionBuilder::new().build_detail();
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_popular_questions(Ok(vec![question.clone()]));
+
+        let result = read_popular_questions(PopularQuestions { limit: Some(1000) }, &questions_dao).await;
+
+        assert_eq!(result, Ok(vec![question]));
+        assert_eq!(*questions_dao.get_popular_questions_args.lock().await, Some(MAX_POPULAR_LIMIT));
+    }
+
+    #[tokio::test]
+    async fn read_popular_questions_should_fail_on_db_error() {
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_popular_questions(Err(DBError::Other(Box::new(std::io::Error::other("oh no!")))));
+
+        let result = read_popular_questions(PopularQuestions::default(), &questions_dao).await;
+
+        assert_eq!(result, Err(HandlerError::default_internal_error()));
+        assert_eq!(*questions_dao.get_popular_questions_args.lock().await, Some(DEFAULT_POPULAR_LIMIT));
+    }
+
+    #[tokio::test]
+    async fn search_questions_should_return_results() {
+        let search_result = QuestionSearchResult {
+            question: QuestionBuilder::new().build_detail(),
+            rank: 0.5,
+            snippet: "<mark>test</mark> title".to_owned(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_search_questions(Ok(vec![search_result.clone()]));
+
+        let search = QuestionSearch {
+            q: "  test\n ".to_owned(),
+            limit: Some(1000),
+        };
+
+        let result = search_questions(search, &questions_dao, None).await;
+
+        assert_eq!(result, Ok(vec![search_result]));
+        assert_eq!(
+            *questions_dao.search_questions_args.lock().await,
+            Some(("test".to_owned(), MAX_SEARCH_LIMIT))
+        );
+    }
+
+    #[tokio::test]
+    async fn search_questions_should_reject_empty_query() {
+        let questions_dao = QuestionsDaoMock::new();
+
+        let search = QuestionSearch {
+            q: " \t ".to_owned(),
+            limit: None,
+        };
+
+        let result = search_questions(search, &questions_dao, None).await;
+
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_question_should_succeed() {
+        let question_id = QuestionId {
+            question_uuid: Uuid::from_u128(123).into(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_delete_question(Ok(()));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = delete_question(question_id, questions_dao.as_ref()).await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), ());
+    }
+
+    #[tokio::test]
+    async fn delete_question_should_return_error() {
+        let question_id = QuestionId {
+            question_uuid: Uuid::from_u128(123).into(),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_delete_question(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+
+        let result = delete_question(question_id, questions_dao.as_ref()).await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn lookup_questions_should_return_questions() {
+        let question_detail = QuestionBuilder::new().build_detail();
+
+        let lookup = QuestionsLookup {
+            question_uuids: vec![question_detail.question_uuid, Uuid::from_u128(123).into()],
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_questions_by_uuids(Ok(vec![question_detail.clone()]));
+
+        let result = lookup_questions(lookup, &questions_dao).await;
+
+        assert_eq!(result, Ok(vec![question_detail]));
+    }
+
+    #[tokio::test]
+    async fn lookup_questions_should_return_error() {
+        let lookup = QuestionsLookup {
+            question_uuids: vec![Uuid::from_u128(123).into()],
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_questions_by_uuids(Err(DBError::Other(Box::new(std::io::Error::other("oh no!")))));
+
+        let result = lookup_questions(lookup, &questions_dao).await;
+
+        assert_eq!(result, Err(HandlerError::default_internal_error()));
+    }
+
+    #[tokio::test]
+    async fn sync_questions_should_return_changes_only() {
+        let unchanged = QuestionBuilder::new().build_detail();
+        let changed = QuestionBuilder::new().build_detail();
+        let created = QuestionBuilder::new().build_detail();
+        let deleted = QuestionUuid::from(Uuid::from_u128(123));
+        let answer = AnswerBuilder::new(changed.question_uuid).build_detail();
+
+        let held = QuestionWithAnswers { question: unchanged.clone(), answers: vec![] };
+        let unchanged_version = entity_tag(&serde_json::to_vec(&held).unwrap());
+        let request = SyncRequest {
+            questions: vec![
+                CachedQuestion { question_uuid: unchanged.question_uuid, version: unchanged_version },
+                CachedQuestion { question_uuid: changed.question_uuid, version: "\"stale\"".to_owned() },
+                CachedQuestion { question_uuid: deleted, version: "\"stale\"".to_owned() },
+            ],
+            since: Some(Utc::now()),
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+        questions_dao.mock_get_questions_by_uuids(Ok(vec![unchanged.clone(), changed.clone()]));
+        // Held questions created since are not returned as created
+        questions_dao.mock_get_questions(Ok(vec![changed.clone(), created.clone()]));
+        let mut answers_dao = AnswersDaoMock::new();
+        answers_dao.mock_get_answers_for_questions(Ok(HashMap::from([(changed.question_uuid, vec![answer.clone()])])));
+
+        let result = sync_questions(request, &questions_dao, &answers_dao).await.unwrap();
+
+        let changed = QuestionWithAnswers { question: changed, answers: vec![answer] };
+        assert_eq!(result.changed.len(), 1);
+        assert_eq!(result.changed[0].question, changed);
+        assert_eq!(result.changed[0].version, entity_tag(&serde_json::to_vec(&changed).unwrap()));
+        assert_eq!(result.created.len(), 1);
+        assert_eq!(result.created[0].question, QuestionWithAnswers { question: created, answers: vec![] });
+        assert_eq!(result.deleted, vec![deleted]);
+    }
+
+    #[tokio::test]
+    async fn sync_questions_should_return_error() {
+        let request = SyncRequest {
+            questions: vec![CachedQuestion { question_uuid: Uuid::from_u128(123).into(), version: "\"1\"".to_owned() }],
+            since: None,
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+        questions_dao.mock_get_questions_by_uuids(Err(DBError::Other(Box::new(std::io::Error::other("oh no!")))));
+
+        let result = sync_questions(request, &questions_dao, &AnswersDaoMock::new()).await;
+
+        assert_eq!(result, Err(HandlerError::default_internal_error()));
+    }
+
+    #[tokio::test]
+    async fn read_question_with_answers_should_return_question_and_answers() {
+        let question_detail = QuestionDetail {
+            question_uuid: Uuid::from_u128(123).into(),
+            title: "test title".to_owned(),
+            description: "test description".to_owned(),
+            status: QuestionStatus::Open,
+            created_at: Utc::now(),
+            answer_count: 1,
+            last_activity_at: Utc::now(),
+            view_count: 0,
+            word_count: 0,
+            reading_time_seconds: 0,
+            excerpt: None,
+        };
+
+        let question_with_answers = QuestionWithAnswers {
+            answers: vec![AnswerDetail {
+                answer_uuid: Uuid::from_u128(456).into(),
+                question_uuid: question_detail.question_uuid,
+                content: "test content".to_owned(),
+                created_at: question_detail.last_activity_at,
+                word_count: 0,
+                reading_time_seconds: 0,
+            }],
+            question: question_detail,
+        };
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_question_with_answers(Ok(question_with_answers.clone()));
+
+        let question_id = QuestionId {
+            question_uuid: question_with_answers.question.question_uuid,
+        };
+
+        let result = read_question_with_answers(question_id, &questions_dao).await;
+
+        assert_eq!(result, Ok(question_with_answers));
+    }
+
+    #[tokio::test]
+    async fn read_question_with_answers_should_return_bad_request_error() {
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_question_with_answers(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let question_id = QuestionId {
+            question_uuid: Uuid::from_u128(123).into(),
+        };
+
+        let result = read_question_with_answers(question_id, &questions_dao).await;
+
+        assert_eq!(result, Err(HandlerError::BadRequest("test".to_owned())));
+    }
+
+    #[tokio::test]
+    async fn read_question_with_answers_should_return_error() {
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_get_question_with_answers(Err(DBError::Other(Box::new(std::io::Error::other(
+            "oh no!",
+        )))));
+
+        let question_id = QuestionId {
+            question_uuid: Uuid::from_u128(123).into(),
+        };
+
+        let result = read_question_with_answers(question_id, &questions_dao).await;
+
+        assert_eq!(result, Err(HandlerError::default_internal_error()));
+    }
+
+    #[tokio::test]
+    async fn close_question_should_return_closed_question() {
+        let question_detail = QuestionBuilder::new().status(QuestionStatus::Closed).build_detail();
+
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_update_question_status(Ok(question_detail.clone()));
+
+        let question_id = QuestionId {
+            question_uuid: question_detail.question_uuid,
+        };
+
+        let result = close_question(question_id, &questions_dao).await;
+
+        assert_eq!(result, Ok(question_detail));
+        assert_eq!(
+            *questions_dao.update_question_status_args.lock().await,
+            Some((QuestionStatus::Open, QuestionStatus::Closed))
+        );
+    }
+
+    #[tokio::test]
+    async fn close_question_should_return_bad_request_error() {
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_update_question_status(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let question_id = QuestionId {
+            question_uuid: Uuid::from_u128(123).into(),
+        };
+
+        let result = close_question(question_id, &questions_dao).await;
+
+        assert_eq!(result, Err(HandlerError::BadRequest("test".to_owned())));
+    }
+
+    #[tokio::test]
+    async fn reopen_question_should_return_conflict_error() {
+        let mut questions_dao = QuestionsDaoMock::new();
+
+        questions_dao.mock_update_question_status(Err(DBError::Conflict("test".to_owned())));
+
+        let question_id = QuestionId {
+            question_uuid: Uuid::from_u128(123).into(),
+        };
+
+        let result = reopen_question(question_id, &questions_dao).await;
+
+        assert_eq!(result, Err(HandlerError::Conflict("test".to_owned())));
+        assert_eq!(
+            *questions_dao.update_question_status_args.lock().await,
+            Some((QuestionStatus::Closed, QuestionStatus::Open))
+        );
+    }
+
+    #[tokio::test]
+    async fn create_answer_should_return_answer() {
+        let answer = AnswerBuilder::new(Uuid::from_u128(123).into()).build();
+
+        let answer_detail = AnswerDetail {
+            answer_uuid: Uuid::from_u128(456).into(),
+            question_uuid: answer.question_uuid,
+            content: answer.content.clone(),
+            created_at: Utc::now(),
+            word_count: 0,
+            reading_time_seconds: 0,
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_create_answer(Ok(answer_detail.clone()));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+        let result = create_answer(answer, &ContentFilters::default(), answers_dao.as_ref(), &flags_dao()).await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), answer_detail);
+    }
+
+    #[tokio::test]
+    async fn create_answer_should_return_bad_request_error() {
+        let answer = AnswerBuilder::new(Uuid::from_u128(123).into()).build();
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_create_answer(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+        let result = create_answer(answer, &ContentFilters::default(), answers_dao.as_ref(), &flags_dao()).await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn create_answer_should_return_conflict_error() {
+        let answer = AnswerBuilder::new(Uuid::from_u128(123).into()).build();
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_create_answer(Err(DBError::Conflict("test".to_owned())));
+
+        let result = create_answer(answer, &ContentFilters::default(), &answers_dao, &flags_dao()).await;
+
+        assert_eq!(result, Err(HandlerError::Conflict("test".to_owned())));
+    }
+
+    #[tokio::test]
+    async fn create_answer_should_return_internal_error() {
+        let answer = AnswerBuilder::new(Uuid::from_u128(123).into()).build();
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_create_answer(Err(DBError::Other(Box::new(std::io::Error::other(
+            "oh no!",
+        )))));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+        let result = create_answer(answer, &ContentFilters::default(), answers_dao.as_ref(), &flags_dao()).await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn create_answer_should_fail_to_hold_an_answer_to_a_missing_question() {
+        let answer = AnswerBuilder::new(QuestionUuid::new_v4()).content("Check the heck out of the logs").build();
+        let content_filters = ContentFilters::new(vec![(Box::new(WordListFilter::new(["heck"])), FilterAction::Hold)]);
+        let flags_dao = flags_dao();
+
+        // Without a response, so creating the answer would panic
+        let answers_dao = AnswersDaoMock::new();
+
+        let result = create_answer(answer, &content_filters, &answers_dao, &flags_dao).await;
+
+        assert!(matches!(result, Err(HandlerError::BadRequest(_))), "{:?}", result);
+        assert_eq!(flags_dao.get_open_flags().await.unwrap(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn read_answers_should_return_answers() {
+        let answer_detail = AnswerBuilder::new(Uuid::from_u128(123).into()).build_detail();
+
+        let question_id = QuestionId {
+            question_uuid: Uuid::from_u128(123).into(),
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_get_answers(Ok(vec![answer_detail.clone()]));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+        let result = read_answers(question_id, AnswerSort::default(), answers_dao.as_ref()).await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), vec![answer_detail]);
+    }
+
+    #[tokio::test]
+    async fn read_answers_should_return_error() {
+        let question_id = QuestionId {
+            question_uuid: Uuid::from_u128(123).into(),
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_get_answers(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+        let result = read_answers(question_id, AnswerSort::default(), answers_dao.as_ref()).await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn read_answers_batch_should_return_answers_by_question() {
+        let answer_detail = AnswerBuilder::new(Uuid::from_u128(123).into()).build_detail();
+
+        let answers = HashMap::from([
+            (answer_detail.question_uuid, vec![answer_detail.clone()]),
+            (Uuid::from_u128(789).into(), vec![]),
+        ]);
+
+        let batch = AnswersBatch {
+            question_uuids: answers.keys().copied().collect(),
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_get_answers_for_questions(Ok(answers.clone()));
+
+        let result = read_answers_batch(batch, &answers_dao).await;
+
+        assert_eq!(result, Ok(answers));
+    }
+
+    #[tokio::test]
+    async fn read_answers_batch_should_return_error() {
+        let batch = AnswersBatch {
+            question_uuids: vec![Uuid::from_u128(123).into()],
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_get_answers_for_questions(Err(DBError::Other(Box::new(std::io::Error::other("oh no!")))));
+
+        let result = read_answers_batch(batch, &answers_dao).await;
+
+        assert_eq!(result, Err(HandlerError::default_internal_error()));
+    }
+
+    #[tokio::test]
+    async fn poll_answers_should_return_answers_after_the_cursor_at_once() {
+        let question_uuid = QuestionUuid::from(Uuid::from_u128(123));
+        let seen = AnswerBuilder::new(question_uuid).build_detail();
+        let new = AnswerDetail { created_at: seen.created_at + TimeDelta::seconds(1), ..seen.clone() };
+
+        let mut answers_dao = AnswersDaoMock::new();
+        answers_dao.mock_get_answers(Ok(vec![seen.clone(), new.clone()]));
+
+        let query = AnswerUpdatesQuery { since: Some(seen.created_at), wait: Some("60s".to_owned()) };
+        let result = poll_answers(question_uuid, query, &answers_dao, &mut live::Feed::new().subscribe()).await;
+
+        assert_eq!(result, Ok(AnswerUpdates { cursor: Some(new.created_at), answers: vec![new] }));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn poll_answers_should_keep_the_cursor_when_no_answer_arrives() {
+        let since = Utc::now();
+
+        let mut answers_dao = AnswersDaoMock::new();
+        answers_dao.mock_get_answers(Ok(vec![]));
+
+        // Capped, and left to expire on the paused clock
+        let feed = live::Feed::new();
+        let query = AnswerUpdatesQuery { since: Some(since), wait: Some("3600".to_owned()) };
+        let result = poll_answers(Uuid::from_u128(123).into(), query, &answers_dao, &mut feed.subscribe()).await;
+
+        assert_eq!(result, Ok(AnswerUpdates { answers: vec![], cursor: Some(since) }));
+    }
+
+    #[tokio::test]
+    async fn poll_answers_should_reject_invalid_wait() {
+        let feed = live::Feed::new();
+        let query = AnswerUpdatesQuery { since: None, wait: Some("soon".to_owned()) };
+
+        let answers_dao = AnswersDaoMock::new();
+
+        let result = poll_answers(Uuid::from_u128(123).into(), query, &answers_dao, &mut feed.subscribe()).await;
+
+        assert!(matches!(result, Err(HandlerError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn delete_answer_should_succeed() {
+        let answer_id = AnswerId {
+            answer_uuid: Uuid::from_u128(123).into(),
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_delete_answer(Ok(()));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+        let result = delete_answer(answer_id, answers_dao.as_ref()).await;
+
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), ());
+    }
+
+    #[tokio::test]
+    async fn delete_answer_should_return_error() {
+        let answer_id = AnswerId {
+            answer_uuid: Uuid::from_u128(123).into(),
+        };
+
+        let mut answers_dao = AnswersDaoMock::new();
+
+        answers_dao.mock_delete_answer(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+
+        let result = delete_answer(answer_id, answers_dao.as_ref()).await;
+
+        assert!(result.is_err());
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+        );
     }
 
-    struct AnswersDaoMock {
-        create_answer_response: Mutex<Option<Result<AnswerDetail, DBError>>>,
-        delete_answer_response: Mutex<Option<Result<(), DBError>>>,
-        get_answers_response: Mutex<Option<Result<Vec<AnswerDetail>, DBError>>>,
+    struct IncidentsDaoMock {
+        create_incident_response: Mutex<Option<Result<IncidentDetail, DBError>>>,
+        resolve_incident_response: Mutex<Option<Result<(), DBError>>>,
+        get_active_incidents_response: Mutex<Option<Result<Vec<IncidentDetail>, DBError>>>,
     }
 
-    impl AnswersDaoMock {
+    impl IncidentsDaoMock {
         pub fn new() -> Self {
-            AnswersDaoMock {
-                create_answer_response: Mutex::new(None),
-                delete_answer_response: Mutex::new(None),
-                get_answers_response: Mutex::new(None),
+            IncidentsDaoMock {
+                create_incident_response: Mutex::new(None),
+                resolve_incident_response: Mutex::new(None),
+                get_active_incidents_response: Mutex::new(None),
             }
         }
-        pub fn mock_create_answer(&mut self, response: Result<AnswerDetail, DBError>) {
-            self.create_answer_response = Mutex::new(Some(response));
+        pub fn mock_create_incident(&mut self, response: Result<IncidentDetail, DBError>) {
+            self.create_incident_response = Mutex::new(Some(response));
         }
-        pub fn mock_delete_answer(&mut self, response: Result<(), DBError>) {
-            self.delete_answer_response = Mutex::new(Some(response));
+        pub fn mock_resolve_incident(&mut self, response: Result<(), DBError>) {
+            self.resolve_incident_response = Mutex::new(Some(response));
         }
-        pub fn mock_get_answers(&mut self, response: Result<Vec<AnswerDetail>, DBError>) {
-            self.get_answers_response = Mutex::new(Some(response));
+        pub fn mock_get_active_incidents(&mut self, response: Result<Vec<IncidentDetail>, DBError>) {
+            self.get_active_incidents_response = Mutex::new(Some(response));
         }
     }
 
     #[async_trait]
-    impl AnswersDao for AnswersDaoMock {
-        async fn create_answer(&self, _: Answer) -> Result<AnswerDetail, DBError> {
-            self.create_answer_response
+    impl IncidentsDao for IncidentsDaoMock {
+        async fn create_incident(&self, _: Incident) -> Result<IncidentDetail, DBError> {
+            self.create_incident_response
                 .lock()
                 .await
                 .take()
-                .expect("create_answer_response should not be None.")
+                .expect("create_incident_response should not be None.")
         }
-        async fn delete_answer(&self, _: String) -> Result<(), DBError> {
-            self.delete_answer_response
+        async fn resolve_incident(&self, _: Uuid) -> Result<(), DBError> {
+            self.resolve_incident_response
                 .lock()
                 .await
                 .take()
-                .expect("delete_answer_response should not be None.")
+                .expect("resolve_incident_response should not be None.")
         }
-        async fn get_answers(&self, _: String) -> Result<Vec<AnswerDetail>, DBError> {
-            self.get_answers_response
+        async fn get_active_incidents(&self) -> Result<Vec<IncidentDetail>, DBError> {
+            self.get_active_incidents_response
                 .lock()
                 .await
                 .take()
-                .expect("get_answers_response should not be None.")
+                .expect("get_active_incidents_response should not be None.")
         }
     }
 
-    #[tokio::test]
-    async fn create_question_should_return_question() {
-        let question = Question {
+    struct HealthCheckMock {
+        response: Result<(), String>,
+    }
+
+    #[async_trait]
+    impl HealthCheck for HealthCheckMock {
+        fn name(&self) -> &'static str {
+            "database"
+        }
+        async fn check(&self) -> Result<(), String> {
+            self.response.clone()
+        }
+    }
+
+    fn incident_detail() -> IncidentDetail {
+        IncidentDetail {
+            incident_uuid: Uuid::from_u128(789),
             title: "test title".to_owned(),
             description: "test description".to_owned(),
-        };
-
-        let question_detail = QuestionDetail {
-            question_uuid: "123".to_owned(),
-            title: question.title.clone(),
-            description: question.description.clone(),
-            created_at: "now".to_owned(),
-        };
+            created_at: DateTime::UNIX_EPOCH,
+            resolved_at: None,
+        }
+    }
 
-        let mut questions_dao = QuestionsDaoMock::new();
+    #[tokio::test]
+    async fn read_status_should_be_operational() {
+        let mut incidents_dao = IncidentsDaoMock::new();
 
-        questions_dao.mock_create_question(Ok(question_detail.clone()));
+        incidents_dao.mock_get_active_incidents(Ok(vec![]));
 
-        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let health_checks: Vec<Arc<dyn HealthCheck + Send + Sync>> = vec![Arc::new(HealthCheckMock { response: Ok(()) })];
 
-        let result = create_question(question, questions_dao.as_ref()).await;
+        let result = read_status(Duration::from_secs(42), &health_checks, &incidents_dao).await;
 
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), question_detail);
+        assert_eq!(result.status, ServiceStatus::Operational);
+        assert_eq!(result.uptime_secs, 42);
+        assert!(result.degradations.is_empty());
     }
 
     #[tokio::test]
-    async fn create_question_should_return_error() {
-        let question = Question {
-            title: "test title".to_owned(),
-            description: "test description".to_owned(),
-        };
+    async fn read_status_should_be_degraded_with_active_incident() {
+        let mut incidents_dao = IncidentsDaoMock::new();
 
-        let mut questions_dao = QuestionsDaoMock::new();
+        incidents_dao.mock_get_active_incidents(Ok(vec![incident_detail()]));
 
-        questions_dao.mock_create_question(Err(DBError::InvalidUUID("test".to_owned())));
+        let health_checks: Vec<Arc<dyn HealthCheck + Send + Sync>> = vec![Arc::new(HealthCheckMock { response: Ok(()) })];
 
-        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let result = read_status(Duration::ZERO, &health_checks, &incidents_dao).await;
 
-        let result = create_question(question, questions_dao.as_ref()).await;
+        assert_eq!(result.status, ServiceStatus::Degraded);
+        assert_eq!(result.incidents, vec![incident_detail()]);
+    }
 
-        assert!(result.is_err());
-        assert!(
-            std::mem::discriminant(&result.unwrap_err())
-                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
-        );
+    #[tokio::test]
+    async fn read_status_should_report_failing_dependency() {
+        let mut incidents_dao = IncidentsDaoMock::new();
+
+        incidents_dao.mock_get_active_incidents(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let health_checks: Vec<Arc<dyn HealthCheck + Send + Sync>> =
+            vec![Arc::new(HealthCheckMock { response: Err("connection refused".to_owned()) })];
+
+        let result = read_status(Duration::ZERO, &health_checks, &incidents_dao).await;
+
+        assert_eq!(result.status, ServiceStatus::Degraded);
+        assert_eq!(result.degradations.len(), 1);
+        assert!(result.incidents.is_empty());
     }
 
     #[tokio::test]
-    async fn read_questions_should_return_questions() {
-        let question_detail = QuestionDetail {
-            question_uuid: "123".to_owned(),
+    async fn create_incident_should_return_incident() {
+        let incident = Incident {
             title: "test title".to_owned(),
             description: "test description".to_owned(),
-            created_at: "now".to_owned(),
         };
 
-        let mut questions_dao = QuestionsDaoMock::new();
-
-        questions_dao.mock_get_questions(Ok(vec![question_detail.clone()]));
+        let mut incidents_dao = IncidentsDaoMock::new();
 
-        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        incidents_dao.mock_create_incident(Ok(incident_detail()));
 
-        let result = read_questions(questions_dao.as_ref()).await;
+        let result = create_incident(incident, &incidents_dao).await;
 
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), vec![question_detail]);
+        assert_eq!(result, Ok(incident_detail()));
     }
 
     #[tokio::test]
-    async fn read_questions_should_return_error() {
-        let mut questions_dao = QuestionsDaoMock::new();
+    async fn resolve_incident_should_return_bad_request_error() {
+        let incident_id = IncidentId {
+            incident_uuid: Uuid::from_u128(789),
+        };
 
-        questions_dao.mock_get_questions(Err(DBError::InvalidUUID("test".to_owned())));
+        let mut incidents_dao = IncidentsDaoMock::new();
 
-        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        incidents_dao.mock_resolve_incident(Err(DBError::InvalidUUID("test".to_owned())));
 
-        let result = read_questions(questions_dao.as_ref()).await;
+        let result = resolve_incident(incident_id, &incidents_dao).await;
 
-        assert!(result.is_err());
         assert!(
             std::mem::discriminant(&result.unwrap_err())
-                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
         );
     }
 
-    #[tokio::test]
-    async fn delete_question_should_succeed() {
-        let question_id = QuestionId {
-            question_uuid: "123".to_owned(),
-        };
-
-        let mut questions_dao = QuestionsDaoMock::new();
-
-        questions_dao.mock_delete_question(Ok(()));
+    struct WebhooksDaoMock {
+        create_webhook_response: Mutex<Option<Result<WebhookDetail, DBError>>>,
+        delete_webhook_response: Mutex<Option<Result<(), DBError>>>,
+        get_webhooks_response: Mutex<Option<Result<Vec<WebhookDetail>, DBError>>>,
+        create_question_webhook_response: Mutex<Option<Result<QuestionWebhookDetail, DBError>>>,
+        create_question_webhook_args: Mutex<Option<DateTime<Utc>>>,
+    }
 
-        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+    impl WebhooksDaoMock {
+        pub fn new() -> Self {
+            WebhooksDaoMock {
+                create_webhook_response: Mutex::new(None),
+                delete_webhook_response: Mutex::new(None),
+                get_webhooks_response: Mutex::new(None),
+                create_question_webhook_response: Mutex::new(None),
+                create_question_webhook_args: Mutex::new(None),
+            }
+        }
+        pub fn mock_create_webhook(&mut self, response: Result<WebhookDetail, DBError>) {
+            self.create_webhook_response = Mutex::new(Some(response));
+        }
+        pub fn mock_delete_webhook(&mut self, response: Result<(), DBError>) {
+            self.delete_webhook_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_webhooks(&mut self, response: Result<Vec<WebhookDetail>, DBError>) {
+            self.get_webhooks_response = Mutex::new(Some(response));
+        }
+        pub fn mock_create_question_webhook(&mut self, response: Result<QuestionWebhookDetail, DBError>) {
+            self.create_question_webhook_response = Mutex::new(Some(response));
+        }
+    }
 
-        let result = delete_question(question_id, questions_dao.as_ref()).await;
+    #[async_trait]
+    impl WebhooksDao for WebhooksDaoMock {
+        async fn create_webhook(&self, _: Webhook) -> Result<WebhookDetail, DBError> {
+            self.create_webhook_response
+                .lock()
+                .await
+                .take()
+                .expect("create_webhook_response should not be None.")
+        }
+        async fn delete_webhook(&self, _: Uuid) -> Result<(), DBError> {
+            self.delete_webhook_response
+                .lock()
+                .await
+                .take()
+                .expect("delete_webhook_response should not be None.")
+        }
+        async fn get_webhooks(&self) -> Result<Vec<WebhookDetail>, DBError> {
+            self.get_webhooks_response
+                .lock()
+                .await
+                .take()
+                .expect("get_webhooks_response should not be None.")
+        }
+        async fn create_question_webhook(
+            &self,
+            _: QuestionUuid,
+            _: Webhook,
+            expires_at: DateTime<Utc>,
+        ) -> Result<QuestionWebhookDetail, DBError> {
+            *self.create_question_webhook_args.lock().await = Some(expires_at);
+            self.create_question_webhook_response
+                .lock()
+                .await
+                .take()
+                .expect("create_question_webhook_response should not be None.")
+        }
+        async fn get_question_webhooks(&self, _: QuestionUuid) -> Result<Vec<QuestionWebhookDetail>, DBError> {
+            unimplemented!()
+        }
+        async fn delete_expired_question_webhooks(&self, _: DateTime<Utc>) -> Result<u64, DBError> {
+            unimplemented!()
+        }
+    }
 
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), ());
+    fn webhook_detail() -> WebhookDetail {
+        WebhookDetail {
+            webhook_uuid: Uuid::from_u128(321),
+            url: "https://hooks.example.com/qna".to_owned(),
+            secret: "0123456789abcdef".to_owned(),
+            created_at: DateTime::UNIX_EPOCH,
+        }
     }
 
     #[tokio::test]
-    async fn delete_question_should_return_error() {
-        let question_id = QuestionId {
-            question_uuid: "123".to_owned(),
+    async fn create_webhook_should_return_webhook() {
+        let webhook = Webhook {
+            url: "https://hooks.example.com/qna".to_owned(),
+            secret: "0123456789abcdef".to_owned(),
         };
 
-        let mut questions_dao = QuestionsDaoMock::new();
+        let mut webhooks_dao = WebhooksDaoMock::new();
 
-        questions_dao.mock_delete_question(Err(DBError::InvalidUUID("test".to_owned())));
+        webhooks_dao.mock_create_webhook(Ok(webhook_detail()));
 
-        let questions_dao: Box<dyn QuestionsDao + Send + Sync> = Box::new(questions_dao);
+        let result = create_webhook(webhook, &webhooks_dao).await;
 
-        let result = delete_question(question_id, questions_dao.as_ref()).await;
+        assert_eq!(result, Ok(webhook_detail()));
+    }
+
+    #[tokio::test]
+    async fn read_webhooks_should_return_internal_error() {
+        let mut webhooks_dao = WebhooksDaoMock::new();
+
+        webhooks_dao.mock_get_webhooks(Err(DBError::Other(Box::new(std::io::Error::other("test")))));
+
+        let result = read_webhooks(&webhooks_dao).await;
 
-        assert!(result.is_err());
         assert!(
             std::mem::discriminant(&result.unwrap_err())
                 == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
@@ -401,160 +2518,214 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn create_answer_should_return_answer() {
-        let answer = Answer {
-            question_uuid: "123".to_owned(),
-            content: "test content".to_owned(),
-        };
-
-        let answer_detail = AnswerDetail {
-            answer_uuid: "456".to_owned(),
-            question_uuid: answer.question_uuid.clone(),
-            content: answer.content.clone(),
-            created_at: "now".to_owned(),
+    async fn delete_webhook_should_return_bad_request_error() {
+        let webhook_id = WebhookId {
+            webhook_uuid: Uuid::from_u128(321),
         };
 
-        let mut answers_dao = AnswersDaoMock::new();
+        let mut webhooks_dao = WebhooksDaoMock::new();
 
-        answers_dao.mock_create_answer(Ok(answer_detail.clone()));
+        webhooks_dao.mock_delete_webhook(Err(DBError::InvalidUUID("test".to_owned())));
 
-        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+        let result = delete_webhook(webhook_id, &webhooks_dao).await;
 
-        let result = create_answer(answer, answers_dao.as_ref()).await;
+        assert!(
+            std::mem::discriminant(&result.unwrap_err())
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
+        );
+    }
 
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), answer_detail);
+    fn question_webhook(ttl_hours: Option<u32>) -> QuestionWebhook {
+        QuestionWebhook {
+            url: "https://hooks.example.com/qna".to_owned(),
+            secret: "0123456789abcdef".to_owned(),
+            ttl_hours,
+        }
     }
 
     #[tokio::test]
-    async fn create_answer_should_return_bad_request_error() {
-        let answer = Answer {
-            question_uuid: "123".to_owned(),
-            content: "test content".to_owned(),
+    async fn create_question_webhook_should_expire_after_a_week_by_default() {
+        let detail = QuestionWebhookDetail {
+            webhook_uuid: Uuid::from_u128(321),
+            question_uuid: QuestionUuid::from(Uuid::from_u128(123)),
+            url: "https://hooks.example.com/qna".to_owned(),
+            secret: "0123456789abcdef".to_owned(),
+            created_at: DateTime::UNIX_EPOCH,
+            expires_at: DateTime::UNIX_EPOCH + TimeDelta::weeks(1),
         };
 
-        let mut answers_dao = AnswersDaoMock::new();
+        let mut webhooks_dao = WebhooksDaoMock::new();
 
-        answers_dao.mock_create_answer(Err(DBError::InvalidUUID("test".to_owned())));
+        webhooks_dao.mock_create_question_webhook(Ok(detail.clone()));
 
-        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+        let before = Utc::now();
+        let result = create_question_webhook(detail.question_uuid, question_webhook(None), &webhooks_dao).await;
 
-        let result = create_answer(answer, answers_dao.as_ref()).await;
+        assert_eq!(result, Ok(detail));
+
+        let expires_at = webhooks_dao.create_question_webhook_args.lock().await.unwrap();
+        assert!(expires_at >= before + TimeDelta::weeks(1));
+        assert!(expires_at <= Utc::now() + TimeDelta::weeks(1));
+    }
+
+    #[tokio::test]
+    async fn create_question_webhook_should_return_bad_request_error() {
+        let mut webhooks_dao = WebhooksDaoMock::new();
+
+        webhooks_dao.mock_create_question_webhook(Err(DBError::InvalidUUID("test".to_owned())));
+
+        let result = create_question_webhook(QuestionUuid::new_v4(), question_webhook(Some(1)), &webhooks_dao).await;
 
-        assert!(result.is_err());
         assert!(
             std::mem::discriminant(&result.unwrap_err())
                 == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
         );
     }
 
-    #[tokio::test]
-    async fn create_answer_should_return_internal_error() {
-        let answer = Answer {
-            question_uuid: "123".to_owned(),
-            content: "test content".to_owned(),
-        };
-
-        let mut answers_dao = AnswersDaoMock::new();
+    struct MaintenanceDaoMock {
+        get_maintenance_report_response: Mutex<Option<Result<MaintenanceReport, DBError>>>,
+    }
 
-        answers_dao.mock_create_answer(Err(DBError::Other(Box::new(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "oh no!",
-        )))));
+    #[async_trait]
+    impl MaintenanceDao for MaintenanceDaoMock {
+        async fn get_maintenance_report(&self) -> Result<MaintenanceReport, DBError> {
+            self.get_maintenance_report_response
+                .lock()
+                .await
+                .take()
+                .expect("get_maintenance_report_response should not be None.")
+        }
+    }
 
-        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+    #[tokio::test]
+    async fn read_maintenance_should_return_internal_error() {
+        let maintenance_dao = MaintenanceDaoMock {
+            get_maintenance_report_response: Mutex::new(Some(Err(DBError::Other(Box::new(std::io::Error::other("test")))))),
+        };
 
-        let result = create_answer(answer, answers_dao.as_ref()).await;
+        let result = read_maintenance(&maintenance_dao).await;
 
-        assert!(result.is_err());
         assert!(
             std::mem::discriminant(&result.unwrap_err())
                 == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
         );
     }
 
-    #[tokio::test]
-    async fn read_answers_should_return_answers() {
-        let answer_detail = AnswerDetail {
-            answer_uuid: "456".to_owned(),
-            question_uuid: "123".to_owned(),
-            content: "test content".to_owned(),
-            created_at: "now".to_owned(),
-        };
+    struct JobsDaoMock {
+        create_job_response: Mutex<Option<Result<JobDetail, DBError>>>,
+        get_job_response: Mutex<Option<Result<JobDetail, DBError>>>,
+        get_running_jobs_response: Mutex<Option<Result<Vec<JobDetail>, DBError>>>,
+    }
 
-        let question_id = QuestionId {
-            question_uuid: "123".to_owned(),
-        };
+    impl JobsDaoMock {
+        pub fn new() -> Self {
+            JobsDaoMock {
+                create_job_response: Mutex::new(None),
+                get_job_response: Mutex::new(None),
+                get_running_jobs_response: Mutex::new(None),
+            }
+        }
+        pub fn mock_create_job(&mut self, response: Result<JobDetail, DBError>) {
+            self.create_job_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_job(&mut self, response: Result<JobDetail, DBError>) {
+            self.get_job_response = Mutex::new(Some(response));
+        }
+        pub fn mock_get_running_jobs(&mut self, response: Result<Vec<JobDetail>, DBError>) {
+            self.get_running_jobs_response = Mutex::new(Some(response));
+        }
+    }
 
-        let mut answers_dao = AnswersDaoMock::new();
+    #[async_trait]
+    impl JobsDao for JobsDaoMock {
+        async fn create_job(&self, _: JobKind) -> Result<JobDetail, DBError> {
+            self.create_job_response
+                .lock()
+                .await
+                .take()
+                .expect("create_job_response should not be None.")
+        }
+        async fn get_job(&self, _: JobUuid) -> Result<JobDetail, DBError> {
+            self.get_job_response
+                .lock()
+                .await
+                .take()
+                .expect("get_job_response should not be None.")
+        }
+        async fn get_running_jobs(&self) -> Result<Vec<JobDetail>, DBError> {
+            self.get_running_jobs_response
+                .lock()
+                .await
+                .take()
+                .expect("get_running_jobs_response should not be None.")
+        }
+        async fn update_job(&self, _: &JobDetail) -> Result<JobDetail, DBError> {
+            unimplemented!("No handler updates jobs")
+        }
+    }
 
-        answers_dao.mock_get_answers(Ok(vec![answer_detail.clone()]));
+    fn job_detail() -> JobDetail {
+        JobDetail {
+            job_uuid: Uuid::from_u128(654).into(),
+            kind: JobKind::ReindexSearch,
+            status: JobStatus::Running,
+            processed: 0,
+            cursor: None,
+            last_error: None,
+            created_at: DateTime::UNIX_EPOCH,
+            updated_at: DateTime::UNIX_EPOCH,
+        }
+    }
 
-        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+    #[tokio::test]
+    async fn create_job_should_return_job() {
+        let mut jobs_dao = JobsDaoMock::new();
 
-        let result = read_answers(question_id, answers_dao.as_ref()).await;
+        jobs_dao.mock_get_running_jobs(Ok(vec![]));
+        jobs_dao.mock_create_job(Ok(job_detail()));
 
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), vec![answer_detail]);
+        let result = create_job(JobKind::ReindexSearch, &jobs_dao).await;
+
+        assert_eq!(result, Ok(job_detail()));
     }
 
     #[tokio::test]
-    async fn read_answers_should_return_error() {
-        let question_id = QuestionId {
-            question_uuid: "123".to_owned(),
-        };
-
-        let mut answers_dao = AnswersDaoMock::new();
-
-        answers_dao.mock_get_answers(Err(DBError::InvalidUUID("test".to_owned())));
+    async fn create_job_should_return_conflict_error_while_one_is_running() {
+        let mut jobs_dao = JobsDaoMock::new();
 
-        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+        jobs_dao.mock_get_running_jobs(Ok(vec![job_detail()]));
 
-        let result = read_answers(question_id, answers_dao.as_ref()).await;
+        let result = create_job(JobKind::ReindexSearch, &jobs_dao).await;
 
-        assert!(result.is_err());
         assert!(
             std::mem::discriminant(&result.unwrap_err())
-                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+                == std::mem::discriminant(&HandlerError::Conflict("".to_owned()))
         );
     }
 
     #[tokio::test]
-    async fn delete_answer_should_succeed() {
-        let answer_id = AnswerId {
-            answer_uuid: "123".to_owned(),
-        };
-
-        let mut answers_dao = AnswersDaoMock::new();
-
-        answers_dao.mock_delete_answer(Ok(()));
+    async fn create_job_should_return_conflict_error_if_one_started_meanwhile() {
+        let mut jobs_dao = JobsDaoMock::new();
 
-        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+        jobs_dao.mock_get_running_jobs(Ok(vec![]));
+        jobs_dao.mock_create_job(Err(DBError::Conflict("A reindex_search job is already running".to_owned())));
 
-        let result = delete_answer(answer_id, answers_dao.as_ref()).await;
+        let result = create_job(JobKind::ReindexSearch, &jobs_dao).await;
 
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), ());
+        assert_eq!(result, Err(HandlerError::Conflict("A reindex_search job is already running".to_owned())));
     }
 
     #[tokio::test]
-    async fn delete_answer_should_return_error() {
-        let answer_id = AnswerId {
-            answer_uuid: "123".to_owned(),
-        };
-
-        let mut answers_dao = AnswersDaoMock::new();
+    async fn read_job_should_return_bad_request_error() {
+        let mut jobs_dao = JobsDaoMock::new();
 
-        answers_dao.mock_delete_answer(Err(DBError::InvalidUUID("test".to_owned())));
-
-        let answers_dao: Box<dyn AnswersDao + Send + Sync> = Box::new(answers_dao);
+        jobs_dao.mock_get_job(Err(DBError::InvalidUUID("test".to_owned())));
 
-        let result = delete_answer(answer_id, answers_dao.as_ref()).await;
+        let result = read_job(JobUuid::new_v4(), &jobs_dao).await;
 
-        assert!(result.is_err());
         assert!(
             std::mem::discriminant(&result.unwrap_err())
-                == std::mem::discriminant(&HandlerError::InternalError("".to_owned()))
+                == std::mem::discriminant(&HandlerError::BadRequest("".to_owned()))
         );
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,611 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    body::Bytes,
+    extract::{Request, State as AxumState},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use validator::Validate;
+
+use crate::{
+    auth::constant_time_eq, cache_control::add_vary, client_ip::ClientIp, models::*, normalize::Normalize, print,
+    redact::redact, AppState,
+};
+
+use super::{extract::field_errors, handlers_inner, HandlerError};
+
+// AI assistants call the API as tools over the Model Context Protocol, a JSON-RPC 2.0 dialect, each request being
+// answered in a single JSON response rather than a stream. Assistants only get the tools their token grants, and
+// none of them writes: a drafted answer is checked and returned, for a person to post.
+
+/// Version of the Model Context Protocol spoken, answered to clients asking for any version
+const PROTOCOL_VERSION: &str = "2025-06-18";
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Represents the tools a token grants
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolScope {
+    /// Searching questions
+    Search,
+    /// Reading a question with its answers
+    Read,
+    /// Drafting answers, which are never posted
+    Draft,
+}
+
+impl FromStr for ToolScope {
+    type Err = String;
+
+    fn from_str(scope: &str) -> Result<Self, Self::Err> {
+        match scope {
+            "search" => Ok(ToolScope::Search),
+            "read" => Ok(ToolScope::Read),
+            "draft" => Ok(ToolScope::Draft),
+            _ => Err(format!("Unknown tool scope: {}", scope)),
+        }
+    }
+}
+
+/// Bearer tokens of AI assistants and the scopes each grants
+#[derive(Debug)]
+pub struct ToolTokens {
+    tokens: Vec<(String, Arc<[ToolScope]>)>,
+}
+
+impl ToolTokens {
+
+    /// Creates the set of tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - Each token and the scopes it grants.
+    ///
+    /// # Returns
+    ///
+    /// A `ToolTokens` authorizing tool calls.
+    pub fn new(tokens: impl IntoIterator<Item = (String, Vec<ToolScope>)>) -> Self {
+        let tokens = tokens.into_iter().map(|(token, scopes)| (token, scopes.into())).collect();
+
+        ToolTokens { tokens }
+    }
+
+    /// The scopes granted by a token, `None` if it is not one of them.
+    fn scopes(&self, provided: &str) -> Option<GrantedScopes> {
+        // Every token is compared, so timings do not tell which one a guess is closest to
+        self.tokens.iter().fold(None, |granted, (token, scopes)| {
+            match constant_time_eq(provided.as_bytes(), token.as_bytes()) {
+                true => Some(GrantedScopes(scopes.clone())),
+                false => granted,
+            }
+        })
+    }
+}
+
+/// The scopes granted to the request, set by `require_tool_token`
+#[derive(Debug, Clone)]
+pub struct GrantedScopes(Arc<[ToolScope]>);
+
+/// Middleware rejecting requests that don't carry one of the tool tokens as a bearer token.
+///
+/// # Arguments
+///
+/// * `State(tokens)` - The configured tool tokens.
+/// * `client_ip` - The address of the client, if known, for the audit log.
+/// * `request` - The incoming request.
+/// * `next` - The rest of the middleware stack.
+///
+/// # Returns
+///
+/// The response of the inner service, given the scopes of the token, if it matches, otherwise a
+/// `401 Unauthorized` response. Either depends on the `Authorization` header, which `Vary` names.
+pub async fn require_tool_token(
+    AxumState(tokens): AxumState<Arc<ToolTokens>>,
+    client_ip: Option<ClientIp>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let scopes = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .and_then(|token| tokens.scopes(token));
+
+    let mut response = match scopes {
+        Some(scopes) => {
+            request.extensions_mut().insert(scopes);
+            next.run(request).await
+        }
+        None => {
+            let client = client_ip.map_or_else(|| "an unknown address".to_owned(), |ip| ip.to_string());
+            warn!("Rejected tool request from {}.", client);
+            (StatusCode::UNAUTHORIZED, "A valid tool token is required.").into_response()
+        }
+    };
+
+    add_vary(response.headers_mut(), header::AUTHORIZATION);
+    response
+}
+
+/// A tool assistants may call
+struct Tool {
+    name: &'static str,
+    description: &'static str,
+    scope: ToolScope,
+    input_schema: fn() -> Value,
+}
+
+impl Tool {
+
+    /// The definition of the tool listed to assistants.
+    fn definition(&self) -> Value {
+        json!({ "name": self.name, "description": self.description, "inputSchema": (self.input_schema)() })
+    }
+}
+
+const TOOLS: [Tool; 3] = [
+    Tool {
+        name: "search_questions",
+        description: "Searches the titles and descriptions of questions for words, best match first.",
+        scope: ToolScope::Search,
+        input_schema: || {
+            json!({
+                "type": "object",
+                "properties": {
+                    "q": { "type": "string", "description": "Words to search for" },
+                    "limit": {
+                        "type": "integer", "minimum": 1, "maximum": 100, "description": "Most results, 20 by default"
+                    }
+                },
+                "required": ["q"]
+            })
+        },
+    },
+    Tool {
+        name: "get_question",
+        description: "Reads a question along with all of its answers, oldest first.",
+        scope: ToolScope::Read,
+        input_schema: || {
+            json!({
+                "type": "object",
+                "properties": { "question_uuid": { "type": "string", "format": "uuid" } },
+                "required": ["question_uuid"]
+            })
+        },
+    },
+    Tool {
+        name: "draft_answer",
+        description: "Checks an answer to an open question and returns it as it would be posted. The draft is not \
+            posted: a person reviews and posts it.",
+        scope: ToolScope::Draft,
+        input_schema: || {
+            json!({
+                "type": "object",
+                "properties": {
+                    "question_uuid": { "type": "string", "format": "uuid" },
+                    "content": { "type": "string", "minLength": 1, "maxLength": 255 }
+                },
+                "required": ["question_uuid", "content"]
+            })
+        },
+    },
+];
+
+/// Represents a JSON-RPC request, or a notification when it has no `id`
+#[derive(Deserialize)]
+struct RpcRequest {
+    jsonrpc: String,
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// Represents the parameters of a `tools/call` request
+#[derive(Deserialize)]
+struct ToolCall {
+    name: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+/// Represents the arguments of the `draft_answer` tool
+#[derive(Deserialize)]
+struct AnswerDraft {
+    question_uuid: QuestionUuid,
+    content: String,
+}
+
+/// A JSON-RPC error, answered in place of a result
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RpcError { code, message: message.into() }
+    }
+}
+
+/// Asynchronously answers a Model Context Protocol request of an AI assistant.
+///
+/// # Arguments
+///
+/// * `AxumState(state)` - The application state whose DAOs the tools use.
+/// * `Extension(scopes)` - The scopes granted by the token of the request.
+/// * `body` - The JSON-RPC request.
+///
+/// # Returns
+///
+/// The JSON-RPC response, errors included with a `200 OK` status as JSON-RPC clients expect, or an empty
+/// `202 Accepted` response to a notification.
+pub async fn serve(
+    AxumState(state): AxumState<AppState>,
+    Extension(scopes): Extension<GrantedScopes>,
+    body: Bytes,
+) -> Response {
+    let request = match serde_json::from_slice::<Value>(&body) {
+        Ok(request) => request,
+        Err(_) => return reply(Value::Null, Err(RpcError::new(PARSE_ERROR, "Parse error"))),
+    };
+
+    let request = match serde_json::from_value::<RpcRequest>(request) {
+        Ok(request) if request.jsonrpc == "2.0" => request,
+        _ => return reply(Value::Null, Err(RpcError::new(INVALID_REQUEST, "Invalid request"))),
+    };
+
+    // Notifications, such as `notifications/initialized`, need no response
+    let Some(id) = request.id else {
+        return StatusCode::ACCEPTED.into_response();
+    };
+
+    let result = match request.method.as_str() {
+        "initialize" => Ok(json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": env!("CARGO_PKG_NAME"), "version": env!("CARGO_PKG_VERSION") },
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => {
+            let tools: Vec<Value> = granted_tools(&scopes).map(Tool::definition).collect();
+            Ok(json!({ "tools": tools }))
+        }
+        "tools/call" => call_tool(&state, &scopes, request.params).await,
+        method => Err(RpcError::new(METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+    };
+
+    reply(id, result)
+}
+
+/// The tools granted by scopes, in the order they are listed.
+fn granted_tools(scopes: &GrantedScopes) -> impl Iterator<Item = &'static Tool> + '_ {
+    TOOLS.iter().filter(|tool| scopes.0.contains(&tool.scope))
+}
+
+fn reply(id: Value, result: Result<Value, RpcError>) -> Response {
+    let response = match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(RpcError { code, message }) => {
+            json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+        }
+    };
+
+    Json(response).into_response()
+}
+
+/// Calls a tool, its failures being reported in its result, for the assistant to correct its call.
+///
+/// # Returns
+///
+/// The result of the tool, or an `RpcError` if the tool is unknown or not granted, which are not told apart.
+async fn call_tool(state: &AppState, scopes: &GrantedScopes, params: Value) -> Result<Value, RpcError> {
+    let call: ToolCall = serde_json::from_value(params)
+        .map_err(|err| RpcError::new(INVALID_PARAMS, format!("Invalid params: {}", err)))?;
+
+    let tool = granted_tools(scopes)
+        .find(|tool| tool.name == call.name)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("Unknown tool: {}", call.name)))?;
+
+    let result = match tool.scope {
+        ToolScope::Search => search_questions(state, call.arguments).await,
+        ToolScope::Read => get_question(state, call.arguments).await,
+        ToolScope::Draft => draft_answer(state, call.arguments).await,
+    };
+
+    Ok(match result {
+        Ok((structured, text)) => json!({
+            "content": [{ "type": "text", "text": text }],
+            "structuredContent": structured,
+            "isError": false,
+        }),
+        Err(message) => json!({ "content": [{ "type": "text", "text": redact(&message) }], "isError": true }),
+    })
+}
+
+/// The result of a tool, as structured content and the text of its content, or the message of its failure
+type ToolResult = Result<(Value, String), String>;
+
+async fn search_questions(state: &AppState, arguments: Value) -> ToolResult {
+    let search: QuestionSearch = tool_arguments(arguments)?;
+
+    let questions =
+        handlers_inner::search_questions(search, state.questions_dao.as_ref(), state.search_index.as_deref())
+            .await
+            .map_err(handler_message)?;
+
+    let structured = json!({ "questions": questions });
+    Ok((structured.clone(), structured.to_string()))
+}
+
+async fn get_question(state: &AppState, arguments: Value) -> ToolResult {
+    let question_id: QuestionId = tool_arguments(arguments)?;
+
+    let question = handlers_inner::read_question_with_answers(question_id, state.questions_dao.as_ref())
+        .await
+        .map_err(handler_message)?;
+
+    // Text reads better than JSON to a model, and keeps the markdown out of its context
+    let text = print::render_question(&question);
+    Ok((json!(question), text))
+}
+
+async fn draft_answer(state: &AppState, arguments: Value) -> ToolResult {
+    let AnswerDraft { question_uuid, content } = tool_arguments(arguments)?;
+
+    let answer = Answer { answer_uuid: None, question_uuid, content };
+    answer.validate().map_err(|errors| {
+        let messages: Vec<String> = field_errors(errors)
+            .into_iter()
+            .map(|error| format!("{}: {}", error.field.unwrap_or_default(), error.message))
+            .collect();
+
+        format!("Invalid input: {}", messages.join("; "))
+    })?;
+    let answer = answer.normalize();
+
+    let lookup = QuestionsLookup { question_uuids: vec![question_uuid] };
+    let questions = handlers_inner::lookup_questions(lookup, state.questions_dao.as_ref())
+        .await
+        .map_err(handler_message)?;
+
+    match questions.first() {
+        None => return Err(format!("No question with UUID {}", question_uuid)),
+        Some(question) if question.status != QuestionStatus::Open => {
+            return Err(format!("The question is {} and does not accept answers", question.status));
+        }
+        Some(_) => {}
+    }
+
+    let text = format!("Draft answer, not posted, for a person to review and post:\n\n{}", answer.content);
+    Ok((json!({ "question_uuid": question_uuid, "content": answer.content, "posted": false }), text))
+}
+
+/// Reads the arguments of a tool, absent arguments standing for none.
+fn tool_arguments<T: DeserializeOwned>(arguments: Value) -> Result<T, String> {
+    let arguments = if arguments.is_null() { json!({}) } else { arguments };
+
+    serde_json::from_value(arguments).map_err(|err| format!("Invalid arguments: {}", err))
+}
+
+fn handler_message(error: HandlerError) -> String {
+    match error {
+        HandlerError::BadRequest(message) | HandlerError::Conflict(message) | HandlerError::InternalError(message) => {
+            message
+        }
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::{body::Body, middleware::from_fn_with_state, routing::post, Router};
+    use tower::ServiceExt;
+
+    use crate::{config::Config, test_support::QuestionBuilder};
+
+    const SEARCH_TOKEN: &str = "search-0123456789";
+    const ALL_TOKEN: &str = "all-0123456789abc";
+
+    fn app(state: AppState) -> Router {
+        let tokens = ToolTokens::new([
+            (SEARCH_TOKEN.to_owned(), vec![ToolScope::Search]),
+            (ALL_TOKEN.to_owned(), vec![ToolScope::Search, ToolScope::Read, ToolScope::Draft]),
+        ]);
+
+        Router::new()
+            .route("/mcp", post(serve))
+            .route_layer(from_fn_with_state(Arc::new(tokens), require_tool_token))
+            .with_state(state)
+    }
+
+    async fn send(app: &Router, token: &str, body: Value) -> (StatusCode, Value) {
+        let request = Request::post("/mcp")
+            .header(header::AUTHORIZATION, format!("Bearer {}", token))
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+    }
+
+    async fn call(app: &Router, token: &str, name: &str, arguments: Value) -> Value {
+        let request = json!({
+            "jsonrpc": "2.0", "id": 1, "method": "tools/call", "params": { "name": name, "arguments": arguments }
+        });
+
+        send(app, token, request).await.1
+    }
+
+    async fn state_with_question(status: QuestionStatus) -> (AppState, QuestionDetail) {
+        let state = AppState::in_memory(&Config::default());
+        let question = state
+            .questions_dao
+            .create_question(QuestionBuilder::new().title("How do I split a crate?").build())
+            .await
+            .unwrap();
+
+        if status != QuestionStatus::Open {
+            let questions_dao = &state.questions_dao;
+            questions_dao.update_question_status(question.question_uuid, QuestionStatus::Open, status).await.unwrap();
+        }
+
+        (state, question)
+    }
+
+    #[test]
+    fn should_parse_tool_scopes() {
+        assert_eq!("search".parse(), Ok(ToolScope::Search));
+        assert_eq!("read".parse(), Ok(ToolScope::Read));
+        assert_eq!("draft".parse(), Ok(ToolScope::Draft));
+        assert!("write".parse::<ToolScope>().is_err());
+    }
+
+    #[tokio::test]
+    async fn should_reject_missing_or_unknown_token() {
+        let app = app(AppState::in_memory(&Config::default()));
+        let ping = json!({ "jsonrpc": "2.0", "id": 1, "method": "ping" });
+
+        let (status, _) = send(&app, "search-wrong", ping.clone()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, response) = send(&app, SEARCH_TOKEN, ping).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response, json!({ "jsonrpc": "2.0", "id": 1, "result": {} }));
+    }
+
+    #[tokio::test]
+    async fn should_initialize_and_accept_notifications() {
+        let app = app(AppState::in_memory(&Config::default()));
+
+        let initialize = json!({ "jsonrpc": "2.0", "id": "init", "method": "initialize", "params": {} });
+        let (_, response) = send(&app, SEARCH_TOKEN, initialize).await;
+        assert_eq!(response["id"], "init");
+        assert_eq!(response["result"]["protocolVersion"], PROTOCOL_VERSION);
+        assert_eq!(response["result"]["capabilities"], json!({ "tools": {} }));
+
+        let (status, _) =
+            send(&app, SEARCH_TOKEN, json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+    }
+
+    #[tokio::test]
+    async fn should_answer_protocol_errors() {
+        let app = app(AppState::in_memory(&Config::default()));
+
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": "resources/list" });
+        let (_, response) = send(&app, SEARCH_TOKEN, request).await;
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let (_, response) = send(&app, SEARCH_TOKEN, json!({ "jsonrpc": "1.0", "id": 1, "method": "ping" })).await;
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+
+        let (_, response) = send(&app, SEARCH_TOKEN, json!([{ "jsonrpc": "2.0", "id": 1, "method": "ping" }])).await;
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn should_only_list_and_call_granted_tools() {
+        let (state, question) = state_with_question(QuestionStatus::Open).await;
+        let app = app(state);
+        let list = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" });
+
+        let (_, response) = send(&app, SEARCH_TOKEN, list.clone()).await;
+        let tools = response["result"]["tools"].as_array().unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0]["name"], "search_questions");
+        assert_eq!(tools[0]["inputSchema"]["required"], json!(["q"]));
+
+        let arguments = json!({ "question_uuid": question.question_uuid });
+        let response = call(&app, SEARCH_TOKEN, "get_question", arguments).await;
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+        assert_eq!(response["error"]["message"], "Unknown tool: get_question");
+
+        let (_, response) = send(&app, ALL_TOKEN, list).await;
+        assert_eq!(response["result"]["tools"].as_array().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn should_search_and_read_questions() {
+        let (state, question) = state_with_question(QuestionStatus::Open).await;
+        let app = app(state);
+
+        let response = call(&app, SEARCH_TOKEN, "search_questions", json!({ "q": "crate" })).await;
+        let result = &response["result"];
+        assert_eq!(result["isError"], false);
+        assert_eq!(result["structuredContent"]["questions"][0]["title"], "How do I split a crate?");
+
+        let response = call(&app, ALL_TOKEN, "get_question", json!({ "question_uuid": question.question_uuid })).await;
+        let result = &response["result"];
+        assert_eq!(result["isError"], false);
+        assert_eq!(result["structuredContent"]["question_uuid"], json!(question.question_uuid));
+        assert!(result["content"][0]["text"].as_str().unwrap().starts_with("How do I split a crate?\n"));
+    }
+
+    #[tokio::test]
+    async fn should_report_tool_failures_in_results() {
+        let app = app(AppState::in_memory(&Config::default()));
+
+        let response = call(&app, SEARCH_TOKEN, "search_questions", json!({})).await;
+        assert_eq!(response["result"]["isError"], true);
+        assert!(response["result"]["content"][0]["text"].as_str().unwrap().starts_with("Invalid arguments"));
+
+        let response = call(&app, SEARCH_TOKEN, "search_questions", json!({ "q": " " })).await;
+        assert_eq!(response["result"]["isError"], true);
+        assert_eq!(response["result"]["content"][0]["text"], "Search query must not be empty");
+    }
+
+    #[tokio::test]
+    async fn should_draft_answers_without_posting_them() {
+        let (state, question) = state_with_question(QuestionStatus::Open).await;
+        let answers_dao = state.answers_dao.clone();
+        let app = app(state);
+
+        let arguments = json!({ "question_uuid": question.question_uuid, "content": "  Add a src/lib.rs " });
+        let response = call(&app, ALL_TOKEN, "draft_answer", arguments).await;
+
+        let result = &response["result"];
+        assert_eq!(result["isError"], false);
+        assert_eq!(result["structuredContent"]["content"], "Add a src/lib.rs");
+        assert_eq!(result["structuredContent"]["posted"], false);
+        assert!(answers_dao.get_answers(question.question_uuid, AnswerSort::Oldest).await.unwrap().is_empty());
+
+        let arguments = json!({ "question_uuid": question.question_uuid, "content": " " });
+        let response = call(&app, ALL_TOKEN, "draft_answer", arguments).await;
+        assert_eq!(response["result"]["isError"], true);
+        assert!(response["result"]["content"][0]["text"].as_str().unwrap().starts_with("Invalid input: content"));
+    }
+
+    #[tokio::test]
+    async fn should_not_draft_answers_to_closed_or_unknown_questions() {
+        let (state, question) = state_with_question(QuestionStatus::Closed).await;
+        let app = app(state);
+
+        for question_uuid in [question.question_uuid, QuestionUuid::new_v4()] {
+            let arguments = json!({ "question_uuid": question_uuid, "content": "Add a src/lib.rs" });
+            let response = call(&app, ALL_TOKEN, "draft_answer", arguments).await;
+
+            assert_eq!(response["result"]["isError"], true, "{}", question_uuid);
+        }
+    }
+}
@@ -1,24 +1,50 @@
+use std::{sync::Arc, time::Instant};
+
 use axum::{
-    extract::State as AxumState, http::StatusCode, response::IntoResponse, Json as JsonAxum,
+    extract::{ws::WebSocketUpgrade, Path, Query, State as AxumState},
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
 };
+use futures::Stream;
 
-use crate::{models::*, AppState};
+use crate::{
+    event_schemas::event_schemas, excerpt::set_excerpts, health::check_readiness, jobs, live, models::*,
+    persistance::maintenance_dao::MaintenanceDao, print, redact::redact, AppState,
+};
 
+pub mod extract;
+#[cfg(feature = "graphql")]
+pub mod graphql;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 mod handlers_inner;
+pub mod mcp;
+pub mod response;
+
+pub use extract::ValidatedJson;
+pub use handlers_inner::HandlerError;
+pub use response::{ApiResponse, ApiResult};
 
-impl IntoResponse for handlers_inner::HandlerError {
+impl IntoResponse for HandlerError {
     /// Converts the `HandlerError` into an Axum response.
     ///
     /// # Returns
     ///
-    /// An Axum response containing the appropriate status code and message based on the `HandlerError`.
+    /// An Axum response containing the appropriate status code and message based on the `HandlerError`,
+    /// with any secrets in the message redacted.
     fn into_response(self) -> axum::response::Response {
         match self {
-            handlers_inner::HandlerError::BadRequest(msg) => {
-                (StatusCode::BAD_REQUEST, msg).into_response()
+            HandlerError::BadRequest(msg) => {
+                (StatusCode::BAD_REQUEST, redact(&msg)).into_response()
             }
-            handlers_inner::HandlerError::InternalError(msg) => {
-                (StatusCode::INTERNAL_SERVER_ERROR, msg).into_response()
+            HandlerError::Conflict(msg) => {
+                (StatusCode::CONFLICT, redact(&msg)).into_response()
+            }
+            HandlerError::InternalError(msg) => {
+                (StatusCode::INTERNAL_SERVER_ERROR, redact(&msg)).into_response()
             }
         }
     }
@@ -30,37 +56,329 @@ impl IntoResponse for handlers_inner::HandlerError {
 ///
 /// # Arguments
 ///
-/// * `AxumState(AppState { questions_dao, .. })` - The application state containing the `QuestionsDao`.
-/// * `JsonAxum(question)` - The JSON payload containing the details of the question to be created.
+/// * `AxumState(AppState { questions_dao, flags_dao, content_filters, .. })` - The application state containing the
+///   `QuestionsDao`, the `FlagsDao` holding questions for moderation and the content filters.
+/// * `ValidatedJson(question)` - The validated JSON payload containing the details of the question to be created.
 ///
 /// # Returns
 ///
-/// A `Result` containing either a JSON response with the created question detail or an error response.
+/// An `ApiResult` containing either a JSON response with the created question detail or an error response.
+#[utoipa::path(
+    post, path = "/question", tag = "questions", request_body = Question,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Unique key of the request, for retries to replay its response"),
+    ),
+    summary = "Ask a question",
+    description = "Creates an open question, with the `question_uuid` chosen by the client if any. A question held by a \
+                   content filter is returned as it will be created, but only created once a moderator dismisses its \
+                   flag. Retries sent with the same `Idempotency-Key` get the response to the first request rather \
+                   than creating the question again.",
+    responses(
+        (status = 200, description = "Created question", body = QuestionDetail,
+            headers(
+                ("Idempotency-Replayed" = bool, description = "With an `Idempotency-Key`, whether the response was replayed"),
+                ("Idempotency-Key-Expires" = String, description = "HTTP date until which retries get the same response"),
+            )),
+        (status = 400, description = "Question rejected by a content filter", body = String, content_type = "text/plain"),
+        (status = 409, description = "Question UUID already taken, or request with the same `Idempotency-Key` in progress",
+            body = String, content_type = "text/plain"),
+        (status = 422, description = "Invalid body, or `Idempotency-Key` invalid or used with another body", body = InvalidRequest),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
 pub async fn create_question(
     // Example of how to add state to a route. Note that we are using ".." to ignore the other fields in AppState.
-    AxumState(AppState { questions_dao, .. }): AxumState<AppState>,
-    JsonAxum(question): JsonAxum<Question>,
-) -> Result<impl IntoResponse, impl IntoResponse> {
-    handlers_inner::create_question(question, questions_dao.as_ref())
+    AxumState(AppState { questions_dao, flags_dao, content_filters, .. }): AxumState<AppState>,
+    ValidatedJson(question): ValidatedJson<Question>,
+) -> ApiResult<QuestionDetail> {
+    handlers_inner::create_question(question, &content_filters, questions_dao.as_ref(), flags_dao.as_ref())
         .await
-        .map(JsonAxum)
+        .map(ApiResponse::ok)
 }
 
 /// Asynchronously retrieves all questions.
 ///
 /// # Arguments
 ///
-/// * `AxumState(AppState { questions_dao, .. })` - The application state containing the `QuestionsDao`.
+/// * `AxumState(AppState { questions_dao, question_excerpt_chars, .. })` - The application state containing the
+///   `QuestionsDao` and the length of description excerpts.
+/// * `Query(filter)` - The query string, with optional conditions on the `status`, creation time and title of the
+///   questions, and an optional `sort` order.
+/// * `profile` - The response profile, `compact` sparing mobile clients question descriptions and long answers.
+/// * `render` - The format of question descriptions and answers, `html` adding them rendered and sanitized.
+/// * `headers` - The request headers, whose `If-None-Match` spares sending an unchanged list again.
 ///
 /// # Returns
 ///
-/// A `Result` containing either a JSON response with the retrieved questions or an error response.
+/// An `ApiResult` containing either a JSON response with the retrieved questions, a `304 Not Modified`, or an error
+/// response.
+#[utoipa::path(
+    get, path = "/questions", tag = "questions",
+    params(
+        QuestionFilter,
+        ProfileQuery,
+        RenderQuery,
+        ("If-None-Match" = Option<String>, Header, description = "`ETag` of the list already held"),
+    ),
+    summary = "List questions",
+    description = "Lists every question, or only those meeting the conditions given, in the `sort` order, with excerpts of \
+        their descriptions.",
+    responses(
+        (status = 200, description = "Questions, oldest first unless sorted otherwise", body = Vec<QuestionDetail>,
+            headers(("ETag" = String, description = "Hash of the list"))),
+        (status = 304, description = "Unchanged since the `ETag` in `If-None-Match`"),
+        (status = 400, description = "Unknown `status` or `sort`, or invalid time", body = String, content_type = "text/plain"),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
 pub async fn read_questions(
-    AxumState(AppState { questions_dao, .. }): AxumState<AppState>,
-) -> Result<impl IntoResponse, impl IntoResponse> {
-    handlers_inner::read_questions(questions_dao.as_ref())
+    AxumState(AppState { questions_dao, question_excerpt_chars, .. }): AxumState<AppState>,
+    Query(filter): Query<QuestionFilter>,
+    profile: ResponseProfile,
+    render: RenderFormat,
+    headers: HeaderMap,
+) -> ApiResult<Vec<QuestionDetail>> {
+    let mut questions = handlers_inner::read_questions(filter, questions_dao.as_ref()).await?;
+    set_excerpts(&mut questions, question_excerpt_chars);
+
+    Ok(ApiResponse::ok(questions).profile(profile).render(render).etag(&headers))
+}
+
+/// Asynchronously retrieves several questions at once, sparing a caller with many identifiers a request per question.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { questions_dao, question_excerpt_chars, .. })` - The application state containing the
+///   `QuestionsDao` and the length of description excerpts.
+/// * `profile` - The response profile, `compact` sparing mobile clients question descriptions and long answers.
+/// * `render` - The format of question descriptions and answers, `html` adding them rendered and sanitized.
+/// * `ValidatedJson(lookup)` - The validated JSON payload containing the unique identifiers of up to `MAX_BATCH_QUESTIONS` questions.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the questions that exist or an error response.
+#[utoipa::path(
+    post, path = "/questions/lookup", tag = "questions", request_body = QuestionsLookup, params(ProfileQuery, RenderQuery),
+    summary = "Look up questions",
+    description = "Returns the questions among up to 100 UUIDs that exist, oldest first, skipping the others, with \
+                   excerpts of their descriptions.",
+    responses(
+        (status = 200, description = "Questions that exist, oldest first", body = Vec<QuestionDetail>),
+        (status = 422, description = "Invalid body", body = InvalidRequest),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn lookup_questions(
+    AxumState(AppState { questions_dao, question_excerpt_chars, .. }): AxumState<AppState>,
+    profile: ResponseProfile,
+    render: RenderFormat,
+    ValidatedJson(lookup): ValidatedJson<QuestionsLookup>,
+) -> ApiResult<Vec<QuestionDetail>> {
+    let mut questions = handlers_inner::lookup_questions(lookup, questions_dao.as_ref()).await?;
+    set_excerpts(&mut questions, question_excerpt_chars);
+
+    Ok(ApiResponse::ok(questions).profile(profile).render(render))
+}
+
+/// Asynchronously brings the questions an offline client holds up to date in one request, sending only those that
+/// changed, were created or were deleted since.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { questions_dao, answers_dao, .. })` - The application state containing the `QuestionsDao`
+///   and the `AnswersDao`.
+/// * `ValidatedJson(request)` - The validated JSON payload containing up to `MAX_BATCH_QUESTIONS` questions held,
+///   with their versions, and the time of the previous sync.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the questions changed, created and deleted, or an error
+/// response.
+#[utoipa::path(
+    post, path = "/sync", tag = "questions", request_body = SyncRequest,
+    summary = "Sync held questions",
+    description = "Compares up to 100 questions held by a client with their current versions, the `ETag` of the \
+        question with its answers, and returns those in another version with their answers, those that no longer \
+        exist, and the questions created after `since` that are not held. Passing the returned `synced_at` as \
+        `since` to the next sync returns every question created, some of them twice: `synced_at` is a minute \
+        before the questions were read, so that questions committed meanwhile are not missed.",
+    responses(
+        (status = 200, description = "Questions changed, created and deleted", body = SyncResponse),
+        (status = 422, description = "Invalid body", body = InvalidRequest),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn sync_questions(
+    AxumState(AppState { questions_dao, answers_dao, .. }): AxumState<AppState>,
+    ValidatedJson(request): ValidatedJson<SyncRequest>,
+) -> ApiResult<SyncResponse> {
+    handlers_inner::sync_questions(request, questions_dao.as_ref(), answers_dao.as_ref())
+        .await
+        .map(ApiResponse::ok)
+}
+
+/// Asynchronously retrieves the most viewed questions.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { questions_dao, question_excerpt_chars, .. })` - The application state containing the
+///   `QuestionsDao` and the length of description excerpts.
+/// * `Query(popular)` - The query string, with an optional `limit`.
+/// * `profile` - The response profile, `compact` sparing mobile clients question descriptions and long answers.
+/// * `render` - The format of question descriptions and answers, `html` adding them rendered and sanitized.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the most viewed questions or an error response.
+#[utoipa::path(
+    get, path = "/questions/popular", tag = "questions", params(PopularQuestions, ProfileQuery, RenderQuery),
+    summary = "List popular questions",
+    description = "Lists the most viewed questions, with excerpts of their descriptions. Views are those of the \
+        question pages, in JSON or plain text, and are counted in batches, so the latest ones may not show yet.",
+    responses(
+        (status = 200, description = "Questions, most viewed first", body = Vec<QuestionDetail>),
+        (status = 400, description = "Invalid `limit`", body = String, content_type = "text/plain"),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn read_popular_questions(
+    AxumState(AppState { questions_dao, question_excerpt_chars, .. }): AxumState<AppState>,
+    Query(popular): Query<PopularQuestions>,
+    profile: ResponseProfile,
+    render: RenderFormat,
+) -> ApiResult<Vec<QuestionDetail>> {
+    let mut questions = handlers_inner::read_popular_questions(popular, questions_dao.as_ref()).await?;
+    set_excerpts(&mut questions, question_excerpt_chars);
+
+    Ok(ApiResponse::ok(questions).profile(profile).render(render))
+}
+
+/// Asynchronously retrieves a question along with all its answers, saving a question page a round trip, and counts
+/// a view of it.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { questions_dao, view_counter, .. })` - The application state containing the `QuestionsDao`
+///   and the view counter.
+/// * `Path(question_uuid)` - The unique identifier of the question.
+/// * `profile` - The response profile, `compact` sparing mobile clients question descriptions and long answers.
+/// * `render` - The format of question descriptions and answers, `html` adding them rendered and sanitized.
+/// * `headers` - The request headers, whose `If-None-Match` spares sending an unchanged question again.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the question and its answers, a `304 Not Modified`, or an
+/// error response.
+#[utoipa::path(
+    get, path = "/questions/{question_uuid}/full", tag = "questions",
+    summary = "Read a question with its answers",
+    description = "Returns a question along with all of its answers, oldest first.",
+    params(
+        ("question_uuid" = QuestionUuid, Path, description = "Unique identifier of the question"),
+        ProfileQuery,
+        RenderQuery,
+        ("If-None-Match" = Option<String>, Header, description = "`ETag` of the question already held"),
+    ),
+    responses(
+        (status = 200, description = "Question and its answers, oldest first", body = QuestionWithAnswers,
+            headers(("ETag" = String, description = "Hash of the question and its answers"))),
+        (status = 304, description = "Unchanged since the `ETag` in `If-None-Match`"),
+        (status = 400, description = "No such question", body = String, content_type = "text/plain"),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn read_question_with_answers(
+    AxumState(AppState { questions_dao, view_counter, .. }): AxumState<AppState>,
+    Path(question_uuid): Path<QuestionUuid>,
+    profile: ResponseProfile,
+    render: RenderFormat,
+    headers: HeaderMap,
+) -> ApiResult<QuestionWithAnswers> {
+    let question_id = QuestionId { question_uuid };
+    let question = handlers_inner::read_question_with_answers(question_id, questions_dao.as_ref()).await?;
+    view_counter.record(question_uuid);
+
+    Ok(ApiResponse::ok(question).profile(profile).render(render).etag(&headers))
+}
+
+/// Asynchronously renders a question along with all its answers as plain text, for terminals and prompts, and counts
+/// a view of it.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { questions_dao, view_counter, .. })` - The application state containing the `QuestionsDao`
+///   and the view counter.
+/// * `Path(question_file)` - The unique identifier of the question followed by `.txt`.
+///
+/// # Returns
+///
+/// Either the question and its answers as plain text, or an error response: `404 Not Found` for a path not ending in
+/// `.txt`, `400 Bad Request` for an invalid or unknown question.
+#[utoipa::path(
+    get, path = "/question/{question_uuid}.txt", tag = "questions",
+    summary = "Print a question with its answers",
+    description = "Returns a question along with all of its answers, oldest first, as plain text with markdown \
+        stripped: a header of `Name: value` lines, the description, then each answer after a line of dashes.",
+    params(("question_uuid" = QuestionUuid, Path, description = "Unique identifier of the question")),
+    responses(
+        (status = 200, description = "Question and its answers", body = String, content_type = "text/plain"),
+        (status = 400, description = "No such question", body = String, content_type = "text/plain"),
+        (status = 404, description = "Path not ending in `.txt`"),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn read_question_text(
+    AxumState(AppState { questions_dao, view_counter, .. }): AxumState<AppState>,
+    Path(question_file): Path<String>,
+) -> Result<String, Response> {
+    // A parameter cannot share its path segment with a suffix, so the segment is parsed here
+    let question_uuid = question_file.strip_suffix(".txt").ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    let question_uuid = question_uuid
+        .parse()
+        .map_err(|_| HandlerError::BadRequest(format!("Invalid question UUID: {}", question_uuid)).into_response())?;
+
+    let question = handlers_inner::read_question_with_answers(QuestionId { question_uuid }, questions_dao.as_ref())
         .await
-        .map(JsonAxum)
+        .map_err(IntoResponse::into_response)?;
+    view_counter.record(question_uuid);
+
+    Ok(print::render_question(&question))
+}
+
+/// Asynchronously searches questions.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { questions_dao, search_index, question_excerpt_chars, .. })` - The application state
+///   containing the `QuestionsDao`, the search index if any and the length of description excerpts.
+/// * `Query(search)` - The query string, with the words to search for in `q` and an optional `limit`.
+/// * `profile` - The response profile, `compact` sparing mobile clients question descriptions and long answers.
+/// * `render` - The format of question descriptions and answers, `html` adding them rendered and sanitized.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the matching questions or an error response.
+#[utoipa::path(
+    get, path = "/questions/search", tag = "questions", params(QuestionSearch, ProfileQuery, RenderQuery),
+    summary = "Search questions",
+    description = "Full-text search over titles and descriptions, most relevant first. With a search cluster \
+                   configured, answers are searched too.",
+    responses(
+        (status = 200, description = "Matching questions, most relevant first", body = Vec<QuestionSearchResult>),
+        (status = 400, description = "Empty search", body = String, content_type = "text/plain"),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn search_questions(
+    AxumState(AppState { questions_dao, search_index, question_excerpt_chars, .. }): AxumState<AppState>,
+    Query(search): Query<QuestionSearch>,
+    profile: ResponseProfile,
+    render: RenderFormat,
+) -> ApiResult<Vec<QuestionSearchResult>> {
+    let mut results = handlers_inner::search_questions(search, questions_dao.as_ref(), search_index.as_deref()).await?;
+    set_excerpts(results.iter_mut().map(|result| &mut result.question), question_excerpt_chars);
+
+    Ok(ApiResponse::ok(results).profile(profile).render(render))
 }
 
 /// Asynchronously deletes a question.
@@ -68,16 +386,90 @@ pub async fn read_questions(
 /// # Arguments
 ///
 /// * `AxumState(AppState { questions_dao, .. })` - The application state containing the `QuestionsDao`.
-/// * `JsonAxum(question_uuid)` - The JSON payload containing the unique identifier of the question to be deleted.
+/// * `ValidatedJson(question_uuid)` - The validated JSON payload containing the unique identifier of the question to be deleted.
 ///
 /// # Returns
 ///
-/// A `Result` containing either a successful response or an error response.
+/// An `ApiResult` containing either an empty response or an error response.
+#[utoipa::path(
+    delete, path = "/question", tag = "questions", request_body = QuestionId,
+    summary = "Delete a question",
+    description = "Deletes a question along with its answers.",
+    responses(
+        (status = 200, description = "Question and its answers deleted"),
+        (status = 422, description = "Invalid body", body = InvalidRequest),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
 pub async fn delete_question(
     AxumState(AppState { questions_dao, .. }): AxumState<AppState>,
-    JsonAxum(question_uuid): JsonAxum<QuestionId>,
-) -> Result<impl IntoResponse, impl IntoResponse> {
-    handlers_inner::delete_question(question_uuid, questions_dao.as_ref()).await
+    ValidatedJson(question_uuid): ValidatedJson<QuestionId>,
+) -> ApiResult<()> {
+    handlers_inner::delete_question(question_uuid, questions_dao.as_ref()).await?;
+
+    Ok(ApiResponse::empty())
+}
+
+/// Asynchronously closes a question, so it no longer accepts answers.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { questions_dao, .. })` - The application state containing the `QuestionsDao`.
+/// * `Path(question_uuid)` - The unique identifier of the question to be closed.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the closed question detail or an error response.
+#[utoipa::path(
+    post, path = "/questions/{question_uuid}/close", tag = "questions",
+    summary = "Close a question",
+    description = "Closes an open question, so it no longer accepts answers.",
+    params(("question_uuid" = QuestionUuid, Path, description = "Unique identifier of the question")),
+    responses(
+        (status = 200, description = "Closed question", body = QuestionDetail),
+        (status = 400, description = "No such question", body = String, content_type = "text/plain"),
+        (status = 409, description = "Question is not open", body = String, content_type = "text/plain"),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn close_question(
+    AxumState(AppState { questions_dao, .. }): AxumState<AppState>,
+    Path(question_uuid): Path<QuestionUuid>,
+) -> ApiResult<QuestionDetail> {
+    handlers_inner::close_question(QuestionId { question_uuid }, questions_dao.as_ref())
+        .await
+        .map(ApiResponse::ok)
+}
+
+/// Asynchronously reopens a closed question, so it accepts answers again.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { questions_dao, .. })` - The application state containing the `QuestionsDao`.
+/// * `Path(question_uuid)` - The unique identifier of the question to be reopened.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the reopened question detail or an error response.
+#[utoipa::path(
+    post, path = "/questions/{question_uuid}/reopen", tag = "questions",
+    summary = "Reopen a question",
+    description = "Reopens a closed question, so it accepts answers again.",
+    params(("question_uuid" = QuestionUuid, Path, description = "Unique identifier of the question")),
+    responses(
+        (status = 200, description = "Reopened question", body = QuestionDetail),
+        (status = 400, description = "No such question", body = String, content_type = "text/plain"),
+        (status = 409, description = "Question is not closed", body = String, content_type = "text/plain"),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn reopen_question(
+    AxumState(AppState { questions_dao, .. }): AxumState<AppState>,
+    Path(question_uuid): Path<QuestionUuid>,
+) -> ApiResult<QuestionDetail> {
+    handlers_inner::reopen_question(QuestionId { question_uuid }, questions_dao.as_ref())
+        .await
+        .map(ApiResponse::ok)
 }
 
 // ---- CRUD for Answers ----
@@ -86,19 +478,45 @@ pub async fn delete_question(
 ///
 /// # Arguments
 ///
-/// * `AxumState(AppState { answers_dao, .. })` - The application state containing the `AnswersDao`.
-/// * `JsonAxum(answer)` - The JSON payload containing the details of the answer to be created.
+/// * `AxumState(AppState { answers_dao, flags_dao, content_filters, .. })` - The application state containing the
+///   `AnswersDao`, the `FlagsDao` holding answers for moderation and the content filters.
+/// * `ValidatedJson(answer)` - The validated JSON payload containing the details of the answer to be created.
 ///
 /// # Returns
 ///
-/// A `Result` containing either a JSON response with the created answer detail or an error response.
+/// An `ApiResult` containing either a JSON response with the created answer detail or an error response.
+#[utoipa::path(
+    post, path = "/answer", tag = "answers", request_body = Answer,
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Unique key of the request, for retries to replay its response"),
+    ),
+    summary = "Answer a question",
+    description = "Creates an answer to an open question, with the `answer_uuid` chosen by the client if any. An \
+                   answer held by a content filter is returned as it will be created, but only created once a \
+                   moderator dismisses its flag. Retries sent with the same `Idempotency-Key` get the response to the first request rather than creating the \
+                   answer again.",
+    responses(
+        (status = 200, description = "Created answer", body = AnswerDetail,
+            headers(
+                ("Idempotency-Replayed" = bool, description = "With an `Idempotency-Key`, whether the response was replayed"),
+                ("Idempotency-Key-Expires" = String, description = "HTTP date until which retries get the same response"),
+            )),
+        (status = 400, description = "No such question, or answer rejected by a content filter",
+            body = String, content_type = "text/plain"),
+        (status = 409, description = "Question does not accept answers, answer UUID already taken, or request with the same \
+            `Idempotency-Key` in progress",
+            body = String, content_type = "text/plain"),
+        (status = 422, description = "Invalid body, or `Idempotency-Key` invalid or used with another body", body = InvalidRequest),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
 pub async fn create_answer(
-    AxumState(AppState { answers_dao, .. }): AxumState<AppState>,
-    JsonAxum(answer): JsonAxum<Answer>,
-) -> Result<impl IntoResponse, impl IntoResponse> {
-    handlers_inner::create_answer(answer, answers_dao.as_ref())
+    AxumState(AppState { answers_dao, flags_dao, content_filters, .. }): AxumState<AppState>,
+    ValidatedJson(answer): ValidatedJson<Answer>,
+) -> ApiResult<AnswerDetail> {
+    handlers_inner::create_answer(answer, &content_filters, answers_dao.as_ref(), flags_dao.as_ref())
         .await
-        .map(JsonAxum)
+        .map(ApiResponse::ok)
 }
 
 /// Asynchronously retrieves all answers for a given question.
@@ -106,18 +524,72 @@ pub async fn create_answer(
 /// # Arguments
 ///
 /// * `AxumState(AppState { answers_dao, .. })` - The application state containing the `AnswersDao`.
-/// * `JsonAxum(question_uuid)` - The JSON payload containing the unique identifier of the question for which answers are to be retrieved.
+/// * `Query(order)` - The query string, with an optional `sort` order.
+/// * `profile` - The response profile, `compact` sparing mobile clients question descriptions and long answers.
+/// * `render` - The format of question descriptions and answers, `html` adding them rendered and sanitized.
+/// * `ValidatedJson(question_uuid)` - The validated JSON payload containing the unique identifier of the question for which answers are to be retrieved.
 ///
 /// # Returns
 ///
-/// A `Result` containing either a JSON response with the retrieved answers or an error response.
+/// An `ApiResult` containing either a JSON response with the retrieved answers or an error response.
+#[utoipa::path(
+    get, path = "/answers", tag = "answers", request_body = QuestionId, params(AnswerOrder, ProfileQuery, RenderQuery),
+    summary = "List answers",
+    description = "Lists the answers of a question, in the `sort` order.",
+    responses(
+        (status = 200, description = "Answers of the question, oldest first unless sorted otherwise", body = Vec<AnswerDetail>),
+        (status = 400, description = "Unknown `sort`", body = String, content_type = "text/plain"),
+        (status = 422, description = "Invalid body", body = InvalidRequest),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
 pub async fn read_answers(
     AxumState(AppState { answers_dao, .. }): AxumState<AppState>,
-    JsonAxum(question_uuid): JsonAxum<QuestionId>,
-) -> Result<impl IntoResponse, impl IntoResponse> {
-    handlers_inner::read_answers(question_uuid, answers_dao.as_ref())
+    Query(order): Query<AnswerOrder>,
+    profile: ResponseProfile,
+    render: RenderFormat,
+    ValidatedJson(question_uuid): ValidatedJson<QuestionId>,
+) -> ApiResult<Vec<AnswerDetail>> {
+    handlers_inner::read_answers(question_uuid, order.sort.unwrap_or_default(), answers_dao.as_ref())
+        .await
+        .map(|answers| ApiResponse::ok(answers).profile(profile).render(render))
+}
+
+/// Asynchronously retrieves the answers of several questions at once, sparing a list of questions a request per question.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { answers_dao, .. })` - The application state containing the `AnswersDao`.
+/// * `profile` - The response profile, `compact` sparing mobile clients question descriptions and long answers.
+/// * `render` - The format of question descriptions and answers, `html` adding them rendered and sanitized.
+/// * `ValidatedJson(batch)` - The validated JSON payload containing the unique identifiers of up to `MAX_BATCH_QUESTIONS` questions.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the answers keyed by question or an error response.
+#[utoipa::path(
+    post, path = "/answers/batch", tag = "answers", request_body = AnswersBatch, params(ProfileQuery, RenderQuery),
+    summary = "List answers of several questions",
+    description = "Returns the answers of up to 100 questions keyed by question, with none for unanswered questions.",
+    responses(
+        (
+            status = 200,
+            description = "Answers keyed by question, oldest first",
+            body = HashMap<QuestionUuid, Vec<AnswerDetail>>
+        ),
+        (status = 422, description = "Invalid body", body = InvalidRequest),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn read_answers_batch(
+    AxumState(AppState { answers_dao, .. }): AxumState<AppState>,
+    profile: ResponseProfile,
+    render: RenderFormat,
+    ValidatedJson(batch): ValidatedJson<AnswersBatch>,
+) -> ApiResult<AnswersByQuestion> {
+    handlers_inner::read_answers_batch(batch, answers_dao.as_ref())
         .await
-        .map(JsonAxum)
+        .map(|answers| ApiResponse::ok(answers).profile(profile).render(render))
 }
 
 /// Asynchronously deletes an answer.
@@ -125,14 +597,767 @@ pub async fn read_answers(
 /// # Arguments
 ///
 /// * `AxumState(AppState { answers_dao, .. })` - The application state containing the `AnswersDao`.
-/// * `JsonAxum(answer_uuid)` - The JSON payload containing the unique identifier of the answer to be deleted.
+/// * `ValidatedJson(answer_uuid)` - The validated JSON payload containing the unique identifier of the answer to be deleted.
 ///
 /// # Returns
 ///
-/// A `Result` containing either a successful response or an error response.
+/// An `ApiResult` containing either an empty response or an error response.
+#[utoipa::path(
+    delete, path = "/answer", tag = "answers", request_body = AnswerId,
+    summary = "Delete an answer",
+    description = "Deletes an answer.",
+    responses(
+        (status = 200, description = "Answer deleted"),
+        (status = 422, description = "Invalid body", body = InvalidRequest),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
 pub async fn delete_answer(
     AxumState(AppState { answers_dao, .. }): AxumState<AppState>,
-    JsonAxum(answer_uuid): JsonAxum<AnswerId>,
-) -> Result<impl IntoResponse, impl IntoResponse> {
-    handlers_inner::delete_answer(answer_uuid, answers_dao.as_ref()).await
-}
\ No newline at end of file
+    ValidatedJson(answer_uuid): ValidatedJson<AnswerId>,
+) -> ApiResult<()> {
+    handlers_inner::delete_answer(answer_uuid, answers_dao.as_ref()).await?;
+
+    Ok(ApiResponse::empty())
+}
+
+// ---- Live updates ----
+
+/// Streams the questions created from now on as Server-Sent Events.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { question_feed, .. })` - The application state containing the feed of created questions.
+///
+/// # Returns
+///
+/// An event stream with a `question_created` event per question created, kept open until the client leaves.
+#[utoipa::path(
+    get, path = "/questions/stream", tag = "questions",
+    summary = "Stream new questions",
+    description = "Streams a `question_created` Server-Sent Event, carrying the created question as JSON, for \
+        every question created from then on.",
+    responses(
+        (status = 200, description = "Stream of created questions", body = QuestionDetail,
+            content_type = "text/event-stream"),
+    )
+)]
+pub async fn stream_questions(
+    AxumState(AppState { question_feed, .. }): AxumState<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    Sse::new(live::question_events(question_feed.subscribe())).keep_alive(KeepAlive::default())
+}
+
+/// Upgrades the connection to a WebSocket pushing the answers created for a question.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { answer_feed, .. })` - The application state containing the feed of created answers.
+/// * `Path(question_uuid)` - The unique identifier of the question to watch.
+/// * `upgrade` - The request to upgrade the connection.
+///
+/// # Returns
+///
+/// A `101 Switching Protocols` response, after which every answer created for the question is sent as a JSON text
+/// message.
+#[utoipa::path(
+    get, path = "/ws/questions/{question_uuid}", tag = "answers",
+    summary = "Watch the answers of a question",
+    description = "Upgrades to a WebSocket, over which every answer created for the question from then on is sent \
+        as a JSON text message.",
+    params(("question_uuid" = QuestionUuid, Path, description = "Unique identifier of the question")),
+    responses(
+        (status = 101, description = "Switching to a WebSocket of answers, each sent as an `AnswerDetail`"),
+    )
+)]
+pub async fn watch_answers(
+    AxumState(AppState { answer_feed, .. }): AxumState<AppState>,
+    Path(question_uuid): Path<QuestionUuid>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    // Subscribing before the upgrade, so answers created while it completes are not missed
+    let answers = answer_feed.subscribe();
+
+    upgrade.on_upgrade(move |socket| live::push_answers(socket, question_uuid, answers))
+}
+
+/// Asynchronously long polls for the answers created for a question, for clients behind proxies that block
+/// Server-Sent Events and WebSockets.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { answers_dao, answer_feed, .. })` - The application state containing the `AnswersDao` and
+///   the feed of created answers.
+/// * `Path(question_uuid)` - The unique identifier of the question to poll.
+/// * `Query(query)` - The query string, with the cursor of the previous poll and the longest time to wait.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the answers created after the cursor, and the next cursor,
+/// or an error response.
+#[utoipa::path(
+    get, path = "/question/{question_uuid}/updates", tag = "answers",
+    summary = "Long poll for the answers of a question",
+    description = "Returns the answers created for the question after `since` at once if there are any, or else \
+        holds the request until one is created or `wait` expires. Polling again with the returned `cursor` as \
+        `since` receives every answer once, without Server-Sent Events or WebSockets.",
+    params(
+        ("question_uuid" = QuestionUuid, Path, description = "Unique identifier of the question"),
+        AnswerUpdatesQuery,
+    ),
+    responses(
+        (status = 200, description = "Answers created after `since`, empty if none was created in time",
+            body = AnswerUpdates),
+        (status = 400, description = "Invalid `since` or `wait`", body = String, content_type = "text/plain"),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn poll_answers(
+    AxumState(AppState { answers_dao, answer_feed, .. }): AxumState<AppState>,
+    Path(question_uuid): Path<QuestionUuid>,
+    Query(query): Query<AnswerUpdatesQuery>,
+) -> ApiResult<AnswerUpdates> {
+    let mut answers = answer_feed.subscribe();
+
+    handlers_inner::poll_answers(question_uuid, query, answers_dao.as_ref(), &mut answers)
+        .await
+        .map(ApiResponse::ok)
+}
+
+/// Asynchronously registers a webhook notified of the answers created for a question, until it expires.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { webhooks_dao, .. })` - The application state containing the `WebhooksDao`.
+/// * `Path(question_uuid)` - The unique identifier of the question to watch.
+/// * `ValidatedJson(webhook)` - The validated JSON payload containing the URL, secret and lifetime of the webhook.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the registered webhook detail or an error response.
+#[utoipa::path(
+    post, path = "/question/{question_uuid}/watch-webhook", tag = "answers", request_body = QuestionWebhook,
+    summary = "Watch the answers of a question with a webhook",
+    description = "Registers a URL to which every answer created for the question is POSTed as an `answer.created` \
+        event, signed with the secret like the payloads of other webhooks. The webhook expires after `ttl_hours`, a \
+        week by default, or when the question is deleted.",
+    params(("question_uuid" = QuestionUuid, Path, description = "Unique identifier of the question")),
+    responses(
+        (status = 200, description = "Registered webhook, without its secret", body = QuestionWebhookDetail),
+        (status = 400, description = "No such question", body = String, content_type = "text/plain"),
+        (status = 422, description = "Invalid body", body = InvalidRequest),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn create_question_webhook(
+    AxumState(AppState { webhooks_dao, .. }): AxumState<AppState>,
+    Path(question_uuid): Path<QuestionUuid>,
+    ValidatedJson(webhook): ValidatedJson<QuestionWebhook>,
+) -> ApiResult<QuestionWebhookDetail> {
+    handlers_inner::create_question_webhook(question_uuid, webhook, webhooks_dao.as_ref())
+        .await
+        .map(ApiResponse::ok)
+}
+
+// ---- Events ----
+
+/// Serves the JSON Schemas of the payloads delivered to webhooks and brokers.
+///
+/// # Returns
+///
+/// A JSON response with the schema of each event and the version of the API.
+#[utoipa::path(
+    get, path = "/events/schemas", tag = "events",
+    summary = "Read the schemas of events",
+    description = "Returns a JSON Schema (draft 2020-12) of the payload of each event delivered to webhooks and \
+                   published to brokers, by event name, so consumers can validate payloads and generate their types. \
+                   The schemas are those of the running version of the API.",
+    responses(
+        (status = 200, description = "Schema of each event", body = EventSchemas),
+    )
+)]
+pub async fn read_event_schemas() -> ApiResult<EventSchemas> {
+    Ok(ApiResponse::ok(event_schemas()))
+}
+
+// ---- Moderation ----
+
+/// Asynchronously flags a question for moderators to review.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { flags_dao, .. })` - The application state containing the `FlagsDao`.
+/// * `Path(question_uuid)` - The unique identifier of the question to flag.
+/// * `ValidatedJson(flag)` - The validated JSON payload containing the reason for the flag.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the flag detail or an error response.
+#[utoipa::path(
+    post, path = "/questions/{question_uuid}/flag", tag = "moderation", request_body = Flag,
+    summary = "Flag a question",
+    description = "Flags a question for moderators to review, with a reason code and an optional comment.",
+    params(("question_uuid" = QuestionUuid, Path, description = "Unique identifier of the question")),
+    responses(
+        (status = 200, description = "Raised flag", body = FlagDetail),
+        (status = 400, description = "No such question", body = String, content_type = "text/plain"),
+        (status = 422, description = "Invalid body", body = InvalidRequest),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn flag_question(
+    AxumState(AppState { flags_dao, .. }): AxumState<AppState>,
+    Path(question_uuid): Path<QuestionUuid>,
+    ValidatedJson(flag): ValidatedJson<Flag>,
+) -> ApiResult<FlagDetail> {
+    handlers_inner::flag_question(question_uuid, flag, flags_dao.as_ref())
+        .await
+        .map(ApiResponse::ok)
+}
+
+/// Asynchronously flags an answer for moderators to review.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { flags_dao, .. })` - The application state containing the `FlagsDao`.
+/// * `Path(answer_uuid)` - The unique identifier of the answer to flag.
+/// * `ValidatedJson(flag)` - The validated JSON payload containing the reason for the flag.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the flag detail or an error response.
+#[utoipa::path(
+    post, path = "/answers/{answer_uuid}/flag", tag = "moderation", request_body = Flag,
+    summary = "Flag an answer",
+    description = "Flags an answer for moderators to review, with a reason code and an optional comment. The flag \
+        records the question of the answer too, to review the answer in context.",
+    params(("answer_uuid" = AnswerUuid, Path, description = "Unique identifier of the answer")),
+    responses(
+        (status = 200, description = "Raised flag", body = FlagDetail),
+        (status = 400, description = "No such answer", body = String, content_type = "text/plain"),
+        (status = 422, description = "Invalid body", body = InvalidRequest),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn flag_answer(
+    AxumState(AppState { flags_dao, .. }): AxumState<AppState>,
+    Path(answer_uuid): Path<AnswerUuid>,
+    ValidatedJson(flag): ValidatedJson<Flag>,
+) -> ApiResult<FlagDetail> {
+    handlers_inner::flag_answer(answer_uuid, flag, flags_dao.as_ref())
+        .await
+        .map(ApiResponse::ok)
+}
+
+/// Asynchronously reads the flags moderators have yet to resolve.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { flags_dao, .. })` - The application state containing the `FlagsDao`.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the open flags, oldest first, or an error response.
+#[utoipa::path(
+    get, path = "/moderation/queue", tag = "moderation", security(("admin_token" = [])),
+    summary = "Read the moderation queue",
+    description = "Returns the flags not resolved yet, oldest first.",
+    responses(
+        (status = 200, description = "Open flags", body = Vec<FlagDetail>),
+        (status = 401, description = "Missing or wrong admin token", body = String, content_type = "text/plain"),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn read_moderation_queue(
+    AxumState(AppState { flags_dao, .. }): AxumState<AppState>,
+) -> ApiResult<Vec<FlagDetail>> {
+    handlers_inner::read_moderation_queue(flags_dao.as_ref())
+        .await
+        .map(ApiResponse::ok)
+}
+
+/// Asynchronously reads every flag raised on a question or its answers, including the tags of content filters.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { flags_dao, .. })` - The application state containing the `FlagsDao`.
+/// * `Path(question_uuid)` - The unique identifier of the question.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the flags, oldest first, or an error response.
+#[utoipa::path(
+    get, path = "/moderation/questions/{question_uuid}/flags", tag = "moderation", security(("admin_token" = [])),
+    summary = "Read the flags of a question",
+    description = "Returns every flag raised on a question or its answers, oldest first, whatever their resolution. \
+        Flags resolved as `tagged` were raised by content filters that only tag content, and never entered the queue.",
+    params(("question_uuid" = QuestionUuid, Path, description = "Unique identifier of the question")),
+    responses(
+        (status = 200, description = "Flags of the question and its answers", body = Vec<FlagDetail>),
+        (status = 401, description = "Missing or wrong admin token", body = String, content_type = "text/plain"),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn read_question_flags(
+    AxumState(AppState { flags_dao, .. }): AxumState<AppState>,
+    Path(question_uuid): Path<QuestionUuid>,
+) -> ApiResult<Vec<FlagDetail>> {
+    handlers_inner::read_question_flags(question_uuid, flags_dao.as_ref())
+        .await
+        .map(ApiResponse::ok)
+}
+
+/// Asynchronously resolves a flag, removing the flagged content if moderators decided so.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { flags_dao, questions_dao, answers_dao, .. })` - The application state containing the
+///   `FlagsDao`, and the DAOs of the content removed.
+/// * `Path(flag_uuid)` - The unique identifier of the flag to resolve.
+/// * `ValidatedJson(decision)` - The validated JSON payload containing the decision of the moderators.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with every flag resolved or an error response.
+#[utoipa::path(
+    post, path = "/moderation/{flag_uuid}/resolve", tag = "moderation", request_body = FlagDecision,
+    security(("admin_token" = [])),
+    summary = "Resolve a flag",
+    description = "Dismisses a flag, or removes the question or answer flagged. Dismissing a flag holding content \
+        creates the content, while removing it discards the content. Every open flag on the same content is resolved \
+        with it, so the content is reviewed once however many times it was flagged.",
+    params(("flag_uuid" = FlagUuid, Path, description = "Unique identifier of the flag")),
+    responses(
+        (status = 200, description = "Resolved flags, oldest first", body = Vec<FlagDetail>),
+        (status = 400, description = "No such flag", body = String, content_type = "text/plain"),
+        (status = 401, description = "Missing or wrong admin token", body = String, content_type = "text/plain"),
+        (status = 409, description = "Flag resolved already, or content held that can no longer be created",
+            body = String, content_type = "text/plain"),
+        (status = 422, description = "Invalid body", body = InvalidRequest),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn resolve_flag(
+    AxumState(AppState { flags_dao, questions_dao, answers_dao, .. }): AxumState<AppState>,
+    Path(flag_uuid): Path<FlagUuid>,
+    ValidatedJson(decision): ValidatedJson<FlagDecision>,
+) -> ApiResult<Vec<FlagDetail>> {
+    handlers_inner::resolve_flag(flag_uuid, decision, flags_dao.as_ref(), questions_dao.as_ref(), answers_dao.as_ref())
+        .await
+        .map(ApiResponse::ok)
+}
+
+// ---- Status page ----
+
+/// Asynchronously builds the public status page.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { health_checks, incidents_dao, started_at, .. })` - The application state containing the dependency checks, the `IncidentsDao` and the start time.
+///
+/// # Returns
+///
+/// A JSON response with the uptime, failing dependencies and active incidents.
+#[utoipa::path(
+    get, path = "/status", tag = "status",
+    summary = "Read the status page",
+    description = "Reports the uptime, failing dependencies and active incidents.",
+    responses(
+        (status = 200, description = "Status page", body = StatusReport),
+    )
+)]
+pub async fn read_status(
+    AxumState(AppState { health_checks, incidents_dao, started_at, .. }): AxumState<AppState>,
+) -> ApiResult<StatusReport> {
+    Ok(ApiResponse::ok(
+        handlers_inner::read_status(started_at.elapsed(), &health_checks, incidents_dao.as_ref()).await,
+    ))
+}
+
+/// Asynchronously computes the statistics of the questions and answers, for dashboards.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { stats_dao, .. })` - The application state containing the `StatsDao`.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the statistics or an error response.
+#[utoipa::path(
+    get, path = "/stats", tag = "status",
+    summary = "Read statistics",
+    description = "Counts the questions and answers, the questions by number of answers, and the questions asked on \
+        each of the last 30 days, in UTC, today included.",
+    responses(
+        (status = 200, description = "Statistics of the content", body = ContentStats),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn read_stats(AxumState(AppState { stats_dao, .. }): AxumState<AppState>) -> ApiResult<ContentStats> {
+    handlers_inner::read_stats(stats_dao.as_ref()).await.map(ApiResponse::ok)
+}
+
+/// Asynchronously announces a new incident on the status page.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { incidents_dao, .. })` - The application state containing the `IncidentsDao`.
+/// * `ValidatedJson(incident)` - The validated JSON payload containing the details of the incident to be created.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the created incident detail or an error response.
+#[utoipa::path(
+    post, path = "/admin/incident", tag = "admin", request_body = Incident, security(("admin_token" = [])),
+    summary = "Announce an incident",
+    description = "Announces an incident on the status page.",
+    responses(
+        (status = 200, description = "Announced incident", body = IncidentDetail),
+        (status = 401, description = "Missing or wrong admin token", body = String, content_type = "text/plain"),
+        (status = 422, description = "Invalid body", body = InvalidRequest),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn create_incident(
+    AxumState(AppState { incidents_dao, .. }): AxumState<AppState>,
+    ValidatedJson(incident): ValidatedJson<Incident>,
+) -> ApiResult<IncidentDetail> {
+    handlers_inner::create_incident(incident, incidents_dao.as_ref())
+        .await
+        .map(ApiResponse::ok)
+}
+
+/// Asynchronously resolves an incident, removing it from the status page.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { incidents_dao, .. })` - The application state containing the `IncidentsDao`.
+/// * `ValidatedJson(incident_uuid)` - The validated JSON payload containing the unique identifier of the incident to be resolved.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either an empty response or an error response.
+#[utoipa::path(
+    post, path = "/admin/incident/resolve", tag = "admin", request_body = IncidentId, security(("admin_token" = [])),
+    summary = "Resolve an incident",
+    description = "Removes an incident from the status page.",
+    responses(
+        (status = 200, description = "Incident resolved"),
+        (status = 400, description = "No such incident", body = String, content_type = "text/plain"),
+        (status = 401, description = "Missing or wrong admin token", body = String, content_type = "text/plain"),
+        (status = 422, description = "Invalid body", body = InvalidRequest),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn resolve_incident(
+    AxumState(AppState { incidents_dao, .. }): AxumState<AppState>,
+    ValidatedJson(incident_uuid): ValidatedJson<IncidentId>,
+) -> ApiResult<()> {
+    handlers_inner::resolve_incident(incident_uuid, incidents_dao.as_ref()).await?;
+
+    Ok(ApiResponse::empty())
+}
+
+/// Asynchronously registers a webhook, notified of the questions and answers created or deleted from now on.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { webhooks_dao, .. })` - The application state containing the `WebhooksDao`.
+/// * `ValidatedJson(webhook)` - The validated JSON payload containing the URL and secret of the webhook.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the registered webhook detail or an error response.
+#[utoipa::path(
+    post, path = "/admin/webhook", tag = "admin", request_body = Webhook, security(("admin_token" = [])),
+    summary = "Register a webhook",
+    description = "Registers a URL to POST signed events to when questions and answers are created or deleted.",
+    responses(
+        (status = 200, description = "Registered webhook, without its secret", body = WebhookDetail),
+        (status = 401, description = "Missing or wrong admin token", body = String, content_type = "text/plain"),
+        (status = 422, description = "Invalid body", body = InvalidRequest),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn create_webhook(
+    AxumState(AppState { webhooks_dao, .. }): AxumState<AppState>,
+    ValidatedJson(webhook): ValidatedJson<Webhook>,
+) -> ApiResult<WebhookDetail> {
+    handlers_inner::create_webhook(webhook, webhooks_dao.as_ref())
+        .await
+        .map(ApiResponse::ok)
+}
+
+/// Asynchronously lists the registered webhooks.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { webhooks_dao, .. })` - The application state containing the `WebhooksDao`.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the webhooks, oldest first, or an error response.
+#[utoipa::path(
+    get, path = "/admin/webhooks", tag = "admin", security(("admin_token" = [])),
+    summary = "List webhooks",
+    description = "Lists the registered webhooks, oldest first, without their secrets.",
+    responses(
+        (status = 200, description = "Registered webhooks", body = Vec<WebhookDetail>),
+        (status = 401, description = "Missing or wrong admin token", body = String, content_type = "text/plain"),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn read_webhooks(
+    AxumState(AppState { webhooks_dao, .. }): AxumState<AppState>,
+) -> ApiResult<Vec<WebhookDetail>> {
+    handlers_inner::read_webhooks(webhooks_dao.as_ref())
+        .await
+        .map(ApiResponse::ok)
+}
+
+/// Asynchronously deletes a webhook, stopping its deliveries.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { webhooks_dao, .. })` - The application state containing the `WebhooksDao`.
+/// * `ValidatedJson(webhook_uuid)` - The validated JSON payload containing the unique identifier of the webhook to be deleted.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either an empty response or an error response.
+#[utoipa::path(
+    delete, path = "/admin/webhook", tag = "admin", request_body = WebhookId, security(("admin_token" = [])),
+    summary = "Delete a webhook",
+    description = "Deletes a webhook. Deliveries already being retried still complete.",
+    responses(
+        (status = 200, description = "Webhook deleted"),
+        (status = 400, description = "No such webhook", body = String, content_type = "text/plain"),
+        (status = 401, description = "Missing or wrong admin token", body = String, content_type = "text/plain"),
+        (status = 422, description = "Invalid body", body = InvalidRequest),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn delete_webhook(
+    AxumState(AppState { webhooks_dao, .. }): AxumState<AppState>,
+    ValidatedJson(webhook_uuid): ValidatedJson<WebhookId>,
+) -> ApiResult<()> {
+    handlers_inner::delete_webhook(webhook_uuid, webhooks_dao.as_ref()).await?;
+
+    Ok(ApiResponse::empty())
+}
+
+/// Asynchronously starts rebuilding the search index in the background, swapping it for the current one when done.
+///
+/// # Arguments
+///
+/// * `AxumState(state)` - The application state, containing the `JobsDao` and the search index, the route only being
+///   mounted when there is one.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a `202 Accepted` JSON response with the job started, or an error response.
+#[utoipa::path(
+    post, path = "/admin/search/reindex", tag = "admin", security(("admin_token" = [])),
+    summary = "Rebuild the search index",
+    description = "Starts writing every question, along with its answers, to a new search index, in batches, while \
+        searches keep using the current one. Questions and answers written meanwhile go to both. Once every question \
+        was written, the new index replaces the current one at once, and the current one is deleted. The job records \
+        its progress after each batch, and is resumed where it was by another instance if it stalls, such as when \
+        its instance restarts. Only mounted when a search index is configured.",
+    responses(
+        (status = 202, description = "Job started", body = JobDetail),
+        (status = 401, description = "Missing or wrong admin token", body = String, content_type = "text/plain"),
+        (status = 409, description = "A reindex job is running already", body = String, content_type = "text/plain"),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn create_reindex_job(AxumState(state): AxumState<AppState>) -> ApiResult<JobDetail> {
+    let job = handlers_inner::create_job(JobKind::ReindexSearch, state.jobs_dao.as_ref()).await?;
+    jobs::spawn(&state, job.clone());
+
+    Ok(ApiResponse::ok(job).status(StatusCode::ACCEPTED))
+}
+
+/// Asynchronously reads a background job, along with its progress.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { jobs_dao, .. })` - The application state containing the `JobsDao`.
+/// * `Path(job_uuid)` - The unique identifier of the job.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the job detail or an error response.
+#[utoipa::path(
+    get, path = "/admin/jobs/{job_uuid}", tag = "admin", security(("admin_token" = [])),
+    summary = "Read a job",
+    description = "Returns a background job: whether it completed, the questions it processed so far, the last of \
+        them, which it resumes after, and the error of its last attempt at a batch if it failed.",
+    params(("job_uuid" = JobUuid, Path, description = "Unique identifier of the job")),
+    responses(
+        (status = 200, description = "Job and its progress", body = JobDetail),
+        (status = 400, description = "No such job", body = String, content_type = "text/plain"),
+        (status = 401, description = "Missing or wrong admin token", body = String, content_type = "text/plain"),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn read_job(
+    AxumState(AppState { jobs_dao, .. }): AxumState<AppState>,
+    Path(job_uuid): Path<JobUuid>,
+) -> ApiResult<JobDetail> {
+    handlers_inner::read_job(job_uuid, jobs_dao.as_ref())
+        .await
+        .map(ApiResponse::ok)
+}
+
+/// Reports the error budget consumption of the service level objectives.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { slo_tracker, .. })` - The application state containing the SLO tracker.
+///
+/// # Returns
+///
+/// A JSON response with the availability and latency error budgets over the rolling window.
+#[utoipa::path(
+    get, path = "/admin/slo", tag = "admin", security(("admin_token" = [])),
+    summary = "Read error budgets",
+    description = "Reports the availability and latency error budgets over the rolling SLO window.",
+    responses(
+        (status = 200, description = "Error budgets", body = SloReport),
+        (status = 401, description = "Missing or wrong admin token", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn read_slo(
+    AxumState(AppState { slo_tracker, .. }): AxumState<AppState>,
+) -> ApiResult<SloReport> {
+    Ok(ApiResponse::ok(slo_tracker.report(Instant::now())))
+}
+
+/// Reports the requests in flight on each API route, and the limits of the routes that have one.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { concurrency_tracker, .. })` - The application state containing the concurrency tracker.
+///
+/// # Returns
+///
+/// A JSON response with a report per route.
+#[utoipa::path(
+    get, path = "/admin/concurrency", tag = "admin", security(("admin_token" = [])),
+    summary = "Read requests in flight",
+    description = "Reports the requests in flight on each API route, and the limits of the routes that have one.",
+    responses(
+        (status = 200, description = "Requests in flight per route", body = Vec<RouteConcurrencyReport>),
+        (status = 401, description = "Missing or wrong admin token", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn read_concurrency(
+    AxumState(AppState { concurrency_tracker, .. }): AxumState<AppState>,
+) -> ApiResult<Vec<RouteConcurrencyReport>> {
+    Ok(ApiResponse::ok(concurrency_tracker.report()))
+}
+
+/// Reports the deprecated routes and query parameters, and how many requests used each since the server started.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { deprecations, .. })` - The application state containing the registry of deprecations.
+///
+/// # Returns
+///
+/// A JSON response with a report per deprecated feature.
+#[utoipa::path(
+    get, path = "/admin/deprecations", tag = "admin", security(("admin_token" = [])),
+    summary = "Read deprecated features",
+    description = "Reports the deprecated routes and query parameters, and how many requests used each since the \
+                   server started, so they can be removed once clients stopped using them.",
+    responses(
+        (status = 200, description = "Uses per deprecated feature", body = Vec<DeprecationReport>),
+        (status = 401, description = "Missing or wrong admin token", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn read_deprecations(
+    AxumState(AppState { deprecations, .. }): AxumState<AppState>,
+) -> ApiResult<Vec<DeprecationReport>> {
+    Ok(ApiResponse::ok(deprecations.report()))
+}
+
+/// Asynchronously reports the dead tuples, bloat and vacuum activity of the database tables, and the size and use of
+/// their indexes.
+///
+/// # Arguments
+///
+/// * `AxumState(maintenance_dao)` - The `MaintenanceDao` of the database, the route only being mounted when it has one.
+///
+/// # Returns
+///
+/// An `ApiResult` containing either a JSON response with the report, or an error response.
+#[utoipa::path(
+    get, path = "/admin/maintenance", tag = "admin", security(("admin_token" = [])),
+    summary = "Read database maintenance",
+    description = "Reports the dead tuples, estimated bloat and last (auto)vacuum and (auto)analyze of every table, \
+        and the size, estimated bloat and scans of every index. Only mounted when the database is Postgres.",
+    responses(
+        (status = 200, description = "Maintenance of the tables and indexes", body = MaintenanceReport),
+        (status = 401, description = "Missing or wrong admin token", body = String, content_type = "text/plain"),
+        (status = 500, description = "Unexpected error", body = String, content_type = "text/plain"),
+    )
+)]
+pub async fn read_maintenance(
+    AxumState(maintenance_dao): AxumState<Arc<dyn MaintenanceDao + Send + Sync>>,
+) -> ApiResult<MaintenanceReport> {
+    handlers_inner::read_maintenance(maintenance_dao.as_ref())
+        .await
+        .map(ApiResponse::ok)
+}
+
+// ---- Health ----
+
+/// Liveness probe, succeeds as long as the server is able to handle requests.
+///
+/// # Returns
+///
+/// A `200 OK` response.
+#[utoipa::path(
+    get, path = "/health", tag = "status",
+    summary = "Liveness probe",
+    description = "Succeeds as long as the server is able to handle requests.",
+    responses(
+        (status = 200, description = "Server is up"),
+    )
+)]
+pub async fn health() -> ApiResult<()> {
+    Ok(ApiResponse::empty())
+}
+
+/// Readiness probe, checks every external dependency the service needs.
+///
+/// # Arguments
+///
+/// * `AxumState(AppState { health_checks, .. })` - The application state containing the dependency checks.
+///
+/// # Returns
+///
+/// A JSON report of each check, with a `200 OK` status if all dependencies are up or `503 Service Unavailable` otherwise.
+#[utoipa::path(
+    get, path = "/ready", tag = "status",
+    summary = "Readiness probe",
+    description = "Checks every external dependency the service needs.",
+    responses(
+        (status = 200, description = "Every dependency is up", body = ReadinessReport),
+        (status = 503, description = "Some dependency is down", body = ReadinessReport),
+    )
+)]
+pub async fn ready(
+    AxumState(AppState { health_checks, .. }): AxumState<AppState>,
+) -> ApiResult<ReadinessReport> {
+    let report = check_readiness(&health_checks).await;
+
+    let status = match report.status {
+        HealthStatus::Up => StatusCode::OK,
+        HealthStatus::Down => StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    Ok(ApiResponse::ok(report).status(status))
+}
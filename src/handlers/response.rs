@@ -0,0 +1,471 @@
+use axum::{
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    models::{PaginationMeta, RenderFormat, ResponseProfile},
+    sanitize::render_markdown,
+};
+
+use super::HandlerError;
+
+/// Characters of answer contents kept by the compact profile
+pub const COMPACT_PREVIEW_CHARS: usize = 140;
+
+/// Result of every API handler, a successful `ApiResponse` or a `HandlerError`.
+pub type ApiResult<T> = Result<ApiResponse<T>, HandlerError>;
+
+/// Successful response of an API handler, with its body serialized as JSON.
+///
+/// Responses default to `200 OK` with no extra headers; use the builder methods to change that.
+#[derive(Debug)]
+pub struct ApiResponse<T> {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Option<T>,
+    pagination: Option<PaginationMeta>,
+    /// Entity tags of the request's `If-None-Match`, when the response is tagged with an `ETag`
+    if_none_match: Option<Vec<String>>,
+    profile: ResponseProfile,
+    render: RenderFormat,
+}
+
+/// Body of a paginated response, with the page itself under `data`
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    data: &'a T,
+    meta: &'a PaginationMeta,
+}
+
+impl<T> ApiResponse<T> {
+
+    /// Creates a `200 OK` response.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The body of the response.
+    ///
+    /// # Returns
+    ///
+    /// An `ApiResponse` with `body` as its JSON body.
+    pub fn ok(body: T) -> Self {
+        ApiResponse {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: Some(body),
+            pagination: None,
+            if_none_match: None,
+            profile: ResponseProfile::Full,
+            render: RenderFormat::Markdown,
+        }
+    }
+
+    /// Sets the status code of the response.
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Sets a header of the response, replacing any previous value.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.insert(name, value);
+        self
+    }
+
+    /// Wraps the body in an envelope, `{ "data": body, "meta": pagination }`.
+    pub fn pagination(mut self, pagination: PaginationMeta) -> Self {
+        self.pagination = Some(pagination);
+        self
+    }
+
+    /// Shapes the body for a response profile, as it is serialized.
+    pub fn profile(mut self, profile: ResponseProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Renders the markdown of the questions and answers in the body, as it is serialized.
+    pub fn render(mut self, render: RenderFormat) -> Self {
+        self.render = render;
+        self
+    }
+
+    /// Tags a successful response with an `ETag` hashing its body, and turns it into a `304 Not Modified` without a
+    /// body when the request's `If-None-Match` already holds that tag.
+    ///
+    /// # Arguments
+    ///
+    /// * `request_headers` - The headers of the request, read for `If-None-Match`.
+    ///
+    /// # Returns
+    ///
+    /// The `ApiResponse`, to be tagged once its body is serialized.
+    pub fn etag(mut self, request_headers: &HeaderMap) -> Self {
+        let tags = request_headers
+            .get_all(header::IF_NONE_MATCH)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|tag| tag.trim().to_owned())
+            .collect();
+
+        self.if_none_match = Some(tags);
+        self
+    }
+}
+
+/// The strong entity tag of a JSON body, quoted as `ETag` wants it.
+///
+/// View counts are left out of it: they change with reads of a question rather than with the question, and every
+/// flush of them would otherwise expire the tags held by clients and the versions they sync.
+pub(crate) fn entity_tag(json: &[u8]) -> String {
+    let versioned = match serde_json::from_slice::<Value>(json) {
+        Ok(mut value) => {
+            remove_view_counts(&mut value);
+            serde_json::to_vec(&value).unwrap_or_else(|_| json.to_vec())
+        }
+        Err(_) => json.to_vec(),
+    };
+
+    // Half of SHA-256 is plenty to tell versions of a body apart
+    format!("\"{}\"", hex::encode(&Sha256::digest(&versioned)[..16]))
+}
+
+/// Removes the view counts of a serialized body, wherever questions are in it.
+fn remove_view_counts(value: &mut Value) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(remove_view_counts),
+        Value::Object(fields) => {
+            fields.remove("view_count");
+            fields.values_mut().for_each(remove_view_counts);
+        }
+        _ => {}
+    }
+}
+
+/// Shapes a serialized body for a profile, wherever questions and answers are in it.
+fn shape(value: &mut Value, profile: ResponseProfile) {
+    if profile == ResponseProfile::Full {
+        return;
+    }
+
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(|item| shape(item, profile)),
+        Value::Object(fields) => {
+            fields.remove("description");
+
+            if let Some(Value::String(content)) = fields.get_mut("content") {
+                let length = content.chars().count();
+                if let Some((end, _)) = content.char_indices().nth(COMPACT_PREVIEW_CHARS) {
+                    content.truncate(end);
+                }
+                fields.insert("content_length".to_owned(), length.into());
+            }
+
+            fields.values_mut().for_each(|field| shape(field, profile));
+        }
+        _ => {}
+    }
+}
+
+/// Adds the sanitized HTML of the descriptions and contents of a serialized body next to their markdown, wherever
+/// questions and answers are in it.
+fn render(value: &mut Value) {
+    match value {
+        Value::Array(items) => items.iter_mut().for_each(render),
+        Value::Object(fields) => {
+            fields.values_mut().for_each(render);
+
+            for (field, rendered_field) in [("description", "rendered_description"), ("content", "rendered_content")] {
+                if let Some(Value::String(markdown)) = fields.get(field) {
+                    let html = render_markdown(markdown);
+                    fields.insert(rendered_field.to_owned(), html.into());
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether an `If-None-Match` holds a tag, compared weakly as RFC 9110 requires.
+fn matches_any(if_none_match: &[String], tag: &str) -> bool {
+    if_none_match
+        .iter()
+        .any(|candidate| candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == tag)
+}
+
+impl ApiResponse<()> {
+
+    /// Creates a `200 OK` response without a body.
+    pub fn empty() -> Self {
+        ApiResponse {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: None,
+            pagination: None,
+            if_none_match: None,
+            profile: ResponseProfile::Full,
+            render: RenderFormat::Markdown,
+        }
+    }
+}
+
+impl<T: Serialize> ApiResponse<T> {
+
+    /// Serializes a body, in its envelope if paginated, shaped for the profile and rendered.
+    fn to_json(&self, body: &T) -> serde_json::Result<Vec<u8>> {
+        let envelope = self.pagination.as_ref().map(|meta| Envelope { data: body, meta });
+
+        // Only shaped or rendered bodies go through a `Value`, which would sort the fields of every other one
+        if self.profile == ResponseProfile::Full && self.render == RenderFormat::Markdown {
+            return match envelope {
+                Some(envelope) => serde_json::to_vec(&envelope),
+                None => serde_json::to_vec(body),
+            };
+        }
+
+        let mut value = match envelope {
+            Some(envelope) => serde_json::to_value(envelope)?,
+            None => serde_json::to_value(body)?,
+        };
+        shape(&mut value, self.profile);
+        // After shaping, so compact answers are rendered from their preview
+        if self.render == RenderFormat::Html {
+            render(&mut value);
+        }
+
+        serde_json::to_vec(&value)
+    }
+}
+
+impl<T: Serialize> IntoResponse for ApiResponse<T> {
+    /// Converts the `ApiResponse` into an Axum response.
+    ///
+    /// # Returns
+    ///
+    /// An Axum response with the status code, headers and JSON body of the `ApiResponse`.
+    fn into_response(self) -> Response {
+        let json = match &self.body {
+            Some(body) => match self.to_json(body) {
+                Ok(json) => Some(json),
+                Err(err) => return (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()).into_response(),
+            },
+            None => None,
+        };
+
+        let tag = match (&self.if_none_match, &json) {
+            (Some(_), Some(json)) if self.status.is_success() => Some(entity_tag(json)),
+            _ => None,
+        };
+        let not_modified = matches!((&self.if_none_match, &tag), (Some(tags), Some(tag)) if matches_any(tags, tag));
+
+        let mut response = match json {
+            _ if not_modified => StatusCode::NOT_MODIFIED.into_response(),
+            Some(json) => {
+                let content_type = [(header::CONTENT_TYPE, HeaderValue::from_static("application/json"))];
+                (self.status, content_type, json).into_response()
+            }
+            None => self.status.into_response(),
+        };
+
+        response.headers_mut().extend(self.headers);
+        if let Some(tag) = tag.and_then(|tag| HeaderValue::from_str(&tag).ok()) {
+            response.headers_mut().insert(header::ETAG, tag);
+        }
+        response
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::http::header;
+    use serde_json::{json, Value};
+
+    async fn body(response: Response) -> Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap_or(Value::Null)
+    }
+
+    #[tokio::test]
+    async fn should_serialize_body_as_json() {
+        let response = ApiResponse::ok(vec!["first", "second"]).into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(body(response).await, json!(["first", "second"]));
+    }
+
+    #[tokio::test]
+    async fn should_set_status_and_headers() {
+        let response = ApiResponse::ok("created")
+            .status(StatusCode::CREATED)
+            .header(header::CACHE_CONTROL, HeaderValue::from_static("no-store"))
+            .into_response();
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+    }
+
+    #[tokio::test]
+    async fn should_wrap_paginated_body_in_envelope() {
+        let response = ApiResponse::ok(vec![1, 2])
+            .pagination(PaginationMeta {
+                limit: 2,
+                offset: 4,
+                total: Some(7),
+            })
+            .into_response();
+
+        assert_eq!(
+            body(response).await,
+            json!({ "data": [1, 2], "meta": { "limit": 2, "offset": 4, "total": 7 } })
+        );
+    }
+
+    fn if_none_match(value: &str) -> HeaderMap {
+        HeaderMap::from_iter([(header::IF_NONE_MATCH, HeaderValue::from_str(value).unwrap())])
+    }
+
+    #[tokio::test]
+    async fn should_tag_body_with_its_hash() {
+        let response = ApiResponse::ok(vec![1, 2]).etag(&HeaderMap::new()).into_response();
+        let other = ApiResponse::ok(vec![1, 3]).etag(&HeaderMap::new()).into_response();
+
+        let tag = response.headers()[header::ETAG].to_str().unwrap().to_owned();
+        assert_eq!(tag, entity_tag(b"[1,2]"));
+        assert_eq!(tag.len(), 2 + 32);
+        assert_ne!(other.headers()[header::ETAG], tag);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(body(response).await, json!([1, 2]));
+    }
+
+    #[tokio::test]
+    async fn should_answer_not_modified_when_tag_matches() {
+        let tag = entity_tag(b"[1,2]");
+
+        for value in [tag.clone(), format!("W/{}", tag), format!("\"other\", {}", tag), "*".to_owned()] {
+            let response = ApiResponse::ok(vec![1, 2])
+                .header(header::CACHE_CONTROL, HeaderValue::from_static("no-cache"))
+                .etag(&if_none_match(&value))
+                .into_response();
+
+            assert_eq!(response.status(), StatusCode::NOT_MODIFIED, "{}", value);
+            assert_eq!(response.headers()[header::ETAG], tag.as_str());
+            assert_eq!(response.headers()[header::CACHE_CONTROL], "no-cache");
+            assert_eq!(body(response).await, Value::Null);
+        }
+    }
+
+    #[tokio::test]
+    async fn should_serve_body_when_tag_differs() {
+        let response = ApiResponse::ok(vec![1, 2]).etag(&if_none_match("\"stale\"")).into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body(response).await, json!([1, 2]));
+    }
+
+    #[tokio::test]
+    async fn should_tag_paginated_body_with_its_envelope() {
+        let meta = PaginationMeta {
+            limit: 2,
+            offset: 0,
+            total: None,
+        };
+        let first = ApiResponse::ok(vec![1, 2]).pagination(meta.clone()).etag(&HeaderMap::new()).into_response();
+        let second = ApiResponse::ok(vec![1, 2])
+            .pagination(PaginationMeta { total: Some(2), ..meta })
+            .etag(&HeaderMap::new())
+            .into_response();
+
+        assert_ne!(first.headers()[header::ETAG], second.headers()[header::ETAG]);
+    }
+
+    #[tokio::test]
+    async fn should_shape_body_for_compact_profile() {
+        let content = "é".repeat(COMPACT_PREVIEW_CHARS + 10);
+        let question = json!({
+            "title": "Title",
+            "description": "Dropped",
+            "answers": [{ "content": content }, { "content": "Short" }],
+        });
+
+        let response = ApiResponse::ok(vec![question]).profile(ResponseProfile::Compact).into_response();
+
+        assert_eq!(
+            body(response).await,
+            json!([{
+                "title": "Title",
+                "answers": [
+                    { "content": "é".repeat(COMPACT_PREVIEW_CHARS), "content_length": COMPACT_PREVIEW_CHARS + 10 },
+                    { "content": "Short", "content_length": 5 },
+                ],
+            }])
+        );
+    }
+
+    #[tokio::test]
+    async fn should_render_markdown_of_body_to_html() {
+        let question = json!({
+            "title": "**Title**",
+            "description": "Why *this*?<script>alert(1)</script>",
+            "answers": [{ "content": "Use `unwrap`" }],
+        });
+
+        let response = ApiResponse::ok(question).render(RenderFormat::Html).into_response();
+
+        assert_eq!(
+            body(response).await,
+            json!({
+                "title": "**Title**",
+                "description": "Why *this*?<script>alert(1)</script>",
+                "rendered_description": "<p>Why <em>this</em>?</p>\n",
+                "answers": [{ "content": "Use `unwrap`", "rendered_content": "<p>Use <code>unwrap</code></p>\n" }],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn should_tag_shaped_body() {
+        let question = json!({ "title": "Title", "description": "Dropped" });
+
+        let full = ApiResponse::ok(question.clone()).etag(&HeaderMap::new()).into_response();
+        let compact = ApiResponse::ok(question)
+            .profile(ResponseProfile::Compact)
+            .etag(&HeaderMap::new())
+            .into_response();
+
+        assert_ne!(full.headers()[header::ETAG], compact.headers()[header::ETAG]);
+        assert_eq!(compact.headers()[header::ETAG], entity_tag(br#"{"title":"Title"}"#).as_str());
+    }
+
+    #[tokio::test]
+    async fn should_tag_body_regardless_of_view_counts() {
+        let read = |view_count: i64| json!([{ "title": "Title", "view_count": view_count, "answers": [] }]);
+
+        let before = ApiResponse::ok(read(1)).etag(&HeaderMap::new()).into_response();
+        let after = ApiResponse::ok(read(5)).etag(&HeaderMap::new()).into_response();
+
+        assert_eq!(before.headers()[header::ETAG], after.headers()[header::ETAG]);
+        assert_eq!(entity_tag(&serde_json::to_vec(&read(1)).unwrap()), entity_tag(br#"[{"title":"Title","answers":[]}]"#));
+    }
+
+    #[tokio::test]
+    async fn should_have_no_body_when_empty() {
+        let response = ApiResponse::empty().status(StatusCode::ACCEPTED).into_response();
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert!(!response.headers().contains_key(header::CONTENT_TYPE));
+        assert_eq!(body(response).await, Value::Null);
+    }
+}
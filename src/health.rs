@@ -0,0 +1,136 @@
+use std::{sync::Arc, time::{Duration, Instant}};
+
+use async_trait::async_trait;
+
+use crate::models::{DependencyCheck, HealthStatus, ReadinessReport};
+
+/// How long a single dependency check may take before it is reported as down
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A trait representing a check of an external dependency the service needs to serve requests.
+#[async_trait]
+pub trait HealthCheck {
+    /// The name the dependency is reported under.
+    fn name(&self) -> &'static str;
+
+    /// Asynchronously checks whether the dependency is reachable.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned if the dependency is usable, otherwise, a description of the problem is returned.
+    async fn check(&self) -> Result<(), String>;
+}
+
+/// Asynchronously runs all dependency checks concurrently.
+///
+/// # Arguments
+///
+/// * `checks` - The dependency checks to run.
+///
+/// # Returns
+///
+/// A `ReadinessReport` holding the outcome of each check, which is up only if all checks are up.
+pub async fn check_readiness(checks: &[Arc<dyn HealthCheck + Send + Sync>]) -> ReadinessReport {
+    let checks = futures::future::join_all(checks.iter().map(|check| run_check(check.as_ref()))).await;
+
+    let status = if checks.iter().all(|c| c.status == HealthStatus::Up) {
+        HealthStatus::Up
+    } else {
+        HealthStatus::Down
+    };
+
+    ReadinessReport { status, checks }
+}
+
+/// Asynchronously runs a single check, timing it and bounding it by `CHECK_TIMEOUT`.
+async fn run_check(check: &(dyn HealthCheck + Send + Sync)) -> DependencyCheck {
+    let started = Instant::now();
+
+    let result = match tokio::time::timeout(CHECK_TIMEOUT, check.check()).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("Timed out after {:?}", CHECK_TIMEOUT)),
+    };
+
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(()) => DependencyCheck {
+            name: check.name().to_owned(),
+            status: HealthStatus::Up,
+            latency_ms,
+            error: None,
+        },
+        Err(err) => {
+            warn!("Health check {} failed: {}", check.name(), err);
+
+            DependencyCheck {
+                name: check.name().to_owned(),
+                status: HealthStatus::Down,
+                latency_ms,
+                error: Some(err),
+            }
+        }
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct HealthCheckMock {
+        name: &'static str,
+        response: Result<(), String>,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl HealthCheck for HealthCheckMock {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+        async fn check(&self) -> Result<(), String> {
+            tokio::time::sleep(self.delay).await;
+            self.response.clone()
+        }
+    }
+
+    fn mock(name: &'static str, response: Result<(), String>) -> Arc<dyn HealthCheck + Send + Sync> {
+        Arc::new(HealthCheckMock { name, response, delay: Duration::ZERO })
+    }
+
+    #[tokio::test]
+    async fn should_be_up_when_all_checks_pass() {
+        let report = check_readiness(&[mock("database", Ok(())), mock("cache", Ok(()))]).await;
+
+        assert_eq!(report.status, HealthStatus::Up);
+        assert_eq!(report.checks.len(), 2);
+        assert!(report.checks.iter().all(|c| c.error.is_none()));
+    }
+
+    #[tokio::test]
+    async fn should_be_down_when_any_check_fails() {
+        let report = check_readiness(&[mock("database", Ok(())), mock("cache", Err("refused".to_owned()))]).await;
+
+        assert_eq!(report.status, HealthStatus::Down);
+        assert_eq!(report.checks[0].status, HealthStatus::Up);
+        assert_eq!(report.checks[1].status, HealthStatus::Down);
+        assert_eq!(report.checks[1].error, Some("refused".to_owned()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_be_down_when_check_times_out() {
+        let check: Arc<dyn HealthCheck + Send + Sync> = Arc::new(HealthCheckMock {
+            name: "database",
+            response: Ok(()),
+            delay: CHECK_TIMEOUT * 2,
+        });
+
+        let report = check_readiness(&[check]).await;
+
+        assert_eq!(report.status, HealthStatus::Down);
+    }
+}
@@ -0,0 +1,447 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    body::{to_bytes, Body},
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderName, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, TimeDelta, Utc};
+use sha2::{Digest, Sha256};
+use tokio::task::JoinHandle;
+
+use crate::{
+    client_ip::ClientIp,
+    handlers::ApiResponse,
+    models::{FieldError, IdempotentRequest, IdempotentResponse, InvalidRequest},
+    persistance::idempotency_dao::IdempotencyDao,
+    AppState,
+};
+
+// Clients retrying a create after a timeout cannot tell whether the first attempt went through. Sending the same
+// `Idempotency-Key` with each attempt makes the retries safe: the first request claims the key, and its response is
+// recorded and replayed to the others instead of creating the content again. Only successful responses are recorded,
+// as failed requests changed nothing and can simply be served again.
+
+/// Header naming the request a client may retry
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+
+/// Header telling whether the response to a request with an idempotency key was replayed, `true`, or served anew, `false`
+pub const IDEMPOTENCY_REPLAYED: HeaderName = HeaderName::from_static("idempotency-replayed");
+
+/// Header giving the HTTP date until which retries with the same idempotency key get the same response
+pub const IDEMPOTENCY_KEY_EXPIRES: HeaderName = HeaderName::from_static("idempotency-key-expires");
+
+/// Longest idempotency key accepted, UUIDs being the expected kind of key
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Largest request body hashed, the default limit of the JSON extractor the body is read by afterwards
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+
+/// Seconds clients are asked to wait before retrying a request whose key is still in use
+const RETRY_AFTER_SECS: &str = "1";
+
+/// Wait between two deletions of expired keys
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// A key claimed by the request being served, released if the request does not complete it, so that it can be retried
+struct Claim {
+    dao: Arc<dyn IdempotencyDao + Send + Sync>,
+    route: String,
+    key: String,
+    settled: bool,
+}
+
+impl Claim {
+    /// Records the response to replay to retries, releasing the key if it cannot be.
+    ///
+    /// # Returns
+    ///
+    /// Whether the response was recorded.
+    async fn complete(&mut self, response: &IdempotentResponse) -> bool {
+        self.settled = true;
+
+        match self.dao.complete_key(&self.route, &self.key, response).await {
+            Ok(()) => true,
+            Err(err) => {
+                error!("Failed to record the response for an idempotency key: {:?}", err);
+                self.release().await;
+                false
+            }
+        }
+    }
+
+    /// Releases the key, so that the request can be retried.
+    async fn release(&mut self) {
+        self.settled = true;
+
+        if let Err(err) = self.dao.release_key(&self.route, &self.key).await {
+            error!("Failed to release an idempotency key: {:?}", err);
+        }
+    }
+}
+
+impl Drop for Claim {
+    /// Releases the key of a request dropped before being served, as when its client disconnects.
+    fn drop(&mut self) {
+        if self.settled {
+            return;
+        }
+
+        let (dao, route, key) = (self.dao.clone(), std::mem::take(&mut self.route), std::mem::take(&mut self.key));
+        tokio::spawn(async move {
+            if let Err(err) = dao.release_key(&route, &key).await {
+                error!("Failed to release an idempotency key: {:?}", err);
+            }
+        });
+    }
+}
+
+/// Middleware replaying the recorded response to requests retried with the same `Idempotency-Key` header.
+///
+/// Requests without the header are served as usual. A key is scoped to the route it was sent to, and expires after
+/// the configured TTL. Responses to requests with a key tell whether they were replayed and, once recorded, until
+/// when, and the outcome is logged along with the client address for the audit log.
+///
+/// # Arguments
+///
+/// * `State(state)` - The application state, with the `IdempotencyDao` keys are recorded in.
+/// * `client_ip` - The address of the client, if known, for the audit log.
+/// * `request` - The incoming request.
+/// * `next` - The rest of the middleware stack.
+///
+/// # Returns
+///
+/// The response of the inner service, the response recorded for the key, a `409 Conflict` while the first request
+/// with the key is still being served, or a `422 Unprocessable Entity` for an invalid key or a key already used with
+/// another body.
+pub async fn replay_responses(
+    State(state): State<AppState>,
+    client_ip: Option<ClientIp>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(key) = request.headers().get(&IDEMPOTENCY_KEY) else {
+        return next.run(request).await;
+    };
+
+    let key = match key.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LENGTH => key.to_owned(),
+        _ => {
+            return invalid_key("invalid_idempotency_key", "must be 1 to 255 visible ASCII characters").into_response()
+        }
+    };
+
+    let route = match request.extensions().get::<MatchedPath>() {
+        Some(path) => path.as_str().to_owned(),
+        None => request.uri().path().to_owned(),
+    };
+    let client = client_ip.map_or_else(|| "an unknown address".to_owned(), |ip| ip.to_string());
+    let audit = |outcome: &str| info!("Idempotent request to {} with key {} from {}: {}.", route, key, client, outcome);
+
+    let (parts, body) = request.into_parts();
+    let Ok(body) = to_bytes(body, MAX_BODY_BYTES).await else {
+        return (StatusCode::PAYLOAD_TOO_LARGE, "Request body is too large.").into_response();
+    };
+    let request_hash = hex::encode(Sha256::digest(&body));
+
+    let ttl = state.idempotency_key_ttl;
+    let dao = state.idempotency_dao.clone();
+    let claimed_at = Utc::now();
+
+    match dao.claim_key(&route, &key, &request_hash, expired_before(ttl)).await {
+        Ok(None) => {}
+        Ok(Some(request)) if request.request_hash != request_hash => {
+            audit("refused, the key was already used with another request body");
+            return invalid_key("idempotency_key_reused", "was already used with another request body").into_response();
+        }
+        Ok(Some(IdempotentRequest { response: None, .. })) => {
+            audit("refused, the first request with the key is still being served");
+            return (
+                StatusCode::CONFLICT,
+                [(header::RETRY_AFTER, RETRY_AFTER_SECS)],
+                "A request with this idempotency key is still being served, please try again.",
+            )
+                .into_response();
+        }
+        Ok(Some(IdempotentRequest { response: Some(response), claimed_at, .. })) => {
+            audit("replayed");
+            return replay(response, expires_at(claimed_at, ttl));
+        }
+        Err(err) => {
+            error!("Failed to claim an idempotency key: {:?}", err);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to claim the idempotency key.").into_response();
+        }
+    }
+
+    let mut claim = Claim { dao, route: route.clone(), key: key.clone(), settled: false };
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    if !response.status().is_success() {
+        claim.release().await;
+        audit("served, failed and not recorded");
+        return with_idempotency_headers(response, false, None);
+    }
+
+    let (parts, body) = response.into_parts();
+    let body = match to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(err) => {
+            claim.release().await;
+            error!("Failed to read a response to record for an idempotency key: {:?}", err);
+            return (StatusCode::INTERNAL_SERVER_ERROR, "Failed to read the response.").into_response();
+        }
+    };
+
+    let recorded = match std::str::from_utf8(&body) {
+        Ok(text) => {
+            let recorded = IdempotentResponse {
+                status: parts.status.as_u16(),
+                body: text.to_owned(),
+            };
+            claim.complete(&recorded).await
+        }
+        // Only JSON responses are expected, which are always UTF-8
+        Err(_) => {
+            claim.release().await;
+            false
+        }
+    };
+
+    audit(if recorded { "served and recorded" } else { "served, not recorded" });
+    let expires_at = recorded.then(|| expires_at(claimed_at, ttl)).flatten();
+
+    with_idempotency_headers(Response::from_parts(parts, Body::from(body)), false, expires_at)
+}
+
+/// Starts deleting expired idempotency keys in the background, so they do not pile up.
+///
+/// # Arguments
+///
+/// * `state` - The application state, whose `IdempotencyDao` is purged.
+///
+/// # Returns
+///
+/// A `JoinHandle` of the task deleting keys, which runs until aborted.
+pub fn start(state: &AppState) -> JoinHandle<()> {
+    let (dao, ttl) = (state.idempotency_dao.clone(), state.idempotency_key_ttl);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = dao.delete_expired_keys(expired_before(ttl)).await {
+                error!("Failed to delete expired idempotency keys: {:?}", err);
+            }
+        }
+    })
+}
+
+/// The time before which keys were claimed long enough ago to have expired.
+fn expired_before(ttl: Duration) -> DateTime<Utc> {
+    TimeDelta::from_std(ttl)
+        .ok()
+        .and_then(|ttl| Utc::now().checked_sub_signed(ttl))
+        .unwrap_or(DateTime::<Utc>::MIN_UTC)
+}
+
+/// The time a key claimed at `claimed_at` expires, `None` if too far in the future to tell.
+fn expires_at(claimed_at: DateTime<Utc>, ttl: Duration) -> Option<DateTime<Utc>> {
+    TimeDelta::from_std(ttl).ok().and_then(|ttl| claimed_at.checked_add_signed(ttl))
+}
+
+/// Replays the response recorded for a key.
+fn replay(response: IdempotentResponse, expires_at: Option<DateTime<Utc>>) -> Response {
+    let status = StatusCode::from_u16(response.status).unwrap_or(StatusCode::OK);
+    let response = (status, [(header::CONTENT_TYPE, "application/json")], response.body).into_response();
+
+    with_idempotency_headers(response, true, expires_at)
+}
+
+/// Tells whether a response was replayed and, if recorded, until when retries get it.
+fn with_idempotency_headers(mut response: Response, replayed: bool, expires_at: Option<DateTime<Utc>>) -> Response {
+    let headers = response.headers_mut();
+    headers.insert(IDEMPOTENCY_REPLAYED, HeaderValue::from_static(if replayed { "true" } else { "false" }));
+
+    // HTTP dates are always in GMT
+    let expires = expires_at.map(|at| at.format("%a, %d %b %Y %H:%M:%S GMT").to_string());
+    if let Some(expires) = expires.and_then(|expires| HeaderValue::from_str(&expires).ok()) {
+        headers.insert(IDEMPOTENCY_KEY_EXPIRES, expires);
+    }
+
+    response
+}
+
+fn invalid_key(code: &str, message: &str) -> ApiResponse<InvalidRequest> {
+    let error = FieldError {
+        field: Some("Idempotency-Key".to_owned()),
+        code: code.to_owned(),
+        message: message.to_owned(),
+    };
+
+    ApiResponse::ok(InvalidRequest { errors: vec![error] }).status(StatusCode::UNPROCESSABLE_ENTITY)
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use axum::{middleware::from_fn_with_state, routing::post, Router};
+    use tower::ServiceExt;
+
+    use crate::config::Config;
+
+    /// A route counting the requests it served, answering each with its number
+    fn app(state: AppState, served: Arc<AtomicU64>, status: StatusCode) -> Router {
+        let handler = move |body: String| async move {
+            let count = served.fetch_add(1, Ordering::SeqCst) + 1;
+            (status, format!("{{\"count\":{},\"body\":{:?}}}", count, body))
+        };
+
+        Router::new()
+            .route("/question", post(handler).route_layer(from_fn_with_state(state.clone(), replay_responses)))
+            .with_state(state)
+    }
+
+    fn request(key: Option<&str>, body: &str) -> Request {
+        let mut builder = Request::post("/question");
+        if let Some(key) = key {
+            builder = builder.header(IDEMPOTENCY_KEY, key);
+        }
+        builder.body(Body::from(body.to_owned())).unwrap()
+    }
+
+    /// Sends a request, returning the status, `Idempotency-Replayed` header and body of the response
+    async fn send(app: &Router, key: Option<&str>, body: &str) -> (StatusCode, Option<String>, String) {
+        let response = app.clone().oneshot(request(key, body)).await.unwrap();
+        let replayed = response.headers().get(&IDEMPOTENCY_REPLAYED).map(|value| value.to_str().unwrap().to_owned());
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+        (status, replayed, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn should_replay_response_to_retries() {
+        let served = Arc::new(AtomicU64::new(0));
+        let app = app(AppState::in_memory(&Config::default()), served.clone(), StatusCode::OK);
+
+        let first = send(&app, Some("key-1"), "a").await;
+        let retry = send(&app, Some("key-1"), "a").await;
+
+        assert_eq!(first, (StatusCode::OK, Some("false".to_owned()), r#"{"count":1,"body":"a"}"#.to_owned()));
+        assert_eq!(retry, (StatusCode::OK, Some("true".to_owned()), first.2));
+        assert_eq!(served.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn should_serve_requests_without_keys_every_time() {
+        let served = Arc::new(AtomicU64::new(0));
+        let app = app(AppState::in_memory(&Config::default()), served.clone(), StatusCode::OK);
+
+        let (_, replayed, _) = send(&app, None, "a").await;
+        assert_eq!(replayed, None);
+        send(&app, None, "a").await;
+        send(&app, Some("key-1"), "a").await;
+        send(&app, Some("key-2"), "a").await;
+
+        assert_eq!(served.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn should_reject_key_reused_with_another_body() {
+        let app = app(AppState::in_memory(&Config::default()), Arc::default(), StatusCode::OK);
+
+        send(&app, Some("key-1"), "a").await;
+        let (status, _, body) = send(&app, Some("key-1"), "b").await;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body.contains("idempotency_key_reused"), "{}", body);
+    }
+
+    #[tokio::test]
+    async fn should_reject_invalid_key() {
+        let app = app(AppState::in_memory(&Config::default()), Arc::default(), StatusCode::OK);
+
+        let (status, _, body) = send(&app, Some(&"k".repeat(MAX_KEY_LENGTH + 1)), "a").await;
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body.contains("invalid_idempotency_key"), "{}", body);
+    }
+
+    #[tokio::test]
+    async fn should_serve_failed_requests_again() {
+        let served = Arc::new(AtomicU64::new(0));
+        let app = app(AppState::in_memory(&Config::default()), served.clone(), StatusCode::CONFLICT);
+
+        send(&app, Some("key-1"), "a").await;
+        let (status, replayed, _) = send(&app, Some("key-1"), "a").await;
+
+        assert_eq!((status, replayed.as_deref()), (StatusCode::CONFLICT, Some("false")));
+        assert_eq!(served.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn should_refuse_retries_while_first_request_is_served() {
+        let state = AppState::in_memory(&Config::default());
+        state.idempotency_dao.claim_key("/question", "key-1", &hex::encode(Sha256::digest(b"a")), Utc::now()).await.unwrap();
+        let app = app(state, Arc::default(), StatusCode::OK);
+
+        let (status, replayed, _) = send(&app, Some("key-1"), "a").await;
+
+        assert_eq!((status, replayed), (StatusCode::CONFLICT, None));
+    }
+
+    #[tokio::test]
+    async fn should_serve_expired_keys_again() {
+        let served = Arc::new(AtomicU64::new(0));
+        let mut state = AppState::in_memory(&Config::default());
+        state.idempotency_key_ttl = Duration::ZERO;
+        let app = app(state, served.clone(), StatusCode::OK);
+
+        send(&app, Some("key-1"), "a").await;
+        tokio::time::sleep(Duration::from_millis(2)).await;
+        let (_, replayed, _) = send(&app, Some("key-1"), "b").await;
+
+        assert_eq!(replayed.as_deref(), Some("false"));
+        assert_eq!(served.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn should_tell_when_keys_expire() {
+        let mut state = AppState::in_memory(&Config::default());
+        state.idempotency_key_ttl = Duration::from_secs(60 * 60);
+        let app = app(state, Arc::default(), StatusCode::OK);
+
+        let expires = |response: &Response| {
+            let expires = response.headers()[&IDEMPOTENCY_KEY_EXPIRES].to_str().unwrap();
+            DateTime::parse_from_rfc2822(&expires.replace("GMT", "+0000")).unwrap().with_timezone(&Utc)
+        };
+
+        let first = app.clone().oneshot(request(Some("key-1"), "a")).await.unwrap();
+        let retry = app.clone().oneshot(request(Some("key-1"), "a")).await.unwrap();
+        let expected = Utc::now() + TimeDelta::hours(1);
+
+        assert!((expected - expires(&first)).num_seconds().abs() <= 2, "{:?}", first.headers());
+        // The key is recorded as claimed just after the first request is, which may fall in the next second
+        assert!((expires(&retry) - expires(&first)).num_seconds().abs() <= 1);
+    }
+
+    #[test]
+    fn should_format_expiry_as_http_date() {
+        let expires_at = DateTime::parse_from_rfc3339("2026-10-16T14:05:09Z").unwrap().with_timezone(&Utc);
+        let response = with_idempotency_headers(StatusCode::OK.into_response(), true, Some(expires_at));
+
+        assert_eq!(response.headers()[&IDEMPOTENCY_KEY_EXPIRES], "Fri, 16 Oct 2026 14:05:09 GMT");
+        assert_eq!(response.headers()[&IDEMPOTENCY_REPLAYED], "true");
+    }
+}
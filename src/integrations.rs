@@ -0,0 +1,342 @@
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde_json::{json, Value};
+use tokio::{
+    sync::broadcast::{error::RecvError, Receiver},
+    task::JoinHandle,
+    time::Instant,
+};
+
+use crate::{excerpt::excerpt, models::QuestionDetail, outbox::ContentEvent, AppState};
+
+// New questions are announced in team chats, through the incoming webhooks of Slack channels or Discord servers.
+// Messages are rendered from a template set by the operator, into which the question is inserted escaped, so a title
+// cannot ping a whole channel. The webhook URLs are secrets, anyone knowing one can post to the channel, so they are
+// never logged: webhooks are named by their kind and position in the configuration instead.
+//
+// Both services rate limit incoming webhooks, so each webhook is sent at most a configured number of messages per
+// minute, and questions over that are left unannounced rather than queued up behind a burst.
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Window messages are counted over, for the rate limit
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Most characters of the description inserted in a message
+const DESCRIPTION_CHARS: usize = 280;
+
+/// Longest message Discord accepts, in characters
+const DISCORD_MAX_CHARS: usize = 2000;
+
+/// Represents the chat services messages can be posted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChatKind {
+    Slack,
+    Discord,
+}
+
+impl FromStr for ChatKind {
+    type Err = String;
+
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        match kind {
+            "slack" => Ok(ChatKind::Slack),
+            "discord" => Ok(ChatKind::Discord),
+            _ => Err(format!("Unknown chat service: {}", kind)),
+        }
+    }
+}
+
+impl ChatKind {
+
+    /// Escapes text inserted in a message, so the service shows it as is.
+    fn escape(self, text: &str) -> String {
+        match self {
+            // Slack only interprets these, `<!channel>` pinging everyone in the channel
+            ChatKind::Slack => text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;"),
+            // Mentions are disabled on the whole message instead, see `payload`
+            ChatKind::Discord => text.to_owned(),
+        }
+    }
+
+    /// The body of the request posting a message.
+    fn payload(self, message: &str) -> Value {
+        match self {
+            ChatKind::Slack => json!({ "text": message }),
+            ChatKind::Discord => json!({
+                "content": message.chars().take(DISCORD_MAX_CHARS).collect::<String>(),
+                // Neither `@everyone` nor any user or role mentioned in a question is pinged
+                "allowed_mentions": { "parse": [] },
+            }),
+        }
+    }
+}
+
+/// Messages sent to a webhook in the current window
+#[derive(Debug)]
+struct RateWindow {
+    started_at: Instant,
+    sent: u32,
+}
+
+/// An incoming webhook of a chat service, and how many messages it was sent lately
+#[derive(Debug)]
+struct ChatWebhook {
+    kind: ChatKind,
+    url: String,
+    window: Mutex<RateWindow>,
+}
+
+impl ChatWebhook {
+
+    /// Counts a message towards the rate limit, unless the webhook is at its limit.
+    ///
+    /// # Returns
+    ///
+    /// Whether the message may be sent.
+    fn try_send(&self, limit: u32) -> bool {
+        let mut window = self.window.lock().unwrap();
+
+        if window.started_at.elapsed() >= RATE_WINDOW {
+            *window = RateWindow { started_at: Instant::now(), sent: 0 };
+        }
+
+        let allowed = window.sent < limit;
+        if allowed {
+            window.sent += 1;
+        }
+
+        allowed
+    }
+}
+
+/// Chat webhooks new questions are announced to
+pub struct ChatIntegrations {
+    client: reqwest::Client,
+    webhooks: Vec<ChatWebhook>,
+    /// The message, in which `{title}`, `{description}` and `{question_uuid}` are replaced with those of the question
+    template: String,
+    /// Most messages sent to each webhook per minute
+    messages_per_minute: u32,
+}
+
+impl ChatIntegrations {
+
+    /// Creates the integrations.
+    ///
+    /// # Arguments
+    ///
+    /// * `webhooks` - The kind and URL of each incoming webhook.
+    /// * `template` - The message, with `{title}`, `{description}` and `{question_uuid}` placeholders.
+    /// * `messages_per_minute` - Most messages sent to each webhook per minute.
+    pub fn new(webhooks: impl IntoIterator<Item = (ChatKind, String)>, template: &str, messages_per_minute: u32) -> Self {
+        let webhooks = webhooks
+            .into_iter()
+            .map(|(kind, url)| ChatWebhook {
+                kind,
+                url,
+                window: Mutex::new(RateWindow { started_at: Instant::now(), sent: 0 }),
+            })
+            .collect();
+
+        ChatIntegrations {
+            // The URLs are set by the operator, so they do not need an `OutboundClient`
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("Failed to create chat HTTP client!"),
+            webhooks,
+            template: template.to_owned(),
+            messages_per_minute,
+        }
+    }
+
+    /// Renders the message announcing a question to a chat service.
+    fn render(&self, kind: ChatKind, question: &QuestionDetail) -> String {
+        let values = [
+            ("{title}", kind.escape(&question.title)),
+            ("{description}", kind.escape(&excerpt(&question.description, DESCRIPTION_CHARS))),
+            ("{question_uuid}", question.question_uuid.to_string()),
+        ];
+
+        // In a single pass, so placeholders written in a question are left as they are
+        let mut message = String::new();
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find('{') {
+            message.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            match values.iter().find(|(placeholder, _)| rest.starts_with(placeholder)) {
+                Some((placeholder, value)) => {
+                    message.push_str(value);
+                    rest = &rest[placeholder.len()..];
+                }
+                None => {
+                    message.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        message.push_str(rest);
+
+        message
+    }
+
+    /// Asynchronously announces a question to every webhook not at its rate limit.
+    async fn announce(&self, question: &QuestionDetail) {
+        for (i, webhook) in self.webhooks.iter().enumerate() {
+            if !webhook.try_send(self.messages_per_minute) {
+                let question_uuid = question.question_uuid;
+                warn!("Chat webhook {} ({:?}) is at its rate limit, not announcing {}.", i, webhook.kind, question_uuid);
+                continue;
+            }
+
+            let payload = webhook.kind.payload(&self.render(webhook.kind, question));
+            let result = self.client.post(&webhook.url).json(&payload).send().await;
+
+            match result {
+                Ok(response) if response.status().is_success() => {}
+                Ok(response) => error!("Chat webhook {} ({:?}) answered with {}.", i, webhook.kind, response.status()),
+                // Without the URL, which reqwest errors carry
+                Err(err) => error!("Failed to post to chat webhook {} ({:?}): {}", i, webhook.kind, err.without_url()),
+            }
+        }
+    }
+}
+
+/// Starts announcing the questions created from now on to the chat webhooks.
+///
+/// # Arguments
+///
+/// * `state` - The application state, whose content events are announced.
+/// * `integrations` - The chat webhooks to announce questions to.
+///
+/// # Returns
+///
+/// A `JoinHandle` of the task announcing questions, which runs until the feed of content events is dropped.
+pub fn start(state: &AppState, integrations: Arc<ChatIntegrations>) -> JoinHandle<()> {
+    tokio::spawn(announce_questions(integrations, state.content_events.subscribe()))
+}
+
+async fn announce_questions(integrations: Arc<ChatIntegrations>, mut events: Receiver<ContentEvent>) {
+    loop {
+        let question = match events.recv().await {
+            Ok(ContentEvent::QuestionCreated(question)) => question,
+            Ok(_) => continue,
+            Err(RecvError::Lagged(missed)) => {
+                warn!("Chat integrations missed {} events, as messages could not keep up.", missed);
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+
+        integrations.announce(&question).await;
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::{extract::State, routing::post, Json, Router};
+
+    use crate::{config::Config, models::QuestionId, test_support::QuestionBuilder};
+
+    /// Bodies received by a fake chat service
+    type Messages = Arc<Mutex<Vec<Value>>>;
+
+    /// Serves a fake chat service, recording the messages posted to it.
+    async fn chat() -> (String, Messages) {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+
+        let app = Router::new()
+            .route(
+                "/hook",
+                post(|State(messages): State<Messages>, Json(body): Json<Value>| async move {
+                    messages.lock().unwrap().push(body);
+                }),
+            )
+            .with_state(messages.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (url, messages)
+    }
+
+    fn question() -> QuestionDetail {
+        QuestionBuilder::new()
+            .title("Why does <!channel> & @everyone panic in {description}?")
+            .description("It **panics** on startup.")
+            .build_detail()
+    }
+
+    #[tokio::test]
+    async fn should_post_escaped_messages_in_the_format_of_each_service() {
+        let (url, messages) = chat().await;
+        let integrations = ChatIntegrations::new(
+            [(ChatKind::Slack, url.clone()), (ChatKind::Discord, url)],
+            "New question: {title}\n{description}",
+            20,
+        );
+
+        integrations.announce(&question()).await;
+
+        let messages = messages.lock().unwrap();
+        assert_eq!(
+            *messages,
+            [
+                json!({
+                    "text": "New question: Why does &lt;!channel&gt; &amp; @everyone panic in {description}?\n\
+                             It panics on startup.",
+                }),
+                json!({
+                    "content": "New question: Why does <!channel> & @everyone panic in {description}?\n\
+                                It panics on startup.",
+                    "allowed_mentions": { "parse": [] },
+                }),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_only_announce_new_questions() {
+        let (url, messages) = chat().await;
+        let integrations = Arc::new(ChatIntegrations::new([(ChatKind::Slack, url)], "{question_uuid}", 20));
+        let state = AppState::in_memory(&Config::default());
+        let task = start(&state, integrations);
+
+        let question = question();
+        state.content_events.publish(ContentEvent::QuestionDeleted(QuestionId { question_uuid: question.question_uuid }));
+        state.content_events.publish(ContentEvent::QuestionCreated(question.clone()));
+        drop(state);
+        task.await.unwrap();
+
+        assert_eq!(*messages.lock().unwrap(), [json!({ "text": question.question_uuid.to_string() })]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_drop_messages_over_the_rate_limit_until_the_window_passes() {
+        let webhook = ChatWebhook {
+            kind: ChatKind::Slack,
+            url: String::new(),
+            window: Mutex::new(RateWindow { started_at: Instant::now(), sent: 0 }),
+        };
+
+        assert!(webhook.try_send(2));
+        assert!(webhook.try_send(2));
+        assert!(!webhook.try_send(2));
+
+        tokio::time::advance(RATE_WINDOW).await;
+        assert!(webhook.try_send(2));
+    }
+}
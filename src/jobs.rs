@@ -0,0 +1,358 @@
+use std::time::Duration;
+
+use chrono::{TimeDelta, Utc};
+use tokio::task::JoinHandle;
+
+use crate::{
+    models::{DBError, JobDetail, JobKind, JobStatus, QuestionUuid, QuestionWithAnswers},
+    search_index::{SearchError, SearchIndex},
+    AppState,
+};
+
+// Jobs go through every question in the background, a batch at a time, and record their progress after each batch.
+// A job interrupted by a restart or a crash is resumed after the last question it processed, by whichever instance
+// notices first that it has not made progress for a while. Jobs are updated only if nobody else updated them since,
+// so an instance that was merely slow stops at its next batch once another took its job over; the batch both worked
+// on is only processed twice, which indexing questions again makes harmless.
+
+/// Questions processed per batch
+const BATCH_SIZE: i64 = 100;
+
+/// Wait between two batches, so a job leaves the database and the search cluster room for the traffic they serve
+const BATCH_DELAY: Duration = Duration::from_secs(1);
+
+/// Wait before retrying a batch that failed
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// How long a running job goes without progress before another instance resumes it, longer than a batch can take
+const STALLED_AFTER: TimeDelta = TimeDelta::minutes(5);
+
+/// Interval between two looks for stalled jobs
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Starts resuming the running jobs that stalled, such as those of an instance that stopped, in the background.
+///
+/// # Arguments
+///
+/// * `state` - The application state, whose `JobsDao` is watched and which the jobs resumed run with.
+///
+/// # Returns
+///
+/// A `JoinHandle` of the task resuming jobs, which runs until aborted.
+pub fn start(state: &AppState) -> JoinHandle<()> {
+    let state = state.clone();
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = resume_stalled_jobs(&state).await {
+                error!("Failed to resume stalled jobs: {:?}", err);
+            }
+        }
+    })
+}
+
+/// Takes over, and runs, the running jobs not updated for `STALLED_AFTER`.
+async fn resume_stalled_jobs(state: &AppState) -> Result<(), DBError> {
+    let stalled_before = Utc::now() - STALLED_AFTER;
+
+    for job in state.jobs_dao.get_running_jobs().await? {
+        if job.updated_at > stalled_before {
+            continue;
+        }
+
+        // Updating the job claims it, unless another instance claimed it first
+        match state.jobs_dao.update_job(&job).await {
+            Ok(job) => {
+                info!("Resuming the {} job {} after {} questions.", job.kind.as_str(), job.job_uuid, job.processed);
+                spawn(state, job);
+            }
+            Err(DBError::Conflict(_)) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(())
+}
+
+/// Runs a job in the background, from where it is at.
+///
+/// # Arguments
+///
+/// * `state` - The application state the job runs with.
+/// * `job` - The job, as last updated.
+///
+/// # Returns
+///
+/// A `JoinHandle` of the task running the job, which ends once the job completes or another instance takes it over.
+pub fn spawn(state: &AppState, job: JobDetail) -> JoinHandle<()> {
+    let state = state.clone();
+
+    tokio::spawn(async move { run(&state, job).await })
+}
+
+async fn run(state: &AppState, mut job: JobDetail) {
+    let search_index = match job.kind {
+        JobKind::ReindexSearch => match &state.search_index {
+            Some(search_index) => search_index.clone(),
+            // Left running, for an instance with a search index to resume
+            None => {
+                warn!("Cannot run the {} job {} without a search index.", job.kind.as_str(), job.job_uuid);
+                return;
+            }
+        },
+    };
+
+    // The job fills an index of its own, replacing the one searched once every question is in it
+    let rebuild = job.job_uuid.to_string();
+    while let Err(err) = search_index.begin_rebuild(&rebuild).await {
+        error!("Failed to create the index of the job {}, retrying in {:?}: {}", job.job_uuid, RETRY_DELAY, err);
+        tokio::time::sleep(RETRY_DELAY).await;
+    }
+
+    loop {
+        let batch = match reindex_batch(state, search_index.as_ref(), &rebuild, job.cursor).await {
+            Ok(None) => search_index.complete_rebuild(&rebuild).await.map(|()| None),
+            batch => batch,
+        };
+
+        let next = match batch {
+            Ok(Some((processed, cursor))) => JobDetail {
+                processed: job.processed + processed,
+                cursor: Some(cursor),
+                last_error: None,
+                ..job.clone()
+            },
+            Ok(None) => JobDetail { status: JobStatus::Completed, last_error: None, ..job.clone() },
+            Err(err) => {
+                warn!("The job {} failed a batch, retrying in {:?}: {}", job.job_uuid, RETRY_DELAY, err);
+                JobDetail { last_error: Some(err.to_string()), ..job.clone() }
+            }
+        };
+
+        match state.jobs_dao.update_job(&next).await {
+            Ok(updated) => job = updated,
+            Err(DBError::Conflict(_)) => {
+                info!("The {} job {} was taken over by another instance.", job.kind.as_str(), job.job_uuid);
+                return;
+            }
+            // The batch is done again once the database is back
+            Err(err) => error!("Failed to record the progress of the job {}: {:?}", job.job_uuid, err),
+        }
+
+        if job.status == JobStatus::Completed {
+            info!("Completed the {} job {} after {} questions.", job.kind.as_str(), job.job_uuid, job.processed);
+            return;
+        }
+
+        let delay = if job.last_error.is_none() { BATCH_DELAY } else { RETRY_DELAY };
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Writes the batch of questions after `cursor`, along with their answers, to the new index of a rebuild.
+///
+/// # Returns
+///
+/// A `Result` containing the number of questions written and the last of them on success, `None` if there are no
+/// questions left, or a `SearchError` on failure.
+async fn reindex_batch(
+    state: &AppState,
+    search_index: &(dyn SearchIndex + Send + Sync),
+    rebuild: &str,
+    cursor: Option<QuestionUuid>,
+) -> Result<Option<(i64, QuestionUuid)>, SearchError> {
+    let questions = state.questions_dao.get_questions_after(cursor, BATCH_SIZE).await.map_err(SearchError::Database)?;
+    let Some(last) = questions.last().map(|question| question.question_uuid) else {
+        return Ok(None);
+    };
+
+    let question_uuids = questions.iter().map(|question| question.question_uuid).collect();
+    let mut answers = state.answers_dao.get_answers_for_questions(question_uuids).await.map_err(SearchError::Database)?;
+
+    let processed = questions.len() as i64;
+    for question in questions {
+        let answers = answers.remove(&question.question_uuid).unwrap_or_default();
+        search_index.index_question(rebuild, &QuestionWithAnswers { question, answers }).await?;
+    }
+
+    Ok(Some((processed, last)))
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    };
+
+    use async_trait::async_trait;
+    use reqwest::StatusCode;
+
+    use crate::{
+        config::Config,
+        outbox::ContentEvent,
+        persistance::{jobs_dao::JobsDaoInMemory, memory::MemoryStore},
+        search_index::IndexMatch,
+        test_support::{AnswerBuilder, QuestionBuilder},
+    };
+
+    /// Index recording the questions written to it and the rebuilds completed, failing the first `failures` writes
+    #[derive(Default)]
+    struct RecordingIndex {
+        indexed: Mutex<Vec<QuestionWithAnswers>>,
+        completed: Mutex<Vec<String>>,
+        failures: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl SearchIndex for RecordingIndex {
+        async fn prepare(&self) -> Result<(), SearchError> {
+            Ok(())
+        }
+
+        async fn index_event(&self, _: &ContentEvent) -> Result<(), SearchError> {
+            Ok(())
+        }
+
+        async fn begin_rebuild(&self, _: &str) -> Result<(), SearchError> {
+            Ok(())
+        }
+
+        async fn index_question(&self, _: &str, question: &QuestionWithAnswers) -> Result<(), SearchError> {
+            let failing = self.failures.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1)).is_ok();
+            if failing {
+                return Err(SearchError::Status(StatusCode::SERVICE_UNAVAILABLE, "unavailable".to_owned()));
+            }
+
+            self.indexed.lock().unwrap().push(question.clone());
+            Ok(())
+        }
+
+        async fn complete_rebuild(&self, rebuild: &str) -> Result<(), SearchError> {
+            self.completed.lock().unwrap().push(rebuild.to_owned());
+            Ok(())
+        }
+
+        async fn search(&self, _: &str, _: i64) -> Result<Vec<IndexMatch>, SearchError> {
+            Ok(Vec::new())
+        }
+    }
+
+    /// State keeping its data in `store`, and writing to `index`
+    fn state(store: Arc<MemoryStore>, index: Arc<RecordingIndex>) -> AppState {
+        AppState {
+            jobs_dao: Arc::new(JobsDaoInMemory::new(store)),
+            search_index: Some(index),
+            ..AppState::in_memory(&Config::default())
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_index_every_question_with_its_answers() {
+        let index = Arc::new(RecordingIndex::default());
+        let state = state(Arc::new(MemoryStore::new()), index.clone());
+
+        let mut questions = Vec::new();
+        for _ in 0..BATCH_SIZE + 1 {
+            questions.push(state.questions_dao.create_question(QuestionBuilder::new().build()).await.unwrap());
+        }
+        let answer = AnswerBuilder::new(questions[0].question_uuid).build();
+        let answer = state.answers_dao.create_answer(answer).await.unwrap();
+
+        let job = state.jobs_dao.create_job(JobKind::ReindexSearch).await.unwrap();
+        spawn(&state, job.clone()).await.unwrap();
+
+        let job = state.jobs_dao.get_job(job.job_uuid).await.unwrap();
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(job.processed, BATCH_SIZE + 1);
+
+        assert_eq!(*index.completed.lock().unwrap(), vec![job.job_uuid.to_string()]);
+        let indexed = index.indexed.lock().unwrap();
+        assert_eq!(indexed.len(), questions.len());
+        let answered = indexed.iter().find(|indexed| indexed.question.question_uuid == answer.question_uuid).unwrap();
+        assert_eq!(answered.answers, vec![answer]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_retry_failed_batches() {
+        let index = Arc::new(RecordingIndex { failures: AtomicUsize::new(1), ..RecordingIndex::default() });
+        let state = state(Arc::new(MemoryStore::new()), index.clone());
+        state.questions_dao.create_question(QuestionBuilder::new().build()).await.unwrap();
+
+        let job = state.jobs_dao.create_job(JobKind::ReindexSearch).await.unwrap();
+        let running = spawn(&state, job.clone());
+
+        // Long enough for the first attempt, not for the retry
+        tokio::time::sleep(RETRY_DELAY / 2).await;
+        let failed = state.jobs_dao.get_job(job.job_uuid).await.unwrap();
+        assert_eq!(failed.status, JobStatus::Running);
+        assert_eq!(failed.processed, 0);
+        assert!(failed.last_error.unwrap().contains("unavailable"));
+
+        running.await.unwrap();
+        let job = state.jobs_dao.get_job(job.job_uuid).await.unwrap();
+        assert_eq!((job.status, job.processed, job.last_error), (JobStatus::Completed, 1, None));
+        assert_eq!(index.indexed.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_resume_stalled_jobs_where_they_were() {
+        let store = Arc::new(MemoryStore::new());
+        let index = Arc::new(RecordingIndex::default());
+        let state = state(store.clone(), index.clone());
+
+        let mut questions = Vec::new();
+        for _ in 0..3 {
+            questions.push(state.questions_dao.create_question(QuestionBuilder::new().build()).await.unwrap());
+        }
+        questions.sort_by_key(|question| question.question_uuid);
+
+        // A job that processed the first question, left alone while still making progress
+        let job = state.jobs_dao.create_job(JobKind::ReindexSearch).await.unwrap();
+        let running = state
+            .jobs_dao
+            .update_job(&JobDetail { processed: 1, cursor: Some(questions[0].question_uuid), ..job })
+            .await
+            .unwrap();
+
+        resume_stalled_jobs(&state).await.unwrap();
+        assert_eq!(state.jobs_dao.get_job(running.job_uuid).await.unwrap(), running);
+
+        // Then resumed once its instance stopped
+        store.jobs.write().unwrap().get_mut(&running.job_uuid).unwrap().value.updated_at -= STALLED_AFTER;
+        resume_stalled_jobs(&state).await.unwrap();
+        tokio::time::sleep(BATCH_DELAY * 3).await;
+
+        let resumed = state.jobs_dao.get_job(running.job_uuid).await.unwrap();
+        assert_eq!((resumed.status, resumed.processed), (JobStatus::Completed, 3));
+
+        let indexed: Vec<QuestionUuid> =
+            index.indexed.lock().unwrap().iter().map(|indexed| indexed.question.question_uuid).collect();
+        assert_eq!(indexed, vec![questions[1].question_uuid, questions[2].question_uuid]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_stop_jobs_taken_over() {
+        let index = Arc::new(RecordingIndex::default());
+        let state = state(Arc::new(MemoryStore::new()), index.clone());
+        state.questions_dao.create_question(QuestionBuilder::new().build()).await.unwrap();
+
+        let job = state.jobs_dao.create_job(JobKind::ReindexSearch).await.unwrap();
+        let taken_over = state.jobs_dao.update_job(&job).await.unwrap();
+
+        spawn(&state, job).await.unwrap();
+
+        assert_eq!(state.jobs_dao.get_job(taken_over.job_uuid).await.unwrap(), taken_over);
+        assert!(index.completed.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,485 @@
+#[macro_use]
+extern crate log;
+
+pub mod auth;
+pub mod cache_control;
+pub mod canary;
+pub mod client_ip;
+pub mod concurrency;
+pub mod config;
+pub mod content_filter;
+pub mod deprecation;
+pub mod event_schemas;
+#[cfg(any(feature = "nats", feature = "kafka"))]
+pub mod events;
+pub mod excerpt;
+pub mod handlers;
+pub mod health;
+pub mod idempotency;
+pub mod integrations;
+pub mod jobs;
+pub mod live;
+pub mod loadgen;
+pub mod models;
+pub mod normalize;
+pub mod openapi;
+pub mod outbound;
+pub mod outbox;
+pub mod persistance;
+pub mod policy;
+pub mod print;
+pub mod reading_time;
+pub mod recording;
+pub mod redact;
+pub mod sanitize;
+pub mod search_index;
+pub mod seed;
+pub mod slo;
+#[cfg(feature = "otel")]
+pub mod telemetry;
+#[cfg(feature = "test-support")]
+pub mod test_support;
+pub mod views;
+pub mod webhooks;
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use axum::{
+    middleware::from_fn_with_state,
+    routing::{delete, get, post},
+    Router,
+};
+use sqlx::PgPool;
+
+use auth::AdminAuth;
+use cache_control::CachePolicy;
+use canary::Canary;
+use client_ip::TrustedProxies;
+use concurrency::ConcurrencyTracker;
+use deprecation::DeprecationRegistry;
+use config::Config;
+use content_filter::ContentFilters;
+use handlers::*;
+use health::HealthCheck;
+use live::Feed;
+use models::{AnswerDetail, QuestionDetail};
+use outbox::ContentEvent;
+use persistance::{
+    answers_dao::{AnswersDao, AnswersDaoImpl, AnswersDaoInMemory},
+    flags_dao::{FlagsDao, FlagsDaoImpl, FlagsDaoInMemory},
+    health::PostgresHealthCheck,
+    idempotency_dao::{IdempotencyDao, IdempotencyDaoImpl, IdempotencyDaoInMemory},
+    incidents_dao::{IncidentsDao, IncidentsDaoImpl, IncidentsDaoInMemory},
+    jobs_dao::{JobsDao, JobsDaoImpl, JobsDaoInMemory},
+    maintenance_dao::{MaintenanceDao, MaintenanceDaoImpl},
+    memory::MemoryStore,
+    outbox_dao::{OutboxDao, OutboxDaoImpl, OutboxDaoInMemory},
+    DatabasePool,
+    questions_dao::{QuestionsDao, QuestionsDaoImpl, QuestionsDaoInMemory},
+    stats_dao::{StatsDao, StatsDaoImpl, StatsDaoInMemory},
+    webhooks_dao::{WebhooksDao, WebhooksDaoImpl, WebhooksDaoInMemory},
+};
+use policy::{AuthorizationPolicy, OpaPolicy};
+use search_index::{ElasticsearchIndex, SearchIndex};
+use slo::SloTracker;
+use views::ViewCounter;
+
+/// Represents the application state containing DAO instances for questions and answers.
+#[derive(Clone)]
+pub struct AppState {
+    pub questions_dao: Arc<dyn QuestionsDao + Send + Sync>,
+    pub answers_dao: Arc<dyn AnswersDao + Send + Sync>,
+    pub incidents_dao: Arc<dyn IncidentsDao + Send + Sync>,
+    pub webhooks_dao: Arc<dyn WebhooksDao + Send + Sync>,
+    pub outbox_dao: Arc<dyn OutboxDao + Send + Sync>,
+    pub idempotency_dao: Arc<dyn IdempotencyDao + Send + Sync>,
+    pub jobs_dao: Arc<dyn JobsDao + Send + Sync>,
+    pub stats_dao: Arc<dyn StatsDao + Send + Sync>,
+    pub flags_dao: Arc<dyn FlagsDao + Send + Sync>,
+    /// Maintenance statistics of the database, which only Postgres reports
+    pub maintenance_dao: Option<Arc<dyn MaintenanceDao + Send + Sync>>,
+    pub health_checks: Arc<[Arc<dyn HealthCheck + Send + Sync>]>,
+    pub started_at: Instant,
+    pub slo_tracker: Arc<SloTracker>,
+    pub concurrency_tracker: Arc<ConcurrencyTracker>,
+    /// Deprecated routes and query parameters, and their uses
+    pub deprecations: Arc<DeprecationRegistry>,
+    /// Filters new questions and answers go through before they are created
+    pub content_filters: Arc<ContentFilters>,
+    /// Views of questions counted until they are written to the database
+    pub view_counter: Arc<ViewCounter>,
+    /// Admin token and service principals protecting the admin API, which is not mounted when `None`
+    pub admin_auth: Option<Arc<AdminAuth>>,
+    /// Proxies trusted to report the client address
+    pub trusted_proxies: Arc<TrustedProxies>,
+    /// Instance read traffic is shadowed to, if any
+    pub canary: Option<Arc<Canary>>,
+    /// Index searched instead of the database, if any
+    pub search_index: Option<Arc<dyn SearchIndex + Send + Sync>>,
+    /// `Cache-Control` directives of the routes, if any are configured
+    pub cache_policy: Option<Arc<CachePolicy>>,
+    /// Tokens of the AI assistants calling tools over MCP, which is not mounted when `None`
+    pub tool_tokens: Option<Arc<mcp::ToolTokens>>,
+    /// Questions created, as dispatched from the outbox, streamed to live dashboards
+    pub question_feed: Feed<QuestionDetail>,
+    /// Answers created, as dispatched from the outbox, pushed to the clients watching their question
+    pub answer_feed: Feed<AnswerDetail>,
+    /// Writes dispatched from the outbox by this instance, delivered to webhooks
+    pub content_events: Feed<ContentEvent>,
+    /// Most characters of the description excerpts in lists of questions, 0 to leave them out
+    pub question_excerpt_chars: usize,
+    /// How long the response to a request made with an idempotency key is replayed to its retries
+    pub idempotency_key_ttl: Duration,
+    /// Recorder capturing API requests, if any
+    #[cfg(feature = "record")]
+    pub recorder: Option<Arc<recording::Recorder>>,
+}
+
+impl AppState {
+
+    /// Creates the state for a Postgres-backed API.
+    ///
+    /// # Arguments
+    ///
+    /// * `pool` - The Postgres connection pool backing the DAOs.
+    /// * `config` - The application configuration.
+    ///
+    /// # Returns
+    ///
+    /// An `AppState` with Postgres DAOs and health checks.
+    pub fn new(pool: PgPool, config: &Config) -> Self {
+        // External dependencies reported by the readiness probe
+        let health_checks: Vec<Arc<dyn HealthCheck + Send + Sync>> = vec![Arc::new(PostgresHealthCheck::new(pool.clone()))];
+
+        AppState {
+            maintenance_dao: Some(Arc::new(MaintenanceDaoImpl::new(pool.clone()))),
+            ..Self::with_daos(
+                config,
+                Arc::new(QuestionsDaoImpl::new(pool.clone())),
+                Arc::new(AnswersDaoImpl::new(pool.clone())),
+                Arc::new(IncidentsDaoImpl::new(pool.clone())),
+                Arc::new(WebhooksDaoImpl::new(pool.clone())),
+                Arc::new(OutboxDaoImpl::new(pool.clone())),
+                Arc::new(IdempotencyDaoImpl::new(pool.clone())),
+                Arc::new(JobsDaoImpl::new(pool.clone())),
+                Arc::new(StatsDaoImpl::new(pool.clone())),
+                Arc::new(FlagsDaoImpl::new(pool)),
+                health_checks,
+            )
+        }
+    }
+
+    /// Creates the state for a SQLite-backed API.
+    ///
+    /// # Arguments
+    ///
+    /// * `pool` - The SQLite connection pool backing the DAOs.
+    /// * `config` - The application configuration.
+    ///
+    /// # Returns
+    ///
+    /// An `AppState` with SQLite DAOs and health checks.
+    #[cfg(feature = "sqlite")]
+    pub fn sqlite(pool: sqlx::SqlitePool, config: &Config) -> Self {
+        use persistance::sqlite::*;
+
+        let health_checks: Vec<Arc<dyn HealthCheck + Send + Sync>> = vec![Arc::new(SqliteHealthCheck::new(pool.clone()))];
+
+        Self::with_daos(
+            config,
+            Arc::new(QuestionsDaoSqlite::new(pool.clone())),
+            Arc::new(AnswersDaoSqlite::new(pool.clone())),
+            Arc::new(IncidentsDaoSqlite::new(pool.clone())),
+            Arc::new(WebhooksDaoSqlite::new(pool.clone())),
+            Arc::new(OutboxDaoSqlite::new(pool.clone())),
+            Arc::new(IdempotencyDaoSqlite::new(pool.clone())),
+            Arc::new(JobsDaoSqlite::new(pool.clone())),
+            Arc::new(StatsDaoSqlite::new(pool.clone())),
+            Arc::new(FlagsDaoSqlite::new(pool)),
+            health_checks,
+        )
+    }
+
+    /// Creates the state for a MySQL-backed API.
+    ///
+    /// # Arguments
+    ///
+    /// * `pool` - The MySQL connection pool backing the DAOs.
+    /// * `config` - The application configuration.
+    ///
+    /// # Returns
+    ///
+    /// An `AppState` with MySQL DAOs and health checks.
+    #[cfg(feature = "mysql")]
+    pub fn mysql(pool: sqlx::MySqlPool, config: &Config) -> Self {
+        use persistance::mysql::*;
+
+        let health_checks: Vec<Arc<dyn HealthCheck + Send + Sync>> = vec![Arc::new(MySqlHealthCheck::new(pool.clone()))];
+
+        Self::with_daos(
+            config,
+            Arc::new(QuestionsDaoMySql::new(pool.clone())),
+            Arc::new(AnswersDaoMySql::new(pool.clone())),
+            Arc::new(IncidentsDaoMySql::new(pool.clone())),
+            Arc::new(WebhooksDaoMySql::new(pool.clone())),
+            Arc::new(OutboxDaoMySql::new(pool.clone())),
+            Arc::new(IdempotencyDaoMySql::new(pool.clone())),
+            Arc::new(JobsDaoMySql::new(pool.clone())),
+            Arc::new(StatsDaoMySql::new(pool.clone())),
+            Arc::new(FlagsDaoMySql::new(pool)),
+            health_checks,
+        )
+    }
+
+    /// Creates the state for an API backed by whichever database the pool connects to.
+    ///
+    /// # Arguments
+    ///
+    /// * `pool` - The connection pool backing the DAOs.
+    /// * `config` - The application configuration.
+    ///
+    /// # Returns
+    ///
+    /// An `AppState` with DAOs and health checks for that database.
+    pub fn for_database(pool: &DatabasePool, config: &Config) -> Self {
+        match pool {
+            DatabasePool::Postgres(pool) => Self::new(pool.clone(), config),
+            #[cfg(feature = "sqlite")]
+            DatabasePool::Sqlite(pool) => Self::sqlite(pool.clone(), config),
+            #[cfg(feature = "mysql")]
+            DatabasePool::MySql(pool) => Self::mysql(pool.clone(), config),
+        }
+    }
+
+    /// Creates the state for an API keeping its data in memory, which is lost on exit.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The application configuration.
+    ///
+    /// # Returns
+    ///
+    /// An `AppState` with in-memory DAOs and no external dependencies to check.
+    pub fn in_memory(config: &Config) -> Self {
+        let store = Arc::new(MemoryStore::new());
+
+        Self::with_daos(
+            config,
+            Arc::new(QuestionsDaoInMemory::new(store.clone())),
+            Arc::new(AnswersDaoInMemory::new(store.clone())),
+            Arc::new(IncidentsDaoInMemory::new(store.clone())),
+            Arc::new(WebhooksDaoInMemory::new(store.clone())),
+            Arc::new(OutboxDaoInMemory::new(store.clone())),
+            Arc::new(IdempotencyDaoInMemory::new(store.clone())),
+            Arc::new(JobsDaoInMemory::new(store.clone())),
+            Arc::new(StatsDaoInMemory::new(store.clone())),
+            Arc::new(FlagsDaoInMemory::new(store)),
+            Vec::new(),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn with_daos(
+        config: &Config,
+        questions_dao: Arc<dyn QuestionsDao + Send + Sync>,
+        answers_dao: Arc<dyn AnswersDao + Send + Sync>,
+        incidents_dao: Arc<dyn IncidentsDao + Send + Sync>,
+        webhooks_dao: Arc<dyn WebhooksDao + Send + Sync>,
+        outbox_dao: Arc<dyn OutboxDao + Send + Sync>,
+        idempotency_dao: Arc<dyn IdempotencyDao + Send + Sync>,
+        jobs_dao: Arc<dyn JobsDao + Send + Sync>,
+        stats_dao: Arc<dyn StatsDao + Send + Sync>,
+        flags_dao: Arc<dyn FlagsDao + Send + Sync>,
+        health_checks: Vec<Arc<dyn HealthCheck + Send + Sync>>,
+    ) -> Self {
+        AppState {
+            questions_dao,
+            answers_dao,
+            incidents_dao,
+            webhooks_dao,
+            outbox_dao,
+            idempotency_dao,
+            jobs_dao,
+            stats_dao,
+            flags_dao,
+            maintenance_dao: None,
+            health_checks: health_checks.into(),
+            started_at: Instant::now(),
+            slo_tracker: Arc::new(SloTracker::new(config.slo_targets())),
+            concurrency_tracker: Arc::new(ConcurrencyTracker::new(config.route_concurrency_limits())),
+            deprecations: Arc::new(DeprecationRegistry::new(config.deprecations())),
+            content_filters: Arc::new(ContentFilters::new(config.content_filters())),
+            view_counter: Arc::new(ViewCounter::new()),
+            admin_auth: config.admin_token.as_deref().map(|token| {
+                let policy = config.policy_url.as_deref().map(|url| {
+                    Arc::new(OpaPolicy::new(url, config.policy_cache_ttl())) as Arc<dyn AuthorizationPolicy + Send + Sync>
+                });
+                Arc::new(AdminAuth::new(Arc::from(token), config.service_principals(), policy))
+            }),
+            trusted_proxies: Arc::new(config.trusted_proxies()),
+            canary: config.canary_url.as_deref().map(|url| {
+                Arc::new(Canary::new(url, config.canary_traffic_percent, config.canary_diff_sample_percent))
+            }),
+            search_index: config.search_url.as_deref().map(|url| {
+                Arc::new(ElasticsearchIndex::new(url, &config.search_index)) as Arc<dyn SearchIndex + Send + Sync>
+            }),
+            cache_policy: Some(config.cache_control_rules())
+                .filter(|rules| !rules.is_empty())
+                .map(|rules| Arc::new(CachePolicy::new(rules))),
+            tool_tokens: Some(config.tool_tokens())
+                .filter(|tokens| !tokens.is_empty())
+                .map(|tokens| Arc::new(mcp::ToolTokens::new(tokens))),
+            question_feed: Feed::new(),
+            answer_feed: Feed::new(),
+            content_events: Feed::new(),
+            question_excerpt_chars: config.question_excerpt_chars,
+            idempotency_key_ttl: config.idempotency_key_ttl(),
+            // Creating the recording is fallible, so it is left to the caller
+            #[cfg(feature = "record")]
+            recorder: None,
+        }
+    }
+}
+
+/// Builds the API router, so it can be served or mounted without spawning a listener.
+///
+/// # Arguments
+///
+/// * `state` - The application state shared by all handlers.
+///
+/// # Returns
+///
+/// A `Router` serving every endpoint of the API.
+pub fn app(state: AppState) -> Router {
+    // Retries of creates replay the response to the first attempt rather than creating the content again
+    let replay_responses = from_fn_with_state(state.clone(), idempotency::replay_responses);
+
+    let api = Router::new()
+        .route("/question", post(create_question).route_layer(replay_responses.clone()))
+        .route("/questions", get(read_questions))
+        .route("/questions/search", get(search_questions))
+        .route("/questions/popular", get(read_popular_questions))
+        .route("/questions/lookup", post(lookup_questions))
+        .route("/sync", post(sync_questions))
+        .route("/questions/:question_uuid/full", get(read_question_with_answers))
+        .route("/question/:question_file", get(read_question_text))
+        .route("/question/:question_uuid/watch-webhook", post(create_question_webhook))
+        .route("/questions/:question_uuid/close", post(close_question))
+        .route("/questions/:question_uuid/reopen", post(reopen_question))
+        .route("/questions/:question_uuid/flag", post(flag_question))
+        .route("/question", delete(delete_question))
+        .route("/answer", post(create_answer).route_layer(replay_responses))
+        .route("/answers", get(read_answers))
+        .route("/answers/batch", post(read_answers_batch))
+        .route("/answers/:answer_uuid/flag", post(flag_answer))
+        .route("/answer", delete(delete_answer));
+
+    // Assistants are API clients too, but only get the tools their token grants
+    let api = match &state.tool_tokens {
+        Some(tokens) => api.route(
+            "/mcp",
+            post(mcp::serve).route_layer(from_fn_with_state(tokens.clone(), mcp::require_tool_token)),
+        ),
+        None => api,
+    };
+
+    #[cfg(feature = "graphql")]
+    let api = api.route(
+        "/graphql",
+        get(graphql::graphiql)
+            .post(graphql::graphql)
+            .with_state(graphql::schema(&state)),
+    );
+
+    // Shadowing sits inside the SLO middleware, so its overhead counts towards latency
+    let api = match &state.canary {
+        Some(canary) => api.route_layer(from_fn_with_state(canary.clone(), canary::shadow_reads)),
+        None => api,
+    };
+
+    #[cfg(feature = "record")]
+    let api = match &state.recorder {
+        Some(recorder) => api.route_layer(from_fn_with_state(recorder.clone(), recording::record_requests)),
+        None => api,
+    };
+
+    // Deprecated features are announced even on requests refused for being over a route's limit
+    let api = if state.deprecations.is_empty() {
+        api
+    } else {
+        api.route_layer(from_fn_with_state(state.deprecations.clone(), deprecation::announce_deprecations))
+    };
+
+    let app = api
+        // Requests refused for being over a route's limit count towards the SLOs too
+        .route_layer(from_fn_with_state(state.concurrency_tracker.clone(), concurrency::limit_concurrency))
+        // Only API traffic counts towards the SLOs, not probes or the admin API
+        .route_layer(from_fn_with_state(state.slo_tracker.clone(), slo::track_requests))
+        .route("/health", get(health))
+        .route("/ready", get(ready))
+        .route("/status", get(read_status))
+        .route("/stats", get(read_stats))
+        // Outside the SLO middleware, as these connections are held open for as long as there is nothing to send
+        .route("/questions/stream", get(stream_questions))
+        .route("/ws/questions/:question_uuid", get(watch_answers))
+        .route("/question/:question_uuid/updates", get(poll_answers))
+        .route("/events/schemas", get(read_event_schemas))
+        .merge(openapi::swagger_ui());
+
+    // The admin and moderation APIs only exist when a token to protect them is configured
+    let app = match &state.admin_auth {
+        Some(admin_auth) => {
+            let admin = Router::new()
+                .route("/incident", post(create_incident))
+                .route("/incident/resolve", post(resolve_incident))
+                .route("/slo", get(read_slo))
+                .route("/concurrency", get(read_concurrency))
+                .route("/deprecations", get(read_deprecations))
+                .route("/webhook", post(create_webhook))
+                .route("/webhooks", get(read_webhooks))
+                .route("/webhook", delete(delete_webhook));
+
+            // Only mounted for databases keeping maintenance statistics
+            let admin = match &state.maintenance_dao {
+                Some(maintenance_dao) => {
+                    admin.route("/maintenance", get(read_maintenance).with_state(maintenance_dao.clone()))
+                }
+                None => admin,
+            };
+
+            // Only mounted with a search index to write to
+            let admin = match &state.search_index {
+                Some(_) => admin.route("/search/reindex", post(create_reindex_job)),
+                None => admin,
+            };
+
+            let admin = admin
+                .route("/jobs/:job_uuid", get(read_job))
+                .route_layer(from_fn_with_state(admin_auth.clone(), auth::require_admin_token));
+
+            // Moderators are admins too, granted the moderation scope when they are services
+            let moderation = Router::new()
+                .route("/queue", get(read_moderation_queue))
+                .route("/questions/:question_uuid/flags", get(read_question_flags))
+                .route("/:flag_uuid/resolve", post(resolve_flag))
+                .route_layer(from_fn_with_state(admin_auth.clone(), auth::require_admin_token));
+
+            app.nest("/admin", admin).nest("/moderation", moderation)
+        }
+        None => app,
+    };
+
+    // Applies to the admin API too, which a policy may keep out of caches
+    let app = match &state.cache_policy {
+        Some(policy) => app.layer(from_fn_with_state(policy.clone(), cache_control::apply_policy)),
+        None => app,
+    };
+
+    // Outermost, so the client address is known to every route and middleware
+    let app = app.layer(from_fn_with_state(state.trusted_proxies.clone(), client_ip::resolve_client_ip));
+
+    app.with_state(state)
+}
@@ -0,0 +1,340 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    extract::ws::{Message, WebSocket},
+    response::sse::Event,
+};
+use chrono::{DateTime, Utc};
+use futures::{stream, Stream};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{
+    self,
+    error::{RecvError, TryRecvError},
+    Receiver,
+};
+
+use crate::models::{AnswerDetail, QuestionDetail, QuestionUuid};
+
+/// Events kept for subscribers that fall behind, beyond which they miss the oldest ones
+const FEED_CAPACITY: usize = 256;
+
+/// Events of the live feeds, as relayed between instances
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum LiveEvent {
+    QuestionCreated(QuestionDetail),
+    AnswerCreated(AnswerDetail),
+}
+
+/// Forwards the events published through a feed beyond this instance
+type Relay<T> = Arc<dyn Fn(&T) + Send + Sync>;
+
+/// In-process feed of events, delivering every event published to all current subscribers.
+///
+/// Only subscribers of this instance are notified, unless a relay forwards the events to the other replicas.
+#[derive(Clone)]
+pub struct Feed<T> {
+    sender: broadcast::Sender<T>,
+    relay: Option<Relay<T>>,
+}
+
+impl<T: Clone> Feed<T> {
+
+    /// Creates a feed without subscribers.
+    ///
+    /// # Returns
+    ///
+    /// A `Feed` to publish events to.
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(FEED_CAPACITY);
+
+        Feed { sender, relay: None }
+    }
+
+    /// Returns a handle to the same feed that also hands every event it publishes to a relay.
+    ///
+    /// Handles cloned before keep publishing to local subscribers only, e.g. to republish relayed events without
+    /// relaying them back.
+    ///
+    /// # Arguments
+    ///
+    /// * `relay` - Called with every event published through the returned handle.
+    ///
+    /// # Returns
+    ///
+    /// A `Feed` sharing its subscribers with this one.
+    pub fn relayed(&self, relay: impl Fn(&T) + Send + Sync + 'static) -> Self {
+        Feed {
+            sender: self.sender.clone(),
+            relay: Some(Arc::new(relay)),
+        }
+    }
+
+    /// Publishes an event to the current subscribers, if any, and to the relay.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The event to deliver.
+    pub fn publish(&self, event: T) {
+        if let Some(relay) = &self.relay {
+            relay(&event);
+        }
+
+        // Nobody listening is not an error, the event is simply dropped
+        let _ = self.sender.send(event);
+    }
+
+    /// Subscribes to the events published from now on.
+    ///
+    /// # Returns
+    ///
+    /// A `Receiver` of the events.
+    pub fn subscribe(&self) -> Receiver<T> {
+        self.sender.subscribe()
+    }
+}
+
+impl<T: Clone> Default for Feed<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Turns the questions created into Server-Sent Events, ending when the feed does.
+///
+/// # Arguments
+///
+/// * `questions` - The subscription to every question created.
+///
+/// # Returns
+///
+/// A `Stream` of `question_created` events, each carrying the question as JSON.
+pub fn question_events(questions: Receiver<QuestionDetail>) -> impl Stream<Item = Result<Event, axum::Error>> {
+    stream::unfold(questions, |mut questions| async move {
+        loop {
+            match questions.recv().await {
+                Ok(question) => {
+                    let event = Event::default().event("question_created").json_data(&question);
+                    return Some((event, questions));
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    warn!("Question stream missed {} questions", missed);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+}
+
+/// Pushes the answers created for a question over a WebSocket, as JSON text messages, until the client leaves.
+///
+/// # Arguments
+///
+/// * `socket` - The upgraded connection to the client.
+/// * `question_uuid` - The question whose answers are pushed.
+/// * `answers` - The subscription to every answer created.
+pub async fn push_answers(mut socket: WebSocket, question_uuid: QuestionUuid, mut answers: Receiver<AnswerDetail>) {
+    loop {
+        tokio::select! {
+            answer = answers.recv() => match answer {
+                Ok(answer) if answer.question_uuid == question_uuid => {
+                    let message = match serde_json::to_string(&answer) {
+                        Ok(message) => message,
+                        Err(err) => {
+                            error!("Failed to serialize answer: {:?}", err);
+                            continue;
+                        }
+                    };
+
+                    if socket.send(Message::Text(message)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(missed)) => {
+                    warn!("WebSocket of question {} missed {} answers", question_uuid, missed);
+                }
+                Err(RecvError::Closed) => break,
+            },
+            // Pings are answered by the socket itself, and nothing else is expected from the client
+            message = socket.recv() => match message {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+/// Waits for the answers created for a question after a cursor, for at most `wait`, on behalf of a long poll.
+///
+/// Returns as soon as a first answer arrives, along with the others already received. Nothing is returned if the
+/// subscription falls behind, so that the next poll catches up from the database rather than skip answers.
+///
+/// # Arguments
+///
+/// * `answers` - The subscription to every answer created.
+/// * `question_uuid` - The question whose answers are waited for.
+/// * `since` - Only answers created after this time are returned, such as those published late by the outbox.
+/// * `wait` - The longest time to wait for a first answer.
+///
+/// # Returns
+///
+/// The answers received, oldest first, empty if none arrived in time.
+pub async fn next_answers(
+    answers: &mut Receiver<AnswerDetail>,
+    question_uuid: QuestionUuid,
+    since: Option<DateTime<Utc>>,
+    wait: Duration,
+) -> Vec<AnswerDetail> {
+    let is_new = |answer: &AnswerDetail| {
+        answer.question_uuid == question_uuid && since.is_none_or(|since| answer.created_at > since)
+    };
+
+    let first = tokio::time::timeout(wait, async {
+        loop {
+            match answers.recv().await {
+                Ok(answer) if is_new(&answer) => return Some(answer),
+                Ok(_) => {}
+                Err(RecvError::Lagged(missed)) => {
+                    warn!("Long poll of question {} missed {} answers", question_uuid, missed);
+                    return None;
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    let Ok(Some(first)) = first.await else {
+        return Vec::new();
+    };
+
+    // Answers missed by falling behind from here were created after those kept, so the next poll catches up on them
+    let mut received = vec![first];
+    loop {
+        match answers.try_recv() {
+            Ok(answer) if is_new(&answer) => received.push(answer),
+            Ok(_) => {}
+            Err(TryRecvError::Empty | TryRecvError::Lagged(_) | TryRecvError::Closed) => return received,
+        }
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeDelta;
+    use futures::StreamExt;
+
+    use crate::{
+        models::{AnswerUuid, QuestionStatus},
+        test_support::AnswerBuilder,
+    };
+
+    #[tokio::test]
+    async fn should_deliver_events_to_every_subscriber() {
+        let feed = Feed::new();
+
+        let mut first = feed.subscribe();
+        let mut second = feed.subscribe();
+
+        feed.publish(1);
+
+        assert_eq!(first.recv().await.unwrap(), 1);
+        assert_eq!(second.recv().await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_only_deliver_events_published_after_subscribing() {
+        let feed = Feed::new();
+
+        // Dropped, as nobody is subscribed yet
+        feed.publish(1);
+
+        let mut subscriber = feed.subscribe();
+        feed.publish(2);
+
+        assert_eq!(subscriber.recv().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_hand_events_to_the_relay_of_the_handle_publishing_them() {
+        let feed = Feed::new();
+        let relayed = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let relaying = {
+            let relayed = relayed.clone();
+            feed.relayed(move |event| relayed.lock().unwrap().push(*event))
+        };
+
+        let mut subscriber = feed.subscribe();
+        relaying.publish(1);
+        feed.publish(2);
+
+        assert_eq!(subscriber.recv().await.unwrap(), 1);
+        assert_eq!(subscriber.recv().await.unwrap(), 2);
+        assert_eq!(*relayed.lock().unwrap(), vec![1]);
+    }
+
+    #[tokio::test]
+    async fn should_return_the_new_answers_to_the_question_polled() {
+        let feed = Feed::new();
+        let mut answers = feed.subscribe();
+
+        let question_uuid = QuestionUuid::new_v4();
+        let seen = AnswerBuilder::new(question_uuid).build_detail();
+        let new = AnswerDetail { created_at: seen.created_at + TimeDelta::seconds(1), ..seen.clone() };
+        let also_new = AnswerDetail { answer_uuid: AnswerUuid::new_v4(), ..new.clone() };
+
+        // Published late, to another question, then new
+        feed.publish(seen.clone());
+        feed.publish(AnswerBuilder::new(QuestionUuid::new_v4()).build_detail());
+        feed.publish(new.clone());
+        feed.publish(also_new.clone());
+
+        let received = next_answers(&mut answers, question_uuid, Some(seen.created_at), Duration::from_secs(1)).await;
+
+        assert_eq!(received, vec![new, also_new]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_return_no_answers_once_the_wait_expires() {
+        let feed = Feed::new();
+        let mut answers = feed.subscribe();
+        let question_uuid = QuestionUuid::new_v4();
+
+        feed.publish(AnswerBuilder::new(QuestionUuid::new_v4()).build_detail());
+
+        assert!(next_answers(&mut answers, question_uuid, None, Duration::from_secs(30)).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_stream_questions_until_the_feed_is_dropped() {
+        let feed = Feed::new();
+        let events = question_events(feed.subscribe());
+
+        feed.publish(QuestionDetail {
+            question_uuid: QuestionUuid::default(),
+            title: "Is this streamed?".to_owned(),
+            description: "".to_owned(),
+            status: QuestionStatus::Open,
+            created_at: Utc::now(),
+            answer_count: 0,
+            last_activity_at: Utc::now(),
+            view_count: 0,
+            word_count: 0,
+            reading_time_seconds: 0,
+            excerpt: None,
+        });
+        drop(feed);
+
+        let events: Vec<_> = events.collect().await;
+
+        assert_eq!(events.len(), 1);
+        assert!(events[0].is_ok());
+    }
+}
@@ -0,0 +1,410 @@
+use std::{
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde_json::json;
+use thiserror::Error;
+use tokio::{
+    sync::Semaphore,
+    time::{Instant, MissedTickBehavior},
+};
+
+use crate::{
+    models::{Answer, AnswerSort, Question, QuestionDetail, QuestionFilter, QuestionUuid},
+    persistance::{answers_dao::AnswersDao, questions_dao::QuestionsDao},
+};
+
+/// Errors for parsing a `--mix` argument
+#[derive(Error, Debug, PartialEq)]
+pub enum MixError {
+    #[error("Expected `read=<weight>,write=<weight>` but got `{0}`")]
+    Format(String),
+
+    #[error("At least one weight must be positive")]
+    Empty,
+}
+
+/// Represents the relative weights of read and write operations
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mix {
+    pub read: u32,
+    pub write: u32,
+}
+
+impl FromStr for Mix {
+    type Err = MixError;
+
+    /// Parses weights such as `read=80,write=20`, a missing kind gets a weight of 0.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut mix = Mix { read: 0, write: 0 };
+
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (kind, weight) = part.split_once('=').ok_or_else(|| MixError::Format(s.to_owned()))?;
+            let weight: u32 = weight.trim().parse().map_err(|_| MixError::Format(s.to_owned()))?;
+
+            match kind.trim() {
+                "read" => mix.read = weight,
+                "write" => mix.write = weight,
+                _ => return Err(MixError::Format(s.to_owned())),
+            }
+        }
+
+        if mix.read + mix.write == 0 {
+            return Err(MixError::Empty);
+        }
+
+        Ok(mix)
+    }
+}
+
+/// Represents a single operation of the workload
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    ReadQuestions,
+    /// Reads the answers of a question created earlier in the run, picked by the index
+    ReadAnswers(usize),
+    CreateQuestion(Question),
+    /// Answers a question created earlier in the run, picked by the index
+    CreateAnswer(usize, String),
+}
+
+/// Generates the same sequence of operations for the same seed.
+pub struct Workload {
+    rng: StdRng,
+    mix: Mix,
+    sequence: u64,
+}
+
+impl Workload {
+    pub fn new(seed: u64, mix: Mix) -> Self {
+        Workload {
+            rng: StdRng::seed_from_u64(seed),
+            mix,
+            sequence: 0,
+        }
+    }
+
+    /// Picks the next operation according to the mix.
+    pub fn next_operation(&mut self) -> Operation {
+        self.sequence += 1;
+
+        let read = self.rng.gen_range(0..self.mix.read + self.mix.write) < self.mix.read;
+        let half = self.rng.gen_bool(0.5);
+        let index = self.rng.gen::<u32>() as usize;
+
+        match (read, half) {
+            (true, true) => Operation::ReadQuestions,
+            (true, false) => Operation::ReadAnswers(index),
+            (false, true) => Operation::CreateQuestion(Question {
+                question_uuid: None,
+                title: format!("Load test question {}", self.sequence),
+                description: format!("Generated by loadgen, operation {}", self.sequence),
+            }),
+            (false, false) => Operation::CreateAnswer(index, format!("Load test answer {}", self.sequence)),
+        }
+    }
+}
+
+/// Something the workload can be run against, the HTTP API or the DAO layer.
+#[async_trait]
+pub trait LoadTarget {
+
+    /// Asynchronously executes an operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - The operation to execute.
+    /// * `question_uuid` - The question the operation refers to, if it refers to one.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the UUID of the question created by the operation, if any, or an error message on failure.
+    async fn execute(&self, operation: Operation, question_uuid: Option<QuestionUuid>) -> Result<Option<QuestionUuid>, String>;
+}
+
+/// Runs the workload against a running instance over HTTP.
+pub struct HttpTarget {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+/// Constructor
+impl HttpTarget {
+    pub fn new(base_url: &str) -> Self {
+        HttpTarget {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_owned(),
+        }
+    }
+}
+
+#[async_trait]
+impl LoadTarget for HttpTarget {
+    async fn execute(&self, operation: Operation, question_uuid: Option<QuestionUuid>) -> Result<Option<QuestionUuid>, String> {
+        let creates_question = matches!(operation, Operation::CreateQuestion(_));
+
+        let request = match operation {
+            Operation::ReadQuestions => self.client.get(format!("{}/questions", self.base_url)),
+            Operation::ReadAnswers(_) => self.client
+                .get(format!("{}/answers", self.base_url))
+                .json(&json!({ "question_uuid": question_uuid })),
+            Operation::CreateQuestion(question) => self.client
+                .post(format!("{}/question", self.base_url))
+                .json(&json!({ "title": question.title, "description": question.description })),
+            Operation::CreateAnswer(_, content) => self.client
+                .post(format!("{}/answer", self.base_url))
+                .json(&json!({ "question_uuid": question_uuid, "content": content })),
+        };
+
+        let response = request.send().await.map_err(|e| e.to_string())?;
+
+        if !response.status().is_success() {
+            return Err(format!("status {}", response.status()));
+        }
+
+        if !creates_question {
+            return Ok(None);
+        }
+
+        let question: QuestionDetail = response.json().await.map_err(|e| e.to_string())?;
+
+        Ok(Some(question.question_uuid))
+    }
+}
+
+/// Runs the workload against the DAO layer, bypassing HTTP.
+pub struct DaoTarget {
+    questions_dao: Arc<dyn QuestionsDao + Send + Sync>,
+    answers_dao: Arc<dyn AnswersDao + Send + Sync>,
+}
+
+/// Constructor
+impl DaoTarget {
+    pub fn new(questions_dao: Arc<dyn QuestionsDao + Send + Sync>, answers_dao: Arc<dyn AnswersDao + Send + Sync>) -> Self {
+        DaoTarget { questions_dao, answers_dao }
+    }
+}
+
+#[async_trait]
+impl LoadTarget for DaoTarget {
+    async fn execute(&self, operation: Operation, question_uuid: Option<QuestionUuid>) -> Result<Option<QuestionUuid>, String> {
+        let question_uuid = question_uuid.unwrap_or_default();
+
+        match operation {
+            Operation::ReadQuestions => self.questions_dao.get_questions(&QuestionFilter::default()).await.map(|_| None),
+            Operation::ReadAnswers(_) => self.answers_dao.get_answers(question_uuid, AnswerSort::default()).await.map(|_| None),
+            Operation::CreateQuestion(question) => {
+                self.questions_dao.create_question(question).await.map(|q| Some(q.question_uuid))
+            }
+            Operation::CreateAnswer(_, content) => {
+                self.answers_dao.create_answer(Answer { answer_uuid: None, question_uuid, content }).await.map(|_| None)
+            }
+        }.map_err(|e| e.to_string())
+    }
+}
+
+/// Controls the shape of a load test
+#[derive(Debug, Clone)]
+pub struct LoadOptions {
+    /// Operations started per second
+    pub rps: u32,
+    pub mix: Mix,
+    pub duration: Duration,
+    /// Seed of the workload, the same seed yields the same sequence of operations
+    pub seed: u64,
+    /// Most operations in flight at once, further ones wait and their wait counts towards latency
+    pub concurrency: usize,
+}
+
+/// Represents the latency distribution of a load test
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadReport {
+    pub operations: usize,
+    pub errors: usize,
+    /// Operations completed per second
+    pub throughput: f64,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+/// Runs a workload at a fixed rate and measures how long operations take.
+///
+/// Latency is measured from when an operation was due to start rather than when it actually
+/// started, so a saturated target shows up in the percentiles instead of slowing the load down.
+///
+/// # Arguments
+///
+/// * `target` - What to run the workload against.
+/// * `options` - The rate, mix and length of the run.
+///
+/// # Returns
+///
+/// A `LoadReport` of the latencies of all operations.
+pub async fn run(target: Arc<dyn LoadTarget + Send + Sync>, options: &LoadOptions) -> LoadReport {
+    let mut workload = Workload::new(options.seed, options.mix);
+    let in_flight = Arc::new(Semaphore::new(options.concurrency.max(1)));
+    let questions: Arc<Mutex<Vec<QuestionUuid>>> = Arc::default();
+
+    let mut interval = tokio::time::interval(Duration::from_secs(1) / options.rps.max(1));
+    interval.set_missed_tick_behavior(MissedTickBehavior::Burst);
+
+    let started_at = Instant::now();
+    let mut tasks = Vec::new();
+
+    while started_at.elapsed() < options.duration {
+        let due = interval.tick().await;
+        let operation = workload.next_operation();
+
+        let permit = in_flight.clone().acquire_owned().await.expect("Load test semaphore closed!");
+        let target = target.clone();
+        let questions = questions.clone();
+
+        tasks.push(tokio::spawn(async move {
+            let _permit = permit;
+
+            // Operations on a question need one to exist, so create one first if there is none
+            let question_uuid = match &operation {
+                Operation::ReadAnswers(index) | Operation::CreateAnswer(index, _) => {
+                    let questions = questions.lock().unwrap();
+                    questions.get(index % questions.len().max(1)).copied()
+                }
+                _ => None,
+            };
+
+            let operation = match (&operation, &question_uuid) {
+                (Operation::ReadAnswers(_) | Operation::CreateAnswer(..), None) => Operation::CreateQuestion(Question {
+                    question_uuid: None,
+                    title: "Load test question".to_owned(),
+                    description: "Generated by loadgen".to_owned(),
+                }),
+                _ => operation,
+            };
+
+            let result = target.execute(operation, question_uuid).await;
+
+            if let Ok(Some(uuid)) = &result {
+                questions.lock().unwrap().push(*uuid);
+            }
+
+            (due.elapsed(), result.is_err())
+        }));
+    }
+
+    let mut latencies = Vec::with_capacity(tasks.len());
+    let mut errors = 0;
+
+    for task in tasks {
+        let (latency, failed) = task.await.expect("Load test task panicked!");
+        latencies.push(latency);
+        errors += failed as usize;
+    }
+
+    let elapsed = started_at.elapsed();
+    latencies.sort();
+
+    LoadReport {
+        operations: latencies.len(),
+        errors,
+        throughput: latencies.len() as f64 / elapsed.as_secs_f64(),
+        p50: percentile(&latencies, 50.0),
+        p90: percentile(&latencies, 90.0),
+        p99: percentile(&latencies, 99.0),
+        max: latencies.last().copied().unwrap_or_default(),
+    }
+}
+
+/// Picks the nearest-rank percentile of sorted latencies.
+fn percentile(sorted: &[Duration], percent: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::persistance::{
+        answers_dao::AnswersDaoInMemory, memory::MemoryStore, questions_dao::QuestionsDaoInMemory,
+    };
+
+    #[test]
+    fn should_parse_mix() {
+        assert_eq!("read=80,write=20".parse(), Ok(Mix { read: 80, write: 20 }));
+        assert_eq!("write=1".parse(), Ok(Mix { read: 0, write: 1 }));
+        assert_eq!("read=0".parse::<Mix>(), Err(MixError::Empty));
+        assert!(matches!("read=80,delete=20".parse::<Mix>(), Err(MixError::Format(_))));
+        assert!(matches!("read=most".parse::<Mix>(), Err(MixError::Format(_))));
+    }
+
+    #[test]
+    fn should_generate_same_workload_for_same_seed() {
+        let mix = Mix { read: 50, write: 50 };
+        let mut first = Workload::new(7, mix);
+        let mut second = Workload::new(7, mix);
+        let mut other = Workload::new(8, mix);
+
+        let first: Vec<Operation> = (0..100).map(|_| first.next_operation()).collect();
+        let second: Vec<Operation> = (0..100).map(|_| second.next_operation()).collect();
+        let other: Vec<Operation> = (0..100).map(|_| other.next_operation()).collect();
+
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    }
+
+    #[test]
+    fn should_only_generate_reads_for_read_only_mix() {
+        let mut workload = Workload::new(1, Mix { read: 1, write: 0 });
+
+        assert!((0..100).all(|_| matches!(
+            workload.next_operation(),
+            Operation::ReadQuestions | Operation::ReadAnswers(_)
+        )));
+    }
+
+    #[test]
+    fn should_compute_nearest_rank_percentiles() {
+        let latencies: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+
+        assert_eq!(percentile(&latencies, 50.0), Duration::from_millis(50));
+        assert_eq!(percentile(&latencies, 99.0), Duration::from_millis(99));
+        assert_eq!(percentile(&latencies, 100.0), Duration::from_millis(100));
+        assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn should_run_workload_against_daos() {
+        let store = Arc::new(MemoryStore::new());
+        let target = Arc::new(DaoTarget::new(
+            Arc::new(QuestionsDaoInMemory::new(store.clone())),
+            Arc::new(AnswersDaoInMemory::new(store)),
+        ));
+
+        let options = LoadOptions {
+            rps: 200,
+            mix: Mix { read: 50, write: 50 },
+            duration: Duration::from_millis(250),
+            seed: 1,
+            concurrency: 8,
+        };
+
+        let report = run(target, &options).await;
+
+        assert!(report.operations >= 40, "{:?}", report);
+        assert_eq!(report.errors, 0);
+        assert!(report.p50 <= report.p99 && report.p99 <= report.max);
+    }
+}
@@ -3,65 +3,621 @@ extern crate log;
 
 extern crate pretty_env_logger;
 
-mod handlers;
-mod models;
-mod persistance;
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
 
-use::std::sync::Arc;
+use axum::http::{header, HeaderValue, Method};
+use clap::{Parser, Subcommand};
 use dotenvy::dotenv;
-use handlers::*;
-use sqlx::postgres::PgPoolOptions;
-use axum::{
-    routing::{delete, get, post},
-    Router,
-};
-use persistance::{
-    answers_dao::{AnswersDao, AnswersDaoImpl},
-    questions_dao::{QuestionsDao, QuestionsDaoImpl},
+use tokio::{signal, sync::Notify};
+use tower_http::cors::CorsLayer;
+
+use tech_qna_api::{
+    app,
+    config::{Config, QuestionsCacheBackend, StorageBackend},
+    idempotency,
+    integrations::{self, ChatIntegrations},
+    jobs,
+    persistance::{
+        cache::{CachedAnswersDao, CachedQuestionsDao, InMemoryQuestionsCache, QuestionsCache},
+        notify, partitions, DatabasePool,
+    },
+    loadgen::{self, DaoTarget, HttpTarget, LoadOptions, LoadTarget, Mix},
+    recording::{self, ReplayOptions},
+    redact::{self, redact},
+    outbox, search_index,
+    seed::{self, SeedData, SeedSummary},
+    views, webhooks, AppState,
 };
+#[cfg(any(feature = "nats", feature = "kafka"))]
+use tech_qna_api::events;
+#[cfg(feature = "grpc")]
+use tech_qna_api::handlers::grpc;
+#[cfg(feature = "redis")]
+use tech_qna_api::persistance::cache::RedisQuestionsCache;
+#[cfg(feature = "otel")]
+use tech_qna_api::telemetry;
 
-/// Represents the application state containing DAO instances for questions and answers.
-#[derive(Clone)]
-pub struct AppState {
-    pub questions_dao: Arc<dyn QuestionsDao + Send + Sync>,
-    pub answers_dao: Arc<dyn AnswersDao + Send + Sync>,
+/// Backend API for a StackOverflow-like Q&A app
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Serve the API (the default when no subcommand is given)
+    Serve,
+    /// Apply pending database migrations and exit
+    Migrate,
+    /// Bulk-insert questions and answers from a JSON file, e.g. for demo environments
+    Seed {
+        /// Path of the seed file
+        #[arg(long)]
+        file: PathBuf,
+        /// Only check the file and report what would be inserted, without connecting to the database
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Re-issue requests from a recording against a running instance, e.g. for load testing
+    Replay {
+        /// Path of the recording, written by a server built with the `record` feature
+        #[arg(long)]
+        file: PathBuf,
+        /// Base URL of the instance to send the requests to
+        #[arg(long, default_value = "http://127.0.0.1:8000")]
+        target: String,
+        /// Multiplier applied to the recorded pace, 0 sends requests back to back
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+        /// Most requests waiting for a response at once
+        #[arg(long, default_value_t = 32)]
+        concurrency: usize,
+    },
+    /// Generate a seeded workload at a fixed rate and print latency percentiles
+    Loadgen {
+        /// Operations started per second
+        #[arg(long, default_value_t = 100)]
+        rps: u32,
+        /// Relative weights of reads and writes
+        #[arg(long, default_value = "read=80,write=20")]
+        mix: Mix,
+        /// Length of the run in seconds
+        #[arg(long, default_value_t = 30)]
+        duration: u64,
+        /// Seed of the workload, the same seed yields the same sequence of operations
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+        /// Most operations in flight at once
+        #[arg(long, default_value_t = 256)]
+        concurrency: usize,
+        /// Base URL of the instance to load
+        #[arg(long, default_value = "http://127.0.0.1:8000", conflicts_with = "direct")]
+        target: String,
+        /// Call the DAOs of the configured storage backend directly instead of a running instance
+        #[arg(long)]
+        direct: bool,
+    },
 }
 
 /// Main entry point of the application
 #[tokio::main]
 async fn main() {
-    const MAX_CONNECTIONS: u32 = 5;
+    let cli = Cli::parse();
 
-    pretty_env_logger::init();
     dotenv().ok();
 
-    // Create a new PgPoolOptions instance
-    let pool = PgPoolOptions::new().max_connections(MAX_CONNECTIONS)
-                                                   .connect(&std::env::var("DATABASE_URL")
-                                                   .expect("DATABASE_URL must be set."))
-                                                   .await
-                                                   .expect("Failed to create Postgres connection pool!");
-
-    // Create DataAccessObject instances 
-    let questions_dao = Arc::new(QuestionsDaoImpl::new(pool.clone()));
-    let answers_dao = Arc::new(AnswersDaoImpl::new(pool));
-
-    let app_state = AppState {questions_dao, answers_dao};
-
-    let app = Router::new()
-        .route("/question", post(create_question))
-        .route("/questions", get(read_questions))
-        .route("/question", delete(delete_question))
-        .route("/answer", post(create_answer))
-        .route("/answers", get(read_answers))
-        .route("/answer", delete(delete_answer))
-        .with_state(app_state);
-
-    let listener = tokio::net::TcpListener::bind("127.0.0.1:8000")
+    // Replaying only talks to another instance, so it needs none of the server's configuration
+    if let Some(Command::Replay { file, target, speed, concurrency }) = cli.command {
+        init_logger(None);
+        replay(file, ReplayOptions { target, speed, concurrency }).await;
+        return;
+    }
+
+    // Same for load tests over HTTP, only direct ones need the storage configuration
+    if let Some(Command::Loadgen { rps, mix, duration, seed, concurrency, target, direct: false }) = cli.command {
+        init_logger(None);
+        let options = LoadOptions { rps, mix, duration: Duration::from_secs(duration), seed, concurrency };
+        loadgen(Arc::new(HttpTarget::new(&target)), &target, &options).await;
+        return;
+    }
+
+    // Dry runs of seeding write nothing, so they do not need the storage configuration either
+    if let Some(Command::Seed { file, dry_run: true }) = &cli.command {
+        init_logger(None);
+        let (_, summary) = load_seed(file);
+        info!("Seeding would insert {} questions and {} answers.", summary.questions, summary.answers);
+        return;
+    }
+
+    // The logger is configured from the config, so errors loading it can only go to stderr
+    let config = Config::load().unwrap_or_else(|err| {
+        eprintln!("{}", redact(&err.to_string()));
+        std::process::exit(1);
+    });
+
+    init_logger(config.log_level.clone());
+
+    #[cfg(feature = "otel")]
+    let tracer_provider = telemetry::init();
+
+    let command = cli.command.unwrap_or(Command::Serve);
+
+    let pool = match config.storage_backend {
+        StorageBackend::Database => {
+            // The scheme was validated when loading the config
+            let kind = config.database_kind().expect("Unsupported DATABASE_URL scheme!");
+
+            let pool = DatabasePool::connect(kind, &config.database_url, config.database_max_connections)
+                .await
+                .expect("Failed to create database connection pool!");
+
+            if config.run_migrations || matches!(command, Command::Migrate) {
+                info!("Running database migrations.");
+                pool.run_migrations().await.expect("Failed to run database migrations!");
+            }
+
+            Some(pool)
+        }
+        StorageBackend::Memory => {
+            if matches!(command, Command::Migrate | Command::Seed { .. }) {
+                error!("The migrate and seed commands need a database, not STORAGE_BACKEND=memory.");
+                std::process::exit(1);
+            }
+
+            warn!("Using in-memory storage, all data will be lost on exit.");
+            None
+        }
+    };
+
+    match (command, &pool) {
+        (Command::Serve, Some(pool)) => {
+            let state = share_live_events(&config, pool, AppState::for_database(pool, &config)).await;
+            schedule_partition_maintenance(&config, pool);
+            serve(&config, state).await
+        }
+        (Command::Serve, None) => serve(&config, AppState::in_memory(&config)).await,
+        (Command::Migrate, _) => info!("Migrations applied."),
+        (Command::Replay { .. }, _) => unreachable!("replay is handled before loading the config"),
+        (Command::Loadgen { rps, mix, duration, seed, concurrency, .. }, pool) => {
+            let state = match pool {
+                Some(pool) => AppState::for_database(pool, &config),
+                None => AppState::in_memory(&config),
+            };
+
+            let options = LoadOptions { rps, mix, duration: Duration::from_secs(duration), seed, concurrency };
+            let target = DaoTarget::new(state.questions_dao, state.answers_dao);
+            loadgen(Arc::new(target), "the DAO layer", &options).await;
+        }
+        (Command::Seed { file, .. }, Some(pool)) => {
+            // Checked in full first, so a bad record does not leave the file half inserted
+            let (data, _) = load_seed(&file);
+
+            let state = AppState::for_database(pool, &config);
+
+            match seed::seed(data, state.questions_dao.as_ref(), state.answers_dao.as_ref()).await {
+                Ok(summary) => info!("Seeded {} questions and {} answers.", summary.questions, summary.answers),
+                Err(err) => {
+                    error!("{}", seed::SeedError::from(err));
+                    pool.close().await;
+                    std::process::exit(1);
+                }
+            }
+        }
+        (Command::Seed { .. }, None) => unreachable!("seeding requires a database"),
+    }
+
+    if let Some(pool) = pool {
+        pool.close().await;
+    }
+
+    #[cfg(feature = "otel")]
+    if let Some(provider) = tracer_provider {
+        telemetry::shutdown(provider);
+    }
+}
+
+/// Installs the logger, which redacts secrets from every line it writes.
+///
+/// # Arguments
+///
+/// * `filters` - The log level filters, `RUST_LOG` is used if `None`.
+fn init_logger(filters: Option<String>) {
+    let mut builder = pretty_env_logger::formatted_builder();
+    if let Some(filters) = filters.or_else(|| std::env::var("RUST_LOG").ok()) {
+        builder.parse_filters(&filters);
+    }
+
+    let logger = builder.build();
+    let max_level = logger.filter();
+    redact::init_logger(logger, max_level).expect("Failed to install the logger!");
+}
+
+/// Shares the live events of the state with the other instances, when `LIVE_FANOUT` is set.
+///
+/// # Arguments
+///
+/// * `config` - The application configuration.
+/// * `pool` - The database connection pool, which must be Postgres for events to be shared.
+/// * `state` - The application state, holding the feeds of live events.
+///
+/// # Returns
+///
+/// The `AppState` to serve, exiting if listening for the events of the other instances fails.
+async fn share_live_events(config: &Config, pool: &DatabasePool, state: AppState) -> AppState {
+    match pool {
+        // The database was validated to be Postgres when loading the config
+        DatabasePool::Postgres(pool) if config.live_fanout => {
+            let state = notify::share_feeds(pool.clone(), state).await.unwrap_or_else(|err| {
+                error!("Failed to listen for the live events of other instances: {}", err);
+                std::process::exit(1);
+            });
+
+            info!("Sharing live events with other instances on channel {}.", notify::CHANNEL);
+            state
+        }
+        _ => state,
+    }
+}
+
+/// Schedules the maintenance of the partitioned answers table, when on Postgres and `PARTITION_MAINTENANCE_SECS` is
+/// not 0.
+///
+/// # Arguments
+///
+/// * `config` - The application configuration.
+/// * `pool` - The database connection pool, only Postgres partitions the answers table.
+fn schedule_partition_maintenance(config: &Config, pool: &DatabasePool) {
+    if let (DatabasePool::Postgres(pool), Some(every)) = (pool, config.partition_maintenance_interval()) {
+        partitions::schedule(pool.clone(), every);
+        info!("Maintaining the answers partitions every {:?}.", every);
+    }
+}
+
+/// Serves the API until a shutdown signal arrives and in-flight requests have drained.
+///
+/// # Arguments
+///
+/// * `config` - The application configuration.
+/// * `state` - The application state, holding the DAOs of the configured storage backend.
+async fn serve(config: &Config, state: AppState) {
+    #[cfg(feature = "record")]
+    let state = match &config.record_file {
+        Some(path) => {
+            let recorder = recording::Recorder::start(path).await.unwrap_or_else(|err| {
+                error!("Failed to create recording {}: {}", path.display(), err);
+                std::process::exit(1);
+            });
+
+            info!("Recording API requests to {}.", path.display());
+            AppState { recorder: Some(Arc::new(recorder)), ..state }
+        }
+        None => state,
+    };
+
+    #[cfg(not(feature = "record"))]
+    if config.record_file.is_some() {
+        warn!("RECORD_FILE is set, but recording requires building with the `record` feature.");
+    }
+
+    let cache: Option<Arc<dyn QuestionsCache + Send + Sync>> = match config.questions_cache_backend() {
+        Some(QuestionsCacheBackend::Memory) => {
+            info!("Caching question lists in memory for {:?}.", config.questions_cache_ttl());
+            Some(Arc::new(InMemoryQuestionsCache::new(
+                config.questions_cache_ttl(),
+                config.questions_cache_max_questions,
+            )))
+        }
+        #[cfg(feature = "redis")]
+        Some(QuestionsCacheBackend::Redis) => {
+            let url = config.redis_url.as_deref().unwrap_or_default();
+            let cache = RedisQuestionsCache::connect(url, config.questions_cache_ttl()).await.unwrap_or_else(|err| {
+                error!("Failed to connect to Redis: {}", err);
+                std::process::exit(1);
+            });
+
+            info!("Caching question lists in Redis for {:?}.", config.questions_cache_ttl());
+            Some(Arc::new(cache))
+        }
+        #[cfg(not(feature = "redis"))]
+        Some(QuestionsCacheBackend::Redis) => {
+            warn!("REDIS_URL is set, but caching in Redis requires building with the `redis` feature.");
+            None
+        }
+        None => None,
+    };
+
+    let state = match cache {
+        Some(cache) => AppState {
+            questions_dao: Arc::new(CachedQuestionsDao::new(state.questions_dao.clone(), cache.clone())),
+            answers_dao: Arc::new(CachedAnswersDao::new(state.answers_dao.clone(), cache)),
+            ..state
+        },
+        None => state,
+    };
+
+    // Each event of the outbox is claimed by a single instance, so webhooks are not notified twice with live fan-out
+    outbox::start(&state);
+    webhooks::start(&state);
+    webhooks::purge_expired(&state);
+    idempotency::start(&state);
+    views::start(&state);
+
+    let chat_webhooks = config.chat_webhooks();
+    if !chat_webhooks.is_empty() {
+        info!("Announcing new questions to {} chat webhooks.", chat_webhooks.len());
+        let integrations = ChatIntegrations::new(
+            chat_webhooks,
+            &config.chat_message_template,
+            config.chat_messages_per_minute,
+        );
+        integrations::start(&state, Arc::new(integrations));
+    }
+
+    if let Some(search_index) = &state.search_index {
+        search_index::start(&state, search_index.clone());
+        // Reindexing is the only kind of job, so there is nothing to resume without an index
+        jobs::start(&state);
+        info!("Searching questions in the {} index.", config.search_index);
+    }
+
+    #[cfg(any(feature = "nats", feature = "kafka"))]
+    if let Some(url) = &config.events_url {
+        let publisher = events::Publisher::connect(url).await.unwrap_or_else(|err| {
+            error!("Failed to set up event publishing: {}", err);
+            std::process::exit(1);
+        });
+
+        events::start(&state, publisher, config.events_topic.clone());
+        info!("Publishing content events to {}.", config.events_topic);
+    }
+
+    #[cfg(not(any(feature = "nats", feature = "kafka")))]
+    if config.events_url.is_some() {
+        warn!("EVENTS_URL is set, but publishing events requires building with the `nats` or `kafka` feature.");
+    }
+
+    // Stopped once the HTTP server starts draining, so both drain on the same signal
+    #[cfg(feature = "grpc")]
+    let (stop_grpc, grpc_server) = match config.grpc_bind_address() {
+        Some(address) => {
+            let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+            (Some(stop), Some(serve_grpc(address, state.clone(), stopped).await))
+        }
+        None => (None, None),
+    };
+
+    #[cfg(not(feature = "grpc"))]
+    if config.grpc_port.is_some() {
+        warn!("GRPC_PORT is set, but gRPC requires building with the `grpc` feature.");
+    }
+
+    // Views counted since the last write are written once requests have drained
+    let (view_counter, questions_dao) = (state.view_counter.clone(), state.questions_dao.clone());
+
+    let app = app(state);
+
+    let app = if config.cors_origins.is_empty() {
+        app
+    } else {
+        // Origins were validated when loading the config
+        let origins: Vec<HeaderValue> = config.cors_origins.iter().filter_map(|o| o.parse().ok()).collect();
+
+        app.layer(CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods([Method::GET, Method::POST, Method::DELETE])
+            .allow_headers([header::CONTENT_TYPE, header::IF_NONE_MATCH, idempotency::IDEMPOTENCY_KEY])
+            .expose_headers([header::ETAG, idempotency::IDEMPOTENCY_REPLAYED, idempotency::IDEMPOTENCY_KEY_EXPIRES]))
+    };
+
+    #[cfg(feature = "otel")]
+    let app = app.layer(axum::middleware::from_fn(telemetry::propagate_trace_context));
+
+    let bind_address = config.bind_address();
+
+    let listener = tokio::net::TcpListener::bind(bind_address)
+        .await
+        .unwrap_or_else(|err| {
+            match err.kind() {
+                std::io::ErrorKind::AddrInUse => error!("Port {} is already in use, set PORT to use another one.", config.port),
+                _ => error!("Failed to bind to {}: {}", bind_address, err),
+            }
+            std::process::exit(1);
+        });
+
+    println!("Running on {}", bind_address);
+
+    let shutdown_timeout = config.shutdown_timeout();
+
+    let shutdown_started = Arc::new(Notify::new());
+
+    // The peer address is where the search for the client address starts
+    let app = app.into_make_service_with_connect_info::<SocketAddr>();
+
+    let server = axum::serve(listener, app).with_graceful_shutdown({
+        let shutdown_started = shutdown_started.clone();
+        async move {
+            shutdown_signal().await;
+            shutdown_started.notify_one();
+
+            #[cfg(feature = "grpc")]
+            if let Some(stop) = stop_grpc {
+                let _ = stop.send(());
+            }
+        }
+    });
+
+    // Stop waiting for connections to drain once the timeout elapses
+    tokio::select! {
+        result = server => result.unwrap(),
+        _ = async {
+            shutdown_started.notified().await;
+            tokio::time::sleep(shutdown_timeout).await;
+        } => warn!("In-flight requests did not finish within {:?}, shutting down anyway.", shutdown_timeout),
+    }
+
+    #[cfg(feature = "grpc")]
+    if let Some(grpc_server) = grpc_server {
+        if tokio::time::timeout(shutdown_timeout, grpc_server).await.is_err() {
+            warn!("In-flight gRPC calls did not finish within {:?}, shutting down anyway.", shutdown_timeout);
+        }
+    }
+
+    views::flush(&view_counter, questions_dao.as_ref()).await;
+
+    info!("Server stopped.");
+}
+
+/// Binds the gRPC listener and serves the gRPC API in the background until `stopped` resolves.
+///
+/// # Arguments
+///
+/// * `address` - The socket address to listen on.
+/// * `state` - The application state, shared with the HTTP server.
+/// * `stopped` - Resolves when the server should stop accepting calls and drain.
+///
+/// # Returns
+///
+/// A handle resolving once in-flight calls have drained.
+#[cfg(feature = "grpc")]
+async fn serve_grpc(
+    address: SocketAddr,
+    state: AppState,
+    stopped: tokio::sync::oneshot::Receiver<()>,
+) -> tokio::task::JoinHandle<()> {
+    let listener = tokio::net::TcpListener::bind(address)
         .await
-        .unwrap();
+        .unwrap_or_else(|err| {
+            match err.kind() {
+                std::io::ErrorKind::AddrInUse => error!("Port {} is already in use, set GRPC_PORT to use another one.", address.port()),
+                _ => error!("Failed to bind to {}: {}", address, err),
+            }
+            std::process::exit(1);
+        });
+
+    let incoming = tonic::transport::server::TcpIncoming::from_listener(listener, true, None).unwrap_or_else(|err| {
+        error!("Failed to listen on {}: {}", address, err);
+        std::process::exit(1);
+    });
+
+    println!("gRPC running on {}", address);
+
+    tokio::spawn(async move {
+        let shutdown = async {
+            let _ = stopped.await;
+        };
+
+        if let Err(err) = grpc::server(state).serve_with_incoming_shutdown(incoming, shutdown).await {
+            error!("gRPC server failed: {}", err);
+        }
+    })
+}
 
-    println!("Running on 127.0.0.1:8080");
-    
-    axum::serve(listener, app).await.unwrap();
-}
\ No newline at end of file
+/// Reads and checks a seed file, exiting with every problem found if it cannot be inserted.
+///
+/// # Arguments
+///
+/// * `file` - Path of the seed file.
+///
+/// # Returns
+///
+/// The seed data, and the number of records seeding it would insert.
+fn load_seed(file: &Path) -> (SeedData, SeedSummary) {
+    let data = seed::load(file).unwrap_or_else(|err| {
+        error!("{}", err);
+        std::process::exit(1);
+    });
+
+    match seed::check(&data) {
+        Ok(summary) => (data, summary),
+        Err(problems) => {
+            for problem in &problems {
+                error!("{}", problem);
+            }
+            error!("Found {} problems in {}, nothing was inserted.", problems.len(), file.display());
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Replays a recording and prints a summary of the responses.
+///
+/// # Arguments
+///
+/// * `file` - Path of the recording.
+/// * `options` - Where to send the requests and how fast.
+async fn replay(file: PathBuf, options: ReplayOptions) {
+    let result = match recording::load(&file) {
+        Ok(requests) => {
+            println!("Replaying {} requests against {}", requests.len(), options.target);
+            recording::replay(requests, &options).await
+        }
+        Err(err) => Err(err),
+    };
+
+    match result {
+        Ok(summary) => println!(
+            "Sent {} requests: {} succeeded, {} client errors, {} server errors, {} failed",
+            summary.sent, summary.succeeded, summary.client_errors, summary.server_errors, summary.failed
+        ),
+        Err(err) => {
+            error!("{}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Runs a load test and prints the latency percentiles.
+///
+/// # Arguments
+///
+/// * `target` - What to run the workload against.
+/// * `description` - How to name the target in the output.
+/// * `options` - The rate, mix and length of the run.
+async fn loadgen(target: Arc<dyn LoadTarget + Send + Sync>, description: &str, options: &LoadOptions) {
+    println!(
+        "Running {} operations/s (read={}, write={}) against {} for {:?}",
+        options.rps, options.mix.read, options.mix.write, description, options.duration
+    );
+
+    let report = loadgen::run(target, options).await;
+
+    println!("Operations: {} ({} errors), {:.1}/s", report.operations, report.errors, report.throughput);
+    println!(
+        "Latency: p50 {:?}, p90 {:?}, p99 {:?}, max {:?}",
+        report.p50, report.p90, report.p99, report.max
+    );
+}
+
+/// Completes when the process receives SIGINT (Ctrl+C) or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler!");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler!")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    info!("Shutdown signal received, draining in-flight requests.");
+}
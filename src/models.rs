@@ -1,50 +1,485 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    str::FromStr,
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
 use thiserror::Error;
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::reading_time;
+
+/// Declares an identifier wrapping a UUID, so that the identifiers of different records cannot be mixed up.
+///
+/// Identifiers are parsed once, where they enter the API, and serialize and are stored as plain UUIDs.
+macro_rules! uuid_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(
+            Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, sqlx::Type, ToSchema,
+        )]
+        #[serde(transparent)]
+        #[sqlx(transparent)]
+        pub struct $name(Uuid);
+
+        impl $name {
+            /// Generates a new random identifier.
+            pub fn new_v4() -> Self {
+                $name(Uuid::new_v4())
+            }
+
+            pub fn as_uuid(&self) -> &Uuid {
+                &self.0
+            }
+        }
+
+        impl From<Uuid> for $name {
+            fn from(uuid: Uuid) -> Self {
+                $name(uuid)
+            }
+        }
+
+        impl From<$name> for Uuid {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = uuid::Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Uuid::parse_str(s).map($name)
+            }
+        }
+    };
+}
+
+uuid_id! {
+    /// Identifies a question
+    QuestionUuid
+}
+
+uuid_id! {
+    /// Identifies an answer
+    AnswerUuid
+}
+
+uuid_id! {
+    /// Identifies a background job
+    JobUuid
+}
+
+uuid_id! {
+    /// Identifies a flag raised on a question or answer
+    FlagUuid
+}
+
+/// Longest text a title, description or answer can have, as stored in a `VARCHAR(255)` column
+pub const MAX_TEXT_LENGTH: u64 = 255;
+
+/// Fails for text that would be empty once normalized.
+fn not_blank(text: &str) -> Result<(), ValidationError> {
+    if text.trim().is_empty() {
+        return Err(ValidationError::new("blank").with_message("must not be blank".into()));
+    }
+
+    Ok(())
+}
 
 /// Represents a question
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Validate, ToSchema)]
 pub struct Question {
+    /// Chosen by the client to create the question offline, generated when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub question_uuid: Option<QuestionUuid>,
+    #[validate(
+        custom(function = "not_blank"),
+        length(max = "MAX_TEXT_LENGTH", message = "must be at most 255 characters")
+    )]
+    #[schema(min_length = 1, max_length = 255)]
     pub title: String,
+    #[validate(length(max = "MAX_TEXT_LENGTH", message = "must be at most 255 characters"))]
+    #[schema(max_length = 255)]
     pub description: String,
 }
 
+impl Question {
+    /// Counts the words of the title and description.
+    pub fn word_count(&self) -> i64 {
+        reading_time::word_count(&[&self.title, &self.description])
+    }
+}
+
+/// Represents where a question is in its lifecycle
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, sqlx::Type, ToSchema)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "question_status", rename_all = "lowercase")]
+pub enum QuestionStatus {
+    /// Accepting answers, the status of every new question
+    Open,
+    /// Not accepting answers until reopened
+    Closed,
+    /// Kept for reference only, no longer accepting answers nor changing status
+    Archived,
+}
+
+impl QuestionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuestionStatus::Open => "open",
+            QuestionStatus::Closed => "closed",
+            QuestionStatus::Archived => "archived",
+        }
+    }
+}
+
+impl fmt::Display for QuestionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error for text that is not a question status
+#[derive(Error, Debug)]
+#[error("Invalid question status: {0}")]
+pub struct InvalidQuestionStatus(pub String);
+
+impl FromStr for QuestionStatus {
+    type Err = InvalidQuestionStatus;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(QuestionStatus::Open),
+            "closed" => Ok(QuestionStatus::Closed),
+            "archived" => Ok(QuestionStatus::Archived),
+            _ => Err(InvalidQuestionStatus(s.to_owned())),
+        }
+    }
+}
+
+impl TryFrom<String> for QuestionStatus {
+    type Error = InvalidQuestionStatus;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 /// Represents a question detail
-#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, ToSchema)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject), graphql(complex))]
 pub struct QuestionDetail {
-    pub question_uuid: String,
+    pub question_uuid: QuestionUuid,
     pub title: String,
     pub description: String,
-    pub created_at: String,
+    pub status: QuestionStatus,
+    pub created_at: DateTime<Utc>,
+    pub answer_count: i64,
+    /// When the latest answer was posted, or the question itself if it has no answers
+    pub last_activity_at: DateTime<Utc>,
+    /// Times the question was read, views being counted in batches so the latest ones may not show yet
+    #[serde(default)]
+    pub view_count: i64,
+    /// Words of the title and description, counted when the question is written
+    #[serde(default)]
+    pub word_count: i64,
+    /// Estimated time to read the title and description
+    #[serde(default)]
+    pub reading_time_seconds: i64,
+    /// Start of the description as plain text, in lists of questions only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "graphql", graphql(skip))]
+    pub excerpt: Option<String>,
 }
 
 /// Represents a Question ID from the DB
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Validate, ToSchema)]
 pub struct QuestionId {
-    pub question_uuid: String,
+    pub question_uuid: QuestionUuid,
+}
+
+/// Represents the order of a question listing
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestionSort {
+    /// Oldest first, the default
+    #[default]
+    Oldest,
+    /// Newest first
+    Newest,
+    /// Most answers first, then oldest first
+    MostAnswered,
+}
+
+impl QuestionSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QuestionSort::Oldest => "oldest",
+            QuestionSort::Newest => "newest",
+            QuestionSort::MostAnswered => "most_answered",
+        }
+    }
+}
+
+/// Represents the query string of a question listing
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct QuestionFilter {
+    /// Only list questions with this status, all questions are listed when `None`
+    pub status: Option<QuestionStatus>,
+    /// Order of the questions, `oldest` first by default
+    pub sort: Option<QuestionSort>,
+    /// Only list questions created after this time
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only list questions created before this time
+    pub created_before: Option<DateTime<Utc>>,
+    /// Only list questions whose title contains this text, ignoring case
+    pub title_contains: Option<String>,
+    /// Only list questions with at least one answer when `true`, or without any when `false`
+    pub answered: Option<bool>,
+}
+
+/// Represents the order of an answer listing
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AnswerSort {
+    /// Oldest first, the default
+    #[default]
+    Oldest,
+    /// Newest first
+    Newest,
+}
+
+impl AnswerSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnswerSort::Oldest => "oldest",
+            AnswerSort::Newest => "newest",
+        }
+    }
+}
+
+/// Represents the query string of an answer listing
+#[derive(Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AnswerOrder {
+    /// Order of the answers, `oldest` first by default
+    pub sort: Option<AnswerSort>,
+}
+
+/// Represents the query string of a long poll for the answers of a question
+#[derive(Serialize, Deserialize, Debug, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct AnswerUpdatesQuery {
+    /// Cursor returned by the previous poll, only answers created after it are returned; every answer when `None`
+    pub since: Option<DateTime<Utc>>,
+    /// Longest time to hold the request until answers arrive, in seconds such as `30s`, 30 by default and at most 60
+    pub wait: Option<String>,
+}
+
+/// Represents the answers returned by a long poll, along with the cursor to poll for the next ones
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct AnswerUpdates {
+    /// Answers created after the cursor polled with, oldest first, empty if none arrived in time
+    pub answers: Vec<AnswerDetail>,
+    /// Creation time of the last answer returned so far, to pass as `since` to the next poll
+    pub cursor: Option<DateTime<Utc>>,
+}
+
+/// Represents how much of the questions and answers responses carry
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseProfile {
+    /// Everything, the default
+    #[default]
+    Full,
+    /// No question descriptions, and answer contents cut to a preview next to their full length, for mobile clients
+    Compact,
+}
+
+/// Represents the query string selecting a response profile
+#[derive(Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProfileQuery {
+    /// How much of the questions and answers to return, `full` by default
+    pub profile: Option<ResponseProfile>,
+}
+
+/// Represents what question descriptions and answer contents are returned as, besides their markdown
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderFormat {
+    /// The markdown only, the default
+    #[default]
+    Markdown,
+    /// Sanitized HTML too, in `rendered_description` and `rendered_content`
+    Html,
+}
+
+/// Represents the query string selecting what content is rendered to
+#[derive(Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RenderQuery {
+    /// What descriptions and contents are returned as besides markdown, `markdown` by default
+    pub render: Option<RenderFormat>,
+}
+
+/// Represents the query string of a question search
+#[derive(Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct QuestionSearch {
+    /// Words to search for
+    pub q: String,
+    /// Most results to return, 20 by default and at most 100
+    pub limit: Option<i64>,
+}
+
+/// Represents the query string of a listing of the most viewed questions
+#[derive(Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PopularQuestions {
+    /// Most questions to return, 20 by default and at most 100
+    pub limit: Option<i64>,
+}
+
+/// Represents the questions to look up in one request
+#[derive(Serialize, Deserialize, Validate, ToSchema)]
+pub struct QuestionsLookup {
+    #[validate(length(min = 1, max = "MAX_BATCH_QUESTIONS", message = "must have between 1 and 100 questions"))]
+    #[schema(min_items = 1, max_items = 100)]
+    pub question_uuids: Vec<QuestionUuid>,
+}
+
+/// Represents a question matching a search
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, ToSchema)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
+pub struct QuestionSearchResult {
+    #[serde(flatten)]
+    pub question: QuestionDetail,
+    /// Relevance of the question to the search, higher is better
+    pub rank: f32,
+    /// HTML excerpt of the title and description with the matching words wrapped in `<mark>` tags
+    pub snippet: String,
+}
+
+/// Represents a question along with all its answers, oldest first
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, ToSchema)]
+pub struct QuestionWithAnswers {
+    #[serde(flatten)]
+    pub question: QuestionDetail,
+    pub answers: Vec<AnswerDetail>,
 }
 
 // ----------
 
 /// Represents an answer
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Validate, ToSchema)]
 pub struct Answer {
-    pub question_uuid: String,
+    /// Chosen by the client to create the answer offline, generated when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer_uuid: Option<AnswerUuid>,
+    pub question_uuid: QuestionUuid,
+    #[validate(
+        custom(function = "not_blank"),
+        length(max = "MAX_TEXT_LENGTH", message = "must be at most 255 characters")
+    )]
+    #[schema(min_length = 1, max_length = 255)]
     pub content: String,
 }
 
+impl Answer {
+    /// Counts the words of the content.
+    pub fn word_count(&self) -> i64 {
+        reading_time::word_count(&[&self.content])
+    }
+}
+
 /// Represents an answer detail
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::SimpleObject))]
 pub struct AnswerDetail {
-    pub answer_uuid: String,
-    pub question_uuid: String,
+    pub answer_uuid: AnswerUuid,
+    pub question_uuid: QuestionUuid,
     pub content: String,
-    pub created_at: String,
+    pub created_at: DateTime<Utc>,
+    /// Words of the content, counted when the answer is written
+    #[serde(default)]
+    pub word_count: i64,
+    /// Estimated time to read the content
+    #[serde(default)]
+    pub reading_time_seconds: i64,
 }
 
 // Represents an answer ID in the DB
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Validate, ToSchema)]
 pub struct AnswerId {
-    pub answer_uuid: String,
+    pub answer_uuid: AnswerUuid,
+}
+
+/// Most questions, or answers of questions, that can be looked up in one batch
+pub const MAX_BATCH_QUESTIONS: u64 = 100;
+
+/// Represents the questions to look up the answers of in one request
+#[derive(Serialize, Deserialize, Validate, ToSchema)]
+pub struct AnswersBatch {
+    #[validate(length(min = 1, max = "MAX_BATCH_QUESTIONS", message = "must have between 1 and 100 questions"))]
+    #[schema(min_items = 1, max_items = 100)]
+    pub question_uuids: Vec<QuestionUuid>,
+}
+
+/// Answers of several questions, keyed by question
+pub type AnswersByQuestion = HashMap<QuestionUuid, Vec<AnswerDetail>>;
+
+/// Represents a question held by a client, in the version it holds
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct CachedQuestion {
+    pub question_uuid: QuestionUuid,
+    /// `ETag` of the question with its answers, as returned by the previous sync or by reading it in full
+    pub version: String,
+}
+
+/// Represents the questions a client holds, to bring up to date in one request
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Validate, ToSchema)]
+pub struct SyncRequest {
+    #[validate(length(max = "MAX_BATCH_QUESTIONS", message = "must have at most 100 questions"))]
+    #[schema(max_items = 100)]
+    pub questions: Vec<CachedQuestion>,
+    /// `synced_at` of the previous sync, questions created since are returned; none are when `None`
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// Represents a question with its answers, in its current version
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct SyncedQuestion {
+    #[serde(flatten)]
+    pub question: QuestionWithAnswers,
+    /// `ETag` of the question with its answers, to hold on to for the next sync
+    pub version: String,
+}
+
+/// Represents what changed in the questions a client holds, and the questions created since its previous sync
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct SyncResponse {
+    /// Questions held in another version than the current one, oldest first
+    pub changed: Vec<SyncedQuestion>,
+    /// Questions created since the previous sync that are not held yet, oldest first
+    pub created: Vec<SyncedQuestion>,
+    /// Questions held that no longer exist
+    pub deleted: Vec<QuestionUuid>,
+    /// Time of this sync, by the clock of the database and a minute early, to pass as `since` to the next one
+    pub synced_at: DateTime<Utc>,
 }
 
 /// Errors for database operations
@@ -55,6 +490,10 @@ pub enum DBError {
     #[error("Invalid UUID provided: {0}")]
     InvalidUUID(String),
 
+    /// The record exists, but its state does not allow the operation
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
     /// All other errors
     #[error("Database error occurred")]
     Other(#[from] Box<dyn std::error::Error + Send + Sync>),
@@ -63,4 +502,578 @@ pub enum DBError {
 // Source: https://www.postgresql.org/docs/current/errcodes-appendix.html
 pub mod postgres_error_codes {
     pub const FOREIGN_KEY_VIOLATION: &str = "23503";
-}
\ No newline at end of file
+    pub const UNIQUE_VIOLATION: &str = "23505";
+}
+
+/// MySQL reports integrity errors with a shared SQLSTATE, so they are told apart by error number
+pub mod mysql_error_codes {
+    pub const NO_REFERENCED_ROW: u16 = 1216;
+    pub const NO_REFERENCED_ROW_2: u16 = 1452;
+    pub const DUPLICATE_ENTRY: u16 = 1062;
+}
+// ----------
+
+/// Represents the state of the service or one of its dependencies
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    Up,
+    Down,
+}
+
+/// Represents the outcome of checking a single dependency
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct DependencyCheck {
+    pub name: String,
+    pub status: HealthStatus,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Represents the readiness of the service, which is only up if every dependency is
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ReadinessReport {
+    pub status: HealthStatus,
+    pub checks: Vec<DependencyCheck>,
+}
+
+// ----------
+
+/// Represents an incident announced on the status page
+#[derive(Serialize, Deserialize, Validate, ToSchema)]
+pub struct Incident {
+    #[validate(
+        custom(function = "not_blank"),
+        length(max = "MAX_TEXT_LENGTH", message = "must be at most 255 characters")
+    )]
+    #[schema(min_length = 1, max_length = 255)]
+    pub title: String,
+    #[validate(length(max = "MAX_TEXT_LENGTH", message = "must be at most 255 characters"))]
+    #[schema(max_length = 255)]
+    pub description: String,
+}
+
+/// Represents an incident detail
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct IncidentDetail {
+    pub incident_uuid: Uuid,
+    pub title: String,
+    pub description: String,
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// Represents an incident ID in the DB
+#[derive(Serialize, Deserialize, Validate, ToSchema)]
+pub struct IncidentId {
+    pub incident_uuid: Uuid,
+}
+
+/// Represents the overall state of the service shown on the status page
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ServiceStatus {
+    Operational,
+    Degraded,
+}
+
+/// Represents the status page, listing failing dependencies and ongoing incidents
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct StatusReport {
+    pub status: ServiceStatus,
+    pub uptime_secs: u64,
+    pub degradations: Vec<DependencyCheck>,
+    pub incidents: Vec<IncidentDetail>,
+}
+
+// ----------
+
+/// Represents the error budget of a single service level objective
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct SloObjectiveReport {
+    pub target: f64,
+    pub total_requests: u64,
+    pub good_requests: u64,
+    /// Fraction of good requests
+    pub sli: f64,
+    /// Fraction of the error budget used up, above 1.0 once exhausted
+    pub error_budget_consumed: f64,
+    pub error_budget_remaining: f64,
+}
+
+/// Represents the error budgets over the rolling SLO window
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct SloReport {
+    pub window_secs: u64,
+    pub latency_threshold_ms: u64,
+    pub availability: SloObjectiveReport,
+    pub latency: SloObjectiveReport,
+}
+
+/// Represents the requests in flight on one route
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct RouteConcurrencyReport {
+    pub route: String,
+    pub in_flight: u64,
+    /// Most requests in flight at once since the server started
+    pub peak_in_flight: u64,
+    /// Most requests allowed in flight at once, unlimited when `None`
+    pub limit: Option<u64>,
+    /// Requests refused for being over the limit since the server started
+    pub rejected_requests: u64,
+}
+
+/// Represents a deprecated route or query parameter, and how much clients still use it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct DeprecationReport {
+    /// The route, with its method if only one is deprecated, or the query parameter, e.g. `/questions?profile`
+    pub feature: String,
+    pub deprecated_at: DateTime<Utc>,
+    /// When the feature is removed, if decided
+    pub sunset_at: Option<DateTime<Utc>>,
+    /// The URL of what replaces the feature, if anything
+    pub successor: Option<String>,
+    /// Requests using the feature since the server started
+    pub uses: u64,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Represents the dead tuples, bloat and vacuum activity of one table, or partition of the answers table
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct TableMaintenance {
+    pub table_name: String,
+    pub live_tuples: i64,
+    pub dead_tuples: i64,
+    /// Fraction of the tuples that are dead, waiting to be vacuumed
+    pub dead_tuple_ratio: f64,
+    pub table_bytes: i64,
+    pub indexes_bytes: i64,
+    /// Bytes beyond what the live tuples need, `None` until the table has been analyzed
+    pub estimated_bloat_bytes: Option<i64>,
+    pub last_vacuum: Option<DateTime<Utc>>,
+    pub last_autovacuum: Option<DateTime<Utc>>,
+    pub last_analyze: Option<DateTime<Utc>>,
+    pub last_autoanalyze: Option<DateTime<Utc>>,
+    pub vacuum_count: i64,
+    pub autovacuum_count: i64,
+}
+
+/// Represents the size, bloat and use of one index
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct IndexMaintenance {
+    pub index_name: String,
+    pub table_name: String,
+    pub index_bytes: i64,
+    /// Bytes beyond what the indexed tuples need, `None` until the table has been analyzed, and for indexes other
+    /// than B-trees on plain columns
+    pub estimated_bloat_bytes: Option<i64>,
+    /// Scans of the index since statistics were last reset, an index never scanned being only a cost to writes
+    pub scans: i64,
+}
+
+/// Represents the maintenance state of the tables and indexes of the database
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct MaintenanceReport {
+    pub tables: Vec<TableMaintenance>,
+    pub indexes: Vec<IndexMaintenance>,
+}
+
+// ----------
+
+/// Represents how many questions have the same number of answers
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct AnswerCountBucket {
+    pub answer_count: i64,
+    pub question_count: i64,
+}
+
+/// Represents how many questions were asked on a day, in UTC
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct DailyQuestions {
+    pub day: NaiveDate,
+    pub question_count: i64,
+}
+
+/// Represents totals of the questions and answers, for dashboards
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ContentStats {
+    pub question_count: i64,
+    pub answer_count: i64,
+    /// Questions by number of answers, fewest answers first, leaving out numbers no question has
+    pub answers_per_question: Vec<AnswerCountBucket>,
+    /// Questions asked on each day of the period, oldest first, including days without any
+    pub questions_per_day: Vec<DailyQuestions>,
+}
+
+// ----------
+
+/// Longest webhook URL accepted
+pub const MAX_URL_LENGTH: u64 = 2048;
+
+/// Shortest webhook secret accepted, to rule out trivially guessable ones
+pub const MIN_WEBHOOK_SECRET_LENGTH: u64 = 16;
+
+fn http_url(url: &str) -> Result<(), ValidationError> {
+    match url::Url::parse(url) {
+        Ok(url) if matches!(url.scheme(), "http" | "https") && url.has_host() => Ok(()),
+        _ => Err(ValidationError::new("url").with_message("must be an http or https URL".into())),
+    }
+}
+
+/// Represents a URL to notify of content events, along with the secret their payloads are signed with
+#[derive(Serialize, Deserialize, Debug, Clone, Validate, ToSchema)]
+pub struct Webhook {
+    #[validate(
+        custom(function = "http_url"),
+        length(max = "MAX_URL_LENGTH", message = "must be at most 2048 characters")
+    )]
+    #[schema(max_length = 2048)]
+    pub url: String,
+    #[validate(length(
+        min = "MIN_WEBHOOK_SECRET_LENGTH",
+        max = "MAX_TEXT_LENGTH",
+        message = "must be between 16 and 255 characters"
+    ))]
+    #[schema(min_length = 16, max_length = 255)]
+    pub secret: String,
+}
+
+/// Represents a registered webhook, whose secret is never sent back
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct WebhookDetail {
+    pub webhook_uuid: Uuid,
+    pub url: String,
+    #[serde(skip_serializing, default)]
+    #[schema(ignore)]
+    pub secret: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Represents a webhook ID in the DB
+#[derive(Serialize, Deserialize, Validate, ToSchema)]
+pub struct WebhookId {
+    pub webhook_uuid: Uuid,
+}
+
+/// Hours a question webhook stays registered for when no `ttl_hours` is given
+pub const DEFAULT_QUESTION_WEBHOOK_TTL_HOURS: u32 = 7 * 24;
+
+/// Represents a URL to notify of the new answers to a single question, until the webhook expires
+#[derive(Serialize, Deserialize, Debug, Clone, Validate, ToSchema)]
+pub struct QuestionWebhook {
+    #[validate(
+        custom(function = "http_url"),
+        length(max = "MAX_URL_LENGTH", message = "must be at most 2048 characters")
+    )]
+    #[schema(max_length = 2048)]
+    pub url: String,
+    #[validate(length(
+        min = "MIN_WEBHOOK_SECRET_LENGTH",
+        max = "MAX_TEXT_LENGTH",
+        message = "must be between 16 and 255 characters"
+    ))]
+    #[schema(min_length = 16, max_length = 255)]
+    pub secret: String,
+    /// Hours until the webhook expires, a week by default
+    #[validate(range(min = 1, max = 720, message = "must be between 1 and 720 hours"))]
+    #[schema(minimum = 1, maximum = 720)]
+    pub ttl_hours: Option<u32>,
+}
+
+/// Represents a webhook registered for a single question, whose secret is never sent back
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct QuestionWebhookDetail {
+    pub webhook_uuid: Uuid,
+    pub question_uuid: QuestionUuid,
+    pub url: String,
+    #[serde(skip_serializing, default)]
+    #[schema(ignore)]
+    pub secret: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Question webhooks are delivered to like any other webhook
+impl From<QuestionWebhookDetail> for WebhookDetail {
+    fn from(webhook: QuestionWebhookDetail) -> Self {
+        WebhookDetail {
+            webhook_uuid: webhook.webhook_uuid,
+            url: webhook.url,
+            secret: webhook.secret,
+            created_at: webhook.created_at,
+        }
+    }
+}
+
+/// Represents the JSON Schemas of the payloads delivered to webhooks and brokers
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct EventSchemas {
+    /// Version of the API the schemas describe
+    pub version: String,
+    /// JSON Schema of the payload of each event, by event name
+    #[schema(value_type = Object)]
+    pub schemas: BTreeMap<String, serde_json::Value>,
+}
+
+// ----------
+
+/// Represents the work a background job does
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Writes every question, along with its answers, to the search index
+    ReindexSearch,
+}
+
+impl JobKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobKind::ReindexSearch => "reindex_search",
+        }
+    }
+}
+
+impl FromStr for JobKind {
+    type Err = String;
+
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        match kind {
+            "reindex_search" => Ok(JobKind::ReindexSearch),
+            _ => Err(format!("Unknown job kind: {}", kind)),
+        }
+    }
+}
+
+/// Represents whether a background job is done
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// Being worked on, or waiting to be resumed by an instance
+    Running,
+    Completed,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Running => "running",
+            JobStatus::Completed => "completed",
+        }
+    }
+}
+
+impl FromStr for JobStatus {
+    type Err = String;
+
+    fn from_str(status: &str) -> Result<Self, Self::Err> {
+        match status {
+            "running" => Ok(JobStatus::Running),
+            "completed" => Ok(JobStatus::Completed),
+            _ => Err(format!("Unknown job status: {}", status)),
+        }
+    }
+}
+
+/// Represents a background job and its progress
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct JobDetail {
+    pub job_uuid: JobUuid,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    /// Questions processed so far
+    pub processed: i64,
+    /// The last question processed, which the job resumes after
+    pub cursor: Option<QuestionUuid>,
+    /// Why the last attempt at the current batch failed, `None` once a batch succeeds
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// When the job last made progress or retried, a running job not updated for a while being resumed by another
+    /// instance
+    pub updated_at: DateTime<Utc>,
+}
+
+// ----------
+
+/// Represents why a question or answer was flagged
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagReason {
+    /// Advertising, or content posted in bulk
+    Spam,
+    /// Rude, abusive or hateful
+    Offensive,
+    /// Not about technology, or not a question
+    OffTopic,
+    /// Asked, or answered, before
+    Duplicate,
+    /// Anything else, explained in the comment
+    Other,
+}
+
+impl FlagReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlagReason::Spam => "spam",
+            FlagReason::Offensive => "offensive",
+            FlagReason::OffTopic => "off_topic",
+            FlagReason::Duplicate => "duplicate",
+            FlagReason::Other => "other",
+        }
+    }
+}
+
+impl FromStr for FlagReason {
+    type Err = String;
+
+    fn from_str(reason: &str) -> Result<Self, Self::Err> {
+        match reason {
+            "spam" => Ok(FlagReason::Spam),
+            "offensive" => Ok(FlagReason::Offensive),
+            "off_topic" => Ok(FlagReason::OffTopic),
+            "duplicate" => Ok(FlagReason::Duplicate),
+            "other" => Ok(FlagReason::Other),
+            _ => Err(format!("Unknown flag reason: {}", reason)),
+        }
+    }
+}
+
+/// Represents how a moderator acted on flagged content, or that a content filter only tagged it
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FlagResolution {
+    /// The content was found fine and kept
+    Dismissed,
+    /// The content was deleted
+    Removed,
+    /// The content was tagged by a content filter, and never held in the moderation queue
+    Tagged,
+}
+
+impl FlagResolution {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlagResolution::Dismissed => "dismissed",
+            FlagResolution::Removed => "removed",
+            FlagResolution::Tagged => "tagged",
+        }
+    }
+}
+
+impl FromStr for FlagResolution {
+    type Err = String;
+
+    fn from_str(resolution: &str) -> Result<Self, Self::Err> {
+        match resolution {
+            "dismissed" => Ok(FlagResolution::Dismissed),
+            "removed" => Ok(FlagResolution::Removed),
+            "tagged" => Ok(FlagResolution::Tagged),
+            _ => Err(format!("Unknown flag resolution: {}", resolution)),
+        }
+    }
+}
+
+/// Represents a flag raised on a question or answer, for moderators to review
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Validate, ToSchema)]
+pub struct Flag {
+    pub reason: FlagReason,
+    /// What is wrong with the content, e.g. which question it duplicates
+    #[validate(length(max = "MAX_TEXT_LENGTH", message = "must be at most 255 characters"))]
+    #[schema(max_length = 255)]
+    pub comment: Option<String>,
+}
+
+/// Represents a flag detail
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct FlagDetail {
+    pub flag_uuid: FlagUuid,
+    /// The question flagged, or the question of the answer flagged
+    pub question_uuid: QuestionUuid,
+    /// The answer flagged, `None` for flags on questions
+    pub answer_uuid: Option<AnswerUuid>,
+    pub reason: FlagReason,
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// How a moderator acted on the content, `None` while the flag is in the moderation queue, `tagged` for flags
+    /// content filters raised without holding the content
+    pub resolution: Option<FlagResolution>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    /// The question or answer as submitted, for flags content filters raised to hold it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub held: Option<HeldContent>,
+}
+
+/// Represents a question or answer a content filter held for moderation. It is only created, and from then on read
+/// and announced like any other, once a moderator dismisses the flag holding it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum HeldContent {
+    Question(Question),
+    Answer(Answer),
+}
+
+/// Fails for the resolution only content filters give.
+fn moderator_resolution(resolution: &FlagResolution) -> Result<(), ValidationError> {
+    if *resolution == FlagResolution::Tagged {
+        return Err(ValidationError::new("resolution").with_message("must be dismissed or removed".into()));
+    }
+
+    Ok(())
+}
+
+/// Represents a moderator's decision on flagged content
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Validate, ToSchema)]
+pub struct FlagDecision {
+    /// `dismissed` or `removed`
+    #[validate(custom(function = "moderator_resolution"))]
+    pub resolution: FlagResolution,
+}
+
+// ----------
+
+/// Represents a request made with an idempotency key, and its response once it has one
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdempotentRequest {
+    /// Hex encoded SHA-256 of the request body, to tell retries from other requests reusing the key
+    pub request_hash: String,
+    /// The response to replay, `None` while the request is still being served
+    pub response: Option<IdempotentResponse>,
+    /// When the key was claimed, from which it expires
+    pub claimed_at: DateTime<Utc>,
+}
+
+/// Represents the response recorded for an idempotency key
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdempotentResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+// ----------
+
+/// Represents the page of a collection returned by an API response
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PaginationMeta {
+    pub limit: u64,
+    pub offset: u64,
+    /// Number of items in the whole collection, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<u64>,
+}
+
+// ----------
+
+/// Represents why one field, or the whole body if `field` is `None`, of a request is invalid
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct FieldError {
+    pub field: Option<String>,
+    /// Machine readable reason, e.g. `length` or `blank`
+    pub code: String,
+    pub message: String,
+}
+
+/// Represents the body of a response to an invalid request
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct InvalidRequest {
+    pub errors: Vec<FieldError>,
+}
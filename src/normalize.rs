@@ -0,0 +1,133 @@
+use unicode_normalization::UnicodeNormalization;
+
+use crate::models::{Answer, Flag, Incident, Question};
+
+/// Cleans up user input before it is validated and stored, so visually identical strings are
+/// also byte-identical.
+pub trait Normalize {
+    fn normalize(self) -> Self;
+}
+
+impl Normalize for Question {
+    fn normalize(self) -> Self {
+        Question {
+            question_uuid: self.question_uuid,
+            title: normalize_title(&self.title),
+            description: normalize_text(&self.description),
+        }
+    }
+}
+
+impl Normalize for Answer {
+    fn normalize(self) -> Self {
+        Answer {
+            answer_uuid: self.answer_uuid,
+            question_uuid: self.question_uuid,
+            content: normalize_text(&self.content),
+        }
+    }
+}
+
+impl Normalize for Incident {
+    fn normalize(self) -> Self {
+        Incident {
+            title: normalize_title(&self.title),
+            description: normalize_text(&self.description),
+        }
+    }
+}
+
+impl Normalize for Flag {
+    fn normalize(self) -> Self {
+        // A comment left blank is no comment
+        let comment = self.comment.map(|comment| normalize_text(&comment)).filter(|comment| !comment.is_empty());
+
+        Flag { reason: self.reason, comment }
+    }
+}
+
+/// Normalizes free text such as descriptions and answers.
+///
+/// The text is converted to Unicode NFC, `\r\n` and `\r` line endings become `\n`, control
+/// characters other than newlines and tabs are removed and surrounding whitespace is trimmed.
+///
+/// # Arguments
+///
+/// * `text` - The text to normalize.
+///
+/// # Returns
+///
+/// The normalized text.
+pub fn normalize_text(text: &str) -> String {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+
+    text.nfc()
+        .filter(|c| !c.is_control() || *c == '\n' || *c == '\t')
+        .collect::<String>()
+        .trim()
+        .to_owned()
+}
+
+/// Normalizes a single line of text such as a title.
+///
+/// Like `normalize_text`, but every run of whitespace, including newlines, is collapsed into a
+/// single space.
+///
+/// # Arguments
+///
+/// * `title` - The title to normalize.
+///
+/// # Returns
+///
+/// The normalized title.
+pub fn normalize_title(title: &str) -> String {
+    normalize_text(title).split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_compose_to_nfc() {
+        // "e" followed by a combining acute accent
+        assert_eq!(normalize_text("Cafe\u{301}"), "Caf\u{e9}");
+    }
+
+    #[test]
+    fn should_strip_control_characters() {
+        assert_eq!(normalize_text("a\u{0}b\u{1b}[31mc\u{7f}"), "ab[31mc");
+    }
+
+    #[test]
+    fn should_keep_newlines_and_tabs_in_text() {
+        assert_eq!(normalize_text("  line 1\r\n\tline 2\rline 3\n"), "line 1\n\tline 2\nline 3");
+    }
+
+    #[test]
+    fn should_collapse_whitespace_in_titles() {
+        assert_eq!(normalize_title("  How   do I\n\tuse\u{a0}Rust? "), "How do I use Rust?");
+    }
+
+    #[test]
+    fn should_normalize_question() {
+        let question = Question {
+            question_uuid: None,
+            title: " Cafe\u{301}  title ".to_owned(),
+            description: "description\u{0}\n".to_owned(),
+        };
+
+        assert_eq!(
+            question.normalize(),
+            Question {
+                question_uuid: None,
+                title: "Caf\u{e9} title".to_owned(),
+                description: "description".to_owned(),
+            }
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::handlers;
+
+/// Path the OpenAPI specification is served at
+pub const SPEC_PATH: &str = "/api-doc/openapi.json";
+
+/// Path the Swagger UI is served at
+pub const SWAGGER_UI_PATH: &str = "/swagger-ui";
+
+/// OpenAPI specification of the API.
+///
+/// Every handler listed here must carry a `#[utoipa::path]` annotation and every type it references must derive
+/// `ToSchema`, or the crate does not compile, so the specification cannot fall behind the handlers.
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Tech Q&A API"),
+    paths(
+        handlers::create_question,
+        handlers::read_questions,
+        handlers::search_questions,
+        handlers::read_popular_questions,
+        handlers::lookup_questions,
+        handlers::sync_questions,
+        handlers::stream_questions,
+        handlers::read_question_with_answers,
+        handlers::read_question_text,
+        handlers::close_question,
+        handlers::reopen_question,
+        handlers::delete_question,
+        handlers::create_answer,
+        handlers::read_answers,
+        handlers::read_answers_batch,
+        handlers::delete_answer,
+        handlers::watch_answers,
+        handlers::poll_answers,
+        handlers::create_question_webhook,
+        handlers::read_event_schemas,
+        handlers::flag_question,
+        handlers::flag_answer,
+        handlers::read_moderation_queue,
+        handlers::read_question_flags,
+        handlers::resolve_flag,
+        handlers::health,
+        handlers::ready,
+        handlers::read_status,
+        handlers::read_stats,
+        handlers::create_incident,
+        handlers::resolve_incident,
+        handlers::read_slo,
+        handlers::read_concurrency,
+        handlers::read_deprecations,
+        handlers::read_maintenance,
+        handlers::create_webhook,
+        handlers::read_webhooks,
+        handlers::delete_webhook,
+        handlers::create_reindex_job,
+        handlers::read_job,
+    ),
+    modifiers(&AdminToken),
+    tags(
+        (name = "questions", description = "Asking, finding and closing questions"),
+        (name = "answers", description = "Answering questions"),
+        (name = "events", description = "Schemas of the events delivered to webhooks and brokers"),
+        (name = "moderation", description = "Flagging questions and answers, and the moderation queue, only mounted when `ADMIN_TOKEN` is set"),
+        (name = "status", description = "Probes, the public status page and statistics"),
+        (name = "admin", description = "Incidents, service levels, webhooks, background jobs and database maintenance, only mounted when `ADMIN_TOKEN` is set"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Declares the bearer token protecting the admin API.
+struct AdminToken;
+
+impl Modify for AdminToken {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "admin_token",
+                SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+            );
+        }
+    }
+}
+
+/// Builds the routes serving the OpenAPI specification and a Swagger UI to explore it.
+///
+/// # Returns
+///
+/// A `SwaggerUi` to merge into the API router.
+pub fn swagger_ui() -> SwaggerUi {
+    SwaggerUi::new(SWAGGER_UI_PATH).url(SPEC_PATH, ApiDoc::openapi())
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use axum::{
+        body::Body,
+        http::{header, Method, Request, StatusCode},
+    };
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use tower::ServiceExt;
+
+    use crate::{
+        app,
+        config::Config,
+        models::{DBError, MaintenanceReport},
+        persistance::maintenance_dao::MaintenanceDao,
+        AppState,
+    };
+
+    /// Stands in for Postgres, so that the routes only mounted for it are too
+    struct NoMaintenance;
+
+    #[async_trait]
+    impl MaintenanceDao for NoMaintenance {
+        async fn get_maintenance_report(&self) -> Result<MaintenanceReport, DBError> {
+            Ok(MaintenanceReport { tables: Vec::new(), indexes: Vec::new() })
+        }
+    }
+
+    #[tokio::test]
+    async fn every_documented_operation_should_be_routed() {
+        let config = Config {
+            admin_token: Some("s3cr3t-t0ken".to_owned()),
+            // Nothing listens there, which the reindex job started only retries
+            search_url: Some("http://127.0.0.1:9".to_owned()),
+            ..Config::default()
+        };
+        let router = app(AppState {
+            maintenance_dao: Some(Arc::new(NoMaintenance)),
+            ..AppState::in_memory(&config)
+        });
+
+        let spec = ApiDoc::openapi();
+        assert!(!spec.paths.paths.is_empty());
+
+        for (path, item) in &spec.paths.paths {
+            // Any valid UUID will do, as only whether the route exists matters
+            let uri = path
+                .replace("{question_uuid}", "00000000-0000-0000-0000-000000000000")
+                .replace("{answer_uuid}", "00000000-0000-0000-0000-000000000000")
+                .replace("{flag_uuid}", "00000000-0000-0000-0000-000000000000")
+                .replace("{job_uuid}", "00000000-0000-0000-0000-000000000000");
+
+            let operations = [
+                (Method::GET, &item.get),
+                (Method::POST, &item.post),
+                (Method::PUT, &item.put),
+                (Method::DELETE, &item.delete),
+                (Method::PATCH, &item.patch),
+            ];
+
+            for (method, _) in operations.iter().filter(|(_, operation)| operation.is_some()) {
+                let request = Request::builder()
+                    .method(method)
+                    .uri(&uri)
+                    .header(header::AUTHORIZATION, "Bearer s3cr3t-t0ken")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .body(Body::from("{}"))
+                    .unwrap();
+
+                let status = router.clone().oneshot(request).await.unwrap().status();
+
+                assert!(
+                    status != StatusCode::NOT_FOUND && status != StatusCode::METHOD_NOT_ALLOWED,
+                    "{} {} is documented but not routed",
+                    method,
+                    path
+                );
+            }
+        }
+    }
+}
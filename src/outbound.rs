@@ -0,0 +1,283 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+    time::Duration,
+};
+
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    redirect, Method, RequestBuilder, Url,
+};
+use thiserror::Error;
+
+// Integrations (webhooks, link unfurling, ...) request URLs that users control, so they must use
+// `OutboundClient` rather than a plain `reqwest::Client`, which would happily fetch
+// `http://169.254.169.254/` or `http://localhost:5432/`. Clients for URLs set by the operator,
+// like the canary, are trusted and do not need it.
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+const MAX_REDIRECTS: usize = 5;
+
+/// Errors for outbound requests refused before they are sent
+#[derive(Error, Debug)]
+pub enum OutboundError {
+
+    /// The URL could not be parsed
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(#[from] url::ParseError),
+
+    /// The URL does not use `http` or `https`
+    #[error("URL scheme not allowed: {0}")]
+    SchemeNotAllowed(String),
+
+    /// The URL points at a private, loopback or otherwise internal address
+    #[error("Address not allowed: {0}")]
+    AddressNotAllowed(IpAddr),
+
+    /// The host name does not resolve to any public address
+    #[error("No public address for host: {0}")]
+    NoPublicAddress(String),
+}
+
+/// HTTP client for requests to user supplied URLs, which refuses to reach internal addresses.
+///
+/// Host names are resolved by the client itself and every address it may connect to is checked,
+/// so a name that resolves to a public address when validated and to an internal one when
+/// connecting (DNS rebinding) is refused too. Redirects are re-checked and limited, and proxies
+/// from the environment are ignored as they would resolve names on the client's behalf.
+#[derive(Clone)]
+pub struct OutboundClient {
+    client: reqwest::Client,
+}
+
+impl OutboundClient {
+
+    /// Creates a client with the default timeouts and redirect limit.
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .dns_resolver(Arc::new(PublicResolver))
+            .no_proxy()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
+            .redirect(redirect::Policy::custom(|attempt| {
+                if attempt.previous().len() > MAX_REDIRECTS {
+                    attempt.error("too many redirects")
+                } else if let Err(err) = check_url(attempt.url()) {
+                    attempt.error(err)
+                } else {
+                    attempt.follow()
+                }
+            }))
+            .build()
+            .expect("Failed to create outbound HTTP client!");
+
+        OutboundClient { client }
+    }
+
+    /// Starts building a request, after checking the URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The HTTP method.
+    /// * `url` - The URL to request.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the request builder on success, or an `OutboundError` if the URL is not allowed.
+    pub fn request(&self, method: Method, url: &str) -> Result<RequestBuilder, OutboundError> {
+        let url = Url::parse(url)?;
+        check_url(&url)?;
+
+        Ok(self.client.request(method, url))
+    }
+
+    /// Starts building a `GET` request, after checking the URL.
+    pub fn get(&self, url: &str) -> Result<RequestBuilder, OutboundError> {
+        self.request(Method::GET, url)
+    }
+
+    /// Starts building a `POST` request, after checking the URL.
+    pub fn post(&self, url: &str) -> Result<RequestBuilder, OutboundError> {
+        self.request(Method::POST, url)
+    }
+}
+
+impl Default for OutboundClient {
+    fn default() -> Self {
+        OutboundClient::new()
+    }
+}
+
+/// Checks the parts of a URL that can be checked without resolving it.
+///
+/// IP address hosts are checked here, as the client only calls the resolver for host names.
+fn check_url(url: &Url) -> Result<(), OutboundError> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(OutboundError::SchemeNotAllowed(url.scheme().to_owned()));
+    }
+
+    let ip = match url.host() {
+        Some(url::Host::Ipv4(ip)) => IpAddr::V4(ip),
+        Some(url::Host::Ipv6(ip)) => IpAddr::V6(ip),
+        _ => return Ok(()),
+    };
+
+    if !is_public(ip) {
+        return Err(OutboundError::AddressNotAllowed(ip));
+    }
+
+    Ok(())
+}
+
+/// Resolves host names, keeping only the public addresses.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_owned();
+            let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|address| is_public(address.ip()))
+                .collect();
+
+            if addresses.is_empty() {
+                return Err(OutboundError::NoPublicAddress(host).into());
+            }
+
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Whether an address is reachable on the public internet, rather than loopback, private,
+/// link-local (which includes cloud metadata endpoints), shared, multicast or reserved.
+///
+/// # Arguments
+///
+/// * `ip` - The address to check.
+///
+/// # Returns
+///
+/// `true` if outbound requests may connect to the address.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        IpAddr::V6(ip) => is_public_v6(ip),
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_broadcast()
+        || ip.is_documentation()
+        || ip.is_multicast()
+        // "This network"
+        || a == 0
+        // Shared address space (carrier-grade NAT)
+        || (a == 100 && (64..128).contains(&b))
+        // IETF protocol assignments
+        || (a == 192 && b == 0 && c == 0)
+        // Benchmarking
+        || (a == 198 && (18..20).contains(&b))
+        // Reserved
+        || a >= 240)
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    // Addresses embedding an IPv4 address are as public as the address they embed
+    if let Some(v4) = ip.to_ipv4_mapped() {
+        return is_public_v4(v4);
+    }
+
+    let segments = ip.segments();
+
+    // NAT64
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        let [a, b] = segments[6].to_be_bytes();
+        let [c, d] = segments[7].to_be_bytes();
+        return is_public_v4(Ipv4Addr::new(a, b, c, d));
+    }
+
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        // IPv4-compatible, deprecated
+        || segments[..6] == [0; 6]
+        // Unique local
+        || (segments[0] & 0xfe00) == 0xfc00
+        // Link-local
+        || (segments[0] & 0xffc0) == 0xfe80
+        // Site-local, deprecated
+        || (segments[0] & 0xffc0) == 0xfec0
+        // Documentation
+        || (segments[0] == 0x2001 && segments[1] == 0x0db8))
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_refuse_internal_addresses() {
+        for ip in [
+            "0.0.0.0", "127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1",
+            "192.0.0.8", "198.18.0.1", "224.0.0.1", "255.255.255.255", "240.0.0.1", "::", "::1", "::ffff:127.0.0.1",
+            "::ffff:10.0.0.1", "::127.0.0.1", "64:ff9b::a9fe:a9fe", "fc00::1", "fd12:3456::1", "fe80::1",
+            "ff02::1", "2001:db8::1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{} should not be public", ip);
+        }
+    }
+
+    #[test]
+    fn should_allow_public_addresses() {
+        for ip in ["1.1.1.1", "8.8.8.8", "172.32.0.1", "100.128.0.1", "2606:4700:4700::1111", "::ffff:8.8.8.8"] {
+            assert!(is_public(ip.parse().unwrap()), "{} should be public", ip);
+        }
+    }
+
+    #[test]
+    fn should_refuse_urls_before_sending() {
+        let client = OutboundClient::new();
+
+        assert!(matches!(client.get("file:///etc/passwd"), Err(OutboundError::SchemeNotAllowed(_))));
+        assert!(matches!(client.get("gopher://example.com"), Err(OutboundError::SchemeNotAllowed(_))));
+        assert!(matches!(client.get("not a url"), Err(OutboundError::InvalidUrl(_))));
+
+        // Alternative notations of internal addresses are normalized by the URL parser
+        for url in ["http://127.0.0.1/", "http://2130706433/", "http://0x7f.1/", "http://[::1]:8080/", "http://[::ffff:a9fe:a9fe]/"] {
+            assert!(matches!(client.get(url), Err(OutboundError::AddressNotAllowed(_))), "{} was allowed", url);
+        }
+
+        assert!(client.get("https://example.com/hook").is_ok());
+    }
+
+    #[tokio::test]
+    async fn should_refuse_host_names_resolving_to_internal_addresses() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let result = OutboundClient::new()
+            .get(&format!("http://localhost:{}/", port))
+            .unwrap()
+            .send()
+            .await;
+
+        // Refused by the resolver, rather than waiting for a response from the listener
+        let err = result.unwrap_err();
+        assert!(format!("{:?}", err).contains("NoPublicAddress"), "{:?}", err);
+    }
+}
@@ -0,0 +1,156 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::{task::JoinHandle, time::Instant};
+
+use crate::{
+    live::Feed,
+    models::{AnswerDetail, AnswerId, QuestionDetail, QuestionId},
+    persistance::outbox_dao::OutboxDao,
+    AppState,
+};
+
+/// Events dispatched at once, the rest waiting for the next round
+const BATCH_SIZE: i64 = 100;
+
+/// Wait between two looks for new events, once the outbox has been drained
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Wait between two deletions of delivered events
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How long delivered events are kept, to look into what was published
+const RETENTION: TimeDelta = TimeDelta::days(1);
+
+/// Writes recorded in the outbox, and published once committed to the live feeds and webhooks
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", content = "data")]
+pub enum ContentEvent {
+    #[serde(rename = "question.created")]
+    QuestionCreated(QuestionDetail),
+    #[serde(rename = "question.deleted")]
+    QuestionDeleted(QuestionId),
+    #[serde(rename = "answer.created")]
+    AnswerCreated(AnswerDetail),
+    #[serde(rename = "answer.deleted")]
+    AnswerDeleted(AnswerId),
+}
+
+impl ContentEvent {
+
+    /// The name of the event, as sent in webhook payloads and the `X-Webhook-Event` header.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContentEvent::QuestionCreated(_) => "question.created",
+            ContentEvent::QuestionDeleted(_) => "question.deleted",
+            ContentEvent::AnswerCreated(_) => "answer.created",
+            ContentEvent::AnswerDeleted(_) => "answer.deleted",
+        }
+    }
+}
+
+/// Feeds the events of the outbox are published to
+#[derive(Clone)]
+struct Publishers {
+    question_feed: Feed<QuestionDetail>,
+    answer_feed: Feed<AnswerDetail>,
+    content_events: Feed<ContentEvent>,
+}
+
+impl Publishers {
+    fn publish(&self, event: ContentEvent) {
+        match &event {
+            ContentEvent::QuestionCreated(question) => self.question_feed.publish(question.clone()),
+            ContentEvent::AnswerCreated(answer) => self.answer_feed.publish(answer.clone()),
+            ContentEvent::QuestionDeleted(_) | ContentEvent::AnswerDeleted(_) => {}
+        }
+
+        self.content_events.publish(event);
+    }
+}
+
+/// Starts dispatching the events recorded in the outbox to the live feeds and the webhooks.
+///
+/// Every instance runs a dispatcher, each event being claimed by one of them only. With `LIVE_FANOUT`, the live
+/// feeds relay it to the clients of the other instances.
+///
+/// # Arguments
+///
+/// * `state` - The application state, whose `OutboxDao` is drained into its feeds.
+///
+/// # Returns
+///
+/// A `JoinHandle` of the task dispatching events, which runs until aborted.
+pub fn start(state: &AppState) -> JoinHandle<()> {
+    let publishers = Publishers {
+        question_feed: state.question_feed.clone(),
+        answer_feed: state.answer_feed.clone(),
+        content_events: state.content_events.clone(),
+    };
+
+    tokio::spawn(dispatch(state.outbox_dao.clone(), publishers))
+}
+
+async fn dispatch(outbox_dao: Arc<dyn OutboxDao + Send + Sync>, publishers: Publishers) {
+    let mut purged_at = Instant::now();
+
+    loop {
+        let publish = |event| publishers.publish(event);
+
+        match outbox_dao.dispatch_events(BATCH_SIZE, &publish).await {
+            // More events may be waiting
+            Ok(dispatched) if dispatched as i64 == BATCH_SIZE => continue,
+            Ok(_) => {}
+            Err(err) => error!("Failed to dispatch the events of the outbox: {:?}", err),
+        }
+
+        if purged_at.elapsed() >= PURGE_INTERVAL {
+            purged_at = Instant::now();
+
+            if let Err(err) = outbox_dao.delete_delivered_events(Utc::now() - RETENTION).await {
+                error!("Failed to delete the delivered events of the outbox: {:?}", err);
+            }
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio::time::timeout;
+
+    use crate::{config::Config, models::Answer, test_support::QuestionBuilder};
+
+    #[tokio::test]
+    async fn should_publish_committed_writes_to_the_feeds() {
+        let state = AppState::in_memory(&Config::default());
+        let mut questions = state.question_feed.subscribe();
+        let mut answers = state.answer_feed.subscribe();
+        let mut events = state.content_events.subscribe();
+
+        let dispatcher = start(&state);
+
+        let question = state.questions_dao.create_question(QuestionBuilder::new().build()).await.unwrap();
+        let answer = state.answers_dao.create_answer(Answer {
+            answer_uuid: None,
+            question_uuid: question.question_uuid,
+            content: "dispatched".to_owned(),
+        }).await.unwrap();
+
+        let wait = Duration::from_secs(5);
+        assert_eq!(timeout(wait, questions.recv()).await.unwrap().unwrap(), question);
+        assert_eq!(timeout(wait, answers.recv()).await.unwrap().unwrap(), answer);
+        assert_eq!(timeout(wait, events.recv()).await.unwrap().unwrap(), ContentEvent::QuestionCreated(question));
+        assert_eq!(timeout(wait, events.recv()).await.unwrap().unwrap(), ContentEvent::AnswerCreated(answer));
+
+        dispatcher.abort();
+    }
+}
@@ -1,7 +1,21 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::{types::Uuid, PgExecutor, PgPool};
+
+use crate::{
+    models::{
+        postgres_error_codes, Answer, AnswerDetail, AnswerId, AnswerSort, AnswerUuid, AnswersByQuestion, DBError,
+        QuestionStatus, QuestionUuid,
+    },
+    outbox::ContentEvent,
+    reading_time,
+};
 
-use crate::models::{postgres_error_codes, Answer, AnswerDetail, DBError};
+use super::{
+    memory::{self, MemoryStore},
+    outbox_dao,
+};
 
 /// A trait representing data access operations for questions in the database.
 #[async_trait]
@@ -15,7 +29,8 @@ pub trait AnswersDao {
     ///
     /// # Returns
     ///
-    /// A `Result` containing the newly created answer detail on success, or a `DBError` on failure.
+    /// A `Result` containing the newly created answer detail on success, or a `DBError` on failure. The error is
+    /// `DBError::InvalidUUID` if the question does not exist, and `DBError::Conflict` if it is not open.
     async fn create_answer(&self, answer: Answer) -> Result<AnswerDetail, DBError>;
 
     /// Asynchronously deletes an answer from the database.
@@ -27,14 +42,74 @@ pub trait AnswersDao {
     /// # Returns
     ///
     /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
-    async fn delete_answer(&self, answer_uuid: String) -> Result<(), DBError>;
+    async fn delete_answer(&self, answer_uuid: AnswerUuid) -> Result<(), DBError>;
 
-    /// Asynchronously retrieves all answers from the database.
+    /// Asynchronously retrieves all answers of a question from the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    /// * `sort` - The order of the answers.
     ///
     /// # Returns
     ///
     /// A `Result` containing a vector of answer details on success, or a `DBError` on failure.
-    async fn get_answers(&self, question_uuid: String) -> Result<Vec<AnswerDetail>, DBError>;
+    async fn get_answers(&self, question_uuid: QuestionUuid, sort: AnswerSort) -> Result<Vec<AnswerDetail>, DBError>;
+
+    /// Asynchronously retrieves the answers of several questions at once, oldest first, sparing a list of
+    /// questions a call per question.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuids` - The unique identifiers of the questions.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the answers keyed by question on success, or a `DBError` on failure. Every question
+    /// is a key, with an empty vector if it has no answers or does not exist.
+    async fn get_answers_for_questions(
+        &self,
+        question_uuids: Vec<QuestionUuid>,
+    ) -> Result<AnswersByQuestion, DBError>;
+}
+
+/// Retrieves all answers for a UUID, through the pool or as part of a transaction.
+///
+/// # Arguments
+///
+/// * `executor` - The pool, or the connection of the transaction.
+/// * `question_uuid` - The unique identifier of the question.
+/// * `sort` - The order of the answers.
+///
+/// # Returns
+///
+/// A `Result` containing a vector of answer details on success, or a `DBError` on failure.
+pub(crate) async fn fetch_answers(
+    executor: impl PgExecutor<'_>,
+    question_uuid: QuestionUuid,
+    sort: AnswerSort,
+) -> Result<Vec<AnswerDetail>, DBError> {
+
+    // Get all answers from DB
+    let records = sqlx::query!(
+        "SELECT * FROM answers WHERE question_uuid = $1 ORDER BY CASE WHEN $2 = 'newest' THEN created_at END DESC, created_at",
+        question_uuid.as_uuid(),
+        sort.as_str()
+    ).fetch_all(executor)
+     .await
+     .map_err(|e| DBError::Other(Box::new(e)))?;
+
+    // Put the records in an array of AnswerDetail
+    let answers = records.into_iter().map(|r| AnswerDetail {
+        answer_uuid: r.answer_uuid.into(),
+        question_uuid: r.question_uuid.into(),
+        content: r.content,
+        created_at: r.created_at.and_utc(),
+        word_count: r.word_count,
+        reading_time_seconds: r.reading_time_seconds,
+    }).collect();
+
+    Ok(answers)
 }
 
 /// Implementation of the `AnswersDao` trait for PostgreSQL database.
@@ -61,26 +136,33 @@ impl AnswersDao for AnswersDaoImpl {
     /// # Returns
     ///
     /// A `Result` containing the newly created answer detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
     async fn create_answer(&self, answer: Answer) -> Result<AnswerDetail, DBError> {
+        let mut tx = self.db.begin().await.map_err(|e| DBError::Other(Box::new(e)))?;
+        let word_count = answer.word_count();
 
-        // Attempt to get question UUID (for the answer), make sure it is valid
-        let uuid = sqlx::types::Uuid::parse_str(&answer.question_uuid).map_err(|_| {
-            DBError::InvalidUUID(format!("Could not parse answer UUID: {}", answer.question_uuid))
-        })?;
-
-        // If executing the query results in an error, check to see if
+        // Only insert if the question is open, locking it so it cannot be closed or deleted
+        // until the answer is committed. If executing the query results in an error, check to see if
         // the error code matches `postgres_error_codes::FOREIGN_KEY_VIOLATION`.
         // If so early return the `DBError::InvalidUUID` error. Otherwise early return
         // the `DBError::Other` error.
+        // A UUID chosen by the client that is already taken, under any question, violates the primary key
+        // of `answer_ids`, which is reported as a conflict. It is claimed by a trigger in this transaction,
+        // so a concurrent insert of the same UUID waits for this one to commit, then fails.
         let record = sqlx::query!(
             r#"
-                INSERT INTO answers ( question_uuid, content )
-                VALUES ( $1, $2 )
+                INSERT INTO answers ( answer_uuid, question_uuid, content, word_count, reading_time_seconds )
+                SELECT COALESCE($3, gen_random_uuid()), question_uuid, $2, $4, $5 FROM questions
+                WHERE question_uuid = $1 AND status = 'open'
+                FOR SHARE
                 RETURNING *
             "#,
-            uuid,
-            answer.content
-        ).fetch_one(&self.db)
+            answer.question_uuid.as_uuid(),
+            answer.content,
+            answer.answer_uuid.map(|uuid| *uuid.as_uuid()),
+            word_count,
+            reading_time::reading_time_seconds(word_count)
+        ).fetch_optional(&mut *tx)
          .await
          .map_err(|e: sqlx::Error| match e {
             sqlx::Error::Database(e) => {
@@ -88,19 +170,42 @@ impl AnswersDao for AnswersDaoImpl {
                     if code.eq(postgres_error_codes::FOREIGN_KEY_VIOLATION) {
                         return DBError::InvalidUUID(format!("Invalid question UUID: {}", answer.question_uuid));
                     }
+                    if let (true, Some(uuid)) = (code.eq(postgres_error_codes::UNIQUE_VIOLATION), answer.answer_uuid) {
+                        return super::already_exists("Answer", uuid);
+                    }
                 }
                 DBError::Other(Box::new(e))
             }
             e => DBError::Other(Box::new(e)),
          })?;
 
-        // Return created record
-        Ok(AnswerDetail {
-            answer_uuid: record.answer_uuid.to_string(),
-            question_uuid: record.question_uuid.to_string(),
+        let Some(record) = record else {
+            // Nothing was inserted, find out why
+            let status = sqlx::query_scalar!(
+                r#"SELECT status AS "status: QuestionStatus" FROM questions WHERE question_uuid = $1"#,
+                answer.question_uuid.as_uuid()
+            ).fetch_optional(&mut *tx)
+             .await
+             .map_err(|e| DBError::Other(Box::new(e)))?;
+
+            return Err(super::unexpected_status(answer.question_uuid, status));
+        };
+
+        let detail = AnswerDetail {
+            answer_uuid: record.answer_uuid.into(),
+            question_uuid: record.question_uuid.into(),
             content: record.content,
-            created_at: record.created_at.to_string(),
-        })
+            created_at: record.created_at.and_utc(),
+            word_count: record.word_count,
+            reading_time_seconds: record.reading_time_seconds,
+        };
+
+        // Published once committed, along with the answer
+        outbox_dao::record_event(&mut tx, &ContentEvent::AnswerCreated(detail.clone())).await?;
+        tx.commit().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        // Return created record
+        Ok(detail)
     }
 
     /// Asynchronously deletes an answer from the database.
@@ -112,17 +217,22 @@ impl AnswersDao for AnswersDaoImpl {
     /// # Returns
     ///
     /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
-    async fn delete_answer(&self, answer_uuid: String) -> Result<(), DBError> {
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn delete_answer(&self, answer_uuid: AnswerUuid) -> Result<(), DBError> {
+
+        let mut tx = self.db.begin().await.map_err(|e| DBError::Other(Box::new(e)))?;
 
-        // Attempt to get the answer UUID, make sure it is valid
-        let uuid = sqlx::types::Uuid::parse_str(&answer_uuid).map_err(|_| {
-            DBError::InvalidUUID(format!("Could not parse answer UUID: {}", answer_uuid))
-        })?;
+        // Delete from DB. Answers are partitioned by question, so this looks the answer up in every partition's index
+        let result = sqlx::query!("DELETE FROM answers WHERE answer_uuid = $1", answer_uuid.as_uuid())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
 
-        // Delete from DB
-        sqlx::query!("DELETE FROM answers WHERE answer_uuid = $1", uuid).execute(&self.db)
-                                                                        .await
-                                                                        .map_err(|e| DBError::Other(Box::new(e)))?;
+        // Deleting an answer that is already gone is not an event
+        if result.rows_affected() > 0 {
+            outbox_dao::record_event(&mut tx, &ContentEvent::AnswerDeleted(AnswerId { answer_uuid })).await?;
+        }
+        tx.commit().await.map_err(|e| DBError::Other(Box::new(e)))?;
 
         Ok(())
     }
@@ -132,26 +242,167 @@ impl AnswersDao for AnswersDaoImpl {
     /// # Returns
     ///
     /// A `Result` containing a vector of answer details on success, or a `DBError` on failure.
-    async fn get_answers(&self, question_uuid: String) -> Result<Vec<AnswerDetail>, DBError> {
-
-        // Attempt to get question UUID (for the answer), make sure it is valid
-        let uuid = sqlx::types::Uuid::parse_str(&question_uuid).map_err(|_| {
-            DBError::InvalidUUID(format!("Could not parse question with UUID: {}", question_uuid))
-        })?;
-
-        // Get all answers from DB
-        let records = sqlx::query!("SELECT * FROM answers WHERE question_uuid = $1", uuid).fetch_all(&self.db)
-                                                                                                       .await
-                                                                                                       .map_err(|e| DBError::Other(Box::new(e)))?;
-
-        // Put the records in an array of AnswerDetail
-        let answers = records.iter().map(|r| AnswerDetail {
-            answer_uuid: r.answer_uuid.to_string(),
-            question_uuid: r.question_uuid.to_string(),
-            content: r.content.clone(),
-            created_at: r.created_at.to_string(),
-        }).collect();
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_answers(&self, question_uuid: QuestionUuid, sort: AnswerSort) -> Result<Vec<AnswerDetail>, DBError> {
+        fetch_answers(&self.db, question_uuid, sort).await
+    }
+
+    /// Asynchronously retrieves the answers of several questions from the database, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuids` - The unique identifiers of the questions.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the answers keyed by question on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_answers_for_questions(
+        &self,
+        question_uuids: Vec<QuestionUuid>,
+    ) -> Result<AnswersByQuestion, DBError> {
+        let uuids: Vec<Uuid> = question_uuids.iter().map(|uuid| *uuid.as_uuid()).collect();
+
+        // A single query for every question, rather than one per question. Joining on the questions, rather than
+        // filtering with `= ANY($1)`, lets each lookup skip the other partitions even once the statement is planned
+        // generically.
+        let records = sqlx::query!(
+            r#"
+                SELECT a.* FROM (SELECT DISTINCT unnest($1::uuid[])) AS u (question_uuid)
+                JOIN answers a ON a.question_uuid = u.question_uuid
+                ORDER BY a.created_at
+            "#,
+            &uuids
+        ).fetch_all(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let answers = records.into_iter().map(|r| AnswerDetail {
+            answer_uuid: r.answer_uuid.into(),
+            question_uuid: r.question_uuid.into(),
+            content: r.content,
+            created_at: r.created_at.and_utc(),
+            word_count: r.word_count,
+            reading_time_seconds: r.reading_time_seconds,
+        });
+
+        Ok(super::group_by_question(&question_uuids, answers))
+    }
+}
+
+/// Implementation of the `AnswersDao` trait keeping answers in memory, for local development
+/// and tests.
+pub struct AnswersDaoInMemory {
+    store: Arc<MemoryStore>,
+}
+
+/// Constructor
+impl AnswersDaoInMemory {
+    pub fn new(store: Arc<MemoryStore>) -> Self {
+        AnswersDaoInMemory { store }
+    }
+}
+
+#[async_trait]
+impl AnswersDao for AnswersDaoInMemory {
+
+    /// Asynchronously creates a new answer in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer` - The answer to be created.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created answer detail on success, or a `DBError` on failure.
+    async fn create_answer(&self, answer: Answer) -> Result<AnswerDetail, DBError> {
+
+        // Hold the questions lock so the question cannot be deleted or closed before the answer is inserted
+        let questions = self.store.questions.read().map_err(memory::poisoned)?;
+
+        match questions.get(&answer.question_uuid).map(|row| row.value.status) {
+            Some(QuestionStatus::Open) => {}
+            status => return Err(super::unexpected_status(answer.question_uuid, status)),
+        }
+
+        let uuid = answer.answer_uuid.unwrap_or_else(AnswerUuid::new_v4);
+        let word_count = answer.word_count();
+
+        let detail = AnswerDetail {
+            answer_uuid: uuid,
+            question_uuid: answer.question_uuid,
+            content: answer.content,
+            created_at: super::now(),
+            word_count,
+            reading_time_seconds: reading_time::reading_time_seconds(word_count),
+        };
+
+        let row = self.store.row(detail.clone());
+        let mut answers = self.store.answers.write().map_err(memory::poisoned)?;
+        let mut outbox = self.store.outbox.write().map_err(memory::poisoned)?;
+
+        if answers.contains_key(&uuid) {
+            return Err(super::already_exists("Answer", uuid));
+        }
+        answers.insert(uuid, row);
+        outbox.push_back(ContentEvent::AnswerCreated(detail.clone()));
+
+        Ok(detail)
+    }
+
+    /// Asynchronously deletes an answer from memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer_uuid` - The unique identifier of the answer to be deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    async fn delete_answer(&self, answer_uuid: AnswerUuid) -> Result<(), DBError> {
+        let mut answers = self.store.answers.write().map_err(memory::poisoned)?;
+        let mut outbox = self.store.outbox.write().map_err(memory::poisoned)?;
+
+        if answers.remove(&answer_uuid).is_some() {
+            outbox.push_back(ContentEvent::AnswerDeleted(AnswerId { answer_uuid }));
+        }
+
+        Ok(())
+    }
+
+    /// Asynchronously retrieves all answers for a UUID from memory.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of answer details on success, or a `DBError` on failure.
+    async fn get_answers(&self, question_uuid: QuestionUuid, sort: AnswerSort) -> Result<Vec<AnswerDetail>, DBError> {
+        let answers = self.store.answers.read().map_err(memory::poisoned)?;
+
+        let mut answers = memory::in_order(answers.values().filter(|row| row.value.question_uuid == question_uuid));
+        if sort == AnswerSort::Newest {
+            answers.reverse();
+        }
 
         Ok(answers)
     }
-}
\ No newline at end of file
+
+    /// Asynchronously retrieves the answers of several questions from memory, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuids` - The unique identifiers of the questions.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the answers keyed by question on success, or a `DBError` on failure.
+    async fn get_answers_for_questions(
+        &self,
+        question_uuids: Vec<QuestionUuid>,
+    ) -> Result<AnswersByQuestion, DBError> {
+        let answers = self.store.answers.read().map_err(memory::poisoned)?;
+
+        let answers = memory::in_order(answers.values().filter(|row| question_uuids.contains(&row.value.question_uuid)));
+
+        Ok(super::group_by_question(&question_uuids, answers))
+    }
+}
@@ -0,0 +1,586 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use moka::future::Cache;
+#[cfg(feature = "redis")]
+use redis::{aio::ConnectionLike, aio::ConnectionManager, RedisError};
+
+use crate::models::{
+    Answer, AnswerDetail, AnswerSort, AnswerUuid, AnswersByQuestion, DBError, Question, QuestionDetail,
+    QuestionFilter, QuestionSearchResult, QuestionSort, QuestionStatus, QuestionUuid, QuestionWithAnswers,
+};
+
+use super::{answers_dao::AnswersDao, questions_dao::QuestionsDao};
+
+// Question lists are cached one per status filter, when filtered on nothing else and in the default order, and all of
+// them are invalidated on any write changing what they list, answers included since questions carry their answer
+// count. Writes are made to the database first, so a list read concurrently may be cached stale, but only until it
+// expires.
+
+/// Prefix of the keys question lists are cached under in Redis
+#[cfg(feature = "redis")]
+const KEY_PREFIX: &str = "qna:questions";
+
+/// Status filters question lists are cached for, every one of which is invalidated on writes
+#[cfg(feature = "redis")]
+const FILTERS: [Option<QuestionStatus>; 4] = [
+    None,
+    Some(QuestionStatus::Open),
+    Some(QuestionStatus::Closed),
+    Some(QuestionStatus::Archived),
+];
+
+/// A trait representing a cache of question lists, keyed by the status they are filtered on.
+///
+/// Caches never fail, a list that cannot be read is a miss and one that cannot be written is left uncached.
+#[async_trait]
+pub trait QuestionsCache {
+
+    /// Asynchronously reads a cached question list.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status the list is filtered on, `None` for the list of every question.
+    ///
+    /// # Returns
+    ///
+    /// The questions, oldest first, if the list is cached and has not expired.
+    async fn get(&self, status: Option<QuestionStatus>) -> Option<Vec<QuestionDetail>>;
+
+    /// Asynchronously caches a question list until it expires.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status the list is filtered on, `None` for the list of every question.
+    /// * `questions` - The questions, oldest first.
+    async fn set(&self, status: Option<QuestionStatus>, questions: &[QuestionDetail]);
+
+    /// Asynchronously removes every cached question list.
+    async fn invalidate(&self);
+}
+
+/// Question lists cached in process memory, expiring after a TTL.
+///
+/// Writes made by other instances do not invalidate it, so it only suits deployments of a single instance.
+#[derive(Clone)]
+pub struct InMemoryQuestionsCache {
+    lists: Cache<Option<QuestionStatus>, Arc<Vec<QuestionDetail>>>,
+}
+
+impl InMemoryQuestionsCache {
+
+    /// Creates an empty cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `ttl` - How long a cached list is served before being read from the database again.
+    /// * `max_questions` - Most questions held across the cached lists, the least used lists being evicted beyond it.
+    ///
+    /// # Returns
+    ///
+    /// An `InMemoryQuestionsCache` holding no list.
+    pub fn new(ttl: Duration, max_questions: u64) -> Self {
+        let lists = Cache::builder()
+            .time_to_live(ttl)
+            // Lists are weighed by their questions, an empty one still taking a slot
+            .weigher(|_, questions: &Arc<Vec<QuestionDetail>>| questions.len().clamp(1, u32::MAX as usize) as u32)
+            .max_capacity(max_questions)
+            .build();
+
+        InMemoryQuestionsCache { lists }
+    }
+}
+
+#[async_trait]
+impl QuestionsCache for InMemoryQuestionsCache {
+
+    async fn get(&self, status: Option<QuestionStatus>) -> Option<Vec<QuestionDetail>> {
+        self.lists.get(&status).await.map(|questions| questions.as_ref().clone())
+    }
+
+    async fn set(&self, status: Option<QuestionStatus>, questions: &[QuestionDetail]) {
+        self.lists.insert(status, Arc::new(questions.to_vec())).await;
+    }
+
+    async fn invalidate(&self) {
+        self.lists.invalidate_all();
+    }
+}
+
+/// Question lists cached in Redis as JSON, expiring after a TTL.
+///
+/// Redis being unreachable is logged and treated as a cache miss, so the API keeps serving from the database.
+#[cfg(feature = "redis")]
+#[derive(Clone)]
+pub struct RedisQuestionsCache<C = ConnectionManager> {
+    redis: C,
+    ttl: Duration,
+}
+
+#[cfg(feature = "redis")]
+impl RedisQuestionsCache {
+
+    /// Connects to the Redis server question lists are cached in.
+    ///
+    /// The connection is re-established in the background if it drops later on.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The `redis://` or `rediss://` URL of the server.
+    /// * `ttl` - How long a cached list is served before being read from the database again.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the cache on success, or the `RedisError` met connecting.
+    pub async fn connect(url: &str, ttl: Duration) -> Result<Self, RedisError> {
+        let client = redis::Client::open(url)?;
+        let redis = ConnectionManager::new(client).await?;
+
+        Ok(RedisQuestionsCache::new(redis, ttl))
+    }
+}
+
+#[cfg(feature = "redis")]
+impl<C> RedisQuestionsCache<C> {
+
+    /// Creates a cache over an established connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `redis` - The connection to Redis, cloned for every command.
+    /// * `ttl` - How long a cached list is served before being read from the database again.
+    ///
+    /// # Returns
+    ///
+    /// A `RedisQuestionsCache` using that connection.
+    pub fn new(redis: C, ttl: Duration) -> Self {
+        RedisQuestionsCache { redis, ttl }
+    }
+}
+
+#[cfg(feature = "redis")]
+#[async_trait]
+impl<C: ConnectionLike + Clone + Send + Sync> QuestionsCache for RedisQuestionsCache<C> {
+
+    async fn get(&self, status: Option<QuestionStatus>) -> Option<Vec<QuestionDetail>> {
+        let cached: Option<String> = match redis::cmd("GET").arg(key(status)).query_async(&mut self.redis.clone()).await {
+            Ok(cached) => cached,
+            Err(err) => {
+                warn!("Failed to read cached questions, reading them from the database: {}", err);
+                return None;
+            }
+        };
+
+        // Lists cached by a version serializing questions differently are read again
+        cached.and_then(|json| serde_json::from_str(&json).ok())
+    }
+
+    async fn set(&self, status: Option<QuestionStatus>, questions: &[QuestionDetail]) {
+        let json = match serde_json::to_string(questions) {
+            Ok(json) => json,
+            Err(err) => {
+                error!("Failed to serialize questions to cache: {:?}", err);
+                return;
+            }
+        };
+
+        let result: Result<(), RedisError> = redis::cmd("SET")
+            .arg(key(status))
+            .arg(json)
+            .arg("EX")
+            .arg(self.ttl.as_secs().max(1))
+            .query_async(&mut self.redis.clone())
+            .await;
+
+        if let Err(err) = result {
+            warn!("Failed to cache questions: {}", err);
+        }
+    }
+
+    async fn invalidate(&self) {
+        let keys: Vec<String> = FILTERS.into_iter().map(key).collect();
+
+        let result: Result<(), RedisError> = redis::cmd("DEL").arg(keys).query_async(&mut self.redis.clone()).await;
+
+        // Nothing more can be done, the lists expire on their own
+        if let Err(err) = result {
+            error!("Failed to invalidate cached questions, they may be stale until they expire: {}", err);
+        }
+    }
+}
+
+/// The Redis key the list of questions with a status, or of every question, is cached under.
+#[cfg(feature = "redis")]
+fn key(status: Option<QuestionStatus>) -> String {
+    match status {
+        Some(status) => format!("{}:{}", KEY_PREFIX, status.as_str()),
+        None => format!("{}:all", KEY_PREFIX),
+    }
+}
+
+/// Implementation of the `QuestionsDao` trait serving question lists from a `QuestionsCache`, and delegating
+/// everything else to the wrapped DAO.
+pub struct CachedQuestionsDao {
+    inner: Arc<dyn QuestionsDao + Send + Sync>,
+    cache: Arc<dyn QuestionsCache + Send + Sync>,
+}
+
+/// Constructor
+impl CachedQuestionsDao {
+    pub fn new(inner: Arc<dyn QuestionsDao + Send + Sync>, cache: Arc<dyn QuestionsCache + Send + Sync>) -> Self {
+        CachedQuestionsDao { inner, cache }
+    }
+}
+
+#[async_trait]
+impl QuestionsDao for CachedQuestionsDao {
+
+    /// Asynchronously creates a new question, then invalidates the cached question lists.
+    async fn create_question(&self, question: Question) -> Result<QuestionDetail, DBError> {
+        let question = self.inner.create_question(question).await?;
+        self.cache.invalidate().await;
+
+        Ok(question)
+    }
+
+    /// Asynchronously deletes a question, then invalidates the cached question lists.
+    async fn delete_question(&self, question_uuid: QuestionUuid) -> Result<(), DBError> {
+        self.inner.delete_question(question_uuid).await?;
+        self.cache.invalidate().await;
+
+        Ok(())
+    }
+
+    /// Asynchronously retrieves the questions from the cache, or from the wrapped DAO and caches them. Questions
+    /// filtered on more than their status, or in another order than the default, are always retrieved from the
+    /// wrapped DAO.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The conditions the questions must meet, and their order.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the questions on success, or a `DBError` on failure.
+    async fn get_questions(&self, filter: &QuestionFilter) -> Result<Vec<QuestionDetail>, DBError> {
+        let cached = filter.sort.unwrap_or_default() == QuestionSort::default()
+            && filter.created_after.is_none()
+            && filter.created_before.is_none()
+            && filter.title_contains.is_none()
+            && filter.answered.is_none();
+        if !cached {
+            return self.inner.get_questions(filter).await;
+        }
+
+        if let Some(questions) = self.cache.get(filter.status).await {
+            return Ok(questions);
+        }
+
+        let questions = self.inner.get_questions(filter).await?;
+        self.cache.set(filter.status, &questions).await;
+
+        Ok(questions)
+    }
+
+    async fn get_questions_by_uuids(&self, question_uuids: Vec<QuestionUuid>) -> Result<Vec<QuestionDetail>, DBError> {
+        self.inner.get_questions_by_uuids(question_uuids).await
+    }
+
+    async fn get_questions_after(
+        &self,
+        after: Option<QuestionUuid>,
+        limit: i64,
+    ) -> Result<Vec<QuestionDetail>, DBError> {
+        self.inner.get_questions_after(after, limit).await
+    }
+
+    async fn get_popular_questions(&self, limit: i64) -> Result<Vec<QuestionDetail>, DBError> {
+        self.inner.get_popular_questions(limit).await
+    }
+
+    async fn get_question_with_answers(&self, question_uuid: QuestionUuid) -> Result<QuestionWithAnswers, DBError> {
+        self.inner.get_question_with_answers(question_uuid).await
+    }
+
+    /// Asynchronously changes the status of a question, then invalidates the cached question lists.
+    async fn update_question_status(
+        &self,
+        question_uuid: QuestionUuid,
+        from: QuestionStatus,
+        to: QuestionStatus,
+    ) -> Result<QuestionDetail, DBError> {
+        let question = self.inner.update_question_status(question_uuid, from, to).await?;
+        self.cache.invalidate().await;
+
+        Ok(question)
+    }
+
+    /// Asynchronously adds views to questions, leaving the cached question lists as they are, so the view counts
+    /// they show may be as old as the lists.
+    async fn add_question_views(&self, views: &HashMap<QuestionUuid, i64>) -> Result<(), DBError> {
+        self.inner.add_question_views(views).await
+    }
+
+    async fn search_questions(&self, query: String, limit: i64) -> Result<Vec<QuestionSearchResult>, DBError> {
+        self.inner.search_questions(query, limit).await
+    }
+
+    async fn current_time(&self) -> Result<DateTime<Utc>, DBError> {
+        self.inner.current_time().await
+    }
+}
+
+/// Implementation of the `AnswersDao` trait delegating to the wrapped DAO, and invalidating the cached question
+/// lists when answers are created or deleted, as they change the answer counts of the questions.
+pub struct CachedAnswersDao {
+    inner: Arc<dyn AnswersDao + Send + Sync>,
+    cache: Arc<dyn QuestionsCache + Send + Sync>,
+}
+
+/// Constructor
+impl CachedAnswersDao {
+    pub fn new(inner: Arc<dyn AnswersDao + Send + Sync>, cache: Arc<dyn QuestionsCache + Send + Sync>) -> Self {
+        CachedAnswersDao { inner, cache }
+    }
+}
+
+#[async_trait]
+impl AnswersDao for CachedAnswersDao {
+
+    /// Asynchronously creates a new answer, then invalidates the cached question lists.
+    async fn create_answer(&self, answer: Answer) -> Result<AnswerDetail, DBError> {
+        let answer = self.inner.create_answer(answer).await?;
+        self.cache.invalidate().await;
+
+        Ok(answer)
+    }
+
+    /// Asynchronously deletes an answer, then invalidates the cached question lists.
+    async fn delete_answer(&self, answer_uuid: AnswerUuid) -> Result<(), DBError> {
+        self.inner.delete_answer(answer_uuid).await?;
+        self.cache.invalidate().await;
+
+        Ok(())
+    }
+
+    async fn get_answers(&self, question_uuid: QuestionUuid, sort: AnswerSort) -> Result<Vec<AnswerDetail>, DBError> {
+        self.inner.get_answers(question_uuid, sort).await
+    }
+
+    async fn get_answers_for_questions(
+        &self,
+        question_uuids: Vec<QuestionUuid>,
+    ) -> Result<AnswersByQuestion, DBError> {
+        self.inner.get_answers_for_questions(question_uuids).await
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{
+        config::Config,
+        persistance::{memory::MemoryStore, questions_dao::QuestionsDaoInMemory},
+        test_support::QuestionBuilder,
+    };
+
+    const TTL: Duration = Duration::from_secs(30);
+
+    fn cached_dao(cache: Arc<dyn QuestionsCache + Send + Sync>) -> (CachedQuestionsDao, Arc<MemoryStore>) {
+        let store = Arc::new(MemoryStore::new());
+        let inner = QuestionsDaoInMemory::new(store.clone());
+
+        (CachedQuestionsDao::new(Arc::new(inner), cache), store)
+    }
+
+    #[tokio::test]
+    async fn in_memory_cache_should_serve_lists_until_invalidated() {
+        let cache = Arc::new(InMemoryQuestionsCache::new(TTL, 100));
+        let (dao, _) = cached_dao(cache.clone());
+
+        assert_eq!(dao.get_questions(&QuestionFilter::default()).await.unwrap(), vec![]);
+        assert_eq!(cache.get(None).await, Some(vec![]));
+        assert_eq!(cache.get(Some(QuestionStatus::Open)).await, None);
+
+        let question = dao.create_question(QuestionBuilder::new().build()).await.unwrap();
+
+        assert_eq!(cache.get(None).await, None);
+        assert_eq!(dao.get_questions(&QuestionFilter::default()).await.unwrap(), vec![question]);
+    }
+
+    #[tokio::test]
+    async fn in_memory_cache_should_expire_lists() {
+        let cache = InMemoryQuestionsCache::new(Duration::from_millis(50), 100);
+        cache.set(None, &[]).await;
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert_eq!(cache.get(None).await, None);
+    }
+
+    #[tokio::test]
+    async fn in_memory_cache_should_hold_at_most_max_questions() {
+        let cache = InMemoryQuestionsCache::new(TTL, 2);
+        let questions = vec![QuestionBuilder::new().build_detail(); 3];
+
+        cache.set(None, &questions).await;
+        cache.lists.run_pending_tasks().await;
+
+        assert_eq!(cache.get(None).await, None);
+    }
+
+    #[tokio::test]
+    async fn create_answer_should_invalidate_cached_lists() {
+        let state = crate::AppState::in_memory(&Config::default());
+        let question = state.questions_dao.create_question(QuestionBuilder::new().build()).await.unwrap();
+        let cache = Arc::new(InMemoryQuestionsCache::new(TTL, 100));
+        cache.set(None, &[]).await;
+
+        let dao = CachedAnswersDao::new(state.answers_dao.clone(), cache.clone());
+
+        dao.create_answer(Answer {
+            answer_uuid: None,
+            question_uuid: question.question_uuid,
+            content: "invalidating".to_owned(),
+        }).await.unwrap();
+
+        assert_eq!(cache.get(None).await, None);
+    }
+
+    #[cfg(feature = "redis")]
+    mod redis_cache {
+        use super::*;
+
+        use redis::Value;
+        use redis_test::{MockCmd, MockRedisConnection};
+
+        fn cached_dao(redis: &MockRedisConnection) -> CachedQuestionsDao {
+            super::cached_dao(Arc::new(RedisQuestionsCache::new(redis.clone(), TTL))).0
+        }
+
+        fn invalidation() -> MockCmd {
+            MockCmd::new(
+                redis::cmd("DEL").arg(vec![
+                    "qna:questions:all",
+                    "qna:questions:open",
+                    "qna:questions:closed",
+                    "qna:questions:archived",
+                ]),
+                Ok(Value::Int(1)),
+            )
+        }
+
+        fn unreachable() -> RedisError {
+            RedisError::from((redis::ErrorKind::IoError, "connection refused"))
+        }
+
+        /// Fails if some of the commands expected by a mocked connection were not sent.
+        async fn assert_all_sent(mut redis: MockRedisConnection) {
+            // With none left, the mock refuses any command without saying which one was expected instead
+            let result: Result<Value, RedisError> = redis::cmd("PING").query_async(&mut redis).await;
+            let err = result.unwrap_err().to_string();
+
+            assert!(!err.contains("expected="), "{}", err);
+        }
+
+        #[test]
+        fn should_key_lists_by_status() {
+            assert_eq!(key(None), "qna:questions:all");
+            assert_eq!(key(Some(QuestionStatus::Closed)), "qna:questions:closed");
+        }
+
+        #[tokio::test]
+        async fn get_questions_should_serve_cached_lists() {
+            let question = QuestionBuilder::new().build_detail();
+            let json = serde_json::to_string(&vec![question.clone()]).unwrap();
+
+            let redis = MockRedisConnection::new(vec![MockCmd::new(
+                redis::cmd("GET").arg("qna:questions:open"),
+                Ok(Value::BulkString(json.into_bytes())),
+            )]);
+
+            let filter = QuestionFilter { status: Some(QuestionStatus::Open), ..QuestionFilter::default() };
+            let questions = cached_dao(&redis).get_questions(&filter).await.unwrap();
+
+            assert_eq!(questions, vec![question]);
+            assert_all_sent(redis).await;
+        }
+
+        #[tokio::test]
+        async fn get_questions_should_cache_lists_read_on_a_miss() {
+            let redis = MockRedisConnection::new(vec![
+                MockCmd::new(redis::cmd("GET").arg("qna:questions:all"), Ok(Value::Nil)),
+                MockCmd::new(
+                    redis::cmd("SET").arg("qna:questions:all").arg("[]").arg("EX").arg(30),
+                    Ok(Value::Okay),
+                ),
+            ]);
+
+            let questions = cached_dao(&redis).get_questions(&QuestionFilter::default()).await.unwrap();
+
+            assert_eq!(questions, vec![]);
+            assert_all_sent(redis).await;
+        }
+
+        #[tokio::test]
+        async fn get_questions_should_read_from_the_database_when_redis_fails() {
+            let redis = MockRedisConnection::new(vec![
+                MockCmd::new(redis::cmd("GET").arg("qna:questions:all"), Err::<Value, _>(unreachable())),
+                MockCmd::new(
+                    redis::cmd("SET").arg("qna:questions:all").arg("[]").arg("EX").arg(30),
+                    Err::<Value, _>(unreachable()),
+                ),
+            ]);
+
+            let questions = cached_dao(&redis).get_questions(&QuestionFilter::default()).await.unwrap();
+
+            assert_eq!(questions, vec![]);
+            assert_all_sent(redis).await;
+        }
+
+        #[tokio::test]
+        async fn create_question_should_invalidate_cached_lists() {
+            let redis = MockRedisConnection::new(vec![invalidation()]);
+
+            cached_dao(&redis).create_question(QuestionBuilder::new().build()).await.unwrap();
+
+            assert_all_sent(redis).await;
+        }
+
+        #[tokio::test]
+        async fn failed_writes_should_not_invalidate_cached_lists() {
+            let redis = MockRedisConnection::new(vec![]);
+
+            let result = cached_dao(&redis)
+                .update_question_status(uuid::Uuid::nil().into(), QuestionStatus::Open, QuestionStatus::Closed)
+                .await;
+
+            assert!(result.is_err());
+            assert_all_sent(redis).await;
+        }
+
+        #[tokio::test]
+        async fn create_answer_should_invalidate_cached_lists() {
+            let state = crate::AppState::in_memory(&Config::default());
+            let question = state.questions_dao.create_question(QuestionBuilder::new().build()).await.unwrap();
+            let redis = MockRedisConnection::new(vec![invalidation()]);
+
+            let cache = Arc::new(RedisQuestionsCache::new(redis.clone(), TTL));
+            let dao = CachedAnswersDao::new(state.answers_dao.clone(), cache);
+
+            dao.create_answer(Answer {
+                answer_uuid: None,
+                question_uuid: question.question_uuid,
+                content: "invalidating".to_owned(),
+            }).await.unwrap();
+
+            assert_all_sent(redis).await;
+        }
+    }
+}
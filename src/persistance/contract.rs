@@ -0,0 +1,1633 @@
+// Contract tests every `QuestionsDao`, `AnswersDao`, `OutboxDao`, `IdempotencyDao`, `JobsDao`, `WebhooksDao`,
+// `StatsDao` and `FlagsDao` implementation must pass, so the backends stay interchangeable. Each contract is a
+// function taking fresh DAOs over an empty database, and `dao_contract_tests!`, `outbox_contract_tests!`,
+// `idempotency_contract_tests!`, `jobs_contract_tests!`, `question_webhooks_contract_tests!`,
+// `stats_contract_tests!` and `flags_contract_tests!` expand to one test per contract for a backend.
+
+use std::{collections::HashMap, sync::Mutex};
+
+use chrono::{Days, TimeDelta, Utc};
+
+use crate::{
+    models::{
+        AnswerCountBucket, AnswerId, AnswerSort, AnswerUuid, AnswersByQuestion, DBError, Flag, FlagReason,
+        FlagResolution, HeldContent, IdempotentRequest, IdempotentResponse, JobDetail, JobKind, JobStatus, JobUuid, QuestionId,
+        QuestionFilter, QuestionSort, QuestionStatus, QuestionUuid, Webhook,
+    },
+    outbox::ContentEvent,
+    test_support::{AnswerBuilder, QuestionBuilder},
+};
+
+use super::{
+    answers_dao::AnswersDao, flags_dao::FlagsDao, idempotency_dao::IdempotencyDao, jobs_dao::JobsDao,
+    outbox_dao::OutboxDao, questions_dao::QuestionsDao, stats_dao::StatsDao, webhooks_dao::WebhooksDao,
+};
+
+type QuestionsDaoRef<'a> = &'a (dyn QuestionsDao + Sync + Send);
+type AnswersDaoRef<'a> = &'a (dyn AnswersDao + Sync + Send);
+type OutboxDaoRef<'a> = &'a (dyn OutboxDao + Sync + Send);
+type IdempotencyDaoRef<'a> = &'a (dyn IdempotencyDao + Sync + Send);
+type JobsDaoRef<'a> = &'a (dyn JobsDao + Sync + Send);
+type WebhooksDaoRef<'a> = &'a (dyn WebhooksDao + Sync + Send);
+type StatsDaoRef<'a> = &'a (dyn StatsDao + Sync + Send);
+type FlagsDaoRef<'a> = &'a (dyn FlagsDao + Sync + Send);
+
+/// Expands to one test per contract, each running against the DAOs returned by a setup block.
+///
+/// ```ignore
+/// dao_contract_tests!(#[sqlx::test] async fn(pool: PgPool) {
+///     (QuestionsDaoImpl::new(pool.clone()), AnswersDaoImpl::new(pool))
+/// });
+/// ```
+macro_rules! dao_contract_tests {
+    (#[$test:meta] async fn $params:tt $daos:block) => {
+        $crate::persistance::contract::dao_contract_tests!(@tests #[$test] $params $daos;
+            create_question_should_be_open_and_unanswered,
+            create_question_should_keep_a_chosen_uuid,
+            get_questions_should_list_oldest_first,
+            get_questions_should_filter_by_status,
+            get_questions_should_sort_newest_or_most_answered_first,
+            get_questions_should_filter_by_creation_time,
+            get_questions_should_filter_by_title,
+            get_questions_should_filter_by_answers,
+            get_questions_by_uuids_should_skip_missing_questions,
+            get_questions_after_should_page_through_every_question,
+            get_popular_questions_should_list_most_viewed_first,
+            delete_question_should_delete_its_answers,
+            delete_should_ignore_missing_uuids,
+            update_question_status_should_fail_with_missing_uuid,
+            update_question_status_should_fail_from_wrong_status,
+            create_answer_should_fail_with_missing_uuid,
+            create_answer_should_fail_on_closed_question,
+            create_answer_should_keep_a_chosen_uuid,
+            create_answer_should_keep_a_chosen_uuid_unique_when_created_at_once,
+            get_answers_should_list_oldest_first,
+            get_answers_should_sort_newest_first,
+            get_answers_for_questions_should_group_by_question,
+            get_question_with_answers_should_count_answers,
+            get_question_with_answers_should_fail_with_missing_uuid,
+            created_content_should_store_its_reading_time,
+            search_questions_should_match_words,
+            current_time_should_be_the_clock_questions_are_stamped_with
+        );
+    };
+    (@tests #[$test:meta] $params:tt $daos:block; $($contract:ident),*) => {
+        $(
+            #[$test]
+            async fn $contract $params -> Result<(), String> {
+                let (questions_dao, answers_dao) = $daos;
+                $crate::persistance::contract::$contract(&questions_dao, &answers_dao).await
+            }
+        )*
+    };
+}
+
+pub(crate) use dao_contract_tests;
+
+/// Expands to one test per outbox contract, each running against the DAOs returned by a setup block, which must
+/// share their database.
+///
+/// ```ignore
+/// outbox_contract_tests!(#[sqlx::test] async fn(pool: PgPool) {
+///     (QuestionsDaoImpl::new(pool.clone()), AnswersDaoImpl::new(pool.clone()), OutboxDaoImpl::new(pool))
+/// });
+/// ```
+macro_rules! outbox_contract_tests {
+    (#[$test:meta] async fn $params:tt $daos:block) => {
+        $crate::persistance::contract::outbox_contract_tests!(@tests #[$test] $params $daos;
+            writes_should_be_dispatched_in_order_once,
+            dispatch_events_should_stop_at_the_limit,
+            failed_writes_should_not_record_events
+        );
+    };
+    (@tests #[$test:meta] $params:tt $daos:block; $($contract:ident),*) => {
+        $(
+            #[$test]
+            async fn $contract $params -> Result<(), String> {
+                let (questions_dao, answers_dao, outbox_dao) = $daos;
+                $crate::persistance::contract::$contract(&questions_dao, &answers_dao, &outbox_dao).await
+            }
+        )*
+    };
+}
+
+pub(crate) use outbox_contract_tests;
+
+/// Expands to one test per idempotency contract, each running against the DAO returned by a setup block.
+///
+/// ```ignore
+/// idempotency_contract_tests!(#[sqlx::test] async fn(pool: PgPool) { IdempotencyDaoImpl::new(pool) });
+/// ```
+macro_rules! idempotency_contract_tests {
+    (#[$test:meta] async fn $params:tt $dao:block) => {
+        $crate::persistance::contract::idempotency_contract_tests!(@tests #[$test] $params $dao;
+            claim_key_should_claim_new_keys_once,
+            completed_keys_should_return_their_response,
+            released_keys_should_be_claimable_again,
+            expired_keys_should_be_claimable_again,
+            keys_should_be_scoped_to_their_route,
+            delete_expired_keys_should_keep_live_keys
+        );
+    };
+    (@tests #[$test:meta] $params:tt $dao:block; $($contract:ident),*) => {
+        $(
+            #[$test]
+            async fn $contract $params -> Result<(), String> {
+                let idempotency_dao = $dao;
+                $crate::persistance::contract::$contract(&idempotency_dao).await
+            }
+        )*
+    };
+}
+
+pub(crate) use idempotency_contract_tests;
+
+/// Expands to one test per jobs contract, each running against the DAO returned by a setup block.
+///
+/// ```ignore
+/// jobs_contract_tests!(#[sqlx::test] async fn(pool: PgPool) { JobsDaoImpl::new(pool) });
+/// ```
+macro_rules! jobs_contract_tests {
+    (#[$test:meta] async fn $params:tt $dao:block) => {
+        $crate::persistance::contract::jobs_contract_tests!(@tests #[$test] $params $dao;
+            create_job_should_be_running_without_progress,
+            create_job_should_fail_while_one_of_its_kind_is_running,
+            update_job_should_record_progress,
+            update_job_should_fail_if_updated_meanwhile,
+            get_running_jobs_should_skip_completed_jobs,
+            get_job_should_fail_with_missing_uuid
+        );
+    };
+    (@tests #[$test:meta] $params:tt $dao:block; $($contract:ident),*) => {
+        $(
+            #[$test]
+            async fn $contract $params -> Result<(), String> {
+                let jobs_dao = $dao;
+                $crate::persistance::contract::$contract(&jobs_dao).await
+            }
+        )*
+    };
+}
+
+pub(crate) use jobs_contract_tests;
+
+/// Expands to one test per question webhooks contract, each running against the DAOs returned by a setup block,
+/// which must share their database.
+///
+/// ```ignore
+/// question_webhooks_contract_tests!(#[sqlx::test] async fn(pool: PgPool) {
+///     (QuestionsDaoImpl::new(pool.clone()), WebhooksDaoImpl::new(pool))
+/// });
+/// ```
+macro_rules! question_webhooks_contract_tests {
+    (#[$test:meta] async fn $params:tt $daos:block) => {
+        $crate::persistance::contract::question_webhooks_contract_tests!(@tests #[$test] $params $daos;
+            create_question_webhook_should_fail_with_missing_uuid,
+            get_question_webhooks_should_skip_expired_and_other_questions,
+            delete_question_should_delete_its_webhooks,
+            delete_expired_question_webhooks_should_keep_live_webhooks
+        );
+    };
+    (@tests #[$test:meta] $params:tt $daos:block; $($contract:ident),*) => {
+        $(
+            #[$test]
+            async fn $contract $params -> Result<(), String> {
+                let (questions_dao, webhooks_dao) = $daos;
+                $crate::persistance::contract::$contract(&questions_dao, &webhooks_dao).await
+            }
+        )*
+    };
+}
+
+pub(crate) use question_webhooks_contract_tests;
+
+/// Expands to one test per stats contract, each running against the DAOs returned by a setup block, which must
+/// share their database.
+///
+/// ```ignore
+/// stats_contract_tests!(#[sqlx::test] async fn(pool: PgPool) {
+///     (QuestionsDaoImpl::new(pool.clone()), AnswersDaoImpl::new(pool.clone()), StatsDaoImpl::new(pool))
+/// });
+/// ```
+macro_rules! stats_contract_tests {
+    (#[$test:meta] async fn $params:tt $daos:block) => {
+        $crate::persistance::contract::stats_contract_tests!(@tests #[$test] $params $daos;
+            get_stats_should_be_empty_without_questions,
+            get_stats_should_count_questions_and_answers
+        );
+    };
+    (@tests #[$test:meta] $params:tt $daos:block; $($contract:ident),*) => {
+        $(
+            #[$test]
+            async fn $contract $params -> Result<(), String> {
+                let (questions_dao, answers_dao, stats_dao) = $daos;
+                $crate::persistance::contract::$contract(&questions_dao, &answers_dao, &stats_dao).await
+            }
+        )*
+    };
+}
+
+pub(crate) use stats_contract_tests;
+
+/// Expands to one test per flags contract, each running against the DAOs returned by a setup block, which must
+/// share their database.
+///
+/// ```ignore
+/// flags_contract_tests!(#[sqlx::test] async fn(pool: PgPool) {
+///     (QuestionsDaoImpl::new(pool.clone()), AnswersDaoImpl::new(pool.clone()), FlagsDaoImpl::new(pool))
+/// });
+/// ```
+macro_rules! flags_contract_tests {
+    (#[$test:meta] async fn $params:tt $daos:block) => {
+        $crate::persistance::contract::flags_contract_tests!(@tests #[$test] $params $daos;
+            flag_should_fail_for_missing_content,
+            flag_answer_should_record_its_question,
+            tag_should_record_a_flag_kept_out_of_the_queue,
+            hold_should_keep_content_on_its_flag_only,
+            resolve_flags_should_resolve_every_open_flag_on_the_content
+        );
+    };
+    (@tests #[$test:meta] $params:tt $daos:block; $($contract:ident),*) => {
+        $(
+            #[$test]
+            async fn $contract $params -> Result<(), String> {
+                let (questions_dao, answers_dao, flags_dao) = $daos;
+                $crate::persistance::contract::$contract(&questions_dao, &answers_dao, &flags_dao).await
+            }
+        )*
+    };
+}
+
+pub(crate) use flags_contract_tests;
+
+/// A UUID no question or answer has
+const MISSING_UUID: &str = "a22abcd2-22ab-2222-a22b-2abc2a2b22cc";
+
+fn expect_invalid_uuid<T: std::fmt::Debug>(result: Result<T, DBError>) -> Result<(), String> {
+    match result {
+        Err(DBError::InvalidUUID(_)) => Ok(()),
+        result => Err(format!("Expected an invalid UUID error but got the following result: {:?}", result)),
+    }
+}
+
+fn expect_conflict<T: std::fmt::Debug>(result: Result<T, DBError>) -> Result<(), String> {
+    match result {
+        Err(DBError::Conflict(_)) => Ok(()),
+        result => Err(format!("Expected a conflict error but got the following result: {:?}", result)),
+    }
+}
+
+pub(crate) async fn create_question_should_be_open_and_unanswered(
+    questions_dao: QuestionsDaoRef<'_>,
+    _: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let created = questions_dao
+        .create_question(QuestionBuilder::new().title("test title").build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    if created.title != "test title"
+        || created.status != QuestionStatus::Open
+        || created.answer_count != 0
+        || created.last_activity_at != created.created_at
+    {
+        return Err(format!("Unexpected created question: {:?}", created));
+    }
+
+    let questions = questions_dao.get_questions(&QuestionFilter::default()).await.map_err(|e| format!("{:?}", e))?;
+
+    if questions != [created] {
+        return Err(format!("Expected only the created question, got {:?}", questions));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn create_question_should_keep_a_chosen_uuid(
+    questions_dao: QuestionsDaoRef<'_>,
+    _: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let question_uuid: QuestionUuid = "c33abcd3-33ab-3333-a33b-3abc3a3b33cc".parse().unwrap();
+
+    let created = questions_dao
+        .create_question(QuestionBuilder::new().question_uuid(question_uuid).build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    if created.question_uuid != question_uuid {
+        return Err(format!("Expected the chosen UUID, got {:?}", created));
+    }
+
+    expect_conflict(questions_dao.create_question(QuestionBuilder::new().question_uuid(question_uuid).build()).await)
+}
+
+pub(crate) async fn get_questions_should_list_oldest_first(
+    questions_dao: QuestionsDaoRef<'_>,
+    _: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    for title in ["first", "second", "third"] {
+        questions_dao
+            .create_question(QuestionBuilder::new().title(title).build())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+    }
+
+    let questions = questions_dao.get_questions(&QuestionFilter::default()).await.map_err(|e| format!("{:?}", e))?;
+    let titles: Vec<_> = questions.iter().map(|q| q.title.as_str()).collect();
+
+    if titles != ["first", "second", "third"] {
+        return Err(format!("Expected questions oldest first, got {:?}", titles));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_questions_should_filter_by_status(
+    questions_dao: QuestionsDaoRef<'_>,
+    _: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let open = questions_dao
+        .create_question(QuestionBuilder::new().title("open").build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let closed = questions_dao
+        .create_question(QuestionBuilder::new().title("closed").build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    let closed = questions_dao
+        .update_question_status(closed.question_uuid, QuestionStatus::Open, QuestionStatus::Closed)
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    let open_questions = questions_dao
+        .get_questions(&QuestionFilter { status: Some(QuestionStatus::Open), ..QuestionFilter::default() })
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let closed_questions = questions_dao
+        .get_questions(&QuestionFilter { status: Some(QuestionStatus::Closed), ..QuestionFilter::default() })
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    if open_questions != [open] || closed_questions != [closed] {
+        return Err(format!("Unexpected filtered questions: {:?} and {:?}", open_questions, closed_questions));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_questions_should_sort_newest_or_most_answered_first(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    for (title, answers) in [("first", 1), ("second", 0), ("third", 2)] {
+        let question = questions_dao
+            .create_question(QuestionBuilder::new().title(title).build())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        for _ in 0..answers {
+            answers_dao
+                .create_answer(AnswerBuilder::new(question.question_uuid).build())
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+        }
+    }
+
+    for (sort, expected) in [
+        (QuestionSort::Newest, ["third", "second", "first"]),
+        (QuestionSort::MostAnswered, ["third", "first", "second"]),
+    ] {
+        let filter = QuestionFilter { sort: Some(sort), ..QuestionFilter::default() };
+        let questions = questions_dao.get_questions(&filter).await.map_err(|e| format!("{:?}", e))?;
+        let titles: Vec<_> = questions.iter().map(|q| q.title.as_str()).collect();
+
+        if titles != expected {
+            return Err(format!("Expected questions {} first, got {:?}", sort.as_str(), titles));
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_questions_should_filter_by_creation_time(
+    questions_dao: QuestionsDaoRef<'_>,
+    _: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let mut questions = Vec::new();
+    for title in ["first", "second", "third"] {
+        // Apart enough for backends keeping times to the millisecond
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+
+        let question = questions_dao
+            .create_question(QuestionBuilder::new().title(title).build())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        questions.push(question);
+    }
+
+    let filter = QuestionFilter {
+        created_after: Some(questions[0].created_at),
+        created_before: Some(questions[2].created_at),
+        ..QuestionFilter::default()
+    };
+    let between = questions_dao.get_questions(&filter).await.map_err(|e| format!("{:?}", e))?;
+
+    let filter = QuestionFilter { created_after: Some(questions[1].created_at), ..QuestionFilter::default() };
+    let after = questions_dao.get_questions(&filter).await.map_err(|e| format!("{:?}", e))?;
+
+    if between != questions[1..2] || after != questions[2..] {
+        return Err(format!("Unexpected questions by creation time: {:?} and {:?}", between, after));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_questions_should_filter_by_title(
+    questions_dao: QuestionsDaoRef<'_>,
+    _: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    for title in ["Borrowing in Rust", "100% test coverage", "100 tests", "Trust issues"] {
+        questions_dao
+            .create_question(QuestionBuilder::new().title(title).build())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+    }
+
+    for (text, expected) in [
+        ("RUST", vec!["Borrowing in Rust", "Trust issues"]),
+        ("100%", vec!["100% test coverage"]),
+        ("in rust", vec!["Borrowing in Rust"]),
+    ] {
+        let filter = QuestionFilter { title_contains: Some(text.to_owned()), ..QuestionFilter::default() };
+        let questions = questions_dao.get_questions(&filter).await.map_err(|e| format!("{:?}", e))?;
+        let titles: Vec<_> = questions.iter().map(|q| q.title.as_str()).collect();
+
+        if titles != expected {
+            return Err(format!("Expected {:?} for titles containing {:?}, got {:?}", expected, text, titles));
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_questions_should_filter_by_answers(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let mut questions = Vec::new();
+    for _ in 0..3 {
+        let question = questions_dao
+            .create_question(QuestionBuilder::new().build())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        questions.push(question.question_uuid);
+    }
+
+    answers_dao
+        .create_answer(AnswerBuilder::new(questions[1]).build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    for (answered, expected) in [(false, vec![questions[0], questions[2]]), (true, vec![questions[1]])] {
+        let filter = QuestionFilter { answered: Some(answered), ..QuestionFilter::default() };
+        let listed = questions_dao.get_questions(&filter).await.map_err(|e| format!("{:?}", e))?;
+        let listed: Vec<_> = listed.iter().map(|q| q.question_uuid).collect();
+
+        if listed != expected {
+            return Err(format!("Expected {:?} for answered={}, got {:?}", expected, answered, listed));
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_questions_after_should_page_through_every_question(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let mut question_uuids = Vec::new();
+    for _ in 0..5 {
+        let question = questions_dao
+            .create_question(QuestionBuilder::new().build())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        question_uuids.push(question.question_uuid);
+    }
+    question_uuids.sort();
+
+    answers_dao
+        .create_answer(AnswerBuilder::new(question_uuids[0]).build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    let mut pages = Vec::new();
+    let mut after = None;
+    loop {
+        let page = questions_dao.get_questions_after(after, 2).await.map_err(|e| format!("{:?}", e))?;
+        let Some(last) = page.last() else { break };
+
+        after = Some(last.question_uuid);
+        pages.push(page);
+    }
+
+    let paged: Vec<_> = pages.iter().flatten().map(|q| q.question_uuid).collect();
+    let sizes: Vec<_> = pages.iter().map(Vec::len).collect();
+    if paged != question_uuids || sizes != [2, 2, 1] {
+        return Err(format!("Expected {:?} in pages of 2, got {:?} in pages of {:?}", question_uuids, paged, sizes));
+    }
+
+    if pages[0][0].answer_count != 1 {
+        return Err(format!("Expected the answer to be counted, got {:?}", pages[0][0]));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_popular_questions_should_list_most_viewed_first(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let mut question_uuids = Vec::new();
+    for _ in 0..3 {
+        let question = questions_dao
+            .create_question(QuestionBuilder::new().build())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        question_uuids.push(question.question_uuid);
+    }
+
+    answers_dao
+        .create_answer(AnswerBuilder::new(question_uuids[1]).build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    // Views add up, and those of questions that do not exist are skipped
+    let views = HashMap::from([(question_uuids[1], 1), (question_uuids[2], 1), (QuestionUuid::new_v4(), 5)]);
+    questions_dao.add_question_views(&views).await.map_err(|e| format!("{:?}", e))?;
+    questions_dao
+        .add_question_views(&HashMap::from([(question_uuids[1], 2)]))
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    let popular = questions_dao.get_popular_questions(2).await.map_err(|e| format!("{:?}", e))?;
+
+    let listed: Vec<_> = popular.iter().map(|q| (q.question_uuid, q.view_count, q.answer_count)).collect();
+    if listed != [(question_uuids[1], 3, 1), (question_uuids[2], 1, 0)] {
+        return Err(format!("Expected the 2nd and 3rd questions with 3 and 1 views, got {:?}", listed));
+    }
+
+    let question = questions_dao
+        .get_question_with_answers(question_uuids[0])
+        .await
+        .map_err(|e| format!("{:?}", e))?
+        .question;
+    if question.view_count != 0 {
+        return Err(format!("Expected no views, got {:?}", question));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_questions_by_uuids_should_skip_missing_questions(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let mut questions = Vec::new();
+    for _ in 0..3 {
+        questions.push(questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?);
+    }
+
+    answers_dao
+        .create_answer(AnswerBuilder::new(questions[2].question_uuid).build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    // Requested newest first, with a question that does not exist
+    let question_uuids = vec![questions[2].question_uuid, MISSING_UUID.parse().unwrap(), questions[0].question_uuid];
+
+    let result = questions_dao.get_questions_by_uuids(question_uuids).await.map_err(|e| format!("{:?}", e))?;
+    let counts: Vec<_> = result.iter().map(|q| (q.question_uuid, q.answer_count)).collect();
+
+    if counts != [(questions[0].question_uuid, 0), (questions[2].question_uuid, 1)] {
+        return Err(format!("Expected the existing questions oldest first, got {:?}", result));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn current_time_should_be_the_clock_questions_are_stamped_with(
+    questions_dao: QuestionsDaoRef<'_>,
+    _: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let before = questions_dao.current_time().await.map_err(|e| format!("{:?}", e))?;
+    let question = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+    let after = questions_dao.current_time().await.map_err(|e| format!("{:?}", e))?;
+
+    if !(before <= question.created_at && question.created_at <= after) {
+        return Err(format!("Expected the question created between {} and {}, got {}", before, after, question.created_at));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn delete_question_should_delete_its_answers(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let deleted = questions_dao
+        .create_question(QuestionBuilder::new().title("deleted").build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let kept = questions_dao
+        .create_question(QuestionBuilder::new().title("kept").build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    for question_uuid in [deleted.question_uuid, kept.question_uuid] {
+        answers_dao.create_answer(AnswerBuilder::new(question_uuid).build()).await.map_err(|e| format!("{:?}", e))?;
+    }
+
+    questions_dao.delete_question(deleted.question_uuid).await.map_err(|e| format!("{:?}", e))?;
+
+    let deleted_answers = answers_dao
+        .get_answers(deleted.question_uuid, AnswerSort::default())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let kept_answers = answers_dao
+        .get_answers(kept.question_uuid, AnswerSort::default())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    if !deleted_answers.is_empty() || kept_answers.len() != 1 {
+        return Err(format!("Unexpected answers left: {:?} and {:?}", deleted_answers, kept_answers));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn delete_should_ignore_missing_uuids(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    // Deleting is idempotent, so a retried delete succeeds
+    questions_dao.delete_question(MISSING_UUID.parse().unwrap()).await.map_err(|e| format!("{:?}", e))?;
+    answers_dao.delete_answer(MISSING_UUID.parse().unwrap()).await.map_err(|e| format!("{:?}", e))?;
+
+    Ok(())
+}
+
+pub(crate) async fn update_question_status_should_fail_with_missing_uuid(
+    questions_dao: QuestionsDaoRef<'_>,
+    _: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let result = questions_dao
+        .update_question_status(MISSING_UUID.parse().unwrap(), QuestionStatus::Open, QuestionStatus::Closed)
+        .await;
+
+    expect_invalid_uuid(result)
+}
+
+pub(crate) async fn update_question_status_should_fail_from_wrong_status(
+    questions_dao: QuestionsDaoRef<'_>,
+    _: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let created = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+
+    let result = questions_dao
+        .update_question_status(created.question_uuid, QuestionStatus::Closed, QuestionStatus::Open)
+        .await;
+
+    expect_conflict(result)
+}
+
+pub(crate) async fn create_answer_should_fail_with_missing_uuid(
+    _: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let result = answers_dao.create_answer(AnswerBuilder::new(MISSING_UUID.parse().unwrap()).build()).await;
+
+    expect_invalid_uuid(result)
+}
+
+pub(crate) async fn create_answer_should_fail_on_closed_question(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let created = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+
+    questions_dao
+        .update_question_status(created.question_uuid, QuestionStatus::Open, QuestionStatus::Closed)
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    expect_conflict(answers_dao.create_answer(AnswerBuilder::new(created.question_uuid).build()).await)
+}
+
+pub(crate) async fn create_answer_should_keep_a_chosen_uuid(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let answer_uuid: AnswerUuid = "d44abcd4-44ab-4444-a44b-4abc4a4b44cc".parse().unwrap();
+    let first = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+    let second = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+
+    let created = answers_dao
+        .create_answer(AnswerBuilder::new(first.question_uuid).answer_uuid(answer_uuid).build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    if created.answer_uuid != answer_uuid {
+        return Err(format!("Expected the chosen UUID, got {:?}", created));
+    }
+
+    // Taken whichever question the answer is to
+    for question in [&first, &second] {
+        let answer = AnswerBuilder::new(question.question_uuid).answer_uuid(answer_uuid).build();
+        expect_conflict(answers_dao.create_answer(answer).await)?;
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn create_answer_should_keep_a_chosen_uuid_unique_when_created_at_once(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let answer_uuid: AnswerUuid = "e55abcd5-55ab-5555-a55b-5abc5a5b55cc".parse().unwrap();
+    let first = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+    let second = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+
+    // Under different questions, so only the uniqueness of the UUID itself can tell them apart
+    let (first_result, second_result) = futures::join!(
+        answers_dao.create_answer(AnswerBuilder::new(first.question_uuid).answer_uuid(answer_uuid).build()),
+        answers_dao.create_answer(AnswerBuilder::new(second.question_uuid).answer_uuid(answer_uuid).build()),
+    );
+
+    match (first_result, second_result) {
+        (Ok(_), conflicting) | (conflicting, Ok(_)) => expect_conflict(conflicting)?,
+        results => return Err(format!("Expected one of the answers to be created but got {:?}", results)),
+    }
+
+    let answers = answers_dao
+        .get_answers_for_questions(vec![first.question_uuid, second.question_uuid])
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let created = answers.values().flatten().filter(|answer| answer.answer_uuid == answer_uuid).count();
+
+    if created != 1 {
+        return Err(format!("Expected a single answer with the chosen UUID but got {:?}", answers));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_answers_should_list_oldest_first(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let created = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+
+    let mut answers = Vec::new();
+    for _ in 0..3 {
+        let answer = answers_dao
+            .create_answer(AnswerBuilder::new(created.question_uuid).build())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        answers.push(answer);
+    }
+
+    let result = answers_dao
+        .get_answers(created.question_uuid, AnswerSort::default())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    if result != answers {
+        return Err(format!("Expected answers oldest first, got {:?}", result));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_answers_should_sort_newest_first(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let created = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+
+    let mut answers = Vec::new();
+    for _ in 0..3 {
+        let answer = answers_dao
+            .create_answer(AnswerBuilder::new(created.question_uuid).build())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        answers.push(answer);
+    }
+    answers.reverse();
+
+    let result = answers_dao
+        .get_answers(created.question_uuid, AnswerSort::Newest)
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    if result != answers {
+        return Err(format!("Expected answers newest first, got {:?}", result));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_answers_for_questions_should_group_by_question(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let answered = questions_dao
+        .create_question(QuestionBuilder::new().title("answered").build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let unanswered = questions_dao
+        .create_question(QuestionBuilder::new().title("unanswered").build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let other = questions_dao
+        .create_question(QuestionBuilder::new().title("other").build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    let mut answers = Vec::new();
+    for question_uuid in [answered.question_uuid, other.question_uuid, answered.question_uuid] {
+        let answer = answers_dao
+            .create_answer(AnswerBuilder::new(question_uuid).build())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        answers.push(answer);
+    }
+
+    let missing: QuestionUuid = MISSING_UUID.parse().unwrap();
+
+    let result = answers_dao
+        .get_answers_for_questions(vec![answered.question_uuid, unanswered.question_uuid, missing])
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    let expected = AnswersByQuestion::from([
+        (answered.question_uuid, vec![answers[0].clone(), answers[2].clone()]),
+        (unanswered.question_uuid, vec![]),
+        (missing, vec![]),
+    ]);
+
+    if result != expected {
+        return Err(format!("Unexpected answers by question: {:?}", result));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_question_with_answers_should_count_answers(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let created = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+
+    let mut answers = Vec::new();
+    for _ in 0..2 {
+        let answer = answers_dao
+            .create_answer(AnswerBuilder::new(created.question_uuid).build())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        answers.push(answer);
+    }
+
+    let result = questions_dao
+        .get_question_with_answers(created.question_uuid)
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    if result.question.answer_count != 2 || result.question.last_activity_at != answers[1].created_at {
+        return Err(format!("Unexpected question: {:?}", result.question));
+    }
+
+    if result.answers != answers {
+        return Err(format!("Unexpected answers: {:?}", result.answers));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_question_with_answers_should_fail_with_missing_uuid(
+    questions_dao: QuestionsDaoRef<'_>,
+    _: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    expect_invalid_uuid(questions_dao.get_question_with_answers(MISSING_UUID.parse().unwrap()).await)
+}
+
+pub(crate) async fn created_content_should_store_its_reading_time(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    // 4 words in the title and 40 in the description, read in 13.2 seconds
+    let question = QuestionBuilder::new().title("How do I skim?").description("word ".repeat(40)).build();
+    let created = questions_dao.create_question(question).await.map_err(|e| format!("{:?}", e))?;
+    let answer = AnswerBuilder::new(created.question_uuid).content("Read  it\ntwice").build();
+    let answer = answers_dao.create_answer(answer).await.map_err(|e| format!("{:?}", e))?;
+
+    if (created.word_count, created.reading_time_seconds) != (44, 14) {
+        return Err(format!("Unexpected question: {:?}", created));
+    }
+    if (answer.word_count, answer.reading_time_seconds) != (3, 1) {
+        return Err(format!("Unexpected answer: {:?}", answer));
+    }
+
+    let result = questions_dao
+        .get_question_with_answers(created.question_uuid)
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    if (result.question.word_count, result.question.reading_time_seconds) != (44, 14) || result.answers != [answer] {
+        return Err(format!("Unexpected question with answers: {:?}", result));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn search_questions_should_match_words(
+    questions_dao: QuestionsDaoRef<'_>,
+    _: AnswersDaoRef<'_>,
+) -> Result<(), String> {
+    let matching = questions_dao
+        .create_question(QuestionBuilder::new().title("How do I configure the tokio runtime?").build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    questions_dao
+        .create_question(QuestionBuilder::new().title("What is a borrow checker?").build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    let results = questions_dao.search_questions("tokio runtime".to_owned(), 10).await.map_err(|e| format!("{:?}", e))?;
+    let uuids: Vec<_> = results.iter().map(|r| r.question.question_uuid).collect();
+
+    if uuids != [matching.question_uuid] {
+        return Err(format!("Expected only the matching question, got {:?}", results));
+    }
+
+    let results = questions_dao.search_questions("python".to_owned(), 10).await.map_err(|e| format!("{:?}", e))?;
+
+    if !results.is_empty() {
+        return Err(format!("Expected no results, got {:?}", results));
+    }
+
+    Ok(())
+}
+
+/// Dispatches events from the outbox, collecting them rather than publishing them.
+async fn dispatch(outbox_dao: OutboxDaoRef<'_>, limit: i64) -> Result<Vec<ContentEvent>, String> {
+    let events = Mutex::new(Vec::new());
+
+    let dispatched = outbox_dao
+        .dispatch_events(limit, &|event| events.lock().unwrap().push(event))
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    let events = events.into_inner().unwrap();
+
+    if dispatched != events.len() {
+        return Err(format!("Reported {} events dispatched but published {:?}", dispatched, events));
+    }
+
+    Ok(events)
+}
+
+pub(crate) async fn writes_should_be_dispatched_in_order_once(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+    outbox_dao: OutboxDaoRef<'_>,
+) -> Result<(), String> {
+    let question = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+    let answer = answers_dao
+        .create_answer(AnswerBuilder::new(question.question_uuid).build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    answers_dao.delete_answer(answer.answer_uuid).await.map_err(|e| format!("{:?}", e))?;
+    questions_dao.delete_question(question.question_uuid).await.map_err(|e| format!("{:?}", e))?;
+
+    let events = dispatch(outbox_dao, 10).await?;
+    let expected = vec![
+        ContentEvent::QuestionCreated(question.clone()),
+        ContentEvent::AnswerCreated(answer.clone()),
+        ContentEvent::AnswerDeleted(AnswerId { answer_uuid: answer.answer_uuid }),
+        ContentEvent::QuestionDeleted(QuestionId { question_uuid: question.question_uuid }),
+    ];
+
+    if events != expected {
+        return Err(format!("Expected the writes in order but got: {:?}", events));
+    }
+
+    let events = dispatch(outbox_dao, 10).await?;
+
+    if !events.is_empty() {
+        return Err(format!("Expected delivered events not to be dispatched again but got: {:?}", events));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn dispatch_events_should_stop_at_the_limit(
+    questions_dao: QuestionsDaoRef<'_>,
+    _: AnswersDaoRef<'_>,
+    outbox_dao: OutboxDaoRef<'_>,
+) -> Result<(), String> {
+    let mut questions = Vec::new();
+    for title in ["first", "second", "third"] {
+        let question = questions_dao
+            .create_question(QuestionBuilder::new().title(title).build())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        questions.push(ContentEvent::QuestionCreated(question));
+    }
+
+    let first = dispatch(outbox_dao, 2).await?;
+    let rest = dispatch(outbox_dao, 2).await?;
+
+    if first != questions[..2] || rest != questions[2..] {
+        return Err(format!("Expected two events then one but got: {:?} then {:?}", first, rest));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn failed_writes_should_not_record_events(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+    outbox_dao: OutboxDaoRef<'_>,
+) -> Result<(), String> {
+    let missing_uuid: QuestionUuid = MISSING_UUID.parse().unwrap();
+
+    expect_invalid_uuid(answers_dao.create_answer(AnswerBuilder::new(missing_uuid).build()).await)?;
+    // Deleting what does not exist succeeds, but changes nothing
+    questions_dao.delete_question(missing_uuid).await.map_err(|e| format!("{:?}", e))?;
+    answers_dao.delete_answer(MISSING_UUID.parse().unwrap()).await.map_err(|e| format!("{:?}", e))?;
+
+    let events = dispatch(outbox_dao, 10).await?;
+
+    if !events.is_empty() {
+        return Err(format!("Expected no events but got: {:?}", events));
+    }
+
+    Ok(())
+}
+
+/// Claims a key on `/question` for a request hashed as `hash`, with keys claimed over an hour ago expired.
+async fn claim(idempotency_dao: IdempotencyDaoRef<'_>, key: &str, hash: &str) -> Result<Option<IdempotentRequest>, String> {
+    idempotency_dao
+        .claim_key("/question", key, hash, Utc::now() - TimeDelta::hours(1))
+        .await
+        .map_err(|e| format!("{:?}", e))
+}
+
+/// The hash and response of a request a key was claimed by, leaving out when, which the database decides.
+fn claimed_by(request: Option<IdempotentRequest>) -> Option<(String, Option<IdempotentResponse>)> {
+    request.map(|request| (request.request_hash, request.response))
+}
+
+fn response() -> IdempotentResponse {
+    IdempotentResponse {
+        status: 200,
+        body: r#"{"title":"test title"}"#.to_owned(),
+    }
+}
+
+pub(crate) async fn claim_key_should_claim_new_keys_once(idempotency_dao: IdempotencyDaoRef<'_>) -> Result<(), String> {
+    let first = claim(idempotency_dao, "key", "hash").await?;
+    let second = claim(idempotency_dao, "key", "other hash").await?;
+
+    if first.is_some() || claimed_by(second.clone()) != Some(("hash".to_owned(), None)) {
+        return Err(format!("Expected the key to be claimed by the first request but got: {:?} then {:?}", first, second));
+    }
+
+    let claimed_at = second.map(|request| request.claimed_at).unwrap_or_default();
+    if (Utc::now() - claimed_at).num_minutes().abs() > 1 {
+        return Err(format!("Expected the key to be claimed just now but got: {}", claimed_at));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn completed_keys_should_return_their_response(
+    idempotency_dao: IdempotencyDaoRef<'_>,
+) -> Result<(), String> {
+    claim(idempotency_dao, "key", "hash").await?;
+    idempotency_dao.complete_key("/question", "key", &response()).await.map_err(|e| format!("{:?}", e))?;
+    // Completed keys are not released, as their request succeeded
+    idempotency_dao.release_key("/question", "key").await.map_err(|e| format!("{:?}", e))?;
+
+    let claimed = claim(idempotency_dao, "key", "hash").await?;
+
+    if claimed_by(claimed.clone()) != Some(("hash".to_owned(), Some(response()))) {
+        return Err(format!("Expected the recorded response but got: {:?}", claimed));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn released_keys_should_be_claimable_again(
+    idempotency_dao: IdempotencyDaoRef<'_>,
+) -> Result<(), String> {
+    claim(idempotency_dao, "key", "hash").await?;
+    idempotency_dao.release_key("/question", "key").await.map_err(|e| format!("{:?}", e))?;
+
+    let claimed = claim(idempotency_dao, "key", "hash").await?;
+
+    if claimed.is_some() {
+        return Err(format!("Expected the released key to be claimed again but got: {:?}", claimed));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn expired_keys_should_be_claimable_again(
+    idempotency_dao: IdempotencyDaoRef<'_>,
+) -> Result<(), String> {
+    claim(idempotency_dao, "key", "hash").await?;
+    idempotency_dao.complete_key("/question", "key", &response()).await.map_err(|e| format!("{:?}", e))?;
+
+    let claimed = idempotency_dao
+        .claim_key("/question", "key", "other hash", Utc::now() + TimeDelta::minutes(1))
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let reclaimed = claim(idempotency_dao, "key", "hash").await?;
+
+    if claimed.is_some() || claimed_by(reclaimed.clone()) != Some(("other hash".to_owned(), None)) {
+        return Err(format!("Expected the expired key to be claimed anew but got: {:?} then {:?}", claimed, reclaimed));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn keys_should_be_scoped_to_their_route(idempotency_dao: IdempotencyDaoRef<'_>) -> Result<(), String> {
+    claim(idempotency_dao, "key", "hash").await?;
+
+    let claimed = idempotency_dao
+        .claim_key("/answer", "key", "hash", Utc::now() - TimeDelta::hours(1))
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    if claimed.is_some() {
+        return Err(format!("Expected the key to be claimed on another route but got: {:?}", claimed));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn delete_expired_keys_should_keep_live_keys(
+    idempotency_dao: IdempotencyDaoRef<'_>,
+) -> Result<(), String> {
+    claim(idempotency_dao, "key", "hash").await?;
+
+    let kept = idempotency_dao.delete_expired_keys(Utc::now() - TimeDelta::hours(1)).await.map_err(|e| format!("{:?}", e))?;
+    let deleted = idempotency_dao.delete_expired_keys(Utc::now() + TimeDelta::minutes(1)).await.map_err(|e| format!("{:?}", e))?;
+
+    if (kept, deleted) != (0, 1) {
+        return Err(format!("Expected the key to be deleted once expired but got {} then {} deleted", kept, deleted));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn create_job_should_be_running_without_progress(jobs_dao: JobsDaoRef<'_>) -> Result<(), String> {
+    let created = jobs_dao.create_job(JobKind::ReindexSearch).await.map_err(|e| format!("{:?}", e))?;
+    let read = jobs_dao.get_job(created.job_uuid).await.map_err(|e| format!("{:?}", e))?;
+
+    if created.kind != JobKind::ReindexSearch
+        || created.status != JobStatus::Running
+        || created.processed != 0
+        || created.cursor.is_some()
+        || created.last_error.is_some()
+        || read != created
+    {
+        return Err(format!("Expected a running job without progress but got {:?} then {:?}", created, read));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn create_job_should_fail_while_one_of_its_kind_is_running(
+    jobs_dao: JobsDaoRef<'_>,
+) -> Result<(), String> {
+    let created = jobs_dao.create_job(JobKind::ReindexSearch).await.map_err(|e| format!("{:?}", e))?;
+    expect_conflict(jobs_dao.create_job(JobKind::ReindexSearch).await)?;
+
+    // Another may start once it completes
+    jobs_dao
+        .update_job(&JobDetail { status: JobStatus::Completed, ..created })
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    jobs_dao.create_job(JobKind::ReindexSearch).await.map_err(|e| format!("{:?}", e))?;
+
+    Ok(())
+}
+
+pub(crate) async fn update_job_should_record_progress(jobs_dao: JobsDaoRef<'_>) -> Result<(), String> {
+    let created = jobs_dao.create_job(JobKind::ReindexSearch).await.map_err(|e| format!("{:?}", e))?;
+    let cursor = QuestionUuid::new_v4();
+    let last_error = Some("timed out".to_owned());
+
+    let failed = jobs_dao
+        .update_job(&JobDetail { processed: 100, cursor: Some(cursor), last_error, ..created })
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let completed = jobs_dao
+        .update_job(&JobDetail { status: JobStatus::Completed, last_error: None, ..failed.clone() })
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let read = jobs_dao.get_job(completed.job_uuid).await.map_err(|e| format!("{:?}", e))?;
+
+    if failed.processed != 100
+        || failed.cursor != Some(cursor)
+        || failed.last_error.as_deref() != Some("timed out")
+        || completed.status != JobStatus::Completed
+        || completed.last_error.is_some()
+        || completed.updated_at < failed.updated_at
+        || read != completed
+    {
+        return Err(format!("Expected the progress of the job but got {:?} then {:?}", failed, read));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn update_job_should_fail_if_updated_meanwhile(jobs_dao: JobsDaoRef<'_>) -> Result<(), String> {
+    let created = jobs_dao.create_job(JobKind::ReindexSearch).await.map_err(|e| format!("{:?}", e))?;
+
+    // Read back, since a backend may store timestamps with less precision than they are created with
+    let stale = JobDetail {
+        updated_at: created.updated_at - TimeDelta::seconds(1),
+        ..jobs_dao.get_job(created.job_uuid).await.map_err(|e| format!("{:?}", e))?
+    };
+    expect_conflict(jobs_dao.update_job(&stale).await)?;
+
+    let missing = JobDetail { job_uuid: JobUuid::new_v4(), ..created };
+    expect_conflict(jobs_dao.update_job(&missing).await)
+}
+
+pub(crate) async fn get_running_jobs_should_skip_completed_jobs(jobs_dao: JobsDaoRef<'_>) -> Result<(), String> {
+    // A single job of a kind runs at a time, so the first completes before the second starts
+    let completed = jobs_dao.create_job(JobKind::ReindexSearch).await.map_err(|e| format!("{:?}", e))?;
+    jobs_dao
+        .update_job(&JobDetail { status: JobStatus::Completed, ..completed })
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let created = jobs_dao.create_job(JobKind::ReindexSearch).await.map_err(|e| format!("{:?}", e))?;
+
+    let running = jobs_dao.get_running_jobs().await.map_err(|e| format!("{:?}", e))?;
+    let running: Vec<JobUuid> = running.into_iter().map(|job| job.job_uuid).collect();
+
+    if running != vec![created.job_uuid] {
+        return Err(format!("Expected only the running job but got: {:?}", running));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_job_should_fail_with_missing_uuid(jobs_dao: JobsDaoRef<'_>) -> Result<(), String> {
+    expect_invalid_uuid(jobs_dao.get_job(JobUuid::new_v4()).await)
+}
+
+fn webhook(url: &str) -> Webhook {
+    Webhook {
+        url: url.to_owned(),
+        secret: "0123456789abcdef".to_owned(),
+    }
+}
+
+pub(crate) async fn create_question_webhook_should_fail_with_missing_uuid(
+    _: QuestionsDaoRef<'_>,
+    webhooks_dao: WebhooksDaoRef<'_>,
+) -> Result<(), String> {
+    let tomorrow = Utc::now() + TimeDelta::days(1);
+    let result = webhooks_dao
+        .create_question_webhook(MISSING_UUID.parse().unwrap(), webhook("https://example.com/"), tomorrow)
+        .await;
+
+    expect_invalid_uuid(result)
+}
+
+pub(crate) async fn get_question_webhooks_should_skip_expired_and_other_questions(
+    questions_dao: QuestionsDaoRef<'_>,
+    webhooks_dao: WebhooksDaoRef<'_>,
+) -> Result<(), String> {
+    let question = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+    let other = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+
+    let mut created = Vec::new();
+    for (question_uuid, url, expires_in) in [
+        (question.question_uuid, "https://example.com/first", TimeDelta::days(1)),
+        (question.question_uuid, "https://example.com/expired", TimeDelta::minutes(-1)),
+        (other.question_uuid, "https://example.com/other", TimeDelta::days(1)),
+        (question.question_uuid, "https://example.com/second", TimeDelta::hours(1)),
+    ] {
+        let webhook = webhooks_dao
+            .create_question_webhook(question_uuid, webhook(url), Utc::now() + expires_in)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        created.push(webhook);
+    }
+
+    let results = webhooks_dao.get_question_webhooks(question.question_uuid).await.map_err(|e| format!("{:?}", e))?;
+
+    if results != vec![created[0].clone(), created[3].clone()] {
+        return Err(format!("Expected the live webhooks of the question oldest first but got: {:?}", results));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn delete_question_should_delete_its_webhooks(
+    questions_dao: QuestionsDaoRef<'_>,
+    webhooks_dao: WebhooksDaoRef<'_>,
+) -> Result<(), String> {
+    let question = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+    let tomorrow = Utc::now() + TimeDelta::days(1);
+
+    webhooks_dao
+        .create_question_webhook(question.question_uuid, webhook("https://example.com/"), tomorrow)
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    questions_dao.delete_question(question.question_uuid).await.map_err(|e| format!("{:?}", e))?;
+
+    let results = webhooks_dao.get_question_webhooks(question.question_uuid).await.map_err(|e| format!("{:?}", e))?;
+
+    if !results.is_empty() {
+        return Err(format!("Expected the webhooks to be deleted with their question but got: {:?}", results));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn delete_expired_question_webhooks_should_keep_live_webhooks(
+    questions_dao: QuestionsDaoRef<'_>,
+    webhooks_dao: WebhooksDaoRef<'_>,
+) -> Result<(), String> {
+    let question = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+    let expires_at = Utc::now() + TimeDelta::hours(1);
+
+    webhooks_dao
+        .create_question_webhook(question.question_uuid, webhook("https://example.com/"), expires_at)
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    let kept = webhooks_dao
+        .delete_expired_question_webhooks(expires_at - TimeDelta::minutes(1))
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let deleted = webhooks_dao.delete_expired_question_webhooks(expires_at).await.map_err(|e| format!("{:?}", e))?;
+
+    if (kept, deleted) != (0, 1) {
+        return Err(format!("Expected the webhook deleted once expired but got {} then {} deleted", kept, deleted));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_stats_should_be_empty_without_questions(
+    _: QuestionsDaoRef<'_>,
+    _: AnswersDaoRef<'_>,
+    stats_dao: StatsDaoRef<'_>,
+) -> Result<(), String> {
+    let today = Utc::now().date_naive();
+    let stats = stats_dao.get_stats(today - Days::new(6)).await.map_err(|e| format!("{:?}", e))?;
+
+    if stats.question_count != 0
+        || stats.answer_count != 0
+        || !stats.answers_per_question.is_empty()
+        || stats.questions_per_day.len() != 7
+        || stats.questions_per_day.iter().any(|daily| daily.question_count != 0)
+    {
+        return Err(format!("Expected 7 days without questions but got: {:?}", stats));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn get_stats_should_count_questions_and_answers(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+    stats_dao: StatsDaoRef<'_>,
+) -> Result<(), String> {
+    let mut questions = Vec::new();
+    for _ in 0..3 {
+        let question = questions_dao.create_question(QuestionBuilder::new().build()).await;
+        questions.push(question.map_err(|e| format!("{:?}", e))?);
+    }
+    for question_uuid in [questions[0].question_uuid, questions[0].question_uuid, questions[1].question_uuid] {
+        answers_dao.create_answer(AnswerBuilder::new(question_uuid).build()).await.map_err(|e| format!("{:?}", e))?;
+    }
+
+    let today = Utc::now().date_naive();
+    let stats = stats_dao.get_stats(today - Days::new(29)).await.map_err(|e| format!("{:?}", e))?;
+
+    let buckets = [(0, 1), (1, 1), (2, 1)]
+        .map(|(answer_count, question_count)| AnswerCountBucket { answer_count, question_count });
+
+    if (stats.question_count, stats.answer_count) != (3, 3) || stats.answers_per_question != buckets {
+        return Err(format!("Expected 3 questions with 0, 1 and 2 answers but got: {:?}", stats));
+    }
+
+    match stats.questions_per_day.split_last() {
+        Some((last, earlier))
+            if earlier.len() == 29
+                && earlier.iter().all(|daily| daily.question_count == 0)
+                && (last.day, last.question_count) == (today, 3) => Ok(()),
+        _ => Err(format!("Expected the 3 questions on the last of 30 days but got: {:?}", stats.questions_per_day)),
+    }
+}
+
+fn flag(reason: FlagReason) -> Flag {
+    Flag { reason, comment: Some("See the guidelines".to_owned()) }
+}
+
+pub(crate) async fn flag_should_fail_for_missing_content(
+    _: QuestionsDaoRef<'_>,
+    _: AnswersDaoRef<'_>,
+    flags_dao: FlagsDaoRef<'_>,
+) -> Result<(), String> {
+    let question = flags_dao.flag_question(MISSING_UUID.parse().unwrap(), flag(FlagReason::Spam)).await;
+    let answer = flags_dao.flag_answer(MISSING_UUID.parse().unwrap(), flag(FlagReason::Spam)).await;
+
+    match (question, answer) {
+        (Err(DBError::InvalidUUID(_)), Err(DBError::InvalidUUID(_))) => {}
+        results => return Err(format!("Expected InvalidUUID errors but got: {:?}", results)),
+    }
+
+    let open = flags_dao.get_open_flags().await.map_err(|e| format!("{:?}", e))?;
+    if !open.is_empty() {
+        return Err(format!("Expected no flags but got: {:?}", open));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn flag_answer_should_record_its_question(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+    flags_dao: FlagsDaoRef<'_>,
+) -> Result<(), String> {
+    let question = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+    let answer = answers_dao
+        .create_answer(AnswerBuilder::new(question.question_uuid).build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    let created = flags_dao.flag_answer(answer.answer_uuid, flag(FlagReason::Offensive)).await.map_err(|e| format!("{:?}", e))?;
+
+    let expected = (question.question_uuid, Some(answer.answer_uuid), FlagReason::Offensive, None);
+    if (created.question_uuid, created.answer_uuid, created.reason, created.resolution) != expected {
+        return Err(format!("Expected an open flag on the answer but got: {:?}", created));
+    }
+
+    let read = flags_dao.get_flag(created.flag_uuid).await.map_err(|e| format!("{:?}", e))?;
+    if read != created {
+        return Err(format!("Expected {:?} but got: {:?}", created, read));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn tag_should_record_a_flag_kept_out_of_the_queue(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+    flags_dao: FlagsDaoRef<'_>,
+) -> Result<(), String> {
+    let question = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+    let answer = answers_dao
+        .create_answer(AnswerBuilder::new(question.question_uuid).build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    let question_tag = flags_dao.tag_question(question.question_uuid, flag(FlagReason::Offensive)).await;
+    let question_tag = question_tag.map_err(|e| format!("{:?}", e))?;
+    let answer_tag = flags_dao.tag_answer(answer.answer_uuid, flag(FlagReason::Spam)).await.map_err(|e| format!("{:?}", e))?;
+    let answer_flag = flags_dao.flag_answer(answer.answer_uuid, flag(FlagReason::Spam)).await.map_err(|e| format!("{:?}", e))?;
+
+    for tag in [&question_tag, &answer_tag] {
+        if tag.resolution != Some(FlagResolution::Tagged) || tag.resolved_at.is_none() {
+            return Err(format!("Expected a tag but got: {:?}", tag));
+        }
+    }
+    if answer_tag.question_uuid != question.question_uuid {
+        return Err(format!("Expected the tag to record the question of the answer but got: {:?}", answer_tag));
+    }
+
+    // Out of the queue, and left as they are when the flags of the content are resolved
+    let open = flags_dao.get_open_flags().await.map_err(|e| format!("{:?}", e))?;
+    if open != [answer_flag.clone()] {
+        return Err(format!("Expected only the open flag in the queue but got: {:?}", open));
+    }
+    flags_dao.resolve_flags(&answer_flag, FlagResolution::Dismissed).await.map_err(|e| format!("{:?}", e))?;
+
+    let flags = flags_dao.get_question_flags(question.question_uuid).await.map_err(|e| format!("{:?}", e))?;
+    let flags: Vec<_> = flags.into_iter().map(|flag| (flag.flag_uuid, flag.resolution)).collect();
+    let expected = [
+        (question_tag.flag_uuid, Some(FlagResolution::Tagged)),
+        (answer_tag.flag_uuid, Some(FlagResolution::Tagged)),
+        (answer_flag.flag_uuid, Some(FlagResolution::Dismissed)),
+    ];
+    if flags != expected {
+        return Err(format!("Expected every flag of the question oldest first but got: {:?}", flags));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn hold_should_keep_content_on_its_flag_only(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+    flags_dao: FlagsDaoRef<'_>,
+) -> Result<(), String> {
+    let question = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+    let held_question = QuestionBuilder::new().title("Held").question_uuid(QuestionUuid::new_v4()).build();
+    let held_answer = AnswerBuilder::new(question.question_uuid).answer_uuid(AnswerUuid::new_v4()).build();
+
+    let question_hold = flags_dao
+        .hold_question(held_question.clone(), flag(FlagReason::Spam))
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    let answer_hold = flags_dao
+        .hold_answer(held_answer.clone(), flag(FlagReason::Offensive))
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    let expected = (held_question.question_uuid.unwrap(), None, None, Some(HeldContent::Question(held_question)));
+    if (question_hold.question_uuid, question_hold.answer_uuid, question_hold.resolution, question_hold.held.clone())
+        != expected
+    {
+        return Err(format!("Expected an open flag holding the question but got: {:?}", question_hold));
+    }
+    let expected = (question.question_uuid, held_answer.answer_uuid, None, Some(HeldContent::Answer(held_answer)));
+    if (answer_hold.question_uuid, answer_hold.answer_uuid, answer_hold.resolution, answer_hold.held.clone()) != expected {
+        return Err(format!("Expected an open flag holding the answer but got: {:?}", answer_hold));
+    }
+
+    // In the queue, but not created
+    let open = flags_dao.get_open_flags().await.map_err(|e| format!("{:?}", e))?;
+    if open != [question_hold, answer_hold] {
+        return Err(format!("Expected both holds in the queue but got: {:?}", open));
+    }
+    let questions = questions_dao.get_questions(&QuestionFilter::default()).await.map_err(|e| format!("{:?}", e))?;
+    let answers = answers_dao
+        .get_answers(question.question_uuid, AnswerSort::Oldest)
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+    if questions.len() != 1 || !answers.is_empty() {
+        return Err(format!("Expected no held content created but got: {:?} and {:?}", questions, answers));
+    }
+
+    let missing = flags_dao
+        .hold_answer(AnswerBuilder::new(MISSING_UUID.parse().unwrap()).build(), flag(FlagReason::Spam))
+        .await;
+    if !matches!(missing, Err(DBError::InvalidUUID(_))) {
+        return Err(format!("Expected an InvalidUUID error but got: {:?}", missing));
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn resolve_flags_should_resolve_every_open_flag_on_the_content(
+    questions_dao: QuestionsDaoRef<'_>,
+    answers_dao: AnswersDaoRef<'_>,
+    flags_dao: FlagsDaoRef<'_>,
+) -> Result<(), String> {
+    let question = questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+    let answer = answers_dao
+        .create_answer(AnswerBuilder::new(question.question_uuid).build())
+        .await
+        .map_err(|e| format!("{:?}", e))?;
+
+    let mut flags = Vec::new();
+    for reason in [FlagReason::Spam, FlagReason::OffTopic] {
+        flags.push(flags_dao.flag_question(question.question_uuid, flag(reason)).await.map_err(|e| format!("{:?}", e))?);
+    }
+    let answer_flag = flags_dao.flag_answer(answer.answer_uuid, flag(FlagReason::Spam)).await.map_err(|e| format!("{:?}", e))?;
+
+    let resolved = flags_dao.resolve_flags(&flags[1], FlagResolution::Removed).await.map_err(|e| format!("{:?}", e))?;
+
+    let resolved_uuids: Vec<_> = resolved.iter().map(|flag| flag.flag_uuid).collect();
+    if resolved_uuids != [flags[0].flag_uuid, flags[1].flag_uuid]
+        || resolved.iter().any(|flag| flag.resolution != Some(FlagResolution::Removed) || flag.resolved_at.is_none())
+    {
+        return Err(format!("Expected both flags on the question removed but got: {:?}", resolved));
+    }
+
+    let open = flags_dao.get_open_flags().await.map_err(|e| format!("{:?}", e))?;
+    if open != [answer_flag] {
+        return Err(format!("Expected only the flag on the answer open but got: {:?}", open));
+    }
+
+    // Resolving again leaves the flags as they were
+    let again = flags_dao.resolve_flags(&flags[0], FlagResolution::Dismissed).await.map_err(|e| format!("{:?}", e))?;
+    let read = flags_dao.get_flag(flags[0].flag_uuid).await.map_err(|e| format!("{:?}", e))?;
+    if !again.is_empty() || read.resolution != Some(FlagResolution::Removed) {
+        return Err(format!("Expected the flags to stay removed but got: {:?} and {:?}", again, read));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,750 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::{
+    types::{Json, Uuid},
+    PgPool,
+};
+
+use crate::models::{
+    Answer, AnswerUuid, DBError, Flag, FlagDetail, FlagReason, FlagResolution, FlagUuid, HeldContent, Question,
+    QuestionUuid,
+};
+
+use super::memory::{self, MemoryStore};
+
+/// A trait representing data access operations for the flags raised on questions and answers in the database.
+///
+/// Flags are resolved per content rather than one by one: a moderator acting on a question or answer resolves every
+/// open flag raised on it at once.
+#[async_trait]
+pub trait FlagsDao {
+
+    /// Asynchronously flags a question.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    /// * `flag` - Why the question is flagged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure. The error is
+    /// `DBError::InvalidUUID` if the question does not exist.
+    async fn flag_question(&self, question_uuid: QuestionUuid, flag: Flag) -> Result<FlagDetail, DBError>;
+
+    /// Asynchronously flags an answer.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer_uuid` - The unique identifier of the answer.
+    /// * `flag` - Why the answer is flagged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure. The error is
+    /// `DBError::InvalidUUID` if the answer does not exist.
+    async fn flag_answer(&self, answer_uuid: AnswerUuid, flag: Flag) -> Result<FlagDetail, DBError>;
+
+    /// Asynchronously tags a question, recording a flag resolved as `tagged` from the start, so it never enters the
+    /// moderation queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    /// * `flag` - Why the question is tagged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure. The error is
+    /// `DBError::InvalidUUID` if the question does not exist.
+    async fn tag_question(&self, question_uuid: QuestionUuid, flag: Flag) -> Result<FlagDetail, DBError>;
+
+    /// Asynchronously tags an answer, recording a flag resolved as `tagged` from the start, so it never enters the
+    /// moderation queue.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer_uuid` - The unique identifier of the answer.
+    /// * `flag` - Why the answer is tagged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure. The error is
+    /// `DBError::InvalidUUID` if the answer does not exist.
+    async fn tag_answer(&self, answer_uuid: AnswerUuid, flag: Flag) -> Result<FlagDetail, DBError>;
+
+    /// Asynchronously holds a new question for moderation, storing it on an open flag instead of creating it, in a
+    /// single write. It is created once a moderator dismisses the flag.
+    ///
+    /// # Arguments
+    ///
+    /// * `question` - The question to hold, given a UUID if it has none yet.
+    /// * `flag` - Why the question is held.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail, holding the question, on success, or a `DBError` on
+    /// failure.
+    async fn hold_question(&self, question: Question, flag: Flag) -> Result<FlagDetail, DBError>;
+
+    /// Asynchronously holds a new answer for moderation, storing it on an open flag instead of creating it, in a
+    /// single write. It is created once a moderator dismisses the flag.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer` - The answer to hold, given a UUID if it has none yet.
+    /// * `flag` - Why the answer is held.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail, holding the answer, on success, or a `DBError` on
+    /// failure. The error is `DBError::InvalidUUID` if the question answered does not exist.
+    async fn hold_answer(&self, answer: Answer, flag: Flag) -> Result<FlagDetail, DBError>;
+
+    /// Asynchronously retrieves a flag.
+    ///
+    /// # Arguments
+    ///
+    /// * `flag_uuid` - The unique identifier of the flag.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the flag detail on success, or a `DBError` on failure. The error is
+    /// `DBError::InvalidUUID` if the flag does not exist.
+    async fn get_flag(&self, flag_uuid: FlagUuid) -> Result<FlagDetail, DBError>;
+
+    /// Asynchronously retrieves the flags that were not resolved yet, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of flag details on success, or a `DBError` on failure.
+    async fn get_open_flags(&self) -> Result<Vec<FlagDetail>, DBError>;
+
+    /// Asynchronously retrieves every flag raised on a question or its answers, whatever their resolution, oldest
+    /// first.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of flag details on success, or a `DBError` on failure. The vector is empty for
+    /// a question never flagged, and flags are kept once their question is deleted.
+    async fn get_question_flags(&self, question_uuid: QuestionUuid) -> Result<Vec<FlagDetail>, DBError>;
+
+    /// Asynchronously resolves every open flag raised on the same question or answer as a flag.
+    ///
+    /// # Arguments
+    ///
+    /// * `flag` - A flag on the content acted on.
+    /// * `resolution` - How the content was acted on.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the flags resolved, oldest first, on success, or a `DBError` on failure. Flags resolved
+    /// before are left as they were, so the vector is empty if every flag on the content was.
+    async fn resolve_flags(&self, flag: &FlagDetail, resolution: FlagResolution) -> Result<Vec<FlagDetail>, DBError>;
+}
+
+/// The error for a flag that does not exist.
+pub(crate) fn unknown_flag(flag_uuid: FlagUuid) -> DBError {
+    DBError::InvalidUUID(format!("Invalid flag UUID: {}", flag_uuid))
+}
+
+/// Reads the reason and resolution of a flag, which are stored as text.
+pub(crate) fn parse_flag_state(
+    reason: &str,
+    resolution: Option<&str>,
+) -> Result<(FlagReason, Option<FlagResolution>), DBError> {
+    let reason = reason.parse().map_err(|e: String| DBError::Other(e.into()))?;
+    let resolution = resolution.map(str::parse).transpose().map_err(|e: String| DBError::Other(e.into()))?;
+
+    Ok((reason, resolution))
+}
+
+/// Gives a question to hold a UUID if it has none, as it is identified by its flag until it is created.
+pub(crate) fn held_question(question: Question) -> (QuestionUuid, HeldContent) {
+    let question_uuid = question.question_uuid.unwrap_or_else(QuestionUuid::new_v4);
+
+    (question_uuid, HeldContent::Question(Question { question_uuid: Some(question_uuid), ..question }))
+}
+
+/// Gives an answer to hold a UUID if it has none, as it is identified by its flag until it is created.
+pub(crate) fn held_answer(answer: Answer) -> (AnswerUuid, HeldContent) {
+    let answer_uuid = answer.answer_uuid.unwrap_or_else(AnswerUuid::new_v4);
+
+    (answer_uuid, HeldContent::Answer(Answer { answer_uuid: Some(answer_uuid), ..answer }))
+}
+
+/// Reads the content held by a flag, which is stored as JSON.
+pub(crate) fn parse_held_content(held: Option<serde_json::Value>) -> Result<Option<HeldContent>, DBError> {
+    held.map(serde_json::from_value).transpose().map_err(|e| DBError::Other(Box::new(e)))
+}
+
+/// A flag as stored in Postgres
+struct FlagRow {
+    flag_uuid: Uuid,
+    question_uuid: Uuid,
+    answer_uuid: Option<Uuid>,
+    reason: String,
+    comment: Option<String>,
+    created_at: NaiveDateTime,
+    resolution: Option<String>,
+    resolved_at: Option<NaiveDateTime>,
+    held_content: Option<serde_json::Value>,
+}
+
+impl TryFrom<FlagRow> for FlagDetail {
+    type Error = DBError;
+
+    fn try_from(r: FlagRow) -> Result<Self, Self::Error> {
+        let (reason, resolution) = parse_flag_state(&r.reason, r.resolution.as_deref())?;
+
+        Ok(FlagDetail {
+            flag_uuid: r.flag_uuid.into(),
+            question_uuid: r.question_uuid.into(),
+            answer_uuid: r.answer_uuid.map(Into::into),
+            reason,
+            comment: r.comment,
+            created_at: r.created_at.and_utc(),
+            resolution,
+            resolved_at: r.resolved_at.map(|t| t.and_utc()),
+            held: parse_held_content(r.held_content)?,
+        })
+    }
+}
+
+/// Implementation of the `FlagsDao` trait for PostgreSQL database.
+pub struct FlagsDaoImpl {
+    db: PgPool,
+}
+
+/// Constructor
+impl FlagsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        FlagsDaoImpl { db }
+    }
+
+    /// Inserts a flag on a question, open or resolved from the start.
+    async fn insert_question_flag(
+        &self,
+        question_uuid: QuestionUuid,
+        flag: Flag,
+        resolution: Option<FlagResolution>,
+    ) -> Result<FlagDetail, DBError> {
+
+        // Only insert if the question exists, as flags do not reference it
+        let record = sqlx::query_as!(
+            FlagRow,
+            r#"
+                INSERT INTO flags ( question_uuid, reason, comment, resolution, resolved_at )
+                SELECT question_uuid, $2, $3, $4, CASE WHEN $4::VARCHAR IS NULL THEN NULL ELSE CURRENT_TIMESTAMP END
+                FROM questions
+                WHERE question_uuid = $1
+                RETURNING *
+            "#,
+            question_uuid.as_uuid(),
+            flag.reason.as_str(),
+            flag.comment,
+            resolution.as_ref().map(FlagResolution::as_str)
+        ).fetch_optional(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        record.ok_or_else(|| super::unknown_question(question_uuid))?.try_into()
+    }
+
+    /// Inserts a flag on an answer, open or resolved from the start.
+    async fn insert_answer_flag(
+        &self,
+        answer_uuid: AnswerUuid,
+        flag: Flag,
+        resolution: Option<FlagResolution>,
+    ) -> Result<FlagDetail, DBError> {
+
+        // Only insert if the answer exists, which tells the question it belongs to
+        let record = sqlx::query_as!(
+            FlagRow,
+            r#"
+                INSERT INTO flags ( question_uuid, answer_uuid, reason, comment, resolution, resolved_at )
+                SELECT question_uuid, answer_uuid, $2, $3, $4,
+                    CASE WHEN $4::VARCHAR IS NULL THEN NULL ELSE CURRENT_TIMESTAMP END
+                FROM answers
+                WHERE answer_uuid = $1
+                RETURNING *
+            "#,
+            answer_uuid.as_uuid(),
+            flag.reason.as_str(),
+            flag.comment,
+            resolution.as_ref().map(FlagResolution::as_str)
+        ).fetch_optional(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        record.ok_or_else(|| super::unknown_answer(answer_uuid))?.try_into()
+    }
+
+    /// Inserts an open flag holding new content.
+    async fn insert_held_flag(
+        &self,
+        question_uuid: QuestionUuid,
+        answer_uuid: Option<AnswerUuid>,
+        flag: Flag,
+        held: HeldContent,
+    ) -> Result<FlagDetail, DBError> {
+
+        // An answer is only held if its question exists, as it could not be created otherwise
+        let record = sqlx::query_as!(
+            FlagRow,
+            r#"
+                INSERT INTO flags ( question_uuid, answer_uuid, reason, comment, held_content )
+                SELECT $1, $2, $3, $4, $5
+                WHERE $2::uuid IS NULL OR EXISTS (SELECT 1 FROM questions WHERE question_uuid = $1)
+                RETURNING *
+            "#,
+            question_uuid.as_uuid(),
+            answer_uuid.map(Uuid::from),
+            flag.reason.as_str(),
+            flag.comment,
+            Json(held) as _
+        ).fetch_optional(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        record.ok_or_else(|| super::unknown_question(question_uuid))?.try_into()
+    }
+}
+
+#[async_trait]
+impl FlagsDao for FlagsDaoImpl {
+
+    /// Asynchronously flags a question in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    /// * `flag` - Why the question is flagged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn flag_question(&self, question_uuid: QuestionUuid, flag: Flag) -> Result<FlagDetail, DBError> {
+        self.insert_question_flag(question_uuid, flag, None).await
+    }
+
+    /// Asynchronously flags an answer in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer_uuid` - The unique identifier of the answer.
+    /// * `flag` - Why the answer is flagged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn flag_answer(&self, answer_uuid: AnswerUuid, flag: Flag) -> Result<FlagDetail, DBError> {
+        self.insert_answer_flag(answer_uuid, flag, None).await
+    }
+
+    /// Asynchronously tags a question in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    /// * `flag` - Why the question is tagged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn tag_question(&self, question_uuid: QuestionUuid, flag: Flag) -> Result<FlagDetail, DBError> {
+        self.insert_question_flag(question_uuid, flag, Some(FlagResolution::Tagged)).await
+    }
+
+    /// Asynchronously tags an answer in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer_uuid` - The unique identifier of the answer.
+    /// * `flag` - Why the answer is tagged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn tag_answer(&self, answer_uuid: AnswerUuid, flag: Flag) -> Result<FlagDetail, DBError> {
+        self.insert_answer_flag(answer_uuid, flag, Some(FlagResolution::Tagged)).await
+    }
+
+    /// Asynchronously holds a new question for moderation in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `question` - The question to hold.
+    /// * `flag` - Why the question is held.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn hold_question(&self, question: Question, flag: Flag) -> Result<FlagDetail, DBError> {
+        let (question_uuid, held) = held_question(question);
+        self.insert_held_flag(question_uuid, None, flag, held).await
+    }
+
+    /// Asynchronously holds a new answer for moderation in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer` - The answer to hold.
+    /// * `flag` - Why the answer is held.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn hold_answer(&self, answer: Answer, flag: Flag) -> Result<FlagDetail, DBError> {
+        let question_uuid = answer.question_uuid;
+        let (answer_uuid, held) = held_answer(answer);
+        self.insert_held_flag(question_uuid, Some(answer_uuid), flag, held).await
+    }
+
+    /// Asynchronously retrieves a flag from the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `flag_uuid` - The unique identifier of the flag.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the flag detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_flag(&self, flag_uuid: FlagUuid) -> Result<FlagDetail, DBError> {
+        let record = sqlx::query_as!(FlagRow, "SELECT * FROM flags WHERE flag_uuid = $1", flag_uuid.as_uuid())
+            .fetch_optional(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        record.ok_or_else(|| unknown_flag(flag_uuid))?.try_into()
+    }
+
+    /// Asynchronously retrieves the open flags from the database, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of flag details on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_open_flags(&self) -> Result<Vec<FlagDetail>, DBError> {
+        let records = sqlx::query_as!(FlagRow, "SELECT * FROM flags WHERE resolved_at IS NULL ORDER BY created_at")
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        records.into_iter().map(FlagDetail::try_from).collect()
+    }
+
+    /// Asynchronously retrieves the flags of a question and its answers from the database, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of flag details on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_question_flags(&self, question_uuid: QuestionUuid) -> Result<Vec<FlagDetail>, DBError> {
+        let records = sqlx::query_as!(
+            FlagRow,
+            "SELECT * FROM flags WHERE question_uuid = $1 ORDER BY created_at",
+            question_uuid.as_uuid()
+        ).fetch_all(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        records.into_iter().map(FlagDetail::try_from).collect()
+    }
+
+    /// Asynchronously resolves every open flag raised on the same question or answer as a flag in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `flag` - A flag on the content acted on.
+    /// * `resolution` - How the content was acted on.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the flags resolved, oldest first, on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn resolve_flags(&self, flag: &FlagDetail, resolution: FlagResolution) -> Result<Vec<FlagDetail>, DBError> {
+        let records = sqlx::query_as!(
+            FlagRow,
+            r#"
+                UPDATE flags SET resolution = $3, resolved_at = CURRENT_TIMESTAMP
+                WHERE question_uuid = $1 AND answer_uuid IS NOT DISTINCT FROM $2 AND resolved_at IS NULL
+                RETURNING *
+            "#,
+            flag.question_uuid.as_uuid(),
+            flag.answer_uuid.map(Uuid::from),
+            resolution.as_str()
+        ).fetch_all(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        // `RETURNING` does not keep any order
+        let mut flags = records.into_iter().map(FlagDetail::try_from).collect::<Result<Vec<_>, _>>()?;
+        flags.sort_by_key(|flag| flag.created_at);
+
+        Ok(flags)
+    }
+}
+
+/// Implementation of the `FlagsDao` trait keeping flags in memory, for local development and tests.
+pub struct FlagsDaoInMemory {
+    store: Arc<MemoryStore>,
+}
+
+/// Constructor
+impl FlagsDaoInMemory {
+    pub fn new(store: Arc<MemoryStore>) -> Self {
+        FlagsDaoInMemory { store }
+    }
+
+    /// Stores a new flag, open or resolved from the start, holding new content or not.
+    fn insert(
+        &self,
+        question_uuid: QuestionUuid,
+        answer_uuid: Option<AnswerUuid>,
+        flag: Flag,
+        resolution: Option<FlagResolution>,
+        held: Option<HeldContent>,
+    ) -> Result<FlagDetail, DBError> {
+        let created_at = super::now();
+
+        let detail = FlagDetail {
+            flag_uuid: FlagUuid::new_v4(),
+            question_uuid,
+            answer_uuid,
+            reason: flag.reason,
+            comment: flag.comment,
+            created_at,
+            resolution,
+            resolved_at: resolution.map(|_| created_at),
+            held,
+        };
+
+        let row = self.store.row(detail.clone());
+        self.store.flags.write().map_err(memory::poisoned)?.insert(detail.flag_uuid, row);
+
+        Ok(detail)
+    }
+
+    /// Stores a new flag on a question, if it exists.
+    fn insert_question_flag(
+        &self,
+        question_uuid: QuestionUuid,
+        flag: Flag,
+        resolution: Option<FlagResolution>,
+    ) -> Result<FlagDetail, DBError> {
+        // Held while inserting, so the question cannot be deleted meanwhile
+        let questions = self.store.questions.read().map_err(memory::poisoned)?;
+
+        if !questions.contains_key(&question_uuid) {
+            return Err(super::unknown_question(question_uuid));
+        }
+
+        self.insert(question_uuid, None, flag, resolution, None)
+    }
+
+    /// Stores a new flag on an answer, if it exists.
+    fn insert_answer_flag(
+        &self,
+        answer_uuid: AnswerUuid,
+        flag: Flag,
+        resolution: Option<FlagResolution>,
+    ) -> Result<FlagDetail, DBError> {
+        let answers = self.store.answers.read().map_err(memory::poisoned)?;
+
+        let question_uuid = answers
+            .get(&answer_uuid)
+            .map(|row| row.value.question_uuid)
+            .ok_or_else(|| super::unknown_answer(answer_uuid))?;
+
+        self.insert(question_uuid, Some(answer_uuid), flag, resolution, None)
+    }
+}
+
+#[async_trait]
+impl FlagsDao for FlagsDaoInMemory {
+
+    /// Asynchronously flags a question in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    /// * `flag` - Why the question is flagged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    async fn flag_question(&self, question_uuid: QuestionUuid, flag: Flag) -> Result<FlagDetail, DBError> {
+        self.insert_question_flag(question_uuid, flag, None)
+    }
+
+    /// Asynchronously flags an answer in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer_uuid` - The unique identifier of the answer.
+    /// * `flag` - Why the answer is flagged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    async fn flag_answer(&self, answer_uuid: AnswerUuid, flag: Flag) -> Result<FlagDetail, DBError> {
+        self.insert_answer_flag(answer_uuid, flag, None)
+    }
+
+    /// Asynchronously tags a question in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    /// * `flag` - Why the question is tagged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    async fn tag_question(&self, question_uuid: QuestionUuid, flag: Flag) -> Result<FlagDetail, DBError> {
+        self.insert_question_flag(question_uuid, flag, Some(FlagResolution::Tagged))
+    }
+
+    /// Asynchronously tags an answer in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer_uuid` - The unique identifier of the answer.
+    /// * `flag` - Why the answer is tagged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    async fn tag_answer(&self, answer_uuid: AnswerUuid, flag: Flag) -> Result<FlagDetail, DBError> {
+        self.insert_answer_flag(answer_uuid, flag, Some(FlagResolution::Tagged))
+    }
+
+    /// Asynchronously holds a new question for moderation in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `question` - The question to hold.
+    /// * `flag` - Why the question is held.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    async fn hold_question(&self, question: Question, flag: Flag) -> Result<FlagDetail, DBError> {
+        let (question_uuid, held) = held_question(question);
+        self.insert(question_uuid, None, flag, None, Some(held))
+    }
+
+    /// Asynchronously holds a new answer for moderation in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer` - The answer to hold.
+    /// * `flag` - Why the answer is held.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    async fn hold_answer(&self, answer: Answer, flag: Flag) -> Result<FlagDetail, DBError> {
+        // Held while inserting, so the question cannot be deleted meanwhile
+        let questions = self.store.questions.read().map_err(memory::poisoned)?;
+
+        let question_uuid = answer.question_uuid;
+        if !questions.contains_key(&question_uuid) {
+            return Err(super::unknown_question(question_uuid));
+        }
+
+        let (answer_uuid, held) = held_answer(answer);
+        self.insert(question_uuid, Some(answer_uuid), flag, None, Some(held))
+    }
+
+    /// Asynchronously retrieves a flag from memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `flag_uuid` - The unique identifier of the flag.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the flag detail on success, or a `DBError` on failure.
+    async fn get_flag(&self, flag_uuid: FlagUuid) -> Result<FlagDetail, DBError> {
+        let flags = self.store.flags.read().map_err(memory::poisoned)?;
+
+        flags.get(&flag_uuid).map(|row| row.value.clone()).ok_or_else(|| unknown_flag(flag_uuid))
+    }
+
+    /// Asynchronously retrieves the open flags from memory, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of flag details on success, or a `DBError` on failure.
+    async fn get_open_flags(&self) -> Result<Vec<FlagDetail>, DBError> {
+        let flags = self.store.flags.read().map_err(memory::poisoned)?;
+
+        Ok(memory::in_order(flags.values().filter(|row| row.value.resolution.is_none())))
+    }
+
+    /// Asynchronously retrieves the flags of a question and its answers from memory, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of flag details on success, or a `DBError` on failure.
+    async fn get_question_flags(&self, question_uuid: QuestionUuid) -> Result<Vec<FlagDetail>, DBError> {
+        let flags = self.store.flags.read().map_err(memory::poisoned)?;
+
+        Ok(memory::in_order(flags.values().filter(|row| row.value.question_uuid == question_uuid)))
+    }
+
+    /// Asynchronously resolves every open flag raised on the same question or answer as a flag in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `flag` - A flag on the content acted on.
+    /// * `resolution` - How the content was acted on.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the flags resolved, oldest first, on success, or a `DBError` on failure.
+    async fn resolve_flags(&self, flag: &FlagDetail, resolution: FlagResolution) -> Result<Vec<FlagDetail>, DBError> {
+        let mut flags = self.store.flags.write().map_err(memory::poisoned)?;
+        let resolved_at = super::now();
+
+        let mut resolved = Vec::new();
+        for row in flags.values_mut() {
+            let open = row.value.resolution.is_none()
+                && row.value.question_uuid == flag.question_uuid
+                && row.value.answer_uuid == flag.answer_uuid;
+
+            if open {
+                row.value.resolution = Some(resolution);
+                row.value.resolved_at = Some(resolved_at);
+                resolved.push((row.sequence, row.value.clone()));
+            }
+        }
+
+        resolved.sort_by_key(|(sequence, _)| *sequence);
+        Ok(resolved.into_iter().map(|(_, flag)| flag).collect())
+    }
+}
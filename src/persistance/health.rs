@@ -0,0 +1,36 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::health::HealthCheck;
+
+/// Implementation of the `HealthCheck` trait for PostgreSQL database.
+pub struct PostgresHealthCheck {
+    db: PgPool,
+}
+
+/// Constructor
+impl PostgresHealthCheck {
+    pub fn new(db: PgPool) -> Self {
+        PostgresHealthCheck { db }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for PostgresHealthCheck {
+    fn name(&self) -> &'static str {
+        "database"
+    }
+
+    /// Asynchronously runs a trivial query to make sure a connection can be acquired and used.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned if the database answered, otherwise, the error is returned.
+    async fn check(&self) -> Result<(), String> {
+        sqlx::query("SELECT 1")
+            .execute(&self.db)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
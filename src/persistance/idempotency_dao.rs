@@ -0,0 +1,344 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::models::{DBError, IdempotentRequest, IdempotentResponse};
+
+use super::memory::{self, MemoryStore};
+
+/// A trait representing data access operations for idempotency keys, which record the response to a request so
+/// that retries of it get the same response rather than making the same write twice.
+///
+/// Keys are scoped to a route, so the same key sent to two routes names two requests.
+#[async_trait]
+pub trait IdempotencyDao {
+
+    /// Asynchronously claims an idempotency key for a request about to be served, unless the key is already taken.
+    ///
+    /// Keys claimed before `expired_before` are expired, and claimed again as if they were new.
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - The route the request was made to.
+    /// * `key` - The idempotency key sent with the request.
+    /// * `request_hash` - The hash of the request body.
+    /// * `expired_before` - Keys claimed before this time are expired.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `None` if the key was claimed, or the request the key was already claimed by, on
+    /// success, or a `DBError` on failure.
+    async fn claim_key(
+        &self,
+        route: &str,
+        key: &str,
+        request_hash: &str,
+        expired_before: DateTime<Utc>,
+    ) -> Result<Option<IdempotentRequest>, DBError>;
+
+    /// Asynchronously records the response to the request that claimed an idempotency key.
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - The route the request was made to.
+    /// * `key` - The idempotency key sent with the request.
+    /// * `response` - The response to replay to retries of the request.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    async fn complete_key(&self, route: &str, key: &str, response: &IdempotentResponse) -> Result<(), DBError>;
+
+    /// Asynchronously releases an idempotency key whose request failed, so that it can be retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - The route the request was made to.
+    /// * `key` - The idempotency key sent with the request.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    async fn release_key(&self, route: &str, key: &str) -> Result<(), DBError>;
+
+    /// Asynchronously deletes the idempotency keys claimed before a given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - Keys claimed before this time are deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of keys deleted on success, or a `DBError` on failure.
+    async fn delete_expired_keys(&self, before: DateTime<Utc>) -> Result<u64, DBError>;
+}
+
+/// Builds the request recorded under an idempotency key from the columns it is stored in.
+pub(crate) fn idempotent_request(
+    request_hash: String,
+    status: Option<i64>,
+    body: Option<String>,
+    claimed_at: DateTime<Utc>,
+) -> IdempotentRequest {
+    let response = status.zip(body).map(|(status, body)| IdempotentResponse {
+        status: status as u16,
+        body,
+    });
+
+    IdempotentRequest { request_hash, response, claimed_at }
+}
+
+/// Implementation of the `IdempotencyDao` trait for PostgreSQL database.
+pub struct IdempotencyDaoImpl {
+    db: PgPool,
+}
+
+/// Constructor
+impl IdempotencyDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        IdempotencyDaoImpl { db }
+    }
+}
+
+#[async_trait]
+impl IdempotencyDao for IdempotencyDaoImpl {
+
+    /// Asynchronously claims an idempotency key for a request about to be served, unless the key is already taken.
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - The route the request was made to.
+    /// * `key` - The idempotency key sent with the request.
+    /// * `request_hash` - The hash of the request body.
+    /// * `expired_before` - Keys claimed before this time are expired.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `None` if the key was claimed, or the request the key was already claimed by, on
+    /// success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn claim_key(
+        &self,
+        route: &str,
+        key: &str,
+        request_hash: &str,
+        expired_before: DateTime<Utc>,
+    ) -> Result<Option<IdempotentRequest>, DBError> {
+        // The key may be released between failing to claim it and reading who claimed it, in which case it is tried again
+        loop {
+            sqlx::query!(
+                "DELETE FROM idempotency_keys WHERE route = $1 AND idempotency_key = $2 AND created_at < $3",
+                route,
+                key,
+                expired_before.naive_utc()
+            ).execute(&self.db).await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+            // Of concurrent requests with the same key, only one inserts it
+            let result = sqlx::query!(
+                r#"
+                    INSERT INTO idempotency_keys ( route, idempotency_key, request_hash )
+                    VALUES ( $1, $2, $3 )
+                    ON CONFLICT DO NOTHING
+                "#,
+                route,
+                key,
+                request_hash
+            ).execute(&self.db).await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+            if result.rows_affected() == 1 {
+                return Ok(None);
+            }
+
+            let record = sqlx::query!(
+                "SELECT request_hash, status, body, created_at FROM idempotency_keys WHERE route = $1 AND idempotency_key = $2",
+                route,
+                key
+            ).fetch_optional(&self.db).await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+            if let Some(r) = record {
+                let request = idempotent_request(r.request_hash, r.status.map(i64::from), r.body, r.created_at.and_utc());
+                return Ok(Some(request));
+            }
+        }
+    }
+
+    /// Asynchronously records the response to the request that claimed an idempotency key.
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - The route the request was made to.
+    /// * `key` - The idempotency key sent with the request.
+    /// * `response` - The response to replay to retries of the request.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn complete_key(&self, route: &str, key: &str, response: &IdempotentResponse) -> Result<(), DBError> {
+        sqlx::query!(
+            "UPDATE idempotency_keys SET status = $3, body = $4 WHERE route = $1 AND idempotency_key = $2",
+            route,
+            key,
+            response.status as i16,
+            response.body
+        ).execute(&self.db).await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    /// Asynchronously releases an idempotency key whose request failed, so that it can be retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - The route the request was made to.
+    /// * `key` - The idempotency key sent with the request.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn release_key(&self, route: &str, key: &str) -> Result<(), DBError> {
+        sqlx::query!(
+            "DELETE FROM idempotency_keys WHERE route = $1 AND idempotency_key = $2 AND status IS NULL",
+            route,
+            key
+        ).execute(&self.db).await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    /// Asynchronously deletes the idempotency keys claimed before a given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - Keys claimed before this time are deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of keys deleted on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn delete_expired_keys(&self, before: DateTime<Utc>) -> Result<u64, DBError> {
+        let result = sqlx::query!("DELETE FROM idempotency_keys WHERE created_at < $1", before.naive_utc())
+            .execute(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Implementation of the `IdempotencyDao` trait for idempotency keys kept in memory, for local
+/// development and tests.
+pub struct IdempotencyDaoInMemory {
+    store: Arc<MemoryStore>,
+}
+
+/// Constructor
+impl IdempotencyDaoInMemory {
+    pub fn new(store: Arc<MemoryStore>) -> Self {
+        IdempotencyDaoInMemory { store }
+    }
+}
+
+#[async_trait]
+impl IdempotencyDao for IdempotencyDaoInMemory {
+
+    /// Asynchronously claims an idempotency key kept in memory, unless the key is already taken.
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - The route the request was made to.
+    /// * `key` - The idempotency key sent with the request.
+    /// * `request_hash` - The hash of the request body.
+    /// * `expired_before` - Keys claimed before this time are expired.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `None` if the key was claimed, or the request the key was already claimed by, on
+    /// success, or a `DBError` on failure.
+    async fn claim_key(
+        &self,
+        route: &str,
+        key: &str,
+        request_hash: &str,
+        expired_before: DateTime<Utc>,
+    ) -> Result<Option<IdempotentRequest>, DBError> {
+        let mut keys = self.store.idempotency_keys.write().map_err(memory::poisoned)?;
+
+        let id = (route.to_owned(), key.to_owned());
+        if let Some(request) = keys.get(&id).filter(|request| request.claimed_at >= expired_before) {
+            return Ok(Some(request.clone()));
+        }
+
+        let request = IdempotentRequest {
+            request_hash: request_hash.to_owned(),
+            response: None,
+            claimed_at: super::now(),
+        };
+        keys.insert(id, request);
+
+        Ok(None)
+    }
+
+    /// Asynchronously records the response to the request that claimed an idempotency key kept in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - The route the request was made to.
+    /// * `key` - The idempotency key sent with the request.
+    /// * `response` - The response to replay to retries of the request.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    async fn complete_key(&self, route: &str, key: &str, response: &IdempotentResponse) -> Result<(), DBError> {
+        let mut keys = self.store.idempotency_keys.write().map_err(memory::poisoned)?;
+
+        if let Some(request) = keys.get_mut(&(route.to_owned(), key.to_owned())) {
+            request.response = Some(response.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Asynchronously releases an idempotency key kept in memory whose request failed, so that it can be retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - The route the request was made to.
+    /// * `key` - The idempotency key sent with the request.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    async fn release_key(&self, route: &str, key: &str) -> Result<(), DBError> {
+        let mut keys = self.store.idempotency_keys.write().map_err(memory::poisoned)?;
+
+        let id = (route.to_owned(), key.to_owned());
+        if keys.get(&id).is_some_and(|request| request.response.is_none()) {
+            keys.remove(&id);
+        }
+
+        Ok(())
+    }
+
+    /// Asynchronously deletes the idempotency keys kept in memory claimed before a given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - Keys claimed before this time are deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of keys deleted on success, or a `DBError` on failure.
+    async fn delete_expired_keys(&self, before: DateTime<Utc>) -> Result<u64, DBError> {
+        let mut keys = self.store.idempotency_keys.write().map_err(memory::poisoned)?;
+
+        let count = keys.len();
+        keys.retain(|_, request| request.claimed_at >= before);
+
+        Ok((count - keys.len()) as u64)
+    }
+}
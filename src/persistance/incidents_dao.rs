@@ -0,0 +1,217 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{DBError, Incident, IncidentDetail};
+
+use super::memory::{self, MemoryStore};
+
+/// A trait representing data access operations for status page incidents in the database.
+#[async_trait]
+pub trait IncidentsDao {
+
+    /// Asynchronously creates a new, unresolved incident in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `incident` - The incident to be created.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created incident detail on success, or a `DBError` on failure.
+    async fn create_incident(&self, incident: Incident) -> Result<IncidentDetail, DBError>;
+
+    /// Asynchronously marks an incident as resolved, removing it from the status page.
+    ///
+    /// # Arguments
+    ///
+    /// * `incident_uuid` - The unique identifier of the incident to be resolved.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    async fn resolve_incident(&self, incident_uuid: Uuid) -> Result<(), DBError>;
+
+    /// Asynchronously retrieves all unresolved incidents from the database, newest first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of incident details on success, or a `DBError` on failure.
+    async fn get_active_incidents(&self) -> Result<Vec<IncidentDetail>, DBError>;
+}
+
+/// Implementation of the `IncidentsDao` trait for PostgreSQL database.
+pub struct IncidentsDaoImpl {
+    db: PgPool,
+}
+
+/// Constructor
+impl IncidentsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        IncidentsDaoImpl { db }
+    }
+}
+
+#[async_trait]
+impl IncidentsDao for IncidentsDaoImpl {
+
+    /// Asynchronously creates a new, unresolved incident in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `incident` - The incident to be created.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created incident detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn create_incident(&self, incident: Incident) -> Result<IncidentDetail, DBError> {
+
+        // Insert record into DB
+        let record = sqlx::query!(
+            r#"
+                INSERT INTO incidents ( title, description )
+                VALUES ( $1, $2 )
+                RETURNING *
+            "#,
+            incident.title,
+            incident.description
+        ).fetch_one(&self.db).await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        // Return created record
+        Ok(IncidentDetail {
+            incident_uuid: record.incident_uuid,
+            title: record.title,
+            description: record.description,
+            created_at: record.created_at.and_utc(),
+            resolved_at: record.resolved_at.map(|t| t.and_utc()),
+        })
+    }
+
+    /// Asynchronously marks an incident as resolved, removing it from the status page.
+    ///
+    /// # Arguments
+    ///
+    /// * `incident_uuid` - The unique identifier of the incident to be resolved.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn resolve_incident(&self, incident_uuid: Uuid) -> Result<(), DBError> {
+
+        // Keep the first resolution time if the incident is resolved twice
+        sqlx::query!(
+            "UPDATE incidents SET resolved_at = CURRENT_TIMESTAMP WHERE incident_uuid = $1 AND resolved_at IS NULL",
+            incident_uuid
+        ).execute(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    /// Asynchronously retrieves all unresolved incidents from the database, newest first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of incident details on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_active_incidents(&self) -> Result<Vec<IncidentDetail>, DBError> {
+
+        // Get all unresolved incidents from DB
+        let records = sqlx::query!("SELECT * FROM incidents WHERE resolved_at IS NULL ORDER BY created_at DESC")
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        // Put the records in an array of IncidentDetail
+        let incidents = records.into_iter().map(|r| IncidentDetail {
+            incident_uuid: r.incident_uuid,
+            title: r.title,
+            description: r.description,
+            created_at: r.created_at.and_utc(),
+            resolved_at: r.resolved_at.map(|t| t.and_utc()),
+        }).collect();
+
+        Ok(incidents)
+    }
+}
+
+/// Implementation of the `IncidentsDao` trait keeping incidents in memory, for local
+/// development and tests.
+pub struct IncidentsDaoInMemory {
+    store: Arc<MemoryStore>,
+}
+
+/// Constructor
+impl IncidentsDaoInMemory {
+    pub fn new(store: Arc<MemoryStore>) -> Self {
+        IncidentsDaoInMemory { store }
+    }
+}
+
+#[async_trait]
+impl IncidentsDao for IncidentsDaoInMemory {
+
+    /// Asynchronously creates a new, unresolved incident in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `incident` - The incident to be created.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created incident detail on success, or a `DBError` on failure.
+    async fn create_incident(&self, incident: Incident) -> Result<IncidentDetail, DBError> {
+        let uuid = Uuid::new_v4();
+
+        let detail = IncidentDetail {
+            incident_uuid: uuid,
+            title: incident.title,
+            description: incident.description,
+            created_at: super::now(),
+            resolved_at: None,
+        };
+
+        let row = self.store.row(detail.clone());
+        self.store.incidents.write().map_err(memory::poisoned)?.insert(uuid, row);
+
+        Ok(detail)
+    }
+
+    /// Asynchronously marks an incident as resolved, removing it from the status page.
+    ///
+    /// # Arguments
+    ///
+    /// * `incident_uuid` - The unique identifier of the incident to be resolved.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    async fn resolve_incident(&self, incident_uuid: Uuid) -> Result<(), DBError> {
+        let mut incidents = self.store.incidents.write().map_err(memory::poisoned)?;
+
+        // Keep the first resolution time if the incident is resolved twice
+        if let Some(row) = incidents.get_mut(&incident_uuid) {
+            row.value.resolved_at.get_or_insert_with(super::now);
+        }
+
+        Ok(())
+    }
+
+    /// Asynchronously retrieves all unresolved incidents from memory, newest first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of incident details on success, or a `DBError` on failure.
+    async fn get_active_incidents(&self) -> Result<Vec<IncidentDetail>, DBError> {
+        let incidents = self.store.incidents.read().map_err(memory::poisoned)?;
+
+        let mut active = memory::in_order(incidents.values().filter(|row| row.value.resolved_at.is_none()));
+        active.reverse();
+
+        Ok(active)
+    }
+}
@@ -0,0 +1,337 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{postgres_error_codes, DBError, JobDetail, JobKind, JobStatus, JobUuid};
+
+use super::memory::{self, MemoryStore};
+
+/// A trait representing data access operations for background jobs in the database.
+///
+/// Jobs are updated optimistically: an update only applies if the job was not updated since it was read, so that an
+/// instance resuming a job it found stalled and the instance that was running it cannot both carry on with it.
+#[async_trait]
+pub trait JobsDao {
+
+    /// Asynchronously creates a running job, with no progress yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The work the job does.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created job detail on success, or a `DBError` on failure. The error is
+    /// `DBError::Conflict` if a job of the same kind is already running.
+    async fn create_job(&self, kind: JobKind) -> Result<JobDetail, DBError>;
+
+    /// Asynchronously retrieves a job.
+    ///
+    /// # Arguments
+    ///
+    /// * `job_uuid` - The unique identifier of the job.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the job detail on success, or a `DBError` on failure. The error is
+    /// `DBError::InvalidUUID` if the job does not exist.
+    async fn get_job(&self, job_uuid: JobUuid) -> Result<JobDetail, DBError>;
+
+    /// Asynchronously retrieves the jobs that are still running, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of job details on success, or a `DBError` on failure.
+    async fn get_running_jobs(&self) -> Result<Vec<JobDetail>, DBError>;
+
+    /// Asynchronously saves the status and progress of a job, provided it was not updated since it was read.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The job, as read or last updated, with its new status and progress.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated job detail, with its new `updated_at`, on success, or a `DBError` on
+    /// failure. The error is `DBError::Conflict` if the job was updated meanwhile, or does not exist.
+    async fn update_job(&self, job: &JobDetail) -> Result<JobDetail, DBError>;
+}
+
+/// The error for a job that was updated by someone else since it was read.
+pub(crate) fn updated_meanwhile(job_uuid: JobUuid) -> DBError {
+    DBError::Conflict(format!("Job {} was updated by another instance", job_uuid))
+}
+
+/// The error for a job whose kind already has one running.
+pub(crate) fn already_running(kind: JobKind) -> DBError {
+    DBError::Conflict(format!("A {} job is already running", kind.as_str()))
+}
+
+/// The error for a job that does not exist.
+pub(crate) fn unknown_job(job_uuid: JobUuid) -> DBError {
+    DBError::InvalidUUID(format!("Invalid job UUID: {}", job_uuid))
+}
+
+/// Reads the kind and status of a job, which are stored as text.
+pub(crate) fn parse_job_state(kind: &str, status: &str) -> Result<(JobKind, JobStatus), DBError> {
+    let kind = kind.parse().map_err(|e: String| DBError::Other(e.into()))?;
+    let status = status.parse().map_err(|e: String| DBError::Other(e.into()))?;
+
+    Ok((kind, status))
+}
+
+/// A job as stored in Postgres
+struct JobRow {
+    job_uuid: Uuid,
+    kind: String,
+    status: String,
+    processed: i64,
+    last_question_uuid: Option<Uuid>,
+    last_error: Option<String>,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+}
+
+impl TryFrom<JobRow> for JobDetail {
+    type Error = DBError;
+
+    fn try_from(r: JobRow) -> Result<Self, Self::Error> {
+        let (kind, status) = parse_job_state(&r.kind, &r.status)?;
+
+        Ok(JobDetail {
+            job_uuid: r.job_uuid.into(),
+            kind,
+            status,
+            processed: r.processed,
+            cursor: r.last_question_uuid.map(Into::into),
+            last_error: r.last_error,
+            created_at: r.created_at.and_utc(),
+            updated_at: r.updated_at.and_utc(),
+        })
+    }
+}
+
+/// Implementation of the `JobsDao` trait for PostgreSQL database.
+pub struct JobsDaoImpl {
+    db: PgPool,
+}
+
+/// Constructor
+impl JobsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        JobsDaoImpl { db }
+    }
+}
+
+#[async_trait]
+impl JobsDao for JobsDaoImpl {
+
+    /// Asynchronously creates a running job in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The work the job does.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created job detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn create_job(&self, kind: JobKind) -> Result<JobDetail, DBError> {
+        // Timestamps come from the application, like those of `update_job`, so both use the same clock
+        let now = super::now().naive_utc();
+
+        let record = sqlx::query_as!(
+            JobRow,
+            r#"
+                INSERT INTO jobs ( kind, status, created_at, updated_at )
+                VALUES ( $1, $2, $3, $3 )
+                RETURNING *
+            "#,
+            kind.as_str(),
+            JobStatus::Running.as_str(),
+            now
+        ).fetch_one(&self.db)
+         .await
+         .map_err(|e: sqlx::Error| match e {
+            // Only one job of a kind may be running, which a partial unique index enforces
+            sqlx::Error::Database(e) if e.code().is_some_and(|code| code.eq(postgres_error_codes::UNIQUE_VIOLATION)) => {
+                already_running(kind)
+            }
+            e => DBError::Other(Box::new(e)),
+         })?;
+
+        record.try_into()
+    }
+
+    /// Asynchronously retrieves a job from the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `job_uuid` - The unique identifier of the job.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the job detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_job(&self, job_uuid: JobUuid) -> Result<JobDetail, DBError> {
+        let record = sqlx::query_as!(JobRow, "SELECT * FROM jobs WHERE job_uuid = $1", Uuid::from(job_uuid))
+            .fetch_optional(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        record.ok_or_else(|| unknown_job(job_uuid))?.try_into()
+    }
+
+    /// Asynchronously retrieves the running jobs from the database, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of job details on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_running_jobs(&self) -> Result<Vec<JobDetail>, DBError> {
+        let records = sqlx::query_as!(
+            JobRow,
+            "SELECT * FROM jobs WHERE status = $1 ORDER BY created_at",
+            JobStatus::Running.as_str()
+        ).fetch_all(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        records.into_iter().map(JobDetail::try_from).collect()
+    }
+
+    /// Asynchronously saves the status and progress of a job in the database, provided it was not updated since
+    /// it was read.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The job, as read or last updated, with its new status and progress.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated job detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn update_job(&self, job: &JobDetail) -> Result<JobDetail, DBError> {
+        let record = sqlx::query_as!(
+            JobRow,
+            r#"
+                UPDATE jobs
+                SET status = $2, processed = $3, last_question_uuid = $4, last_error = $5, updated_at = $6
+                WHERE job_uuid = $1 AND updated_at = $7
+                RETURNING *
+            "#,
+            Uuid::from(job.job_uuid),
+            job.status.as_str(),
+            job.processed,
+            job.cursor.map(Uuid::from),
+            job.last_error,
+            super::now().naive_utc(),
+            job.updated_at.naive_utc()
+        ).fetch_optional(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        record.ok_or_else(|| updated_meanwhile(job.job_uuid))?.try_into()
+    }
+}
+
+/// Implementation of the `JobsDao` trait keeping jobs in memory, for local development and tests.
+pub struct JobsDaoInMemory {
+    store: Arc<MemoryStore>,
+}
+
+/// Constructor
+impl JobsDaoInMemory {
+    pub fn new(store: Arc<MemoryStore>) -> Self {
+        JobsDaoInMemory { store }
+    }
+}
+
+#[async_trait]
+impl JobsDao for JobsDaoInMemory {
+
+    /// Asynchronously creates a running job in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The work the job does.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created job detail on success, or a `DBError` on failure.
+    async fn create_job(&self, kind: JobKind) -> Result<JobDetail, DBError> {
+        let now = super::now();
+
+        let detail = JobDetail {
+            job_uuid: JobUuid::new_v4(),
+            kind,
+            status: JobStatus::Running,
+            processed: 0,
+            cursor: None,
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+        };
+
+        let row = self.store.row(detail.clone());
+        let mut jobs = self.store.jobs.write().map_err(memory::poisoned)?;
+
+        if jobs.values().any(|row| row.value.kind == kind && row.value.status == JobStatus::Running) {
+            return Err(already_running(kind));
+        }
+        jobs.insert(detail.job_uuid, row);
+
+        Ok(detail)
+    }
+
+    /// Asynchronously retrieves a job from memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `job_uuid` - The unique identifier of the job.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the job detail on success, or a `DBError` on failure.
+    async fn get_job(&self, job_uuid: JobUuid) -> Result<JobDetail, DBError> {
+        let jobs = self.store.jobs.read().map_err(memory::poisoned)?;
+
+        jobs.get(&job_uuid).map(|row| row.value.clone()).ok_or_else(|| unknown_job(job_uuid))
+    }
+
+    /// Asynchronously retrieves the running jobs from memory, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of job details on success, or a `DBError` on failure.
+    async fn get_running_jobs(&self) -> Result<Vec<JobDetail>, DBError> {
+        let jobs = self.store.jobs.read().map_err(memory::poisoned)?;
+
+        Ok(memory::in_order(jobs.values().filter(|row| row.value.status == JobStatus::Running)))
+    }
+
+    /// Asynchronously saves the status and progress of a job in memory, provided it was not updated since it was
+    /// read.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The job, as read or last updated, with its new status and progress.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated job detail on success, or a `DBError` on failure.
+    async fn update_job(&self, job: &JobDetail) -> Result<JobDetail, DBError> {
+        let mut jobs = self.store.jobs.write().map_err(memory::poisoned)?;
+
+        let row = jobs
+            .get_mut(&job.job_uuid)
+            .filter(|row| row.value.updated_at == job.updated_at)
+            .ok_or_else(|| updated_meanwhile(job.job_uuid))?;
+
+        row.value = JobDetail { updated_at: super::now(), ..job.clone() };
+
+        Ok(row.value.clone())
+    }
+}
@@ -0,0 +1,122 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::models::{DBError, IndexMaintenance, MaintenanceReport, TableMaintenance};
+
+// Bloat is estimated from the planner statistics, comparing the pages of a relation with those its live tuples fill
+// at their average width. It is rough, but costs nothing, unlike `pgstattuple` which reads every page and needs an
+// extension installed.
+
+/// A trait representing data access operations for the maintenance statistics the database keeps about its tables.
+#[async_trait]
+pub trait MaintenanceDao {
+
+    /// Asynchronously reads the dead tuples, bloat and vacuum activity of the tables, and the size and use of their
+    /// indexes.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the report on success, or a `DBError` on failure.
+    async fn get_maintenance_report(&self) -> Result<MaintenanceReport, DBError>;
+}
+
+/// Implementation of the `MaintenanceDao` trait for PostgreSQL database, reading the `pg_stat` views.
+pub struct MaintenanceDaoImpl {
+    db: PgPool,
+}
+
+/// Constructor
+impl MaintenanceDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        MaintenanceDaoImpl { db }
+    }
+}
+
+#[async_trait]
+impl MaintenanceDao for MaintenanceDaoImpl {
+
+    /// Asynchronously reads the maintenance statistics of the tables of the current schema, and their indexes.
+    ///
+    /// The partitioned answers table is reported per partition, as those are what get vacuumed.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the report, ordered by table, on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_maintenance_report(&self) -> Result<MaintenanceReport, DBError> {
+        // Tuples take a 24 bytes header and a 4 bytes line pointer, pages a 24 bytes header
+        let tables = sqlx::query_as!(
+            TableMaintenance,
+            r#"
+                WITH widths AS (
+                    SELECT tablename, sum(avg_width) AS width FROM pg_stats
+                    WHERE schemaname = current_schema()
+                    GROUP BY tablename
+                )
+                SELECT
+                    s.relname::text AS "table_name!",
+                    s.n_live_tup AS "live_tuples!",
+                    s.n_dead_tup AS "dead_tuples!",
+                    coalesce(s.n_dead_tup::float8 / nullif(s.n_live_tup + s.n_dead_tup, 0), 0) AS "dead_tuple_ratio!",
+                    pg_table_size(s.relid) AS "table_bytes!",
+                    pg_indexes_size(s.relid) AS "indexes_bytes!",
+                    CASE WHEN w.width IS NOT NULL OR c.reltuples = 0 THEN greatest(
+                        c.relpages::bigint - ceil(
+                            greatest(c.reltuples, 0) * (28 + coalesce(w.width, 0))
+                            / (current_setting('block_size')::int - 24)
+                        )::bigint,
+                        0
+                    ) * current_setting('block_size')::bigint END AS "estimated_bloat_bytes",
+                    s.last_vacuum,
+                    s.last_autovacuum,
+                    s.last_analyze,
+                    s.last_autoanalyze,
+                    s.vacuum_count AS "vacuum_count!",
+                    s.autovacuum_count AS "autovacuum_count!"
+                FROM pg_stat_user_tables s
+                JOIN pg_class c ON c.oid = s.relid
+                LEFT JOIN widths w ON w.tablename = s.relname
+                WHERE s.schemaname = current_schema() AND c.relkind = 'r'
+                ORDER BY s.relname
+            "#
+        ).fetch_all(&self.db).await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        // B-tree tuples take an 8 bytes header and a 4 bytes line pointer, pages a 40 bytes header and are filled
+        // up to 90%, the first page holding metadata only
+        let indexes = sqlx::query_as!(
+            IndexMaintenance,
+            r#"
+                WITH widths AS (
+                    SELECT i.indexrelid, sum(st.avg_width) AS width
+                    FROM pg_index i
+                    JOIN pg_class t ON t.oid = i.indrelid
+                    JOIN pg_attribute a ON a.attrelid = i.indrelid AND a.attnum = ANY(i.indkey)
+                    JOIN pg_stats st ON st.schemaname = current_schema() AND st.tablename = t.relname AND st.attname = a.attname
+                    -- Expressions are numbered 0, and have no statistics to size them with
+                    WHERE NOT 0 = ANY(i.indkey)
+                    GROUP BY i.indexrelid
+                )
+                SELECT
+                    s.indexrelname::text AS "index_name!",
+                    s.relname::text AS "table_name!",
+                    pg_relation_size(s.indexrelid) AS "index_bytes!",
+                    CASE WHEN am.amname = 'btree' AND (w.width IS NOT NULL OR c.reltuples = 0) THEN greatest(
+                        c.relpages::bigint - 1 - ceil(
+                            greatest(c.reltuples, 0) * (12 + coalesce(w.width, 0))
+                            / ((current_setting('block_size')::int - 40) * 0.9)
+                        )::bigint,
+                        0
+                    ) * current_setting('block_size')::bigint END AS "estimated_bloat_bytes",
+                    s.idx_scan AS "scans!"
+                FROM pg_stat_user_indexes s
+                JOIN pg_class c ON c.oid = s.indexrelid
+                JOIN pg_am am ON am.oid = c.relam
+                LEFT JOIN widths w ON w.indexrelid = s.indexrelid
+                WHERE s.schemaname = current_schema()
+                ORDER BY s.relname, s.indexrelname
+            "#
+        ).fetch_all(&self.db).await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(MaintenanceReport { tables, indexes })
+    }
+}
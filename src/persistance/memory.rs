@@ -0,0 +1,93 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        PoisonError, RwLock,
+    },
+};
+
+use sqlx::types::Uuid;
+
+use crate::{
+    models::{
+        AnswerDetail, AnswerUuid, DBError, FlagDetail, FlagUuid, IdempotentRequest, IncidentDetail, JobDetail, JobUuid,
+        QuestionDetail, QuestionUuid, QuestionWebhookDetail, WebhookDetail,
+    },
+    outbox::ContentEvent,
+};
+
+/// A record kept in memory, along with its insertion order
+pub(crate) struct Row<T> {
+    pub(crate) sequence: u64,
+    pub(crate) value: T,
+}
+
+/// Tables backing the in-memory DAOs, shared between them the way a connection pool is.
+///
+/// Locks are always taken in field order (questions before answers) so that DAOs holding
+/// more than one cannot deadlock.
+#[derive(Default)]
+pub struct MemoryStore {
+    pub(crate) questions: RwLock<HashMap<QuestionUuid, Row<QuestionDetail>>>,
+    pub(crate) answers: RwLock<HashMap<AnswerUuid, Row<AnswerDetail>>>,
+    pub(crate) incidents: RwLock<HashMap<Uuid, Row<IncidentDetail>>>,
+    pub(crate) webhooks: RwLock<HashMap<Uuid, Row<WebhookDetail>>>,
+    pub(crate) question_webhooks: RwLock<HashMap<Uuid, Row<QuestionWebhookDetail>>>,
+    pub(crate) jobs: RwLock<HashMap<JobUuid, Row<JobDetail>>>,
+    pub(crate) flags: RwLock<HashMap<FlagUuid, Row<FlagDetail>>>,
+    /// Events of the writes, recorded while still holding the locks of the tables written to
+    pub(crate) outbox: RwLock<VecDeque<ContentEvent>>,
+    /// Requests made with an idempotency key, by route and key
+    pub(crate) idempotency_keys: RwLock<HashMap<(String, String), IdempotentRequest>>,
+    sequence: AtomicU64,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        MemoryStore::default()
+    }
+
+    /// Wraps a value in a row that sorts after every row created before it.
+    pub(crate) fn row<T>(&self, value: T) -> Row<T> {
+        Row {
+            sequence: self.sequence.fetch_add(1, Ordering::Relaxed),
+            value,
+        }
+    }
+}
+
+/// Collects rows in insertion order.
+pub(crate) fn in_order<'a, T: Clone + 'a>(rows: impl Iterator<Item = &'a Row<T>>) -> Vec<T> {
+    let mut rows: Vec<&Row<T>> = rows.collect();
+    rows.sort_by_key(|row| row.sequence);
+    rows.into_iter().map(|row| row.value.clone()).collect()
+}
+
+/// Counts the answers of a question and finds when it was last answered, the way a join would in a database.
+///
+/// # Arguments
+///
+/// * `question` - The question, as stored.
+/// * `answers` - Every answer in the store.
+///
+/// # Returns
+///
+/// The question with its `answer_count` and `last_activity_at` filled in.
+pub(crate) fn with_activity(mut question: QuestionDetail, answers: &HashMap<AnswerUuid, Row<AnswerDetail>>) -> QuestionDetail {
+    let answers = answers.values().filter(|row| row.value.question_uuid == question.question_uuid);
+
+    question.answer_count = 0;
+    question.last_activity_at = question.created_at;
+
+    for row in answers {
+        question.answer_count += 1;
+        question.last_activity_at = question.last_activity_at.max(row.value.created_at);
+    }
+
+    question
+}
+
+/// Maps a lock poisoned by a panicking writer to a database error.
+pub(crate) fn poisoned<T>(_: PoisonError<T>) -> DBError {
+    DBError::Other("In-memory store lock poisoned".into())
+}
@@ -1,5 +1,239 @@
 pub mod answers_dao;
+pub mod cache;
+#[cfg(test)]
+mod contract;
+pub mod flags_dao;
+pub mod health;
+pub mod idempotency_dao;
+pub mod incidents_dao;
+pub mod jobs_dao;
+pub mod maintenance_dao;
+pub mod memory;
+pub mod notify;
+pub mod outbox_dao;
+pub mod partitions;
 pub mod questions_dao;
+mod search;
+pub mod stats_dao;
+pub mod webhooks_dao;
+#[cfg(feature = "mysql")]
+pub mod mysql;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use sqlx::{migrate::MigrateError, postgres::PgPoolOptions, PgPool, Postgres, Transaction};
+
+use crate::{
+    config::DatabaseKind,
+    models::{AnswerDetail, AnswerUuid, AnswersByQuestion, DBError, QuestionStatus, QuestionUuid},
+};
+
+/// Connection pool of the database `DATABASE_URL` points at
+#[derive(Clone)]
+pub enum DatabasePool {
+    Postgres(PgPool),
+    #[cfg(feature = "sqlite")]
+    Sqlite(sqlx::SqlitePool),
+    #[cfg(feature = "mysql")]
+    MySql(sqlx::MySqlPool),
+}
+
+impl DatabasePool {
+
+    /// Connects to a database.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of database the URL points at.
+    /// * `url` - The database URL.
+    /// * `max_connections` - Size of the connection pool.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the connection pool on success, or a `sqlx::Error` on failure.
+    pub async fn connect(kind: DatabaseKind, url: &str, max_connections: u32) -> Result<Self, sqlx::Error> {
+        match kind {
+            DatabaseKind::Postgres => {
+                let pool = PgPoolOptions::new().max_connections(max_connections).connect(url).await?;
+                Ok(DatabasePool::Postgres(pool))
+            }
+            #[cfg(feature = "sqlite")]
+            DatabaseKind::Sqlite => Ok(DatabasePool::Sqlite(sqlite::connect(url, max_connections).await?)),
+            #[cfg(feature = "mysql")]
+            DatabaseKind::MySql => {
+                let pool = sqlx::mysql::MySqlPoolOptions::new().max_connections(max_connections).connect(url).await?;
+                Ok(DatabasePool::MySql(pool))
+            }
+        }
+    }
+
+    /// Applies any pending migrations for this kind of database.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `MigrateError` is returned.
+    pub async fn run_migrations(&self) -> Result<(), MigrateError> {
+        match self {
+            DatabasePool::Postgres(pool) => run_migrations(pool).await,
+            #[cfg(feature = "sqlite")]
+            DatabasePool::Sqlite(pool) => sqlite::run_migrations(pool).await,
+            #[cfg(feature = "mysql")]
+            DatabasePool::MySql(pool) => mysql::run_migrations(pool).await,
+        }
+    }
+
+    /// Closes all connections, waiting for the ones in use to be returned.
+    pub async fn close(&self) {
+        match self {
+            DatabasePool::Postgres(pool) => pool.close().await,
+            #[cfg(feature = "sqlite")]
+            DatabasePool::Sqlite(pool) => pool.close().await,
+            #[cfg(feature = "mysql")]
+            DatabasePool::MySql(pool) => pool.close().await,
+        }
+    }
+}
+
+/// Applies any pending migrations from the `migrations/` directory, which is embedded in the binary.
+///
+/// # Arguments
+///
+/// * `pool` - The connection pool of the database to migrate.
+///
+/// # Returns
+///
+/// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `MigrateError` is returned.
+pub async fn run_migrations(pool: &PgPool) -> Result<(), MigrateError> {
+    sqlx::migrate!().run(pool).await
+}
+
+/// Begins two read-only transactions on two connections, both seeing the database as of the same snapshot.
+///
+/// Queries run concurrently on each read consistent data, as if they ran one after the other in a single
+/// transaction. The first transaction exports its snapshot, which the second imports, so the first must stay open
+/// until the second has begun, which is the case once this returns.
+///
+/// # Arguments
+///
+/// * `db` - The Postgres connection pool, which both connections are taken from.
+///
+/// # Returns
+///
+/// A `Result` containing both transactions on success, or a `DBError` on failure.
+pub(crate) async fn begin_shared_snapshot(
+    db: &PgPool,
+) -> Result<(Transaction<'static, Postgres>, Transaction<'static, Postgres>), DBError> {
+    // Snapshots can only be shared by transactions keeping the same one for their whole duration
+    const BEGIN: &str = "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY";
+
+    let (mut exporter, mut importer) = tokio::try_join!(db.begin(), db.begin()).map_err(|e| DBError::Other(Box::new(e)))?;
+
+    sqlx::query(BEGIN).execute(&mut *exporter).await.map_err(|e| DBError::Other(Box::new(e)))?;
+    let snapshot: String = sqlx::query_scalar("SELECT pg_export_snapshot()")
+        .fetch_one(&mut *exporter)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+    // The snapshot is an identifier generated by Postgres, and `SET TRANSACTION` takes no parameters
+    sqlx::query(BEGIN).execute(&mut *importer).await.map_err(|e| DBError::Other(Box::new(e)))?;
+    sqlx::query(&format!("SET TRANSACTION SNAPSHOT '{}'", snapshot))
+        .execute(&mut *importer)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+    Ok((exporter, importer))
+}
+
+/// The current time at the microsecond precision of Postgres timestamps, for backends that do
+/// not generate timestamps themselves.
+pub(crate) fn now() -> DateTime<Utc> {
+    let now = Utc::now();
+    now.duration_trunc(TimeDelta::microseconds(1)).unwrap_or(now)
+}
+
+/// The `LIKE` pattern matching text that contains some text as is, its wildcards escaped with `!`, which must be
+/// named as the escape character: `LIKE $1 ESCAPE '!'`. Backslash, the usual one, needs escaping itself in MySQL.
+pub(crate) fn contains_pattern(text: &str) -> String {
+    let mut pattern = String::with_capacity(text.len() + 2);
+
+    pattern.push('%');
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '!') {
+            pattern.push('!');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+
+    pattern
+}
+
+/// The error for a question that was expected to have another status.
+///
+/// # Arguments
+///
+/// * `question_uuid` - The unique identifier of the question.
+/// * `status` - The current status of the question, `None` if it does not exist.
+pub(crate) fn unexpected_status(question_uuid: QuestionUuid, status: Option<QuestionStatus>) -> DBError {
+    match status {
+        Some(status) => DBError::Conflict(format!("Question {} is {}", question_uuid, status)),
+        None => unknown_question(question_uuid),
+    }
+}
+
+/// The error for a question or answer created with a unique identifier that is already taken.
+///
+/// # Arguments
+///
+/// * `kind` - What was created, `Question` or `Answer`.
+/// * `uuid` - The unique identifier chosen for it.
+pub(crate) fn already_exists(kind: &str, uuid: impl std::fmt::Display) -> DBError {
+    DBError::Conflict(format!("{} {} already exists", kind, uuid))
+}
+
+/// The error for a question that does not exist.
+///
+/// # Arguments
+///
+/// * `question_uuid` - The unique identifier of the question.
+pub(crate) fn unknown_question(question_uuid: QuestionUuid) -> DBError {
+    DBError::InvalidUUID(format!("Invalid question UUID: {}", question_uuid))
+}
+
+/// The error for an answer that does not exist.
+///
+/// # Arguments
+///
+/// * `answer_uuid` - The unique identifier of the answer.
+pub(crate) fn unknown_answer(answer_uuid: AnswerUuid) -> DBError {
+    DBError::InvalidUUID(format!("Invalid answer UUID: {}", answer_uuid))
+}
+
+/// Groups answers by question, the way a batch lookup returns them.
+///
+/// # Arguments
+///
+/// * `question_uuids` - The unique identifiers of the questions looked up.
+/// * `answers` - The answers of those questions, in the order to keep within each question.
+///
+/// # Returns
+///
+/// The answers keyed by question, with an empty vector for every question without answers.
+pub(crate) fn group_by_question(
+    question_uuids: &[QuestionUuid],
+    answers: impl IntoIterator<Item = AnswerDetail>,
+) -> AnswersByQuestion {
+    let mut grouped: HashMap<_, Vec<_>> = question_uuids.iter().map(|uuid| (*uuid, Vec::new())).collect();
+
+    for answer in answers {
+        grouped.entry(answer.question_uuid).or_default().push(answer);
+    }
+
+    grouped
+}
 
 #[cfg(test)]
 mod tests;
\ No newline at end of file
@@ -0,0 +1,1942 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use sqlx::{
+    migrate::MigrateError,
+    mysql::MySqlDatabaseError,
+    types::{uuid::fmt::Hyphenated, Json, Uuid},
+    Executor, FromRow, MySql, MySqlConnection, QueryBuilder, MySqlPool,
+};
+
+use crate::{
+    health::HealthCheck,
+    models::{
+        mysql_error_codes, Answer, AnswerCountBucket, AnswerDetail, AnswerId, AnswerSort, AnswerUuid, AnswersByQuestion,
+        ContentStats, DBError, Flag, FlagDetail, FlagResolution, FlagUuid, HeldContent, IdempotentRequest,
+        IdempotentResponse, Incident, IncidentDetail, JobDetail, JobKind, JobStatus, JobUuid, Question, QuestionDetail,
+        QuestionFilter, QuestionId, QuestionSearchResult, QuestionStatus, QuestionUuid, QuestionWebhookDetail,
+        QuestionWithAnswers, Webhook, WebhookDetail,
+    },
+    outbox::ContentEvent,
+    reading_time,
+};
+
+use super::{
+    answers_dao::AnswersDao,
+    flags_dao::{self, FlagsDao},
+    idempotency_dao::{idempotent_request, IdempotencyDao},
+    incidents_dao::IncidentsDao,
+    jobs_dao::{self, JobsDao},
+    outbox_dao::OutboxDao,
+    questions_dao::QuestionsDao,
+    search,
+    stats_dao::{self, StatsDao},
+    webhooks_dao::WebhooksDao,
+};
+
+// The compile-time checked `query!` macros can only target one database, Postgres, so the
+// MySQL DAOs use runtime checked queries mapped with `FromRow`. MySQL has no `RETURNING`, so
+// UUIDs and timestamps are generated here and the details built from the inserted values.
+
+#[derive(FromRow)]
+struct QuestionRow {
+    question_uuid: Hyphenated,
+    title: String,
+    description: String,
+    // MySQL reports `ENUM` columns as strings, which enums deriving `sqlx::Type` cannot be decoded from
+    #[sqlx(try_from = "String")]
+    status: QuestionStatus,
+    created_at: DateTime<Utc>,
+    answer_count: i64,
+    last_activity_at: DateTime<Utc>,
+    view_count: i64,
+    word_count: i64,
+    reading_time_seconds: i64,
+}
+
+impl From<QuestionRow> for QuestionDetail {
+    fn from(r: QuestionRow) -> Self {
+        QuestionDetail {
+            question_uuid: r.question_uuid.into_uuid().into(),
+            title: r.title,
+            description: r.description,
+            status: r.status,
+            created_at: r.created_at,
+            answer_count: r.answer_count,
+            last_activity_at: r.last_activity_at,
+            view_count: r.view_count,
+            word_count: r.word_count,
+            reading_time_seconds: r.reading_time_seconds,
+            excerpt: None,
+        }
+    }
+}
+
+/// Selects `QuestionRow`s, counting the answers of each question, to be followed by a `WHERE` and a `GROUP BY q.question_uuid`
+const SELECT_QUESTIONS: &str = "
+    SELECT q.*, COUNT(a.answer_uuid) AS answer_count, COALESCE(MAX(a.created_at), q.created_at) AS last_activity_at
+    FROM questions q
+    LEFT JOIN answers a ON a.question_uuid = q.question_uuid
+";
+
+#[derive(FromRow)]
+struct AnswerRow {
+    answer_uuid: Hyphenated,
+    question_uuid: Hyphenated,
+    content: String,
+    created_at: DateTime<Utc>,
+    word_count: i64,
+    reading_time_seconds: i64,
+}
+
+impl From<AnswerRow> for AnswerDetail {
+    fn from(r: AnswerRow) -> Self {
+        AnswerDetail {
+            answer_uuid: r.answer_uuid.into_uuid().into(),
+            question_uuid: r.question_uuid.into_uuid().into(),
+            content: r.content,
+            created_at: r.created_at,
+            word_count: r.word_count,
+            reading_time_seconds: r.reading_time_seconds,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct IncidentRow {
+    incident_uuid: Hyphenated,
+    title: String,
+    description: String,
+    created_at: DateTime<Utc>,
+    resolved_at: Option<DateTime<Utc>>,
+}
+
+impl From<IncidentRow> for IncidentDetail {
+    fn from(r: IncidentRow) -> Self {
+        IncidentDetail {
+            incident_uuid: r.incident_uuid.into_uuid(),
+            title: r.title,
+            description: r.description,
+            created_at: r.created_at,
+            resolved_at: r.resolved_at,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct WebhookRow {
+    webhook_uuid: Hyphenated,
+    url: String,
+    secret: String,
+    created_at: DateTime<Utc>,
+}
+
+impl From<WebhookRow> for WebhookDetail {
+    fn from(r: WebhookRow) -> Self {
+        WebhookDetail {
+            webhook_uuid: r.webhook_uuid.into_uuid(),
+            url: r.url,
+            secret: r.secret,
+            created_at: r.created_at,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct QuestionWebhookRow {
+    webhook_uuid: Hyphenated,
+    question_uuid: Hyphenated,
+    url: String,
+    secret: String,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+}
+
+impl From<QuestionWebhookRow> for QuestionWebhookDetail {
+    fn from(r: QuestionWebhookRow) -> Self {
+        QuestionWebhookDetail {
+            webhook_uuid: r.webhook_uuid.into_uuid(),
+            question_uuid: r.question_uuid.into_uuid().into(),
+            url: r.url,
+            secret: r.secret,
+            created_at: r.created_at,
+            expires_at: r.expires_at,
+        }
+    }
+}
+
+#[derive(FromRow)]
+struct JobRow {
+    job_uuid: Hyphenated,
+    kind: String,
+    status: String,
+    processed: i64,
+    last_question_uuid: Option<Hyphenated>,
+    last_error: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl TryFrom<JobRow> for JobDetail {
+    type Error = DBError;
+
+    fn try_from(r: JobRow) -> Result<Self, Self::Error> {
+        let (kind, status) = jobs_dao::parse_job_state(&r.kind, &r.status)?;
+
+        Ok(JobDetail {
+            job_uuid: r.job_uuid.into_uuid().into(),
+            kind,
+            status,
+            processed: r.processed,
+            cursor: r.last_question_uuid.map(|uuid| uuid.into_uuid().into()),
+            last_error: r.last_error,
+            created_at: r.created_at,
+            updated_at: r.updated_at,
+        })
+    }
+}
+
+#[derive(FromRow)]
+struct FlagRow {
+    flag_uuid: Hyphenated,
+    question_uuid: Hyphenated,
+    answer_uuid: Option<Hyphenated>,
+    reason: String,
+    comment: Option<String>,
+    created_at: DateTime<Utc>,
+    resolution: Option<String>,
+    resolved_at: Option<DateTime<Utc>>,
+    held_content: Option<Json<HeldContent>>,
+}
+
+impl TryFrom<FlagRow> for FlagDetail {
+    type Error = DBError;
+
+    fn try_from(r: FlagRow) -> Result<Self, Self::Error> {
+        let (reason, resolution) = flags_dao::parse_flag_state(&r.reason, r.resolution.as_deref())?;
+
+        Ok(FlagDetail {
+            flag_uuid: r.flag_uuid.into_uuid().into(),
+            question_uuid: r.question_uuid.into_uuid().into(),
+            answer_uuid: r.answer_uuid.map(|uuid| uuid.into_uuid().into()),
+            reason,
+            comment: r.comment,
+            created_at: r.created_at,
+            resolution,
+            resolved_at: r.resolved_at,
+            held: r.held_content.map(|held| held.0),
+        })
+    }
+}
+
+/// Whether a MySQL error number means a referenced row does not exist.
+fn is_foreign_key_violation(number: u16) -> bool {
+    number == mysql_error_codes::NO_REFERENCED_ROW || number == mysql_error_codes::NO_REFERENCED_ROW_2
+}
+
+/// Looks up the status of a question.
+///
+/// # Arguments
+///
+/// * `db` - The pool, or the connection of the transaction, to look it up with.
+/// * `question_uuid` - The unique identifier of the question.
+///
+/// # Returns
+///
+/// A `Result` containing the status, or `None` if the question does not exist, on success, or a `DBError` on failure.
+async fn question_status(
+    db: impl Executor<'_, Database = MySql>,
+    question_uuid: QuestionUuid,
+) -> Result<Option<QuestionStatus>, DBError> {
+    let status = sqlx::query_scalar::<_, String>("SELECT status FROM questions WHERE question_uuid = ?")
+        .bind(question_uuid.as_uuid().hyphenated())
+        .fetch_optional(db)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+    status.map(|status| status.parse().map_err(|e| DBError::Other(Box::new(e)))).transpose()
+}
+
+/// Applies any pending migrations from the `migrations/mysql/` directory, which is embedded in the binary.
+///
+/// # Arguments
+///
+/// * `pool` - The connection pool of the database to migrate.
+///
+/// # Returns
+///
+/// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `MigrateError` is returned.
+pub async fn run_migrations(pool: &MySqlPool) -> Result<(), MigrateError> {
+    sqlx::migrate!("./migrations/mysql").run(pool).await
+}
+
+/// Records an event in the outbox, as part of the transaction making the write it is about.
+///
+/// # Arguments
+///
+/// * `conn` - The connection of the transaction.
+/// * `event` - The event to be recorded.
+///
+/// # Returns
+///
+/// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+async fn record_event(conn: &mut MySqlConnection, event: &ContentEvent) -> Result<(), DBError> {
+    sqlx::query("INSERT INTO outbox ( event ) VALUES ( ? )")
+        .bind(Json(event))
+        .execute(conn)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+    Ok(())
+}
+
+/// Implementation of the `QuestionsDao` trait for MySQL database.
+pub struct QuestionsDaoMySql {
+    db: MySqlPool,
+}
+
+/// Constructor
+impl QuestionsDaoMySql {
+    pub fn new(db: MySqlPool) -> Self {
+        QuestionsDaoMySql { db }
+    }
+}
+
+#[async_trait]
+impl QuestionsDao for QuestionsDaoMySql {
+
+    /// Asynchronously creates a new question in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `question` - The question to be created.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created question detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn create_question(&self, question: Question) -> Result<QuestionDetail, DBError> {
+        let uuid = question.question_uuid.unwrap_or_else(QuestionUuid::new_v4);
+        let created_at = super::now();
+        let word_count = question.word_count();
+        let reading_time_seconds = reading_time::reading_time_seconds(word_count);
+
+        let mut tx = self.db.begin().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        sqlx::query(
+            r#"
+                INSERT INTO questions ( question_uuid, title, description, created_at, word_count, reading_time_seconds )
+                VALUES ( ?, ?, ?, ?, ?, ? )
+            "#,
+        ).bind(uuid.as_uuid().hyphenated())
+         .bind(&question.title)
+         .bind(&question.description)
+         .bind(created_at)
+         .bind(word_count)
+         .bind(reading_time_seconds)
+         .execute(&mut *tx)
+         .await
+         .map_err(|e: sqlx::Error| match e {
+            sqlx::Error::Database(e) => {
+                if let Some(e) = e.try_downcast_ref::<MySqlDatabaseError>() {
+                    if e.number() == mysql_error_codes::DUPLICATE_ENTRY {
+                        return super::already_exists("Question", uuid);
+                    }
+                }
+                DBError::Other(Box::new(e))
+            }
+            e => DBError::Other(Box::new(e)),
+         })?;
+
+        let detail = QuestionDetail {
+            question_uuid: uuid,
+            title: question.title,
+            description: question.description,
+            status: QuestionStatus::Open,
+            created_at,
+            answer_count: 0,
+            last_activity_at: created_at,
+            view_count: 0,
+            word_count,
+            reading_time_seconds,
+            excerpt: None,
+        };
+
+        record_event(&mut tx, &ContentEvent::QuestionCreated(detail.clone())).await?;
+        tx.commit().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(detail)
+    }
+
+    /// Asynchronously deletes a question and its answers from the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question to be deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn delete_question(&self, question_uuid: QuestionUuid) -> Result<(), DBError> {
+        let mut tx = self.db.begin().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let result = sqlx::query("DELETE FROM questions WHERE question_uuid = ?")
+            .bind(question_uuid.as_uuid().hyphenated())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        if result.rows_affected() > 0 {
+            record_event(&mut tx, &ContentEvent::QuestionDeleted(QuestionId { question_uuid })).await?;
+        }
+        tx.commit().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    /// Asynchronously retrieves all questions from the database, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The conditions the questions must meet, and their order.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of question details on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_questions(&self, filter: &QuestionFilter) -> Result<Vec<QuestionDetail>, DBError> {
+        let mut query = QueryBuilder::new(format!("{SELECT_QUESTIONS} WHERE TRUE"));
+
+        if let Some(status) = filter.status {
+            query.push(" AND q.status = ").push_bind(status.as_str());
+        }
+        if let Some(created_after) = filter.created_after {
+            query.push(" AND q.created_at > ").push_bind(created_after);
+        }
+        if let Some(created_before) = filter.created_before {
+            query.push(" AND q.created_at < ").push_bind(created_before);
+        }
+        if let Some(title) = &filter.title_contains {
+            query.push(" AND q.title LIKE ").push_bind(super::contains_pattern(title)).push(" ESCAPE '!'");
+        }
+        if let Some(answered) = filter.answered {
+            query.push(if answered { " AND EXISTS" } else { " AND NOT EXISTS" });
+            query.push(" (SELECT 1 FROM answers u WHERE u.question_uuid = q.question_uuid)");
+        }
+
+        let sort = filter.sort.unwrap_or_default().as_str();
+        query.push(" GROUP BY q.question_uuid ORDER BY CASE WHEN ").push_bind(sort);
+        query.push(" = 'newest' THEN q.created_at END DESC, CASE WHEN ").push_bind(sort);
+        query.push(" = 'most_answered' THEN COUNT(a.answer_uuid) END DESC, q.created_at");
+
+        let records = query
+            .build_query_as::<QuestionRow>()
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(records.into_iter().map(QuestionDetail::from).collect())
+    }
+
+    /// Asynchronously retrieves several questions from the database, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuids` - The unique identifiers of the questions.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the details of the questions that exist on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_questions_by_uuids(&self, question_uuids: Vec<QuestionUuid>) -> Result<Vec<QuestionDetail>, DBError> {
+        if question_uuids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // MySQL has no arrays, so the questions are listed as `IN (?, ?, ...)`
+        let mut query = QueryBuilder::new(format!("{SELECT_QUESTIONS} WHERE q.question_uuid IN ("));
+        let mut separated = query.separated(", ");
+        for question_uuid in &question_uuids {
+            separated.push_bind(question_uuid.as_uuid().hyphenated());
+        }
+        separated.push_unseparated(") GROUP BY q.question_uuid ORDER BY q.created_at");
+
+        let records = query
+            .build_query_as::<QuestionRow>()
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(records.into_iter().map(QuestionDetail::from).collect())
+    }
+
+    /// Asynchronously retrieves a page of every question from the database, in the order of their unique
+    /// identifiers.
+    ///
+    /// # Arguments
+    ///
+    /// * `after` - The unique identifier of the last question of the previous page, `None` for the first page.
+    /// * `limit` - The maximum number of questions to return.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the details of the questions on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_questions_after(
+        &self,
+        after: Option<QuestionUuid>,
+        limit: i64,
+    ) -> Result<Vec<QuestionDetail>, DBError> {
+        // Hyphenated lowercase UUIDs sort as text the way they do as bytes
+        let mut query = QueryBuilder::new(format!("{SELECT_QUESTIONS} WHERE TRUE"));
+        if let Some(after) = after {
+            query.push(" AND q.question_uuid > ").push_bind(after.as_uuid().hyphenated());
+        }
+        query.push(" GROUP BY q.question_uuid ORDER BY q.question_uuid LIMIT ").push_bind(limit);
+
+        let records = query
+            .build_query_as::<QuestionRow>()
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(records.into_iter().map(QuestionDetail::from).collect())
+    }
+
+    /// Asynchronously retrieves the most viewed questions from the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of questions to return.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the details of the questions, most viewed first, on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_popular_questions(&self, limit: i64) -> Result<Vec<QuestionDetail>, DBError> {
+        let query =
+            format!("{SELECT_QUESTIONS} GROUP BY q.question_uuid ORDER BY q.view_count DESC, q.created_at LIMIT ?");
+
+        let records = sqlx::query_as::<_, QuestionRow>(&query)
+            .bind(limit)
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(records.into_iter().map(QuestionDetail::from).collect())
+    }
+
+    /// Asynchronously retrieves a question along with all its answers from the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the question and its answers, oldest first, on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_question_with_answers(&self, question_uuid: QuestionUuid) -> Result<QuestionWithAnswers, DBError> {
+        let query = format!("{SELECT_QUESTIONS} WHERE q.question_uuid = ? GROUP BY q.question_uuid");
+
+        // One after the other in a transaction, so the answers listed are those counted even while another is
+        // being created, as MySQL connections cannot share a snapshot
+        let mut tx = self.db.begin().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let question = sqlx::query_as::<_, QuestionRow>(&query)
+            .bind(question_uuid.as_uuid().hyphenated())
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+        let answers = sqlx::query_as::<_, AnswerRow>("SELECT * FROM answers WHERE question_uuid = ? ORDER BY created_at")
+            .bind(question_uuid.as_uuid().hyphenated())
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        tx.rollback().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let question = question.ok_or_else(|| super::unknown_question(question_uuid))?;
+
+        Ok(QuestionWithAnswers {
+            question: question.into(),
+            answers: answers.into_iter().map(AnswerDetail::from).collect(),
+        })
+    }
+
+    /// Asynchronously changes the status of a question, provided it currently has the expected status.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    /// * `from` - The status the question must have.
+    /// * `to` - The new status of the question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated question detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn update_question_status(
+        &self,
+        question_uuid: QuestionUuid,
+        from: QuestionStatus,
+        to: QuestionStatus,
+    ) -> Result<QuestionDetail, DBError> {
+        let result = sqlx::query("UPDATE questions SET status = ? WHERE question_uuid = ? AND status = ?")
+            .bind(to.as_str())
+            .bind(question_uuid.as_uuid().hyphenated())
+            .bind(from.as_str())
+            .execute(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::unexpected_status(question_uuid, question_status(&self.db, question_uuid).await?));
+        }
+
+        let query = format!("{SELECT_QUESTIONS} WHERE q.question_uuid = ? GROUP BY q.question_uuid");
+
+        let record = sqlx::query_as::<_, QuestionRow>(&query)
+            .bind(question_uuid.as_uuid().hyphenated())
+            .fetch_one(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(record.into())
+    }
+
+    /// Asynchronously adds views to the view counts of questions in the database, one `UPDATE` per question in a
+    /// single transaction.
+    ///
+    /// # Arguments
+    ///
+    /// * `views` - The number of views of each question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn add_question_views(&self, views: &HashMap<QuestionUuid, i64>) -> Result<(), DBError> {
+        let mut tx = self.db.begin().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        for (question_uuid, count) in views {
+            sqlx::query("UPDATE questions SET view_count = view_count + ? WHERE question_uuid = ?")
+                .bind(count)
+                .bind(question_uuid.as_uuid().hyphenated())
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| DBError::Other(Box::new(e)))?;
+        }
+        tx.commit().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    /// Asynchronously searches questions. MySQL has no index the search can use, so every
+    /// question is loaded and ranked in the application.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The words to search for.
+    /// * `limit` - The maximum number of results to return.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the matching questions, best match first, on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn search_questions(&self, query: String, limit: i64) -> Result<Vec<QuestionSearchResult>, DBError> {
+        let questions = self.get_questions(&QuestionFilter::default()).await?;
+
+        Ok(search::rank(questions, &query, limit))
+    }
+
+    /// Asynchronously reads the current time of the clock questions are stamped with, which is the one of this
+    /// instance.
+    async fn current_time(&self) -> Result<DateTime<Utc>, DBError> {
+        Ok(super::now())
+    }
+}
+
+/// Implementation of the `AnswersDao` trait for MySQL database.
+pub struct AnswersDaoMySql {
+    db: MySqlPool,
+}
+
+/// Constructor
+impl AnswersDaoMySql {
+    pub fn new(db: MySqlPool) -> Self {
+        AnswersDaoMySql { db }
+    }
+}
+
+#[async_trait]
+impl AnswersDao for AnswersDaoMySql {
+
+    /// Asynchronously creates a new answer in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer` - The answer to be created.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created answer detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn create_answer(&self, answer: Answer) -> Result<AnswerDetail, DBError> {
+        let uuid = answer.answer_uuid.unwrap_or_else(AnswerUuid::new_v4);
+        let created_at = super::now();
+        let word_count = answer.word_count();
+        let reading_time_seconds = reading_time::reading_time_seconds(word_count);
+
+        let mut tx = self.db.begin().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        // Only insert if the question is open. InnoDB share-locks the question while selecting it, so it
+        // cannot be closed or deleted meanwhile. Should it be deleted anyway, the insert fails with one of
+        // the "no referenced row" error numbers, which maps to `DBError::InvalidUUID` like the Postgres
+        // foreign key violation.
+        let result = sqlx::query(
+            r#"
+                INSERT INTO answers ( answer_uuid, question_uuid, content, created_at, word_count, reading_time_seconds )
+                SELECT ?, question_uuid, ?, ?, ?, ? FROM questions
+                WHERE question_uuid = ? AND status = 'open'
+            "#,
+        ).bind(uuid.as_uuid().hyphenated())
+         .bind(&answer.content)
+         .bind(created_at)
+         .bind(word_count)
+         .bind(reading_time_seconds)
+         .bind(answer.question_uuid.as_uuid().hyphenated())
+         .execute(&mut *tx)
+         .await
+         .map_err(|e: sqlx::Error| match e {
+            sqlx::Error::Database(e) => {
+                if let Some(e) = e.try_downcast_ref::<MySqlDatabaseError>() {
+                    if is_foreign_key_violation(e.number()) {
+                        return DBError::InvalidUUID(format!("Invalid question UUID: {}", answer.question_uuid));
+                    }
+                    if e.number() == mysql_error_codes::DUPLICATE_ENTRY {
+                        return super::already_exists("Answer", uuid);
+                    }
+                }
+                DBError::Other(Box::new(e))
+            }
+            e => DBError::Other(Box::new(e)),
+         })?;
+
+        if result.rows_affected() == 0 {
+            let status = question_status(&mut *tx, answer.question_uuid).await?;
+            return Err(super::unexpected_status(answer.question_uuid, status));
+        }
+
+        let detail = AnswerDetail {
+            answer_uuid: uuid,
+            question_uuid: answer.question_uuid,
+            content: answer.content,
+            created_at,
+            word_count,
+            reading_time_seconds,
+        };
+
+        record_event(&mut tx, &ContentEvent::AnswerCreated(detail.clone())).await?;
+        tx.commit().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(detail)
+    }
+
+    /// Asynchronously deletes an answer from the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer_uuid` - The unique identifier of the answer to be deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn delete_answer(&self, answer_uuid: AnswerUuid) -> Result<(), DBError> {
+        let mut tx = self.db.begin().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let result = sqlx::query("DELETE FROM answers WHERE answer_uuid = ?")
+            .bind(answer_uuid.as_uuid().hyphenated())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        if result.rows_affected() > 0 {
+            record_event(&mut tx, &ContentEvent::AnswerDeleted(AnswerId { answer_uuid })).await?;
+        }
+        tx.commit().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    /// Asynchronously retrieves all answers for a UUID from the database.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of answer details on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_answers(&self, question_uuid: QuestionUuid, sort: AnswerSort) -> Result<Vec<AnswerDetail>, DBError> {
+        let query = "SELECT * FROM answers WHERE question_uuid = ? ORDER BY CASE WHEN ? = 'newest' THEN created_at END DESC, created_at";
+
+        let records = sqlx::query_as::<_, AnswerRow>(query)
+            .bind(question_uuid.as_uuid().hyphenated())
+            .bind(sort.as_str())
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(records.into_iter().map(AnswerDetail::from).collect())
+    }
+
+    /// Asynchronously retrieves the answers of several questions from the database, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuids` - The unique identifiers of the questions.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the answers keyed by question on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_answers_for_questions(
+        &self,
+        question_uuids: Vec<QuestionUuid>,
+    ) -> Result<AnswersByQuestion, DBError> {
+        if question_uuids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        // MySQL has no arrays, so the questions are listed as `IN (?, ?, ...)`
+        let mut query = QueryBuilder::new("SELECT * FROM answers WHERE question_uuid IN (");
+        let mut separated = query.separated(", ");
+        for question_uuid in &question_uuids {
+            separated.push_bind(question_uuid.as_uuid().hyphenated());
+        }
+        separated.push_unseparated(") ORDER BY created_at");
+
+        let records = query
+            .build_query_as::<AnswerRow>()
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(super::group_by_question(&question_uuids, records.into_iter().map(AnswerDetail::from)))
+    }
+}
+
+/// Implementation of the `IncidentsDao` trait for MySQL database.
+pub struct IncidentsDaoMySql {
+    db: MySqlPool,
+}
+
+/// Constructor
+impl IncidentsDaoMySql {
+    pub fn new(db: MySqlPool) -> Self {
+        IncidentsDaoMySql { db }
+    }
+}
+
+#[async_trait]
+impl IncidentsDao for IncidentsDaoMySql {
+
+    /// Asynchronously creates a new, unresolved incident in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `incident` - The incident to be created.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created incident detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn create_incident(&self, incident: Incident) -> Result<IncidentDetail, DBError> {
+        let uuid = Uuid::new_v4();
+        let created_at = super::now();
+
+        sqlx::query("INSERT INTO incidents ( incident_uuid, title, description, created_at ) VALUES ( ?, ?, ?, ? )")
+            .bind(uuid.hyphenated())
+            .bind(&incident.title)
+            .bind(&incident.description)
+            .bind(created_at)
+            .execute(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(IncidentDetail {
+            incident_uuid: uuid,
+            title: incident.title,
+            description: incident.description,
+            created_at,
+            resolved_at: None,
+        })
+    }
+
+    /// Asynchronously marks an incident as resolved, removing it from the status page.
+    ///
+    /// # Arguments
+    ///
+    /// * `incident_uuid` - The unique identifier of the incident to be resolved.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn resolve_incident(&self, incident_uuid: Uuid) -> Result<(), DBError> {
+        // Keep the first resolution time if the incident is resolved twice
+        sqlx::query("UPDATE incidents SET resolved_at = ? WHERE incident_uuid = ? AND resolved_at IS NULL")
+            .bind(super::now())
+            .bind(incident_uuid.hyphenated())
+            .execute(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    /// Asynchronously retrieves all unresolved incidents from the database, newest first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of incident details on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_active_incidents(&self) -> Result<Vec<IncidentDetail>, DBError> {
+        let records = sqlx::query_as::<_, IncidentRow>("SELECT * FROM incidents WHERE resolved_at IS NULL ORDER BY created_at DESC")
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(records.into_iter().map(IncidentDetail::from).collect())
+    }
+}
+
+/// Implementation of the `WebhooksDao` trait for MySQL database.
+pub struct WebhooksDaoMySql {
+    db: MySqlPool,
+}
+
+/// Constructor
+impl WebhooksDaoMySql {
+    pub fn new(db: MySqlPool) -> Self {
+        WebhooksDaoMySql { db }
+    }
+}
+
+#[async_trait]
+impl WebhooksDao for WebhooksDaoMySql {
+
+    /// Asynchronously registers a new webhook in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `webhook` - The webhook to be registered.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly registered webhook detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn create_webhook(&self, webhook: Webhook) -> Result<WebhookDetail, DBError> {
+        let uuid = Uuid::new_v4();
+        let created_at = super::now();
+
+        sqlx::query("INSERT INTO webhooks ( webhook_uuid, url, secret, created_at ) VALUES ( ?, ?, ?, ? )")
+            .bind(uuid.hyphenated())
+            .bind(&webhook.url)
+            .bind(&webhook.secret)
+            .bind(created_at)
+            .execute(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(WebhookDetail {
+            webhook_uuid: uuid,
+            url: webhook.url,
+            secret: webhook.secret,
+            created_at,
+        })
+    }
+
+    /// Asynchronously deletes a webhook from the database, which stops its deliveries.
+    ///
+    /// # Arguments
+    ///
+    /// * `webhook_uuid` - The unique identifier of the webhook to be deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn delete_webhook(&self, webhook_uuid: Uuid) -> Result<(), DBError> {
+        sqlx::query("DELETE FROM webhooks WHERE webhook_uuid = ?")
+            .bind(webhook_uuid.hyphenated())
+            .execute(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    /// Asynchronously retrieves all webhooks, with their secrets, from the database, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of webhook details on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_webhooks(&self) -> Result<Vec<WebhookDetail>, DBError> {
+        let records = sqlx::query_as::<_, WebhookRow>("SELECT * FROM webhooks ORDER BY created_at")
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(records.into_iter().map(WebhookDetail::from).collect())
+    }
+
+    /// Asynchronously registers a webhook notified of the new answers to a single question in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question watched.
+    /// * `webhook` - The webhook to be registered.
+    /// * `expires_at` - The time the webhook stops being notified.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly registered webhook detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn create_question_webhook(
+        &self,
+        question_uuid: QuestionUuid,
+        webhook: Webhook,
+        expires_at: DateTime<Utc>,
+    ) -> Result<QuestionWebhookDetail, DBError> {
+        let uuid = Uuid::new_v4();
+        let created_at = super::now();
+
+        // Only insert if the question exists. Should it be deleted meanwhile, the foreign key fails instead.
+        let result = sqlx::query(
+            r#"
+                INSERT INTO question_webhooks ( webhook_uuid, question_uuid, url, secret, created_at, expires_at )
+                SELECT ?, question_uuid, ?, ?, ?, ? FROM questions
+                WHERE question_uuid = ?
+            "#,
+        ).bind(uuid.hyphenated())
+         .bind(&webhook.url)
+         .bind(&webhook.secret)
+         .bind(created_at)
+         .bind(expires_at)
+         .bind(question_uuid.as_uuid().hyphenated())
+         .execute(&self.db)
+         .await
+         .map_err(|e: sqlx::Error| match e {
+            sqlx::Error::Database(e) => {
+                if let Some(e) = e.try_downcast_ref::<MySqlDatabaseError>() {
+                    if is_foreign_key_violation(e.number()) {
+                        return super::unknown_question(question_uuid);
+                    }
+                }
+                DBError::Other(Box::new(e))
+            }
+            e => DBError::Other(Box::new(e)),
+         })?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::unknown_question(question_uuid));
+        }
+
+        Ok(QuestionWebhookDetail {
+            webhook_uuid: uuid,
+            question_uuid,
+            url: webhook.url,
+            secret: webhook.secret,
+            created_at,
+            expires_at,
+        })
+    }
+
+    /// Asynchronously retrieves the webhooks of a question that have not expired from the database, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of webhook details on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_question_webhooks(&self, question_uuid: QuestionUuid) -> Result<Vec<QuestionWebhookDetail>, DBError> {
+        let records = sqlx::query_as::<_, QuestionWebhookRow>(
+            "SELECT * FROM question_webhooks WHERE question_uuid = ? AND expires_at > ? ORDER BY created_at",
+        ).bind(question_uuid.as_uuid().hyphenated())
+         .bind(super::now())
+         .fetch_all(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(records.into_iter().map(QuestionWebhookDetail::from).collect())
+    }
+
+    /// Asynchronously deletes the question webhooks that expired at or before a time from the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - The time up to which webhooks have expired.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of webhooks deleted on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn delete_expired_question_webhooks(&self, before: DateTime<Utc>) -> Result<u64, DBError> {
+        let result = sqlx::query("DELETE FROM question_webhooks WHERE expires_at <= ?")
+            .bind(before)
+            .execute(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Implementation of the `JobsDao` trait for MySQL database.
+pub struct JobsDaoMySql {
+    db: MySqlPool,
+}
+
+/// Constructor
+impl JobsDaoMySql {
+    pub fn new(db: MySqlPool) -> Self {
+        JobsDaoMySql { db }
+    }
+}
+
+#[async_trait]
+impl JobsDao for JobsDaoMySql {
+
+    /// Asynchronously creates a running job in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The work the job does.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created job detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn create_job(&self, kind: JobKind) -> Result<JobDetail, DBError> {
+        let uuid = Uuid::new_v4();
+        let created_at = super::now();
+
+        sqlx::query(
+            r#"
+                INSERT INTO jobs ( job_uuid, kind, status, processed, created_at, updated_at )
+                VALUES ( ?, ?, ?, 0, ?, ? )
+            "#,
+        ).bind(uuid.hyphenated())
+         .bind(kind.as_str())
+         .bind(JobStatus::Running.as_str())
+         .bind(created_at)
+         .bind(created_at)
+         .execute(&self.db)
+         .await
+         .map_err(|e: sqlx::Error| match e {
+            sqlx::Error::Database(e) => {
+                if let Some(e) = e.try_downcast_ref::<MySqlDatabaseError>() {
+                    if e.number() == mysql_error_codes::DUPLICATE_ENTRY {
+                        return jobs_dao::already_running(kind);
+                    }
+                }
+                DBError::Other(Box::new(e))
+            }
+            e => DBError::Other(Box::new(e)),
+         })?;
+
+        Ok(JobDetail {
+            job_uuid: uuid.into(),
+            kind,
+            status: JobStatus::Running,
+            processed: 0,
+            cursor: None,
+            last_error: None,
+            created_at,
+            updated_at: created_at,
+        })
+    }
+
+    /// Asynchronously retrieves a job from the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `job_uuid` - The unique identifier of the job.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the job detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_job(&self, job_uuid: JobUuid) -> Result<JobDetail, DBError> {
+        let record = sqlx::query_as::<_, JobRow>("SELECT * FROM jobs WHERE job_uuid = ?")
+            .bind(job_uuid.as_uuid().hyphenated())
+            .fetch_optional(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        record.ok_or_else(|| jobs_dao::unknown_job(job_uuid))?.try_into()
+    }
+
+    /// Asynchronously retrieves the running jobs from the database, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of job details on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_running_jobs(&self) -> Result<Vec<JobDetail>, DBError> {
+        let records = sqlx::query_as::<_, JobRow>("SELECT * FROM jobs WHERE status = ? ORDER BY created_at")
+            .bind(JobStatus::Running.as_str())
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        records.into_iter().map(JobDetail::try_from).collect()
+    }
+
+    /// Asynchronously saves the status and progress of a job in the database, provided it was not updated since
+    /// it was read.
+    ///
+    /// # Arguments
+    ///
+    /// * `job` - The job, as read or last updated, with its new status and progress.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated job detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn update_job(&self, job: &JobDetail) -> Result<JobDetail, DBError> {
+        let updated_at = super::now();
+
+        let result = sqlx::query(
+            r#"
+                UPDATE jobs
+                SET status = ?, processed = ?, last_question_uuid = ?, last_error = ?, updated_at = ?
+                WHERE job_uuid = ? AND updated_at = ?
+            "#,
+        ).bind(job.status.as_str())
+         .bind(job.processed)
+         .bind(job.cursor.map(|uuid| uuid.as_uuid().hyphenated()))
+         .bind(&job.last_error)
+         .bind(updated_at)
+         .bind(job.job_uuid.as_uuid().hyphenated())
+         .bind(job.updated_at)
+         .execute(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(jobs_dao::updated_meanwhile(job.job_uuid));
+        }
+
+        Ok(JobDetail { updated_at, ..job.clone() })
+    }
+}
+
+/// Implementation of the `OutboxDao` trait for MySQL database.
+pub struct OutboxDaoMySql {
+    db: MySqlPool,
+}
+
+/// Constructor
+impl OutboxDaoMySql {
+    pub fn new(db: MySqlPool) -> Self {
+        OutboxDaoMySql { db }
+    }
+}
+
+#[async_trait]
+impl OutboxDao for OutboxDaoMySql {
+
+    /// Asynchronously publishes the oldest undelivered events of the outbox, then marks them delivered.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of events to dispatch.
+    /// * `publish` - Publishes an event, in the order they were recorded.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of events dispatched on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn dispatch_events(&self, limit: i64, publish: &(dyn Fn(ContentEvent) + Send + Sync)) -> Result<usize, DBError> {
+        let mut tx = self.db.begin().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        // Rows locked by another instance's dispatcher are left to it
+        let records = sqlx::query_as::<_, (i64, Json<ContentEvent>)>(
+            r#"
+                SELECT event_id, event FROM outbox
+                WHERE delivered_at IS NULL
+                ORDER BY event_id
+                LIMIT ?
+                FOR UPDATE SKIP LOCKED
+            "#,
+        ).bind(limit)
+         .fetch_all(&mut *tx)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        if records.is_empty() {
+            return Ok(0);
+        }
+
+        let mut query = QueryBuilder::new("UPDATE outbox SET delivered_at = CURRENT_TIMESTAMP(6) WHERE event_id IN (");
+        let mut separated = query.separated(", ");
+        for (event_id, _) in &records {
+            separated.push_bind(*event_id);
+        }
+        separated.push_unseparated(")");
+
+        let count = records.len();
+        for (_, event) in records {
+            publish(event.0);
+        }
+
+        query
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        tx.commit().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(count)
+    }
+
+    /// Asynchronously deletes the events delivered before a given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - Events delivered before this time are deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of events deleted on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn delete_delivered_events(&self, before: DateTime<Utc>) -> Result<u64, DBError> {
+        let result = sqlx::query("DELETE FROM outbox WHERE delivered_at < ?")
+            .bind(before)
+            .execute(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Implementation of the `IdempotencyDao` trait for MySQL database.
+pub struct IdempotencyDaoMySql {
+    db: MySqlPool,
+}
+
+/// Constructor
+impl IdempotencyDaoMySql {
+    pub fn new(db: MySqlPool) -> Self {
+        IdempotencyDaoMySql { db }
+    }
+}
+
+#[async_trait]
+impl IdempotencyDao for IdempotencyDaoMySql {
+
+    /// Asynchronously claims an idempotency key for a request about to be served, unless the key is already taken.
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - The route the request was made to.
+    /// * `key` - The idempotency key sent with the request.
+    /// * `request_hash` - The hash of the request body.
+    /// * `expired_before` - Keys claimed before this time are expired.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing `None` if the key was claimed, or the request the key was already claimed by, on
+    /// success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn claim_key(
+        &self,
+        route: &str,
+        key: &str,
+        request_hash: &str,
+        expired_before: DateTime<Utc>,
+    ) -> Result<Option<IdempotentRequest>, DBError> {
+        // The key may be released between failing to claim it and reading who claimed it, in which case it is tried again
+        loop {
+            sqlx::query(
+                "DELETE FROM idempotency_keys \
+                 WHERE route = ? AND idempotency_key = ? AND created_at < ?",
+            ).bind(route)
+             .bind(key)
+             .bind(expired_before)
+             .execute(&self.db)
+             .await
+             .map_err(|e| DBError::Other(Box::new(e)))?;
+
+            // Of concurrent requests with the same key, only one inserts it, the others' inserts being ignored
+            let result = sqlx::query(
+                "INSERT IGNORE INTO idempotency_keys ( route, idempotency_key, request_hash ) VALUES ( ?, ?, ? )",
+            ).bind(route)
+             .bind(key)
+             .bind(request_hash)
+             .execute(&self.db)
+             .await
+             .map_err(|e| DBError::Other(Box::new(e)))?;
+
+            if result.rows_affected() == 1 {
+                return Ok(None);
+            }
+
+            let record = sqlx::query_as::<_, (String, Option<i64>, Option<String>, DateTime<Utc>)>(
+                "SELECT request_hash, status, body, created_at FROM idempotency_keys WHERE route = ? AND idempotency_key = ?",
+            ).bind(route)
+             .bind(key)
+             .fetch_optional(&self.db)
+             .await
+             .map_err(|e| DBError::Other(Box::new(e)))?;
+
+            if let Some((request_hash, status, body, claimed_at)) = record {
+                return Ok(Some(idempotent_request(request_hash, status, body, claimed_at)));
+            }
+        }
+    }
+
+    /// Asynchronously records the response to the request that claimed an idempotency key.
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - The route the request was made to.
+    /// * `key` - The idempotency key sent with the request.
+    /// * `response` - The response to replay to retries of the request.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn complete_key(&self, route: &str, key: &str, response: &IdempotentResponse) -> Result<(), DBError> {
+        sqlx::query("UPDATE idempotency_keys SET status = ?, body = ? WHERE route = ? AND idempotency_key = ?")
+            .bind(response.status as i64)
+            .bind(&response.body)
+            .bind(route)
+            .bind(key)
+            .execute(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    /// Asynchronously releases an idempotency key whose request failed, so that it can be retried.
+    ///
+    /// # Arguments
+    ///
+    /// * `route` - The route the request was made to.
+    /// * `key` - The idempotency key sent with the request.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn release_key(&self, route: &str, key: &str) -> Result<(), DBError> {
+        sqlx::query("DELETE FROM idempotency_keys WHERE route = ? AND idempotency_key = ? AND status IS NULL")
+            .bind(route)
+            .bind(key)
+            .execute(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    /// Asynchronously deletes the idempotency keys claimed before a given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - Keys claimed before this time are deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of keys deleted on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn delete_expired_keys(&self, before: DateTime<Utc>) -> Result<u64, DBError> {
+        let result = sqlx::query("DELETE FROM idempotency_keys WHERE created_at < ?")
+            .bind(before)
+            .execute(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Implementation of the `StatsDao` trait for MySQL database.
+pub struct StatsDaoMySql {
+    db: MySqlPool,
+}
+
+/// Constructor
+impl StatsDaoMySql {
+    pub fn new(db: MySqlPool) -> Self {
+        StatsDaoMySql { db }
+    }
+}
+
+#[async_trait]
+impl StatsDao for StatsDaoMySql {
+
+    /// Asynchronously computes the statistics of the content in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `since` - The first day questions are counted per day for, in UTC.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the statistics on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_stats(&self, since: NaiveDate) -> Result<ContentStats, DBError> {
+        let buckets = sqlx::query_as::<_, (i64, i64)>(
+            r#"
+                SELECT answer_count, COUNT(*)
+                FROM (
+                    SELECT COUNT(a.answer_uuid) AS answer_count
+                    FROM questions q
+                    LEFT JOIN answers a ON a.question_uuid = q.question_uuid
+                    GROUP BY q.question_uuid
+                ) counts
+                GROUP BY answer_count
+                ORDER BY answer_count
+            "#,
+        ).fetch_all(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let daily = sqlx::query_as::<_, (NaiveDate, i64)>(
+            r#"
+                SELECT DATE(created_at), COUNT(*) FROM questions
+                WHERE created_at >= ?
+                GROUP BY 1
+            "#,
+        ).bind(since.and_time(NaiveTime::MIN).and_utc())
+         .fetch_all(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let answers_per_question = buckets
+            .into_iter()
+            .map(|(answer_count, question_count)| AnswerCountBucket { answer_count, question_count })
+            .collect();
+
+        Ok(stats_dao::content_stats(answers_per_question, since, daily.into_iter().collect()))
+    }
+}
+
+/// Implementation of the `FlagsDao` trait for MySQL database.
+pub struct FlagsDaoMySql {
+    db: MySqlPool,
+}
+
+/// Constructor
+impl FlagsDaoMySql {
+    pub fn new(db: MySqlPool) -> Self {
+        FlagsDaoMySql { db }
+    }
+
+    /// Inserts a flag on a question, open or resolved from the start.
+    async fn insert_question_flag(
+        &self,
+        question_uuid: QuestionUuid,
+        flag: Flag,
+        resolution: Option<FlagResolution>,
+    ) -> Result<FlagDetail, DBError> {
+        let uuid = Uuid::new_v4();
+        let created_at = super::now();
+        let resolved_at = resolution.map(|_| created_at);
+
+        // Only insert if the question exists, as flags do not reference it
+        let result = sqlx::query(
+            r#"
+                INSERT INTO flags ( flag_uuid, question_uuid, reason, comment, created_at, resolution, resolved_at )
+                SELECT ?, question_uuid, ?, ?, ?, ?, ? FROM questions
+                WHERE question_uuid = ?
+            "#,
+        ).bind(uuid.hyphenated())
+         .bind(flag.reason.as_str())
+         .bind(&flag.comment)
+         .bind(created_at)
+         .bind(resolution.as_ref().map(FlagResolution::as_str))
+         .bind(resolved_at)
+         .bind(question_uuid.as_uuid().hyphenated())
+         .execute(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::unknown_question(question_uuid));
+        }
+
+        Ok(FlagDetail {
+            flag_uuid: uuid.into(),
+            question_uuid,
+            answer_uuid: None,
+            reason: flag.reason,
+            comment: flag.comment,
+            created_at,
+            resolution,
+            resolved_at,
+            held: None,
+        })
+    }
+
+    /// Inserts a flag on an answer, open or resolved from the start.
+    async fn insert_answer_flag(
+        &self,
+        answer_uuid: AnswerUuid,
+        flag: Flag,
+        resolution: Option<FlagResolution>,
+    ) -> Result<FlagDetail, DBError> {
+        let uuid = FlagUuid::new_v4();
+        let created_at = super::now();
+
+        // Only insert if the answer exists, which tells the question it belongs to
+        let result = sqlx::query(
+            r#"
+                INSERT INTO flags (
+                    flag_uuid, question_uuid, answer_uuid, reason, comment, created_at, resolution, resolved_at
+                )
+                SELECT ?, question_uuid, answer_uuid, ?, ?, ?, ?, ? FROM answers
+                WHERE answer_uuid = ?
+            "#,
+        ).bind(uuid.as_uuid().hyphenated())
+         .bind(flag.reason.as_str())
+         .bind(&flag.comment)
+         .bind(created_at)
+         .bind(resolution.as_ref().map(FlagResolution::as_str))
+         .bind(resolution.map(|_| created_at))
+         .bind(answer_uuid.as_uuid().hyphenated())
+         .execute(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::unknown_answer(answer_uuid));
+        }
+
+        // Read back for the question of the answer
+        self.get_flag(uuid).await
+    }
+
+    /// Inserts an open flag holding new content.
+    async fn insert_held_flag(
+        &self,
+        question_uuid: QuestionUuid,
+        answer_uuid: Option<AnswerUuid>,
+        flag: Flag,
+        held: HeldContent,
+    ) -> Result<FlagDetail, DBError> {
+        let uuid = FlagUuid::new_v4();
+        let created_at = super::now();
+
+        // An answer is only held if its question exists, as it could not be created otherwise
+        let result = sqlx::query(
+            r#"
+                INSERT INTO flags ( flag_uuid, question_uuid, answer_uuid, reason, comment, created_at, held_content )
+                SELECT ?, ?, ?, ?, ?, ?, ? FROM DUAL
+                WHERE ? IS NULL OR EXISTS (SELECT 1 FROM questions WHERE question_uuid = ?)
+            "#,
+        ).bind(uuid.as_uuid().hyphenated())
+         .bind(question_uuid.as_uuid().hyphenated())
+         .bind(answer_uuid.map(|uuid| uuid.as_uuid().hyphenated()))
+         .bind(flag.reason.as_str())
+         .bind(&flag.comment)
+         .bind(created_at)
+         .bind(Json(&held))
+         .bind(answer_uuid.map(|uuid| uuid.as_uuid().hyphenated()))
+         .bind(question_uuid.as_uuid().hyphenated())
+         .execute(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(super::unknown_question(question_uuid));
+        }
+
+        Ok(FlagDetail {
+            flag_uuid: uuid,
+            question_uuid,
+            answer_uuid,
+            reason: flag.reason,
+            comment: flag.comment,
+            created_at,
+            resolution: None,
+            resolved_at: None,
+            held: Some(held),
+        })
+    }
+}
+
+#[async_trait]
+impl FlagsDao for FlagsDaoMySql {
+
+    /// Asynchronously flags a question in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    /// * `flag` - Why the question is flagged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn flag_question(&self, question_uuid: QuestionUuid, flag: Flag) -> Result<FlagDetail, DBError> {
+        self.insert_question_flag(question_uuid, flag, None).await
+    }
+
+    /// Asynchronously flags an answer in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer_uuid` - The unique identifier of the answer.
+    /// * `flag` - Why the answer is flagged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn flag_answer(&self, answer_uuid: AnswerUuid, flag: Flag) -> Result<FlagDetail, DBError> {
+        self.insert_answer_flag(answer_uuid, flag, None).await
+    }
+
+    /// Asynchronously tags a question in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    /// * `flag` - Why the question is tagged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn tag_question(&self, question_uuid: QuestionUuid, flag: Flag) -> Result<FlagDetail, DBError> {
+        self.insert_question_flag(question_uuid, flag, Some(FlagResolution::Tagged)).await
+    }
+
+    /// Asynchronously tags an answer in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer_uuid` - The unique identifier of the answer.
+    /// * `flag` - Why the answer is tagged.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn tag_answer(&self, answer_uuid: AnswerUuid, flag: Flag) -> Result<FlagDetail, DBError> {
+        self.insert_answer_flag(answer_uuid, flag, Some(FlagResolution::Tagged)).await
+    }
+
+    /// Asynchronously holds a new question for moderation in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `question` - The question to hold.
+    /// * `flag` - Why the question is held.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn hold_question(&self, question: Question, flag: Flag) -> Result<FlagDetail, DBError> {
+        let (question_uuid, held) = flags_dao::held_question(question);
+        self.insert_held_flag(question_uuid, None, flag, held).await
+    }
+
+    /// Asynchronously holds a new answer for moderation in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `answer` - The answer to hold.
+    /// * `flag` - Why the answer is held.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created flag detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn hold_answer(&self, answer: Answer, flag: Flag) -> Result<FlagDetail, DBError> {
+        let question_uuid = answer.question_uuid;
+        let (answer_uuid, held) = flags_dao::held_answer(answer);
+        self.insert_held_flag(question_uuid, Some(answer_uuid), flag, held).await
+    }
+
+    /// Asynchronously retrieves a flag from the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `flag_uuid` - The unique identifier of the flag.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the flag detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_flag(&self, flag_uuid: FlagUuid) -> Result<FlagDetail, DBError> {
+        let record = sqlx::query_as::<_, FlagRow>("SELECT * FROM flags WHERE flag_uuid = ?")
+            .bind(flag_uuid.as_uuid().hyphenated())
+            .fetch_optional(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        record.ok_or_else(|| flags_dao::unknown_flag(flag_uuid))?.try_into()
+    }
+
+    /// Asynchronously retrieves the open flags from the database, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of flag details on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_open_flags(&self) -> Result<Vec<FlagDetail>, DBError> {
+        let records = sqlx::query_as::<_, FlagRow>("SELECT * FROM flags WHERE resolved_at IS NULL ORDER BY created_at")
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        records.into_iter().map(FlagDetail::try_from).collect()
+    }
+
+    /// Asynchronously retrieves the flags of a question and its answers from the database, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of flag details on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_question_flags(&self, question_uuid: QuestionUuid) -> Result<Vec<FlagDetail>, DBError> {
+        let records = sqlx::query_as::<_, FlagRow>("SELECT * FROM flags WHERE question_uuid = ? ORDER BY created_at")
+            .bind(question_uuid.as_uuid().hyphenated())
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        records.into_iter().map(FlagDetail::try_from).collect()
+    }
+
+    /// Asynchronously resolves every open flag raised on the same question or answer as a flag in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `flag` - A flag on the content acted on.
+    /// * `resolution` - How the content was acted on.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the flags resolved, oldest first, on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn resolve_flags(&self, flag: &FlagDetail, resolution: FlagResolution) -> Result<Vec<FlagDetail>, DBError> {
+        let resolved_at = super::now();
+        let mut tx = self.db.begin().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        // Locked, so a moderator resolving the same content meanwhile waits and then finds nothing left to resolve
+        let records = sqlx::query_as::<_, FlagRow>(
+            r#"
+                SELECT * FROM flags
+                WHERE question_uuid = ? AND answer_uuid <=> ? AND resolved_at IS NULL
+                ORDER BY created_at
+                FOR UPDATE
+            "#,
+        ).bind(flag.question_uuid.as_uuid().hyphenated())
+         .bind(flag.answer_uuid.map(|uuid| uuid.as_uuid().hyphenated()))
+         .fetch_all(&mut *tx)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        if records.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut query = QueryBuilder::new("UPDATE flags SET resolution = ");
+        query.push_bind(resolution.as_str()).push(", resolved_at = ").push_bind(resolved_at);
+        query.push(" WHERE flag_uuid IN (");
+        let mut separated = query.separated(", ");
+        for record in &records {
+            separated.push_bind(record.flag_uuid);
+        }
+        separated.push_unseparated(")");
+
+        query
+            .build()
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        tx.commit().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        records
+            .into_iter()
+            .map(|record| {
+                let flag = FlagDetail::try_from(record)?;
+                Ok(FlagDetail { resolution: Some(resolution), resolved_at: Some(resolved_at), ..flag })
+            })
+            .collect()
+    }
+}
+
+/// Implementation of the `HealthCheck` trait for MySQL database.
+pub struct MySqlHealthCheck {
+    db: MySqlPool,
+}
+
+/// Constructor
+impl MySqlHealthCheck {
+    pub fn new(db: MySqlPool) -> Self {
+        MySqlHealthCheck { db }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for MySqlHealthCheck {
+    fn name(&self) -> &'static str {
+        "database"
+    }
+
+    /// Asynchronously runs a trivial query to make sure a connection can be acquired and used.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned if the database answered, otherwise, the error is returned.
+    async fn check(&self) -> Result<(), String> {
+        sqlx::query("SELECT 1")
+            .execute(&self.db)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_recognize_foreign_key_violations() {
+        assert!(is_foreign_key_violation(1452));
+        assert!(is_foreign_key_violation(1216));
+
+        // Duplicate entry and deleting a referenced row are different errors
+        assert!(!is_foreign_key_violation(1062));
+        assert!(!is_foreign_key_violation(1451));
+    }
+}
@@ -0,0 +1,178 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgListener, PgPool};
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::{
+    live::{Feed, LiveEvent},
+    models::{AnswerDetail, QuestionDetail},
+    AppState,
+};
+
+/// Channel the instances notify each other of live events on
+pub const CHANNEL: &str = "question_events";
+
+/// Events waiting to be sent, beyond which new ones are dropped rather than slowing down the handlers
+const PENDING_NOTIFICATIONS: usize = 256;
+
+/// Pause before listening again after the connection could not be restored
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Payload of a notification, which Postgres caps at 8000 bytes. Questions and answers are limited to a few
+/// hundred characters, so events fit comfortably.
+#[derive(Serialize, Deserialize)]
+struct Notification {
+    /// Instance the event was published by, which already delivered it to its own subscribers
+    origin: Uuid,
+    #[serde(flatten)]
+    event: LiveEvent,
+}
+
+/// Shares the live feeds of the state with every other instance doing the same on the database.
+///
+/// Events published from the outbox are sent with `NOTIFY question_events`, and notifications from other instances
+/// are republished to the local subscribers of the feeds. Notifications sent while the connection listening for them
+/// is being restored are lost.
+///
+/// # Arguments
+///
+/// * `pool` - The Postgres connection pool, one connection of which is dedicated to listening.
+/// * `state` - The application state whose feeds are shared.
+///
+/// # Returns
+///
+/// A `Result` containing the state with feeds relaying their events, or the error met when starting to listen.
+pub async fn share_feeds(pool: PgPool, state: AppState) -> Result<AppState, sqlx::Error> {
+    let origin = Uuid::new_v4();
+
+    let mut listener = PgListener::connect_with(&pool).await?;
+    listener.listen(CHANNEL).await?;
+
+    // Handles taken before relaying, so republished events are not sent back
+    let (question_feed, answer_feed) = (state.question_feed.clone(), state.answer_feed.clone());
+    let mut closed = pool.close_event();
+    tokio::spawn(async move {
+        // Stops listening with the pool, rather than keeping the dedicated connection open after it is closed
+        closed.do_until(republish(listener, origin, question_feed, answer_feed)).await
+    });
+
+    let (pending, to_send) = mpsc::channel(PENDING_NOTIFICATIONS);
+    let mut closed = pool.close_event();
+    tokio::spawn(async move { closed.do_until(send_notifications(pool, origin, to_send)).await });
+
+    let question_pending = pending.clone();
+    let question_feed = state.question_feed.relayed(move |question: &QuestionDetail| {
+        queue(&question_pending, LiveEvent::QuestionCreated(question.clone()))
+    });
+    let answer_feed = state.answer_feed.relayed(move |answer: &AnswerDetail| {
+        queue(&pending, LiveEvent::AnswerCreated(answer.clone()))
+    });
+
+    Ok(AppState { question_feed, answer_feed, ..state })
+}
+
+fn queue(pending: &mpsc::Sender<LiveEvent>, event: LiveEvent) {
+    if pending.try_send(event).is_err() {
+        warn!("Dropped a live event, as too many are waiting to be sent to the other instances.");
+    }
+}
+
+async fn send_notifications(pool: PgPool, origin: Uuid, mut to_send: mpsc::Receiver<LiveEvent>) {
+    while let Some(event) = to_send.recv().await {
+        let payload = match serde_json::to_string(&Notification { origin, event }) {
+            Ok(payload) => payload,
+            Err(err) => {
+                error!("Failed to serialize live event: {:?}", err);
+                continue;
+            }
+        };
+
+        let result = sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(CHANNEL)
+            .bind(payload)
+            .execute(&pool)
+            .await;
+
+        if let Err(err) = result {
+            error!("Failed to notify the other instances of a live event: {:?}", err);
+        }
+    }
+}
+
+async fn republish(
+    mut listener: PgListener,
+    origin: Uuid,
+    question_feed: Feed<QuestionDetail>,
+    answer_feed: Feed<AnswerDetail>,
+) {
+    loop {
+        // Reconnects by itself when the connection is lost, and only fails when it cannot
+        let notification = match listener.recv().await {
+            Ok(notification) => notification,
+            Err(err) => {
+                error!("Failed to listen for live events of the other instances: {:?}", err);
+                tokio::time::sleep(RETRY_DELAY).await;
+                continue;
+            }
+        };
+
+        match serde_json::from_str::<Notification>(notification.payload()) {
+            Ok(Notification { origin: from, .. }) if from == origin => {}
+            Ok(Notification { event: LiveEvent::QuestionCreated(question), .. }) => question_feed.publish(question),
+            Ok(Notification { event: LiveEvent::AnswerCreated(answer), .. }) => answer_feed.publish(answer),
+            Err(err) => warn!("Ignored an invalid notification on {}: {:?}", CHANNEL, err),
+        }
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::Utc;
+    use tokio::{sync::broadcast::error::TryRecvError, time::timeout};
+
+    use crate::{config::Config, models::QuestionStatus};
+
+    fn question() -> QuestionDetail {
+        QuestionDetail {
+            question_uuid: Uuid::new_v4().into(),
+            title: "Seen by every instance?".to_owned(),
+            description: "".to_owned(),
+            status: QuestionStatus::Open,
+            created_at: Utc::now(),
+            answer_count: 0,
+            last_activity_at: Utc::now(),
+            view_count: 0,
+            word_count: 0,
+            reading_time_seconds: 0,
+            excerpt: None,
+        }
+    }
+
+    #[sqlx::test]
+    async fn should_republish_events_of_other_instances_once(pool: PgPool) {
+        let first = share_feeds(pool.clone(), AppState::new(pool.clone(), &Config::default())).await.unwrap();
+        let second = share_feeds(pool.clone(), AppState::new(pool, &Config::default())).await.unwrap();
+
+        let mut first_questions = first.question_feed.subscribe();
+        let mut second_questions = second.question_feed.subscribe();
+
+        let question = question();
+        first.question_feed.publish(question.clone());
+
+        let received = timeout(Duration::from_secs(5), second_questions.recv()).await.unwrap().unwrap();
+        assert_eq!(received, question);
+
+        // Delivered locally when published, and not again when its own notification comes back
+        assert_eq!(first_questions.recv().await.unwrap(), question);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(first_questions.try_recv(), Err(TryRecvError::Empty));
+    }
+}
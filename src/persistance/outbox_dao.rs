@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{types::Json, PgConnection, PgPool};
+
+use crate::{models::DBError, outbox::ContentEvent};
+
+use super::memory::{self, MemoryStore};
+
+/// A trait representing data access operations for the outbox, where write events are recorded along with the writes.
+#[async_trait]
+pub trait OutboxDao {
+
+    /// Asynchronously publishes the oldest undelivered events of the outbox, then marks them delivered.
+    ///
+    /// The events are claimed while being published, so that no other instance dispatches them too. They are only
+    /// marked delivered once published, so an instance stopping in between leaves them to be published again.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of events to dispatch.
+    /// * `publish` - Publishes an event, in the order they were recorded.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of events dispatched on success, or a `DBError` on failure.
+    async fn dispatch_events(&self, limit: i64, publish: &(dyn Fn(ContentEvent) + Send + Sync)) -> Result<usize, DBError>;
+
+    /// Asynchronously deletes the events delivered before a given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - Events delivered before this time are deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of events deleted on success, or a `DBError` on failure.
+    async fn delete_delivered_events(&self, before: DateTime<Utc>) -> Result<u64, DBError>;
+}
+
+/// Records an event in the outbox, as part of the transaction making the write it is about.
+///
+/// # Arguments
+///
+/// * `conn` - The connection of the transaction.
+/// * `event` - The event to be recorded.
+///
+/// # Returns
+///
+/// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+pub(crate) async fn record_event(conn: &mut PgConnection, event: &ContentEvent) -> Result<(), DBError> {
+    sqlx::query!("INSERT INTO outbox ( event ) VALUES ( $1 )", Json(event) as _)
+        .execute(conn)
+        .await
+        .map_err(|e| DBError::Other(Box::new(e)))?;
+
+    Ok(())
+}
+
+/// Implementation of the `OutboxDao` trait for PostgreSQL database.
+pub struct OutboxDaoImpl {
+    db: PgPool,
+}
+
+/// Constructor
+impl OutboxDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        OutboxDaoImpl { db }
+    }
+}
+
+#[async_trait]
+impl OutboxDao for OutboxDaoImpl {
+
+    /// Asynchronously publishes the oldest undelivered events of the outbox, then marks them delivered.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of events to dispatch.
+    /// * `publish` - Publishes an event, in the order they were recorded.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of events dispatched on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn dispatch_events(&self, limit: i64, publish: &(dyn Fn(ContentEvent) + Send + Sync)) -> Result<usize, DBError> {
+        let mut tx = self.db.begin().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        // Rows locked by another instance's dispatcher are left to it
+        let records = sqlx::query!(
+            r#"
+                SELECT event_id, event AS "event: Json<ContentEvent>" FROM outbox
+                WHERE delivered_at IS NULL
+                ORDER BY event_id
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            "#,
+            limit
+        ).fetch_all(&mut *tx).await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let event_ids: Vec<i64> = records.iter().map(|r| r.event_id).collect();
+        for record in records {
+            publish(record.event.0);
+        }
+
+        sqlx::query!("UPDATE outbox SET delivered_at = now() WHERE event_id = ANY($1)", &event_ids)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        tx.commit().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(event_ids.len())
+    }
+
+    /// Asynchronously deletes the events delivered before a given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - Events delivered before this time are deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of events deleted on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn delete_delivered_events(&self, before: DateTime<Utc>) -> Result<u64, DBError> {
+        let result = sqlx::query!("DELETE FROM outbox WHERE delivered_at < $1", before.naive_utc())
+            .execute(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Implementation of the `OutboxDao` trait draining the events recorded in memory, for local
+/// development and tests.
+pub struct OutboxDaoInMemory {
+    store: Arc<MemoryStore>,
+}
+
+/// Constructor
+impl OutboxDaoInMemory {
+    pub fn new(store: Arc<MemoryStore>) -> Self {
+        OutboxDaoInMemory { store }
+    }
+}
+
+#[async_trait]
+impl OutboxDao for OutboxDaoInMemory {
+
+    /// Asynchronously publishes the oldest events recorded in memory, which are removed as they are.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of events to dispatch.
+    /// * `publish` - Publishes an event, in the order they were recorded.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of events dispatched on success, or a `DBError` on failure.
+    async fn dispatch_events(&self, limit: i64, publish: &(dyn Fn(ContentEvent) + Send + Sync)) -> Result<usize, DBError> {
+        let events: Vec<ContentEvent> = {
+            let mut outbox = self.store.outbox.write().map_err(memory::poisoned)?;
+            let count = outbox.len().min(limit.max(0) as usize);
+            outbox.drain(..count).collect()
+        };
+
+        let count = events.len();
+        events.into_iter().for_each(publish);
+
+        Ok(count)
+    }
+
+    /// Delivered events are not kept in memory, so there is nothing to delete.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing 0 on success.
+    async fn delete_delivered_events(&self, _before: DateTime<Utc>) -> Result<u64, DBError> {
+        Ok(0)
+    }
+}
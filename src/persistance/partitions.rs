@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tokio::{
+    task::JoinHandle,
+    time::{Instant, MissedTickBehavior},
+};
+
+/// Partitions the answers table is split into, by hash of their question
+pub const ANSWERS_PARTITIONS: usize = 16;
+
+/// Estimated number of rows of a partition, as of its last analysis
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartitionSize {
+    pub partition: String,
+    pub rows: i64,
+}
+
+/// Refreshes the planner statistics of the partitioned answers table, and reports the size of each partition.
+///
+/// Autovacuum analyzes the partitions, but never the partitioned table itself, whose statistics the planner relies
+/// on when joining questions with their answers. They are only kept up to date by analyzing it explicitly.
+///
+/// # Arguments
+///
+/// * `pool` - The Postgres connection pool.
+///
+/// # Returns
+///
+/// A `Result` containing the size of every partition on success, or the error met analyzing the table.
+pub async fn maintain_answers(pool: &PgPool) -> Result<Vec<PartitionSize>, sqlx::Error> {
+    sqlx::query("ANALYZE answers").execute(pool).await?;
+
+    sqlx::query_as!(
+        PartitionSize,
+        r#"
+            SELECT c.relname::text AS "partition!", c.reltuples::bigint AS "rows!"
+            FROM pg_inherits i
+            JOIN pg_class c ON c.oid = i.inhrelid
+            WHERE i.inhparent = 'answers'::regclass
+            ORDER BY c.relname
+        "#
+    ).fetch_all(pool).await
+}
+
+/// Maintains the partitioned answers table periodically, starting one interval from now.
+///
+/// # Arguments
+///
+/// * `pool` - The Postgres connection pool, which the maintenance stops with when it is closed.
+/// * `every` - The interval between two maintenances.
+///
+/// # Returns
+///
+/// A `JoinHandle` of the task maintaining the table.
+pub fn schedule(pool: PgPool, every: Duration) -> JoinHandle<()> {
+    let mut closed = pool.close_event();
+
+    tokio::spawn(async move {
+        // Result only tells whether the pool was closed, which is the way this stops
+        let _ = closed.do_until(maintain_periodically(pool, every)).await;
+    })
+}
+
+async fn maintain_periodically(pool: PgPool, every: Duration) {
+    let mut ticks = tokio::time::interval_at(Instant::now() + every, every);
+    // A maintenance running late is not worth catching up on
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        ticks.tick().await;
+
+        match maintain_answers(&pool).await {
+            Ok(sizes) => {
+                let rows: i64 = sizes.iter().map(|size| size.rows).sum();
+                let largest = sizes.iter().max_by_key(|size| size.rows);
+
+                if let Some(largest) = largest {
+                    info!(
+                        "Analyzed answers: {} rows across {} partitions, the largest being {} with {}.",
+                        rows, sizes.len(), largest.partition, largest.rows
+                    );
+                }
+            }
+            Err(err) => error!("Failed to maintain the answers partitions: {:?}", err),
+        }
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sqlx::{Executor, PgConnection};
+    use uuid::Uuid;
+
+    async fn questions_with_answers(pool: &PgPool, questions: usize, answers: usize) -> Vec<Uuid> {
+        let mut uuids = Vec::new();
+
+        for _ in 0..questions {
+            let uuid: Uuid = sqlx::query_scalar(
+                "INSERT INTO questions ( title, description ) VALUES ( 'title', 'description' ) RETURNING question_uuid",
+            ).fetch_one(pool).await.unwrap();
+
+            for _ in 0..answers {
+                sqlx::query("INSERT INTO answers ( question_uuid, content ) VALUES ( $1, 'content' )")
+                    .bind(uuid)
+                    .execute(pool)
+                    .await
+                    .unwrap();
+            }
+
+            uuids.push(uuid);
+        }
+
+        uuids
+    }
+
+    /// Explains a query with the plan a prepared statement ends up with, which is only pruned at execution.
+    async fn explain_generic(conn: &mut PgConnection, query: &str, arguments: &str) -> String {
+        conn.execute("SET plan_cache_mode = force_generic_plan").await.unwrap();
+        conn.execute(format!("PREPARE statement AS {}", query).as_str()).await.unwrap();
+
+        let lines: Vec<String> = sqlx::query_scalar(&format!(
+            "EXPLAIN (ANALYZE, COSTS OFF, TIMING OFF, SUMMARY OFF) EXECUTE statement({})",
+            arguments
+        )).fetch_all(&mut *conn).await.unwrap();
+
+        lines.join("\n")
+    }
+
+    /// Counts the partitions of the answers table scanned by an explained query.
+    fn scanned_partitions(plan: &str) -> usize {
+        plan.lines()
+            .filter(|line| line.contains(" on answers_p"))
+            // Bitmap index scans are listed under the scan of their partition
+            .filter(|line| !line.contains("Bitmap Index Scan") && !line.contains("never executed"))
+            .count()
+    }
+
+    #[sqlx::test]
+    async fn should_report_the_rows_of_every_partition(pool: PgPool) {
+        questions_with_answers(&pool, 10, 3).await;
+
+        let sizes = maintain_answers(&pool).await.unwrap();
+
+        assert_eq!(sizes.len(), ANSWERS_PARTITIONS);
+        assert_eq!(sizes.iter().map(|size| size.rows).sum::<i64>(), 30);
+    }
+
+    #[sqlx::test]
+    async fn should_read_the_answers_of_a_question_from_its_partition_only(pool: PgPool) {
+        let uuids = questions_with_answers(&pool, 20, 2).await;
+        let mut conn = pool.acquire().await.unwrap();
+
+        // Same query as AnswersDao::get_answers
+        let plan = explain_generic(
+            &mut conn,
+            "SELECT * FROM answers WHERE question_uuid = $1 ORDER BY created_at",
+            &format!("'{}'", uuids[0]),
+        ).await;
+
+        assert_eq!(scanned_partitions(&plan), 1, "{}", plan);
+    }
+
+    #[sqlx::test]
+    async fn should_read_the_answers_of_several_questions_from_their_partitions_only(pool: PgPool) {
+        let uuids = questions_with_answers(&pool, 20, 2).await;
+        let mut conn = pool.acquire().await.unwrap();
+
+        // Nested loops over the index are what the planner picks once the table is large, rather than hashing or
+        // scanning every answer
+        conn.execute("SET enable_hashjoin = off; SET enable_mergejoin = off; SET enable_seqscan = off").await.unwrap();
+
+        // Same query as AnswersDao::get_answers_for_questions
+        let plan = explain_generic(
+            &mut conn,
+            r#"
+                SELECT a.* FROM (SELECT DISTINCT unnest($1::uuid[])) AS u (question_uuid)
+                JOIN answers a ON a.question_uuid = u.question_uuid
+                ORDER BY a.created_at
+            "#,
+            &format!("ARRAY['{}', '{}']::uuid[]", uuids[0], uuids[1]),
+        ).await;
+
+        assert!((1..=2).contains(&scanned_partitions(&plan)), "{}", plan);
+    }
+
+    #[sqlx::test]
+    async fn should_keep_answer_uuids_unique_across_partitions(pool: PgPool) {
+        let uuids = questions_with_answers(&pool, 2, 0).await;
+        let answer_uuid = Uuid::new_v4();
+        let insert = "INSERT INTO answers ( answer_uuid, question_uuid, content ) VALUES ( $1, $2, 'content' )";
+
+        sqlx::query(insert).bind(answer_uuid).bind(uuids[0]).execute(&pool).await.unwrap();
+
+        let error = sqlx::query(insert).bind(answer_uuid).bind(uuids[1]).execute(&pool).await.unwrap_err();
+        let code = error.as_database_error().and_then(|e| e.code()).map(|code| code.into_owned());
+        assert_eq!(code.as_deref(), Some("23505"));
+
+        // Free again once the answer is deleted, here along with its question
+        sqlx::query("DELETE FROM questions WHERE question_uuid = $1").bind(uuids[0]).execute(&pool).await.unwrap();
+        sqlx::query(insert).bind(answer_uuid).bind(uuids[1]).execute(&pool).await.unwrap();
+    }
+}
@@ -1,7 +1,23 @@
+use std::{collections::HashMap, sync::Arc};
+
 use async_trait::async_trait;
-use sqlx::PgPool;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::{types::Uuid, FromRow, PgPool, QueryBuilder};
+
+use crate::{
+    models::{
+        postgres_error_codes, AnswerSort, DBError, Question, QuestionDetail, QuestionFilter, QuestionId,
+        QuestionSearchResult, QuestionSort, QuestionStatus, QuestionUuid, QuestionWithAnswers,
+    },
+    outbox::ContentEvent,
+    reading_time, sanitize,
+};
 
-use crate::models::{DBError, Question, QuestionDetail};
+use super::{
+    answers_dao,
+    memory::{self, MemoryStore},
+    outbox_dao, search,
+};
 
 /// A trait representing data access operations for questions in the database.
 #[async_trait]
@@ -26,14 +42,141 @@ pub trait QuestionsDao {
     /// # Returns
     ///
     /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
-    async fn delete_question(&self, question_uuid: String) -> Result<(), DBError>;
+    async fn delete_question(&self, question_uuid: QuestionUuid) -> Result<(), DBError>;
 
-    /// Asynchronously retrieves all questions from the database.
+    /// Asynchronously retrieves all questions from the database, along with the number of answers each has and
+    /// when it was last answered.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The conditions the questions must meet, and their order. Questions are retrieved oldest first
+    ///   unless sorted otherwise.
     ///
     /// # Returns
     ///
     /// A `Result` containing a vector of question details on success, or a `DBError` on failure.
-    async fn get_questions(&self) -> Result<Vec<QuestionDetail>, DBError>;
+    async fn get_questions(&self, filter: &QuestionFilter) -> Result<Vec<QuestionDetail>, DBError>;
+
+    /// Asynchronously retrieves several questions at once, along with the number of answers each has and when it
+    /// was last answered.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuids` - The unique identifiers of the questions.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the details of the questions that exist, oldest first, on success, or a `DBError` on
+    /// failure.
+    async fn get_questions_by_uuids(&self, question_uuids: Vec<QuestionUuid>) -> Result<Vec<QuestionDetail>, DBError>;
+
+    /// Asynchronously retrieves a page of every question, in the order of their unique identifiers, for jobs going
+    /// through all of them. Questions created while paging may be left out, but none is listed twice.
+    ///
+    /// # Arguments
+    ///
+    /// * `after` - The unique identifier of the last question of the previous page, `None` for the first page.
+    /// * `limit` - The maximum number of questions to return.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the details of the questions on success, or a `DBError` on failure. The page is empty
+    /// past the last question.
+    async fn get_questions_after(
+        &self,
+        after: Option<QuestionUuid>,
+        limit: i64,
+    ) -> Result<Vec<QuestionDetail>, DBError>;
+
+    /// Asynchronously retrieves the most viewed questions, along with the number of answers each has and when it was
+    /// last answered.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of questions to return.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the details of the questions, most viewed first and oldest first among those viewed as
+    /// many times, on success, or a `DBError` on failure.
+    async fn get_popular_questions(&self, limit: i64) -> Result<Vec<QuestionDetail>, DBError>;
+
+    /// Asynchronously retrieves a question along with all its answers, so a question page needs a single call.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the question, its answer count and its answers, oldest first, on success, or a
+    /// `DBError` on failure. The error is `DBError::InvalidUUID` if the question does not exist.
+    async fn get_question_with_answers(&self, question_uuid: QuestionUuid) -> Result<QuestionWithAnswers, DBError>;
+
+    /// Asynchronously changes the status of a question, provided it currently has the expected status.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    /// * `from` - The status the question must have.
+    /// * `to` - The new status of the question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated question detail on success, or a `DBError` on failure. The error is
+    /// `DBError::InvalidUUID` if the question does not exist, and `DBError::Conflict` if it has another status.
+    async fn update_question_status(
+        &self,
+        question_uuid: QuestionUuid,
+        from: QuestionStatus,
+        to: QuestionStatus,
+    ) -> Result<QuestionDetail, DBError>;
+
+    /// Asynchronously adds views to the view counts of questions, in a single transaction however many questions
+    /// were viewed.
+    ///
+    /// # Arguments
+    ///
+    /// * `views` - The number of views of each question, questions that no longer exist being skipped.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    async fn add_question_views(&self, views: &HashMap<QuestionUuid, i64>) -> Result<(), DBError>;
+
+    /// Asynchronously searches the titles and descriptions of questions.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The words to search for, all of which must match.
+    /// * `limit` - The maximum number of results to return.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the matching questions, best match first, on success, or a `DBError` on failure.
+    async fn search_questions(&self, query: String, limit: i64) -> Result<Vec<QuestionSearchResult>, DBError>;
+
+    /// Asynchronously reads the current time of the clock questions are stamped with when they are created.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the current time on success, or a `DBError` on failure.
+    async fn current_time(&self) -> Result<DateTime<Utc>, DBError>;
+}
+
+/// A question listed by `get_questions`, whose query is built at runtime
+#[derive(FromRow)]
+struct QuestionRow {
+    question_uuid: Uuid,
+    title: String,
+    description: String,
+    status: QuestionStatus,
+    created_at: NaiveDateTime,
+    answer_count: i64,
+    last_activity_at: NaiveDateTime,
+    view_count: i64,
+    word_count: i64,
+    reading_time_seconds: i64,
 }
 
 /// Implementation of the `QuestionsDao` trait for PostgreSQL database.
@@ -60,26 +203,59 @@ impl QuestionsDao for QuestionsDaoImpl {
     /// # Returns
     ///
     /// A `Result` containing the newly created question detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
     async fn create_question(&self, question: Question) -> Result<QuestionDetail, DBError> {
+        let mut tx = self.db.begin().await.map_err(|e| DBError::Other(Box::new(e)))?;
+        let word_count = question.word_count();
 
-        // Insert record into DB
+        // Insert record into DB, with the UUID chosen by the client if any. A UUID already taken
+        // violates the primary key, which is reported as a conflict.
         let record = sqlx::query!(
             r#"
-                INSERT INTO questions ( title, description )
-                VALUES ( $1, $2 )
-                RETURNING *
+                INSERT INTO questions ( question_uuid, title, description, word_count, reading_time_seconds )
+                VALUES ( COALESCE($1, gen_random_uuid()), $2, $3, $4, $5 )
+                RETURNING question_uuid, title, description, status AS "status: QuestionStatus", created_at,
+                          word_count, reading_time_seconds
             "#,
+            question.question_uuid.map(|uuid| *uuid.as_uuid()),
             question.title,
-            question.description
-        ).fetch_one(&self.db).await.map_err(|e| DBError::Other(Box::new(e)))?;
+            question.description,
+            word_count,
+            reading_time::reading_time_seconds(word_count)
+        ).fetch_one(&mut *tx)
+         .await
+         .map_err(|e: sqlx::Error| match e {
+            sqlx::Error::Database(e) => {
+                if let (Some(code), Some(uuid)) = (e.code(), question.question_uuid) {
+                    if code.eq(postgres_error_codes::UNIQUE_VIOLATION) {
+                        return super::already_exists("Question", uuid);
+                    }
+                }
+                DBError::Other(Box::new(e))
+            }
+            e => DBError::Other(Box::new(e)),
+         })?;
 
-        // Return created record
-        Ok(QuestionDetail {
-            question_uuid: record.question_uuid.to_string(),
+        let detail = QuestionDetail {
+            question_uuid: record.question_uuid.into(),
             title: record.title,
             description: record.description,
-            created_at: record.created_at.to_string(),
-        })
+            status: record.status,
+            created_at: record.created_at.and_utc(),
+            answer_count: 0,
+            last_activity_at: record.created_at.and_utc(),
+            view_count: 0,
+            word_count: record.word_count,
+            reading_time_seconds: record.reading_time_seconds,
+            excerpt: None,
+        };
+
+        // Published once committed, along with the question
+        outbox_dao::record_event(&mut tx, &ContentEvent::QuestionCreated(detail.clone())).await?;
+        tx.commit().await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        // Return created record
+        Ok(detail)
     }
 
     /// Asynchronously deletes a question from the database.
@@ -91,41 +267,749 @@ impl QuestionsDao for QuestionsDaoImpl {
     /// # Returns
     ///
     /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
-    async fn delete_question(&self, question_uuid: String) -> Result<(), DBError> {
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn delete_question(&self, question_uuid: QuestionUuid) -> Result<(), DBError> {
 
-        // Attempt to get the question UUID, make sure it is valid
-        let uuid = sqlx::types::Uuid::parse_str(&question_uuid).map_err(|_| {
-            DBError::InvalidUUID(format!("Could not parse question UUID: {}", question_uuid))
-        })?;
+        let mut tx = self.db.begin().await.map_err(|e| DBError::Other(Box::new(e)))?;
 
         // Delete ID from DB
-        sqlx::query!("DELETE FROM questions WHERE question_uuid = $1", uuid).execute(&self.db)
-                                                                            .await
-                                                                            .map_err(|e| DBError::Other(Box::new(e)))?;
+        let result = sqlx::query!("DELETE FROM questions WHERE question_uuid = $1", question_uuid.as_uuid())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        // Deleting a question that is already gone is not an event
+        if result.rows_affected() > 0 {
+            outbox_dao::record_event(&mut tx, &ContentEvent::QuestionDeleted(QuestionId { question_uuid })).await?;
+        }
+        tx.commit().await.map_err(|e| DBError::Other(Box::new(e)))?;
 
         Ok(())
     }
 
     /// Asynchronously retrieves all questions for a UUID from the database.
     ///
+    /// # Arguments
+    ///
+    /// * `filter` - The conditions the questions must meet, and their order.
+    ///
     /// # Returns
     ///
     /// A `Result` containing a vector of question details on success, or a `DBError` on failure.
-    async fn get_questions(&self) -> Result<Vec<QuestionDetail>, DBError> {
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_questions(&self, filter: &QuestionFilter) -> Result<Vec<QuestionDetail>, DBError> {
+
+        // Get all questions from DB, counting their answers in the same query. Only the conditions set are part of
+        // the query, so it is built at runtime rather than checked by `query!`.
+        let mut query = QueryBuilder::new(
+            "SELECT q.question_uuid, q.title, q.description, q.status, q.created_at,
+                    COUNT(a.answer_uuid) AS answer_count,
+                    COALESCE(MAX(a.created_at), q.created_at) AS last_activity_at, q.view_count, q.word_count,
+                    q.reading_time_seconds
+             FROM questions q
+             LEFT JOIN answers a ON a.question_uuid = q.question_uuid
+             WHERE TRUE",
+        );
+
+        if let Some(status) = filter.status {
+            query.push(" AND q.status = ").push_bind(status);
+        }
+        if let Some(created_after) = filter.created_after {
+            query.push(" AND q.created_at > ").push_bind(created_after.naive_utc());
+        }
+        if let Some(created_before) = filter.created_before {
+            query.push(" AND q.created_at < ").push_bind(created_before.naive_utc());
+        }
+        if let Some(title) = &filter.title_contains {
+            query.push(" AND q.title ILIKE ").push_bind(super::contains_pattern(title)).push(" ESCAPE '!'");
+        }
+        if let Some(answered) = filter.answered {
+            query.push(if answered { " AND EXISTS" } else { " AND NOT EXISTS" });
+            query.push(" (SELECT 1 FROM answers u WHERE u.question_uuid = q.question_uuid)");
+        }
 
-        // Get all questions from DB
-        let records = sqlx::query!("SELECT * FROM questions").fetch_all(&self.db)
-                                                                          .await
-                                                                          .map_err(|e| DBError::Other(Box::new(e)))?;
+        // The order is a parameter like any other, each `CASE` only sorting for its own order and leaving ties to
+        // the ones after it
+        let sort = filter.sort.unwrap_or_default().as_str();
+        query.push(" GROUP BY q.question_uuid ORDER BY CASE WHEN ").push_bind(sort);
+        query.push(" = 'newest' THEN q.created_at END DESC, CASE WHEN ").push_bind(sort);
+        query.push(" = 'most_answered' THEN COUNT(a.answer_uuid) END DESC, q.created_at");
+
+        let records = query
+            .build_query_as::<QuestionRow>()
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
 
         // Put the records in an array of QuestionDetail
-        let questions = records.iter().map(|r| QuestionDetail {
-            question_uuid: r.question_uuid.to_string(),
-            title: r.title.clone(),
-            description: r.description.clone(),
-            created_at: r.created_at.to_string(),
+        let questions = records.into_iter().map(|r| QuestionDetail {
+            question_uuid: r.question_uuid.into(),
+            title: r.title,
+            description: r.description,
+            status: r.status,
+            created_at: r.created_at.and_utc(),
+            answer_count: r.answer_count,
+            last_activity_at: r.last_activity_at.and_utc(),
+            view_count: r.view_count,
+            word_count: r.word_count,
+            reading_time_seconds: r.reading_time_seconds,
+            excerpt: None,
         }).collect();
 
         Ok(questions)
     }
-}
\ No newline at end of file
+
+    /// Asynchronously retrieves several questions from the database, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuids` - The unique identifiers of the questions.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the details of the questions that exist on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_questions_by_uuids(&self, question_uuids: Vec<QuestionUuid>) -> Result<Vec<QuestionDetail>, DBError> {
+        let uuids: Vec<Uuid> = question_uuids.iter().map(|uuid| *uuid.as_uuid()).collect();
+
+        let records = sqlx::query!(
+            r#"
+                SELECT q.question_uuid, q.title, q.description, q.status AS "status: QuestionStatus", q.created_at,
+                       COUNT(a.answer_uuid) AS "answer_count!",
+                       COALESCE(MAX(a.created_at), q.created_at) AS "last_activity_at!", q.view_count, q.word_count,
+                       q.reading_time_seconds
+                FROM questions q
+                LEFT JOIN answers a ON a.question_uuid = q.question_uuid
+                WHERE q.question_uuid = ANY($1)
+                GROUP BY q.question_uuid
+                ORDER BY q.created_at
+            "#,
+            &uuids
+        ).fetch_all(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let questions = records.into_iter().map(|r| QuestionDetail {
+            question_uuid: r.question_uuid.into(),
+            title: r.title,
+            description: r.description,
+            status: r.status,
+            created_at: r.created_at.and_utc(),
+            answer_count: r.answer_count,
+            last_activity_at: r.last_activity_at.and_utc(),
+            view_count: r.view_count,
+            word_count: r.word_count,
+            reading_time_seconds: r.reading_time_seconds,
+            excerpt: None,
+        }).collect();
+
+        Ok(questions)
+    }
+
+    /// Asynchronously retrieves a page of every question from the database, in the order of their unique
+    /// identifiers.
+    ///
+    /// # Arguments
+    ///
+    /// * `after` - The unique identifier of the last question of the previous page, `None` for the first page.
+    /// * `limit` - The maximum number of questions to return.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the details of the questions on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_questions_after(
+        &self,
+        after: Option<QuestionUuid>,
+        limit: i64,
+    ) -> Result<Vec<QuestionDetail>, DBError> {
+        let records = sqlx::query!(
+            r#"
+                SELECT q.question_uuid, q.title, q.description, q.status AS "status: QuestionStatus", q.created_at,
+                       COUNT(a.answer_uuid) AS "answer_count!",
+                       COALESCE(MAX(a.created_at), q.created_at) AS "last_activity_at!", q.view_count, q.word_count,
+                       q.reading_time_seconds
+                FROM questions q
+                LEFT JOIN answers a ON a.question_uuid = q.question_uuid
+                WHERE $1::uuid IS NULL OR q.question_uuid > $1
+                GROUP BY q.question_uuid
+                ORDER BY q.question_uuid
+                LIMIT $2
+            "#,
+            after.map(Uuid::from),
+            limit
+        ).fetch_all(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let questions = records.into_iter().map(|r| QuestionDetail {
+            question_uuid: r.question_uuid.into(),
+            title: r.title,
+            description: r.description,
+            status: r.status,
+            created_at: r.created_at.and_utc(),
+            answer_count: r.answer_count,
+            last_activity_at: r.last_activity_at.and_utc(),
+            view_count: r.view_count,
+            word_count: r.word_count,
+            reading_time_seconds: r.reading_time_seconds,
+            excerpt: None,
+        }).collect();
+
+        Ok(questions)
+    }
+
+    /// Asynchronously retrieves the most viewed questions from the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of questions to return.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the details of the questions, most viewed first, on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_popular_questions(&self, limit: i64) -> Result<Vec<QuestionDetail>, DBError> {
+        let records = sqlx::query!(
+            r#"
+                SELECT q.question_uuid, q.title, q.description, q.status AS "status: QuestionStatus", q.created_at,
+                       COUNT(a.answer_uuid) AS "answer_count!",
+                       COALESCE(MAX(a.created_at), q.created_at) AS "last_activity_at!", q.view_count, q.word_count,
+                       q.reading_time_seconds
+                FROM questions q
+                LEFT JOIN answers a ON a.question_uuid = q.question_uuid
+                GROUP BY q.question_uuid
+                ORDER BY q.view_count DESC, q.created_at
+                LIMIT $1
+            "#,
+            limit
+        ).fetch_all(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let questions = records.into_iter().map(|r| QuestionDetail {
+            question_uuid: r.question_uuid.into(),
+            title: r.title,
+            description: r.description,
+            status: r.status,
+            created_at: r.created_at.and_utc(),
+            answer_count: r.answer_count,
+            last_activity_at: r.last_activity_at.and_utc(),
+            view_count: r.view_count,
+            word_count: r.word_count,
+            reading_time_seconds: r.reading_time_seconds,
+            excerpt: None,
+        }).collect();
+
+        Ok(questions)
+    }
+
+    /// Asynchronously retrieves a question along with all its answers from the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the question and its answers, oldest first, on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_question_with_answers(&self, question_uuid: QuestionUuid) -> Result<QuestionWithAnswers, DBError> {
+
+        // The two queries run concurrently, on two connections of the pool sharing a snapshot, so the answers listed
+        // are those counted even while another is being created
+        let (mut question_tx, mut answers_tx) = super::begin_shared_snapshot(&self.db).await?;
+
+        let (question, answers) = tokio::try_join!(
+            async {
+                sqlx::query!(
+                    r#"
+                        SELECT q.question_uuid, q.title, q.description, q.status AS "status: QuestionStatus", q.created_at,
+                               COUNT(a.answer_uuid) AS "answer_count!",
+                               COALESCE(MAX(a.created_at), q.created_at) AS "last_activity_at!", q.view_count,
+                               q.word_count, q.reading_time_seconds
+                        FROM questions q
+                        LEFT JOIN answers a ON a.question_uuid = q.question_uuid
+                        WHERE q.question_uuid = $1
+                        GROUP BY q.question_uuid
+                    "#,
+                    question_uuid.as_uuid()
+                ).fetch_optional(&mut *question_tx).await.map_err(|e| DBError::Other(Box::new(e)))
+            },
+            answers_dao::fetch_answers(&mut *answers_tx, question_uuid, AnswerSort::Oldest)
+        )?;
+
+        // Both only read, so there is nothing to commit
+        tokio::try_join!(question_tx.rollback(), answers_tx.rollback()).map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let question = question.ok_or_else(|| super::unknown_question(question_uuid))?;
+
+        Ok(QuestionWithAnswers {
+            question: QuestionDetail {
+                question_uuid: question.question_uuid.into(),
+                title: question.title,
+                description: question.description,
+                status: question.status,
+                created_at: question.created_at.and_utc(),
+                answer_count: question.answer_count,
+                last_activity_at: question.last_activity_at.and_utc(),
+                view_count: question.view_count,
+                word_count: question.word_count,
+                reading_time_seconds: question.reading_time_seconds,
+                excerpt: None,
+            },
+            answers,
+        })
+    }
+
+    /// Asynchronously changes the status of a question, provided it currently has the expected status.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    /// * `from` - The status the question must have.
+    /// * `to` - The new status of the question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated question detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn update_question_status(
+        &self,
+        question_uuid: QuestionUuid,
+        from: QuestionStatus,
+        to: QuestionStatus,
+    ) -> Result<QuestionDetail, DBError> {
+
+        // Checking the current status in the update itself makes concurrent changes safe
+        let record = sqlx::query!(
+            r#"
+                WITH updated AS (
+                    UPDATE questions SET status = $3
+                    WHERE question_uuid = $1 AND status = $2
+                    RETURNING question_uuid, title, description, status, created_at, view_count, word_count,
+                              reading_time_seconds
+                )
+                SELECT u.question_uuid AS "question_uuid!", u.title AS "title!", u.description AS "description!",
+                       u.status AS "status!: QuestionStatus", u.created_at AS "created_at!",
+                       COUNT(a.answer_uuid) AS "answer_count!",
+                       COALESCE(MAX(a.created_at), u.created_at) AS "last_activity_at!", u.view_count AS "view_count!",
+                       u.word_count AS "word_count!", u.reading_time_seconds AS "reading_time_seconds!"
+                FROM updated u
+                LEFT JOIN answers a ON a.question_uuid = u.question_uuid
+                GROUP BY u.question_uuid, u.title, u.description, u.status, u.created_at, u.view_count, u.word_count,
+                         u.reading_time_seconds
+            "#,
+            question_uuid.as_uuid(),
+            from as QuestionStatus,
+            to as QuestionStatus
+        ).fetch_optional(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        if let Some(record) = record {
+            return Ok(QuestionDetail {
+                question_uuid: record.question_uuid.into(),
+                title: record.title,
+                description: record.description,
+                status: record.status,
+                created_at: record.created_at.and_utc(),
+                answer_count: record.answer_count,
+                last_activity_at: record.last_activity_at.and_utc(),
+                view_count: record.view_count,
+                word_count: record.word_count,
+                reading_time_seconds: record.reading_time_seconds,
+                excerpt: None,
+            });
+        }
+
+        // Nothing was updated, find out why
+        let status = sqlx::query_scalar!(
+            r#"SELECT status AS "status: QuestionStatus" FROM questions WHERE question_uuid = $1"#,
+            question_uuid.as_uuid()
+        ).fetch_optional(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Err(super::unexpected_status(question_uuid, status))
+    }
+
+    /// Asynchronously adds views to the view counts of questions in the database, in a single `UPDATE`.
+    ///
+    /// # Arguments
+    ///
+    /// * `views` - The number of views of each question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn add_question_views(&self, views: &HashMap<QuestionUuid, i64>) -> Result<(), DBError> {
+        // Passed as two arrays, of the unique identifiers of the questions and of their views, in the same order
+        let uuids: Vec<Uuid> = views.keys().map(|uuid| Uuid::from(*uuid)).collect();
+        let counts: Vec<i64> = views.values().copied().collect();
+
+        sqlx::query!(
+            r#"
+                UPDATE questions q SET view_count = q.view_count + v.views
+                FROM UNNEST($1::uuid[], $2::bigint[]) AS v (question_uuid, views)
+                WHERE q.question_uuid = v.question_uuid
+            "#,
+            &uuids,
+            &counts
+        ).execute(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    /// Asynchronously searches questions using the `search_vector` full-text index.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The words to search for, in `websearch_to_tsquery` syntax.
+    /// * `limit` - The maximum number of results to return.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the matching questions, best match first, on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn search_questions(&self, query: String, limit: i64) -> Result<Vec<QuestionSearchResult>, DBError> {
+        let records = sqlx::query!(
+            r#"
+                SELECT q.question_uuid, q.title, q.description, q.status AS "status: QuestionStatus", q.created_at,
+                       COUNT(a.answer_uuid) AS "answer_count!",
+                       COALESCE(MAX(a.created_at), q.created_at) AS "last_activity_at!", q.view_count, q.word_count,
+                       q.reading_time_seconds,
+                       ts_rank(q.search_vector, query) AS "rank!",
+                       ts_headline('english', q.title || ' ' || q.description, query,
+                                   'StartSel=<mark>, StopSel=</mark>, MaxWords=30, MinWords=10') AS "snippet!"
+                FROM questions q
+                CROSS JOIN websearch_to_tsquery('english', $1) query
+                LEFT JOIN answers a ON a.question_uuid = q.question_uuid
+                WHERE q.search_vector @@ query
+                GROUP BY q.question_uuid, query
+                ORDER BY ts_rank(q.search_vector, query) DESC, q.created_at DESC
+                LIMIT $2
+            "#,
+            query,
+            limit
+        ).fetch_all(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let results = records.into_iter().map(|r| QuestionSearchResult {
+            question: QuestionDetail {
+                question_uuid: r.question_uuid.into(),
+                title: r.title,
+                description: r.description,
+                status: r.status,
+                created_at: r.created_at.and_utc(),
+                answer_count: r.answer_count,
+                last_activity_at: r.last_activity_at.and_utc(),
+                view_count: r.view_count,
+                word_count: r.word_count,
+                reading_time_seconds: r.reading_time_seconds,
+                excerpt: None,
+            },
+            rank: r.rank,
+            // `ts_headline` does not escape the question text
+            snippet: sanitize::sanitize_snippet(&r.snippet),
+        }).collect();
+
+        Ok(results)
+    }
+
+    /// Asynchronously reads the current time of the database, which stamps questions with `CURRENT_TIMESTAMP` in
+    /// the time zone of the session, as `LOCALTIMESTAMP` does.
+    async fn current_time(&self) -> Result<DateTime<Utc>, DBError> {
+        let now = sqlx::query_scalar!(r#"SELECT LOCALTIMESTAMP AS "now!""#)
+            .fetch_one(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(now.and_utc())
+    }
+}
+
+/// Implementation of the `QuestionsDao` trait keeping questions in memory, for local
+/// development and tests.
+pub struct QuestionsDaoInMemory {
+    store: Arc<MemoryStore>,
+}
+
+/// Constructor
+impl QuestionsDaoInMemory {
+    pub fn new(store: Arc<MemoryStore>) -> Self {
+        QuestionsDaoInMemory { store }
+    }
+}
+
+#[async_trait]
+impl QuestionsDao for QuestionsDaoInMemory {
+
+    /// Asynchronously creates a new question in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `question` - The question to be created.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly created question detail on success, or a `DBError` on failure.
+    async fn create_question(&self, question: Question) -> Result<QuestionDetail, DBError> {
+        let uuid = question.question_uuid.unwrap_or_else(QuestionUuid::new_v4);
+        let word_count = question.word_count();
+
+        let created_at = super::now();
+
+        let detail = QuestionDetail {
+            question_uuid: uuid,
+            title: question.title,
+            description: question.description,
+            status: QuestionStatus::Open,
+            created_at,
+            answer_count: 0,
+            last_activity_at: created_at,
+            view_count: 0,
+            word_count,
+            reading_time_seconds: reading_time::reading_time_seconds(word_count),
+            excerpt: None,
+        };
+
+        let row = self.store.row(detail.clone());
+        let mut questions = self.store.questions.write().map_err(memory::poisoned)?;
+        let mut outbox = self.store.outbox.write().map_err(memory::poisoned)?;
+
+        if questions.contains_key(&uuid) {
+            return Err(super::already_exists("Question", uuid));
+        }
+        questions.insert(uuid, row);
+        outbox.push_back(ContentEvent::QuestionCreated(detail.clone()));
+
+        Ok(detail)
+    }
+
+    /// Asynchronously deletes a question and its answers from memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question to be deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    async fn delete_question(&self, question_uuid: QuestionUuid) -> Result<(), DBError> {
+        let mut questions = self.store.questions.write().map_err(memory::poisoned)?;
+        let mut answers = self.store.answers.write().map_err(memory::poisoned)?;
+        let mut webhooks = self.store.question_webhooks.write().map_err(memory::poisoned)?;
+        let mut outbox = self.store.outbox.write().map_err(memory::poisoned)?;
+
+        // Answers and webhooks are deleted along with their question, like the `ON DELETE CASCADE` in Postgres
+        if questions.remove(&question_uuid).is_some() {
+            answers.retain(|_, row| row.value.question_uuid != question_uuid);
+            webhooks.retain(|_, row| row.value.question_uuid != question_uuid);
+            outbox.push_back(ContentEvent::QuestionDeleted(QuestionId { question_uuid }));
+        }
+
+        Ok(())
+    }
+
+    /// Asynchronously retrieves all questions from memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The conditions the questions must meet, and their order.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of question details on success, or a `DBError` on failure.
+    async fn get_questions(&self, filter: &QuestionFilter) -> Result<Vec<QuestionDetail>, DBError> {
+        let questions = self.store.questions.read().map_err(memory::poisoned)?;
+        let answers = self.store.answers.read().map_err(memory::poisoned)?;
+
+        let title = filter.title_contains.as_deref().map(str::to_lowercase);
+        let matches = |question: &QuestionDetail| {
+            filter.status.is_none_or(|status| question.status == status)
+                && filter.created_after.is_none_or(|after| question.created_at > after)
+                && filter.created_before.is_none_or(|before| question.created_at < before)
+                && title.as_deref().is_none_or(|title| question.title.to_lowercase().contains(title))
+        };
+
+        let questions = memory::in_order(questions.values().filter(|row| matches(&row.value)));
+        let mut questions: Vec<QuestionDetail> = questions
+            .into_iter()
+            .map(|question| memory::with_activity(question, &answers))
+            .filter(|question| filter.answered.is_none_or(|answered| answered == (question.answer_count > 0)))
+            .collect();
+
+        // Sorting is stable, so questions with as many answers stay oldest first
+        match filter.sort.unwrap_or_default() {
+            QuestionSort::Oldest => {}
+            QuestionSort::Newest => questions.reverse(),
+            QuestionSort::MostAnswered => questions.sort_by_key(|question| std::cmp::Reverse(question.answer_count)),
+        }
+
+        Ok(questions)
+    }
+
+    /// Asynchronously retrieves several questions from memory, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuids` - The unique identifiers of the questions.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the details of the questions that exist on success, or a `DBError` on failure.
+    async fn get_questions_by_uuids(&self, question_uuids: Vec<QuestionUuid>) -> Result<Vec<QuestionDetail>, DBError> {
+        let questions = self.store.questions.read().map_err(memory::poisoned)?;
+        let answers = self.store.answers.read().map_err(memory::poisoned)?;
+
+        let questions = memory::in_order(questions.values().filter(|row| question_uuids.contains(&row.value.question_uuid)));
+
+        Ok(questions.into_iter().map(|question| memory::with_activity(question, &answers)).collect())
+    }
+
+    /// Asynchronously retrieves a page of every question from memory, in the order of their unique identifiers.
+    ///
+    /// # Arguments
+    ///
+    /// * `after` - The unique identifier of the last question of the previous page, `None` for the first page.
+    /// * `limit` - The maximum number of questions to return.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the details of the questions on success, or a `DBError` on failure.
+    async fn get_questions_after(
+        &self,
+        after: Option<QuestionUuid>,
+        limit: i64,
+    ) -> Result<Vec<QuestionDetail>, DBError> {
+        let questions = self.store.questions.read().map_err(memory::poisoned)?;
+        let answers = self.store.answers.read().map_err(memory::poisoned)?;
+
+        let mut page: Vec<&QuestionDetail> = questions
+            .values()
+            .map(|row| &row.value)
+            .filter(|question| after.is_none_or(|after| question.question_uuid > after))
+            .collect();
+        page.sort_by_key(|question| question.question_uuid);
+        page.truncate(usize::try_from(limit).unwrap_or_default());
+
+        Ok(page.into_iter().map(|question| memory::with_activity(question.clone(), &answers)).collect())
+    }
+
+    /// Asynchronously retrieves the most viewed questions from memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of questions to return.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the details of the questions, most viewed first, on success, or a `DBError` on failure.
+    async fn get_popular_questions(&self, limit: i64) -> Result<Vec<QuestionDetail>, DBError> {
+        let questions = self.store.questions.read().map_err(memory::poisoned)?;
+        let answers = self.store.answers.read().map_err(memory::poisoned)?;
+
+        // Sorting is stable, so questions viewed as many times stay oldest first
+        let mut questions = memory::in_order(questions.values());
+        questions.sort_by_key(|question| std::cmp::Reverse(question.view_count));
+        questions.truncate(usize::try_from(limit).unwrap_or_default());
+
+        Ok(questions.into_iter().map(|question| memory::with_activity(question, &answers)).collect())
+    }
+
+    /// Asynchronously retrieves a question along with all its answers from memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the question and its answers, oldest first, on success, or a `DBError` on failure.
+    async fn get_question_with_answers(&self, question_uuid: QuestionUuid) -> Result<QuestionWithAnswers, DBError> {
+        let questions = self.store.questions.read().map_err(memory::poisoned)?;
+        let answers = self.store.answers.read().map_err(memory::poisoned)?;
+
+        let question = questions.get(&question_uuid).ok_or_else(|| super::unknown_question(question_uuid))?;
+
+        Ok(QuestionWithAnswers {
+            question: memory::with_activity(question.value.clone(), &answers),
+            answers: memory::in_order(answers.values().filter(|row| row.value.question_uuid == question_uuid)),
+        })
+    }
+
+    /// Asynchronously changes the status of a question in memory, provided it currently has the expected status.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    /// * `from` - The status the question must have.
+    /// * `to` - The new status of the question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the updated question detail on success, or a `DBError` on failure.
+    async fn update_question_status(
+        &self,
+        question_uuid: QuestionUuid,
+        from: QuestionStatus,
+        to: QuestionStatus,
+    ) -> Result<QuestionDetail, DBError> {
+        let mut questions = self.store.questions.write().map_err(memory::poisoned)?;
+        let answers = self.store.answers.read().map_err(memory::poisoned)?;
+
+        match questions.get_mut(&question_uuid) {
+            Some(row) if row.value.status == from => {
+                row.value.status = to;
+                Ok(memory::with_activity(row.value.clone(), &answers))
+            }
+            row => Err(super::unexpected_status(question_uuid, row.map(|row| row.value.status))),
+        }
+    }
+
+    /// Asynchronously adds views to the view counts of questions in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `views` - The number of views of each question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    async fn add_question_views(&self, views: &HashMap<QuestionUuid, i64>) -> Result<(), DBError> {
+        let mut questions = self.store.questions.write().map_err(memory::poisoned)?;
+
+        for (question_uuid, count) in views {
+            if let Some(row) = questions.get_mut(question_uuid) {
+                row.value.view_count += count;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Asynchronously searches the questions in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The words to search for.
+    /// * `limit` - The maximum number of results to return.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the matching questions, best match first, on success, or a `DBError` on failure.
+    async fn search_questions(&self, query: String, limit: i64) -> Result<Vec<QuestionSearchResult>, DBError> {
+        let questions = self.get_questions(&QuestionFilter::default()).await?;
+
+        Ok(search::rank(questions, &query, limit))
+    }
+
+    async fn current_time(&self) -> Result<DateTime<Utc>, DBError> {
+        Ok(super::now())
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{
+    models::{QuestionDetail, QuestionSearchResult},
+    sanitize::escape_html,
+};
+
+/// Weight of a match in the description, relative to a match in the title
+const DESCRIPTION_WEIGHT: f32 = 0.4;
+
+/// Largest number of words in a snippet
+const SNIPPET_WORDS: usize = 30;
+
+/// Words shown before the first match in a snippet
+const SNIPPET_LEAD: usize = 5;
+
+/// Ranks questions against a search without a full-text index, for the backends that lack one.
+///
+/// A word matches a term if it starts with it, ignoring case, which stands in for stemming. Like
+/// the plain words of a Postgres `websearch_to_tsquery`, every term has to match, but quoted
+/// phrases, `or` and `-` are not supported.
+///
+/// # Arguments
+///
+/// * `questions` - The questions to search, oldest first.
+/// * `query` - The words to search for.
+/// * `limit` - The maximum number of results to return.
+///
+/// # Returns
+///
+/// The matching questions, best match first and newest first among equal matches.
+pub(crate) fn rank(questions: Vec<QuestionDetail>, query: &str, limit: i64) -> Vec<QuestionSearchResult> {
+    let terms: Vec<String> = query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+
+    if terms.is_empty() {
+        return Vec::new();
+    }
+
+    let mut results: Vec<QuestionSearchResult> = questions
+        .into_iter()
+        .rev()
+        .filter_map(|question| {
+            let mut rank = 0.0;
+
+            for term in &terms {
+                let in_title = count_matches(&question.title, term);
+                let in_description = count_matches(&question.description, term);
+
+                if in_title + in_description == 0 {
+                    return None;
+                }
+
+                rank += in_title as f32 + in_description as f32 * DESCRIPTION_WEIGHT;
+            }
+
+            let snippet = snippet(&format!("{} {}", question.title, question.description), &terms);
+
+            Some(QuestionSearchResult { question, rank, snippet })
+        })
+        .collect();
+
+    // Stable, so newer questions stay ahead of older ones with the same rank
+    results.sort_by(|a, b| b.rank.total_cmp(&a.rank));
+    results.truncate(limit.max(0) as usize);
+    results
+}
+
+/// Splits a word into leading punctuation, the word itself and trailing punctuation.
+fn split_word(word: &str) -> (&str, &str, &str) {
+    let start = word.find(char::is_alphanumeric).unwrap_or(word.len());
+    let end = word
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_alphanumeric())
+        .map_or(start, |(i, c)| i + c.len_utf8());
+
+    (&word[..start], &word[start..end], &word[end..])
+}
+
+fn matches_term(word: &str, term: &str) -> bool {
+    let word = split_word(word).1.to_lowercase();
+    !word.is_empty() && word.starts_with(term)
+}
+
+fn is_match(word: &str, terms: &[String]) -> bool {
+    terms.iter().any(|term| matches_term(word, term))
+}
+
+fn count_matches(text: &str, term: &str) -> usize {
+    text.split_whitespace().filter(|word| matches_term(word, term)).count()
+}
+
+/// Builds an excerpt of `text` starting shortly before the first match, with the text escaped and
+/// matches wrapped in `<mark>` tags.
+fn snippet(text: &str, terms: &[String]) -> String {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let first_match = words.iter().position(|word| is_match(word, terms)).unwrap_or(0);
+    let start = first_match.saturating_sub(SNIPPET_LEAD).min(words.len().saturating_sub(SNIPPET_WORDS));
+
+    words[start..]
+        .iter()
+        .take(SNIPPET_WORDS)
+        .map(|word| {
+            if is_match(word, terms) {
+                let (lead, core, trail) = split_word(word);
+                format!("{}<mark>{}</mark>{}", escape_html(lead), escape_html(core), escape_html(trail))
+            } else {
+                escape_html(word)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::{DateTime, TimeDelta};
+
+    use crate::models::{QuestionStatus, QuestionUuid};
+
+    fn question(seconds: i64, title: &str, description: &str) -> QuestionDetail {
+        QuestionDetail {
+            question_uuid: QuestionUuid::new_v4(),
+            title: title.to_owned(),
+            description: description.to_owned(),
+            status: QuestionStatus::Open,
+            created_at: DateTime::UNIX_EPOCH + TimeDelta::seconds(seconds),
+            answer_count: 0,
+            last_activity_at: DateTime::UNIX_EPOCH + TimeDelta::seconds(seconds),
+            view_count: 0,
+            word_count: 0,
+            reading_time_seconds: 0,
+            excerpt: None,
+        }
+    }
+
+    fn titles(results: &[QuestionSearchResult]) -> Vec<&str> {
+        results.iter().map(|r| r.question.title.as_str()).collect()
+    }
+
+    #[test]
+    fn should_require_every_term_to_match() {
+        let questions = vec![
+            question(0, "Borrow checker errors", "In async Rust code"),
+            question(1, "Borrow checker errors", "In C++ code"),
+        ];
+
+        let results = rank(questions, "rust borrow", 10);
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].question.description, "In async Rust code");
+    }
+
+    #[test]
+    fn should_rank_title_matches_first_then_newest() {
+        let questions = vec![
+            question(0, "Lifetimes", "Still confused"),
+            question(1, "Generics", "Are lifetimes generics?"),
+            question(2, "Lifetimes again", "Still confused"),
+        ];
+
+        let results = rank(questions, "lifetime", 10);
+
+        assert_eq!(titles(&results), ["Lifetimes again", "Lifetimes", "Generics"]);
+    }
+
+    #[test]
+    fn should_limit_results() {
+        let questions = (0..5).map(|i| question(i, "Tokio", "Runtime")).collect();
+
+        assert_eq!(rank(questions, "tokio", 2).len(), 2);
+    }
+
+    #[test]
+    fn should_ignore_queries_without_words() {
+        assert!(rank(vec![question(0, "Title", "Description")], " ?! ", 10).is_empty());
+    }
+
+    #[test]
+    fn should_highlight_matches_without_punctuation() {
+        let snippet = snippet("Why does (Tokio) panic? Tokio's runtime", &["tokio".to_owned()]);
+
+        assert_eq!(snippet, "Why does (<mark>Tokio</mark>) panic? <mark>Tokio&#39;s</mark> runtime");
+    }
+
+    #[test]
+    fn should_escape_snippets() {
+        let snippet = snippet("<script>alert('tokio')</script> tokio", &["tokio".to_owned()]);
+
+        assert_eq!(snippet, "&lt;script&gt;alert(&#39;tokio&#39;)&lt;/script&gt; <mark>tokio</mark>");
+    }
+
+    #[test]
+    fn should_start_snippet_near_first_match() {
+        let text = format!("{} needle", "word ".repeat(100));
+
+        let snippet = snippet(&text, &["needle".to_owned()]);
+
+        assert_eq!(snippet.split_whitespace().count(), SNIPPET_WORDS);
+        assert!(snippet.ends_with("<mark>needle</mark>"));
+    }
+}
@@ -0,0 +1,175 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_trait::async_trait;
+use chrono::{NaiveDate, NaiveTime};
+use sqlx::PgPool;
+
+use crate::models::{AnswerCountBucket, ContentStats, DBError, DailyQuestions};
+
+use super::memory::{self, MemoryStore};
+
+/// A trait representing the aggregate queries behind the statistics of the content in the database.
+#[async_trait]
+pub trait StatsDao {
+
+    /// Asynchronously counts the questions and answers, the questions by number of answers, and the questions asked
+    /// on each day since a date.
+    ///
+    /// # Arguments
+    ///
+    /// * `since` - The first day questions are counted per day for, in UTC.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the statistics, with a count for every day from `since` to today, on success, or a
+    /// `DBError` on failure.
+    async fn get_stats(&self, since: NaiveDate) -> Result<ContentStats, DBError>;
+}
+
+/// Builds the statistics from the questions by number of answers, which add up to the totals, and the days
+/// questions were asked on.
+///
+/// # Arguments
+///
+/// * `answers_per_question` - The questions by number of answers, fewest answers first.
+/// * `since` - The first day questions are counted per day for.
+/// * `daily` - The number of questions asked on each day since `since` that had any.
+///
+/// # Returns
+///
+/// The statistics, counting 0 questions for the days missing from `daily`.
+pub(crate) fn content_stats(
+    answers_per_question: Vec<AnswerCountBucket>,
+    since: NaiveDate,
+    daily: HashMap<NaiveDate, i64>,
+) -> ContentStats {
+    let today = super::now().date_naive();
+
+    ContentStats {
+        question_count: answers_per_question.iter().map(|bucket| bucket.question_count).sum(),
+        answer_count: answers_per_question.iter().map(|bucket| bucket.answer_count * bucket.question_count).sum(),
+        answers_per_question,
+        questions_per_day: since
+            .iter_days()
+            .take_while(|day| *day <= today)
+            .map(|day| DailyQuestions { day, question_count: daily.get(&day).copied().unwrap_or(0) })
+            .collect(),
+    }
+}
+
+/// Implementation of the `StatsDao` trait for PostgreSQL database.
+pub struct StatsDaoImpl {
+    db: PgPool,
+}
+
+/// Constructor
+impl StatsDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        StatsDaoImpl { db }
+    }
+}
+
+#[async_trait]
+impl StatsDao for StatsDaoImpl {
+
+    /// Asynchronously computes the statistics of the content in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `since` - The first day questions are counted per day for, in UTC.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the statistics on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_stats(&self, since: NaiveDate) -> Result<ContentStats, DBError> {
+        let answers_per_question = sqlx::query_as!(
+            AnswerCountBucket,
+            r#"
+                SELECT answer_count AS "answer_count!", COUNT(*) AS "question_count!"
+                FROM (
+                    SELECT COUNT(a.answer_uuid) AS answer_count
+                    FROM questions q
+                    LEFT JOIN answers a ON a.question_uuid = q.question_uuid
+                    GROUP BY q.question_uuid
+                ) counts
+                GROUP BY answer_count
+                ORDER BY answer_count
+            "#
+        ).fetch_all(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let daily = sqlx::query!(
+            r#"
+                SELECT created_at::date AS "day!", COUNT(*) AS "question_count!"
+                FROM questions
+                WHERE created_at >= $1
+                GROUP BY 1
+            "#,
+            since.and_time(NaiveTime::MIN)
+        ).fetch_all(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        let daily = daily.into_iter().map(|r| (r.day, r.question_count)).collect();
+
+        Ok(content_stats(answers_per_question, since, daily))
+    }
+}
+
+/// Implementation of the `StatsDao` trait computing statistics of the content kept in memory, for local
+/// development and tests.
+pub struct StatsDaoInMemory {
+    store: Arc<MemoryStore>,
+}
+
+/// Constructor
+impl StatsDaoInMemory {
+    pub fn new(store: Arc<MemoryStore>) -> Self {
+        StatsDaoInMemory { store }
+    }
+}
+
+#[async_trait]
+impl StatsDao for StatsDaoInMemory {
+
+    /// Asynchronously computes the statistics of the content in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `since` - The first day questions are counted per day for, in UTC.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the statistics on success, or a `DBError` on failure.
+    async fn get_stats(&self, since: NaiveDate) -> Result<ContentStats, DBError> {
+        let questions = self.store.questions.read().map_err(memory::poisoned)?;
+        let answers = self.store.answers.read().map_err(memory::poisoned)?;
+
+        let mut answer_counts: HashMap<_, i64> = questions.keys().map(|question_uuid| (*question_uuid, 0)).collect();
+        for row in answers.values() {
+            if let Some(count) = answer_counts.get_mut(&row.value.question_uuid) {
+                *count += 1;
+            }
+        }
+
+        let mut buckets: HashMap<i64, i64> = HashMap::new();
+        for answer_count in answer_counts.into_values() {
+            *buckets.entry(answer_count).or_default() += 1;
+        }
+
+        let mut answers_per_question: Vec<_> = buckets
+            .into_iter()
+            .map(|(answer_count, question_count)| AnswerCountBucket { answer_count, question_count })
+            .collect();
+        answers_per_question.sort_by_key(|bucket| bucket.answer_count);
+
+        let mut daily: HashMap<NaiveDate, i64> = HashMap::new();
+        for row in questions.values().filter(|row| row.value.created_at.date_naive() >= since) {
+            *daily.entry(row.value.created_at.date_naive()).or_default() += 1;
+        }
+
+        Ok(content_stats(answers_per_question, since, daily))
+    }
+}
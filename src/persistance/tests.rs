@@ -2,48 +2,21 @@ mod answers_tests {
     use sqlx::PgPool;
 
     use crate::{
-        models::{Answer, DBError, Question},
+        models::{Answer, AnswerSort, DBError, Question, QuestionFilter, QuestionStatus},
         persistance::{
             answers_dao::{AnswersDao, AnswersDaoImpl},
             questions_dao::{QuestionsDao, QuestionsDaoImpl},
         },
     };
 
-    #[sqlx::test]
-    async fn create_answer_should_fail_with_malformed_uuid(pool: PgPool) -> Result<(), String> {
-        let answer_doa = AnswersDaoImpl::new(pool);
-
-        let result = answer_doa
-            .create_answer(Answer {
-                question_uuid: "malformed".to_owned(),
-                content: "test content".to_owned(),
-            })
-            .await;
-
-        if result.is_ok() {
-            return Err(format!(
-                "Expected an error but got the following result: {:?}",
-                result.unwrap()
-            ));
-        }
-
-        if let Err(DBError::InvalidUUID(_)) = result {
-            Ok(())
-        } else {
-            Err(format!(
-                "Expected an invalid UUID error but got the following error: {:?}",
-                result.err()
-            ))
-        }
-    }
-
     #[sqlx::test]
     async fn create_answer_should_fail_with_non_existent_uuid(pool: PgPool) -> Result<(), String> {
         let answer_doa = AnswersDaoImpl::new(pool);
 
         let result = answer_doa
             .create_answer(Answer {
-                question_uuid: "a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned(),
+                answer_uuid: None,
+                question_uuid: "a22abcd2-22ab-2222-a22b-2abc2a2b22cc".parse().unwrap(),
                 content: "test content".to_owned(),
             })
             .await;
@@ -75,7 +48,8 @@ mod answers_tests {
 
         let result = answer_doa
             .create_answer(Answer {
-                question_uuid: "a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned(),
+                answer_uuid: None,
+                question_uuid: "a22abcd2-22ab-2222-a22b-2abc2a2b22cc".parse().unwrap(),
                 content: "test content".to_owned(),
             })
             .await;
@@ -104,6 +78,7 @@ mod answers_tests {
 
         let result = question_doa
             .create_question(Question {
+                question_uuid: None,
                 title: "test title".to_owned(),
                 description: "test description".to_owned(),
             })
@@ -112,13 +87,14 @@ mod answers_tests {
 
         let result = answer_doa
             .create_answer(Answer {
+                answer_uuid: None,
                 question_uuid: result.question_uuid,
                 content: "test content".to_owned(),
             })
             .await
             .map_err(|e| format!("{:?}", e))?;
 
-        if result.content != "test content".to_owned() {
+        if result.content != "test content" {
             return Err("Incorrect answer content".to_owned());
         }
 
@@ -126,25 +102,124 @@ mod answers_tests {
     }
 
     #[sqlx::test]
-    async fn delete_answer_should_fail_with_malformed_uuid(pool: PgPool) -> Result<(), String> {
+    async fn get_questions_should_count_answers(pool: PgPool) -> Result<(), String> {
+        let question_doa = QuestionsDaoImpl::new(pool.clone());
         let answer_doa = AnswersDaoImpl::new(pool);
 
-        let result = answer_doa.delete_answer("malformed".to_owned()).await;
+        let mut questions = Vec::new();
+        for title in ["answered", "unanswered"] {
+            let question = question_doa
+                .create_question(Question {
+                    question_uuid: None,
+                    title: title.to_owned(),
+                    description: "test description".to_owned(),
+                })
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+            questions.push(question);
+        }
 
-        if result.is_ok() {
-            return Err(format!(
-                "Expected an error but got the following result: {:?}",
-                result.unwrap()
-            ));
+        let mut answers = Vec::new();
+        for _ in 0..2 {
+            let answer = answer_doa
+                .create_answer(Answer {
+                    answer_uuid: None,
+                    question_uuid: questions[0].question_uuid,
+                    content: "test content".to_owned(),
+                })
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+            answers.push(answer);
         }
 
-        if let Err(DBError::InvalidUUID(_)) = result {
+        let results = question_doa.get_questions(&QuestionFilter::default()).await.map_err(|e| format!("{:?}", e))?;
+        let activity: Vec<_> = results.iter().map(|q| (q.answer_count, q.last_activity_at)).collect();
+
+        if activity != [(2, answers[1].created_at), (0, questions[1].created_at)] {
+            return Err(format!("Unexpected answer counts and activity: {:?}", activity));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn get_question_with_answers_should_return_its_answers(pool: PgPool) -> Result<(), String> {
+        let question_doa = QuestionsDaoImpl::new(pool.clone());
+        let answer_doa = AnswersDaoImpl::new(pool);
+
+        let mut questions = Vec::new();
+        for title in ["answered", "other"] {
+            let question = question_doa
+                .create_question(Question {
+                    question_uuid: None,
+                    title: title.to_owned(),
+                    description: "test description".to_owned(),
+                })
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+            questions.push(question);
+        }
+
+        let mut answers = Vec::new();
+        for question in [&questions[0], &questions[0], &questions[1]] {
+            let answer = answer_doa
+                .create_answer(Answer {
+                    answer_uuid: None,
+                    question_uuid: question.question_uuid,
+                    content: "test content".to_owned(),
+                })
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+            answers.push(answer);
+        }
+
+        let result = question_doa
+            .get_question_with_answers(questions[0].question_uuid)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if result.question.answer_count != 2 || result.question.last_activity_at != answers[1].created_at {
+            return Err(format!("Unexpected question: {:?}", result.question));
+        }
+
+        if result.answers != answers[..2] {
+            return Err(format!("Unexpected answers: {:?}", result.answers));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn create_answer_should_fail_on_closed_question(pool: PgPool) -> Result<(), String> {
+        let question_doa = QuestionsDaoImpl::new(pool.clone());
+        let answer_doa = AnswersDaoImpl::new(pool);
+
+        let result = question_doa
+            .create_question(Question {
+                question_uuid: None,
+                title: "test title".to_owned(),
+                description: "test description".to_owned(),
+            })
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        question_doa
+            .update_question_status(result.question_uuid, QuestionStatus::Open, QuestionStatus::Closed)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let result = answer_doa
+            .create_answer(Answer {
+                answer_uuid: None,
+                question_uuid: result.question_uuid,
+                content: "test content".to_owned(),
+            })
+            .await;
+
+        if let Err(DBError::Conflict(_)) = result {
             Ok(())
         } else {
-            Err(format!(
-                "Expected an invalid UUID error but got the following error: {:?}",
-                result.err()
-            ))
+            Err(format!("Expected a conflict error but got the following result: {:?}", result))
         }
     }
 
@@ -157,7 +232,7 @@ mod answers_tests {
         pool.close().await;
 
         let result = answer_doa
-            .delete_answer("a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned())
+            .delete_answer("a22abcd2-22ab-2222-a22b-2abc2a2b22cc".parse().unwrap())
             .await;
 
         if result.is_ok() {
@@ -184,6 +259,7 @@ mod answers_tests {
 
         let question = question_doa
             .create_question(Question {
+                question_uuid: None,
                 title: "test title".to_owned(),
                 description: "test description".to_owned(),
             })
@@ -192,7 +268,8 @@ mod answers_tests {
 
         let result = answer_doa
             .create_answer(Answer {
-                question_uuid: question.question_uuid.clone(),
+                answer_uuid: None,
+                question_uuid: question.question_uuid,
                 content: "test content".to_owned(),
             })
             .await
@@ -204,40 +281,17 @@ mod answers_tests {
             .map_err(|e| format!("{:?}", e))?;
 
         let results = answer_doa
-            .get_answers(question.question_uuid.clone())
+            .get_answers(question.question_uuid, AnswerSort::default())
             .await
             .map_err(|e| format!("{:?}", e))?;
 
-        if results.len() != 0 {
+        if !results.is_empty() {
             return Err("Answer was not deleted".to_owned());
         }
 
         Ok(())
     }
 
-    #[sqlx::test]
-    async fn get_answers_should_fail_with_malformed_uuid(pool: PgPool) -> Result<(), String> {
-        let answer_doa = AnswersDaoImpl::new(pool);
-
-        let result = answer_doa.get_answers("malformed".to_owned()).await;
-
-        if result.is_ok() {
-            return Err(format!(
-                "Expected an error but got the following result: {:?}",
-                result.unwrap()
-            ));
-        }
-
-        if let Err(DBError::InvalidUUID(_)) = result {
-            Ok(())
-        } else {
-            Err(format!(
-                "Expected an invalid UUID error but got the following error: {:?}",
-                result.err()
-            ))
-        }
-    }
-
     #[sqlx::test]
     async fn get_answers_should_fail_if_database_error_occurs(pool: PgPool) -> Result<(), String> {
         let answer_doa = AnswersDaoImpl::new(pool.clone());
@@ -245,7 +299,7 @@ mod answers_tests {
         pool.close().await;
 
         let result = answer_doa
-            .get_answers("a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned())
+            .get_answers("a22abcd2-22ab-2222-a22b-2abc2a2b22cc".parse().unwrap(), AnswerSort::default())
             .await;
 
         if result.is_ok() {
@@ -272,6 +326,7 @@ mod answers_tests {
 
         let question = question_doa
             .create_question(Question {
+                question_uuid: None,
                 title: "test title".to_owned(),
                 description: "test description".to_owned(),
             })
@@ -280,14 +335,15 @@ mod answers_tests {
 
         let result = answer_doa
             .create_answer(Answer {
-                question_uuid: question.question_uuid.clone(),
+                answer_uuid: None,
+                question_uuid: question.question_uuid,
                 content: "test content".to_owned(),
             })
             .await
             .map_err(|e| format!("{:?}", e))?;
 
         let results = answer_doa
-            .get_answers(question.question_uuid.clone())
+            .get_answers(question.question_uuid, AnswerSort::default())
             .await
             .map_err(|e| format!("{:?}", e))?;
 
@@ -295,7 +351,7 @@ mod answers_tests {
             return Err("Incorrect number of results returned.".to_owned());
         }
 
-        if results.get(0).unwrap().answer_uuid != result.answer_uuid {
+        if results.first().unwrap().answer_uuid != result.answer_uuid {
             return Err("Incorrect answer returned.".to_owned());
         }
 
@@ -307,7 +363,7 @@ mod questions_tests {
     use sqlx::PgPool;
 
     use crate::{
-        models::{DBError, Question},
+        models::{DBError, Question, QuestionFilter, QuestionStatus},
         persistance::questions_dao::{QuestionsDao, QuestionsDaoImpl},
     };
 
@@ -321,6 +377,7 @@ mod questions_tests {
 
         let result = doa
             .create_question(Question {
+                question_uuid: None,
                 title: "test title".to_owned(),
                 description: "test description".to_owned(),
             })
@@ -349,14 +406,15 @@ mod questions_tests {
 
         let result = doa
             .create_question(Question {
+                question_uuid: None,
                 title: "test title".to_owned(),
                 description: "test description".to_owned(),
             })
             .await
             .map_err(|e| format!("{:?}", e))?;
 
-        if result.title != "test title".to_owned()
-            || result.description != "test description".to_owned()
+        if result.title != "test title"
+            || result.description != "test description"
         {
             return Err("Incorrect title or description".to_owned());
         }
@@ -364,29 +422,6 @@ mod questions_tests {
         Ok(())
     }
 
-    #[sqlx::test]
-    async fn delete_question_should_fail_with_malformed_uuid(pool: PgPool) -> Result<(), String> {
-        let doa = QuestionsDaoImpl::new(pool);
-
-        let result = doa.delete_question("malformed".to_owned()).await;
-
-        if result.is_ok() {
-            return Err(format!(
-                "Expected an error but got the following result: {:?}",
-                result.unwrap()
-            ));
-        }
-
-        if let Err(DBError::InvalidUUID(_)) = result {
-            Ok(())
-        } else {
-            Err(format!(
-                "Expected an invalid UUID error but got the following error: {:?}",
-                result.err()
-            ))
-        }
-    }
-
     #[sqlx::test]
     async fn delete_question_should_fail_if_database_error_occurs(
         pool: PgPool,
@@ -396,7 +431,7 @@ mod questions_tests {
         pool.close().await;
 
         let result = doa
-            .delete_question("a22abcd2-22ab-2222-a22b-2abc2a2b22cc".to_owned())
+            .delete_question("a22abcd2-22ab-2222-a22b-2abc2a2b22cc".parse().unwrap())
             .await;
 
         if result.is_ok() {
@@ -422,6 +457,7 @@ mod questions_tests {
 
         let result = doa
             .create_question(Question {
+                question_uuid: None,
                 title: "test title".to_owned(),
                 description: "test description".to_owned(),
             })
@@ -432,9 +468,9 @@ mod questions_tests {
             .await
             .map_err(|e| format!("{:?}", e))?;
 
-        let results = doa.get_questions().await.map_err(|e| format!("{:?}", e))?;
+        let results = doa.get_questions(&QuestionFilter::default()).await.map_err(|e| format!("{:?}", e))?;
 
-        if results.len() != 0 {
+        if !results.is_empty() {
             return Err("Question was not deleted".to_owned());
         }
 
@@ -449,7 +485,7 @@ mod questions_tests {
 
         pool.close().await;
 
-        let result = doa.get_questions().await;
+        let result = doa.get_questions(&QuestionFilter::default()).await;
 
         if result.is_ok() {
             return Err(format!(
@@ -474,22 +510,733 @@ mod questions_tests {
 
         let result = doa
             .create_question(Question {
+                question_uuid: None,
                 title: "test title".to_owned(),
                 description: "test description".to_owned(),
             })
             .await
             .map_err(|e| format!("{:?}", e))?;
 
-        let results = doa.get_questions().await.map_err(|e| format!("{:?}", e))?;
+        let results = doa.get_questions(&QuestionFilter::default()).await.map_err(|e| format!("{:?}", e))?;
 
         if results.len() != 1 {
             return Err("Incorrect number of results returned.".to_owned());
         }
 
-        if results.get(0).unwrap().question_uuid != result.question_uuid {
+        if results.first().unwrap().question_uuid != result.question_uuid {
             return Err("Incorrect question returned.".to_owned());
         }
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[sqlx::test]
+    async fn search_questions_should_rank_and_highlight_matches(pool: PgPool) -> Result<(), String> {
+        let doa = QuestionsDaoImpl::new(pool);
+
+        for (title, description) in [
+            ("Borrowing in closures", "The borrow checker rejects my closure"),
+            ("Async traits", "Can a trait have async methods?"),
+            ("Lifetimes", "Why does borrowing need lifetimes?"),
+        ] {
+            doa.create_question(Question {
+                question_uuid: None,
+                title: title.to_owned(),
+                description: description.to_owned(),
+            })
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        }
+
+        // Stemming matches "borrowing" and "borrow"
+        let results = doa.search_questions("borrowed".to_owned(), 10).await.map_err(|e| format!("{:?}", e))?;
+        let titles: Vec<&str> = results.iter().map(|r| r.question.title.as_str()).collect();
+
+        if titles != ["Borrowing in closures", "Lifetimes"] {
+            return Err(format!("Unexpected search results: {:?}", titles));
+        }
+
+        if !results[0].snippet.contains("<mark>Borrowing</mark>") {
+            return Err(format!("Matches not highlighted: {}", results[0].snippet));
+        }
+
+        let results = doa.search_questions("borrowed".to_owned(), 1).await.map_err(|e| format!("{:?}", e))?;
+
+        if results.len() != 1 {
+            return Err("Search results not limited.".to_owned());
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn get_questions_should_filter_by_status(pool: PgPool) -> Result<(), String> {
+        let doa = QuestionsDaoImpl::new(pool);
+
+        let mut question_uuids = Vec::new();
+        for title in ["open", "closed"] {
+            let result = doa
+                .create_question(Question {
+                    question_uuid: None,
+                    title: title.to_owned(),
+                    description: "test description".to_owned(),
+                })
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+            question_uuids.push(result.question_uuid);
+        }
+
+        doa.update_question_status(question_uuids[1], QuestionStatus::Open, QuestionStatus::Closed)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let results = doa
+            .get_questions(&QuestionFilter { status: Some(QuestionStatus::Closed), ..QuestionFilter::default() })
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        let titles: Vec<&str> = results.iter().map(|q| q.title.as_str()).collect();
+
+        if titles != ["closed"] {
+            return Err(format!("Unexpected closed questions: {:?}", titles));
+        }
+
+        let results = doa.get_questions(&QuestionFilter::default()).await.map_err(|e| format!("{:?}", e))?;
+
+        if results.len() != 2 {
+            return Err("Incorrect number of results returned.".to_owned());
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn update_question_status_should_succeed(pool: PgPool) -> Result<(), String> {
+        let doa = QuestionsDaoImpl::new(pool);
+
+        let result = doa
+            .create_question(Question {
+                question_uuid: None,
+                title: "test title".to_owned(),
+                description: "test description".to_owned(),
+            })
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if result.status != QuestionStatus::Open {
+            return Err(format!("New question is {}", result.status));
+        }
+
+        let closed = doa
+            .update_question_status(result.question_uuid, QuestionStatus::Open, QuestionStatus::Closed)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if closed.status != QuestionStatus::Closed || closed.question_uuid != result.question_uuid {
+            return Err(format!("Question was not closed: {:?}", closed));
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn update_question_status_should_fail_from_wrong_status(pool: PgPool) -> Result<(), String> {
+        let doa = QuestionsDaoImpl::new(pool);
+
+        let result = doa
+            .create_question(Question {
+                question_uuid: None,
+                title: "test title".to_owned(),
+                description: "test description".to_owned(),
+            })
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let result = doa
+            .update_question_status(result.question_uuid, QuestionStatus::Closed, QuestionStatus::Open)
+            .await;
+
+        if let Err(DBError::Conflict(_)) = result {
+            Ok(())
+        } else {
+            Err(format!("Expected a conflict error but got the following result: {:?}", result))
+        }
+    }
+
+    #[sqlx::test]
+    async fn update_question_status_should_fail_with_non_existent_uuid(pool: PgPool) -> Result<(), String> {
+        let doa = QuestionsDaoImpl::new(pool);
+
+        let result = doa
+            .update_question_status(
+                "a22abcd2-22ab-2222-a22b-2abc2a2b22cc".parse().unwrap(),
+                QuestionStatus::Open,
+                QuestionStatus::Closed,
+            )
+            .await;
+
+        if let Err(DBError::InvalidUUID(_)) = result {
+            Ok(())
+        } else {
+            Err(format!("Expected an invalid UUID error but got the following result: {:?}", result))
+        }
+    }
+
+    #[sqlx::test]
+    async fn get_question_with_answers_should_fail_with_non_existent_uuid(pool: PgPool) -> Result<(), String> {
+        let doa = QuestionsDaoImpl::new(pool);
+
+        let result = doa
+            .get_question_with_answers("a22abcd2-22ab-2222-a22b-2abc2a2b22cc".parse().unwrap())
+            .await;
+
+        if let Err(DBError::InvalidUUID(_)) = result {
+            Ok(())
+        } else {
+            Err(format!("Expected an invalid UUID error but got the following result: {:?}", result))
+        }
+    }
+}
+mod incidents_tests {
+    use sqlx::PgPool;
+
+    use crate::{
+        models::Incident,
+        persistance::incidents_dao::{IncidentsDao, IncidentsDaoImpl},
+    };
+
+    #[sqlx::test]
+    async fn get_active_incidents_should_exclude_resolved(pool: PgPool) -> Result<(), String> {
+        let doa = IncidentsDaoImpl::new(pool);
+
+        let resolved = doa
+            .create_incident(Incident {
+                title: "resolved".to_owned(),
+                description: "test description".to_owned(),
+            })
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let active = doa
+            .create_incident(Incident {
+                title: "active".to_owned(),
+                description: "test description".to_owned(),
+            })
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        doa.resolve_incident(resolved.incident_uuid)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let results = doa.get_active_incidents().await.map_err(|e| format!("{:?}", e))?;
+
+        if results != vec![active] {
+            return Err(format!("Expected only the active incident but got: {:?}", results));
+        }
+
+        Ok(())
+    }
+}
+
+mod webhooks_tests {
+    use sqlx::PgPool;
+
+    use crate::{
+        models::Webhook,
+        persistance::webhooks_dao::{WebhooksDao, WebhooksDaoImpl},
+    };
+
+    #[sqlx::test]
+    async fn get_webhooks_should_list_oldest_first_until_deleted(pool: PgPool) -> Result<(), String> {
+        let doa = WebhooksDaoImpl::new(pool);
+
+        let mut created = Vec::new();
+        for url in ["https://example.com/first", "https://example.com/second", "https://example.com/third"] {
+            let webhook = doa
+                .create_webhook(Webhook {
+                    url: url.to_owned(),
+                    secret: "0123456789abcdef".to_owned(),
+                })
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+            created.push(webhook);
+        }
+
+        doa.delete_webhook(created[1].webhook_uuid)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let results = doa.get_webhooks().await.map_err(|e| format!("{:?}", e))?;
+
+        if results != vec![created[0].clone(), created[2].clone()] {
+            return Err(format!("Expected the remaining webhooks oldest first but got: {:?}", results));
+        }
+
+        Ok(())
+    }
+}
+
+mod outbox_tests {
+    use chrono::{TimeDelta, Utc};
+    use sqlx::PgPool;
+
+    use crate::{
+        persistance::{
+            outbox_dao::{OutboxDao, OutboxDaoImpl},
+            questions_dao::{QuestionsDao, QuestionsDaoImpl},
+        },
+        test_support::QuestionBuilder,
+    };
+
+    #[sqlx::test]
+    async fn delete_delivered_events_should_keep_undelivered_ones(pool: PgPool) -> Result<(), String> {
+        let questions_dao = QuestionsDaoImpl::new(pool.clone());
+        let doa = OutboxDaoImpl::new(pool);
+
+        questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+        doa.dispatch_events(10, &|_| {}).await.map_err(|e| format!("{:?}", e))?;
+        questions_dao.create_question(QuestionBuilder::new().build()).await.map_err(|e| format!("{:?}", e))?;
+
+        let deleted = doa
+            .delete_delivered_events(Utc::now() + TimeDelta::minutes(1))
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if deleted != 1 {
+            return Err(format!("Expected only the delivered event to be deleted but deleted {}", deleted));
+        }
+
+        let dispatched = doa.dispatch_events(10, &|_| {}).await.map_err(|e| format!("{:?}", e))?;
+
+        if dispatched != 1 {
+            return Err(format!("Expected the undelivered event to be kept but dispatched {}", dispatched));
+        }
+
+        Ok(())
+    }
+}
+
+mod maintenance_tests {
+    use sqlx::{Executor, PgPool};
+
+    use crate::{
+        models::TableMaintenance,
+        persistance::maintenance_dao::{MaintenanceDao, MaintenanceDaoImpl},
+    };
+
+    async fn questions_maintenance(dao: &MaintenanceDaoImpl) -> Result<TableMaintenance, String> {
+        let report = dao.get_maintenance_report().await.map_err(|e| format!("{:?}", e))?;
+
+        report
+            .tables
+            .into_iter()
+            .find(|table| table.table_name == "questions")
+            .ok_or_else(|| "Expected the questions table to be reported".to_owned())
+    }
+
+    #[sqlx::test]
+    async fn get_maintenance_report_should_report_dead_tuples_until_vacuumed(pool: PgPool) -> Result<(), String> {
+        let dao = MaintenanceDaoImpl::new(pool.clone());
+        let mut conn = pool.acquire().await.map_err(|e| format!("{:?}", e))?;
+
+        conn.execute(r#"
+            INSERT INTO questions ( title, description ) SELECT 'title', 'description' FROM generate_series(1, 100);
+            DELETE FROM questions WHERE question_uuid IN ( SELECT question_uuid FROM questions LIMIT 40 );
+            SELECT pg_stat_force_next_flush();
+        "#).await.map_err(|e| format!("{:?}", e))?;
+        // Statistics are only flushed once the transaction running the statements above ends
+        conn.execute("SELECT 1").await.map_err(|e| format!("{:?}", e))?;
+
+        let questions = questions_maintenance(&dao).await?;
+
+        if questions.dead_tuples != 40 || questions.live_tuples != 60 || questions.dead_tuple_ratio != 0.4 {
+            return Err(format!("Expected 40 dead tuples out of 100 but got {:?}", questions));
+        }
+
+        conn.execute("VACUUM ANALYZE questions").await.map_err(|e| format!("{:?}", e))?;
+        conn.execute("SELECT pg_stat_force_next_flush()").await.map_err(|e| format!("{:?}", e))?;
+        conn.execute("SELECT 1").await.map_err(|e| format!("{:?}", e))?;
+
+        let questions = questions_maintenance(&dao).await?;
+
+        if questions.dead_tuples != 0 || questions.last_vacuum.is_none() || questions.vacuum_count != 1 {
+            return Err(format!("Expected the dead tuples to be vacuumed but got {:?}", questions));
+        }
+
+        if questions.estimated_bloat_bytes.is_none() {
+            return Err("Expected bloat to be estimated once the table is analyzed".to_owned());
+        }
+
+        Ok(())
+    }
+}
+
+mod snapshot_tests {
+    use sqlx::PgPool;
+
+    use crate::persistance::begin_shared_snapshot;
+
+    async fn count_questions(executor: impl sqlx::PgExecutor<'_>) -> Result<i64, String> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM questions")
+            .fetch_one(executor)
+            .await
+            .map_err(|e| format!("{:?}", e))
+    }
+
+    #[sqlx::test]
+    async fn begin_shared_snapshot_should_hide_later_writes_from_both_transactions(pool: PgPool) -> Result<(), String> {
+        let (mut exporter, mut importer) = begin_shared_snapshot(&pool).await.map_err(|e| format!("{:?}", e))?;
+
+        // Committed before either transaction reads anything
+        sqlx::query("INSERT INTO questions ( title, description ) VALUES ( 'title', 'description' )")
+            .execute(&pool)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let counts = (count_questions(&mut *exporter).await?, count_questions(&mut *importer).await?);
+
+        if counts != (0, 0) {
+            return Err(format!("Expected neither transaction to see the question but counted {:?}", counts));
+        }
+
+        if count_questions(&pool).await? != 1 {
+            return Err("Expected the question to be committed".to_owned());
+        }
+
+        Ok(())
+    }
+}
+
+mod contract_tests {
+    use sqlx::PgPool;
+
+    use crate::persistance::{
+        answers_dao::AnswersDaoImpl,
+        contract::{
+            dao_contract_tests, flags_contract_tests, idempotency_contract_tests, jobs_contract_tests,
+            outbox_contract_tests, question_webhooks_contract_tests, stats_contract_tests,
+        },
+        flags_dao::FlagsDaoImpl,
+        idempotency_dao::IdempotencyDaoImpl,
+        jobs_dao::JobsDaoImpl,
+        outbox_dao::OutboxDaoImpl,
+        questions_dao::QuestionsDaoImpl,
+        stats_dao::StatsDaoImpl,
+        webhooks_dao::WebhooksDaoImpl,
+    };
+
+    dao_contract_tests!(#[sqlx::test] async fn(pool: PgPool) {
+        (QuestionsDaoImpl::new(pool.clone()), AnswersDaoImpl::new(pool))
+    });
+
+    outbox_contract_tests!(#[sqlx::test] async fn(pool: PgPool) {
+        (QuestionsDaoImpl::new(pool.clone()), AnswersDaoImpl::new(pool.clone()), OutboxDaoImpl::new(pool))
+    });
+
+    idempotency_contract_tests!(#[sqlx::test] async fn(pool: PgPool) { IdempotencyDaoImpl::new(pool) });
+
+    jobs_contract_tests!(#[sqlx::test] async fn(pool: PgPool) { JobsDaoImpl::new(pool) });
+
+    question_webhooks_contract_tests!(#[sqlx::test] async fn(pool: PgPool) {
+        (QuestionsDaoImpl::new(pool.clone()), WebhooksDaoImpl::new(pool))
+    });
+
+    stats_contract_tests!(#[sqlx::test] async fn(pool: PgPool) {
+        (QuestionsDaoImpl::new(pool.clone()), AnswersDaoImpl::new(pool.clone()), StatsDaoImpl::new(pool))
+    });
+
+    flags_contract_tests!(#[sqlx::test] async fn(pool: PgPool) {
+        (QuestionsDaoImpl::new(pool.clone()), AnswersDaoImpl::new(pool.clone()), FlagsDaoImpl::new(pool))
+    });
+}
+
+mod memory_tests {
+    use std::sync::Arc;
+
+    use crate::{
+        models::{Answer, AnswerSort, DBError, Incident, Question, QuestionFilter, Webhook},
+        persistance::{
+            answers_dao::{AnswersDao, AnswersDaoInMemory},
+            incidents_dao::{IncidentsDao, IncidentsDaoInMemory},
+            memory::MemoryStore,
+            questions_dao::{QuestionsDao, QuestionsDaoInMemory},
+            webhooks_dao::{WebhooksDao, WebhooksDaoInMemory},
+        },
+        test_support::QuestionBuilder,
+    };
+
+    fn daos() -> (QuestionsDaoInMemory, AnswersDaoInMemory) {
+        let store = Arc::new(MemoryStore::new());
+        (QuestionsDaoInMemory::new(store.clone()), AnswersDaoInMemory::new(store))
+    }
+
+    fn question(title: &str) -> Question {
+        QuestionBuilder::new().title(title).build()
+    }
+
+    #[tokio::test]
+    async fn get_questions_should_return_questions_in_creation_order() -> Result<(), String> {
+        let (question_doa, _) = daos();
+
+        for title in ["first", "second", "third"] {
+            question_doa.create_question(question(title)).await.map_err(|e| format!("{:?}", e))?;
+        }
+
+        let results = question_doa.get_questions(&QuestionFilter::default()).await.map_err(|e| format!("{:?}", e))?;
+        let titles: Vec<&str> = results.iter().map(|q| q.title.as_str()).collect();
+
+        if titles != ["first", "second", "third"] {
+            return Err(format!("Unexpected question order: {:?}", titles));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn create_answer_should_fail_with_non_existent_uuid() -> Result<(), String> {
+        let (_, answer_doa) = daos();
+
+        let result = answer_doa
+            .create_answer(Answer {
+                answer_uuid: None,
+                question_uuid: "a22abcd2-22ab-2222-a22b-2abc2a2b22cc".parse().unwrap(),
+                content: "test content".to_owned(),
+            })
+            .await;
+
+        if let Err(DBError::InvalidUUID(_)) = result {
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected an invalid UUID error but got the following result: {:?}",
+                result
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn get_questions_should_count_answers() -> Result<(), String> {
+        let (question_doa, answer_doa) = daos();
+
+        let answered = question_doa.create_question(question("answered")).await.map_err(|e| format!("{:?}", e))?;
+        let unanswered = question_doa.create_question(question("unanswered")).await.map_err(|e| format!("{:?}", e))?;
+
+        let answer = answer_doa
+            .create_answer(Answer {
+                answer_uuid: None,
+                question_uuid: answered.question_uuid,
+                content: "test content".to_owned(),
+            })
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let results = question_doa.get_questions(&QuestionFilter::default()).await.map_err(|e| format!("{:?}", e))?;
+        let activity: Vec<_> = results.iter().map(|q| (q.answer_count, q.last_activity_at)).collect();
+
+        if activity != [(1, answer.created_at), (0, unanswered.created_at)] {
+            return Err(format!("Unexpected answer counts and activity: {:?}", activity));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_question_with_answers_should_return_its_answers() -> Result<(), String> {
+        let (question_doa, answer_doa) = daos();
+
+        let answered = question_doa.create_question(question("answered")).await.map_err(|e| format!("{:?}", e))?;
+        let other = question_doa.create_question(question("other")).await.map_err(|e| format!("{:?}", e))?;
+
+        let mut answers = Vec::new();
+        for question_uuid in [answered.question_uuid, other.question_uuid, answered.question_uuid] {
+            let answer = answer_doa
+                .create_answer(Answer {
+                    answer_uuid: None,
+                    question_uuid,
+                    content: "test content".to_owned(),
+                })
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+            answers.push(answer);
+        }
+
+        let result = question_doa
+            .get_question_with_answers(answered.question_uuid)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if result.question.answer_count != 2 || result.answers != [answers[0].clone(), answers[2].clone()] {
+            return Err(format!("Unexpected question with answers: {:?}", result));
+        }
+
+        question_doa
+            .delete_question(answered.question_uuid)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        match question_doa.get_question_with_answers(answered.question_uuid).await {
+            Err(DBError::InvalidUUID(_)) => Ok(()),
+            result => Err(format!("Expected an invalid UUID error but got the following result: {:?}", result)),
+        }
+    }
+
+    #[tokio::test]
+    async fn delete_question_should_delete_its_answers() -> Result<(), String> {
+        let (question_doa, answer_doa) = daos();
+
+        let deleted = question_doa.create_question(question("deleted")).await.map_err(|e| format!("{:?}", e))?;
+        let kept = question_doa.create_question(question("kept")).await.map_err(|e| format!("{:?}", e))?;
+
+        for question_uuid in [&deleted.question_uuid, &kept.question_uuid] {
+            answer_doa
+                .create_answer(Answer {
+                    answer_uuid: None,
+                    question_uuid: *question_uuid,
+                    content: "test content".to_owned(),
+                })
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+        }
+
+        question_doa
+            .delete_question(deleted.question_uuid)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let deleted_answers = answer_doa
+            .get_answers(deleted.question_uuid, AnswerSort::default())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+        let kept_answers = answer_doa
+            .get_answers(kept.question_uuid, AnswerSort::default())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if !deleted_answers.is_empty() || kept_answers.len() != 1 {
+            return Err(format!(
+                "Expected only the answers of the deleted question to be gone, got {:?} and {:?}",
+                deleted_answers, kept_answers
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_active_incidents_should_return_unresolved_newest_first() -> Result<(), String> {
+        let doa = IncidentsDaoInMemory::new(Arc::new(MemoryStore::new()));
+
+        let mut created = Vec::new();
+        for title in ["older", "resolved", "newer"] {
+            let incident = doa
+                .create_incident(Incident {
+                    title: title.to_owned(),
+                    description: "test description".to_owned(),
+                })
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+            created.push(incident);
+        }
+
+        doa.resolve_incident(created[1].incident_uuid)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let results = doa.get_active_incidents().await.map_err(|e| format!("{:?}", e))?;
+
+        if results != vec![created[2].clone(), created[0].clone()] {
+            return Err(format!("Expected the active incidents newest first but got: {:?}", results));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn get_webhooks_should_list_oldest_first_until_deleted() -> Result<(), String> {
+        let doa = WebhooksDaoInMemory::new(Arc::new(MemoryStore::new()));
+
+        let mut created = Vec::new();
+        for url in ["https://example.com/first", "https://example.com/second", "https://example.com/third"] {
+            let webhook = doa
+                .create_webhook(Webhook {
+                    url: url.to_owned(),
+                    secret: "0123456789abcdef".to_owned(),
+                })
+                .await
+                .map_err(|e| format!("{:?}", e))?;
+            created.push(webhook);
+        }
+
+        doa.delete_webhook(created[1].webhook_uuid)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        let results = doa.get_webhooks().await.map_err(|e| format!("{:?}", e))?;
+
+        if results != vec![created[0].clone(), created[2].clone()] {
+            return Err(format!("Expected the remaining webhooks oldest first but got: {:?}", results));
+        }
+
+        Ok(())
+    }
+
+    mod contract_tests {
+        use std::sync::Arc;
+
+        use crate::persistance::{
+            answers_dao::AnswersDaoInMemory,
+            contract::{
+                dao_contract_tests, flags_contract_tests, idempotency_contract_tests, jobs_contract_tests,
+                outbox_contract_tests, question_webhooks_contract_tests, stats_contract_tests,
+            },
+            flags_dao::FlagsDaoInMemory,
+            idempotency_dao::IdempotencyDaoInMemory,
+            jobs_dao::JobsDaoInMemory,
+            memory::MemoryStore,
+            outbox_dao::OutboxDaoInMemory,
+            questions_dao::QuestionsDaoInMemory,
+            stats_dao::StatsDaoInMemory,
+            webhooks_dao::WebhooksDaoInMemory,
+        };
+
+        dao_contract_tests!(#[tokio::test] async fn() { super::daos() });
+
+        outbox_contract_tests!(#[tokio::test] async fn() {
+            let store = Arc::new(MemoryStore::new());
+            (
+                QuestionsDaoInMemory::new(store.clone()),
+                AnswersDaoInMemory::new(store.clone()),
+                OutboxDaoInMemory::new(store),
+            )
+        });
+
+        idempotency_contract_tests!(#[tokio::test] async fn() {
+            IdempotencyDaoInMemory::new(Arc::new(MemoryStore::new()))
+        });
+
+        jobs_contract_tests!(#[tokio::test] async fn() { JobsDaoInMemory::new(Arc::new(MemoryStore::new())) });
+
+        question_webhooks_contract_tests!(#[tokio::test] async fn() {
+            let store = Arc::new(MemoryStore::new());
+            (QuestionsDaoInMemory::new(store.clone()), WebhooksDaoInMemory::new(store))
+        });
+
+        stats_contract_tests!(#[tokio::test] async fn() {
+            let store = Arc::new(MemoryStore::new());
+            (
+                QuestionsDaoInMemory::new(store.clone()),
+                AnswersDaoInMemory::new(store.clone()),
+                StatsDaoInMemory::new(store),
+            )
+        });
+
+        flags_contract_tests!(#[tokio::test] async fn() {
+            let store = Arc::new(MemoryStore::new());
+            (
+                QuestionsDaoInMemory::new(store.clone()),
+                AnswersDaoInMemory::new(store.clone()),
+                FlagsDaoInMemory::new(store),
+            )
+        });
+    }
+}
@@ -0,0 +1,423 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::{types::Uuid, PgPool};
+
+use crate::models::{DBError, QuestionUuid, QuestionWebhookDetail, Webhook, WebhookDetail};
+
+use super::memory::{self, MemoryStore};
+
+/// A trait representing data access operations for webhooks in the database.
+#[async_trait]
+pub trait WebhooksDao {
+
+    /// Asynchronously registers a new webhook in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `webhook` - The webhook to be registered.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly registered webhook detail on success, or a `DBError` on failure.
+    async fn create_webhook(&self, webhook: Webhook) -> Result<WebhookDetail, DBError>;
+
+    /// Asynchronously deletes a webhook from the database, which stops its deliveries.
+    ///
+    /// # Arguments
+    ///
+    /// * `webhook_uuid` - The unique identifier of the webhook to be deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    async fn delete_webhook(&self, webhook_uuid: Uuid) -> Result<(), DBError>;
+
+    /// Asynchronously retrieves all webhooks, with their secrets, from the database, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of webhook details on success, or a `DBError` on failure.
+    async fn get_webhooks(&self) -> Result<Vec<WebhookDetail>, DBError>;
+
+    /// Asynchronously registers a webhook notified of the new answers to a single question, until it expires or
+    /// the question is deleted.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question watched.
+    /// * `webhook` - The webhook to be registered.
+    /// * `expires_at` - The time the webhook stops being notified.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly registered webhook detail on success, or a `DBError` on failure. The error
+    /// is `DBError::InvalidUUID` if the question does not exist.
+    async fn create_question_webhook(
+        &self,
+        question_uuid: QuestionUuid,
+        webhook: Webhook,
+        expires_at: DateTime<Utc>,
+    ) -> Result<QuestionWebhookDetail, DBError>;
+
+    /// Asynchronously retrieves the webhooks of a question that have not expired, with their secrets, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of webhook details on success, or a `DBError` on failure.
+    async fn get_question_webhooks(&self, question_uuid: QuestionUuid) -> Result<Vec<QuestionWebhookDetail>, DBError>;
+
+    /// Asynchronously deletes the question webhooks that expired at or before a time.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - The time up to which webhooks have expired.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of webhooks deleted on success, or a `DBError` on failure.
+    async fn delete_expired_question_webhooks(&self, before: DateTime<Utc>) -> Result<u64, DBError>;
+}
+
+/// A question webhook as stored in Postgres
+struct QuestionWebhookRow {
+    webhook_uuid: Uuid,
+    question_uuid: Uuid,
+    url: String,
+    secret: String,
+    created_at: NaiveDateTime,
+    expires_at: NaiveDateTime,
+}
+
+impl From<QuestionWebhookRow> for QuestionWebhookDetail {
+    fn from(r: QuestionWebhookRow) -> Self {
+        QuestionWebhookDetail {
+            webhook_uuid: r.webhook_uuid,
+            question_uuid: r.question_uuid.into(),
+            url: r.url,
+            secret: r.secret,
+            created_at: r.created_at.and_utc(),
+            expires_at: r.expires_at.and_utc(),
+        }
+    }
+}
+
+/// Implementation of the `WebhooksDao` trait for PostgreSQL database.
+pub struct WebhooksDaoImpl {
+    db: PgPool,
+}
+
+/// Constructor
+impl WebhooksDaoImpl {
+    pub fn new(db: PgPool) -> Self {
+        WebhooksDaoImpl { db }
+    }
+}
+
+#[async_trait]
+impl WebhooksDao for WebhooksDaoImpl {
+
+    /// Asynchronously registers a new webhook in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `webhook` - The webhook to be registered.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly registered webhook detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn create_webhook(&self, webhook: Webhook) -> Result<WebhookDetail, DBError> {
+
+        // Insert record into DB
+        let record = sqlx::query!(
+            r#"
+                INSERT INTO webhooks ( url, secret )
+                VALUES ( $1, $2 )
+                RETURNING *
+            "#,
+            webhook.url,
+            webhook.secret
+        ).fetch_one(&self.db).await.map_err(|e| DBError::Other(Box::new(e)))?;
+
+        // Return created record
+        Ok(WebhookDetail {
+            webhook_uuid: record.webhook_uuid,
+            url: record.url,
+            secret: record.secret,
+            created_at: record.created_at.and_utc(),
+        })
+    }
+
+    /// Asynchronously deletes a webhook from the database, which stops its deliveries.
+    ///
+    /// # Arguments
+    ///
+    /// * `webhook_uuid` - The unique identifier of the webhook to be deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn delete_webhook(&self, webhook_uuid: Uuid) -> Result<(), DBError> {
+        sqlx::query!("DELETE FROM webhooks WHERE webhook_uuid = $1", webhook_uuid)
+            .execute(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    /// Asynchronously retrieves all webhooks, with their secrets, from the database, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of webhook details on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_webhooks(&self) -> Result<Vec<WebhookDetail>, DBError> {
+        let records = sqlx::query!("SELECT * FROM webhooks ORDER BY created_at")
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        // Put the records in an array of WebhookDetail
+        let webhooks = records.into_iter().map(|r| WebhookDetail {
+            webhook_uuid: r.webhook_uuid,
+            url: r.url,
+            secret: r.secret,
+            created_at: r.created_at.and_utc(),
+        }).collect();
+
+        Ok(webhooks)
+    }
+
+    /// Asynchronously registers a webhook notified of the new answers to a single question in the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question watched.
+    /// * `webhook` - The webhook to be registered.
+    /// * `expires_at` - The time the webhook stops being notified.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly registered webhook detail on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn create_question_webhook(
+        &self,
+        question_uuid: QuestionUuid,
+        webhook: Webhook,
+        expires_at: DateTime<Utc>,
+    ) -> Result<QuestionWebhookDetail, DBError> {
+
+        // Only insert if the question exists, selecting it rather than relying on the foreign key to tell
+        let record = sqlx::query_as!(
+            QuestionWebhookRow,
+            r#"
+                INSERT INTO question_webhooks ( question_uuid, url, secret, created_at, expires_at )
+                SELECT question_uuid, $2, $3, $4, $5 FROM questions
+                WHERE question_uuid = $1
+                RETURNING *
+            "#,
+            question_uuid.as_uuid(),
+            webhook.url,
+            webhook.secret,
+            super::now().naive_utc(),
+            expires_at.naive_utc()
+        ).fetch_optional(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        record.map(QuestionWebhookDetail::from).ok_or_else(|| super::unknown_question(question_uuid))
+    }
+
+    /// Asynchronously retrieves the webhooks of a question that have not expired from the database, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of webhook details on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn get_question_webhooks(&self, question_uuid: QuestionUuid) -> Result<Vec<QuestionWebhookDetail>, DBError> {
+        let records = sqlx::query_as!(
+            QuestionWebhookRow,
+            "SELECT * FROM question_webhooks WHERE question_uuid = $1 AND expires_at > $2 ORDER BY created_at",
+            question_uuid.as_uuid(),
+            super::now().naive_utc()
+        ).fetch_all(&self.db)
+         .await
+         .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(records.into_iter().map(QuestionWebhookDetail::from).collect())
+    }
+
+    /// Asynchronously deletes the question webhooks that expired at or before a time from the database.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - The time up to which webhooks have expired.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of webhooks deleted on success, or a `DBError` on failure.
+    #[cfg_attr(feature = "otel", tracing::instrument(skip_all, err(Debug)))]
+    async fn delete_expired_question_webhooks(&self, before: DateTime<Utc>) -> Result<u64, DBError> {
+        let result = sqlx::query!("DELETE FROM question_webhooks WHERE expires_at <= $1", before.naive_utc())
+            .execute(&self.db)
+            .await
+            .map_err(|e| DBError::Other(Box::new(e)))?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Implementation of the `WebhooksDao` trait keeping webhooks in memory, for local
+/// development and tests.
+pub struct WebhooksDaoInMemory {
+    store: Arc<MemoryStore>,
+}
+
+/// Constructor
+impl WebhooksDaoInMemory {
+    pub fn new(store: Arc<MemoryStore>) -> Self {
+        WebhooksDaoInMemory { store }
+    }
+}
+
+#[async_trait]
+impl WebhooksDao for WebhooksDaoInMemory {
+
+    /// Asynchronously registers a new webhook in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `webhook` - The webhook to be registered.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly registered webhook detail on success, or a `DBError` on failure.
+    async fn create_webhook(&self, webhook: Webhook) -> Result<WebhookDetail, DBError> {
+        let uuid = Uuid::new_v4();
+
+        let detail = WebhookDetail {
+            webhook_uuid: uuid,
+            url: webhook.url,
+            secret: webhook.secret,
+            created_at: super::now(),
+        };
+
+        let row = self.store.row(detail.clone());
+        self.store.webhooks.write().map_err(memory::poisoned)?.insert(uuid, row);
+
+        Ok(detail)
+    }
+
+    /// Asynchronously deletes a webhook from memory, which stops its deliveries.
+    ///
+    /// # Arguments
+    ///
+    /// * `webhook_uuid` - The unique identifier of the webhook to be deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `DBError` is returned.
+    async fn delete_webhook(&self, webhook_uuid: Uuid) -> Result<(), DBError> {
+        self.store.webhooks.write().map_err(memory::poisoned)?.remove(&webhook_uuid);
+
+        Ok(())
+    }
+
+    /// Asynchronously retrieves all webhooks, with their secrets, from memory, oldest first.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of webhook details on success, or a `DBError` on failure.
+    async fn get_webhooks(&self) -> Result<Vec<WebhookDetail>, DBError> {
+        let webhooks = self.store.webhooks.read().map_err(memory::poisoned)?;
+
+        Ok(memory::in_order(webhooks.values()))
+    }
+
+    /// Asynchronously registers a webhook notified of the new answers to a single question in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question watched.
+    /// * `webhook` - The webhook to be registered.
+    /// * `expires_at` - The time the webhook stops being notified.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the newly registered webhook detail on success, or a `DBError` on failure.
+    async fn create_question_webhook(
+        &self,
+        question_uuid: QuestionUuid,
+        webhook: Webhook,
+        expires_at: DateTime<Utc>,
+    ) -> Result<QuestionWebhookDetail, DBError> {
+
+        // Hold the questions lock so the question cannot be deleted before the webhook is inserted
+        let questions = self.store.questions.read().map_err(memory::poisoned)?;
+        if !questions.contains_key(&question_uuid) {
+            return Err(super::unknown_question(question_uuid));
+        }
+
+        let detail = QuestionWebhookDetail {
+            webhook_uuid: Uuid::new_v4(),
+            question_uuid,
+            url: webhook.url,
+            secret: webhook.secret,
+            created_at: super::now(),
+            expires_at,
+        };
+
+        let row = self.store.row(detail.clone());
+        self.store.question_webhooks.write().map_err(memory::poisoned)?.insert(detail.webhook_uuid, row);
+
+        Ok(detail)
+    }
+
+    /// Asynchronously retrieves the webhooks of a question that have not expired from memory, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing a vector of webhook details on success, or a `DBError` on failure.
+    async fn get_question_webhooks(&self, question_uuid: QuestionUuid) -> Result<Vec<QuestionWebhookDetail>, DBError> {
+        let webhooks = self.store.question_webhooks.read().map_err(memory::poisoned)?;
+        let now = super::now();
+
+        Ok(memory::in_order(
+            webhooks.values().filter(|row| row.value.question_uuid == question_uuid && row.value.expires_at > now),
+        ))
+    }
+
+    /// Asynchronously deletes the question webhooks that expired at or before a time from memory.
+    ///
+    /// # Arguments
+    ///
+    /// * `before` - The time up to which webhooks have expired.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the number of webhooks deleted on success, or a `DBError` on failure.
+    async fn delete_expired_question_webhooks(&self, before: DateTime<Utc>) -> Result<u64, DBError> {
+        let mut webhooks = self.store.question_webhooks.write().map_err(memory::poisoned)?;
+        let count = webhooks.len();
+
+        webhooks.retain(|_, row| row.value.expires_at > before);
+
+        Ok((count - webhooks.len()) as u64)
+    }
+}
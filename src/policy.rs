@@ -0,0 +1,214 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use moka::future::Cache;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::auth::AdminScope;
+
+// Enterprises managing authorization centrally decide which admin requests services may make in Open Policy Agent,
+// rather than through the scopes listed for them in `SERVICE_PRINCIPALS`. Those scopes are still submitted with every
+// decision, so a policy may build upon them, and still decide when the policy engine is unreachable, so an outage of
+// it does not lock services out. The admin token is never submitted to the policy, it grants every endpoint.
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Most decisions cached, the least used ones being evicted beyond it
+const MAX_CACHED_DECISIONS: u64 = 10_000;
+
+/// Errors for decisions the policy engine could not make
+#[derive(Error, Debug)]
+pub enum PolicyError {
+
+    /// The request could not be sent, or its response read
+    #[error("Policy request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    /// The policy engine answered with an error
+    #[error("Policy engine answered with {0}")]
+    Status(StatusCode),
+}
+
+/// An admin request made by a service principal, as submitted to the policy.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct PolicyInput {
+    /// The name of the service
+    pub service: String,
+    pub method: String,
+    /// The route requested, e.g. `/admin/jobs/:job_uuid`
+    pub route: String,
+    /// The scope of the route, `None` for routes only the admin token grants
+    pub scope: Option<AdminScope>,
+    /// The scopes the service is granted in `SERVICE_PRINCIPALS`
+    pub granted: Vec<AdminScope>,
+}
+
+/// A trait representing a policy deciding which admin requests services may make.
+#[async_trait]
+pub trait AuthorizationPolicy {
+
+    /// Asynchronously decides whether a service may make an admin request.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The service and the request it makes.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing whether the request is allowed on success, otherwise, a `PolicyError` if no decision
+    /// could be made.
+    async fn authorize(&self, input: &PolicyInput) -> Result<bool, PolicyError>;
+}
+
+/// Decision of an OPA rule, undefined when none of its definitions apply
+#[derive(Deserialize)]
+struct Decision {
+    result: Option<bool>,
+}
+
+/// Policy evaluated by an Open Policy Agent server, through its data API.
+///
+/// Decisions are cached for a while, so a service polling a job does not cost a round trip per request. Failures are
+/// not cached, the next request asks again.
+pub struct OpaPolicy {
+    client: reqwest::Client,
+    /// The URL of the rule deciding, e.g. `http://localhost:8181/v1/data/qna/admin/allow`
+    url: String,
+    decisions: Cache<PolicyInput, bool>,
+}
+
+impl OpaPolicy {
+
+    /// Creates a client for a rule of an OPA server.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The URL of the rule in the data API, which must evaluate to a boolean.
+    /// * `cache_ttl` - How long a decision is reused for the same service and request.
+    pub fn new(url: &str, cache_ttl: Duration) -> Self {
+        OpaPolicy {
+            // The URL is set by the operator, so it does not need an `OutboundClient`
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("Failed to create policy HTTP client!"),
+            url: url.to_owned(),
+            decisions: Cache::builder().max_capacity(MAX_CACHED_DECISIONS).time_to_live(cache_ttl).build(),
+        }
+    }
+}
+
+#[async_trait]
+impl AuthorizationPolicy for OpaPolicy {
+    async fn authorize(&self, input: &PolicyInput) -> Result<bool, PolicyError> {
+        if let Some(allowed) = self.decisions.get(input).await {
+            return Ok(allowed);
+        }
+
+        let response = self.client.post(&self.url).json(&json!({ "input": input })).send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(PolicyError::Status(status));
+        }
+
+        // A rule left undefined denies, as no definition allows the request
+        let allowed = response.json::<Decision>().await?.result.unwrap_or(false);
+
+        self.decisions.insert(input.clone(), allowed).await;
+        Ok(allowed)
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::{Arc, Mutex};
+
+    use axum::{extract::State, routing::post, Json, Router};
+    use serde_json::Value;
+
+    /// Inputs received by a fake OPA server
+    type Inputs = Arc<Mutex<Vec<Value>>>;
+
+    /// Serves a fake OPA server, recording the inputs it gets and answering them with `status` and `response`.
+    async fn opa(status: StatusCode, response: Value) -> (String, Inputs) {
+        let inputs = Arc::new(Mutex::new(Vec::new()));
+
+        let app = Router::new()
+            .route(
+                "/v1/data/qna/admin/allow",
+                post(move |State(inputs): State<Inputs>, Json(body): Json<Value>| async move {
+                    inputs.lock().unwrap().push(body["input"].clone());
+                    (status, Json(response))
+                }),
+            )
+            .with_state(inputs.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/v1/data/qna/admin/allow", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (url, inputs)
+    }
+
+    fn input(route: &str) -> PolicyInput {
+        PolicyInput {
+            service: "status-page".to_owned(),
+            method: "POST".to_owned(),
+            route: route.to_owned(),
+            scope: Some(AdminScope::Incidents),
+            granted: vec![AdminScope::Incidents],
+        }
+    }
+
+    #[tokio::test]
+    async fn should_submit_the_request_and_cache_the_decision() {
+        let (url, inputs) = opa(StatusCode::OK, json!({ "result": true })).await;
+        let policy = OpaPolicy::new(&url, Duration::from_secs(60));
+
+        assert!(policy.authorize(&input("/admin/incident")).await.unwrap());
+        assert!(policy.authorize(&input("/admin/incident")).await.unwrap());
+
+        let inputs = inputs.lock().unwrap();
+        assert_eq!(
+            *inputs,
+            [json!({
+                "service": "status-page",
+                "method": "POST",
+                "route": "/admin/incident",
+                "scope": "incidents",
+                "granted": ["incidents"],
+            })]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_deny_undefined_decisions() {
+        let (url, _) = opa(StatusCode::OK, json!({})).await;
+        let policy = OpaPolicy::new(&url, Duration::from_secs(60));
+
+        assert!(!policy.authorize(&input("/admin/incident")).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn should_fail_without_caching_when_the_server_errs() {
+        let (url, inputs) = opa(StatusCode::INTERNAL_SERVER_ERROR, json!({})).await;
+        let policy = OpaPolicy::new(&url, Duration::from_secs(60));
+
+        for _ in 0..2 {
+            let result = policy.authorize(&input("/admin/incident")).await;
+            assert!(matches!(result, Err(PolicyError::Status(StatusCode::INTERNAL_SERVER_ERROR))));
+        }
+
+        assert_eq!(inputs.lock().unwrap().len(), 2);
+    }
+}
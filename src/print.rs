@@ -0,0 +1,109 @@
+use std::fmt::Write;
+
+use chrono::SecondsFormat;
+
+use crate::{excerpt::plain_text, models::QuestionWithAnswers};
+
+// The plain text rendering of a question is read in terminals and fed to prompt builders, so it is stable and
+// unadorned: a header of `Name: value` metadata, then the description and each answer as paragraphs of text.
+
+/// Separates the answers of a question, and the first of them from the question
+const SEPARATOR: &str = "----------------------------------------";
+
+/// Renders a question and its answers as plain text, markdown stripped.
+///
+/// # Arguments
+///
+/// * `question` - The question with its answers, in the order to print them.
+///
+/// # Returns
+///
+/// The text, ending with a newline.
+pub fn render_question(question: &QuestionWithAnswers) -> String {
+    let QuestionWithAnswers { question, answers } = question;
+    let title = plain_text(&question.title).replace('\n', " ");
+
+    let mut text = String::new();
+    let _ = writeln!(text, "{}", title);
+    let _ = writeln!(text, "{}", "=".repeat(title.chars().count()));
+    let _ = writeln!(text);
+    let _ = writeln!(text, "Question: {}", question.question_uuid);
+    let _ = writeln!(text, "Status: {}", question.status);
+    let _ = writeln!(text, "Asked: {}", question.created_at.to_rfc3339_opts(SecondsFormat::Secs, true));
+    let _ = writeln!(text, "Last activity: {}", question.last_activity_at.to_rfc3339_opts(SecondsFormat::Secs, true));
+    let _ = writeln!(text, "Answers: {}", answers.len());
+
+    let description = plain_text(&question.description);
+    if !description.is_empty() {
+        let _ = write!(text, "\n{}\n", description);
+    }
+
+    for (i, answer) in answers.iter().enumerate() {
+        let _ = writeln!(text, "\n{}", SEPARATOR);
+        let _ = writeln!(
+            text,
+            "Answer {} of {}, {}",
+            i + 1,
+            answers.len(),
+            answer.created_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+        );
+        let _ = write!(text, "\n{}\n", plain_text(&answer.content));
+    }
+
+    text
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::{TimeZone, Utc};
+
+    use crate::test_support::{AnswerBuilder, QuestionBuilder};
+
+    #[test]
+    fn should_render_question_with_answers() {
+        let asked = Utc.with_ymd_and_hms(2024, 3, 23, 11, 14, 56).unwrap();
+        let answered = Utc.with_ymd_and_hms(2024, 3, 24, 9, 0, 0).unwrap();
+
+        let mut question = QuestionBuilder::new()
+            .title("How do I split a *crate*?")
+            .description("Into a **library** and a binary.\n\nSee [the book](https://doc.rust-lang.org/book/).")
+            .build_detail();
+        question.created_at = asked;
+        question.answer_count = 1;
+        question.last_activity_at = answered;
+
+        let mut answer = AnswerBuilder::new(question.question_uuid).content("Add a `src/lib.rs`.").build_detail();
+        answer.created_at = answered;
+
+        let question = QuestionWithAnswers { question, answers: vec![answer] };
+
+        let expected = format!(
+            "How do I split a crate?\n\
+             =======================\n\
+             \n\
+             Question: {}\n\
+             Status: open\n\
+             Asked: 2024-03-23T11:14:56Z\n\
+             Last activity: 2024-03-24T09:00:00Z\n\
+             Answers: 1\n\
+             \n\
+             Into a library and a binary.\n\
+             \n\
+             See the book.\n\
+             \n\
+             ----------------------------------------\n\
+             Answer 1 of 1, 2024-03-24T09:00:00Z\n\
+             \n\
+             Add a src/lib.rs.\n",
+            question.question.question_uuid
+        );
+
+        assert_eq!(render_question(&question), expected);
+    }
+}
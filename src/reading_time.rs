@@ -0,0 +1,55 @@
+// Clients show how long questions and answers take to read, and analytics add up how much was written, from word
+// counts computed when content is written and stored along with it, rather than on every read. Migrations count the
+// words of content written before them the same way, in SQL.
+
+/// Words read per minute, a usual estimate for prose
+const WORDS_PER_MINUTE: i64 = 200;
+
+/// Counts the words of texts, a word being a run of characters other than whitespace.
+///
+/// # Arguments
+///
+/// * `texts` - The texts, such as the title and description of a question.
+///
+/// # Returns
+///
+/// The number of words of all the texts together.
+pub fn word_count(texts: &[&str]) -> i64 {
+    texts.iter().map(|text| text.split_whitespace().count() as i64).sum()
+}
+
+/// Estimates the time it takes to read words.
+///
+/// # Arguments
+///
+/// * `word_count` - The number of words read.
+///
+/// # Returns
+///
+/// The seconds it takes to read the words, rounded up, so any text takes at least a second.
+pub fn reading_time_seconds(word_count: i64) -> i64 {
+    (word_count * 60 + WORDS_PER_MINUTE - 1) / WORDS_PER_MINUTE
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_count_runs_of_characters_other_than_whitespace() {
+        assert_eq!(word_count(&["How do I", "test  this\nthing\tnow? "]), 7);
+        assert_eq!(word_count(&["", " \n "]), 0);
+    }
+
+    #[test]
+    fn should_round_reading_times_up_to_the_second() {
+        assert_eq!(reading_time_seconds(0), 0);
+        assert_eq!(reading_time_seconds(1), 1);
+        assert_eq!(reading_time_seconds(200), 60);
+        assert_eq!(reading_time_seconds(201), 61);
+    }
+}
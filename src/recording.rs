@@ -0,0 +1,388 @@
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::{sync::Semaphore, time::Instant};
+
+/// Represents a request captured by the recorder, one per line of a recording
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecordedRequest {
+    /// Time since recording started when the request arrived
+    pub offset_ms: u64,
+    pub method: String,
+    /// Path and query string
+    pub uri: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+#[derive(Error, Debug)]
+pub enum ReplayError {
+    #[error("Failed to read recording: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid recording on line {line}: {source}")]
+    Parse {
+        line: usize,
+        source: serde_json::Error,
+    },
+
+    #[error("Invalid method on line {line}: {method}")]
+    Method { line: usize, method: String },
+}
+
+/// Controls how a recording is replayed
+#[derive(Debug, Clone)]
+pub struct ReplayOptions {
+    /// Base URL of the instance to send requests to, e.g. `http://localhost:8000`
+    pub target: String,
+    /// Factor the original pace is multiplied by, requests are sent back to back when 0
+    pub speed: f64,
+    /// Most requests waiting for a response at once
+    pub concurrency: usize,
+}
+
+/// Represents the outcome of a replay
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReplaySummary {
+    pub sent: usize,
+    pub succeeded: usize,
+    pub client_errors: usize,
+    pub server_errors: usize,
+    /// Requests that got no response at all
+    pub failed: usize,
+}
+
+/// Reads a recording written by the recorder.
+///
+/// # Arguments
+///
+/// * `path` - Path of the recording.
+///
+/// # Returns
+///
+/// A `Result` containing the recorded requests in order on success, or a `ReplayError` naming the offending line.
+pub fn load(path: &Path) -> Result<Vec<RecordedRequest>, ReplayError> {
+    let contents = std::fs::read_to_string(path)?;
+
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|source| ReplayError::Parse { line: index + 1, source })
+        })
+        .collect()
+}
+
+/// Re-issues recorded requests against a target instance, keeping their relative timing.
+///
+/// # Arguments
+///
+/// * `requests` - The recorded requests, in recording order.
+/// * `options` - Where to send the requests and how fast.
+///
+/// # Returns
+///
+/// A `Result` containing a `ReplaySummary` of the responses on success, or a `ReplayError` if a request cannot be rebuilt.
+pub async fn replay(requests: Vec<RecordedRequest>, options: &ReplayOptions) -> Result<ReplaySummary, ReplayError> {
+    let client = reqwest::Client::new();
+    let target = options.target.trim_end_matches('/').to_owned();
+    let in_flight = Arc::new(Semaphore::new(options.concurrency.max(1)));
+    let started_at = Instant::now();
+
+    let mut responses = Vec::with_capacity(requests.len());
+
+    for (index, request) in requests.into_iter().enumerate() {
+        let method = Method::from_bytes(request.method.as_bytes()).map_err(|_| ReplayError::Method {
+            line: index + 1,
+            method: request.method.clone(),
+        })?;
+
+        if options.speed > 0.0 {
+            let offset = Duration::from_millis(request.offset_ms).div_f64(options.speed);
+            tokio::time::sleep_until(started_at + offset).await;
+        }
+
+        let permit = in_flight.clone().acquire_owned().await.expect("Replay semaphore closed!");
+
+        let mut builder = client.request(method, format!("{}{}", target, request.uri)).body(request.body);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+
+        responses.push(tokio::spawn(async move {
+            let _permit = permit;
+            builder.send().await.map(|response| response.status())
+        }));
+    }
+
+    let mut summary = ReplaySummary::default();
+
+    for response in responses {
+        summary.sent += 1;
+
+        match response.await.expect("Replay task panicked!") {
+            Ok(status) if status.is_server_error() => summary.server_errors += 1,
+            Ok(status) if status.is_client_error() => summary.client_errors += 1,
+            Ok(_) => summary.succeeded += 1,
+            Err(err) => {
+                debug!("Replayed request failed: {}", err);
+                summary.failed += 1;
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+#[cfg(feature = "record")]
+pub use recorder::{record_requests, Recorder};
+
+#[cfg(feature = "record")]
+mod recorder {
+    use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+    use axum::{
+        body::{to_bytes, Body},
+        extract::{Request, State},
+        http::StatusCode,
+        middleware::Next,
+        response::{IntoResponse, Response},
+    };
+    use tokio::{
+        io::{AsyncWriteExt, BufWriter},
+        sync::mpsc,
+        time::Instant,
+    };
+
+    use super::RecordedRequest;
+    use crate::canary::MAX_BODY_SIZE;
+
+    /// Headers kept in recordings, anything else (e.g. `Authorization`, `Cookie`) is dropped
+    const RECORDED_HEADERS: [&str; 2] = ["content-type", "accept"];
+
+    /// Requests waiting to be written past this limit are dropped rather than slowing down the API
+    const QUEUE_SIZE: usize = 1024;
+
+    /// Appends sanitized requests to a recording from a background task.
+    pub struct Recorder {
+        sender: mpsc::Sender<RecordedRequest>,
+        started_at: Instant,
+    }
+
+    impl Recorder {
+
+        /// Creates the recording, replacing any existing file, and starts writing to it.
+        ///
+        /// # Arguments
+        ///
+        /// * `path` - Path of the recording.
+        ///
+        /// # Returns
+        ///
+        /// A `Result` containing the `Recorder` on success, or an `io::Error` if the file cannot be created.
+        pub async fn start(path: &Path) -> std::io::Result<Self> {
+            let file = tokio::fs::File::create(path).await?;
+            let (sender, mut receiver) = mpsc::channel::<RecordedRequest>(QUEUE_SIZE);
+
+            tokio::spawn(async move {
+                let mut writer = BufWriter::new(file);
+
+                while let Some(request) = receiver.recv().await {
+                    let mut line = serde_json::to_vec(&request).expect("Recorded requests always serialize");
+                    line.push(b'\n');
+
+                    let mut result = writer.write_all(&line).await;
+
+                    // Batch writes while requests keep coming, but never leave any unflushed
+                    if result.is_ok() && receiver.is_empty() {
+                        result = writer.flush().await;
+                    }
+
+                    if let Err(err) = result {
+                        error!("Failed to write recording, stopping the recorder: {}", err);
+                        return;
+                    }
+                }
+            });
+
+            Ok(Recorder {
+                sender,
+                started_at: Instant::now(),
+            })
+        }
+    }
+
+    /// Middleware recording every request it wraps before passing it on.
+    ///
+    /// # Arguments
+    ///
+    /// * `State(recorder)` - The recorder to write to.
+    /// * `request` - The incoming request.
+    /// * `next` - The rest of the middleware stack.
+    ///
+    /// # Returns
+    ///
+    /// The response produced by the inner service, unchanged.
+    pub async fn record_requests(State(recorder): State<Arc<Recorder>>, request: Request, next: Next) -> Response {
+        let (parts, body) = request.into_parts();
+        let body = match to_bytes(body, MAX_BODY_SIZE).await {
+            Ok(body) => body,
+            Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+        };
+
+        let headers: BTreeMap<String, String> = parts.headers
+            .iter()
+            .filter(|(name, _)| RECORDED_HEADERS.contains(&name.as_str()))
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+            .collect();
+
+        let recorded = RecordedRequest {
+            offset_ms: recorder.started_at.elapsed().as_millis() as u64,
+            method: parts.method.to_string(),
+            uri: parts.uri.path_and_query().map(|p| p.to_string()).unwrap_or_default(),
+            headers,
+            body: String::from_utf8_lossy(&body).into_owned(),
+        };
+
+        if recorder.sender.try_send(recorded).is_err() {
+            warn!("Recorder is falling behind, dropped a request.");
+        }
+
+        next.run(Request::from_parts(parts, Body::from(body))).await
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Mutex;
+
+    use axum::{extract::Request, http::StatusCode, Router};
+
+    /// Serves an instance answering with the status in the `status` query parameter and
+    /// keeping the URIs of the requests it receives.
+    async fn spawn_target() -> (String, Arc<Mutex<Vec<String>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let app = Router::new().fallback({
+            let received = received.clone();
+            move |request: Request| {
+                let received = received.clone();
+                async move {
+                    let uri = request.uri().to_string();
+                    received.lock().unwrap().push(uri.clone());
+
+                    let status = uri.split("status=").nth(1).and_then(|s| s.parse().ok()).unwrap_or(200);
+                    StatusCode::from_u16(status).unwrap()
+                }
+            }
+        });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (format!("http://{}", address), received)
+    }
+
+    fn recorded(offset_ms: u64, uri: &str) -> RecordedRequest {
+        RecordedRequest {
+            offset_ms,
+            method: "GET".to_owned(),
+            uri: uri.to_owned(),
+            headers: BTreeMap::new(),
+            body: String::new(),
+        }
+    }
+
+    #[test]
+    fn should_report_invalid_line() {
+        let path = std::env::temp_dir().join(format!("tech-qna-recording-{}.jsonl", std::process::id()));
+        let line = serde_json::to_string(&recorded(0, "/questions")).unwrap();
+        std::fs::write(&path, format!("{}\n\nnot json\n", line)).unwrap();
+
+        let result = load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(ReplayError::Parse { line: 3, .. })));
+    }
+
+    #[tokio::test]
+    async fn should_replay_requests_and_count_outcomes() {
+        let (target, received) = spawn_target().await;
+
+        let requests = vec![
+            recorded(0, "/questions"),
+            recorded(10, "/answers?status=400"),
+            recorded(20, "/question?status=500"),
+        ];
+
+        let options = ReplayOptions { target, speed: 0.0, concurrency: 1 };
+        let summary = replay(requests, &options).await.unwrap();
+
+        assert_eq!(summary, ReplaySummary { sent: 3, succeeded: 1, client_errors: 1, server_errors: 1, failed: 0 });
+        assert_eq!(received.lock().unwrap().as_slice(), ["/questions", "/answers?status=400", "/question?status=500"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_scale_delays_by_speed() {
+        let (target, _) = spawn_target().await;
+
+        let options = ReplayOptions { target, speed: 2.0, concurrency: 4 };
+        let started_at = Instant::now();
+
+        replay(vec![recorded(0, "/questions"), recorded(10_000, "/questions")], &options).await.unwrap();
+
+        let elapsed = started_at.elapsed();
+        assert!(elapsed >= Duration::from_secs(5) && elapsed < Duration::from_secs(10), "{:?}", elapsed);
+    }
+
+    #[cfg(feature = "record")]
+    #[tokio::test]
+    async fn should_record_sanitized_requests() {
+        use axum::{body::Body, http::header, middleware::from_fn_with_state, routing::post};
+        use tower::ServiceExt;
+
+        let path = std::env::temp_dir().join(format!("tech-qna-recorder-{}.jsonl", std::process::id()));
+        let recorder = Arc::new(Recorder::start(&path).await.unwrap());
+
+        let app = Router::new()
+            .route("/question", post(|body: String| async move { body }))
+            .route_layer(from_fn_with_state(recorder, record_requests));
+
+        let request = Request::post("/question?draft=true")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::AUTHORIZATION, "Bearer secret")
+            .body(Body::from(r#"{"title":"t"}"#))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, r#"{"title":"t"}"#);
+
+        // Give the writer task a moment to flush
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let requests = load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].uri, "/question?draft=true");
+        assert_eq!(requests[0].body, r#"{"title":"t"}"#);
+        assert_eq!(
+            requests[0].headers,
+            BTreeMap::from([("content-type".to_owned(), "application/json".to_owned())])
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use std::{collections::HashSet, sync::LazyLock};
+
+use ammonia::{Builder, UrlRelative};
+use pulldown_cmark::{html, Options, Parser};
+
+// Content is stored exactly as it was submitted. Anything that turns it into HTML has to go
+// through this module, so there is a single place to audit for script injection.
+
+/// Tags allowed in HTML rendered from user content, e.g. from markdown
+const CONTENT_TAGS: &[&str] = &[
+    "a", "b", "blockquote", "br", "code", "del", "em", "h1", "h2", "h3", "h4", "h5", "h6", "hr", "i", "li",
+    "mark", "ol", "p", "pre", "strong", "sub", "sup", "table", "tbody", "td", "th", "thead", "tr", "ul",
+];
+
+/// URL schemes allowed in links, anything else (`javascript:`, `data:`, ...) is dropped
+const URL_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+static CONTENT_SANITIZER: LazyLock<Builder<'static>> = LazyLock::new(|| {
+    let mut builder = Builder::empty();
+    builder
+        .tags(CONTENT_TAGS.iter().copied().collect())
+        .tag_attributes([("a", HashSet::from(["href", "title"]))].into())
+        .url_schemes(URL_SCHEMES.iter().copied().collect())
+        .url_relative(UrlRelative::Deny)
+        .link_rel(Some("noopener noreferrer nofollow ugc"));
+    builder
+});
+
+static SNIPPET_SANITIZER: LazyLock<Builder<'static>> = LazyLock::new(|| {
+    let mut builder = Builder::empty();
+    builder.tags(HashSet::from(["mark"]));
+    builder
+});
+
+/// Escapes text so it can be embedded in HTML, both as element content and as a quoted attribute value.
+///
+/// # Arguments
+///
+/// * `text` - The text to escape.
+///
+/// # Returns
+///
+/// The text with `&`, `<`, `>`, `"` and `'` replaced by character references.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Sanitizes HTML rendered from user content, such as markdown output or link previews.
+///
+/// Only formatting tags and links are kept. Scripts, styles, event handlers and links to
+/// anything but `http`, `https` and `mailto` URLs are removed.
+///
+/// # Arguments
+///
+/// * `html` - The untrusted HTML.
+///
+/// # Returns
+///
+/// HTML that is safe to embed in a page.
+pub fn sanitize_html(html: &str) -> String {
+    CONTENT_SANITIZER.clean(html).to_string()
+}
+
+/// Renders markdown, such as a question description, to HTML that is safe to embed in a page.
+///
+/// Tables and strikethrough are rendered on top of CommonMark. Raw HTML written in the markdown goes through the same
+/// sanitizer as the rest, so only formatting tags and safe links survive, and images are dropped.
+///
+/// # Arguments
+///
+/// * `markdown` - The untrusted markdown.
+///
+/// # Returns
+///
+/// The sanitized HTML.
+pub fn render_markdown(markdown: &str) -> String {
+    let mut rendered = String::with_capacity(markdown.len() * 3 / 2);
+    html::push_html(&mut rendered, Parser::new_ext(markdown, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH));
+
+    sanitize_html(&rendered)
+}
+
+/// Sanitizes a search snippet, keeping only the `<mark>` tags that highlight matches.
+///
+/// # Arguments
+///
+/// * `html` - The snippet, with unescaped question text.
+///
+/// # Returns
+///
+/// The snippet with the question text escaped.
+pub fn sanitize_snippet(html: &str) -> String {
+    SNIPPET_SANITIZER.clean(html).to_string()
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Common XSS payloads, none of which may survive sanitization in an executable form
+    const PAYLOADS: &[&str] = &[
+        "<script>alert(1)</script>",
+        "<img src=x onerror=alert(1)>",
+        "<svg onload=alert(1)>",
+        "<body onload=alert(1)>",
+        "<iframe src=\"javascript:alert(1)\"></iframe>",
+        "<a href=\"javascript:alert(1)\">link</a>",
+        "<a href=\"JaVaScRiPt:alert(1)\">link</a>",
+        "<a href=\"&#106;avascript:alert(1)\">link</a>",
+        "<a href=\"data:text/html;base64,PHNjcmlwdD5hbGVydCgxKTwvc2NyaXB0Pg==\">link</a>",
+        "<p style=\"background:url(javascript:alert(1))\">styled</p>",
+        "<style>@import 'http://evil.example/x.css';</style>",
+        "<object data=\"javascript:alert(1)\"></object>",
+        "<math><mtext><table><mglyph><style><img src=x onerror=alert(1)>",
+        "<<script>script>alert(1)<</script>/script>",
+        "<mark onclick=alert(1)>match</mark>",
+    ];
+
+    fn assert_inert(html: &str) {
+        let lower = html.to_lowercase();
+
+        for needle in ["<script", "<img", "<svg", "<iframe", "<object", "<style", "javascript:", "data:", "onerror", "onload", "onclick", "style="] {
+            assert!(!lower.contains(needle), "{:?} survived in {:?}", needle, html);
+        }
+    }
+
+    #[test]
+    fn should_escape_html() {
+        assert_eq!(
+            escape_html("<b class=\"x\">Tom & Jerry's</b>"),
+            "&lt;b class=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn should_neutralize_payloads_in_content() {
+        for payload in PAYLOADS {
+            assert_inert(&sanitize_html(payload));
+        }
+    }
+
+    #[test]
+    fn should_neutralize_payloads_in_snippets() {
+        for payload in PAYLOADS {
+            assert_inert(&sanitize_snippet(payload));
+        }
+    }
+
+    #[test]
+    fn should_neutralize_escaped_payloads() {
+        for payload in PAYLOADS {
+            let escaped = escape_html(payload);
+            assert!(!escaped.contains('<') && !escaped.contains('"'), "{:?}", escaped);
+        }
+    }
+
+    #[test]
+    fn should_keep_formatting_and_safe_links() {
+        let html = sanitize_html("<p><strong>Bold</strong> <a href=\"https://www.rust-lang.org\">Rust</a></p>");
+
+        assert_eq!(
+            html,
+            "<p><strong>Bold</strong> <a href=\"https://www.rust-lang.org\" rel=\"noopener noreferrer nofollow ugc\">Rust</a></p>"
+        );
+    }
+
+    #[test]
+    fn should_neutralize_payloads_in_markdown() {
+        for payload in PAYLOADS {
+            assert_inert(&render_markdown(payload));
+        }
+
+        assert_inert(&render_markdown("[link](javascript:alert(1)) ![image](https://evil.example/x.png)"));
+    }
+
+    #[test]
+    fn should_render_markdown() {
+        assert_eq!(
+            render_markdown("Why does `unwrap` **panic**?\n\n```rust\nNone::<u8>.unwrap();\n```\n\n~~Solved~~"),
+            "<p>Why does <code>unwrap</code> <strong>panic</strong>?</p>\n<pre><code>None::&lt;u8&gt;.unwrap();\n</code></pre>\n\
+             <p><del>Solved</del></p>\n"
+        );
+    }
+
+    #[test]
+    fn should_keep_only_marks_in_snippets() {
+        assert_eq!(
+            sanitize_snippet("Why does <b>this</b> <mark>panic</mark> & <script>x</script>"),
+            "Why does this <mark>panic</mark> &amp; "
+        );
+    }
+}
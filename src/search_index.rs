@@ -0,0 +1,897 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use reqwest::{Method, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use thiserror::Error;
+use tokio::{
+    sync::broadcast::{error::RecvError, Receiver},
+    task::JoinHandle,
+};
+
+use crate::{
+    models::{DBError, QuestionSearchResult, QuestionUuid, QuestionWithAnswers},
+    outbox::ContentEvent,
+    persistance::questions_dao::QuestionsDao,
+    sanitize::escape_html,
+    AppState,
+};
+
+// Large deployments search an Elasticsearch or OpenSearch cluster rather than the database. The index is kept up to
+// date from the content events dispatched by the outbox, and only holds the text searched: matches are read back from
+// the database, so results carry the current status and answer count of their question.
+//
+// Searches and writes go through an alias, so that the index can be rebuilt from the database without search ever
+// being empty or partial: a rebuild fills a new index, which content events are applied to as well meanwhile, then
+// points the alias at it in one step and deletes the index it replaced.
+
+/// Wait between two attempts at creating the index, while the cluster is unreachable
+const PREPARE_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Boost of a match in the title, relative to a match in the description
+const TITLE_BOOST: f32 = 2.5;
+
+/// Boost of a match in an answer, relative to a match in the description
+const ANSWER_BOOST: f32 = 0.5;
+
+/// Type of the error answered when creating an index that exists
+const ALREADY_EXISTS: &str = "resource_already_exists_exception";
+
+/// Characters of the description highlighted in a snippet
+const SNIPPET_FRAGMENT_CHARS: u32 = 150;
+
+/// Errors for requests to a search index
+#[derive(Error, Debug)]
+pub enum SearchError {
+
+    /// The request could not be sent, or its response read
+    #[error("Search request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    /// The cluster answered with an error
+    #[error("Search cluster answered with {0}: {1}")]
+    Status(StatusCode, String),
+
+    /// The matches could not be read from the database
+    #[error("Failed to read the matching questions: {0}")]
+    Database(DBError),
+}
+
+/// A question matching a search, as found in the index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexMatch {
+    pub question_uuid: QuestionUuid,
+    /// Relevance of the question to the search, higher is better
+    pub score: f32,
+    /// HTML fragments of the title and description, escaped, with the matching words wrapped in `<mark>` tags
+    pub highlights: Vec<String>,
+}
+
+/// A trait representing a full-text index of questions and their answers, searched instead of the database.
+#[async_trait]
+pub trait SearchIndex {
+
+    /// Asynchronously creates the index if it does not exist yet.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `SearchError` is returned.
+    async fn prepare(&self) -> Result<(), SearchError>;
+
+    /// Asynchronously applies a write to the index, and to the index being rebuilt if there is one.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The question or answer created or deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `SearchError` is returned.
+    async fn index_event(&self, event: &ContentEvent) -> Result<(), SearchError>;
+
+    /// Asynchronously creates the new index of a rebuild, unless it already exists, and starts applying writes to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `rebuild` - The name of the rebuild, which its new index is named after.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `SearchError` is returned.
+    async fn begin_rebuild(&self, rebuild: &str) -> Result<(), SearchError>;
+
+    /// Asynchronously writes a question along with its answers to the new index of a rebuild.
+    ///
+    /// # Arguments
+    ///
+    /// * `rebuild` - The name of the rebuild.
+    /// * `question` - The question and all its answers.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `SearchError` is returned.
+    async fn index_question(&self, rebuild: &str, question: &QuestionWithAnswers) -> Result<(), SearchError>;
+
+    /// Asynchronously replaces the index searched with the new index of a rebuild, at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `rebuild` - The name of the rebuild, every question having been written to its index.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `SearchError` is returned.
+    async fn complete_rebuild(&self, rebuild: &str) -> Result<(), SearchError>;
+
+    /// Asynchronously searches the index.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The words to search for, every one of which has to match.
+    /// * `limit` - The maximum number of matches to return.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the matching questions, best match first, on success, or a `SearchError` on failure.
+    async fn search(&self, query: &str, limit: i64) -> Result<Vec<IndexMatch>, SearchError>;
+}
+
+/// Searches questions in an index, reading the questions found from the database.
+///
+/// # Arguments
+///
+/// * `search_index` - The index searched.
+/// * `questions_dao` - The DAO the questions found are read with.
+/// * `query` - The words to search for.
+/// * `limit` - The maximum number of results to return.
+///
+/// # Returns
+///
+/// A `Result` containing the matching questions, best match first, on success, or a `SearchError` on failure.
+/// Questions deleted since they were indexed are skipped.
+pub async fn search_questions(
+    search_index: &(dyn SearchIndex + Sync + Send),
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+    query: &str,
+    limit: i64,
+) -> Result<Vec<QuestionSearchResult>, SearchError> {
+    let matches = search_index.search(query, limit).await?;
+    if matches.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let question_uuids = matches.iter().map(|m| m.question_uuid).collect();
+    let mut questions: HashMap<QuestionUuid, _> = questions_dao
+        .get_questions_by_uuids(question_uuids)
+        .await
+        .map_err(SearchError::Database)?
+        .into_iter()
+        .map(|question| (question.question_uuid, question))
+        .collect();
+
+    let results = matches
+        .into_iter()
+        .filter_map(|m| {
+            let question = questions.remove(&m.question_uuid)?;
+
+            // Questions matching on their answers only have nothing highlighted
+            let snippet = match m.highlights.is_empty() {
+                true => escape_html(&question.title),
+                false => m.highlights.join(" "),
+            };
+
+            Some(QuestionSearchResult { question, rank: m.score, snippet })
+        })
+        .collect();
+
+    Ok(results)
+}
+
+/// Starts applying the content events published from now on to a search index.
+///
+/// # Arguments
+///
+/// * `state` - The application state, whose content events are indexed.
+/// * `search_index` - The index to keep up to date.
+///
+/// # Returns
+///
+/// A `JoinHandle` of the task indexing events, which runs until the feed of content events is dropped.
+pub fn start(state: &AppState, search_index: Arc<dyn SearchIndex + Send + Sync>) -> JoinHandle<()> {
+    tokio::spawn(index_events(state.content_events.subscribe(), search_index))
+}
+
+async fn index_events(mut events: Receiver<ContentEvent>, search_index: Arc<dyn SearchIndex + Send + Sync>) {
+    // Events published meanwhile wait in the receiver
+    while let Err(err) = search_index.prepare().await {
+        error!("Failed to create the search index, retrying in {:?}: {}", PREPARE_RETRY_DELAY, err);
+        tokio::time::sleep(PREPARE_RETRY_DELAY).await;
+    }
+
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(missed)) => {
+                warn!("The search index missed {} events, as indexing could not keep up.", missed);
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+
+        // One at a time, so an answer is not indexed before its question
+        if let Err(err) = search_index.index_event(&event).await {
+            error!("Failed to index the {} event: {}", event.name(), err);
+        }
+    }
+}
+
+/// Hits of a search, the only part of the response read
+#[derive(Deserialize)]
+struct SearchResponse {
+    hits: Hits,
+}
+
+#[derive(Deserialize)]
+struct Hits {
+    hits: Vec<Hit>,
+}
+
+#[derive(Deserialize)]
+struct Hit {
+    #[serde(rename = "_id")]
+    id: String,
+    #[serde(rename = "_score")]
+    score: Option<f32>,
+    #[serde(default)]
+    highlight: HashMap<String, Vec<String>>,
+}
+
+impl SearchResponse {
+
+    /// The matches of the response, skipping documents that are not questions.
+    fn matches(self) -> Vec<IndexMatch> {
+        self.hits
+            .hits
+            .into_iter()
+            .filter_map(|mut hit| {
+                let question_uuid = hit.id.parse().ok()?;
+                let highlights = ["title", "description"]
+                    .into_iter()
+                    .flat_map(|field| hit.highlight.remove(field).unwrap_or_default())
+                    .collect();
+
+                Some(IndexMatch { question_uuid, score: hit.score.unwrap_or_default(), highlights })
+            })
+            .collect()
+    }
+}
+
+/// Search index kept in an Elasticsearch or OpenSearch cluster, through the REST API both share.
+///
+/// Each question is a document, identified by its UUID, holding its title, its description and the content of its
+/// answers. The index is searched through an alias, which a rebuild points at its new index once it is complete.
+pub struct ElasticsearchIndex {
+    client: reqwest::Client,
+    url: String,
+    /// The alias of the index searched
+    alias: String,
+}
+
+impl ElasticsearchIndex {
+
+    /// Creates a client for an index of a cluster.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - The base URL of the cluster, e.g. `http://localhost:9200`.
+    /// * `index` - The name of the alias of the index searched, or of an index created before aliases were used,
+    ///   which the first rebuild replaces with an alias.
+    pub fn new(url: &str, index: &str) -> Self {
+        ElasticsearchIndex {
+            // The URL is set by the operator, so it does not need an `OutboundClient`
+            client: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("Failed to create search HTTP client!"),
+            url: url.trim_end_matches('/').to_owned(),
+            alias: index.to_owned(),
+        }
+    }
+
+    /// The alias of the index being rebuilt, which writes are applied to as well while there is one
+    fn rebuild_alias(&self) -> String {
+        format!("{}-rebuild", self.alias)
+    }
+
+    /// The new index of a rebuild
+    fn rebuild_index(&self, rebuild: &str) -> String {
+        format!("{}-{}", self.alias, rebuild)
+    }
+
+    /// Sends a request to the cluster, failing unless it is answered with a success or one of the `accepted` statuses.
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&Value>,
+        accepted: &[StatusCode],
+    ) -> Result<reqwest::Response, SearchError> {
+        let mut request = self.client.request(method, format!("{}{}", self.url, path));
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+
+        let response = request.send().await?;
+
+        let status = response.status();
+        if !status.is_success() && !accepted.contains(&status) {
+            return Err(SearchError::Status(status, response.text().await.unwrap_or_default()));
+        }
+
+        Ok(response)
+    }
+
+    /// Creates an index with its mappings and an alias, unless it already exists.
+    async fn create_index(&self, index: &str, alias: &str) -> Result<(), SearchError> {
+        let mut body = index_body();
+        body["aliases"] = json!({ alias: {} });
+
+        match self.send(Method::PUT, &format!("/{}", index), Some(&body), &[]).await {
+            Err(SearchError::Status(StatusCode::BAD_REQUEST, body)) if body.contains(ALREADY_EXISTS) => Ok(()),
+            result => result.map(|_| ()),
+        }
+    }
+
+    /// Applies a write to the documents of an index.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The alias of the index.
+    /// * `rebuilding` - Whether the target is the alias of the index being rebuilt, which may not exist, and must not
+    ///   be created by writing to it.
+    /// * `event` - The question or answer created or deleted.
+    async fn apply_event(&self, target: &str, rebuilding: bool, event: &ContentEvent) -> Result<(), SearchError> {
+        // Writing to an alias that does not exist would create an index of that name, unless an alias is required
+        let require_alias = |path: String, separator: char| match rebuilding {
+            true => format!("{}{}require_alias=true", path, separator),
+            false => path,
+        };
+        let missing: &[StatusCode] = if rebuilding { &[StatusCode::NOT_FOUND] } else { &[] };
+
+        match event {
+            ContentEvent::QuestionCreated(question) => {
+                let document = json!({ "title": question.title, "description": question.description, "answers": [] });
+                let path = require_alias(format!("/{}/_doc/{}", target, question.question_uuid), '?');
+                self.send(Method::PUT, &path, Some(&document), missing).await?;
+            }
+            ContentEvent::QuestionDeleted(question) => {
+                // Its answers go with it
+                let path = format!("/{}/_doc/{}", target, question.question_uuid);
+                self.send(Method::DELETE, &path, None, &[StatusCode::NOT_FOUND]).await?;
+            }
+            ContentEvent::AnswerCreated(answer) => {
+                let update = json!({
+                    "script": {
+                        "source": "ctx._source.answers.add(params.answer)",
+                        "params": { "answer": { "answer_uuid": answer.answer_uuid, "content": answer.content } },
+                    }
+                });
+                // Questions asked before the index was set up, or not copied to the rebuilt index yet, are not in it
+                let path = format!("/{}/_update/{}?retry_on_conflict=3", target, answer.question_uuid);
+                self.send(Method::POST, &require_alias(path, '&'), Some(&update), &[StatusCode::NOT_FOUND]).await?;
+            }
+            ContentEvent::AnswerDeleted(answer) => {
+                // The event does not name the question, which is found by the answer instead
+                let update = json!({
+                    "query": { "term": { "answers.answer_uuid": answer.answer_uuid } },
+                    "script": {
+                        "source": "ctx._source.answers.removeIf(a -> a.answer_uuid == params.answer_uuid)",
+                        "params": { "answer_uuid": answer.answer_uuid },
+                    }
+                });
+                let path = format!("/{}/_update_by_query?conflicts=proceed", target);
+                self.send(Method::POST, &path, Some(&update), missing).await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Mappings of the index, analyzing text in English like the Postgres search
+fn index_body() -> Value {
+    json!({
+        "mappings": {
+            "properties": {
+                "title": { "type": "text", "analyzer": "english" },
+                "description": { "type": "text", "analyzer": "english" },
+                "answers": {
+                    "properties": {
+                        "answer_uuid": { "type": "keyword" },
+                        "content": { "type": "text", "analyzer": "english" },
+                    }
+                },
+            }
+        }
+    })
+}
+
+/// Query matching questions containing every word, with the matches in the title and description highlighted
+fn search_body(query: &str, limit: i64) -> Value {
+    json!({
+        "size": limit,
+        "_source": false,
+        "query": {
+            "multi_match": {
+                "query": query,
+                "type": "cross_fields",
+                "operator": "and",
+                "fields": [
+                    format!("title^{}", TITLE_BOOST),
+                    "description",
+                    format!("answers.content^{}", ANSWER_BOOST),
+                ],
+            }
+        },
+        "highlight": {
+            "encoder": "html",
+            "pre_tags": ["<mark>"],
+            "post_tags": ["</mark>"],
+            "fields": {
+                "title": { "number_of_fragments": 0 },
+                "description": { "fragment_size": SNIPPET_FRAGMENT_CHARS, "number_of_fragments": 1 },
+            }
+        }
+    })
+}
+
+#[async_trait]
+impl SearchIndex for ElasticsearchIndex {
+
+    /// Asynchronously creates an index with its mappings, and the alias searched pointing at it, unless the alias or
+    /// an index of that name already exists.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `SearchError` is returned.
+    async fn prepare(&self) -> Result<(), SearchError> {
+        let response = self.send(Method::HEAD, &format!("/{}", self.alias), None, &[StatusCode::NOT_FOUND]).await?;
+        if response.status() != StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+
+        // Named the same by every instance, so instances starting together do not each create one
+        self.create_index(&self.rebuild_index("initial"), &self.alias).await
+    }
+
+    /// Asynchronously applies a write to the documents of the index, and of the index being rebuilt if there is one.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The question or answer created or deleted.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `SearchError` is returned.
+    async fn index_event(&self, event: &ContentEvent) -> Result<(), SearchError> {
+        self.apply_event(&self.alias, false, event).await?;
+
+        // Otherwise a rebuild would miss the writes made after it went past them
+        self.apply_event(&self.rebuild_alias(), true, event).await
+    }
+
+    /// Asynchronously creates the new index of a rebuild, unless it already exists, along with the alias writes are
+    /// applied to while rebuilding.
+    ///
+    /// # Arguments
+    ///
+    /// * `rebuild` - The name of the rebuild, which its new index is named after.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `SearchError` is returned.
+    async fn begin_rebuild(&self, rebuild: &str) -> Result<(), SearchError> {
+        self.create_index(&self.rebuild_index(rebuild), &self.rebuild_alias()).await
+    }
+
+    /// Asynchronously writes the document of a question, with the content of its answers, to the new index of a
+    /// rebuild.
+    ///
+    /// # Arguments
+    ///
+    /// * `rebuild` - The name of the rebuild.
+    /// * `question` - The question and all its answers.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `SearchError` is returned.
+    async fn index_question(&self, rebuild: &str, question: &QuestionWithAnswers) -> Result<(), SearchError> {
+        let QuestionWithAnswers { question, answers } = question;
+        let answers: Vec<Value> = answers
+            .iter()
+            .map(|answer| json!({ "answer_uuid": answer.answer_uuid, "content": answer.content }))
+            .collect();
+
+        let document = json!({ "title": question.title, "description": question.description, "answers": answers });
+        let path = format!("/{}/_doc/{}", self.rebuild_index(rebuild), question.question_uuid);
+        self.send(Method::PUT, &path, Some(&document), &[]).await?;
+
+        Ok(())
+    }
+
+    /// Asynchronously points the alias searched at the new index of a rebuild, and deletes the indices it pointed at.
+    ///
+    /// # Arguments
+    ///
+    /// * `rebuild` - The name of the rebuild, every question having been written to its index.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` indicating success or failure. An empty `Ok(())` is returned on success, otherwise, a `SearchError` is returned.
+    async fn complete_rebuild(&self, rebuild: &str) -> Result<(), SearchError> {
+        let index = self.rebuild_index(rebuild);
+
+        let path = format!("/_alias/{}", self.alias);
+        let response = self.send(Method::GET, &path, None, &[StatusCode::NOT_FOUND]).await?;
+        let mut actions = vec![
+            json!({ "add": { "index": index, "alias": self.alias } }),
+            json!({ "remove": { "index": index, "alias": self.rebuild_alias() } }),
+        ];
+
+        let replaced: Vec<String> = if response.status() == StatusCode::NOT_FOUND {
+            // Not an alias yet, but an index, deleted as the alias takes its name
+            actions.push(json!({ "remove_index": { "index": self.alias } }));
+            Vec::new()
+        } else {
+            let indices: HashMap<String, Value> = response.json().await?;
+            if indices.keys().eq([&index]) {
+                // Swapped already, by an attempt that failed to record it
+                return Ok(());
+            }
+
+            indices.into_keys().filter(|replaced| *replaced != index).collect()
+        };
+
+        actions.extend(replaced.iter().map(|replaced| json!({ "remove": { "index": replaced, "alias": self.alias } })));
+        self.send(Method::POST, "/_aliases", Some(&json!({ "actions": actions })), &[]).await?;
+
+        // No longer searched, or written to
+        for replaced in replaced {
+            let deleted = self.send(Method::DELETE, &format!("/{}", replaced), None, &[StatusCode::NOT_FOUND]).await;
+            if let Err(err) = deleted {
+                warn!("Failed to delete the {} index, replaced by a rebuild: {}", replaced, err);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Asynchronously searches the questions of the index.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The words to search for, every one of which has to match.
+    /// * `limit` - The maximum number of matches to return.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` containing the matching questions, best match first, on success, or a `SearchError` on failure.
+    async fn search(&self, query: &str, limit: i64) -> Result<Vec<IndexMatch>, SearchError> {
+        let response: SearchResponse = self
+            .send(Method::POST, &format!("/{}/_search", self.alias), Some(&search_body(query, limit)), &[])
+            .await?
+            .json()
+            .await?;
+
+        Ok(response.matches())
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Mutex;
+
+    use axum::{extract::State, routing::any, Json, Router};
+
+    use crate::{
+        config::Config,
+        models::{AnswerDetail, AnswerUuid},
+        test_support::QuestionBuilder,
+    };
+
+    /// Index returning fixed matches
+    struct FixedIndex(Vec<IndexMatch>);
+
+    #[async_trait]
+    impl SearchIndex for FixedIndex {
+        async fn prepare(&self) -> Result<(), SearchError> {
+            Ok(())
+        }
+
+        async fn index_event(&self, _: &ContentEvent) -> Result<(), SearchError> {
+            Ok(())
+        }
+
+        async fn begin_rebuild(&self, _: &str) -> Result<(), SearchError> {
+            Ok(())
+        }
+
+        async fn index_question(&self, _: &str, _: &QuestionWithAnswers) -> Result<(), SearchError> {
+            Ok(())
+        }
+
+        async fn complete_rebuild(&self, _: &str) -> Result<(), SearchError> {
+            Ok(())
+        }
+
+        async fn search(&self, _: &str, _: i64) -> Result<Vec<IndexMatch>, SearchError> {
+            Ok(self.0.clone())
+        }
+    }
+
+    /// Requests received by a fake cluster, as their method and path along with their body
+    type Requests = Arc<Mutex<Vec<(String, Value)>>>;
+
+    /// Responses of a fake cluster to the requests whose method and path start with a prefix
+    type Routes = Arc<Vec<(&'static str, StatusCode, Value)>>;
+
+    /// Serves a fake cluster, recording the requests it gets and answering them with `response`.
+    async fn cluster(response: Value) -> (String, Requests) {
+        routed_cluster(vec![("", StatusCode::OK, response)]).await
+    }
+
+    /// Serves a fake cluster, recording the requests it gets and answering them with the first of `routes` matching
+    /// them, or `{}`.
+    async fn routed_cluster(routes: Vec<(&'static str, StatusCode, Value)>) -> (String, Requests) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let app = Router::new().fallback(any(
+            |State((requests, routes)): State<(Requests, Routes)>,
+             request: axum::extract::Request| async move {
+                let target = format!("{} {}", request.method(), request.uri());
+                let body = axum::body::to_bytes(request.into_body(), usize::MAX).await.unwrap();
+                requests.lock().unwrap().push((target.clone(), serde_json::from_slice(&body).unwrap_or(Value::Null)));
+
+                let route = routes.iter().find(|(prefix, _, _)| target.starts_with(prefix));
+                let (status, response) = route.map_or((StatusCode::OK, json!({})), |(_, s, r)| (*s, r.clone()));
+                (status, Json(response))
+            },
+        ))
+        .with_state((requests.clone(), Arc::new(routes)));
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (url, requests)
+    }
+
+    #[tokio::test]
+    async fn should_read_matches_from_database_in_index_order() {
+        let state = AppState::in_memory(&Config::default());
+        let mut questions = Vec::new();
+        for title in ["First <b>", "Second"] {
+            let question = QuestionBuilder::new().title(title).build();
+            questions.push(state.questions_dao.create_question(question).await.unwrap());
+        }
+
+        let index = FixedIndex(vec![
+            IndexMatch {
+                question_uuid: questions[1].question_uuid,
+                score: 2.0,
+                highlights: vec!["<mark>Second</mark>".to_owned()],
+            },
+            // Deleted since it was indexed
+            IndexMatch { question_uuid: QuestionUuid::new_v4(), score: 1.5, highlights: Vec::new() },
+            IndexMatch { question_uuid: questions[0].question_uuid, score: 1.0, highlights: Vec::new() },
+        ]);
+
+        let results = search_questions(&index, state.questions_dao.as_ref(), "second", 10).await.unwrap();
+
+        let found: Vec<_> = results.iter().map(|r| (r.question.question_uuid, r.rank, r.snippet.as_str())).collect();
+        assert_eq!(
+            found,
+            [
+                (questions[1].question_uuid, 2.0, "<mark>Second</mark>"),
+                (questions[0].question_uuid, 1.0, "First &lt;b&gt;"),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_read_matches_from_search_response() {
+        let question_uuid = QuestionUuid::new_v4();
+        let response: SearchResponse = serde_json::from_value(json!({
+            "hits": { "hits": [
+                {
+                    "_id": question_uuid.to_string(),
+                    "_score": 3.5,
+                    "highlight": { "description": ["in <mark>tokio</mark>"], "title": ["<mark>Tokio</mark> panics"] },
+                },
+                { "_id": "not-a-question", "_score": 1.0 },
+            ] }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            response.matches(),
+            [IndexMatch {
+                question_uuid,
+                score: 3.5,
+                highlights: vec!["<mark>Tokio</mark> panics".to_owned(), "in <mark>tokio</mark>".to_owned()],
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_search_with_every_word_required() {
+        let (url, requests) = cluster(json!({ "hits": { "hits": [] } })).await;
+        let index = ElasticsearchIndex::new(&format!("{}/", url), "questions");
+
+        assert_eq!(index.search("tokio runtime", 5).await.unwrap(), []);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].0, "POST /questions/_search");
+        assert_eq!(requests[0].1["size"], 5);
+        assert_eq!(requests[0].1["query"]["multi_match"]["operator"], "and");
+        assert_eq!(requests[0].1["query"]["multi_match"]["query"], "tokio runtime");
+    }
+
+    #[tokio::test]
+    async fn should_index_writes_as_question_documents() {
+        let (url, requests) = cluster(json!({})).await;
+        let index = ElasticsearchIndex::new(&url, "questions");
+        let question = QuestionBuilder::new().title("Title").description("Description").build_detail();
+        let answer = AnswerDetail {
+            answer_uuid: AnswerUuid::new_v4(),
+            question_uuid: question.question_uuid,
+            content: "Content".to_owned(),
+            created_at: question.created_at,
+            word_count: 0,
+            reading_time_seconds: 0,
+        };
+
+        index.index_event(&ContentEvent::QuestionCreated(question.clone())).await.unwrap();
+        index.index_event(&ContentEvent::AnswerCreated(answer.clone())).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].0, format!("PUT /questions/_doc/{}", question.question_uuid));
+        assert_eq!(requests[0].1, json!({ "title": "Title", "description": "Description", "answers": [] }));
+        assert_eq!(requests[1].0, format!("PUT /questions-rebuild/_doc/{}?require_alias=true", question.question_uuid));
+        assert_eq!(requests[1].1, requests[0].1);
+        assert_eq!(requests[2].0, format!("POST /questions/_update/{}?retry_on_conflict=3", question.question_uuid));
+        assert_eq!(requests[2].1["script"]["params"]["answer"]["content"], "Content");
+        assert_eq!(
+            requests[3].0,
+            format!("POST /questions-rebuild/_update/{}?retry_on_conflict=3&require_alias=true", question.question_uuid)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_ignore_missing_index_being_rebuilt() {
+        let (url, requests) = routed_cluster(vec![("PUT /questions-rebuild/", StatusCode::NOT_FOUND, json!({}))]).await;
+        let index = ElasticsearchIndex::new(&url, "questions");
+        let question = QuestionBuilder::new().build_detail();
+
+        index.index_event(&ContentEvent::QuestionCreated(question)).await.unwrap();
+
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn should_create_aliased_index_when_alias_is_missing() {
+        let (url, requests) = routed_cluster(vec![("HEAD /questions", StatusCode::NOT_FOUND, json!({}))]).await;
+        ElasticsearchIndex::new(&url, "questions").prepare().await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[1].0, "PUT /questions-initial");
+        assert_eq!(requests[1].1["aliases"], json!({ "questions": {} }));
+        assert_eq!(requests[1].1["mappings"], index_body()["mappings"]);
+    }
+
+    #[tokio::test]
+    async fn should_keep_existing_alias() {
+        let (url, requests) = cluster(json!({})).await;
+        ElasticsearchIndex::new(&url, "questions").prepare().await.unwrap();
+
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_swap_alias_to_rebuilt_index_at_once() {
+        let aliases = json!({ "questions-initial": { "aliases": { "questions": {} } } });
+        let (url, requests) = routed_cluster(vec![("GET /_alias/questions", StatusCode::OK, aliases)]).await;
+        let index = ElasticsearchIndex::new(&url, "questions");
+
+        index.begin_rebuild("job").await.unwrap();
+        index.complete_rebuild("job").await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].0, "PUT /questions-job");
+        assert_eq!(requests[0].1["aliases"], json!({ "questions-rebuild": {} }));
+        assert_eq!(requests[2].0, "POST /_aliases");
+        assert_eq!(
+            requests[2].1["actions"],
+            json!([
+                { "add": { "index": "questions-job", "alias": "questions" } },
+                { "remove": { "index": "questions-job", "alias": "questions-rebuild" } },
+                { "remove": { "index": "questions-initial", "alias": "questions" } },
+            ])
+        );
+        assert_eq!(requests[3].0, "DELETE /questions-initial");
+    }
+
+    #[tokio::test]
+    async fn should_replace_index_created_before_aliases() {
+        let (url, requests) = routed_cluster(vec![("GET /_alias/questions", StatusCode::NOT_FOUND, json!({}))]).await;
+
+        ElasticsearchIndex::new(&url, "questions").complete_rebuild("job").await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].1["actions"][2], json!({ "remove_index": { "index": "questions" } }));
+    }
+
+    #[tokio::test]
+    async fn should_not_swap_twice() {
+        let aliases = json!({ "questions-job": { "aliases": { "questions": {} } } });
+        let (url, requests) = routed_cluster(vec![("GET /_alias/questions", StatusCode::OK, aliases)]).await;
+
+        ElasticsearchIndex::new(&url, "questions").complete_rebuild("job").await.unwrap();
+
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_index_question_with_its_answers() {
+        let (url, requests) = cluster(json!({})).await;
+        let index = ElasticsearchIndex::new(&url, "questions");
+        let question = QuestionBuilder::new().title("Title").description("Description").build_detail();
+        let answer = AnswerDetail {
+            answer_uuid: AnswerUuid::new_v4(),
+            question_uuid: question.question_uuid,
+            content: "Content".to_owned(),
+            created_at: question.created_at,
+            word_count: 0,
+            reading_time_seconds: 0,
+        };
+
+        let question = QuestionWithAnswers { question, answers: vec![answer.clone()] };
+        index.index_question("job", &question).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].0, format!("PUT /questions-job/_doc/{}", question.question.question_uuid));
+        assert_eq!(
+            requests[0].1,
+            json!({
+                "title": "Title",
+                "description": "Description",
+                "answers": [{ "answer_uuid": answer.answer_uuid, "content": "Content" }],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn should_report_cluster_errors() {
+        let app = Router::new().fallback(|| async { (StatusCode::SERVICE_UNAVAILABLE, "cluster_block_exception") });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let result = ElasticsearchIndex::new(&url, "questions").search("tokio", 5).await;
+
+        assert!(matches!(
+            result,
+            Err(SearchError::Status(StatusCode::SERVICE_UNAVAILABLE, body)) if body == "cluster_block_exception"
+        ));
+    }
+}
@@ -0,0 +1,303 @@
+use std::{collections::HashMap, fmt, path::Path};
+
+use serde::Deserialize;
+use thiserror::Error;
+use validator::Validate;
+
+use crate::{
+    handlers::extract::field_errors,
+    models::{Answer, DBError, Question, QuestionUuid},
+    normalize::{normalize_title, Normalize},
+    persistance::{answers_dao::AnswersDao, questions_dao::QuestionsDao},
+};
+
+/// Errors for seeding the database from a file
+#[derive(Error, Debug)]
+pub enum SeedError {
+
+    /// The seed file could not be read
+    #[error("Failed to read seed file: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The seed file is not valid JSON of the expected shape
+    #[error("Failed to parse seed file: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    /// A record could not be inserted
+    #[error("Failed to insert seed data: {0}")]
+    Database(#[from] DBError),
+}
+
+/// Represents the contents of a seed file
+#[derive(Deserialize, Debug)]
+pub struct SeedData {
+    pub questions: Vec<SeedQuestion>,
+}
+
+/// Represents a question to seed together with the contents of its answers
+#[derive(Deserialize, Debug)]
+pub struct SeedQuestion {
+    pub title: String,
+    pub description: String,
+    #[serde(default)]
+    pub answers: Vec<String>,
+}
+
+/// Represents how many records a seed run inserted
+#[derive(Debug, PartialEq)]
+pub struct SeedSummary {
+    pub questions: usize,
+    pub answers: usize,
+}
+
+/// Represents a record of a seed file that cannot be inserted
+#[derive(Debug, PartialEq)]
+pub struct SeedProblem {
+    /// Where the record is in the file, e.g. `questions[2].answers[0]`
+    pub location: String,
+    pub message: String,
+}
+
+impl fmt::Display for SeedProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// Reads and parses a seed file.
+///
+/// # Arguments
+///
+/// * `path` - The path of the JSON seed file.
+///
+/// # Returns
+///
+/// A `Result` containing the seed data on success, or a `SeedError` on failure.
+pub fn load(path: &Path) -> Result<SeedData, SeedError> {
+    let contents = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+/// Checks the seed data without writing anything, with the rules the API applies to questions and answers, and for
+/// questions with the same title or answers with the same content under a question.
+///
+/// # Arguments
+///
+/// * `data` - The questions and answers to check.
+///
+/// # Returns
+///
+/// A `Result` containing the number of records seeding would insert on success, or every problem found, in the order
+/// of the file.
+pub fn check(data: &SeedData) -> Result<SeedSummary, Vec<SeedProblem>> {
+    let mut problems = Vec::new();
+    let mut summary = SeedSummary { questions: 0, answers: 0 };
+    let mut titles = HashMap::new();
+
+    for (i, seeded) in data.questions.iter().enumerate() {
+        let location = format!("questions[{}]", i);
+        let question = Question {
+            question_uuid: None,
+            title: seeded.title.clone(),
+            description: seeded.description.clone(),
+        };
+        if let Err(errors) = question.validate() {
+            problems.extend(field_errors(errors).into_iter().map(|error| SeedProblem {
+                location: format!("{}.{}", location, error.field.unwrap_or_default()),
+                message: error.message,
+            }));
+        }
+
+        // Titles differing only in case or spacing ask the same question
+        match titles.insert(normalize_title(&seeded.title).to_lowercase(), i) {
+            Some(first) => problems.push(SeedProblem {
+                location: format!("{}.title", location),
+                message: format!("duplicates the title of questions[{}]", first),
+            }),
+            None => summary.questions += 1,
+        }
+
+        let mut contents = HashMap::new();
+        for (j, content) in seeded.answers.iter().enumerate() {
+            let location = format!("{}.answers[{}]", location, j);
+            // The question does not exist yet, any identifier does for validating
+            let answer = Answer { answer_uuid: None, question_uuid: QuestionUuid::new_v4(), content: content.clone() };
+            if let Err(errors) = answer.validate() {
+                problems.extend(field_errors(errors).into_iter().map(|error| SeedProblem {
+                    location: location.clone(),
+                    message: error.message,
+                }));
+            }
+
+            match contents.insert(answer.normalize().content, j) {
+                Some(first) => problems.push(SeedProblem {
+                    location,
+                    message: format!("duplicates the content of questions[{}].answers[{}]", i, first),
+                }),
+                None => summary.answers += 1,
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(summary)
+    } else {
+        Err(problems)
+    }
+}
+
+/// Asynchronously inserts the seed data through the DAOs.
+///
+/// # Arguments
+///
+/// * `data` - The questions and answers to insert.
+/// * `questions_dao` - A reference to an object implementing the `QuestionsDao` trait along with `Sync` and `Send` traits.
+/// * `answers_dao` - A reference to an object implementing the `AnswersDao` trait along with `Send` and `Sync` traits.
+///
+/// # Returns
+///
+/// A `Result` containing the number of inserted records on success, or a `DBError` on the first failure.
+pub async fn seed(
+    data: SeedData,
+    questions_dao: &(dyn QuestionsDao + Sync + Send),
+    answers_dao: &(dyn AnswersDao + Send + Sync),
+) -> Result<SeedSummary, DBError> {
+    let mut summary = SeedSummary { questions: 0, answers: 0 };
+
+    for question in data.questions {
+        let detail = questions_dao
+            .create_question(
+                Question {
+                    question_uuid: None,
+                    title: question.title,
+                    description: question.description,
+                }
+                .normalize(),
+            )
+            .await?;
+
+        summary.questions += 1;
+
+        for content in question.answers {
+            answers_dao
+                .create_answer(
+                    Answer {
+                        answer_uuid: None,
+                        question_uuid: detail.question_uuid,
+                        content,
+                    }
+                    .normalize(),
+                )
+                .await?;
+
+            summary.answers += 1;
+        }
+    }
+
+    Ok(summary)
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sqlx::PgPool;
+
+    use crate::{
+        models::{AnswerSort, QuestionFilter},
+        persistance::{answers_dao::AnswersDaoImpl, questions_dao::QuestionsDaoImpl},
+    };
+
+    #[test]
+    fn should_parse_seed_data_with_optional_answers() {
+        let data: SeedData = serde_json::from_str(
+            r#"{
+                "questions": [
+                    { "title": "first", "description": "with answers", "answers": ["a", "b"] },
+                    { "title": "second", "description": "without answers" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(data.questions.len(), 2);
+        assert_eq!(data.questions[0].answers, vec!["a", "b"]);
+        assert!(data.questions[1].answers.is_empty());
+    }
+
+    #[test]
+    fn check_should_count_the_records_of_valid_data() {
+        let data = SeedData {
+            questions: vec![
+                SeedQuestion { title: "first".to_owned(), description: String::new(), answers: vec!["a".to_owned()] },
+                SeedQuestion { title: "second".to_owned(), description: String::new(), answers: Vec::new() },
+            ],
+        };
+
+        assert_eq!(check(&data), Ok(SeedSummary { questions: 2, answers: 1 }));
+    }
+
+    #[test]
+    fn check_should_report_every_invalid_or_duplicate_record() {
+        let data = SeedData {
+            questions: vec![
+                SeedQuestion {
+                    title: "How do I  join tables?".to_owned(),
+                    description: "x".repeat(256),
+                    answers: vec!["With JOIN".to_owned(), " ".to_owned(), "With JOIN\n".to_owned()],
+                },
+                SeedQuestion { title: "how do i join tables?".to_owned(), description: String::new(), answers: Vec::new() },
+            ],
+        };
+
+        let problems: Vec<String> = check(&data).unwrap_err().iter().map(ToString::to_string).collect();
+
+        assert_eq!(
+            problems,
+            [
+                "questions[0].description: must be at most 255 characters",
+                "questions[0].answers[1]: must not be blank",
+                "questions[0].answers[2]: duplicates the content of questions[0].answers[0]",
+                "questions[1].title: duplicates the title of questions[0]",
+            ]
+        );
+    }
+
+    #[sqlx::test]
+    async fn seed_should_insert_questions_and_answers(pool: PgPool) -> Result<(), String> {
+        let questions_dao = QuestionsDaoImpl::new(pool.clone());
+        let answers_dao = AnswersDaoImpl::new(pool);
+
+        let data = SeedData {
+            questions: vec![SeedQuestion {
+                title: "test title".to_owned(),
+                description: "test description".to_owned(),
+                answers: vec!["first".to_owned(), "second".to_owned()],
+            }],
+        };
+
+        let summary = seed(data, &questions_dao, &answers_dao)
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if summary != (SeedSummary { questions: 1, answers: 2 }) {
+            return Err(format!("Unexpected summary: {:?}", summary));
+        }
+
+        let questions = questions_dao.get_questions(&QuestionFilter::default()).await.map_err(|e| format!("{:?}", e))?;
+        let answers = answers_dao
+            .get_answers(questions[0].question_uuid, AnswerSort::default())
+            .await
+            .map_err(|e| format!("{:?}", e))?;
+
+        if answers.len() != 2 {
+            return Err(format!("Expected 2 answers but got {}", answers.len()));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,245 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::models::{SloObjectiveReport, SloReport};
+
+/// Width of the buckets requests are counted in
+const BUCKET_WIDTH: Duration = Duration::from_secs(60);
+
+/// Represents the service level objectives requests are measured against
+#[derive(Debug, Clone, PartialEq)]
+pub struct SloTargets {
+    /// Fraction of requests that must not fail with a server error
+    pub availability: f64,
+    /// Fraction of requests that must complete within `latency_threshold`
+    pub latency: f64,
+    pub latency_threshold: Duration,
+    /// How far back the error budget is computed
+    pub window: Duration,
+}
+
+/// Request counts for one bucket of time
+#[derive(Debug, Default, Clone, Copy)]
+struct Bucket {
+    index: u64,
+    total: u64,
+    errors: u64,
+    slow: u64,
+}
+
+/// Tracks request outcomes over a rolling window to compute error budget consumption.
+pub struct SloTracker {
+    targets: SloTargets,
+    started_at: Instant,
+    buckets: Mutex<VecDeque<Bucket>>,
+}
+
+impl SloTracker {
+    pub fn new(targets: SloTargets) -> Self {
+        SloTracker {
+            targets,
+            started_at: Instant::now(),
+            buckets: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Records the outcome of a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - When the request completed.
+    /// * `latency` - How long the request took.
+    /// * `failed` - Whether the request failed with a server error.
+    pub fn record(&self, now: Instant, latency: Duration, failed: bool) {
+        let index = self.bucket_index(now);
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if buckets.back().map(|b| b.index) != Some(index) {
+            buckets.push_back(Bucket { index, ..Bucket::default() });
+        }
+
+        let bucket = buckets.back_mut().unwrap();
+        bucket.total += 1;
+        bucket.errors += failed as u64;
+        bucket.slow += (latency > self.targets.latency_threshold) as u64;
+
+        self.evict(&mut buckets, index);
+    }
+
+    /// Computes the error budget consumption over the rolling window.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The end of the window.
+    ///
+    /// # Returns
+    ///
+    /// A `SloReport` for the availability and latency objectives.
+    pub fn report(&self, now: Instant) -> SloReport {
+        let index = self.bucket_index(now);
+        let mut buckets = self.buckets.lock().unwrap();
+        self.evict(&mut buckets, index);
+
+        let (total, errors, slow) = buckets
+            .iter()
+            .fold((0, 0, 0), |(t, e, s), b| (t + b.total, e + b.errors, s + b.slow));
+
+        SloReport {
+            window_secs: self.targets.window.as_secs(),
+            latency_threshold_ms: self.targets.latency_threshold.as_millis() as u64,
+            availability: objective_report(self.targets.availability, total, errors),
+            latency: objective_report(self.targets.latency, total, slow),
+        }
+    }
+
+    fn bucket_index(&self, now: Instant) -> u64 {
+        now.saturating_duration_since(self.started_at).as_secs() / BUCKET_WIDTH.as_secs()
+    }
+
+    /// Drops buckets that fell out of the window ending in bucket `index`.
+    fn evict(&self, buckets: &mut VecDeque<Bucket>, index: u64) {
+        let window_buckets = (self.targets.window.as_secs() / BUCKET_WIDTH.as_secs()).max(1);
+
+        while buckets.front().is_some_and(|b| b.index + window_buckets <= index) {
+            buckets.pop_front();
+        }
+    }
+}
+
+/// Computes the error budget figures for one objective.
+///
+/// The budget is the number of bad requests the target allows for the observed traffic, so
+/// consumption is the ratio of bad requests to that allowance. It exceeds 1.0 once the budget
+/// is exhausted.
+fn objective_report(target: f64, total: u64, bad: u64) -> SloObjectiveReport {
+    let good = total - bad;
+    let allowed_bad = (1.0 - target) * total as f64;
+
+    let budget_consumed = if bad == 0 {
+        0.0
+    } else if allowed_bad > 0.0 {
+        bad as f64 / allowed_bad
+    } else {
+        f64::INFINITY
+    };
+
+    SloObjectiveReport {
+        target,
+        total_requests: total,
+        good_requests: good,
+        sli: if total == 0 { 1.0 } else { good as f64 / total as f64 },
+        error_budget_consumed: budget_consumed,
+        error_budget_remaining: (1.0 - budget_consumed).max(0.0),
+    }
+}
+
+/// Middleware recording the latency and outcome of every request it wraps.
+///
+/// # Arguments
+///
+/// * `State(tracker)` - The tracker to record into.
+/// * `request` - The incoming request.
+/// * `next` - The rest of the middleware stack.
+///
+/// # Returns
+///
+/// The response produced by the inner service, unchanged.
+pub async fn track_requests(State(tracker): State<Arc<SloTracker>>, request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let response = next.run(request).await;
+
+    tracker.record(Instant::now(), started.elapsed(), response.status().is_server_error());
+
+    response
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker() -> SloTracker {
+        SloTracker::new(SloTargets {
+            availability: 0.9,
+            latency: 0.5,
+            latency_threshold: Duration::from_millis(100),
+            window: Duration::from_secs(600),
+        })
+    }
+
+    const FAST: Duration = Duration::from_millis(10);
+    const SLOW: Duration = Duration::from_millis(500);
+
+    #[test]
+    fn should_report_full_budget_without_traffic() {
+        let tracker = tracker();
+
+        let report = tracker.report(Instant::now());
+
+        assert_eq!(report.availability.total_requests, 0);
+        assert_eq!(report.availability.sli, 1.0);
+        assert_eq!(report.availability.error_budget_remaining, 1.0);
+    }
+
+    #[test]
+    fn should_compute_budget_consumption() {
+        let tracker = tracker();
+        let now = tracker.started_at;
+
+        // 20 requests at a 90% target allow 2 failures, 1 failure consumes half the budget
+        for i in 0..20 {
+            tracker.record(now, if i < 5 { SLOW } else { FAST }, i == 0);
+        }
+
+        let report = tracker.report(now);
+
+        assert_eq!(report.availability.total_requests, 20);
+        assert_eq!(report.availability.good_requests, 19);
+        assert!((report.availability.error_budget_consumed - 0.5).abs() < 1e-9);
+        assert!((report.availability.error_budget_remaining - 0.5).abs() < 1e-9);
+
+        // 20 requests at a 50% target allow 10 slow ones, 5 consume half the budget
+        assert_eq!(report.latency.good_requests, 15);
+        assert!((report.latency.error_budget_consumed - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn should_report_exhausted_budget() {
+        let tracker = tracker();
+        let now = tracker.started_at;
+
+        for _ in 0..10 {
+            tracker.record(now, FAST, true);
+        }
+
+        let report = tracker.report(now);
+
+        assert!(report.availability.error_budget_consumed > 1.0);
+        assert_eq!(report.availability.error_budget_remaining, 0.0);
+    }
+
+    #[test]
+    fn should_forget_requests_outside_the_window() {
+        let tracker = tracker();
+        let now = tracker.started_at;
+
+        tracker.record(now, FAST, true);
+        tracker.record(now + Duration::from_secs(300), FAST, false);
+
+        assert_eq!(tracker.report(now + Duration::from_secs(300)).availability.total_requests, 2);
+        assert_eq!(tracker.report(now + Duration::from_secs(660)).availability.total_requests, 1);
+        assert_eq!(tracker.report(now + Duration::from_secs(960)).availability.total_requests, 0);
+    }
+}
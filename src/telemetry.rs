@@ -0,0 +1,88 @@
+use axum::{extract::Request, middleware::Next, response::Response};
+use opentelemetry::{global, trace::TracerProvider as _};
+use opentelemetry_http::HeaderExtractor;
+use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider, Resource};
+use tracing::Instrument;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::layer::SubscriberExt;
+
+const SERVICE_NAME: &str = "tech-qna-api";
+
+/// Sets up OTLP trace export if `OTEL_EXPORTER_OTLP_ENDPOINT` is set.
+///
+/// The exporter itself reads the endpoint (and the other standard `OTEL_EXPORTER_OTLP_*`
+/// variables) from the environment.
+///
+/// # Returns
+///
+/// The tracer provider, which must be shut down before exit to flush pending spans,
+/// or `None` if tracing export is not configured.
+pub fn init() -> Option<SdkTracerProvider> {
+    if std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").is_err() {
+        info!("OTEL_EXPORTER_OTLP_ENDPOINT not set, trace export disabled.");
+        return None;
+    }
+
+    let exporter = match opentelemetry_otlp::SpanExporter::builder().with_http().build() {
+        Ok(exporter) => exporter,
+        Err(err) => {
+            error!("Failed to create OTLP span exporter: {:?}", err);
+            return None;
+        }
+    };
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+        .build();
+
+    // Incoming requests carry their parent span in the W3C `traceparent` header
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let subscriber = tracing_subscriber::Registry::default()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer(SERVICE_NAME)));
+
+    if let Err(err) = tracing::subscriber::set_global_default(subscriber) {
+        error!("Failed to install tracing subscriber: {:?}", err);
+        return None;
+    }
+
+    Some(provider)
+}
+
+/// Flushes pending spans and stops the exporter.
+///
+/// # Arguments
+///
+/// * `provider` - The tracer provider returned by `init`.
+pub fn shutdown(provider: SdkTracerProvider) {
+    if let Err(err) = provider.shutdown() {
+        error!("Failed to shut down tracer provider: {:?}", err);
+    }
+}
+
+/// Middleware wrapping each request in a span whose parent is taken from the incoming
+/// `traceparent` header, so DAO spans join the caller's distributed trace.
+///
+/// # Arguments
+///
+/// * `request` - The incoming request.
+/// * `next` - The rest of the middleware stack.
+///
+/// # Returns
+///
+/// The response produced by the inner service.
+pub async fn propagate_trace_context(request: Request, next: Next) -> Response {
+    let parent = global::get_text_map_propagator(|propagator| {
+        propagator.extract(&HeaderExtractor(request.headers()))
+    });
+
+    let span = tracing::info_span!(
+        "request",
+        http.method = %request.method(),
+        http.route = %request.uri().path(),
+    );
+    let _ = span.set_parent(parent);
+
+    next.run(request).instrument(span).await
+}
@@ -0,0 +1,204 @@
+use chrono::Utc;
+use fake::{faker::lorem::en::Sentence, Fake};
+
+use crate::{
+    models::{Answer, AnswerDetail, AnswerUuid, Question, QuestionDetail, QuestionStatus, QuestionUuid},
+    reading_time,
+};
+
+/// Builds questions for tests, with a random title and description unless given.
+///
+/// ```
+/// # use tech_qna_api::{models::QuestionStatus, test_support::QuestionBuilder};
+/// let question = QuestionBuilder::new().title("How do I join tables?").build();
+/// let closed = QuestionBuilder::new().status(QuestionStatus::Closed).build_detail();
+/// ```
+#[derive(Debug, Clone)]
+pub struct QuestionBuilder {
+    question_uuid: Option<QuestionUuid>,
+    title: String,
+    description: String,
+    status: QuestionStatus,
+}
+
+impl QuestionBuilder {
+
+    /// Creates a builder for an open question with random, valid text.
+    pub fn new() -> Self {
+        // Lorem sentences end with a period, questions with a question mark
+        let title: String = Sentence(3..8).fake();
+
+        QuestionBuilder {
+            question_uuid: None,
+            title: title.replace('.', "?"),
+            description: Sentence(8..16).fake(),
+            status: QuestionStatus::Open,
+        }
+    }
+
+    /// Sets the UUID of the question, as chosen by a client creating it offline.
+    pub fn question_uuid(mut self, question_uuid: QuestionUuid) -> Self {
+        self.question_uuid = Some(question_uuid);
+        self
+    }
+
+    /// Sets the title of the question.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Sets the description of the question.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Sets the status of the question, which only a `QuestionDetail` has.
+    pub fn status(mut self, status: QuestionStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Builds the question, as sent to create it.
+    pub fn build(self) -> Question {
+        Question {
+            question_uuid: self.question_uuid,
+            title: self.title,
+            description: self.description,
+        }
+    }
+
+    /// Builds the question as stored, with a random UUID unless given, created now and without answers or views.
+    pub fn build_detail(self) -> QuestionDetail {
+        let created_at = Utc::now();
+        let word_count = reading_time::word_count(&[&self.title, &self.description]);
+
+        QuestionDetail {
+            question_uuid: self.question_uuid.unwrap_or_else(QuestionUuid::new_v4),
+            title: self.title,
+            description: self.description,
+            status: self.status,
+            created_at,
+            answer_count: 0,
+            last_activity_at: created_at,
+            view_count: 0,
+            word_count,
+            reading_time_seconds: reading_time::reading_time_seconds(word_count),
+            excerpt: None,
+        }
+    }
+}
+
+impl Default for QuestionBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds answers for tests, with random content unless given.
+///
+/// ```
+/// # use tech_qna_api::{models::QuestionUuid, test_support::AnswerBuilder};
+/// # let question_uuid = QuestionUuid::new_v4();
+/// let answer = AnswerBuilder::new(question_uuid).content("With a JOIN").build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct AnswerBuilder {
+    answer_uuid: Option<AnswerUuid>,
+    question_uuid: QuestionUuid,
+    content: String,
+}
+
+impl AnswerBuilder {
+
+    /// Creates a builder for an answer to a question, with random, valid content.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question answered.
+    pub fn new(question_uuid: QuestionUuid) -> Self {
+        AnswerBuilder {
+            answer_uuid: None,
+            question_uuid,
+            content: Sentence(4..16).fake(),
+        }
+    }
+
+    /// Sets the UUID of the answer, as chosen by a client creating it offline.
+    pub fn answer_uuid(mut self, answer_uuid: AnswerUuid) -> Self {
+        self.answer_uuid = Some(answer_uuid);
+        self
+    }
+
+    /// Sets the content of the answer.
+    pub fn content(mut self, content: impl Into<String>) -> Self {
+        self.content = content.into();
+        self
+    }
+
+    /// Builds the answer, as sent to create it.
+    pub fn build(self) -> Answer {
+        Answer {
+            answer_uuid: self.answer_uuid,
+            question_uuid: self.question_uuid,
+            content: self.content,
+        }
+    }
+
+    /// Builds the answer as stored, with a random UUID unless given, and created now.
+    pub fn build_detail(self) -> AnswerDetail {
+        let word_count = reading_time::word_count(&[&self.content]);
+
+        AnswerDetail {
+            answer_uuid: self.answer_uuid.unwrap_or_else(AnswerUuid::new_v4),
+            question_uuid: self.question_uuid,
+            content: self.content,
+            created_at: Utc::now(),
+            word_count,
+            reading_time_seconds: reading_time::reading_time_seconds(word_count),
+        }
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use validator::Validate;
+
+    #[test]
+    fn should_build_valid_random_questions_and_answers() {
+        for _ in 0..100 {
+            let question = QuestionBuilder::new().build();
+            assert!(question.validate().is_ok(), "{:?}", question.title);
+            assert!(question.title.ends_with('?'));
+
+            let answer = AnswerBuilder::new(QuestionUuid::new_v4()).build();
+            assert!(answer.validate().is_ok(), "{:?}", answer.content);
+        }
+    }
+
+    #[test]
+    fn should_override_defaults() {
+        let question = QuestionBuilder::new()
+            .title("title")
+            .description("description")
+            .status(QuestionStatus::Closed)
+            .build_detail();
+
+        assert_eq!(
+            (question.title.as_str(), question.description.as_str(), question.status),
+            ("title", "description", QuestionStatus::Closed)
+        );
+
+        let answer = AnswerBuilder::new(question.question_uuid).content("content").build_detail();
+
+        assert_eq!(answer.question_uuid, question.question_uuid);
+        assert_eq!(answer.content, "content");
+    }
+}
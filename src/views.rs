@@ -0,0 +1,133 @@
+use std::{collections::HashMap, mem, sync::Mutex, time::Duration};
+
+use tokio::task::JoinHandle;
+
+use crate::{models::QuestionUuid, persistance::questions_dao::QuestionsDao, AppState};
+
+// Reading a question must not cost a write to the database each time, so views are counted in memory and added to
+// the view counts of their questions in a single write every few seconds, however often the questions were read
+// meanwhile. Views counted but not written yet are lost if the instance crashes, which a view counter can afford.
+
+/// Wait between two writes of the views counted
+const FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Counts the views of questions until they are written.
+#[derive(Default)]
+pub struct ViewCounter {
+    pending: Mutex<HashMap<QuestionUuid, i64>>,
+}
+
+impl ViewCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts a view of a question.
+    ///
+    /// # Arguments
+    ///
+    /// * `question_uuid` - The unique identifier of the question read.
+    pub fn record(&self, question_uuid: QuestionUuid) {
+        *self.pending.lock().unwrap().entry(question_uuid).or_default() += 1;
+    }
+
+    /// Takes the views counted so far, counting the next ones from zero.
+    fn take(&self) -> HashMap<QuestionUuid, i64> {
+        mem::take(&mut *self.pending.lock().unwrap())
+    }
+
+    /// Counts again views that could not be written, so they are written along with the next ones.
+    fn restore(&self, views: HashMap<QuestionUuid, i64>) {
+        let mut pending = self.pending.lock().unwrap();
+
+        for (question_uuid, count) in views {
+            *pending.entry(question_uuid).or_default() += count;
+        }
+    }
+}
+
+/// Starts writing the views counted by the state every `FLUSH_INTERVAL`.
+///
+/// # Arguments
+///
+/// * `state` - The application state, holding the view counter and the `QuestionsDao` views are written with.
+///
+/// # Returns
+///
+/// The handle of the background task, which runs until the runtime shuts down.
+pub fn start(state: &AppState) -> JoinHandle<()> {
+    let (counter, questions_dao) = (state.view_counter.clone(), state.questions_dao.clone());
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+
+        loop {
+            interval.tick().await;
+            flush(&counter, questions_dao.as_ref()).await;
+        }
+    })
+}
+
+/// Writes the views counted so far, keeping them to write with the next ones if that fails.
+///
+/// # Arguments
+///
+/// * `counter` - The view counter.
+/// * `questions_dao` - The DAO adding the views to the view counts of the questions.
+pub async fn flush(counter: &ViewCounter, questions_dao: &(dyn QuestionsDao + Send + Sync)) {
+    let views = counter.take();
+    if views.is_empty() {
+        return;
+    }
+
+    if let Err(err) = questions_dao.add_question_views(&views).await {
+        error!("Failed to write the views of {} questions: {:?}", views.len(), err);
+        counter.restore(views);
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Arc;
+
+    use crate::{
+        persistance::{memory::MemoryStore, questions_dao::QuestionsDaoInMemory},
+        test_support::QuestionBuilder,
+    };
+
+    #[tokio::test]
+    async fn should_write_views_counted_at_once() {
+        let questions_dao = QuestionsDaoInMemory::new(Arc::new(MemoryStore::new()));
+        let question = questions_dao.create_question(QuestionBuilder::new().build()).await.unwrap();
+
+        let counter = ViewCounter::new();
+        counter.record(question.question_uuid);
+        counter.record(question.question_uuid);
+        counter.record(QuestionUuid::new_v4());
+
+        flush(&counter, &questions_dao).await;
+
+        let question = questions_dao.get_question_with_answers(question.question_uuid).await.unwrap().question;
+        assert_eq!(question.view_count, 2);
+        assert!(counter.take().is_empty());
+    }
+
+    #[test]
+    fn should_count_views_not_written_with_the_next_ones() {
+        let question_uuid = QuestionUuid::new_v4();
+        let counter = ViewCounter::new();
+
+        counter.record(question_uuid);
+        let failed = counter.take();
+        counter.record(question_uuid);
+        counter.restore(failed);
+
+        assert_eq!(counter.take(), HashMap::from([(question_uuid, 2)]));
+    }
+}
@@ -0,0 +1,378 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::header::CONTENT_TYPE;
+use serde::Serialize;
+use sha2::Sha256;
+use thiserror::Error;
+use tokio::{
+    sync::broadcast::{error::RecvError, Receiver},
+    task::JoinHandle,
+};
+use uuid::Uuid;
+
+use crate::{
+    models::{DBError, WebhookDetail},
+    outbound::{OutboundClient, OutboundError},
+    outbox::ContentEvent,
+    persistance::webhooks_dao::WebhooksDao,
+    AppState,
+};
+
+/// Header carrying the name of the event, e.g. `question.created`
+pub const EVENT_HEADER: &str = "X-Webhook-Event";
+
+/// Header carrying the unique identifier of the delivery, the same for every attempt
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery";
+
+/// Header carrying `sha256=` followed by the hex encoded HMAC-SHA256 of the body, keyed with the webhook's secret
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
+/// Attempts made to deliver an event before giving up on it
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Wait before the first retry, doubled before each of the next ones
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Wait between two deletions of the expired question webhooks, which are skipped meanwhile
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Body POSTed to webhooks
+#[derive(Serialize)]
+struct Payload<'a> {
+    delivery_uuid: Uuid,
+    occurred_at: DateTime<Utc>,
+    #[serde(flatten)]
+    event: &'a ContentEvent,
+}
+
+/// Errors for a single attempt at delivering an event
+#[derive(Error, Debug)]
+enum DeliveryError {
+
+    /// The URL is not allowed, which retrying cannot change
+    #[error("refused: {0}")]
+    Refused(OutboundError),
+
+    /// The request failed or was answered with something other than a success
+    #[error("{0}")]
+    Failed(String),
+}
+
+/// Starts delivering the content events published from now on to the registered webhooks.
+///
+/// # Arguments
+///
+/// * `state` - The application state, whose content events are delivered to the webhooks of its `WebhooksDao`.
+///
+/// # Returns
+///
+/// A `JoinHandle` of the task delivering events, which runs until the feed of content events is dropped.
+pub fn start(state: &AppState) -> JoinHandle<()> {
+    tokio::spawn(deliver_events(
+        state.webhooks_dao.clone(),
+        state.content_events.subscribe(),
+        OutboundClient::new(),
+    ))
+}
+
+/// Starts deleting expired question webhooks in the background, so they do not pile up.
+///
+/// # Arguments
+///
+/// * `state` - The application state, whose `WebhooksDao` is purged.
+///
+/// # Returns
+///
+/// A `JoinHandle` of the task deleting webhooks, which runs until aborted.
+pub fn purge_expired(state: &AppState) -> JoinHandle<()> {
+    let webhooks_dao = state.webhooks_dao.clone();
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(err) = webhooks_dao.delete_expired_question_webhooks(Utc::now()).await {
+                error!("Failed to delete expired question webhooks: {:?}", err);
+            }
+        }
+    })
+}
+
+async fn deliver_events(
+    webhooks_dao: Arc<dyn WebhooksDao + Send + Sync>,
+    mut events: Receiver<ContentEvent>,
+    client: OutboundClient,
+) {
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(missed)) => {
+                warn!("Webhooks missed {} events, as deliveries could not keep up.", missed);
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+
+        // Read for every event, so webhooks registered on other instances are notified too
+        let webhooks = match webhooks_for(webhooks_dao.as_ref(), &event).await {
+            Ok(webhooks) => webhooks,
+            Err(err) => {
+                error!("Failed to read webhooks, dropping the {} event: {:?}", event.name(), err);
+                continue;
+            }
+        };
+
+        let event = Arc::new(event);
+        for webhook in webhooks {
+            // Retries of a slow webhook must not hold up the others
+            tokio::spawn(deliver(client.clone(), webhook, event.clone()));
+        }
+    }
+}
+
+/// Reads the webhooks to notify of an event: every global webhook, along with the webhooks watching the question
+/// of a created answer that have not expired.
+async fn webhooks_for(
+    webhooks_dao: &(dyn WebhooksDao + Send + Sync),
+    event: &ContentEvent,
+) -> Result<Vec<WebhookDetail>, DBError> {
+    let mut webhooks = webhooks_dao.get_webhooks().await?;
+
+    if let ContentEvent::AnswerCreated(answer) = event {
+        let watching = webhooks_dao.get_question_webhooks(answer.question_uuid).await?;
+        webhooks.extend(watching.into_iter().map(WebhookDetail::from));
+    }
+
+    Ok(webhooks)
+}
+
+async fn deliver(client: OutboundClient, webhook: WebhookDetail, event: Arc<ContentEvent>) {
+    let delivery_uuid = Uuid::new_v4();
+    let payload = Payload {
+        delivery_uuid,
+        occurred_at: Utc::now(),
+        event: &event,
+    };
+
+    let body = match serde_json::to_vec(&payload) {
+        Ok(body) => body,
+        Err(err) => {
+            error!("Failed to serialize the {} event: {:?}", event.name(), err);
+            return;
+        }
+    };
+    let signature = sign(&webhook.secret, &body);
+
+    let result = with_retries(|| async {
+        let response = client
+            .post(&webhook.url)
+            .map_err(DeliveryError::Refused)?
+            .header(CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, event.name())
+            .header(DELIVERY_HEADER, delivery_uuid.to_string())
+            .header(SIGNATURE_HEADER, &signature)
+            .body(body.clone())
+            .send()
+            .await
+            .map_err(|err| DeliveryError::Failed(err.to_string()))?;
+
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            status => Err(DeliveryError::Failed(format!("answered with {}", status))),
+        }
+    })
+    .await;
+
+    if let Err(err) = result {
+        error!("Gave up delivering {} {} to webhook {}: {}", event.name(), delivery_uuid, webhook.webhook_uuid, err);
+    }
+}
+
+/// Signs a payload for the `X-Webhook-Signature` header.
+///
+/// # Arguments
+///
+/// * `secret` - The secret of the webhook.
+/// * `body` - The body sent to the webhook.
+///
+/// # Returns
+///
+/// `sha256=` followed by the hex encoded HMAC-SHA256 of the body.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Makes attempts until one succeeds, waiting exponentially longer after each failure.
+async fn with_retries<F, Fut>(mut attempt: F) -> Result<(), DeliveryError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), DeliveryError>>,
+{
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempts = 1;
+
+    loop {
+        match attempt().await {
+            Ok(()) => return Ok(()),
+            Err(DeliveryError::Failed(reason)) if attempts < MAX_ATTEMPTS => {
+                warn!("Webhook delivery attempt {} failed, retrying in {:?}: {}", attempts, backoff, reason);
+            }
+            Err(err) => return Err(err),
+        }
+
+        tokio::time::sleep(backoff).await;
+        backoff *= 2;
+        attempts += 1;
+    }
+}
+
+// ***********************************************************
+//                           Tests
+// ***********************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use chrono::TimeDelta;
+    use tokio::time::Instant;
+
+    use crate::{
+        models::{AnswerId, QuestionId, Webhook},
+        persistance::{
+            answers_dao::{AnswersDao, AnswersDaoInMemory},
+            memory::MemoryStore,
+            questions_dao::{QuestionsDao, QuestionsDaoInMemory},
+            webhooks_dao::WebhooksDaoInMemory,
+        },
+        test_support::{AnswerBuilder, QuestionBuilder},
+    };
+
+    fn webhook(url: &str) -> Webhook {
+        Webhook {
+            url: url.to_owned(),
+            secret: "0123456789abcdef".to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_notify_question_webhooks_of_its_answers_only() {
+        let store = Arc::new(MemoryStore::new());
+        let questions_dao = QuestionsDaoInMemory::new(store.clone());
+        let answers_dao = AnswersDaoInMemory::new(store.clone());
+        let webhooks_dao = WebhooksDaoInMemory::new(store);
+
+        let question = questions_dao.create_question(QuestionBuilder::new().build()).await.unwrap();
+        let other = questions_dao.create_question(QuestionBuilder::new().build()).await.unwrap();
+        let answer = answers_dao
+            .create_answer(AnswerBuilder::new(question.question_uuid).build())
+            .await
+            .unwrap();
+
+        let global = webhooks_dao.create_webhook(webhook("https://example.com/all")).await.unwrap();
+        let tomorrow = Utc::now() + TimeDelta::days(1);
+        let watching = webhooks_dao
+            .create_question_webhook(question.question_uuid, webhook("https://example.com/watch"), tomorrow)
+            .await
+            .unwrap();
+        webhooks_dao
+            .create_question_webhook(other.question_uuid, webhook("https://example.com/other"), tomorrow)
+            .await
+            .unwrap();
+
+        let answered = webhooks_for(&webhooks_dao, &ContentEvent::AnswerCreated(answer)).await.unwrap();
+        assert_eq!(answered, vec![global.clone(), watching.into()]);
+
+        let deleted = ContentEvent::QuestionDeleted(QuestionId { question_uuid: question.question_uuid });
+        assert_eq!(webhooks_for(&webhooks_dao, &deleted).await.unwrap(), vec![global]);
+    }
+
+    #[test]
+    fn should_sign_with_hmac_sha256() {
+        // Test case 2 of RFC 4231
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn should_name_events_in_payloads() {
+        let event = ContentEvent::AnswerDeleted(AnswerId {
+            answer_uuid: Uuid::nil().into(),
+        });
+
+        let payload = serde_json::to_value(Payload {
+            delivery_uuid: Uuid::nil(),
+            occurred_at: DateTime::UNIX_EPOCH,
+            event: &event,
+        })
+        .unwrap();
+
+        assert_eq!(
+            payload,
+            serde_json::json!({
+                "delivery_uuid": "00000000-0000-0000-0000-000000000000",
+                "occurred_at": "1970-01-01T00:00:00Z",
+                "event": "answer.deleted",
+                "data": { "answer_uuid": "00000000-0000-0000-0000-000000000000" },
+            })
+        );
+        assert_eq!(payload["event"], event.name());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_retry_failures_with_exponential_backoff() {
+        let attempts = AtomicU32::new(0);
+        let started = Instant::now();
+
+        let result = with_retries(|| async {
+            match attempts.fetch_add(1, Ordering::Relaxed) {
+                0..=2 => Err(DeliveryError::Failed("answered with 503".to_owned())),
+                _ => Ok(()),
+            }
+        })
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(attempts.load(Ordering::Relaxed), 4);
+        assert_eq!(started.elapsed(), Duration::from_secs(1 + 2 + 4));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn should_give_up_after_the_last_attempt() {
+        let attempts = AtomicU32::new(0);
+
+        let result = with_retries(|| async {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err(DeliveryError::Failed("answered with 500".to_owned()))
+        })
+        .await;
+
+        assert!(matches!(result, Err(DeliveryError::Failed(_))));
+        assert_eq!(attempts.load(Ordering::Relaxed), MAX_ATTEMPTS);
+    }
+
+    #[tokio::test]
+    async fn should_not_retry_refused_urls() {
+        let attempts = AtomicU32::new(0);
+
+        let result = with_retries(|| async {
+            attempts.fetch_add(1, Ordering::Relaxed);
+            Err(DeliveryError::Refused(OutboundError::SchemeNotAllowed("ftp".to_owned())))
+        })
+        .await;
+
+        assert!(matches!(result, Err(DeliveryError::Refused(_))));
+        assert_eq!(attempts.load(Ordering::Relaxed), 1);
+    }
+}
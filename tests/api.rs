@@ -0,0 +1,1122 @@
+use std::future::IntoFuture;
+
+use axum::{
+    body::Body,
+    http::{header, Request, StatusCode},
+    Router,
+};
+use futures::StreamExt;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use tower::ServiceExt;
+
+use tech_qna_api::{app, config::Config, content_filter::FilterAction, outbox, views, AppState};
+
+fn router(pool: PgPool, admin_token: Option<&str>) -> Router {
+    let config = Config {
+        admin_token: admin_token.map(str::to_owned),
+        ..Config::default()
+    };
+
+    app(AppState::new(pool, &config))
+}
+
+async fn send(router: &Router, request: Request<Body>) -> (StatusCode, Value) {
+    let response = router.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn json_request(method: &str, uri: &str, body: Value) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[sqlx::test]
+async fn should_create_and_list_questions_and_answers(pool: PgPool) {
+    let router = router(pool, None);
+
+    let (status, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "How do I split a crate?",
+        "description": "Into a library and a binary"
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let question_uuid = question["question_uuid"].as_str().unwrap();
+    assert!(chrono::DateTime::parse_from_rfc3339(question["created_at"].as_str().unwrap()).is_ok());
+
+    let (status, _) = send(&router, json_request("POST", "/answer", json!({
+        "question_uuid": question_uuid,
+        "content": "Add a src/lib.rs"
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, questions) = send(&router, Request::get("/questions").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(questions.as_array().unwrap().len(), 1);
+    assert_eq!(questions[0]["answer_count"], 1);
+
+    let (status, answers) = send(&router, json_request("GET", "/answers", json!({ "question_uuid": question_uuid }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(answers[0]["content"], "Add a src/lib.rs");
+}
+
+#[tokio::test]
+async fn should_serve_from_memory_backend() {
+    let router = app(AppState::in_memory(&Config::default()));
+
+    let (status, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "Do I need Postgres?",
+        "description": "Not with STORAGE_BACKEND=memory"
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _) = send(&router, json_request("DELETE", "/question", json!({
+        "question_uuid": question["question_uuid"]
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, questions) = send(&router, Request::get("/questions").body(Body::empty()).unwrap()).await;
+    assert_eq!(questions, json!([]));
+
+    // No external dependencies, so always ready
+    let (status, _) = send(&router, Request::get("/ready").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn should_serve_openapi_specification_and_swagger_ui() {
+    let router = app(AppState::in_memory(&Config::default()));
+
+    let (status, spec) = send(&router, Request::get("/api-doc/openapi.json").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(spec["info"]["title"], "Tech Q&A API");
+    assert!(spec["paths"]["/questions/{question_uuid}/full"]["get"].is_object());
+    assert!(spec["components"]["schemas"]["QuestionDetail"].is_object());
+
+    let response = router.oneshot(Request::get("/swagger-ui/").body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[cfg(feature = "graphql")]
+#[tokio::test]
+async fn should_serve_graphql() {
+    let router = app(AppState::in_memory(&Config::default()));
+
+    let (status, created) = send(&router, json_request("POST", "/graphql", json!({
+        "query": "mutation($title: String!) { createQuestion(title: $title, description: \"\") { title } }",
+        "variables": { "title": "  Through   GraphQL? " }
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(created, json!({ "data": { "createQuestion": { "title": "Through GraphQL?" } } }));
+
+    let (status, questions) = send(&router, json_request("POST", "/graphql", json!({
+        "query": "{ questions { title answers { content } } }"
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(questions["data"]["questions"][0]["answers"], json!([]));
+}
+
+#[tokio::test]
+async fn should_stream_created_questions() {
+    let state = AppState::in_memory(&Config::default());
+    // Created questions are published once dispatched from the outbox
+    outbox::start(&state);
+    let router = app(state);
+
+    let response = router.clone().oneshot(Request::get("/questions/stream").body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()[header::CONTENT_TYPE], "text/event-stream");
+
+    let (_, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "Is this streamed?",
+        "description": ""
+    }))).await;
+
+    let event = response.into_body().into_data_stream().next().await.unwrap().unwrap();
+    let event = std::str::from_utf8(&event).unwrap();
+    assert!(event.starts_with("event: question_created\ndata: "));
+
+    let data: Value = serde_json::from_str(event.lines().nth(1).unwrap().trim_start_matches("data: ")).unwrap();
+    assert_eq!(data, question);
+}
+
+#[tokio::test]
+async fn should_push_answers_over_websocket() {
+    let state = AppState::in_memory(&Config::default());
+    outbox::start(&state);
+    let router = app(state);
+
+    let (_, watched) = send(&router, json_request("POST", "/question", json!({
+        "title": "Is anyone watching?",
+        "description": ""
+    }))).await;
+    let (_, other) = send(&router, json_request("POST", "/question", json!({
+        "title": "Is anyone else watching?",
+        "description": ""
+    }))).await;
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(axum::serve(listener, router.clone()).into_future());
+
+    let url = format!("ws://{}/ws/questions/{}", address, watched["question_uuid"].as_str().unwrap());
+    let (mut socket, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+
+    for question in [&other, &watched] {
+        let (status, _) = send(&router, json_request("POST", "/answer", json!({
+            "question_uuid": question["question_uuid"],
+            "content": "Yes"
+        }))).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    // The answer to the other question is not pushed
+    let message = socket.next().await.unwrap().unwrap();
+    let answer: Value = serde_json::from_str(message.to_text().unwrap()).unwrap();
+    assert_eq!(answer["question_uuid"], watched["question_uuid"]);
+    assert_eq!(answer["content"], "Yes");
+}
+
+#[tokio::test]
+async fn should_long_poll_answers() {
+    let state = AppState::in_memory(&Config::default());
+    outbox::start(&state);
+    let router = app(state);
+
+    let (_, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "Is anyone polling?",
+        "description": ""
+    }))).await;
+    let uri = format!("/question/{}/updates", question["question_uuid"].as_str().unwrap());
+
+    // Held until the answer arrives, or returning it at once if it is created before the poll reads the database
+    let poll = tokio::spawn({
+        let (router, uri) = (router.clone(), format!("{}?wait=10s", uri));
+        async move { send(&router, Request::get(uri).body(Body::empty()).unwrap()).await }
+    });
+    let (_, answer) = send(&router, json_request("POST", "/answer", json!({
+        "question_uuid": question["question_uuid"],
+        "content": "Yes"
+    }))).await;
+
+    let (status, updates) = poll.await.unwrap();
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updates["answers"], json!([answer]));
+    assert_eq!(updates["cursor"], answer["created_at"]);
+
+    let since = format!("{}?since={}&wait=0s", uri, answer["created_at"].as_str().unwrap());
+    let (status, updates) = send(&router, Request::get(since).body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(updates["answers"], json!([]));
+    assert_eq!(updates["cursor"], answer["created_at"]);
+
+    let (status, _) = send(&router, Request::get(format!("{}?wait=soon", uri)).body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn should_normalize_inputs_before_storing() {
+    let router = app(AppState::in_memory(&Config::default()));
+
+    let (status, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "  Cafe\u{301}\n  ordering\t ",
+        "description": "First line\r\nSecond line\u{0}"
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(question["title"], "Caf\u{e9} ordering");
+    assert_eq!(question["description"], "First line\nSecond line");
+}
+
+#[tokio::test]
+async fn should_search_questions() {
+    let router = app(AppState::in_memory(&Config::default()));
+
+    for title in ["How do I use Tokio?", "What is a trait?"] {
+        send(&router, json_request("POST", "/question", json!({ "title": title, "description": "Asking" }))).await;
+    }
+
+    let (status, results) = send(&router, Request::get("/questions/search?q=tokio").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(results.as_array().unwrap().len(), 1);
+    assert_eq!(results[0]["title"], "How do I use Tokio?");
+    assert_eq!(results[0]["snippet"], "How do I use <mark>Tokio</mark>? Asking");
+
+    let (status, _) = send(&router, Request::get("/questions/search?q=").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn should_reject_invalid_bodies_with_field_errors() {
+    let router = app(AppState::in_memory(&Config::default()));
+
+    let (status, body) = send(&router, json_request("POST", "/question", json!({
+        "title": "   ",
+        "description": "Whitespace only"
+    }))).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body, json!({ "errors": [{ "field": "title", "code": "blank", "message": "must not be blank" }] }));
+
+    let (_, questions) = send(&router, Request::get("/questions").body(Body::empty()).unwrap()).await;
+    assert_eq!(questions, json!([]));
+}
+
+#[sqlx::test]
+async fn should_reject_answer_with_invalid_question_uuid(pool: PgPool) {
+    let router = router(pool, None);
+
+    // Malformed UUIDs are rejected when the body is deserialized
+    let (status, _) = send(&router, json_request("POST", "/answer", json!({
+        "question_uuid": "not-a-uuid",
+        "content": "Orphan"
+    }))).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (status, _) = send(&router, json_request("POST", "/answer", json!({
+        "question_uuid": "a22abcd2-22ab-2222-a22b-2abc2a2b22cc",
+        "content": "Orphan"
+    }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test]
+async fn should_create_with_uuids_chosen_offline(pool: PgPool) {
+    let router = router(pool, None);
+    let question = json!({
+        "question_uuid": "d347261c-3f0e-42d2-8706-5ef9f1b96725",
+        "title": "How do I split a crate?",
+        "description": "Into a library and a binary"
+    });
+    let answer = json!({
+        "answer_uuid": "a1a14a9c-ab9e-481b-8120-67f675531ed2",
+        "question_uuid": "d347261c-3f0e-42d2-8706-5ef9f1b96725",
+        "content": "Add a src/lib.rs"
+    });
+
+    let (status, created) = send(&router, json_request("POST", "/question", question.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(created["question_uuid"], question["question_uuid"]);
+
+    let (status, created) = send(&router, json_request("POST", "/answer", answer.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(created["answer_uuid"], answer["answer_uuid"]);
+
+    // Syncing again finds them already created
+    let (status, _) = send(&router, json_request("POST", "/question", question)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (status, _) = send(&router, json_request("POST", "/answer", answer)).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[sqlx::test]
+async fn should_close_and_reopen_questions(pool: PgPool) {
+    let router = router(pool, None);
+
+    let (_, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "Is this a duplicate?",
+        "description": "Probably"
+    }))).await;
+    assert_eq!(question["status"], "open");
+    let question_uuid = question["question_uuid"].as_str().unwrap();
+    let answer = json!({ "question_uuid": question_uuid, "content": "Yes" });
+
+    let (status, closed) = send(&router, json_request("POST", &format!("/questions/{}/close", question_uuid), json!({}))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(closed["status"], "closed");
+
+    let (status, _) = send(&router, json_request("POST", "/answer", answer.clone())).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let (_, questions) = send(&router, Request::get("/questions?status=closed").body(Body::empty()).unwrap()).await;
+    assert_eq!(questions.as_array().unwrap().len(), 1);
+    let (_, questions) = send(&router, Request::get("/questions?status=open").body(Body::empty()).unwrap()).await;
+    assert_eq!(questions, json!([]));
+
+    let (status, reopened) = send(&router, json_request("POST", &format!("/questions/{}/reopen", question_uuid), json!({}))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(reopened["status"], "open");
+
+    let (status, _) = send(&router, json_request("POST", "/answer", answer)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Only closed questions can be reopened
+    let (status, _) = send(&router, json_request("POST", &format!("/questions/{}/reopen", question_uuid), json!({}))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[sqlx::test]
+async fn should_sort_questions_and_answers(pool: PgPool) {
+    let router = router(pool, None);
+
+    let mut question_uuids = Vec::new();
+    for title in ["Unanswered", "Answered"] {
+        let (_, question) = send(&router, json_request("POST", "/question", json!({
+            "title": title,
+            "description": "Sorting"
+        }))).await;
+        question_uuids.push(question["question_uuid"].as_str().unwrap().to_owned());
+    }
+    for content in ["First", "Second"] {
+        let answer = json!({ "question_uuid": question_uuids[1], "content": content });
+        send(&router, json_request("POST", "/answer", answer)).await;
+    }
+
+    let (_, questions) = send(&router, Request::get("/questions?sort=most_answered").body(Body::empty()).unwrap()).await;
+    assert_eq!(questions[0]["title"], "Answered");
+    let (_, questions) = send(&router, Request::get("/questions?sort=oldest").body(Body::empty()).unwrap()).await;
+    assert_eq!(questions[0]["title"], "Unanswered");
+    let (_, questions) = send(&router, Request::get("/questions?answered=false").body(Body::empty()).unwrap()).await;
+    assert_eq!(questions.as_array().unwrap().len(), 1);
+    assert_eq!(questions[0]["title"], "Unanswered");
+
+    let body = json!({ "question_uuid": question_uuids[1] });
+    let (status, answers) = send(&router, json_request("GET", "/answers?sort=newest", body.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(answers[0]["content"], "Second");
+
+    let (status, _) = send(&router, Request::get("/questions?sort=top").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&router, json_request("GET", "/answers?sort=accepted_first", body)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test]
+async fn should_read_question_with_its_answers(pool: PgPool) {
+    let router = router(pool, None);
+
+    let (_, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "How do I join tables?",
+        "description": "In SQL"
+    }))).await;
+    let question_uuid = question["question_uuid"].as_str().unwrap();
+
+    for content in ["With JOIN", "With a subquery"] {
+        send(&router, json_request("POST", "/answer", json!({ "question_uuid": question_uuid, "content": content }))).await;
+    }
+
+    let uri = format!("/questions/{}/full", question_uuid);
+    let (status, full) = send(&router, Request::get(&uri).body(Body::empty()).unwrap()).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(full["title"], "How do I join tables?");
+    assert_eq!(full["answer_count"], 2);
+    assert_eq!(full["answers"][0]["content"], "With JOIN");
+    assert_eq!(full["answers"][1]["content"], "With a subquery");
+
+    let uri = format!("/questions/{}/full", uuid::Uuid::nil());
+    let (status, _) = send(&router, Request::get(&uri).body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn should_print_question_as_plain_text() {
+    let router = app(AppState::in_memory(&Config::default()));
+
+    let (_, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "How do I join tables?",
+        "description": "In **SQL**"
+    }))).await;
+    let question_uuid = question["question_uuid"].as_str().unwrap();
+    send(&router, json_request("POST", "/answer", json!({ "question_uuid": question_uuid, "content": "With `JOIN`" }))).await;
+
+    let uri = format!("/question/{}.txt", question_uuid);
+    let response = router.clone().oneshot(Request::get(&uri).body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers()[header::CONTENT_TYPE].to_str().unwrap().starts_with("text/plain"));
+
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text.starts_with("How do I join tables?\n=====================\n"));
+    assert!(text.contains("\nIn SQL\n"));
+    assert!(text.contains("\nAnswer 1 of 1, "));
+    assert!(text.ends_with("\n\nWith JOIN\n"));
+
+    let (status, _) = send(&router, Request::get(format!("/question/{}", question_uuid)).body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _) = send(&router, Request::get("/question/unknown.txt").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[sqlx::test]
+async fn should_list_most_viewed_questions(pool: PgPool) {
+    let state = AppState::new(pool, &Config::default());
+    let router = app(state.clone());
+
+    let mut question_uuids = Vec::new();
+    for title in ["Rarely read", "Often read"] {
+        let (_, question) = send(&router, json_request("POST", "/question", json!({
+            "title": title,
+            "description": "Description"
+        }))).await;
+        question_uuids.push(question["question_uuid"].as_str().unwrap().to_owned());
+    }
+
+    for uri in [
+        format!("/questions/{}/full", question_uuids[1]),
+        format!("/question/{}.txt", question_uuids[1]),
+        format!("/questions/{}/full", question_uuids[0]),
+        format!("/questions/{}/full", uuid::Uuid::nil()),
+    ] {
+        send(&router, Request::get(uri).body(Body::empty()).unwrap()).await;
+    }
+
+    // Views are only written in batches
+    let (_, popular) = send(&router, Request::get("/questions/popular").body(Body::empty()).unwrap()).await;
+    assert_eq!(popular[0]["view_count"], 0);
+
+    views::flush(&state.view_counter, state.questions_dao.as_ref()).await;
+
+    let request = Request::get("/questions/popular?limit=1").body(Body::empty()).unwrap();
+    let (status, popular) = send(&router, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(popular.as_array().unwrap().len(), 1);
+    assert_eq!(popular[0]["title"], "Often read");
+    assert_eq!(popular[0]["view_count"], 2);
+}
+
+#[tokio::test]
+async fn should_shape_responses_for_compact_profile() {
+    let router = app(AppState::in_memory(&Config::default()));
+
+    let (_, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "How do I save bandwidth?",
+        "description": "On mobile"
+    }))).await;
+    let content = "Ask for less. ".repeat(15);
+    send(&router, json_request("POST", "/answer", json!({
+        "question_uuid": question["question_uuid"],
+        "content": content.trim()
+    }))).await;
+
+    let uri = format!("/questions/{}/full?profile=compact", question["question_uuid"].as_str().unwrap());
+    let (status, full) = send(&router, Request::get(&uri).body(Body::empty()).unwrap()).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(full["title"], "How do I save bandwidth?");
+    assert!(full.get("description").is_none());
+    assert_eq!(full["answers"][0]["content"].as_str().unwrap().chars().count(), 140);
+    assert_eq!(full["answers"][0]["content_length"], content.trim().len());
+
+    // Lists keep the excerpt standing in for the description
+    let (_, questions) = send(&router, Request::get("/questions?profile=compact").body(Body::empty()).unwrap()).await;
+    assert!(questions[0].get("description").is_none());
+    assert_eq!(questions[0]["excerpt"], "On mobile");
+
+    let (status, _) = send(&router, Request::get("/questions?profile=tiny").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn should_render_markdown_to_sanitized_html_on_request() {
+    let router = app(AppState::in_memory(&Config::default()));
+
+    let (_, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "How do I render markdown?",
+        "description": "With *emphasis*<img src=x onerror=alert(1)>"
+    }))).await;
+    send(&router, json_request("POST", "/answer", json!({
+        "question_uuid": question["question_uuid"],
+        "content": "Use `pulldown-cmark`"
+    }))).await;
+    let uri = format!("/questions/{}/full", question["question_uuid"].as_str().unwrap());
+
+    // Markdown only, unless asked for
+    let (_, full) = send(&router, Request::get(&uri).body(Body::empty()).unwrap()).await;
+    assert!(full.get("rendered_description").is_none());
+
+    let (status, full) = send(&router, Request::get(format!("{}?render=html", uri)).body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(full["description"], "With *emphasis*<img src=x onerror=alert(1)>");
+    assert_eq!(full["rendered_description"], "<p>With <em>emphasis</em></p>\n");
+    assert_eq!(full["answers"][0]["rendered_content"], "<p>Use <code>pulldown-cmark</code></p>\n");
+
+    let (status, _) = send(&router, Request::get(format!("{}?render=pdf", uri)).body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn should_answer_not_modified_until_questions_change() {
+    let router = app(AppState::in_memory(&Config::default()));
+
+    let (_, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "How do I poll cheaply?",
+        "description": "With If-None-Match"
+    }))).await;
+    let full_uri = format!("/questions/{}/full", question["question_uuid"].as_str().unwrap());
+
+    for uri in ["/questions", full_uri.as_str()] {
+        let response = router.clone().oneshot(Request::get(uri).body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[header::ETAG].clone();
+
+        let conditional = || Request::get(uri).header(header::IF_NONE_MATCH, &etag).body(Body::empty()).unwrap();
+        let (status, body) = send(&router, conditional()).await;
+        assert_eq!(status, StatusCode::NOT_MODIFIED);
+        assert_eq!(body, Value::Null);
+
+        send(&router, json_request("POST", "/answer", json!({
+            "question_uuid": question["question_uuid"],
+            "content": "Changes the answer count"
+        }))).await;
+
+        let (status, _) = send(&router, conditional()).await;
+        assert_eq!(status, StatusCode::OK, "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn should_sync_only_what_changed() {
+    let router = app(AppState::in_memory(&Config::default()));
+
+    let mut questions = Vec::new();
+    for title in ["Is this held as is?", "Is this held but answered since?"] {
+        let (_, question) = send(&router, json_request("POST", "/question", json!({
+            "title": title,
+            "description": ""
+        }))).await;
+        questions.push(question);
+    }
+
+    // The client holds both questions as read in full
+    let mut held = Vec::new();
+    for question in &questions {
+        let uri = format!("/questions/{}/full", question["question_uuid"].as_str().unwrap());
+        let response = router.clone().oneshot(Request::get(uri).body(Body::empty()).unwrap()).await.unwrap();
+        held.push(json!({
+            "question_uuid": question["question_uuid"],
+            "version": response.headers()[header::ETAG].to_str().unwrap()
+        }));
+    }
+    held.push(json!({ "question_uuid": "a22abcd2-22ab-2222-a22b-2abc2a2b22cc", "version": "\"deleted\"" }));
+
+    let (_, since) = send(&router, json_request("POST", "/sync", json!({ "questions": [] }))).await;
+    let (_, answer) = send(&router, json_request("POST", "/answer", json!({
+        "question_uuid": questions[1]["question_uuid"],
+        "content": "Yes"
+    }))).await;
+    let (_, created) = send(&router, json_request("POST", "/question", json!({
+        "title": "Is this new?",
+        "description": ""
+    }))).await;
+
+    let (status, sync) = send(&router, json_request("POST", "/sync", json!({
+        "questions": held,
+        "since": since["synced_at"]
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    assert_eq!(sync["changed"].as_array().unwrap().len(), 1);
+    assert_eq!(sync["changed"][0]["question_uuid"], questions[1]["question_uuid"]);
+    assert_eq!(sync["changed"][0]["answers"], json!([answer]));
+    assert_eq!(sync["created"].as_array().unwrap().len(), 1);
+    assert_eq!(sync["created"][0]["question_uuid"], created["question_uuid"]);
+    assert_eq!(sync["deleted"], json!(["a22abcd2-22ab-2222-a22b-2abc2a2b22cc"]));
+
+    // The version returned is the tag of the question read in full
+    let uri = format!("/questions/{}/full", created["question_uuid"].as_str().unwrap());
+    let response = router.clone().oneshot(Request::get(uri).body(Body::empty()).unwrap()).await.unwrap();
+    assert_eq!(sync["created"][0]["version"], response.headers()[header::ETAG].to_str().unwrap());
+}
+
+#[sqlx::test]
+async fn should_sync_questions_committed_after_the_previous_sync(pool: PgPool) {
+    let router = app(AppState::new(pool.clone(), &Config::default()));
+
+    // Stamped when its transaction starts, before the sync, but only committed after it
+    let mut tx = pool.begin().await.unwrap();
+    let question_uuid: uuid::Uuid =
+        sqlx::query_scalar("INSERT INTO questions ( title, description ) VALUES ( 'Is this late?', '' ) RETURNING question_uuid")
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+
+    let (_, sync) = send(&router, json_request("POST", "/sync", json!({ "questions": [], "since": "2000-01-01T00:00:00Z" }))).await;
+    assert_eq!(sync["created"], json!([]));
+    tx.commit().await.unwrap();
+
+    let (status, sync) = send(&router, json_request("POST", "/sync", json!({
+        "questions": [],
+        "since": sync["synced_at"]
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(sync["created"].as_array().unwrap().len(), 1);
+    assert_eq!(sync["created"][0]["question_uuid"], question_uuid.to_string());
+}
+
+#[tokio::test]
+async fn should_keep_tags_and_versions_when_views_are_counted() {
+    let state = AppState::in_memory(&Config::default());
+    let router = app(state.clone());
+
+    let (_, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "Is this read often?",
+        "description": ""
+    }))).await;
+    let uri = format!("/questions/{}/full", question["question_uuid"].as_str().unwrap());
+
+    let response = router.clone().oneshot(Request::get(&uri).body(Body::empty()).unwrap()).await.unwrap();
+    let etag = response.headers()[header::ETAG].clone();
+    views::flush(&state.view_counter, state.questions_dao.as_ref()).await;
+
+    let (_, read) = send(&router, Request::get(&uri).body(Body::empty()).unwrap()).await;
+    assert_eq!(read["view_count"], 1);
+
+    let conditional = Request::get(&uri).header(header::IF_NONE_MATCH, &etag).body(Body::empty()).unwrap();
+    let (status, _) = send(&router, conditional).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+
+    let (status, sync) = send(&router, json_request("POST", "/sync", json!({
+        "questions": [{ "question_uuid": question["question_uuid"], "version": etag.to_str().unwrap() }]
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(sync["changed"], json!([]));
+}
+
+#[sqlx::test]
+async fn should_replay_creates_retried_with_the_same_idempotency_key(pool: PgPool) {
+    let router = router(pool, None);
+
+    let with_key = |uri: &str, key: &str, body: Value| {
+        let mut request = json_request("POST", uri, body);
+        request.headers_mut().insert("idempotency-key", key.parse().unwrap());
+        request
+    };
+    let question = json!({ "title": "How do I retry safely?", "description": "With an idempotency key" });
+
+    let (status, first) = send(&router, with_key("/question", "question-1", question.clone())).await;
+    assert_eq!(status, StatusCode::OK);
+    let response = router.clone().oneshot(with_key("/question", "question-1", question.clone())).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["idempotency-replayed"], "true");
+    assert!(response.headers().contains_key("idempotency-key-expires"));
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(serde_json::from_slice::<Value>(&body).unwrap(), first);
+
+    let answer = json!({ "question_uuid": first["question_uuid"], "content": "Send the same key again" });
+    let (_, first_answer) = send(&router, with_key("/answer", "question-1", answer.clone())).await;
+    let (_, retried_answer) = send(&router, with_key("/answer", "question-1", answer)).await;
+    assert_eq!(retried_answer, first_answer);
+
+    let (_, questions) = send(&router, Request::get("/questions").body(Body::empty()).unwrap()).await;
+    assert_eq!(questions.as_array().unwrap().len(), 1);
+    assert_eq!(questions[0]["answer_count"], 1);
+
+    let other = json!({ "title": "Another question", "description": "Reusing the key" });
+    let (status, body) = send(&router, with_key("/question", "question-1", other)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["errors"][0]["code"], "idempotency_key_reused");
+}
+
+#[sqlx::test]
+async fn should_look_up_several_questions(pool: PgPool) {
+    let router = router(pool, None);
+
+    let mut question_uuids = Vec::new();
+    for title in ["First question", "Second question", "Third question"] {
+        let (_, question) = send(&router, json_request("POST", "/question", json!({
+            "title": title,
+            "description": "Description"
+        }))).await;
+        question_uuids.push(question["question_uuid"].as_str().unwrap().to_owned());
+    }
+
+    let lookup = json!({ "question_uuids": [question_uuids[2], uuid::Uuid::nil(), question_uuids[0]] });
+    let (status, questions) = send(&router, json_request("POST", "/questions/lookup", lookup)).await;
+
+    assert_eq!(status, StatusCode::OK);
+    let titles: Vec<_> = questions.as_array().unwrap().iter().map(|q| q["title"].as_str().unwrap()).collect();
+    assert_eq!(titles, ["First question", "Third question"]);
+
+    let (status, _) = send(&router, json_request("POST", "/questions/lookup", json!({ "question_uuids": ["not a UUID"] }))).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[sqlx::test]
+async fn should_read_answers_of_several_questions(pool: PgPool) {
+    let router = router(pool, None);
+
+    let mut question_uuids = Vec::new();
+    for title in ["First question", "Second question"] {
+        let (_, question) = send(&router, json_request("POST", "/question", json!({
+            "title": title,
+            "description": "Description"
+        }))).await;
+        question_uuids.push(question["question_uuid"].as_str().unwrap().to_owned());
+    }
+
+    send(&router, json_request("POST", "/answer", json!({ "question_uuid": question_uuids[0], "content": "Answer" }))).await;
+
+    let (status, answers) = send(&router, json_request("POST", "/answers/batch", json!({ "question_uuids": question_uuids }))).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(answers[&question_uuids[0]][0]["content"], "Answer");
+    assert_eq!(answers[&question_uuids[1]], json!([]));
+
+    let (status, _) = send(&router, json_request("POST", "/answers/batch", json!({ "question_uuids": [] }))).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn should_report_statistics() {
+    let router = app(AppState::in_memory(&Config::default()));
+
+    let (_, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "How are statistics computed?",
+        "description": "With aggregate queries"
+    }))).await;
+    send(&router, json_request("POST", "/answer", json!({
+        "question_uuid": question["question_uuid"],
+        "content": "GROUP BY"
+    }))).await;
+
+    let (status, stats) = send(&router, Request::get("/stats").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(stats["question_count"], 1);
+    assert_eq!(stats["answer_count"], 1);
+    assert_eq!(stats["answers_per_question"], json!([{ "answer_count": 1, "question_count": 1 }]));
+
+    let days = stats["questions_per_day"].as_array().unwrap();
+    assert_eq!(days.len(), 30);
+    assert_eq!(days[29]["question_count"], 1);
+}
+
+#[sqlx::test]
+async fn should_report_ready_when_database_is_up(pool: PgPool) {
+    let router = router(pool, None);
+
+    let (status, report) = send(&router, Request::get("/ready").body(Body::empty()).unwrap()).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["status"], "up");
+}
+
+#[sqlx::test]
+async fn should_only_mount_admin_api_with_a_token(pool: PgPool) {
+    let without_token = router(pool.clone(), None);
+    let (status, _) = send(&without_token, Request::get("/admin/slo").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let with_token = router(pool, Some("0123456789abcdef"));
+    let (status, _) = send(&with_token, Request::get("/admin/slo").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let request = Request::get("/admin/slo")
+        .header(header::AUTHORIZATION, "Bearer 0123456789abcdef")
+        .body(Body::empty())
+        .unwrap();
+    let (status, report) = send(&with_token, request).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(report["availability"].is_object());
+}
+
+#[tokio::test]
+async fn should_only_mount_tools_with_tokens() {
+    let list = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" });
+
+    let router = app(AppState::in_memory(&Config::default()));
+    let (status, _) = send(&router, json_request("POST", "/mcp", list.clone())).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let config = Config { tool_tokens: vec!["0123456789abcdef=search,read".to_owned()], ..Config::default() };
+    let router = app(AppState::in_memory(&config));
+
+    let mut request = json_request("POST", "/mcp", list);
+    request.headers_mut().insert(header::AUTHORIZATION, "Bearer 0123456789abcdef".parse().unwrap());
+    let (status, response) = send(&router, request).await;
+
+    assert_eq!(status, StatusCode::OK);
+    let tools: Vec<_> = response["result"]["tools"].as_array().unwrap().iter().map(|tool| &tool["name"]).collect();
+    assert_eq!(tools, ["search_questions", "get_question"]);
+}
+
+#[sqlx::test]
+async fn should_report_database_maintenance_on_postgres_only(pool: PgPool) {
+    let request = || {
+        Request::get("/admin/maintenance")
+            .header(header::AUTHORIZATION, "Bearer 0123456789abcdef")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let (status, report) = send(&router(pool, Some("0123456789abcdef")), request()).await;
+    assert_eq!(status, StatusCode::OK);
+    let tables = report["tables"].as_array().unwrap();
+    assert!(tables.iter().any(|table| table["table_name"] == "questions"));
+    assert!(report["indexes"].as_array().unwrap().iter().any(|index| index["index_name"] == "questions_pkey"));
+
+    let config = Config {
+        admin_token: Some("0123456789abcdef".to_owned()),
+        ..Config::default()
+    };
+    let (status, _) = send(&app(AppState::in_memory(&config)), request()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn should_register_list_and_delete_webhooks() {
+    let config = Config {
+        admin_token: Some("0123456789abcdef".to_owned()),
+        ..Config::default()
+    };
+    let router = app(AppState::in_memory(&config));
+
+    let admin_request = |method: &str, uri: &str, body: Value| {
+        let mut request = json_request(method, uri, body);
+        request.headers_mut().insert(header::AUTHORIZATION, "Bearer 0123456789abcdef".parse().unwrap());
+        request
+    };
+
+    let (status, _) = send(&router, admin_request("POST", "/admin/webhook", json!({
+        "url": "https://hooks.example.com/qna",
+        "secret": "too short"
+    }))).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (status, webhook) = send(&router, admin_request("POST", "/admin/webhook", json!({
+        "url": "https://hooks.example.com/qna",
+        "secret": "a secret long enough to sign"
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(webhook.get("secret").is_none());
+
+    let (status, webhooks) = send(&router, admin_request("GET", "/admin/webhooks", Value::Null)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(webhooks, json!([webhook]));
+
+    let (status, _) = send(&router, admin_request("DELETE", "/admin/webhook", json!({
+        "webhook_uuid": webhook["webhook_uuid"]
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, webhooks) = send(&router, admin_request("GET", "/admin/webhooks", Value::Null)).await;
+    assert_eq!(webhooks, json!([]));
+}
+
+#[tokio::test]
+async fn should_watch_a_question_with_a_webhook() {
+    let router = app(AppState::in_memory(&Config::default()));
+
+    let (_, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "How do I watch a question?",
+        "description": "Without keeping a connection open"
+    }))).await;
+    let uri = format!("/question/{}/watch-webhook", question["question_uuid"].as_str().unwrap());
+
+    let (status, _) = send(&router, json_request("POST", &uri, json!({
+        "url": "https://hooks.example.com/qna",
+        "secret": "a secret long enough to sign",
+        "ttl_hours": 0
+    }))).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (status, webhook) = send(&router, json_request("POST", &uri, json!({
+        "url": "https://hooks.example.com/qna",
+        "secret": "a secret long enough to sign",
+        "ttl_hours": 24
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(webhook["question_uuid"], question["question_uuid"]);
+    assert!(webhook["expires_at"].is_string());
+    assert!(webhook.get("secret").is_none());
+
+    let missing = "/question/a22abcd2-22ab-2222-a22b-2abc2a2b22cc/watch-webhook";
+    let (status, _) = send(&router, json_request("POST", missing, json!({
+        "url": "https://hooks.example.com/qna",
+        "secret": "a secret long enough to sign"
+    }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn should_flag_content_and_resolve_flags_from_the_moderation_queue() {
+    let config = Config {
+        admin_token: Some("0123456789abcdef".to_owned()),
+        ..Config::default()
+    };
+    let router = app(AppState::in_memory(&config));
+
+    let admin_request = |method: &str, uri: &str, body: Value| {
+        let mut request = json_request(method, uri, body);
+        request.headers_mut().insert(header::AUTHORIZATION, "Bearer 0123456789abcdef".parse().unwrap());
+        request
+    };
+
+    let (_, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "Cheap watches?",
+        "description": "Visit my shop"
+    }))).await;
+    let question_uuid = question["question_uuid"].as_str().unwrap();
+    let (_, answer) = send(&router, json_request("POST", "/answer", json!({
+        "question_uuid": question_uuid,
+        "content": "Not a question"
+    }))).await;
+
+    let uri = format!("/questions/{}/flag", question_uuid);
+    let (status, _) = send(&router, json_request("POST", &uri, json!({ "reason": "rude" }))).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (status, spam) = send(&router, json_request("POST", &uri, json!({ "reason": "spam" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!((&spam["answer_uuid"], &spam["resolution"]), (&Value::Null, &Value::Null));
+    send(&router, json_request("POST", &uri, json!({ "reason": "off_topic", "comment": "Ads" }))).await;
+
+    let uri = format!("/answers/{}/flag", answer["answer_uuid"].as_str().unwrap());
+    let (status, answer_flag) = send(&router, json_request("POST", &uri, json!({ "reason": "other" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(answer_flag["question_uuid"], question["question_uuid"]);
+
+    let (status, _) = send(&router, json_request("GET", "/moderation/queue", Value::Null)).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, queue) = send(&router, admin_request("GET", "/moderation/queue", Value::Null)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(queue.as_array().unwrap().len(), 3);
+    assert_eq!(queue[0], spam);
+
+    let uri = format!("/moderation/{}/resolve", spam["flag_uuid"].as_str().unwrap());
+    let (status, resolved) = send(&router, admin_request("POST", &uri, json!({ "resolution": "removed" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    let reasons: Vec<_> = resolved.as_array().unwrap().iter().map(|flag| flag["reason"].clone()).collect();
+    assert_eq!(reasons, [json!("spam"), json!("off_topic")]);
+    assert!(resolved.as_array().unwrap().iter().all(|flag| flag["resolution"] == "removed"));
+
+    let (_, questions) = send(&router, Request::get("/questions").body(Body::empty()).unwrap()).await;
+    assert_eq!(questions, json!([]));
+
+    let (_, queue) = send(&router, admin_request("GET", "/moderation/queue", Value::Null)).await;
+    assert_eq!(queue, json!([answer_flag]));
+
+    let (status, _) = send(&router, admin_request("POST", &uri, json!({ "resolution": "dismissed" }))).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn should_reject_or_hold_content_matched_by_the_content_filters() {
+    let config = Config {
+        admin_token: Some("0123456789abcdef".to_owned()),
+        content_filter_words: vec!["heck".to_owned()],
+        content_filter_max_link_ratio: Some(0.3),
+        ..Config::default()
+    };
+    let router = app(AppState::in_memory(&config));
+
+    let (status, _) = send(&router, json_request("POST", "/question", json!({
+        "title": "Why the HECK does it panic?",
+        "description": ""
+    }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "Why does it panic?",
+        "description": "See https://docs.rs"
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, answer) = send(&router, json_request("POST", "/answer", json!({
+        "question_uuid": question["question_uuid"],
+        "content": "Fixed https://a.example https://b.example"
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let mut request = json_request("GET", "/moderation/queue", Value::Null);
+    request.headers_mut().insert(header::AUTHORIZATION, "Bearer 0123456789abcdef".parse().unwrap());
+    let (_, queue) = send(&router, request).await;
+    assert_eq!(queue.as_array().unwrap().len(), 1);
+    assert_eq!(queue[0]["answer_uuid"], answer["answer_uuid"]);
+    assert_eq!(queue[0]["reason"], "spam");
+}
+
+#[tokio::test]
+async fn should_record_tags_of_content_filters_out_of_the_moderation_queue() {
+    let config = Config {
+        admin_token: Some("0123456789abcdef".to_owned()),
+        content_filter_words: vec!["heck".to_owned()],
+        content_filter_words_action: FilterAction::Tag,
+        ..Config::default()
+    };
+    let router = app(AppState::in_memory(&config));
+
+    let admin_request = |method: &str, uri: &str, body: Value| {
+        let mut request = json_request(method, uri, body);
+        request.headers_mut().insert(header::AUTHORIZATION, "Bearer 0123456789abcdef".parse().unwrap());
+        request
+    };
+
+    let (status, question) = send(&router, json_request("POST", "/question", json!({
+        "title": "Why the heck does it panic?",
+        "description": ""
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, queue) = send(&router, admin_request("GET", "/moderation/queue", Value::Null)).await;
+    assert_eq!(queue, json!([]));
+
+    let uri = format!("/moderation/questions/{}/flags", question["question_uuid"].as_str().unwrap());
+    let (status, flags) = send(&router, admin_request("GET", &uri, Value::Null)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(flags.as_array().unwrap().len(), 1);
+    assert_eq!((&flags[0]["reason"], &flags[0]["resolution"]), (&json!("offensive"), &json!("tagged")));
+
+    // Only content filters tag content
+    let uri = format!("/moderation/{}/resolve", flags[0]["flag_uuid"].as_str().unwrap());
+    let (status, _) = send(&router, admin_request("POST", &uri, json!({ "resolution": "tagged" }))).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn should_reindex_questions_in_the_background() {
+    // A search cluster accepting every request
+    let cluster = Router::new().fallback(|| async { axum::Json(json!({ "acknowledged": true })) });
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let search_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(axum::serve(listener, cluster).into_future());
+
+    let config = Config {
+        admin_token: Some("0123456789abcdef".to_owned()),
+        search_url: Some(search_url),
+        ..Config::default()
+    };
+    let router = app(AppState::in_memory(&config));
+
+    let admin_request = |method: &str, uri: &str| {
+        let mut request = json_request(method, uri, Value::Null);
+        request.headers_mut().insert(header::AUTHORIZATION, "Bearer 0123456789abcdef".parse().unwrap());
+        request
+    };
+
+    for title in ["First question", "Second question"] {
+        let question = json!({ "title": title, "description": "Description" });
+        send(&router, json_request("POST", "/question", question)).await;
+    }
+
+    let (status, job) = send(&router, admin_request("POST", "/admin/search/reindex")).await;
+    assert_eq!(status, StatusCode::ACCEPTED);
+    assert_eq!((&job["kind"], &job["status"]), (&json!("reindex_search"), &json!("running")));
+
+    let (status, _) = send(&router, admin_request("POST", "/admin/search/reindex")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    let uri = format!("/admin/jobs/{}", job["job_uuid"].as_str().unwrap());
+    let mut job = Value::Null;
+    for _ in 0..50 {
+        job = send(&router, admin_request("GET", &uri)).await.1;
+        if job["status"] == "completed" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!((&job["status"], &job["processed"]), (&json!("completed"), &json!(2)));
+
+    // Without a search index, there is nothing to reindex, and the path only matches reading a job
+    let config = Config { search_url: None, ..config };
+    let (status, _) = send(&app(AppState::in_memory(&config)), admin_request("POST", "/admin/search/reindex")).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}